@@ -0,0 +1,74 @@
+use gix::apply::Location;
+
+pub struct Options {
+    pub location: Location,
+    pub three_way: bool,
+    pub reject: bool,
+    pub min_context: Option<u32>,
+}
+
+pub(crate) mod function {
+    use std::path::Path;
+
+    use anyhow::{bail, Context};
+    use gix::apply::Location;
+
+    use super::Options;
+
+    pub fn apply(
+        repo: gix::Repository,
+        patch: &Path,
+        mut err: impl std::io::Write,
+        Options {
+            location,
+            three_way,
+            reject,
+            min_context,
+        }: Options,
+    ) -> anyhow::Result<()> {
+        let input = if patch == Path::new("-") {
+            let mut buf = Vec::new();
+            std::io::Read::read_to_end(&mut std::io::stdin(), &mut buf)?;
+            buf
+        } else {
+            std::fs::read(patch).with_context(|| format!("Could not read patch at '{}'", patch.display()))?
+        };
+        let patches = gix::apply::parse(&input)?;
+
+        let mut index = repo.open_index()?;
+        let outcome = repo.apply(
+            &patches,
+            &mut index,
+            location,
+            gix::apply::Options {
+                file: gix::apply::file::Options {
+                    text: gix::apply::text::Options { min_context },
+                    three_way,
+                    ..Default::default()
+                },
+                reject,
+            },
+        )?;
+        if location != Location::Worktree || three_way {
+            index.write(repo.index_write_options()?)?;
+        }
+
+        for file in &outcome.files {
+            if !file.rejected_hunks.is_empty() {
+                writeln!(
+                    err,
+                    "{}: rejected hunks {:?}",
+                    file.path,
+                    file.rejected_hunks.iter().map(|idx| idx + 1).collect::<Vec<_>>()
+                )?;
+            }
+            if file.merge == Some(gix::merge::blob::Resolution::Conflict) {
+                writeln!(err, "{}: applied with conflicts", file.path)?;
+            }
+        }
+        if !outcome.is_clean() {
+            bail!("Patch applied with rejected hunks or conflicts");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::{bail, Context};
+use gix::{
+    bisect::{Outcome, Terms},
+    bstr::BString,
+};
+
+pub fn start(
+    repo: gix::Repository,
+    bad: Option<String>,
+    good: Vec<String>,
+    terms: Terms,
+    no_checkout: bool,
+    out: impl std::io::Write,
+) -> anyhow::Result<()> {
+    let bad = bad.map(|spec| resolve(&repo, &spec)).transpose()?;
+    let good = good
+        .iter()
+        .map(|spec| resolve(&repo, spec))
+        .collect::<Result<Vec<_>, _>>()?;
+    let outcome = repo.bisect_start(bad, &good, gix::bisect::Options { terms, no_checkout })?;
+    print_outcome(&repo, outcome, out)
+}
+
+pub fn mark(repo: gix::Repository, term: String, revs: Vec<String>, out: impl std::io::Write) -> anyhow::Result<()> {
+    let state = repo.bisect_status()?.context("No bisect in progress")?;
+    let Some(mark) = state.terms.mark_by_name(term.as_str().into()) else {
+        bail!(
+            "'{term}' is neither '{}', '{}' nor 'skip'",
+            state.terms.bad,
+            state.terms.good
+        );
+    };
+    let commits = revs
+        .iter()
+        .map(|spec| resolve(&repo, spec))
+        .collect::<Result<Vec<_>, _>>()?;
+    let outcome = repo.bisect_mark(mark, &commits)?;
+    print_outcome(&repo, outcome, out)
+}
+
+pub fn next(repo: gix::Repository, out: impl std::io::Write) -> anyhow::Result<()> {
+    let outcome = repo.bisect_next()?;
+    print_outcome(&repo, outcome, out)
+}
+
+pub fn run(repo: gix::Repository, command: String, out: impl std::io::Write) -> anyhow::Result<()> {
+    let outcome = repo.bisect_run(command.as_str().into())?;
+    print_outcome(&repo, outcome, out)
+}
+
+pub fn log(repo: gix::Repository, mut out: impl std::io::Write) -> anyhow::Result<()> {
+    out.write_all(&repo.bisect_log()?)?;
+    Ok(())
+}
+
+pub fn reset(repo: gix::Repository) -> anyhow::Result<()> {
+    repo.bisect_reset()?;
+    Ok(())
+}
+
+fn resolve(repo: &gix::Repository, spec: &str) -> anyhow::Result<gix::ObjectId> {
+    Ok(repo
+        .rev_parse_single(spec)?
+        .object()?
+        .peel_to_kind(gix::object::Kind::Commit)
+        .with_context(|| format!("'{spec}' does not point to a commit"))?
+        .id)
+}
+
+fn print_outcome(repo: &gix::Repository, outcome: Outcome<'_>, mut out: impl std::io::Write) -> anyhow::Result<()> {
+    let terms = repo.bisect_status()?.map(|state| state.terms).unwrap_or_default();
+    match outcome {
+        Outcome::Waiting { bad, good } => match (bad, good) {
+            (false, 0) => writeln!(out, "status: waiting for both {} and {} commits", terms.good, terms.bad)?,
+            (false, good) => writeln!(
+                out,
+                "status: waiting for {} commit, {good} {} commit{} known",
+                terms.bad,
+                terms.good,
+                if good == 1 { "" } else { "s" }
+            )?,
+            (true, _) => writeln!(
+                out,
+                "status: waiting for {} commit(s), {} commit known",
+                terms.good, terms.bad
+            )?,
+        },
+        Outcome::Next {
+            commit,
+            remaining,
+            steps,
+        } => {
+            writeln!(
+                out,
+                "Bisecting: {remaining} revision{} left to test after this (roughly {steps} step{})",
+                if remaining == 1 { "" } else { "s" },
+                if steps == 1 { "" } else { "s" }
+            )?;
+            writeln!(out, "[{commit}] {}", summary(repo, commit.detach())?)?;
+        }
+        Outcome::MergeBase { commit } => {
+            writeln!(out, "Bisecting: a merge base must be tested")?;
+            writeln!(out, "[{commit}] {}", summary(repo, commit.detach())?)?;
+        }
+        Outcome::FirstBad { commit } => {
+            writeln!(out, "{commit} is the first {} commit", terms.bad)?;
+            writeln!(out, "{}", summary(repo, commit.detach())?)?;
+        }
+        Outcome::OnlySkipped { candidates } => {
+            writeln!(out, "There are only 'skip'ped commits left to test.")?;
+            writeln!(out, "The first {} commit could be any of:", terms.bad)?;
+            for id in candidates {
+                writeln!(out, "{id}")?;
+            }
+            writeln!(out, "We cannot bisect more!")?;
+        }
+    }
+    Ok(())
+}
+
+fn summary(repo: &gix::Repository, id: gix::ObjectId) -> anyhow::Result<BString> {
+    Ok(repo.find_object(id)?.try_into_commit()?.message()?.summary().into_owned())
+}
@@ -52,6 +52,11 @@ pub fn describe(
         statistics,
         max_candidates,
         long_format,
+        match_patterns,
+        exclude_patterns,
+        dirty_suffix,
+        broken_suffix,
+        contains,
     }: describe::Options,
 ) -> Result<()> {
     repo.object_cache_size_if_unset(4 * 1024 * 1024);
@@ -67,12 +72,25 @@ pub fn describe(
     } else {
         Default::default()
     };
-    let resolution = commit
+    let describe = commit
         .describe()
         .names(select_ref)
         .traverse_first_parent(first_parent)
         .id_as_fallback(always)
         .max_candidates(max_candidates)
+        .matching(match_patterns)
+        .excluding(exclude_patterns)
+        .dirty_suffix(dirty_suffix)
+        .broken_suffix(broken_suffix);
+    if contains {
+        match describe.try_name_containing()? {
+            Some(name) => writeln!(out, "{name}")?,
+            None if always => writeln!(out, "{}", commit.id().shorten_or_id())?,
+            None => bail!("Cannot describe '{}' as no tag contains it", commit.id),
+        }
+        return Ok(());
+    }
+    let resolution = describe
         .try_resolve()?
         .with_context(|| format!("Did not find a single candidate ref for naming id '{}'", commit.id))?;
 
@@ -97,5 +115,61 @@ pub mod describe {
         pub long_format: bool,
         pub statistics: bool,
         pub max_candidates: usize,
+        pub match_patterns: Vec<String>,
+        pub exclude_patterns: Vec<String>,
+        pub dirty_suffix: Option<String>,
+        pub broken_suffix: Option<String>,
+        pub contains: bool,
+    }
+}
+
+pub fn name_rev(
+    repo: gix::Repository,
+    revs: &[String],
+    mut out: impl std::io::Write,
+    name_rev::Options {
+        tags_only,
+        ref_patterns,
+        exclude_patterns,
+        name_only,
+    }: name_rev::Options,
+) -> Result<()> {
+    let commits = revs
+        .iter()
+        .map(|rev| {
+            Ok(repo
+                .rev_parse_single(rev.as_str())?
+                .object()?
+                .peel_to_kind(gix::object::Kind::Commit)?
+                .id)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let names = repo.name_rev(
+        &commits,
+        &gix::commit::name_rev::Options {
+            tags_only,
+            ref_patterns: ref_patterns.into_iter().map(Into::into).collect(),
+            exclude_patterns: exclude_patterns.into_iter().map(Into::into).collect(),
+            shorten_names: tags_only && name_only,
+        },
+    )?;
+    for (rev, name) in revs.iter().zip(names) {
+        let name = name.map_or_else(|| "undefined".to_owned(), |name| name.to_string());
+        if name_only {
+            writeln!(out, "{name}")?;
+        } else {
+            writeln!(out, "{rev} {name}")?;
+        }
+    }
+    Ok(())
+}
+
+pub mod name_rev {
+    #[derive(Debug, Clone)]
+    pub struct Options {
+        pub tags_only: bool,
+        pub ref_patterns: Vec<String>,
+        pub exclude_patterns: Vec<String>,
+        pub name_only: bool,
     }
 }
@@ -1,5 +1,5 @@
 use anyhow::{bail, Result};
-use gix::bstr::{BStr, BString};
+use gix::bstr::{BStr, BString, ByteSlice};
 
 use crate::OutputFormat;
 
@@ -50,6 +50,131 @@ pub fn list(
     Ok(())
 }
 
+/// The configuration file to change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum File {
+    /// The configuration file of the repository.
+    Local,
+    /// The configuration file of the current worktree, if `extensions.worktreeConfig` is enabled.
+    Worktree,
+    /// The configuration file of the current user.
+    Global,
+    /// The system-wide configuration file.
+    System,
+}
+
+pub fn set(repo: gix::Repository, file: File, key: BString, value: BString) -> Result<()> {
+    let parsed = parse_key(key.as_bstr())?;
+    let mut config = open_file(&repo, file)?;
+    // Like `git`, replace an existing value where it is instead of adding it to the last section.
+    match config.raw_values(parsed.section_name, parsed.subsection_name, parsed.value_name) {
+        Ok(values) if values.len() > 1 => bail!("Key '{key}' has multiple values and can't be set"),
+        Ok(_) => config
+            .raw_value_mut(parsed.section_name, parsed.subsection_name, parsed.value_name)?
+            .set(value.as_bstr()),
+        Err(_) => {
+            config.set_raw_value(
+                parsed.section_name,
+                parsed.subsection_name,
+                parsed.value_name.to_owned(),
+                value.as_bstr(),
+            )?;
+        }
+    }
+    config.commit()?;
+    Ok(())
+}
+
+pub fn unset(repo: gix::Repository, file: File, key: BString) -> Result<()> {
+    let parsed = parse_key(key.as_bstr())?;
+    let mut config = open_file(&repo, file)?;
+    {
+        let Ok(mut values) = config.raw_values_mut(parsed.section_name, parsed.subsection_name, parsed.value_name)
+        else {
+            bail!("Key '{key}' was not found in '{}'", config.path().display());
+        };
+        if values.len() > 1 {
+            bail!("Key '{key}' has multiple values and can't be unset");
+        }
+        values.delete_all();
+    }
+    config.commit()?;
+    Ok(())
+}
+
+pub fn rename_section(repo: gix::Repository, file: File, old_name: BString, new_name: BString) -> Result<()> {
+    let (old_section, old_subsection) = parse_section(old_name.as_bstr());
+    let (new_section, new_subsection) = parse_section(new_name.as_bstr());
+    let mut config = open_file(&repo, file)?;
+    // Collect all matching sections first, as they may still match after renaming them, for instance if only the case changes.
+    let ids: Vec<_> = config
+        .sections_and_ids_by_name(&old_section)
+        .into_iter()
+        .flatten()
+        .filter(|(section, _)| section.header().subsection_name() == old_subsection)
+        .map(|(_, id)| id)
+        .collect();
+    if ids.is_empty() {
+        bail!("No section named '{old_name}' in '{}'", config.path().display());
+    }
+    for id in ids {
+        config.rename_section_by_id(
+            id,
+            new_section.clone(),
+            new_subsection.map(|name| name.to_owned().into()),
+        )?;
+    }
+    config.commit()?;
+    Ok(())
+}
+
+fn open_file(repo: &gix::Repository, file: File) -> Result<gix::config::FileMut> {
+    use gix::config::Source;
+    Ok(match file {
+        File::Local => repo.config_file_mut(Source::Local)?,
+        File::Worktree => {
+            if repo
+                .config_snapshot()
+                .boolean("extensions.worktreeConfig")
+                .unwrap_or_default()
+            {
+                repo.config_file_mut(Source::Worktree)?
+            } else if repo.worktrees()?.is_empty() {
+                repo.config_file_mut(Source::Local)?
+            } else {
+                bail!("--worktree can only be used with multiple worktrees if 'extensions.worktreeConfig' is enabled")
+            }
+        }
+        File::Global => {
+            // Like `git`, prefer `~/.gitconfig` unless only the XDG configuration file exists.
+            let user = repo.config_file_mut(Source::User)?;
+            if user.path().is_file() {
+                user
+            } else {
+                match repo.config_file_mut(Source::Git) {
+                    Ok(xdg) if xdg.path().is_file() => xdg,
+                    _ => user,
+                }
+            }
+        }
+        File::System => repo.config_file_mut(Source::System)?,
+    })
+}
+
+fn parse_key(key: &BStr) -> Result<gix::config::parse::Key<'_>> {
+    match gix::config::parse::key(key) {
+        Some(key) => Ok(key),
+        None => bail!("Key '{key}' must contain a section and a value name, like 'core.bare'"),
+    }
+}
+
+fn parse_section(name: &BStr) -> (String, Option<&BStr>) {
+    match name.find_byte(b'.') {
+        Some(pos) => (name[..pos].to_str_lossy().into_owned(), Some(name[pos + 1..].as_bstr())),
+        None => (name.to_str_lossy().into_owned(), None),
+    }
+}
+
 struct Filter {
     name: String,
     subsection: Option<BString>,
@@ -17,6 +17,8 @@ pub enum PathsOrPatterns {
     Patterns(Vec<BString>),
 }
 
+pub mod apply;
+pub use apply::function::apply;
 #[cfg(feature = "archive")]
 pub mod archive;
 pub mod commit;
@@ -24,6 +26,7 @@ pub mod config;
 mod credential;
 pub use credential::function as credential;
 pub mod attributes;
+pub mod bisect;
 #[cfg(feature = "blocking-client")]
 pub mod clone;
 pub mod exclude;
@@ -40,6 +43,8 @@ pub use fsck::function as fsck;
 pub mod index;
 pub mod mailmap;
 pub mod odb;
+pub mod range_diff;
+pub use range_diff::function::range_diff;
 pub mod remote;
 pub mod revision;
 pub mod status;
@@ -0,0 +1,53 @@
+pub struct Options {
+    pub creation_factor: u32,
+    pub no_patch: bool,
+}
+
+pub(crate) mod function {
+    use anyhow::bail;
+    use gix::bstr::ByteSlice;
+
+    use super::Options;
+
+    /// Compare two series of commits, given like `git range-diff` accepts them: as `a...b`, as two ranges,
+    /// or as a base followed by two commits.
+    pub fn range_diff(
+        repo: gix::Repository,
+        specs: &[String],
+        out: impl std::io::Write,
+        Options {
+            creation_factor,
+            no_patch,
+        }: Options,
+    ) -> anyhow::Result<()> {
+        let (old, new) = match specs {
+            [symmetric] => {
+                let Some((old, new)) = symmetric.as_bytes().split_once_str("...") else {
+                    bail!("Need a symmetric range like 'a...b' if only one argument is given, got '{symmetric}'");
+                };
+                let old = repo.rev_parse_single(if old.is_empty() { "HEAD".into() } else { old.as_bstr() })?;
+                let new = repo.rev_parse_single(if new.is_empty() { "HEAD".into() } else { new.as_bstr() })?;
+                (repo.rev_walk([old]).hide([new]), repo.rev_walk([new]).hide([old]))
+            }
+            [old, new] => {
+                let (old, new) = (repo.rev_parse(old.as_str())?, repo.rev_parse(new.as_str())?);
+                if old.single().is_some() || new.single().is_some() {
+                    bail!("Need two commit ranges like 'a..b', or a base and two commits");
+                }
+                (old.into_walk()?, new.into_walk()?)
+            }
+            [base, old, new] => {
+                let base = repo.rev_parse_single(base.as_str())?;
+                let (old, new) = (
+                    repo.rev_parse_single(old.as_str())?,
+                    repo.rev_parse_single(new.as_str())?,
+                );
+                (repo.rev_walk([old]).hide([base]), repo.rev_walk([new]).hide([base]))
+            }
+            _ => bail!("Need one symmetric range, two ranges, or a base and two commits"),
+        };
+        let outcome = repo.range_diff(old, new, &gix::range_diff::Options { creation_factor })?;
+        outcome.write_to(out, !no_patch)?;
+        Ok(())
+    }
+}
@@ -43,15 +43,10 @@ pub(crate) mod function {
         repo.object_cache_size_if_unset(4 * 1024 * 1024);
 
         let spec = gix::path::os_str_into_bstr(&spec)?;
-        let id = repo
-            .rev_parse_single(spec)
-            .context("Only single revisions are currently supported")?;
-        let commits = id
-            .object()?
-            .peel_to_kind(gix::object::Kind::Commit)
+        let commits = repo
+            .rev_parse(spec)?
+            .into_walk()
             .context("Need committish as starting point")?
-            .id()
-            .ancestors()
             .sorting(Sorting::ByCommitTimeNewestFirst)
             .all()?;
 
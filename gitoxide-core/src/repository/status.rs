@@ -1,6 +1,6 @@
 use anyhow::{bail, Context};
 use gix::{
-    bstr::{BStr, BString},
+    bstr::{BStr, BString, ByteSlice},
     index::Entry,
     Progress,
 };
@@ -45,12 +45,15 @@ pub fn show(
     }
     let mut index = repo.index_or_empty()?;
     let index = gix::threading::make_mut(&mut index);
-    let pathspec = repo.pathspec(
+    let fs_monitor = repo.query_fs_monitor(index)?;
+    let mut pathspec = repo.pathspec(
         pathspecs,
         true,
         index,
         gix::worktree::stack::state::attributes::Source::WorktreeThenIdMapping,
     )?;
+    let fs_monitor_candidates = fs_monitor.is_some().then(|| compared_entries(index, &mut pathspec));
+    let mut pathspec = pathspec.detach()?;
     let mut progress = progress.add_child("traverse index");
     let start = std::time::Instant::now();
     let options = gix_status::index_as_worktree::Options {
@@ -73,17 +76,20 @@ pub fn show(
     let mut printer = Printer {
         out,
         changes: Vec::new(),
+        dirty: Vec::new(),
     };
+    let worktree = repo
+        .work_dir()
+        .context("This operation cannot be run on a bare repository")?;
     let outcome = gix_status::index_as_worktree(
         index,
-        repo.work_dir()
-            .context("This operation cannot be run on a bare repository")?,
+        worktree,
         &mut printer,
         FastEq,
         Submodule,
         repo.objects.clone().into_arc()?,
         &mut progress,
-        pathspec.detach()?,
+        pathspec.clone(),
         repo.filter_pipeline(Some(gix::hash::ObjectId::empty_tree(repo.object_hash())))?
             .0
             .into_parts()
@@ -92,7 +98,14 @@ pub fn show(
         options,
     )?;
 
-    if outcome.entries_to_update != 0 && allow_write {
+    let (untracked, untracked_cache) = untracked_files(&repo, index, worktree)?;
+    for entry in &untracked {
+        if pathspec.is_included(entry.rela_path.as_bstr(), Some(entry.is_dir)) {
+            printer.visit_untracked(entry)?;
+        }
+    }
+
+    if (outcome.entries_to_update != 0 || fs_monitor.is_some() || untracked_cache.is_some()) && allow_write {
         {
             let entries = index.entries_mut();
             for (entry_index, change) in printer.changes {
@@ -107,21 +120,110 @@ pub fn show(
                 }
             }
         }
-        index.write(gix::index::write::Options {
-            extensions: Default::default(),
-            skip_hash: false, // TODO: make this based on configuration
-        })?;
+        if let Some(candidates) = fs_monitor_candidates {
+            // Only entries that were compared and found unchanged are known to be up-to-date from now on.
+            printer.dirty.sort_unstable();
+            let entries = index.entries_mut();
+            for entry_index in candidates {
+                if printer.dirty.binary_search(&entry_index).is_err() {
+                    entries[entry_index]
+                        .flags
+                        .insert(gix::index::entry::Flags::FSMONITOR_VALID);
+                }
+            }
+        }
+        if let Some(untracked_cache) = untracked_cache {
+            index.set_untracked(untracked_cache);
+        }
+        index.write(repo.index_write_options()?)?;
     }
 
     if statistics {
         writeln!(err, "{outcome:#?}").ok();
+        if let Some(fs_monitor) = fs_monitor {
+            writeln!(err, "{fs_monitor:#?}").ok();
+        }
     }
 
-    writeln!(err, "\nhead -> index and untracked files aren't implemented yet")?;
+    writeln!(err, "\nhead -> index isn't implemented yet")?;
     progress.show_throughput(start);
     Ok(())
 }
 
+/// Return the indices of all entries that `index_as_worktree()` compares with the worktree, as only these can be marked
+/// as valid for the filesystem monitor if they turn out to be unchanged.
+fn compared_entries(index: &gix::index::State, pathspec: &mut gix::Pathspec<'_>) -> Vec<usize> {
+    use gix::index::entry::Flags;
+    index
+        .entries()
+        .iter()
+        .enumerate()
+        .filter_map(|(entry_index, entry)| {
+            (!entry
+                .flags
+                .intersects(Flags::UPTODATE | Flags::SKIP_WORKTREE | Flags::ASSUME_VALID | Flags::FSMONITOR_VALID)
+                && entry.stage() == 0
+                && !entry.mode.is_submodule()
+                && pathspec.is_included(entry.path(index), Some(false)))
+            .then_some(entry_index)
+        })
+        .collect()
+}
+
+/// Find all untracked files in `worktree`, using the untracked cache of `index` if `core.untrackedCache` allows it.
+///
+/// Also return the untracked cache to store in the index, with `Some(None)` indicating that it should be removed, or `None`
+/// if it should be left as is.
+#[allow(clippy::type_complexity)]
+fn untracked_files(
+    repo: &gix::Repository,
+    index: &gix::index::State,
+    worktree: &std::path::Path,
+) -> anyhow::Result<(
+    Vec<gix::dir::walk::Entry>,
+    Option<Option<gix::index::extension::UntrackedCache>>,
+)> {
+    let mut lookup = repo.untracked_cache_lookup(index)?;
+    // Only disabling the cache explicitly removes it, while `keep`, the default, leaves it as is.
+    let remove_untracked_cache = matches!(
+        repo.config_snapshot().try_boolean("core.untrackedCache"),
+        Some(Ok(false))
+    );
+
+    let mut excludes = repo.excludes(
+        index,
+        None,
+        gix::worktree::stack::state::ignore::Source::WorktreeThenIdMappingIfNotSkipped,
+    )?;
+    let mut entries = Vec::new();
+    let outcome = gix::dir::walk(
+        worktree,
+        gix::dir::walk::Context {
+            index,
+            excludes: &mut excludes,
+            objects: &repo.objects,
+            untracked_cache: lookup.as_mut(),
+        },
+        gix::dir::walk::Options::default(),
+        &mut |entry: gix::dir::walk::Entry| {
+            entries.push(entry);
+            gix::dir::walk::Action::Continue
+        },
+    )?;
+    debug_assert!(!outcome.cancelled, "we never cancel");
+
+    let untracked_cache = match lookup {
+        Some(lookup) => {
+            let is_unchanged = lookup.is_valid()
+                && lookup.outcome.directories_changed == 0
+                && lookup.outcome.directories_missing == 0;
+            (!is_unchanged).then(|| lookup.into_untracked_cache())
+        }
+        None => (remove_untracked_cache && index.untracked().is_some()).then_some(None),
+    };
+    Ok((entries, untracked_cache))
+}
+
 #[derive(Clone)]
 struct Submodule;
 
@@ -137,6 +239,8 @@ impl gix_status::index_as_worktree::traits::SubmoduleStatus for Submodule {
 struct Printer<W> {
     out: W,
     changes: Vec<(usize, ApplyChange)>,
+    /// The indices of all entries that were reported as changed.
+    dirty: Vec<usize>,
 }
 
 enum ApplyChange {
@@ -164,6 +268,11 @@ where
 }
 
 impl<W: std::io::Write> Printer<W> {
+    fn visit_untracked(&mut self, entry: &gix::dir::walk::Entry) -> std::io::Result<()> {
+        let trailing_slash = if entry.is_dir { "/" } else { "" };
+        writeln!(&mut self.out, "{: >3} {}{trailing_slash}", "??", entry.rela_path)
+    }
+
     fn visit_inner(&mut self, entry_index: usize, rela_path: &BStr, status: EntryStatus<()>) -> std::io::Result<()> {
         let char_storage;
         let status = match status {
@@ -188,6 +297,7 @@ impl<W: std::io::Write> Printer<W> {
             EntryStatus::IntentToAdd => "A",
         };
 
+        self.dirty.push(entry_index);
         writeln!(&mut self.out, "{status: >3} {rela_path}")
     }
 }
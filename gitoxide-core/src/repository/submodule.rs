@@ -1,5 +1,5 @@
 use anyhow::bail;
-use gix::{commit::describe::SelectRef, prelude::ObjectIdExt, Repository, Submodule};
+use gix::{bstr::BString, commit::describe::SelectRef, prelude::ObjectIdExt, Repository, Submodule};
 
 use crate::OutputFormat;
 
@@ -66,3 +66,110 @@ fn submodule_short_hash(id: Option<gix::ObjectId>, repo: Option<&Repository>) ->
         |id| repo.map_or_else(|| id.to_string(), |repo| id.attach(repo).shorten_or_id().to_string()),
     )
 }
+
+#[cfg(feature = "blocking-client")]
+pub fn add<P>(
+    repo: Repository,
+    url: BString,
+    path: BString,
+    options: gix::submodule::add::Options,
+    progress: P,
+    mut out: impl std::io::Write,
+    format: OutputFormat,
+) -> anyhow::Result<()>
+where
+    P: gix::NestedProgress,
+    P::SubProgress: 'static,
+{
+    if format != OutputFormat::Human {
+        bail!("Only human output is supported for now")
+    }
+    let url = gix::url::parse(url.as_ref())?;
+    let sm_repo = repo.add_submodule(url, &path, options, progress, &gix::interrupt::IS_INTERRUPTED)?;
+    writeln!(
+        out,
+        "Added submodule at '{path}' with HEAD at {head}",
+        head = sm_repo.head_id()?.shorten_or_id()
+    )?;
+    Ok(())
+}
+
+pub fn sync(
+    repo: Repository,
+    paths: Vec<BString>,
+    mut out: impl std::io::Write,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output is supported for now")
+    }
+    for sm in selected(&repo, paths)? {
+        if let Some(url) = sm.sync()? {
+            writeln!(out, "Synchronized url of '{}' to {}", sm.path()?, url.to_bstring())?;
+        }
+    }
+    Ok(())
+}
+
+pub fn deinit(
+    repo: Repository,
+    paths: Vec<BString>,
+    force: bool,
+    mut out: impl std::io::Write,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output is supported for now")
+    }
+    for sm in selected(&repo, paths)? {
+        sm.deinit(force)?;
+        writeln!(out, "Cleared directory '{}'", sm.path()?)?;
+    }
+    Ok(())
+}
+
+pub fn absorb_git_dirs(
+    repo: Repository,
+    paths: Vec<BString>,
+    mut out: impl std::io::Write,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Human {
+        bail!("Only human output is supported for now")
+    }
+    for sm in selected(&repo, paths)? {
+        if sm.absorb_git_dir()? {
+            writeln!(
+                out,
+                "Migrated git directory of '{}' to '{}'",
+                sm.path()?,
+                sm.git_dir().display()
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Return all submodules whose path is in `paths`, or all submodules if `paths` is empty.
+fn selected(repo: &Repository, paths: Vec<BString>) -> anyhow::Result<Vec<Submodule<'_>>> {
+    let submodules: Vec<_> = repo.submodules()?.into_iter().flatten().collect();
+    if paths.is_empty() {
+        return Ok(submodules);
+    }
+    let mut out = Vec::with_capacity(paths.len());
+    for path in paths {
+        let path = path.strip_suffix(b"/").map_or(path.as_slice(), |p| p);
+        let mut found = None;
+        for sm in &submodules {
+            if sm.path()?.as_ref() == path {
+                found = Some(sm.clone());
+                break;
+            }
+        }
+        match found {
+            Some(sm) => out.push(sm),
+            None => bail!("No submodule at path '{}'", gix::bstr::BStr::new(path)),
+        }
+    }
+    Ok(out)
+}
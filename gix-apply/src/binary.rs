@@ -0,0 +1,118 @@
+/// The error returned by [`apply()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The delta expects a base of {expected} bytes, but it has {actual} bytes")]
+    BaseSize { expected: usize, actual: usize },
+    #[error("The delta is malformed")]
+    Delta,
+}
+
+/// The decoded data of a binary patch, as introduced by `literal <size>` or `delta <size>`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum Data {
+    /// The complete content of the file.
+    Literal(Vec<u8>),
+    /// Instructions to create the content of the file from its previous content, in the format of git's pack deltas.
+    Delta(Vec<u8>),
+}
+
+/// Produce the new content of a file from its `base` content and `data`.
+pub fn apply(data: &Data, base: &[u8]) -> Result<Vec<u8>, Error> {
+    let delta = match data {
+        Data::Literal(content) => return Ok(content.clone()),
+        Data::Delta(delta) => delta.as_slice(),
+    };
+    let mut delta = delta;
+    let base_size = decode_size(&mut delta).ok_or(Error::Delta)?;
+    if base_size != base.len() {
+        return Err(Error::BaseSize {
+            expected: base_size,
+            actual: base.len(),
+        });
+    }
+    let result_size = decode_size(&mut delta).ok_or(Error::Delta)?;
+    let mut out = Vec::with_capacity(result_size);
+    while let Some((&cmd, rest)) = delta.split_first() {
+        delta = rest;
+        if cmd & 0x80 != 0 {
+            let mut read = |bits: std::ops::Range<u32>| -> Option<usize> {
+                let mut value = 0;
+                for (shift, bit) in bits.enumerate() {
+                    if cmd & (1 << bit) != 0 {
+                        let (&byte, rest) = delta.split_first()?;
+                        delta = rest;
+                        value |= (byte as usize) << (shift * 8);
+                    }
+                }
+                Some(value)
+            };
+            let offset = read(0..4).ok_or(Error::Delta)?;
+            let size = match read(4..7).ok_or(Error::Delta)? {
+                0 => 0x10000,
+                size => size,
+            };
+            out.extend_from_slice(base.get(offset..offset + size).ok_or(Error::Delta)?);
+        } else if cmd != 0 {
+            let size = cmd as usize;
+            out.extend_from_slice(delta.get(..size).ok_or(Error::Delta)?);
+            delta = &delta[size..];
+        } else {
+            return Err(Error::Delta);
+        }
+    }
+    if out.len() != result_size {
+        return Err(Error::Delta);
+    }
+    Ok(out)
+}
+
+/// Decode a variable-length size as used in the header of deltas.
+fn decode_size(data: &mut &[u8]) -> Option<usize> {
+    let mut size = 0;
+    let mut shift = 0;
+    loop {
+        let (&byte, rest) = data.split_first()?;
+        *data = rest;
+        size |= ((byte & 0x7f) as usize) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Some(size);
+        }
+    }
+}
+
+const BASE85_ALPHABET: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+/// Decode a `line` of a binary patch into `out`, where the first character encodes the amount of decoded bytes and the
+/// remaining characters are base85 encoded.
+pub(crate) fn decode_base85_line(line: &[u8], out: &mut Vec<u8>) -> Option<()> {
+    let (&len, encoded) = line.split_first()?;
+    let len = match len {
+        b'A'..=b'Z' => len - b'A' + 1,
+        b'a'..=b'z' => len - b'a' + 27,
+        _ => return None,
+    } as usize;
+    if encoded.len() != (len + 3) / 4 * 5 {
+        return None;
+    }
+    let start = out.len();
+    for chunk in encoded.chunks(5) {
+        let mut value: u32 = 0;
+        for &c in chunk {
+            let digit = BASE85_ALPHABET.iter().position(|&b| b == c)? as u32;
+            value = value.checked_mul(85)?.checked_add(digit)?;
+        }
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+    out.truncate(start + len);
+    Some(())
+}
+
+/// Decompress the zlib stream in `compressed`, which must decompress to exactly `size` bytes.
+pub(crate) fn inflate(compressed: &[u8], size: usize) -> Option<Vec<u8>> {
+    let mut out = vec![0; size];
+    let (status, _, written) = gix_features::zlib::Inflate::default().once(compressed, &mut out).ok()?;
+    (status == gix_features::zlib::Status::StreamEnd && written == size).then_some(out)
+}
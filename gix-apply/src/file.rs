@@ -0,0 +1,165 @@
+use bstr::BString;
+use gix_merge::blob::Resolution;
+
+use crate::{binary, text, Content, Operation, Patch};
+
+/// The error returned by [`apply()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("{path}: already exists")]
+    AlreadyExists { path: BString },
+    #[error("{path}: does not exist")]
+    Missing { path: BString },
+    #[error("{path}: removal patch leaves file contents")]
+    DeletionLeavesContent { path: BString },
+    #[error("{path}: the binary patch doesn't apply as the file doesn't have the expected content")]
+    BinaryPreimageMismatch { path: BString },
+    #[error(
+        "{path}: cannot apply binary patch without full index line or without the new content in the object database"
+    )]
+    BinaryWithoutData { path: BString },
+    #[error("{path}: could not apply binary patch")]
+    Binary { path: BString, source: binary::Error },
+}
+
+/// Options for use in [`apply()`].
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Options {
+    /// Control how hunks of textual changes are matched.
+    pub text: text::Options,
+    /// If `true`, and if a hunk can't be applied, apply the patch to the version of the file it was created from instead
+    /// and merge the result with the current content of the file, like `git apply --3way` does.
+    ///
+    /// This only works if the patch records the id of the original version of the file, and if it can be found.
+    pub three_way: bool,
+    /// How to present conflicts of three-way merges.
+    pub merge: gix_merge::blob::Options,
+}
+
+/// The outcome of [`apply()`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum Outcome {
+    /// The patch was applied directly to the current content.
+    Applied {
+        /// The new content of the file, or `None` if it was deleted.
+        content: Option<Vec<u8>>,
+        /// The indices of the hunks that didn't apply, which are also excluded from `content`.
+        ///
+        /// If this isn't empty, the caller typically refuses to use `content`, unless it is configured to reject hunks
+        /// like `git apply --reject` does.
+        rejected: Vec<usize>,
+    },
+    /// The patch couldn't be applied directly, so it was applied to the original version of the file and
+    /// the result was merged with the current content.
+    Merged {
+        /// The merged content, which contains conflict markers if `resolution` is [`Resolution::Conflict`].
+        content: Vec<u8>,
+        /// Whether or not the merge had conflicts.
+        resolution: Resolution,
+        /// The original version of the file that the patch was created from, the common ancestor of the merge.
+        base: Vec<u8>,
+        /// The original version with the patch applied, *their* side of the merge.
+        theirs: Vec<u8>,
+    },
+}
+
+/// Apply `patch` to `current`, the current content of the file the patch refers to, or `None` if it doesn't exist,
+/// and use `find_blob` to obtain the content of blobs by their possibly abbreviated id for binary patches without
+/// data and for three-way merges.
+///
+/// Labels for conflict markers are taken from `labels`.
+pub fn apply(
+    patch: &Patch,
+    current: Option<&[u8]>,
+    mut find_blob: impl FnMut(&gix_hash::Prefix) -> Option<Vec<u8>>,
+    labels: gix_merge::blob::Labels<'_>,
+    options: Options,
+) -> Result<Outcome, Error> {
+    let path = || patch.path().to_owned();
+    let current = match (patch.operation, current) {
+        (Operation::Create, Some(_)) => return Err(Error::AlreadyExists { path: path() }),
+        (Operation::Create, None) => &[][..],
+        (_, Some(current)) => current,
+        (_, None) => {
+            return Err(Error::Missing {
+                path: patch.old_path.clone().unwrap_or_else(path),
+            })
+        }
+    };
+
+    let (content, rejected) = match &patch.content {
+        Content::Text(hunks) => {
+            let outcome = text::apply(current, hunks, options.text);
+            if !outcome.rejected.is_empty() && options.three_way {
+                if let Some(merged) = merge(patch, hunks, current, &mut find_blob, labels, options) {
+                    return Ok(merged);
+                }
+            }
+            (outcome.content, outcome.rejected)
+        }
+        Content::Binary { forward, .. } => {
+            if let Some(id) = &patch.old_id {
+                let actual = gix_object::compute_hash(id.as_oid().kind(), gix_object::Kind::Blob, current);
+                if id.cmp_oid(&actual).is_ne() {
+                    return Err(Error::BinaryPreimageMismatch { path: path() });
+                }
+            }
+            let content = binary::apply(forward, current).map_err(|source| Error::Binary { path: path(), source })?;
+            (content, Vec::new())
+        }
+        Content::BinaryWithoutData => {
+            let content = match &patch.new_id {
+                Some(id) if id.hex_len() == id.as_oid().kind().len_in_hex() => find_blob(id),
+                Some(_) => None,
+                None => Some(Vec::new()),
+            }
+            .ok_or_else(|| Error::BinaryWithoutData { path: path() })?;
+            (content, Vec::new())
+        }
+    };
+
+    if patch.operation == Operation::Delete {
+        if !content.is_empty() {
+            return Err(Error::DeletionLeavesContent { path: path() });
+        }
+        return Ok(Outcome::Applied {
+            content: None,
+            rejected,
+        });
+    }
+    Ok(Outcome::Applied {
+        content: Some(content),
+        rejected,
+    })
+}
+
+/// Apply `hunks` to the original version of the file and merge the result with `current`, or return `None` if the
+/// original version is unknown or if the patch doesn't apply to it either.
+fn merge(
+    patch: &Patch,
+    hunks: &[crate::Hunk],
+    current: &[u8],
+    find_blob: &mut impl FnMut(&gix_hash::Prefix) -> Option<Vec<u8>>,
+    labels: gix_merge::blob::Labels<'_>,
+    options: Options,
+) -> Option<Outcome> {
+    let base = match &patch.old_id {
+        Some(id) => find_blob(id)?,
+        None if patch.operation == Operation::Create => Vec::new(),
+        None => return None,
+    };
+    let theirs = text::apply(&base, hunks, options.text);
+    if !theirs.rejected.is_empty() {
+        return None;
+    }
+    let theirs = theirs.content;
+    let mut content = Vec::new();
+    let resolution = gix_merge::blob::merge(&base, current, &theirs, labels, options.merge, &mut content);
+    Some(Outcome::Merged {
+        content,
+        resolution,
+        base,
+        theirs,
+    })
+}
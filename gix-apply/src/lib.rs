@@ -0,0 +1,134 @@
+//! Parse patches as produced by `git diff` or `diff -u` and apply them to the content of files, similar to `git apply`.
+//!
+//! Use [`parse()`] to obtain one [`Patch`] per file, and [`file::apply()`] to apply each of them to the current
+//! content of that file.
+#![deny(missing_docs, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+use bstr::BString;
+use gix_object::tree::EntryKind;
+
+///
+pub mod parse;
+pub use parse::function::parse;
+
+///
+pub mod text;
+
+///
+pub mod binary;
+
+///
+pub mod file;
+
+/// What a [`Patch`] does to the file it refers to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Operation {
+    /// The file is changed in place.
+    Modify,
+    /// The file is created.
+    Create,
+    /// The file is deleted.
+    Delete,
+    /// The file is moved to a new location, and possibly changed along the way.
+    Rename {
+        /// How similar the source and the destination are, from 0 to 100 percent, if known.
+        similarity: Option<u8>,
+    },
+    /// The file is copied to a new location, and possibly changed along the way.
+    Copy {
+        /// How similar the source and the destination are, from 0 to 100 percent, if known.
+        similarity: Option<u8>,
+    },
+}
+
+/// The changes to a single file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Patch {
+    /// The path of the file before the change, relative to the root of the repository, or `None` if it is created.
+    pub old_path: Option<BString>,
+    /// The path of the file after the change, relative to the root of the repository, or `None` if it is deleted.
+    pub new_path: Option<BString>,
+    /// The mode of the file before the change, if known.
+    pub old_mode: Option<EntryKind>,
+    /// The mode of the file after the change, if known.
+    pub new_mode: Option<EntryKind>,
+    /// The (possibly abbreviated) id of the file before the change, as provided by the `index` line of git patches,
+    /// or `None` if unknown or if the file didn't exist.
+    pub old_id: Option<gix_hash::Prefix>,
+    /// The (possibly abbreviated) id of the file after the change, as provided by the `index` line of git patches,
+    /// or `None` if unknown or if the file was deleted.
+    pub new_id: Option<gix_hash::Prefix>,
+    /// What happens to the file.
+    pub operation: Operation,
+    /// How the content of the file changes.
+    pub content: Content,
+}
+
+impl Patch {
+    /// Return the path that identifies this patch best, which is the path after the change unless the file is deleted.
+    pub fn path(&self) -> &bstr::BStr {
+        self.new_path
+            .as_ref()
+            .or(self.old_path.as_ref())
+            .expect("parsing assures that there is at least one path")
+            .as_ref()
+    }
+}
+
+/// The way the content of a file is changed by a [`Patch`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Content {
+    /// The textual changes, which is empty if only the mode or the location of the file changes.
+    Text(Vec<Hunk>),
+    /// The binary changes, as produced by `git diff --binary`.
+    Binary {
+        /// The change from the old to the new version.
+        forward: binary::Data,
+        /// The change from the new to the old version, if present.
+        reverse: Option<binary::Data>,
+    },
+    /// The file is binary, but the patch doesn't contain the changes, as in `Binary files a/x and b/x differ`.
+    ///
+    /// Such a patch can only be applied if the new version is known by its full id, and available in the object database.
+    BinaryWithoutData,
+}
+
+/// A set of changes to the lines of a file, along with the lines around it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Hunk {
+    /// The 1-based line at which the hunk starts in the old version of the file, or the line after which to insert lines
+    /// if `old_lines` is `0`.
+    pub old_start: u32,
+    /// The amount of lines of the old version covered by this hunk.
+    pub old_lines: u32,
+    /// The 1-based line at which the hunk starts in the new version of the file, or the line after which lines were
+    /// removed if `new_lines` is `0`.
+    pub new_start: u32,
+    /// The amount of lines of the new version covered by this hunk.
+    pub new_lines: u32,
+    /// The text after the second `@@` of the hunk header, typically the name of the function the hunk is in.
+    pub section: BString,
+    /// The lines of the hunk.
+    pub lines: Vec<Line>,
+}
+
+/// A line of a [`Hunk`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Line {
+    /// What the line does.
+    pub kind: LineKind,
+    /// The line including its line terminator, unless it was marked with `\ No newline at end of file`.
+    pub content: BString,
+}
+
+/// The kind of a [`Line`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum LineKind {
+    /// A line that exists in the old and the new version, prefixed with ` `.
+    Context,
+    /// A line that exists only in the old version, prefixed with `-`.
+    Removal,
+    /// A line that exists only in the new version, prefixed with `+`.
+    Addition,
+}
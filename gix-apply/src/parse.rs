@@ -0,0 +1,503 @@
+use bstr::BString;
+
+/// The error returned by the [`parse()`][crate::parse()] function.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("No patch was found in the input")]
+    NoPatch,
+    #[error("Line {line}: could not determine the path of the file to patch")]
+    MissingPath { line: usize },
+    #[error("Line {line}: could not unquote path")]
+    Unquote {
+        line: usize,
+        source: gix_quote::ansi_c::undo::Error,
+    },
+    #[error("Line {line}: invalid file mode {mode:?}")]
+    Mode { line: usize, mode: BString },
+    #[error("Line {line}: invalid object id {id:?}")]
+    Id {
+        line: usize,
+        id: BString,
+        source: gix_hash::prefix::from_hex::Error,
+    },
+    #[error("Line {line}: malformed hunk header {header:?}")]
+    HunkHeader { line: usize, header: BString },
+    #[error("Line {line}: unexpected line in hunk, or the hunk is shorter than its header announced")]
+    Hunk { line: usize },
+    #[error("Line {line}: malformed binary patch")]
+    Binary { line: usize },
+    #[error("Line {line}: refusing to use path {path:?} as it would leave the worktree or touch the repository")]
+    InvalidPath {
+        line: usize,
+        path: BString,
+        source: gix_validate::path::component::Error,
+    },
+}
+
+pub(crate) mod function {
+    use std::borrow::Cow;
+
+    use bstr::{BStr, BString, ByteSlice};
+    use gix_object::tree::EntryKind;
+
+    use super::Error;
+    use crate::{binary, Content, Hunk, Line, LineKind, Operation, Patch};
+
+    /// Parse all patches in `input`, which may be the output of `git diff`, `git format-patch` or `diff -u`, and
+    /// return one [`Patch`] for each file.
+    ///
+    /// Lines that are not part of a patch, like the commit message of a mail, are skipped.
+    /// Just like `git apply` does by default, the first component of each path is removed, so `a/dir/file` becomes `dir/file`.
+    /// Paths without a component to remove are used as is.
+    ///
+    /// Like `git apply`, paths that are absolute, contain `.` or `..` components or refer to the `.git` directory
+    /// are rejected, so patches from untrusted sources can't write outside of the worktree or into the repository.
+    pub fn parse(input: &[u8]) -> Result<Vec<Patch>, Error> {
+        let mut lines = Lines {
+            lines: input.lines_with_terminator().collect(),
+            pos: 0,
+        };
+        let mut patches = Vec::new();
+        while let Some(line) = lines.peek() {
+            if line.starts_with(b"diff --git ") {
+                patches.push(parse_git_patch(&mut lines)?);
+            } else if line.starts_with(b"--- ")
+                && lines.peek_at(1).map_or(false, |l| l.starts_with(b"+++ "))
+                && lines.peek_at(2).map_or(false, |l| l.starts_with(b"@@ -"))
+            {
+                patches.push(parse_unified_patch(&mut lines)?);
+            } else {
+                lines.pos += 1;
+            }
+        }
+        if patches.is_empty() {
+            return Err(Error::NoPatch);
+        }
+        Ok(patches)
+    }
+
+    struct Lines<'a> {
+        lines: Vec<&'a [u8]>,
+        pos: usize,
+    }
+
+    impl<'a> Lines<'a> {
+        fn peek(&self) -> Option<&'a [u8]> {
+            self.peek_at(0)
+        }
+
+        fn peek_at(&self, offset: usize) -> Option<&'a [u8]> {
+            self.lines.get(self.pos + offset).copied()
+        }
+
+        /// Return the current line without its line terminator and advance.
+        fn next_trimmed(&mut self) -> Option<&'a [u8]> {
+            let line = self.peek()?;
+            self.pos += 1;
+            Some(trim_newline(line))
+        }
+
+        /// The 1-based number of the current line, for use in errors.
+        fn line_number(&self) -> usize {
+            self.pos + 1
+        }
+    }
+
+    fn trim_newline(line: &[u8]) -> &[u8] {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        line.strip_suffix(b"\r").unwrap_or(line)
+    }
+
+    fn parse_git_patch(lines: &mut Lines<'_>) -> Result<Patch, Error> {
+        let header_line = lines.line_number();
+        let header = lines.next_trimmed().expect("checked by caller");
+        let (mut old_path, mut new_path) = match git_header_paths(&header[b"diff --git ".len()..], header_line)? {
+            Some((old, new)) => (Some(old), Some(new)),
+            None => (None, None),
+        };
+        let mut patch = Patch {
+            old_path: None,
+            new_path: None,
+            old_mode: None,
+            new_mode: None,
+            old_id: None,
+            new_id: None,
+            operation: Operation::Modify,
+            content: Content::Text(Vec::new()),
+        };
+        let mut similarity = None;
+        while let Some(line) = lines.peek().map(trim_newline) {
+            let line_number = lines.line_number();
+            if let Some(mode) = line.strip_prefix(b"old mode ") {
+                patch.old_mode = Some(parse_mode(mode, line_number)?);
+            } else if let Some(mode) = line.strip_prefix(b"new mode ") {
+                patch.new_mode = Some(parse_mode(mode, line_number)?);
+            } else if let Some(mode) = line.strip_prefix(b"deleted file mode ") {
+                patch.old_mode = Some(parse_mode(mode, line_number)?);
+                patch.operation = Operation::Delete;
+            } else if let Some(mode) = line.strip_prefix(b"new file mode ") {
+                patch.new_mode = Some(parse_mode(mode, line_number)?);
+                patch.operation = Operation::Create;
+            } else if let Some(path) = line
+                .strip_prefix(b"rename from ")
+                .or_else(|| line.strip_prefix(b"rename old "))
+            {
+                old_path = Some(unquote(path, line_number)?.into_owned());
+                patch.operation = Operation::Rename { similarity: None };
+            } else if let Some(path) = line
+                .strip_prefix(b"rename to ")
+                .or_else(|| line.strip_prefix(b"rename new "))
+            {
+                new_path = Some(unquote(path, line_number)?.into_owned());
+                patch.operation = Operation::Rename { similarity: None };
+            } else if let Some(path) = line.strip_prefix(b"copy from ") {
+                old_path = Some(unquote(path, line_number)?.into_owned());
+                patch.operation = Operation::Copy { similarity: None };
+            } else if let Some(path) = line.strip_prefix(b"copy to ") {
+                new_path = Some(unquote(path, line_number)?.into_owned());
+                patch.operation = Operation::Copy { similarity: None };
+            } else if let Some(percent) = line.strip_prefix(b"similarity index ") {
+                similarity = percent
+                    .strip_suffix(b"%")
+                    .and_then(|p| p.to_str().ok())
+                    .and_then(|p| p.parse().ok());
+            } else if line.starts_with(b"dissimilarity index ") {
+            } else if let Some(ids) = line.strip_prefix(b"index ") {
+                let (ids, mode) = match ids.find_byte(b' ') {
+                    Some(pos) => (&ids[..pos], Some(parse_mode(&ids[pos + 1..], line_number)?)),
+                    None => (ids, None),
+                };
+                let Some((old, new)) = ids.split_once_str("..") else {
+                    break;
+                };
+                patch.old_id = parse_id(old, line_number)?;
+                patch.new_id = parse_id(new, line_number)?;
+                if let Some(mode) = mode {
+                    patch.old_mode = Some(mode);
+                    patch.new_mode = Some(mode);
+                }
+            } else {
+                break;
+            }
+            lines.pos += 1;
+        }
+        match &mut patch.operation {
+            Operation::Rename { similarity: s } | Operation::Copy { similarity: s } => *s = similarity,
+            _ => {}
+        }
+
+        match lines.peek() {
+            Some(line) if line.starts_with(b"--- ") => {
+                let (old, new) = parse_file_names(lines)?;
+                old_path = old.or(old_path);
+                new_path = new.or(new_path);
+                patch.content = Content::Text(parse_hunks(lines)?);
+            }
+            Some(line) if line.starts_with(b"GIT binary patch") => {
+                lines.pos += 1;
+                let forward = parse_binary_data(lines)?;
+                let reverse = match lines.peek() {
+                    Some(line) if line.starts_with(b"literal ") || line.starts_with(b"delta ") => {
+                        Some(parse_binary_data(lines)?)
+                    }
+                    _ => None,
+                };
+                patch.content = Content::Binary { forward, reverse };
+            }
+            Some(line) if line.starts_with(b"Binary files ") => {
+                lines.pos += 1;
+                patch.content = Content::BinaryWithoutData;
+            }
+            _ => {}
+        }
+
+        match patch.operation {
+            Operation::Create => old_path = None,
+            Operation::Delete => new_path = None,
+            _ => {}
+        }
+        if old_path.is_none() && new_path.is_none() {
+            return Err(Error::MissingPath { line: header_line });
+        }
+        validate_path(old_path.as_ref(), patch.old_mode, header_line)?;
+        validate_path(new_path.as_ref(), patch.new_mode, header_line)?;
+        patch.old_path = old_path;
+        patch.new_path = new_path;
+        Ok(patch)
+    }
+
+    fn parse_unified_patch(lines: &mut Lines<'_>) -> Result<Patch, Error> {
+        let line = lines.line_number();
+        let (old_path, new_path) = parse_file_names(lines)?;
+        if old_path.is_none() && new_path.is_none() {
+            return Err(Error::MissingPath { line });
+        }
+        validate_path(old_path.as_ref(), None, line)?;
+        validate_path(new_path.as_ref(), None, line)?;
+        let hunks = parse_hunks(lines)?;
+        Ok(Patch {
+            operation: match (&old_path, &new_path) {
+                (None, _) => Operation::Create,
+                (_, None) => Operation::Delete,
+                (Some(_), Some(_)) => Operation::Modify,
+            },
+            old_path,
+            new_path,
+            old_mode: None,
+            new_mode: None,
+            old_id: None,
+            new_id: None,
+            content: Content::Text(hunks),
+        })
+    }
+
+    /// Parse the `---` and `+++` lines, with `/dev/null` denoting a file that doesn't exist on that side.
+    fn parse_file_names(lines: &mut Lines<'_>) -> Result<(Option<BString>, Option<BString>), Error> {
+        let mut parse = |prefix: &[u8]| -> Result<Option<BString>, Error> {
+            let line_number = lines.line_number();
+            let line = lines
+                .next_trimmed()
+                .and_then(|line| line.strip_prefix(prefix))
+                .ok_or(Error::MissingPath { line: line_number })?;
+            let name = if line.starts_with(b"\"") {
+                unquote(line, line_number)?
+            } else {
+                // Traditional diffs may add a timestamp after a tab.
+                Cow::Borrowed(line.split_str("\t").next().unwrap_or_default().as_bstr())
+            };
+            Ok((name.as_ref() != "/dev/null").then(|| strip_component(name.as_ref()).to_owned()))
+        };
+        let old = parse(b"--- ")?;
+        let new = parse(b"+++ ")?;
+        Ok((old, new))
+    }
+
+    fn parse_hunks(lines: &mut Lines<'_>) -> Result<Vec<Hunk>, Error> {
+        let mut hunks = Vec::new();
+        while lines.peek().map_or(false, |line| line.starts_with(b"@@ -")) {
+            let line_number = lines.line_number();
+            let header = lines.next_trimmed().expect("peeked");
+            let mut hunk = parse_hunk_header(header).ok_or_else(|| Error::HunkHeader {
+                line: line_number,
+                header: header.into(),
+            })?;
+            let (mut old_remaining, mut new_remaining) = (hunk.old_lines, hunk.new_lines);
+            while old_remaining > 0 || new_remaining > 0 {
+                let line_number = lines.line_number();
+                let line = lines.peek().ok_or(Error::Hunk { line: line_number })?;
+                let (kind, content) = match line.first() {
+                    Some(b' ') => (LineKind::Context, &line[1..]),
+                    Some(b'-') => (LineKind::Removal, &line[1..]),
+                    Some(b'+') => (LineKind::Addition, &line[1..]),
+                    // Some editors remove the trailing space of empty context lines.
+                    Some(b'\n' | b'\r') => (LineKind::Context, line),
+                    Some(b'\\') => {
+                        remove_last_newline(&mut hunk.lines);
+                        lines.pos += 1;
+                        continue;
+                    }
+                    _ => return Err(Error::Hunk { line: line_number }),
+                };
+                let (old, new) = match kind {
+                    LineKind::Context => (1, 1),
+                    LineKind::Removal => (1, 0),
+                    LineKind::Addition => (0, 1),
+                };
+                if old > old_remaining || new > new_remaining {
+                    return Err(Error::Hunk { line: line_number });
+                }
+                old_remaining -= old;
+                new_remaining -= new;
+                hunk.lines.push(Line {
+                    kind,
+                    content: content.into(),
+                });
+                lines.pos += 1;
+            }
+            if lines.peek().map_or(false, |line| line.starts_with(b"\\")) {
+                remove_last_newline(&mut hunk.lines);
+                lines.pos += 1;
+            }
+            hunks.push(hunk);
+        }
+        Ok(hunks)
+    }
+
+    fn remove_last_newline(lines: &mut [Line]) {
+        if let Some(line) = lines.last_mut() {
+            if line.content.ends_with(b"\n") {
+                line.content.pop();
+            }
+        }
+    }
+
+    /// Parse `@@ -<start>[,<lines>] +<start>[,<lines>] @@[ <section>]`.
+    fn parse_hunk_header(line: &[u8]) -> Option<Hunk> {
+        fn range(range: &[u8], prefix: u8) -> Option<(u32, u32)> {
+            let range = range.strip_prefix(&[prefix])?.to_str().ok()?;
+            Some(match range.split_once(',') {
+                Some((start, lines)) => (start.parse().ok()?, lines.parse().ok()?),
+                None => (range.parse().ok()?, 1),
+            })
+        }
+        let line = line.strip_prefix(b"@@ ")?;
+        let end = line.find(b" @@")?;
+        let (old, new) = line[..end].split_once_str(" ")?;
+        let (old_start, old_lines) = range(old, b'-')?;
+        let (new_start, new_lines) = range(new, b'+')?;
+        let section = &line[end + 3..];
+        Some(Hunk {
+            old_start,
+            old_lines,
+            new_start,
+            new_lines,
+            section: section.strip_prefix(b" ").unwrap_or(section).into(),
+            lines: Vec::new(),
+        })
+    }
+
+    /// Parse a `literal <size>` or `delta <size>` block of base85 encoded and compressed data, up to and including
+    /// the empty line that ends it.
+    fn parse_binary_data(lines: &mut Lines<'_>) -> Result<binary::Data, Error> {
+        let line_number = lines.line_number();
+        let err = || Error::Binary { line: line_number };
+        let header = lines.next_trimmed().ok_or_else(err)?;
+        let (is_literal, size) = if let Some(size) = header.strip_prefix(b"literal ") {
+            (true, size)
+        } else if let Some(size) = header.strip_prefix(b"delta ") {
+            (false, size)
+        } else {
+            return Err(err());
+        };
+        let size: usize = size.to_str().ok().and_then(|s| s.parse().ok()).ok_or_else(err)?;
+
+        let mut compressed = Vec::new();
+        loop {
+            let line_number = lines.line_number();
+            let line = lines.next_trimmed().ok_or_else(err)?;
+            if line.is_empty() {
+                break;
+            }
+            binary::decode_base85_line(line, &mut compressed).ok_or(Error::Binary { line: line_number })?;
+        }
+        let data = binary::inflate(&compressed, size).ok_or_else(err)?;
+        Ok(if is_literal {
+            binary::Data::Literal(data)
+        } else {
+            binary::Data::Delta(data)
+        })
+    }
+
+    /// Parse the paths of `a/<old> b/<new>`, which may be quoted. Return `None` if the paths can't be separated
+    /// unambiguously, which can only happen if they contain spaces and are different, in which case they will be
+    /// provided by other header lines.
+    fn git_header_paths(names: &[u8], line: usize) -> Result<Option<(BString, BString)>, Error> {
+        if names.starts_with(b"\"") {
+            let (old, consumed) =
+                gix_quote::ansi_c::undo(names.as_bstr()).map_err(|source| Error::Unquote { line, source })?;
+            let new = names[consumed..].trim_start();
+            let new = if new.starts_with(b"\"") {
+                unquote(new, line)?
+            } else {
+                Cow::Borrowed(new.as_bstr())
+            };
+            return Ok(Some((
+                strip_component(old.as_ref()).to_owned(),
+                strip_component(new.as_ref()).to_owned(),
+            )));
+        }
+        if names.ends_with(b"\"") {
+            let Some(pos) = names.find(b" \"") else {
+                return Ok(None);
+            };
+            let new = unquote(&names[pos + 1..], line)?;
+            return Ok(Some((
+                strip_component(names[..pos].as_bstr()).to_owned(),
+                strip_component(new.as_ref()).to_owned(),
+            )));
+        }
+        // Both names are unquoted, and without a rename they must be the same after removing their prefix.
+        Ok(names
+            .find_iter(b" ")
+            .map(|pos| (names[..pos].as_bstr(), names[pos + 1..].as_bstr()))
+            .find(|(old, new)| strip_component(old) == strip_component(new))
+            .map(|(old, new)| (strip_component(old).to_owned(), strip_component(new).to_owned())))
+    }
+
+    /// Assure that each component of `path` is safe to use in the worktree, the way `git apply` does.
+    fn validate_path(path: Option<&BString>, mode: Option<EntryKind>, line: usize) -> Result<(), Error> {
+        let Some(path) = path else { return Ok(()) };
+        let options = gix_validate::path::component::Options {
+            protect_windows: cfg!(windows),
+            ..Default::default()
+        };
+        let mut components = path.split_str("/").peekable();
+        while let Some(component) = components.next() {
+            let mode = (components.peek().is_none() && mode == Some(EntryKind::Link))
+                .then_some(gix_validate::path::component::Mode::Symlink);
+            gix_validate::path::component(component.as_bstr(), mode, options).map_err(|source| {
+                Error::InvalidPath {
+                    line,
+                    path: path.clone(),
+                    source,
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    fn unquote(path: &[u8], line: usize) -> Result<Cow<'_, BStr>, Error> {
+        if path.starts_with(b"\"") {
+            Ok(gix_quote::ansi_c::undo(path.as_bstr())
+                .map_err(|source| Error::Unquote { line, source })?
+                .0)
+        } else {
+            Ok(Cow::Borrowed(path.as_bstr()))
+        }
+    }
+
+    /// Remove the first component of `path`, like `a/` or `b/`.
+    fn strip_component(path: &BStr) -> &BStr {
+        path.find_byte(b'/').map_or(path, |pos| path[pos + 1..].as_bstr())
+    }
+
+    fn parse_mode(mode: &[u8], line: usize) -> Result<EntryKind, Error> {
+        let err = || Error::Mode {
+            line,
+            mode: mode.into(),
+        };
+        let mode = u32::from_str_radix(mode.to_str().map_err(|_| err())?, 8).map_err(|_| err())?;
+        Ok(match mode {
+            0o40000 => EntryKind::Tree,
+            0o120000 => EntryKind::Link,
+            0o160000 => EntryKind::Commit,
+            mode if mode & 0o170000 == 0o100000 => {
+                if mode & 0o100 == 0o100 {
+                    EntryKind::BlobExecutable
+                } else {
+                    EntryKind::Blob
+                }
+            }
+            _ => return Err(err()),
+        })
+    }
+
+    /// Parse a possibly abbreviated hex id, returning `None` if it is the null id.
+    fn parse_id(id: &[u8], line: usize) -> Result<Option<gix_hash::Prefix>, Error> {
+        if id.iter().all(|b| *b == b'0') {
+            return Ok(None);
+        }
+        let err = |source| Error::Id {
+            line,
+            id: id.into(),
+            source,
+        };
+        gix_hash::Prefix::from_hex(
+            id.to_str()
+                .map_err(|_| err(gix_hash::prefix::from_hex::Error::Invalid))?,
+        )
+        .map(Some)
+        .map_err(err)
+    }
+}
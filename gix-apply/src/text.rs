@@ -0,0 +1,115 @@
+use bstr::ByteSlice;
+
+use crate::{Hunk, LineKind};
+
+/// Options for use in [`apply()`].
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Options {
+    /// The minimum amount of context lines before and after each change that must match, or `None` to require all
+    /// context lines of a hunk to match, which is the default.
+    ///
+    /// If set, context lines furthest away from the change are ignored one by one until the hunk matches,
+    /// like `git apply -C<n>` does.
+    pub min_context: Option<u32>,
+}
+
+/// The outcome of [`apply()`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Outcome {
+    /// The content after applying all hunks that matched.
+    pub content: Vec<u8>,
+    /// The indices of the hunks that didn't match and thus weren't applied.
+    pub rejected: Vec<usize>,
+}
+
+/// Apply `hunks` to `content` and return the new content along with the hunks that couldn't be applied.
+///
+/// Each hunk is first searched at the position its header indicates, and then at increasingly distant positions before
+/// and after it, taking the first match. Hunks that start at the first line or have no trailing context must match
+/// at the beginning or the end of the file respectively, unless `options` allow ignoring context lines.
+pub fn apply(content: &[u8], hunks: &[Hunk], options: Options) -> Outcome {
+    let min_context = options.min_context.unwrap_or(u32::MAX) as usize;
+    let mut image: Vec<&[u8]> = content.lines_with_terminator().collect();
+    let mut rejected = Vec::new();
+    for (idx, hunk) in hunks.iter().enumerate() {
+        let lines = &hunk.lines;
+        let mut leading = lines.iter().take_while(|l| l.kind == LineKind::Context).count();
+        let mut trailing = if leading == lines.len() {
+            0
+        } else {
+            lines.iter().rev().take_while(|l| l.kind == LineKind::Context).count()
+        };
+        let (mut first, mut end) = (0, lines.len());
+        let mut pos = hunk.new_start.saturating_sub(1) as usize;
+        let mut match_beginning = hunk.old_start <= 1;
+        let mut match_end = trailing == 0;
+        loop {
+            let preimage: Vec<&[u8]> = lines[first..end]
+                .iter()
+                .filter(|l| l.kind != LineKind::Addition)
+                .map(|l| l.content.as_slice())
+                .collect();
+            if let Some(start) = find_position(&image, &preimage, pos, match_beginning, match_end) {
+                let postimage = lines[first..end]
+                    .iter()
+                    .filter(|l| l.kind != LineKind::Removal)
+                    .map(|l| l.content.as_slice());
+                image.splice(start..start + preimage.len(), postimage);
+                break;
+            }
+            if leading <= min_context && trailing <= min_context {
+                rejected.push(idx);
+                break;
+            }
+            if match_beginning || match_end {
+                match_beginning = false;
+                match_end = false;
+                continue;
+            }
+            if leading >= trailing {
+                first += 1;
+                leading -= 1;
+                pos += 1;
+            }
+            if trailing > leading {
+                end -= 1;
+                trailing -= 1;
+            }
+        }
+    }
+    Outcome {
+        content: image.concat(),
+        rejected,
+    }
+}
+
+/// Find the line at which `preimage` matches `image`, starting at `pos` and alternating between later and earlier lines
+/// with increasing distance.
+fn find_position(
+    image: &[&[u8]],
+    preimage: &[&[u8]],
+    pos: usize,
+    match_beginning: bool,
+    match_end: bool,
+) -> Option<usize> {
+    let last_start = image.len().checked_sub(preimage.len())?;
+    let matches_at = |start: usize| {
+        (!match_beginning || start == 0)
+            && (!match_end || start == last_start)
+            && image[start..start + preimage.len()] == *preimage
+    };
+    if match_beginning {
+        return matches_at(0).then_some(0);
+    }
+    if match_end {
+        return matches_at(last_start).then_some(last_start);
+    }
+    let pos = pos.min(last_start);
+    (0..=last_start).find_map(|distance| {
+        [pos.checked_add(distance), pos.checked_sub(distance)]
+            .into_iter()
+            .flatten()
+            .filter(|start| *start <= last_start)
+            .find(|start| matches_at(*start))
+    })
+}
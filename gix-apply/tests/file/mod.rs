@@ -0,0 +1,200 @@
+use std::path::Path;
+
+use gix_apply::{
+    file::{apply, Options, Outcome},
+    parse, text,
+};
+use gix_merge::blob::{Labels, Resolution};
+
+fn no_blobs(_: &gix_hash::Prefix) -> Option<Vec<u8>> {
+    None
+}
+
+fn read_optional(path: &Path) -> std::io::Result<Option<Vec<u8>>> {
+    match std::fs::read(path) {
+        Ok(data) => Ok(Some(data)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[test]
+fn patches_of_git_diff_produce_the_changed_tree() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_patches.sh")?;
+    for (patch_name, num_patches) in [("all.patch", 13), ("renames.patch", 13)] {
+        let patches = parse(&std::fs::read(root.join(patch_name))?)?;
+        assert_eq!(patches.len(), num_patches, "{patch_name}: one patch per changed file");
+        for patch in patches {
+            let current = patch
+                .old_path
+                .as_ref()
+                .map(|path| std::fs::read(root.join("base").join(path.to_string())))
+                .transpose()?;
+            let Outcome::Applied { content, rejected } = apply(
+                &patch,
+                current.as_deref(),
+                no_blobs,
+                Labels::default(),
+                Options::default(),
+            )?
+            else {
+                unreachable!("no three-way merges")
+            };
+            assert!(rejected.is_empty(), "{patch_name}: {}: all hunks apply", patch.path());
+            let expected = patch
+                .new_path
+                .as_ref()
+                .map(|path| std::fs::read(root.join("changed").join(path.to_string())))
+                .transpose()?;
+            assert_eq!(content, expected, "{patch_name}: {}", patch.path());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn binary_patches_without_data_need_the_new_blob() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_patches.sh")?;
+    let patches = parse(&std::fs::read(root.join("text-only.patch"))?)?;
+    let binary = patches.iter().find(|p| p.path() == "binary").expect("present");
+    let current = std::fs::read(root.join("base/binary"))?;
+    let err = apply(binary, Some(&current), no_blobs, Labels::default(), Options::default()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "binary: cannot apply binary patch without full index line or without the new content in the object database"
+    );
+    Ok(())
+}
+
+#[test]
+fn hunks_match_like_git_apply() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_patches.sh")?;
+    for (name, min_context) in [
+        ("offset", None),
+        ("fuzz", None),
+        ("fuzz-c1", Some(1)),
+        ("reject", None),
+        ("at-end", None),
+    ] {
+        let patches = parse(&std::fs::read(root.join(format!("{name}.patch")))?)?;
+        let Some(gix_apply::Content::Text(hunks)) = patches.first().map(|p| &p.content) else {
+            unreachable!("one text patch")
+        };
+        let target = std::fs::read(root.join(format!("{name}.target")))?;
+        let outcome = text::apply(&target, hunks, text::Options { min_context });
+        let git_failed = root.join(format!("{name}.failed")).is_file();
+        assert_eq!(!outcome.rejected.is_empty(), git_failed, "{name}");
+        if let Some(expected) = read_optional(&root.join(format!("{name}.expected")))? {
+            assert_eq!(
+                outcome.content.as_slice(),
+                expected.as_slice(),
+                "{name}: the result is the same as the one of `git apply`"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn three_way_merge_when_hunks_do_not_apply() -> crate::Result {
+    let patch =
+        b"diff --git a/f b/f\nindex 0123456..6543210 100644\n--- a/f\n+++ b/f\n@@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n";
+    let patches = parse(patch)?;
+    let base_id = gix_hash::Prefix::from_hex("0123456")?;
+    let find_base = |id: &gix_hash::Prefix| (*id == base_id).then(|| b"1\n2\n3\n4\n".to_vec());
+    let options = Options {
+        three_way: true,
+        ..Default::default()
+    };
+
+    let outcome = apply(
+        &patches[0],
+        Some(b"1\n2\n3\n4\n5\n"),
+        find_base,
+        Labels::default(),
+        options,
+    )?;
+    assert_eq!(
+        outcome,
+        Outcome::Applied {
+            content: Some(b"1\ntwo\n3\n4\n5\n".to_vec()),
+            rejected: Vec::new()
+        },
+        "no merge is needed if the patch applies"
+    );
+
+    let outcome = apply(
+        &patches[0],
+        Some(b"1\n2 ours\n3\n4\n"),
+        find_base,
+        Labels::default(),
+        options,
+    )?;
+    let Outcome::Merged {
+        content,
+        resolution,
+        base,
+        theirs,
+    } = outcome
+    else {
+        unreachable!("the patch doesn't apply")
+    };
+    assert_eq!(resolution, Resolution::Conflict);
+    assert_eq!(content, b"1\n<<<<<<<\n2 ours\n=======\ntwo\n>>>>>>>\n3\n4\n");
+    assert_eq!(base, b"1\n2\n3\n4\n");
+    assert_eq!(theirs, b"1\ntwo\n3\n4\n");
+
+    let outcome = apply(
+        &patches[0],
+        Some(b"1\n2\n3 ours\n4\n"),
+        find_base,
+        Labels::default(),
+        options,
+    )?;
+    assert_eq!(
+        outcome,
+        Outcome::Merged {
+            content: b"1\n<<<<<<<\n2\n3 ours\n=======\ntwo\n3\n>>>>>>>\n4\n".to_vec(),
+            resolution: Resolution::Conflict,
+            base: b"1\n2\n3\n4\n".to_vec(),
+            theirs: b"1\ntwo\n3\n4\n".to_vec(),
+        },
+        "adjacent changes conflict, just like in git"
+    );
+
+    let outcome = apply(
+        &patches[0],
+        Some(b"1\n2 ours\n3\n"),
+        no_blobs,
+        Labels::default(),
+        options,
+    )?;
+    assert_eq!(
+        outcome,
+        Outcome::Applied {
+            content: Some(b"1\n2 ours\n3\n".to_vec()),
+            rejected: vec![0]
+        },
+        "without the base, hunks are rejected"
+    );
+    Ok(())
+}
+
+#[test]
+fn creation_and_deletion_are_validated() -> crate::Result {
+    let patches = parse(b"--- /dev/null\n+++ b/new\n@@ -0,0 +1 @@\n+a\n--- a/old\n+++ /dev/null\n@@ -1 +0,0 @@\n-a\n")?;
+    let err = apply(&patches[0], Some(b""), no_blobs, Labels::default(), Options::default()).unwrap_err();
+    assert_eq!(err.to_string(), "new: already exists");
+    let err = apply(&patches[1], None, no_blobs, Labels::default(), Options::default()).unwrap_err();
+    assert_eq!(err.to_string(), "old: does not exist");
+    let err = apply(
+        &patches[1],
+        Some(b"a\nb\n"),
+        no_blobs,
+        Labels::default(),
+        Options::default(),
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "old: removal patch leaves file contents");
+    Ok(())
+}
@@ -0,0 +1,98 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# `repo` has two commits, and `base/` and `changed/` contain their trees. `all.patch` and `text-only.patch` are
+# the diffs between them with and without binary data, and `renames.patch` is the same with copy detection.
+git init -q repo
+(cd repo
+  seq 1 30 > modified
+  printf 'a\nb\nc' > no-newline
+  printf 'a\nb\n' > gets-no-newline
+  seq 1 10 > deleted
+  seq 1 40 > renamed-source
+  seq 100 140 > copy-source
+  printf '#!/bin/sh\necho hi\n' > exe
+  mkdir dir
+  seq 1 5 > "dir/with space"
+  seq 1 5 > "dir/ümlaut"
+  printf 'bin\0ary\n' > binary
+  for i in $(seq 1 300); do printf 'line %d\0\n' "$i"; done > binary-delta
+  git add . && git commit -q -m base
+
+  sed -i -e 's/^5$/five/' -e 's/^25$/twenty-five/' modified
+  printf 'a\nB\nc' > no-newline
+  printf 'a\nb\nc' > gets-no-newline
+  git rm -q deleted
+  git mv renamed-source renamed-dest
+  sed -i -e 's/^20$/twenty/' renamed-dest
+  sed -e 's/^120$/one-hundred-twenty/' copy-source > copy-dest
+  chmod +x exe
+  seq 1 3 > added
+  : > added-empty
+  sed -i -e 's/^3$/three/' "dir/with space" "dir/ümlaut"
+  printf 'binary\0\n' > binary
+  sed -i -e 's/^line 150/line one-hundred-fifty/' binary-delta
+  git add -A && git commit -q -m changed
+
+  git diff --binary HEAD~1 HEAD > ../all.patch
+  git diff HEAD~1 HEAD > ../text-only.patch
+  git diff -C --find-copies-harder --binary HEAD~1 HEAD > ../renames.patch
+  mkdir ../base ../changed
+  git archive HEAD~1 | tar -x -C ../base
+  git archive HEAD | tar -x -C ../changed
+)
+
+# A traditional unified diff, as produced by `diff -u` with timestamps.
+seq 1 10 > unified.before
+seq 1 10 | sed -e 's/^5$/five/' > unified.after
+{ diff -u unified.before unified.after || true; } > unified.patch
+
+# `<name>.patch` is applied to `<name>.target` with `git apply <args>`, and its result is stored in `<name>.expected`,
+# or `<name>.failed` is created if it didn't apply. For `--reject`, the partial result is stored as well.
+function baseline() {
+  local name=${1:?need name}
+  shift
+  mkdir "$name.tmp"
+  (cd "$name.tmp"
+    git init -q
+    cp "../$name.target" file
+    if git apply "$@" "../$name.patch" 2>/dev/null; then
+      cp file "../$name.expected"
+    else
+      touch "../$name.failed"
+      if [ -e file.rej ]; then
+        cp file "../$name.expected"
+      fi
+    fi
+  )
+  rm -rf "$name.tmp"
+}
+
+function make_patch() {
+  local name=${1:?need name}
+  shift
+  seq 1 30 > a/file
+  seq 1 30 | sed "$@" > b/file
+  { git diff --no-index a/file b/file || true; } | sed -e 's#a/a/file#a/file#g' -e 's#b/b/file#b/file#g' > "$name.patch"
+}
+
+mkdir a b
+make_patch offset -e 's/^15$/fifteen/' -e 's/^27$/twenty-seven/'
+seq 1 30 | sed -e '1i new-first\nnew-second\nnew-third' > offset.target
+baseline offset
+
+make_patch fuzz -e 's/^15$/fifteen/' -e 's/^27$/twenty-seven/'
+seq 1 30 | sed -e 's/^12$/twelve/' > fuzz.target
+baseline fuzz
+cp fuzz.patch fuzz-c1.patch
+cp fuzz.target fuzz-c1.target
+baseline fuzz-c1 -C1
+
+make_patch reject -e 's/^15$/fifteen/' -e 's/^27$/twenty-seven/'
+seq 1 30 | sed -e 's/^14$/fourteen/' > reject.target
+baseline reject --reject
+
+make_patch at-end -e '$a appended'
+seq 1 30 | sed -e '$a other' > at-end.target
+baseline at-end
+rm -rf a b
@@ -0,0 +1,220 @@
+use gix_apply::{binary, parse, Content, LineKind, Operation};
+use gix_object::tree::EntryKind;
+
+fn fixture(name: &str) -> crate::Result<Vec<gix_apply::Patch>> {
+    let root = gix_testtools::scripted_fixture_read_only("make_patches.sh")?;
+    Ok(parse(&std::fs::read(root.join(name))?)?)
+}
+
+#[test]
+fn git_extended_headers() -> crate::Result {
+    let patches = fixture("renames.patch")?;
+    let by_path = |path: &str| {
+        patches
+            .iter()
+            .find(|p| p.path() == path)
+            .unwrap_or_else(|| panic!("{path} is present"))
+    };
+
+    let renamed = by_path("renamed-dest");
+    assert_eq!(renamed.operation, Operation::Rename { similarity: Some(93) });
+    assert_eq!(renamed.old_path.as_ref().expect("set"), "renamed-source");
+    assert_eq!(renamed.old_mode, Some(EntryKind::Blob));
+
+    let copied = by_path("copy-dest");
+    assert_eq!(copied.operation, Operation::Copy { similarity: Some(89) });
+    assert_eq!(copied.old_path.as_ref().expect("set"), "copy-source");
+
+    let deleted = by_path("deleted");
+    assert_eq!(deleted.operation, Operation::Delete);
+    assert_eq!(deleted.new_path, None);
+    assert_eq!(deleted.new_id, None);
+    assert_eq!(deleted.old_id.expect("set").to_string(), "f00c965");
+
+    let added = by_path("added-empty");
+    assert_eq!(added.operation, Operation::Create);
+    assert_eq!(added.old_path, None);
+    assert_eq!(added.new_mode, Some(EntryKind::Blob));
+    assert_eq!(added.content, Content::Text(Vec::new()));
+
+    let exe = by_path("exe");
+    assert_eq!(exe.operation, Operation::Modify);
+    assert_eq!(
+        (exe.old_mode, exe.new_mode),
+        (Some(EntryKind::Blob), Some(EntryKind::BlobExecutable))
+    );
+    assert_eq!(exe.content, Content::Text(Vec::new()), "mode changes have no hunks");
+
+    assert_eq!(
+        by_path("dir/ümlaut").old_path.as_ref().expect("set"),
+        "dir/ümlaut",
+        "quoted paths are unquoted"
+    );
+    assert_eq!(
+        by_path("dir/with space").old_path.as_ref().expect("set"),
+        "dir/with space",
+        "paths with spaces are separated, and the trailing tab is removed"
+    );
+    Ok(())
+}
+
+#[test]
+fn hunks() -> crate::Result {
+    let patches = fixture("text-only.patch")?;
+    let modified = patches.iter().find(|p| p.path() == "modified").expect("present");
+    let Content::Text(hunks) = &modified.content else {
+        unreachable!("text patch")
+    };
+    assert_eq!(hunks.len(), 2);
+    let hunk = &hunks[1];
+    assert_eq!(
+        (hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines),
+        (22, 7, 22, 7)
+    );
+    assert_eq!(hunk.lines.len(), 8);
+    assert_eq!(hunk.lines[3].kind, LineKind::Removal);
+    assert_eq!(hunk.lines[3].content, "25\n");
+    assert_eq!(hunk.lines[4].kind, LineKind::Addition);
+    assert_eq!(hunk.lines[4].content, "twenty-five\n");
+
+    let no_newline = patches.iter().find(|p| p.path() == "no-newline").expect("present");
+    let Content::Text(hunks) = &no_newline.content else {
+        unreachable!("text patch")
+    };
+    let contents: Vec<_> = hunks[0].lines.iter().map(|l| l.content.to_string()).collect();
+    assert_eq!(
+        contents,
+        ["a\n", "b\n", "B\n", "c"],
+        "the marker for a missing newline applies to the line before it"
+    );
+
+    let binary = patches.iter().find(|p| p.path() == "binary").expect("present");
+    assert_eq!(
+        binary.content,
+        Content::BinaryWithoutData,
+        "without --binary, there is no data"
+    );
+    Ok(())
+}
+
+#[test]
+fn binary_patches() -> crate::Result {
+    let patches = fixture("all.patch")?;
+    let binary = patches.iter().find(|p| p.path() == "binary").expect("present");
+    assert_eq!(
+        binary.content,
+        Content::Binary {
+            forward: binary::Data::Literal(b"binary\0\n".to_vec()),
+            reverse: Some(binary::Data::Literal(b"bin\0ary\n".to_vec())),
+        }
+    );
+    let delta = patches.iter().find(|p| p.path() == "binary-delta").expect("present");
+    assert!(
+        matches!(
+            &delta.content,
+            Content::Binary {
+                forward: binary::Data::Delta(_),
+                reverse: Some(binary::Data::Delta(_))
+            }
+        ),
+        "larger files with small changes are encoded as deltas"
+    );
+    Ok(())
+}
+
+#[test]
+fn traditional_unified_diff() -> crate::Result {
+    let patches = fixture("unified.patch")?;
+    assert_eq!(patches.len(), 1);
+    let patch = &patches[0];
+    assert_eq!(patch.operation, Operation::Modify);
+    assert_eq!(
+        patch.old_path.as_ref().expect("set"),
+        "unified.before",
+        "the timestamp is removed, and there is no component to strip"
+    );
+    assert_eq!(patch.new_path.as_ref().expect("set"), "unified.after");
+    Ok(())
+}
+
+#[test]
+fn leading_text_is_skipped_but_a_patch_is_required() {
+    let patches = parse(b"Subject: a mail\n\nsome text\n--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+b\n").expect("valid");
+    assert_eq!(patches.len(), 1);
+    assert_eq!(patches[0].path(), "x");
+
+    assert!(matches!(parse(b"no patch here\n"), Err(parse::Error::NoPatch)));
+    assert!(
+        matches!(
+            parse(b"--- a/x\n+++ b/x\n@@ -1,2 +1 @@\n-a\n+b\n"),
+            Err(parse::Error::Hunk { line: 6 })
+        ),
+        "hunks must have as many lines as their header announces"
+    );
+}
+
+mod invalid_paths {
+    use gix_apply::parse;
+
+    fn assert_rejected(patch: &[u8], path: &str) {
+        match parse(patch) {
+            Err(parse::Error::InvalidPath { path: actual, .. }) => assert_eq!(actual, path),
+            res => panic!("expected {path:?} to be rejected, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn parent_directories() {
+        assert_rejected(b"--- a/../x\n+++ b/../x\n@@ -1 +1 @@\n-a\n+b\n", "../x");
+        assert_rejected(
+            b"diff --git a/dir/../../x b/dir/../../x\n--- a/dir/../../x\n+++ b/dir/../../x\n@@ -1 +1 @@\n-a\n+b\n",
+            "dir/../../x",
+        );
+        assert_rejected(
+            b"diff --git a/x b/y\nsimilarity index 100%\nrename from x\nrename to ../y\n",
+            "../y",
+        );
+    }
+
+    #[test]
+    fn absolute() {
+        assert_rejected(
+            b"--- a//etc/passwd\n+++ b//etc/passwd\n@@ -1 +1 @@\n-a\n+b\n",
+            "/etc/passwd",
+        );
+        assert_rejected(
+            b"diff --git a/x b/y\nsimilarity index 100%\nrename from x\nrename to /etc/passwd\n",
+            "/etc/passwd",
+        );
+    }
+
+    #[test]
+    fn dot_git() {
+        assert_rejected(
+            b"diff --git a/.git/config b/.git/config\n--- a/.git/config\n+++ b/.git/config\n@@ -1 +1 @@\n-a\n+b\n",
+            ".git/config",
+        );
+        assert_rejected(
+            b"diff --git a/x b/.GIT/hooks/pre-commit\nnew file mode 100755\n--- /dev/null\n+++ b/.GIT/hooks/pre-commit\n@@ -0,0 +1 @@\n+evil\n",
+            ".GIT/hooks/pre-commit",
+        );
+        assert_rejected(
+            b"--- a/sub/.git/config\n+++ b/sub/.git/config\n@@ -1 +1 @@\n-a\n+b\n",
+            "sub/.git/config",
+        );
+    }
+
+    #[test]
+    fn symlinked_gitmodules() {
+        assert_rejected(
+            b"diff --git a/.gitmodules b/.gitmodules\nnew file mode 120000\n--- /dev/null\n+++ b/.gitmodules\n@@ -0,0 +1 @@\n+target\n",
+            ".gitmodules",
+        );
+    }
+
+    #[test]
+    fn dotfiles_are_fine() {
+        let patches = parse(b"--- a/.gitignore\n+++ b/.gitignore\n@@ -1 +1 @@\n-a\n+b\n").expect("valid");
+        assert_eq!(patches[0].path(), ".gitignore");
+    }
+}
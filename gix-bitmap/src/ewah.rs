@@ -54,7 +54,7 @@ pub fn decode(data: &[u8]) -> Result<(Vec, &[u8]), decode::Error> {
 mod access {
     use std::convert::{TryFrom, TryInto};
 
-    use super::Vec;
+    use super::{rlw_literal_words, rlw_running_len_bits, rlw_runbit_is_set, Vec};
 
     impl Vec {
         /// Call `f(index)` for each bit that is true, given the index of the bit that identifies it uniquely within the bit array.
@@ -96,33 +96,224 @@ mod access {
         }
     }
 
-    #[inline]
-    fn rlw_running_len_bits(w: &u64) -> u64 {
-        rlw_running_len(w) * 64
+}
+
+mod mutation {
+    use super::{
+        rlw_literal_words, rlw_running_len, rlw_runbit_is_set, Vec, RLW_LARGEST_LITERAL_COUNT,
+        RLW_LARGEST_RUNNING_COUNT, RLW_RUNNING_BITS,
+    };
+
+    const BITS_IN_WORD: usize = 64;
+
+    fn words_for_bits(num_bits: usize) -> usize {
+        (num_bits + BITS_IN_WORD - 1) / BITS_IN_WORD
     }
 
-    #[inline]
-    fn rlw_running_len(w: &u64) -> u64 {
-        (w >> 1) & RLW_LARGEST_RUNNING_COUNT
+    impl Default for Vec {
+        fn default() -> Self {
+            Vec::new()
+        }
     }
 
-    #[inline]
-    fn rlw_literal_words(w: &u64) -> u64 {
-        w >> (1 + RLW_RUNNING_BITS)
+    impl Vec {
+        /// Create a new and empty bitmap.
+        pub fn new() -> Self {
+            Vec {
+                num_bits: 0,
+                bits: vec![0],
+                rlw: 0,
+            }
+        }
+
+        /// Set the bit at `index` to true, extending the bitmap so that it holds `index + 1` bits.
+        ///
+        /// Bits can only be set in ascending order, so `index` must be larger than any previously set bit,
+        /// which is also how `git` builds its bitmaps, producing the exact same compressed representation.
+        ///
+        /// # Panics
+        ///
+        /// If `index` isn't larger than the index of the last set bit.
+        pub fn set(&mut self, index: usize) {
+            let num_bits = self.num_bits();
+            assert!(
+                index >= num_bits,
+                "BUG: bits must be set in ascending order, got {index} with {num_bits} bits already present"
+            );
+            let distance = words_for_bits(index + 1) - words_for_bits(num_bits);
+            self.num_bits = (index + 1).try_into().expect("less than 4 billion bits");
+            let bit = 1u64 << (index % BITS_IN_WORD);
+
+            if distance > 0 {
+                if distance > 1 {
+                    self.add_empty_words(false, (distance - 1) as u64);
+                }
+                self.add_literal(bit);
+                return;
+            }
+
+            let rlw = self.rlw_word();
+            if rlw_literal_words(&rlw) == 0 {
+                self.set_rlw_running_len(rlw_running_len(&rlw) - 1);
+                self.add_literal(bit);
+                return;
+            }
+
+            let last = self.bits.last_mut().expect("at least one word");
+            *last |= bit;
+
+            // check if we just completed a stream of 1s
+            if *last == u64::MAX {
+                self.bits.pop();
+                self.set_rlw_literal_words(rlw_literal_words(&self.rlw_word()) - 1);
+                self.add_empty_word(true);
+            }
+        }
+
+        fn rlw_word(&self) -> u64 {
+            self.bits[self.rlw as usize]
+        }
+
+        fn rlw_mut(&mut self) -> &mut u64 {
+            &mut self.bits[self.rlw as usize]
+        }
+
+        fn set_rlw_run_bit(&mut self, value: bool) {
+            let rlw = self.rlw_mut();
+            if value {
+                *rlw |= 1;
+            } else {
+                *rlw &= !1;
+            }
+        }
+
+        fn set_rlw_running_len(&mut self, len: u64) {
+            let rlw = self.rlw_mut();
+            *rlw &= !(RLW_LARGEST_RUNNING_COUNT << 1);
+            *rlw |= len << 1;
+        }
+
+        fn set_rlw_literal_words(&mut self, count: u64) {
+            let rlw = self.rlw_mut();
+            *rlw &= (1 << (1 + RLW_RUNNING_BITS)) - 1;
+            *rlw |= count << (1 + RLW_RUNNING_BITS);
+        }
+
+        fn push_rlw(&mut self) {
+            self.bits.push(0);
+            self.rlw = (self.bits.len() - 1) as u64;
+        }
+
+        fn add_literal(&mut self, word: u64) {
+            let current = rlw_literal_words(&self.rlw_word());
+            if current >= RLW_LARGEST_LITERAL_COUNT {
+                self.push_rlw();
+                self.bits.push(word);
+                self.set_rlw_literal_words(1);
+                return;
+            }
+            self.bits.push(word);
+            self.set_rlw_literal_words(current + 1);
+        }
+
+        fn add_empty_word(&mut self, value: bool) {
+            let rlw = self.rlw_word();
+            let no_literal = rlw_literal_words(&rlw) == 0;
+            let run_len = rlw_running_len(&rlw);
+
+            if no_literal && run_len == 0 {
+                self.set_rlw_run_bit(value);
+            }
+
+            if no_literal && rlw_runbit_is_set(&self.rlw_word()) == value && run_len < RLW_LARGEST_RUNNING_COUNT {
+                self.set_rlw_running_len(run_len + 1);
+            } else {
+                self.push_rlw();
+                self.set_rlw_run_bit(value);
+                self.set_rlw_running_len(1);
+            }
+        }
+
+        fn add_empty_words(&mut self, value: bool, mut count: u64) {
+            let rlw = self.rlw_word();
+            if rlw_runbit_is_set(&rlw) != value && rlw_running_len(&rlw) + rlw_literal_words(&rlw) == 0 {
+                self.set_rlw_run_bit(value);
+            } else if rlw_literal_words(&rlw) != 0 || rlw_runbit_is_set(&rlw) != value {
+                self.push_rlw();
+                self.set_rlw_run_bit(value);
+            }
+
+            let run_len = rlw_running_len(&self.rlw_word());
+            let can_add = count.min(RLW_LARGEST_RUNNING_COUNT - run_len);
+            self.set_rlw_running_len(run_len + can_add);
+            count -= can_add;
+
+            while count >= RLW_LARGEST_RUNNING_COUNT {
+                self.push_rlw();
+                self.set_rlw_run_bit(value);
+                self.set_rlw_running_len(RLW_LARGEST_RUNNING_COUNT);
+                count -= RLW_LARGEST_RUNNING_COUNT;
+            }
+
+            if count > 0 {
+                self.push_rlw();
+                self.set_rlw_run_bit(value);
+                self.set_rlw_running_len(count);
+            }
+        }
     }
+}
+
+mod write {
+    use std::convert::TryFrom;
+
+    use super::Vec;
 
-    #[inline]
-    fn rlw_runbit_is_set(w: &u64) -> bool {
-        w & 1 == 1
+    impl Vec {
+        /// Serialize this bitmap to `out` in the format understood by [`decode()`][super::decode()].
+        pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+            out.write_all(&self.num_bits.to_be_bytes())?;
+            out.write_all(
+                &u32::try_from(self.bits.len())
+                    .expect("less than 4 billion words")
+                    .to_be_bytes(),
+            )?;
+            for word in &self.bits {
+                out.write_all(&word.to_be_bytes())?;
+            }
+            out.write_all(&u32::try_from(self.rlw).expect("rlw offset fits").to_be_bytes())?;
+            Ok(())
+        }
     }
+}
+
+#[inline]
+fn rlw_running_len_bits(w: &u64) -> u64 {
+    rlw_running_len(w) * 64
+}
+
+#[inline]
+fn rlw_running_len(w: &u64) -> u64 {
+    (w >> 1) & RLW_LARGEST_RUNNING_COUNT
+}
+
+#[inline]
+fn rlw_literal_words(w: &u64) -> u64 {
+    w >> (1 + RLW_RUNNING_BITS)
+}
 
-    const RLW_RUNNING_BITS: u64 = 4 * 8;
-    const RLW_LARGEST_RUNNING_COUNT: u64 = (1 << RLW_RUNNING_BITS) - 1;
+#[inline]
+fn rlw_runbit_is_set(w: &u64) -> bool {
+    w & 1 == 1
 }
 
+const RLW_RUNNING_BITS: u64 = 4 * 8;
+const RLW_LITERAL_BITS: u64 = 64 - 1 - RLW_RUNNING_BITS;
+const RLW_LARGEST_RUNNING_COUNT: u64 = (1 << RLW_RUNNING_BITS) - 1;
+const RLW_LARGEST_LITERAL_COUNT: u64 = (1 << RLW_LITERAL_BITS) - 1;
+
 /// A growable collection of u64 that are seen as stream of individual bits.
-#[allow(dead_code)]
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Vec {
     num_bits: u32,
     bits: std::vec::Vec<u64>,
@@ -0,0 +1,111 @@
+//! Changed-path Bloom filters, which are optionally stored for each commit in a commit-graph file.
+//!
+//! Each filter contains the paths, along with all of their leading directories, that changed between a commit
+//! and its first parent. This allows to learn that a path definitely *didn't* change without diffing trees.
+use bstr::BStr;
+
+/// The seed for the first of the two hashes that all keys are derived from.
+const SEED0: u32 = 0x293a_e76f;
+/// The seed for the second of the two hashes that all keys are derived from.
+const SEED1: u32 = 0x7e64_6e2c;
+
+/// The settings that were used to write all Bloom filters of a commit-graph file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Settings {
+    /// The version of the hash function, which is `1` or `2`.
+    ///
+    /// Version `1` is what older versions of `git` write, with a hash function that is incorrect for paths with bytes
+    /// larger than `0x7f`. It's still readable, as it's only used consistently.
+    pub version: u32,
+    /// The amount of hashes, and thus bits, to set for each key.
+    pub num_hashes: u32,
+    /// The amount of bits per entry, which is only of importance when writing filters.
+    pub bits_per_entry: u32,
+}
+
+/// A Bloom filter of a single commit, with all paths that changed compared to its first parent.
+#[derive(Debug, Copy, Clone)]
+pub struct Filter<'a> {
+    data: &'a [u8],
+    settings: Settings,
+}
+
+/// Initialization
+impl<'a> Filter<'a> {
+    /// Create a new filter from its `data`, which was written using `settings`.
+    pub fn new(data: &'a [u8], settings: Settings) -> Self {
+        Filter { data, settings }
+    }
+}
+
+/// Access
+impl<'a> Filter<'a> {
+    /// Return `false` if `path` definitely didn't change in the commit of this filter, or `true` if it might have changed.
+    ///
+    /// `path` is relative to the root of the repository, without leading or trailing slashes.
+    /// Note that empty filters, as written for commits with too many changes, may contain any path.
+    pub fn may_contain(&self, path: &BStr) -> bool {
+        let num_bits = self.data.len() as u64 * 8;
+        if num_bits == 0 {
+            return true;
+        }
+        let (hash0, hash1) = (
+            murmur3(self.settings.version, SEED0, path),
+            murmur3(self.settings.version, SEED1, path),
+        );
+        (0..self.settings.num_hashes).all(|idx| {
+            let bit = u64::from(hash0.wrapping_add(idx.wrapping_mul(hash1))) % num_bits;
+            self.data[(bit / 8) as usize] & (1 << (bit % 8)) != 0
+        })
+    }
+
+    /// Return the settings used to write this filter.
+    pub fn settings(&self) -> Settings {
+        self.settings
+    }
+
+    /// Return the raw bits of this filter.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// The 32-bit murmur3 hash of `data` with `seed`, as implemented by `git`.
+///
+/// In `version` 1, bytes are sign-extended, which is what `git` did on platforms where `char` is signed.
+fn murmur3(version: u32, mut seed: u32, data: &[u8]) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    let byte = |b: u8| -> u32 {
+        if version == 1 {
+            b as i8 as u32
+        } else {
+            u32::from(b)
+        }
+    };
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in chunks.by_ref() {
+        let k = byte(chunk[0]) | byte(chunk[1]) << 8 | byte(chunk[2]) << 16 | byte(chunk[3]) << 24;
+        seed ^= k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        seed = seed.rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let k = tail
+            .iter()
+            .enumerate()
+            .fold(0_u32, |k, (idx, b)| k ^ byte(*b) << (8 * idx));
+        seed ^= k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+    }
+
+    seed ^= data.len() as u32;
+    seed ^= seed >> 16;
+    seed = seed.wrapping_mul(0x85eb_ca6b);
+    seed ^= seed >> 13;
+    seed = seed.wrapping_mul(0xc2b2_ae35);
+    seed ^= seed >> 16;
+    seed
+}
@@ -5,6 +5,7 @@ use std::{
 };
 
 use crate::{
+    bloom,
     file::{self, commit::Commit, COMMIT_DATA_ENTRY_SIZE_SANS_HASH},
     File,
 };
@@ -108,6 +109,11 @@ impl File {
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    /// Return the settings of the changed-path Bloom filters stored in this file, or `None` if there are none.
+    pub fn bloom_filter_settings(&self) -> Option<bloom::Settings> {
+        self.bloom_filters.as_ref().map(|filters| filters.settings)
+    }
 }
 
 impl File {
@@ -128,6 +134,23 @@ impl File {
         &self.data[start..][..entry_size]
     }
 
+    /// Returns the changed-path Bloom filter of the commit at the given position, if there is a valid one.
+    pub(crate) fn bloom_filter_at(&self, pos: file::Position) -> Option<bloom::Filter<'_>> {
+        let filters = self.bloom_filters.as_ref()?;
+        let pos = pos.0 as usize;
+        let end_at = |pos: usize| {
+            u32::from_be_bytes(
+                self.data[filters.index_offset + pos * 4..][..4]
+                    .try_into()
+                    .expect("4 bytes"),
+            ) as usize
+        };
+        let start = if pos == 0 { 0 } else { end_at(pos - 1) };
+        let end = end_at(pos);
+        let data = &self.data[filters.data.clone()];
+        (start <= end && end <= data.len()).then(|| bloom::Filter::new(&data[start..end], filters.settings))
+    }
+
     /// Returns the byte slice for this file's entire Extra Edge List (EDGE) chunk.
     pub(crate) fn extra_edges_data(&self) -> Option<&[u8]> {
         Some(&self.data[self.extra_edges_list_range.clone()?])
@@ -105,6 +105,12 @@ impl<'a> Commit<'a> {
     pub fn root_tree_id(&self) -> &gix_hash::oid {
         self.root_tree_id
     }
+
+    /// Return the changed-path Bloom filter with all paths that changed compared to the first parent of this commit,
+    /// or `None` if there is none.
+    pub fn bloom_filter(&self) -> Option<crate::bloom::Filter<'a>> {
+        self.file.bloom_filter_at(self.pos)
+    }
 }
 
 impl<'a> Debug for Commit<'a> {
@@ -9,7 +9,8 @@ use memmap2::Mmap;
 
 use crate::{
     file::{
-        ChunkId, BASE_GRAPHS_LIST_CHUNK_ID, COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH,
+        BloomFilters, ChunkId, BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_FILTER_DATA_CHUNK_ID, BLOOM_FILTER_DATA_HEADER_LEN,
+        BLOOM_FILTER_INDEX_CHUNK_ID, COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH,
         EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN, HEADER_LEN, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
     },
     File,
@@ -188,6 +189,7 @@ impl File {
             })??;
 
         let extra_edges_list_range = chunks.usize_offset_by_id(EXTENDED_EDGES_LIST_CHUNK_ID).ok();
+        let bloom_filters = read_bloom_filters(&data, &chunks, commit_data_count);
 
         let trailer = &data[chunks.highest_offset() as usize..];
         if trailer.len() != object_hash.len_in_bytes() {
@@ -225,6 +227,7 @@ impl File {
         Ok(File {
             base_graph_count,
             base_graphs_list_offset,
+            bloom_filters,
             commit_data_offset,
             data,
             extra_edges_list_range,
@@ -257,6 +260,30 @@ impl TryFrom<&Path> for File {
     }
 }
 
+/// Read the location of the changed-path Bloom filters, and ignore them if they are invalid or use an unknown version,
+/// like `git` does.
+fn read_bloom_filters(data: &[u8], chunks: &gix_chunk::file::Index, num_commits: u32) -> Option<BloomFilters> {
+    let index = chunks.usize_offset_by_id(BLOOM_FILTER_INDEX_CHUNK_ID).ok()?;
+    let filters = chunks.usize_offset_by_id(BLOOM_FILTER_DATA_CHUNK_ID).ok()?;
+    if index.len() != num_commits as usize * 4 || filters.len() < BLOOM_FILTER_DATA_HEADER_LEN {
+        return None;
+    }
+    let read_u32 = |ofs: usize| u32::from_be_bytes(data[filters.start + ofs..][..4].try_into().expect("4 bytes"));
+    let settings = crate::bloom::Settings {
+        version: read_u32(0),
+        num_hashes: read_u32(4),
+        bits_per_entry: read_u32(8),
+    };
+    if !matches!(settings.version, 1 | 2) {
+        return None;
+    }
+    Some(BloomFilters {
+        index_offset: index.start,
+        data: filters.start + BLOOM_FILTER_DATA_HEADER_LEN..filters.end,
+        settings,
+    })
+}
+
 // Copied from gix-odb/pack/index/init.rs
 fn read_fan(d: &[u8]) -> ([u32; FAN_LEN], usize) {
     let mut fan = [0; FAN_LEN];
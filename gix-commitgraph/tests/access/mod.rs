@@ -1,3 +1,7 @@
+use bstr::ByteSlice;
+use gix_commitgraph::Graph;
+use gix_testtools::scripted_fixture_read_only;
+
 use crate::{check_common, graph_and_expected, graph_and_expected_named};
 
 #[test]
@@ -111,3 +115,41 @@ fn two_parents() {
     assert_eq!(cg.commit_at(refs["parent2"].pos()).generation(), 1);
     assert_eq!(cg.commit_at(refs["child"].pos()).generation(), 2);
 }
+
+#[test]
+fn bloom_filters() -> gix_testtools::Result {
+    let repo_dir = scripted_fixture_read_only("bloom_filters.sh")?;
+    let cg = Graph::from_info_dir(&repo_dir.join(".git").join("objects").join("info"))?;
+    let baseline = std::fs::read(repo_dir.join("baseline.git"))?;
+
+    let mut commits = 0;
+    for record in baseline.split_str("\n\n").filter(|record| !record.trim().is_empty()) {
+        let mut lines = record.lines();
+        let id = gix_hash::ObjectId::from_hex(lines.next().expect("commit id"))?;
+        let commit = cg.commit_by_id(id).expect("present");
+        let filter = commit.bloom_filter().expect("filters were written for all commits");
+        assert_eq!(filter.settings().version, 1, "git writes version 1 by default");
+
+        let mut num_paths = 0;
+        for path in lines {
+            num_paths += 1;
+            let mut prefix = path;
+            loop {
+                assert!(filter.may_contain(prefix.as_bstr()), "{prefix:?} is contained in {id}");
+                match prefix.rfind_byte(b'/') {
+                    Some(pos) => prefix = &prefix[..pos],
+                    None => break,
+                }
+            }
+        }
+        if num_paths == 0 {
+            assert!(
+                !filter.may_contain("file".into()),
+                "empty commits have empty filters that contain nothing"
+            );
+        }
+        commits += 1;
+    }
+    assert_eq!(commits, 5, "all commits were checked");
+    Ok(())
+}
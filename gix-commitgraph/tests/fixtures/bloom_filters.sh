@@ -0,0 +1,28 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Write the id of each commit along with all paths it changed compared to its first parent into `baseline.git`,
+# with each record separated by an empty line.
+function baseline() {
+  for commit in $(git rev-list HEAD); do
+    {
+      echo "$commit"
+      git diff-tree --root --no-commit-id -r --name-only -z "$commit" | tr '\0' '\n'
+      echo
+    } >> baseline.git
+  done
+}
+
+git init -q
+git config core.quotePath false
+
+echo 1 >file && git add . && git commit -q -m root
+mkdir -p dir/sub && echo 1 >dir/sub/file && echo 1 >dir/other && git add . && git commit -q -m "nested"
+mkdir "ä" && echo 1 >"ä/ö.txt" && git add . && git commit -q -m "non-ascii"
+git commit -q --allow-empty -m "empty"
+echo 2 >dir/sub/file && git rm -q file && git commit -q -am "modification and deletion"
+
+baseline
+
+git commit-graph write --no-progress --reachable --changed-paths
+git repack -adq
@@ -336,8 +336,26 @@ impl<'event> File<'event> {
             .section_ids_by_name_and_subname(name.as_ref(), subsection_name.into())?
             .next_back()
             .expect("list of sections were empty, which violates invariant");
-        let section = self.sections.get_mut(&id).expect("known section-id");
-        section.header = section::Header::new(new_name, new_subsection_name)?;
+        self.rename_section_by_id(id, new_name, new_subsection_name)
+    }
+
+    /// Renames the section identified by `id` to use `new_name` and `new_subsection_name`.
+    ///
+    /// This is useful to rename each of multiple sections exactly once, even if their new name would match their old one,
+    /// as may be the case when only the case of their name changes.
+    pub fn rename_section_by_id(
+        &mut self,
+        id: SectionId,
+        new_name: impl Into<Cow<'event, str>>,
+        new_subsection_name: impl Into<Option<Cow<'event, BStr>>>,
+    ) -> Result<(), rename_section::Error> {
+        let new_header = section::Header::new(new_name, new_subsection_name)?;
+        let section = self
+            .sections
+            .get_mut(&id)
+            .ok_or(rename_section::Error::Lookup(lookup::existing::Error::SectionMissing))?;
+        let prev_header = std::mem::replace(&mut section.header, new_header);
+        self.update_lookup_of_renamed_section(id, &prev_header);
         Ok(())
     }
 
@@ -360,7 +378,11 @@ impl<'event> File<'event> {
             .find(|id| filter(self.sections.get(id).expect("each id has a section").meta()))
             .ok_or(rename_section::Error::Lookup(lookup::existing::Error::KeyMissing))?;
         let section = self.sections.get_mut(&id).expect("known section-id");
-        section.header = section::Header::new(new_name, new_subsection_name)?;
+        let prev_header = std::mem::replace(
+            &mut section.header,
+            section::Header::new(new_name, new_subsection_name)?,
+        );
+        self.update_lookup_of_renamed_section(id, &prev_header);
         Ok(())
     }
 
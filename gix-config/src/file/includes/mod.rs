@@ -38,7 +38,60 @@ impl File<'static> {
 }
 
 pub(crate) fn resolve(config: &mut File<'static>, buf: &mut Vec<u8>, options: init::Options<'_>) -> Result<(), Error> {
-    resolve_includes_recursive(config, 0, buf, options)
+    let mut remote_urls = RemoteUrls::Unknown {
+        unresolved: (options.includes.max_depth != 0 && has_includes(config)).then(|| Box::new(config.clone())),
+    };
+    resolve_includes_recursive(config, 0, buf, options, &mut remote_urls, false)
+}
+
+/// The urls of all remotes in the configuration we resolve includes for, to evaluate `hasconfig:remote.*.url` conditions.
+enum RemoteUrls {
+    /// The urls weren't needed yet, but can be collected from the `unresolved` configuration if it contains includes.
+    Unknown { unresolved: Option<Box<File<'static>>> },
+    /// We are in the first pass that collects urls, which is when all `hasconfig:remote.*.url` conditions are true.
+    Collecting,
+    /// The urls of all remotes, as seen after resolving all includes.
+    Known(Vec<BString>),
+}
+
+impl RemoteUrls {
+    /// Return `true` if `pattern` matches the url of any remote, which are collected on first use by resolving
+    /// all includes of the unresolved configuration like `git` does.
+    fn any_matches(&mut self, pattern: &BStr, options: init::Options<'_>) -> Result<bool, Error> {
+        if let RemoteUrls::Unknown { unresolved } = self {
+            let mut config = unresolved.take().map(|config| *config).unwrap_or_default();
+            let mut collecting = RemoteUrls::Collecting;
+            resolve_includes_recursive(&mut config, 0, &mut Vec::new(), options, &mut collecting, false)?;
+            *self = RemoteUrls::Known(remote_urls_of(&config).map(Cow::into_owned).collect());
+        }
+        Ok(match self {
+            RemoteUrls::Collecting => true,
+            RemoteUrls::Known(urls) => urls.iter().any(|url| {
+                gix_glob::wildmatch(
+                    pattern,
+                    url.as_bstr(),
+                    gix_glob::wildmatch::Mode::NO_MATCH_SLASH_LITERAL,
+                )
+            }),
+            RemoteUrls::Unknown { .. } => unreachable!("urls were collected above"),
+        })
+    }
+}
+
+fn has_includes(config: &File<'_>) -> bool {
+    config.sections.values().any(|section| {
+        let name = section.header.name.as_ref();
+        name == "include" || name == "includeIf"
+    })
+}
+
+fn remote_urls_of<'a>(config: &'a File<'static>) -> impl Iterator<Item = Cow<'a, BStr>> + 'a {
+    config
+        .sections_by_name("remote")
+        .into_iter()
+        .flatten()
+        .filter(|section| section.header.subsection_name.is_some())
+        .flat_map(|section| section.body.values("url"))
 }
 
 fn resolve_includes_recursive(
@@ -46,6 +99,8 @@ fn resolve_includes_recursive(
     depth: u8,
     buf: &mut Vec<u8>,
     options: init::Options<'_>,
+    remote_urls: &mut RemoteUrls,
+    in_hasconfig_include: bool,
 ) -> Result<(), Error> {
     if depth == options.includes.max_depth {
         return if options.includes.err_on_max_depth_exceeded {
@@ -66,28 +121,39 @@ fn resolve_includes_recursive(
         let header = &section.header;
         let header_name = header.name.as_ref();
         if header_name == "include" && header.subsection_name.is_none() {
-            detach_include_paths(&mut section_ids_and_include_paths, section, id)
+            detach_include_paths(&mut section_ids_and_include_paths, section, id, false)
         } else if header_name == "includeIf" {
             if let Some(condition) = &header.subsection_name {
                 let target_config_path = section.meta.path.as_deref();
-                if include_condition_match(condition.as_ref(), target_config_path, options.includes)? {
-                    detach_include_paths(&mut section_ids_and_include_paths, section, id)
+                if include_condition_match(condition.as_ref(), target_config_path, options, remote_urls)? {
+                    let is_hasconfig = condition.starts_with(HASCONFIG_REMOTE_URL);
+                    detach_include_paths(&mut section_ids_and_include_paths, section, id, is_hasconfig)
                 }
             }
         }
     }
 
-    append_followed_includes_recursively(section_ids_and_include_paths, target_config, depth, options, buf)
+    append_followed_includes_recursively(
+        section_ids_and_include_paths,
+        target_config,
+        depth,
+        options,
+        buf,
+        remote_urls,
+        in_hasconfig_include,
+    )
 }
 
 fn append_followed_includes_recursively(
-    section_ids_and_include_paths: Vec<(SectionId, crate::Path<'_>)>,
+    section_ids_and_include_paths: Vec<(SectionId, crate::Path<'_>, bool)>,
     target_config: &mut File<'static>,
     depth: u8,
     options: init::Options<'_>,
     buf: &mut Vec<u8>,
+    remote_urls: &mut RemoteUrls,
+    in_hasconfig_include: bool,
 ) -> Result<(), Error> {
-    for (section_id, config_path) in section_ids_and_include_paths {
+    for (section_id, config_path, is_hasconfig) in section_ids_and_include_paths {
         let meta = OwnShared::clone(&target_config.sections[&section_id].meta);
         let target_config_path = meta.path.as_deref();
         let config_path = match resolve_path(config_path, target_config_path, options.includes)? {
@@ -101,7 +167,7 @@ fn append_followed_includes_recursively(
         buf.clear();
         std::io::copy(&mut std::fs::File::open(&config_path)?, buf)?;
         let config_meta = Metadata {
-            path: Some(config_path),
+            path: Some(config_path.clone()),
             trust: meta.trust,
             level: meta.level + 1,
             source: meta.source,
@@ -117,7 +183,18 @@ fn append_followed_includes_recursively(
                 init::Error::Interpolate(err) => Error::Interpolate(err),
                 init::Error::Includes(_) => unreachable!("BUG: {:?} not possible due to no-follow options", err),
             })?;
-        resolve_includes_recursive(&mut include_config, depth + 1, buf, options)?;
+        let in_hasconfig_include = in_hasconfig_include || is_hasconfig;
+        if in_hasconfig_include && remote_urls_of(&include_config).next().is_some() {
+            return Err(Error::RemoteUrlInHasconfigInclude { path: config_path });
+        }
+        resolve_includes_recursive(
+            &mut include_config,
+            depth + 1,
+            buf,
+            options,
+            remote_urls,
+            in_hasconfig_include,
+        )?;
 
         target_config.append_or_insert(include_config, Some(section_id));
     }
@@ -125,24 +202,33 @@ fn append_followed_includes_recursively(
 }
 
 fn detach_include_paths(
-    include_paths: &mut Vec<(SectionId, crate::Path<'static>)>,
+    include_paths: &mut Vec<(SectionId, crate::Path<'static>, bool)>,
     section: &file::Section<'_>,
     id: SectionId,
+    is_hasconfig: bool,
 ) {
     include_paths.extend(
         section
             .body
             .values("path")
             .into_iter()
-            .map(|path| (id, crate::Path::from(Cow::Owned(path.into_owned())))),
+            .map(|path| (id, crate::Path::from(Cow::Owned(path.into_owned())), is_hasconfig)),
     )
 }
 
+/// The only kind of `hasconfig` condition that `git` supports.
+const HASCONFIG_REMOTE_URL: &[u8] = b"hasconfig:remote.*.url:";
+
 fn include_condition_match(
     condition: &BStr,
     target_config_path: Option<&Path>,
-    options: Options<'_>,
+    init_options: init::Options<'_>,
+    remote_urls: &mut RemoteUrls,
 ) -> Result<bool, Error> {
+    if let Some(pattern) = condition.strip_prefix(HASCONFIG_REMOTE_URL) {
+        return remote_urls.any_matches(pattern.as_bstr(), init_options);
+    }
+    let options = init_options.includes;
     let mut tokens = condition.splitn(2, |b| *b == b':');
     let (prefix, condition) = match (tokens.next(), tokens.next()) {
         (Some(a), Some(b)) => (a, b),
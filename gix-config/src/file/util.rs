@@ -113,6 +113,74 @@ impl<'event> File<'event> {
         new_section_id
     }
 
+    /// Move the section with `id` from the lookup entries of its `prev_header` to the ones of its current header,
+    /// maintaining the order in which sections appear.
+    pub(crate) fn update_lookup_of_renamed_section(&mut self, id: SectionId, prev_header: &section::Header<'event>) {
+        if let Some(lookup) = self.section_lookup_tree.get_mut(&prev_header.name) {
+            lookup.retain_mut(|node| match (node, prev_header.subsection_name.as_deref()) {
+                (SectionBodyIdsLut::NonTerminal(subsections), Some(subsection_name)) => {
+                    if let Some(ids) = subsections.get_mut(subsection_name) {
+                        ids.retain(|v| *v != id);
+                        if ids.is_empty() {
+                            subsections.remove(subsection_name);
+                        }
+                    }
+                    !subsections.is_empty()
+                }
+                (SectionBodyIdsLut::Terminal(ids), None) => {
+                    ids.retain(|v| *v != id);
+                    !ids.is_empty()
+                }
+                _ => true,
+            });
+            if lookup.is_empty() {
+                self.section_lookup_tree.remove(&prev_header.name);
+            }
+        }
+
+        let header = &self.sections[&id].header;
+        let (name, subsection_name) = (header.name.clone(), header.subsection_name.clone());
+        let lookup = self.section_lookup_tree.entry(name).or_default();
+        let ids = match subsection_name {
+            Some(subsection_name) => {
+                let pos = match lookup
+                    .iter()
+                    .position(|node| matches!(node, SectionBodyIdsLut::NonTerminal(_)))
+                {
+                    Some(pos) => pos,
+                    None => {
+                        lookup.push(SectionBodyIdsLut::NonTerminal(Default::default()));
+                        lookup.len() - 1
+                    }
+                };
+                match &mut lookup[pos] {
+                    SectionBodyIdsLut::NonTerminal(subsections) => subsections.entry(subsection_name).or_default(),
+                    SectionBodyIdsLut::Terminal(_) => unreachable!("we found or pushed a non-terminal"),
+                }
+            }
+            None => {
+                let pos = match lookup
+                    .iter()
+                    .position(|node| matches!(node, SectionBodyIdsLut::Terminal(_)))
+                {
+                    Some(pos) => pos,
+                    None => {
+                        lookup.push(SectionBodyIdsLut::Terminal(Vec::new()));
+                        lookup.len() - 1
+                    }
+                };
+                match &mut lookup[pos] {
+                    SectionBodyIdsLut::Terminal(ids) => ids,
+                    SectionBodyIdsLut::NonTerminal(_) => unreachable!("we found or pushed a terminal"),
+                }
+            }
+        };
+        let section_order = &self.section_order;
+        let order = |id: SectionId| section_order.iter().position(|v| *v == id).expect("known section id");
+        let pos = ids.partition_point(|candidate| order(*candidate) < order(id));
+        ids.insert(pos, id);
+    }
+
     /// Returns the mapping between section and subsection name to section ids.
     pub(crate) fn section_ids_by_name_and_subname<'a>(
         &'a self,
@@ -66,6 +66,82 @@ mod rename_section {
             ))
         ));
     }
+
+    #[test]
+    fn renamed_sections_can_be_found_by_their_new_name_only() -> crate::Result {
+        let mut file = gix_config::File::try_from(
+            "[remote \"origin\"]\n\turl = a\n[core]\n\tbare = true\n[remote \"origin\"]\n\tfetch = b\n[remote \"upstream\"]\n\turl = c\n",
+        )?;
+        file.rename_section(
+            "remote",
+            Some("origin".into()),
+            "remote",
+            Some(Cow::Borrowed("upstream".into())),
+        )?;
+        file.rename_section(
+            "remote",
+            Some("origin".into()),
+            "remote",
+            Some(Cow::Borrowed("upstream".into())),
+        )?;
+        assert!(
+            file.rename_section(
+                "remote",
+                Some("origin".into()),
+                "remote",
+                Some(Cow::Borrowed("upstream".into()))
+            )
+            .is_err(),
+            "all sections were renamed"
+        );
+        assert!(file.section("remote", Some("origin".into())).is_err());
+        assert_eq!(
+            file.raw_values("remote", Some("upstream".into()), "url")?,
+            vec![Cow::<bstr::BStr>::Borrowed("a".into()), Cow::Borrowed("c".into())],
+            "sections remain in order of appearance"
+        );
+        assert_eq!(
+            file.raw_value("remote", Some("upstream".into()), "fetch")?.as_ref(),
+            "b"
+        );
+
+        file.rename_section("core", None, "remote", Some(Cow::Borrowed("upstream".into())))?;
+        assert!(file.section("core", None).is_err());
+        assert_eq!(
+            file.raw_value("remote", Some("upstream".into()), "bare")?.as_ref(),
+            "true"
+        );
+        assert_eq!(
+            file.raw_value("remote", Some("upstream".into()), "url")?.as_ref(),
+            "c",
+            "the last section still wins"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sections_can_be_renamed_by_id_to_a_name_that_still_matches() -> crate::Result {
+        let mut file = gix_config::File::try_from("[Core]\n\ta = 1\n[core]\n\tb = 2\n")?;
+        let ids: Vec<_> = file
+            .sections_and_ids_by_name("core")
+            .expect("present")
+            .map(|(_, id)| id)
+            .collect();
+        assert_eq!(ids.len(), 2, "section names are case-insensitive");
+        for id in ids {
+            file.rename_section_by_id(id, "core", None)?;
+        }
+        assert_eq!(file.to_string(), "[core]\n\ta = 1\n[core]\n\tb = 2\n");
+        assert_eq!(file.raw_value("core", None, "a")?.as_ref(), "1");
+
+        let id = file.sections_and_ids().next().expect("present").1;
+        file.remove_section_by_id(id);
+        assert!(matches!(
+            file.rename_section_by_id(id, "other", None),
+            Err(rename_section::Error::Lookup(_))
+        ));
+        Ok(())
+    }
 }
 mod set_meta {
     use gix_config::file;
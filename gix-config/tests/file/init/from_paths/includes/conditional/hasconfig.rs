@@ -0,0 +1,143 @@
+use std::{fs, path::Path};
+
+use gix_config::file::{includes, init};
+use gix_testtools::tempfile::tempdir;
+
+fn options() -> init::Options<'static> {
+    init::Options {
+        includes: includes::Options::follow(Default::default(), Default::default()),
+        ..Default::default()
+    }
+}
+
+fn user_name(dir: &Path, root: &str) -> crate::Result<String> {
+    let config_path = dir.join("root");
+    fs::write(&config_path, root)?;
+    let meta = gix_config::file::Metadata::try_from_path(config_path, gix_config::Source::Api)?;
+    let config = gix_config::File::from_paths_metadata(Some(meta), options())?.expect("non-empty");
+    Ok(config.string_by_key("user.name").expect("present").to_string())
+}
+
+fn write_includes(dir: &Path) -> crate::Result {
+    fs::write(dir.join("inc"), "[user]\n\tname = included\n")?;
+    fs::write(dir.join("inc-remote"), "[remote \"x\"]\n\turl = https://other/a\n")?;
+    fs::write(dir.join("inc-nested-remote"), "[include]\n\tpath = inc-remote\n")?;
+    fs::write(
+        dir.join("normal-inc"),
+        "[remote \"origin\"]\n\turl = https://corp.example/org/repo\n",
+    )?;
+    Ok(())
+}
+
+fn root_with(pattern: &str, path: &str, suffix: &str) -> String {
+    format!("[user]\n\tname = base\n[includeIf \"hasconfig:remote.*.url:{pattern}\"]\n\tpath = {path}\n{suffix}")
+}
+
+const ORIGIN: &str = "[remote \"origin\"]\n\turl = https://corp.example/org/repo\n";
+
+#[test]
+fn remote_urls_are_matched_with_globs_even_if_they_are_defined_later() -> crate::Result {
+    let dir = tempdir()?;
+    write_includes(dir.path())?;
+    for (pattern, expected) in [
+        ("https://corp.example/**", "included"),
+        ("https://corp.example/**/repo", "included"),
+        ("https://corp.example/org/*", "included"),
+        ("https://corp.example/*/repo", "included"),
+        ("https://corp.example/org/re[p]o", "included"),
+        ("https://corp.example/*", "base"),
+        ("https://corp.example*", "base"),
+        ("*corp.example*", "base"),
+        ("HTTPS://corp.example/**", "base"),
+        ("https://nope/**", "base"),
+    ] {
+        assert_eq!(
+            user_name(dir.path(), &root_with(pattern, "inc", ORIGIN))?,
+            expected,
+            "{pattern}: slashes must be matched literally, and the match is case-sensitive"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn remote_urls_from_included_files_are_considered() -> crate::Result {
+    let dir = tempdir()?;
+    write_includes(dir.path())?;
+    assert_eq!(
+        user_name(
+            dir.path(),
+            &root_with("https://corp.example/**", "inc", "[include]\n\tpath = normal-inc\n")
+        )?,
+        "included"
+    );
+    Ok(())
+}
+
+#[test]
+fn included_values_are_placed_after_the_include_section() -> crate::Result {
+    let dir = tempdir()?;
+    write_includes(dir.path())?;
+    let root = root_with(
+        "https://corp.example/**",
+        "inc",
+        &format!("{ORIGIN}[user]\n\tname = after\n"),
+    );
+    assert_eq!(user_name(dir.path(), &root)?, "after");
+    Ok(())
+}
+
+#[test]
+fn only_remote_urls_with_subsection_are_supported() -> crate::Result {
+    let dir = tempdir()?;
+    write_includes(dir.path())?;
+    assert_eq!(
+        user_name(
+            dir.path(),
+            &root_with(
+                "https://corp.example/**",
+                "inc",
+                "[remote]\n\turl = https://corp.example/org/repo\n"
+            )
+        )?,
+        "base",
+        "remote.url isn't a remote url"
+    );
+    assert_eq!(
+        user_name(
+            dir.path(),
+            "[user]\n\tname = base\n[includeIf \"hasconfig:user.name:base\"]\n\tpath = inc\n"
+        )?,
+        "base",
+        "other keys aren't supported by git either"
+    );
+    Ok(())
+}
+
+#[test]
+fn included_files_must_not_contain_remote_urls_even_if_they_do_not_match() -> crate::Result {
+    let dir = tempdir()?;
+    write_includes(dir.path())?;
+    for (pattern, path) in [
+        ("https://corp.example/**", "inc-remote"),
+        ("https://corp.example/**", "inc-nested-remote"),
+        ("https://nope/**", "inc-remote"),
+        ("https://other/**", "inc-remote"),
+    ] {
+        let err = user_name(dir.path(), &root_with(pattern, path, ORIGIN)).unwrap_err();
+        assert!(
+            err.to_string().starts_with("Remote URLs must not be configured in"),
+            "{pattern} {path}: {err}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn unconditionally_included_files_may_contain_remote_urls() -> crate::Result {
+    let dir = tempdir()?;
+    write_includes(dir.path())?;
+    let root = "[user]\n\tname = base\n[include]\n\tpath = inc-remote\n[includeIf \"hasconfig:remote.*.url:https://other/**\"]\n\tpath = inc\n";
+    assert_eq!(user_name(dir.path(), root)?, "included");
+    Ok(())
+}
@@ -1,16 +1,22 @@
 //! For using text diffs, please have a look at the [`imara-diff` documentation](https://docs.rs/imara-diff),
 //! maintained by [Pascal Kuthe](https://github.com/pascalkuthe).
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, hash::Hash, path::PathBuf};
 
 use bstr::BString;
 pub use imara_diff::*;
 
+mod patience;
+
 ///
 pub mod pipeline;
 
 ///
 pub mod platform;
 
+///
+pub mod unified_diff;
+pub use unified_diff::UnifiedDiff;
+
 /// Information about the diff performed to detect similarity.
 #[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
 pub struct DiffLineStats {
@@ -28,6 +34,78 @@ pub struct DiffLineStats {
     pub similarity: f32,
 }
 
+/// The algorithm to use when computing a diff, which adds `Patience` to the algorithms provided by [`imara_diff`].
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Algorithm {
+    /// A variation of the `patience` algorithm that uses a histogram to find the least common lines,
+    /// see [`imara_diff::Algorithm::Histogram`].
+    #[default]
+    Histogram,
+    /// Myers `O((N+M)D)` algorithm with heuristics that ensure reasonable runtime, see [`imara_diff::Algorithm::Myers`].
+    Myers,
+    /// Same as `Myers`, but without heuristics to guarantee a minimal edit sequence.
+    MyersMinimal,
+    /// The `patience` diff algorithm as implemented by `git`, which aligns the lines that are unique in both inputs first
+    /// and diffs the ranges between them recursively, falling back to `Myers` if there are no such lines.
+    ///
+    /// Note that `git` applies its indent heuristic by default, which may place a hunk that could be moved up or down
+    /// elsewhere. The heuristic isn't implemented, so hunks match the ones of `git diff --patience --no-indent-heuristic`.
+    Patience,
+}
+
+impl Algorithm {
+    /// Return the `imara-diff` algorithm that implements this one, or `None` if it's implemented by us.
+    pub fn to_imara(self) -> Option<imara_diff::Algorithm> {
+        Some(match self {
+            Algorithm::Histogram => imara_diff::Algorithm::Histogram,
+            Algorithm::Myers => imara_diff::Algorithm::Myers,
+            Algorithm::MyersMinimal => imara_diff::Algorithm::MyersMinimal,
+            Algorithm::Patience => return None,
+        })
+    }
+}
+
+impl From<imara_diff::Algorithm> for Algorithm {
+    fn from(value: imara_diff::Algorithm) -> Self {
+        match value {
+            imara_diff::Algorithm::Histogram => Algorithm::Histogram,
+            imara_diff::Algorithm::Myers => Algorithm::Myers,
+            imara_diff::Algorithm::MyersMinimal => Algorithm::MyersMinimal,
+        }
+    }
+}
+
+/// Compute an edit-script that transforms `input.before` into `input.after` using the given `algorithm`,
+/// passing each change to `sink` in order.
+///
+/// This is like [`imara_diff::diff()`], but supports all of our [algorithms](Algorithm).
+pub fn diff<S: Sink, T: Eq + Hash>(algorithm: Algorithm, input: &intern::InternedInput<T>, sink: S) -> S::Out {
+    diff_with_tokens(
+        algorithm,
+        &input.before,
+        &input.after,
+        input.interner.num_tokens(),
+        sink,
+    )
+}
+
+/// Compute an edit-script that transforms `before` into `after` using the given `algorithm`,
+/// passing each change to `sink` in order. `num_tokens` is the amount of distinct tokens in both inputs.
+///
+/// This is like [`imara_diff::diff_with_tokens()`], but supports all of our [algorithms](Algorithm).
+pub fn diff_with_tokens<S: Sink>(
+    algorithm: Algorithm,
+    before: &[intern::Token],
+    after: &[intern::Token],
+    num_tokens: u32,
+    sink: S,
+) -> S::Out {
+    match algorithm.to_imara() {
+        Some(algorithm) => imara_diff::diff_with_tokens(algorithm, before, after, num_tokens, sink),
+        None => patience::diff(before, after, num_tokens, sink),
+    }
+}
+
 /// A way to classify a resource suitable for diffing.
 #[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum ResourceKind {
@@ -62,6 +140,10 @@ pub struct Driver {
     /// If `Some(false)`, it won't be considered binary, and the its data will not be sampled for the null-byte either.
     /// Leaving it to `None` means binary detection is automatic, and is based on the presence of the `0` byte in the first 8kB of the buffer.
     pub is_binary: Option<bool>,
+    /// The extended regular expressions to find the lines to show as function context in hunk headers,
+    /// as configured with `diff.<driver>.xfuncname`.
+    /// See [`unified_diff::FunctionName::from_pattern()`] for details.
+    pub function_name_pattern: Option<BString>,
 }
 
 /// A conversion pipeline to take an object or path from what's stored in `git` to what can be diffed, while
@@ -0,0 +1,303 @@
+//! An implementation of the `patience` diff algorithm that produces the same changes as `git diff --patience`,
+//! modelled after `xdiff/xpatience.c`.
+use std::{collections::HashMap, ops::Range};
+
+use imara_diff::{intern::Token, Sink};
+
+/// Compute the changes to turn `before` into `after` using the patience algorithm and pass them to `sink`.
+pub(crate) fn diff<S: Sink>(before: &[Token], after: &[Token], num_tokens: u32, mut sink: S) -> S::Out {
+    let mut state = Patience {
+        before,
+        after,
+        num_tokens,
+        changed_before: vec![false; before.len()],
+        changed_after: vec![false; after.len()],
+    };
+    state.run(0..before.len() as u32, 0..after.len() as u32);
+
+    let (mut changed_before, mut changed_after) = (state.changed_before, state.changed_after);
+    compact(&mut changed_before, &mut changed_after, before);
+    compact(&mut changed_after, &mut changed_before, after);
+    let (mut line1, mut line2) = (0, 0);
+    while line1 < before.len() || line2 < after.len() {
+        let is_changed = |changed: &[bool], line: usize| changed.get(line).copied().unwrap_or(false);
+        if is_changed(&changed_before, line1) || is_changed(&changed_after, line2) {
+            let (start1, start2) = (line1, line2);
+            while is_changed(&changed_before, line1) {
+                line1 += 1;
+            }
+            while is_changed(&changed_after, line2) {
+                line2 += 1;
+            }
+            sink.process_change(start1 as u32..line1 as u32, start2 as u32..line2 as u32);
+        } else {
+            line1 += 1;
+            line2 += 1;
+        }
+    }
+    sink.finish()
+}
+
+struct Patience<'a> {
+    before: &'a [Token],
+    after: &'a [Token],
+    num_tokens: u32,
+    changed_before: Vec<bool>,
+    changed_after: Vec<bool>,
+}
+
+/// Where a line that occurs exactly once in the range of `before` can be found in the range of `after`.
+#[derive(Clone, Copy)]
+enum Line2 {
+    Missing,
+    Unique(u32),
+    NotUnique,
+}
+
+impl Patience<'_> {
+    fn run(&mut self, range1: Range<u32>, range2: Range<u32>) {
+        if range1.is_empty() || range2.is_empty() {
+            mark_changed(&mut self.changed_before, range1);
+            mark_changed(&mut self.changed_after, range2);
+            return;
+        }
+
+        // Lines of `before` in order of their first occurrence, along with their position in `after`.
+        let mut lines = Vec::<(u32, Line2)>::new();
+        let mut line_by_token = HashMap::<Token, usize>::new();
+        for line1 in range1.clone() {
+            match line_by_token.get(&self.before[line1 as usize]) {
+                Some(&idx) => lines[idx].1 = Line2::NotUnique,
+                None => {
+                    line_by_token.insert(self.before[line1 as usize], lines.len());
+                    lines.push((line1, Line2::Missing));
+                }
+            }
+        }
+        let mut has_matches = false;
+        for line2 in range2.clone() {
+            if let Some(&idx) = line_by_token.get(&self.after[line2 as usize]) {
+                has_matches = true;
+                let line = &mut lines[idx].1;
+                *line = match line {
+                    Line2::Missing => Line2::Unique(line2),
+                    Line2::Unique(_) | Line2::NotUnique => Line2::NotUnique,
+                };
+            }
+        }
+
+        if !has_matches {
+            mark_changed(&mut self.changed_before, range1);
+            mark_changed(&mut self.changed_after, range2);
+            return;
+        }
+
+        let common = longest_common_sequence(&lines);
+        if common.is_empty() {
+            self.fall_back_to_myers(range1, range2);
+        } else {
+            self.walk_common_sequence(&common, range1, range2);
+        }
+    }
+
+    /// Diff the ranges between the `common` unique lines, after extending them with lines that are equal as well.
+    fn walk_common_sequence(&mut self, common: &[(u32, u32)], range1: Range<u32>, range2: Range<u32>) {
+        let (mut line1, mut line2) = (range1.start, range2.start);
+        let mut idx = 0;
+        loop {
+            let (next1, next2) = match common.get(idx) {
+                Some(&(mut next1, mut next2)) => {
+                    while next1 > line1 && next2 > line2 && self.is_match(next1 - 1, next2 - 1) {
+                        next1 -= 1;
+                        next2 -= 1;
+                    }
+                    (next1, next2)
+                }
+                None => (range1.end, range2.end),
+            };
+            while line1 < next1 && line2 < next2 && self.is_match(line1, line2) {
+                line1 += 1;
+                line2 += 1;
+            }
+
+            if next1 > line1 || next2 > line2 {
+                self.run(line1..next1, line2..next2);
+            }
+            if idx == common.len() {
+                return;
+            }
+            while common
+                .get(idx + 1)
+                .map_or(false, |&(a, b)| a == common[idx].0 + 1 && b == common[idx].1 + 1)
+            {
+                idx += 1;
+            }
+            line1 = common[idx].0 + 1;
+            line2 = common[idx].1 + 1;
+            idx += 1;
+        }
+    }
+
+    fn fall_back_to_myers(&mut self, range1: Range<u32>, range2: Range<u32>) {
+        let (changed_before, changed_after) = (&mut self.changed_before, &mut self.changed_after);
+        let (offset1, offset2) = (range1.start, range2.start);
+        imara_diff::diff_with_tokens(
+            imara_diff::Algorithm::Myers,
+            &self.before[range1.start as usize..range1.end as usize],
+            &self.after[range2.start as usize..range2.end as usize],
+            self.num_tokens,
+            |before: Range<u32>, after: Range<u32>| {
+                mark_changed(changed_before, before.start + offset1..before.end + offset1);
+                mark_changed(changed_after, after.start + offset2..after.end + offset2);
+            },
+        );
+    }
+
+    fn is_match(&self, line1: u32, line2: u32) -> bool {
+        self.before[line1 as usize] == self.after[line2 as usize]
+    }
+}
+
+/// Find the longest sequence of lines that are unique in both ranges and in the same order in both of them
+/// using patience sorting, and return their positions in `before` and `after`.
+fn longest_common_sequence(lines: &[(u32, Line2)]) -> Vec<(u32, u32)> {
+    // The previous element in the sequence of each candidate, by index into `candidates`.
+    let mut candidates = Vec::<(u32, u32, Option<usize>)>::new();
+    // The index of the candidate at the end of each sequence of length `idx + 1`.
+    let mut sequence = Vec::<usize>::new();
+    for &(line1, line2) in lines {
+        let Line2::Unique(line2) = line2 else {
+            continue;
+        };
+        let pos = sequence.partition_point(|&idx| candidates[idx].1 <= line2);
+        let previous = pos.checked_sub(1).map(|pos| sequence[pos]);
+        candidates.push((line1, line2, previous));
+        let idx = candidates.len() - 1;
+        if pos == sequence.len() {
+            sequence.push(idx);
+        } else {
+            sequence[pos] = idx;
+        }
+    }
+
+    let mut common = Vec::with_capacity(sequence.len());
+    let mut next = sequence.last().copied();
+    while let Some(idx) = next {
+        let (line1, line2, previous) = candidates[idx];
+        common.push((line1, line2));
+        next = previous;
+    }
+    common.reverse();
+    common
+}
+
+/// Move groups of changes in `changed` as far down as possible while keeping the diff the same, merging adjacent groups
+/// along the way, and align them with changes in `changed_other` if possible. This is what `git` does without
+/// the indent heuristic to produce consistent and readable hunks.
+fn compact(changed: &mut [bool], changed_other: &mut [bool], tokens: &[Token]) {
+    let mut group = Group::first(changed);
+    let mut group_other = Group::first(changed_other);
+    loop {
+        if !group.is_empty() {
+            let (mut earliest_end, mut end_matching_other);
+            loop {
+                let len = group.end - group.start;
+                end_matching_other = None;
+                while group.slide_up(changed, tokens) {
+                    assert!(group_other.previous(changed_other), "groups are always in sync");
+                }
+                earliest_end = group.end;
+                if !group_other.is_empty() {
+                    end_matching_other = Some(group.end);
+                }
+                while group.slide_down(changed, tokens) {
+                    assert!(group_other.next(changed_other), "groups are always in sync");
+                    if !group_other.is_empty() {
+                        end_matching_other = Some(group.end);
+                    }
+                }
+                if len == group.end - group.start {
+                    break;
+                }
+            }
+            if group.end != earliest_end && end_matching_other.is_some() {
+                while group_other.is_empty() {
+                    assert!(group.slide_up(changed, tokens), "we could slide down here before");
+                    assert!(group_other.previous(changed_other), "groups are always in sync");
+                }
+            }
+        }
+        if !group.next(changed) {
+            break;
+        }
+        assert!(group_other.next(changed_other), "groups are always in sync");
+    }
+}
+
+/// A possibly empty group of changed lines, which is always followed by an unchanged line or the end of input.
+struct Group {
+    start: usize,
+    end: usize,
+}
+
+impl Group {
+    fn first(changed: &[bool]) -> Self {
+        Group {
+            start: 0,
+            end: changed.iter().take_while(|changed| **changed).count(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    fn next(&mut self, changed: &[bool]) -> bool {
+        if self.end == changed.len() {
+            return false;
+        }
+        self.start = self.end + 1;
+        self.end = self.start + changed[self.start..].iter().take_while(|changed| **changed).count();
+        true
+    }
+
+    fn previous(&mut self, changed: &[bool]) -> bool {
+        if self.start == 0 {
+            return false;
+        }
+        self.end = self.start - 1;
+        self.start = self.end - changed[..self.end].iter().rev().take_while(|changed| **changed).count();
+        true
+    }
+
+    fn slide_down(&mut self, changed: &mut [bool], tokens: &[Token]) -> bool {
+        if self.end == changed.len() || tokens[self.start] != tokens[self.end] {
+            return false;
+        }
+        changed[self.start] = false;
+        changed[self.end] = true;
+        self.start += 1;
+        self.end += 1 + changed[self.end + 1..].iter().take_while(|changed| **changed).count();
+        true
+    }
+
+    fn slide_up(&mut self, changed: &mut [bool], tokens: &[Token]) -> bool {
+        if self.start == 0 || tokens[self.start - 1] != tokens[self.end - 1] {
+            return false;
+        }
+        self.start -= 1;
+        self.end -= 1;
+        changed[self.start] = true;
+        changed[self.end] = false;
+        self.start -= changed[..self.start]
+            .iter()
+            .rev()
+            .take_while(|changed| **changed)
+            .count();
+        true
+    }
+}
+
+fn mark_changed(changed: &mut [bool], range: Range<u32>) {
+    changed[range.start as usize..range.end as usize].fill(true);
+}
@@ -0,0 +1,97 @@
+use bstr::BStr;
+
+/// The error returned by [`FunctionName::from_pattern()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The function name pattern '{pattern}' isn't valid UTF-8")]
+    Utf8 { pattern: bstr::BString },
+    #[error("The function name pattern '{pattern}' could not be compiled")]
+    Regex {
+        pattern: bstr::BString,
+        source: regex::Error,
+    },
+}
+
+/// The maximum amount of bytes of a line that is shown as function context, just like in `git`.
+const MAX_LEN: usize = 80;
+
+/// A way to find lines that start a function, to show them as context in hunk headers.
+#[derive(Debug, Clone)]
+pub enum FunctionName {
+    /// Lines that start with a letter, `_` or `$`, which is what `git` uses if no pattern is configured.
+    Default,
+    /// Lines that match the first of these regular expressions that matches at all, unless it's negated, as configured
+    /// with `diff.<driver>.xfuncname`.
+    ///
+    /// If the expression has a capture group, only its first group is shown.
+    Patterns(Vec<Pattern>),
+}
+
+/// A regular expression for use in [`FunctionName::Patterns`].
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    /// The expression to match lines against, without their line terminator.
+    pub regex: regex::bytes::Regex,
+    /// If `true`, matching lines are not considered function lines.
+    pub is_negated: bool,
+}
+
+impl FunctionName {
+    /// Parse `pattern` with one extended regular expression per line, where those prefixed with `!` are negated,
+    /// just like the value of `diff.<driver>.xfuncname`.
+    pub fn from_pattern(pattern: &BStr) -> Result<Self, Error> {
+        pattern
+            .split(|b| *b == b'\n')
+            .map(|line| {
+                let (line, is_negated) = match line.strip_prefix(b"!") {
+                    Some(line) => (line, true),
+                    None => (line, false),
+                };
+                let line = std::str::from_utf8(line).map_err(|_| Error::Utf8 { pattern: line.into() })?;
+                let regex = regex::bytes::RegexBuilder::new(line)
+                    .unicode(false)
+                    .build()
+                    .map_err(|source| Error::Regex {
+                        pattern: line.into(),
+                        source,
+                    })?;
+                Ok(Pattern { regex, is_negated })
+            })
+            .collect::<Result<_, _>>()
+            .map(FunctionName::Patterns)
+    }
+
+    /// Return the part of `line` to show as function context if it's a function line, or `None` otherwise.
+    ///
+    /// `line` may include its line terminator, which is never part of the returned function context.
+    pub fn find<'a>(&self, line: &'a [u8]) -> Option<&'a [u8]> {
+        let line = match self {
+            FunctionName::Default => match line.first() {
+                Some(b) if b.is_ascii_alphabetic() || *b == b'_' || *b == b'$' => line,
+                _ => return None,
+            },
+            FunctionName::Patterns(patterns) => {
+                let line = line
+                    .strip_suffix(b"\r\n")
+                    .or_else(|| line.strip_suffix(b"\n"))
+                    .unwrap_or(line);
+                let pattern = patterns.iter().find(|pattern| pattern.regex.is_match(line))?;
+                if pattern.is_negated {
+                    return None;
+                }
+                let captures = pattern.regex.captures(line)?;
+                let found = captures.get(1).or_else(|| captures.get(0))?;
+                &line[found.range()]
+            }
+        };
+        let mut line = &line[..line.len().min(MAX_LEN)];
+        while let Some((last, rest)) = line.split_last() {
+            if !last.is_ascii_whitespace() {
+                break;
+            }
+            line = rest;
+        }
+        Some(line)
+    }
+}
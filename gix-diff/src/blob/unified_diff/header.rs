@@ -0,0 +1,110 @@
+use std::io::Write;
+
+use bstr::BStr;
+
+/// One side of a [`Header`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Location<'a> {
+    /// The path of the resource, relative to the root of the repository.
+    pub path: &'a BStr,
+    /// The id of the resource.
+    pub id: &'a gix_hash::oid,
+    /// The kind of the resource.
+    pub mode: gix_object::tree::EntryKind,
+}
+
+/// Information about a resource that was rewritten, which is either a rename or a copy.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Rewrite {
+    /// How similar the source and the destination are, from 0 to 100 percent.
+    pub similarity: u8,
+    /// If `true`, the source still exists and the destination is a copy, otherwise the source was renamed.
+    pub is_copy: bool,
+}
+
+/// The header of the diff of a single resource, as `git diff` writes it before the hunks of its changes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Header<'a> {
+    /// The previous version of the resource, or `None` if it was added.
+    pub old: Option<Location<'a>>,
+    /// The current version of the resource, or `None` if it was deleted.
+    pub new: Option<Location<'a>>,
+    /// Set if `new` was renamed or copied from `old`.
+    pub rewrite: Option<Rewrite>,
+}
+
+impl Header<'_> {
+    /// Write the `diff --git` line along with lines about changed modes, rewrites and ids to `out`, using `hex_len`
+    /// characters of each id.
+    ///
+    /// Note that paths are written as is, without quoting them.
+    pub fn write_to(&self, mut out: impl Write, hex_len: usize) -> std::io::Result<()> {
+        let Some((old_path, new_path)) = self.paths() else {
+            return Ok(());
+        };
+        writeln!(out, "diff --git a/{old_path} b/{new_path}")?;
+        match (self.old, self.new) {
+            (None, Some(new)) => writeln!(out, "new file mode {}", new.mode.as_octal_str())?,
+            (Some(old), None) => writeln!(out, "deleted file mode {}", old.mode.as_octal_str())?,
+            (Some(old), Some(new)) if old.mode != new.mode => {
+                writeln!(out, "old mode {}", old.mode.as_octal_str())?;
+                writeln!(out, "new mode {}", new.mode.as_octal_str())?;
+            }
+            _ => {}
+        }
+        if let Some(rewrite) = self.rewrite {
+            let kind = if rewrite.is_copy { "copy" } else { "rename" };
+            writeln!(out, "similarity index {}%", rewrite.similarity)?;
+            writeln!(out, "{kind} from {old_path}")?;
+            writeln!(out, "{kind} to {new_path}")?;
+        }
+
+        let old_id = self.old.map(|old| old.id.to_hex_with_len(hex_len).to_string());
+        let new_id = self.new.map(|new| new.id.to_hex_with_len(hex_len).to_string());
+        if old_id != new_id {
+            let null = || "0".repeat(old_id.as_ref().or(new_id.as_ref()).map_or(hex_len, String::len));
+            write!(
+                out,
+                "index {}..{}",
+                old_id.clone().unwrap_or_else(null),
+                new_id.clone().unwrap_or_else(null)
+            )?;
+            match (self.old, self.new) {
+                (Some(old), Some(new)) if old.mode == new.mode => writeln!(out, " {}", old.mode.as_octal_str())?,
+                _ => writeln!(out)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the `---` and `+++` lines that introduce the hunks of a textual diff to `out`.
+    pub fn write_paths_to(&self, mut out: impl Write) -> std::io::Result<()> {
+        writeln!(out, "--- {}", Self::display_path("a/", self.old))?;
+        writeln!(out, "+++ {}", Self::display_path("b/", self.new))
+    }
+
+    /// Write the line that is shown instead of hunks if `old` or `new` are binary to `out`.
+    pub fn write_binary_marker_to(&self, mut out: impl Write) -> std::io::Result<()> {
+        writeln!(
+            out,
+            "Binary files {} and {} differ",
+            Self::display_path("a/", self.old),
+            Self::display_path("b/", self.new)
+        )
+    }
+
+    fn paths(&self) -> Option<(&BStr, &BStr)> {
+        match (self.old, self.new) {
+            (Some(old), Some(new)) => Some((old.path, new.path)),
+            (Some(one), None) | (None, Some(one)) => Some((one.path, one.path)),
+            (None, None) => None,
+        }
+    }
+
+    fn display_path(prefix: &str, location: Option<Location<'_>>) -> String {
+        match location {
+            Some(location) => format!("{prefix}{}", location.path),
+            None => "/dev/null".into(),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::{io::Write, ops::Range};
+
+use imara_diff::{
+    intern::{InternedInput, Interner, Token},
+    Sink,
+};
+
+///
+pub mod function_name;
+pub use function_name::FunctionName;
+
+///
+pub mod header;
+pub use header::Header;
+
+///
+pub mod stat;
+
+/// The amount of unchanged lines to show around changes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ContextSize {
+    /// The amount of unchanged lines before and after each change. Changes that are closer than twice this amount
+    /// are shown in the same hunk.
+    pub symmetrical: u32,
+}
+
+impl Default for ContextSize {
+    /// Three lines of context, just like `git diff` uses by default.
+    fn default() -> Self {
+        ContextSize { symmetrical: 3 }
+    }
+}
+
+impl ContextSize {
+    /// Show `lines` of context around each change.
+    pub fn symmetrical(lines: u32) -> Self {
+        ContextSize { symmetrical: lines }
+    }
+}
+
+/// A [`Sink`] that writes changes as hunks of a unified diff, the same way as `git diff` does.
+///
+/// This includes `@@` hunk headers with the function context of each hunk and the `\ No newline at end of file` marker,
+/// but no [file header](Header) which has to be written separately.
+///
+/// The lines to write are taken from an [`InternedInput`] of lines including their terminators, like the one created by
+/// [`Outcome::interned_input()`](crate::blob::platform::prepare_diff::Outcome::interned_input()).
+pub struct UnifiedDiff<'a, W> {
+    before: &'a [Token],
+    after: &'a [Token],
+    interner: &'a Interner<&'a [u8]>,
+    out: W,
+    context_size: ContextSize,
+    function_name: Option<&'a FunctionName>,
+
+    /// The changes that will be written as part of the next hunk.
+    changes: Vec<(Range<u32>, Range<u32>)>,
+    /// The function context of the most recent hunk.
+    function_line: Vec<u8>,
+    /// The first line in `before` that wasn't yet searched for function context.
+    function_line_search_start: u32,
+    /// The amount of hunks we wrote so far.
+    num_hunks: usize,
+    err: Option<std::io::Error>,
+}
+
+impl<'a, W: Write> UnifiedDiff<'a, W> {
+    /// Create a new instance to write hunks for the changes of `input` to `out`, with `context_size` unchanged lines
+    /// around each change.
+    ///
+    /// If `function_name` is set, it's used to find the function context to show in each hunk header, similar to
+    /// `diff.<driver>.xfuncname`, or to [the default](FunctionName::Default) that `git` uses.
+    pub fn new(
+        input: &'a InternedInput<&'a [u8]>,
+        out: W,
+        context_size: ContextSize,
+        function_name: Option<&'a FunctionName>,
+    ) -> Self {
+        UnifiedDiff {
+            before: &input.before,
+            after: &input.after,
+            interner: &input.interner,
+            out,
+            context_size,
+            function_name,
+            changes: Vec::new(),
+            function_line: Vec::new(),
+            function_line_search_start: 0,
+            num_hunks: 0,
+            err: None,
+        }
+    }
+
+    fn write_hunk(&mut self) -> std::io::Result<()> {
+        let (Some((first_before, first_after)), Some((last_before, last_after))) =
+            (self.changes.first().cloned(), self.changes.last().cloned())
+        else {
+            return Ok(());
+        };
+        let context = self.context_size.symmetrical;
+        let start_before = first_before.start.saturating_sub(context);
+        let start_after = first_after.start.saturating_sub(context);
+        let end_before = (last_before.end + context).min(self.before.len() as u32);
+        let end_after = (last_after.end + context).min(self.after.len() as u32);
+
+        self.update_function_line(start_before);
+        write_range_header(&mut self.out, b"@@ -", start_before, end_before - start_before)?;
+        write_range_header(&mut self.out, b" +", start_after, end_after - start_after)?;
+        self.out.write_all(b" @@")?;
+        if !self.function_line.is_empty() {
+            self.out.write_all(b" ")?;
+            self.out.write_all(&self.function_line)?;
+        }
+        self.out.write_all(b"\n")?;
+
+        let mut line_before = start_before;
+        for (before, after) in std::mem::take(&mut self.changes) {
+            self.write_lines(b' ', self.before, line_before..before.start)?;
+            self.write_lines(b'-', self.before, before.clone())?;
+            self.write_lines(b'+', self.after, after)?;
+            line_before = before.end;
+        }
+        self.write_lines(b' ', self.before, line_before..end_before)?;
+        self.num_hunks += 1;
+        Ok(())
+    }
+
+    fn write_lines(&mut self, prefix: u8, tokens: &[Token], range: Range<u32>) -> std::io::Result<()> {
+        for token in &tokens[range.start as usize..range.end as usize] {
+            let line = self.interner[*token];
+            self.out.write_all(&[prefix])?;
+            self.out.write_all(line)?;
+            if !line.ends_with(b"\n") {
+                self.out.write_all(b"\n\\ No newline at end of file\n")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the function context for a hunk starting at `start_before` by searching backwards from the line above it.
+    /// If nothing is found, the function context of the previous hunk is used.
+    fn update_function_line(&mut self, start_before: u32) {
+        let Some(function_name) = self.function_name else {
+            return;
+        };
+        let search_start = self.function_line_search_start;
+        self.function_line_search_start = start_before;
+        let interner = self.interner;
+        if let Some(function_line) = self.before[search_start.min(start_before) as usize..start_before as usize]
+            .iter()
+            .rev()
+            .find_map(|token| function_name.find(interner[*token]))
+        {
+            self.function_line.clear();
+            self.function_line.extend_from_slice(function_line);
+        }
+    }
+}
+
+fn write_range_header(out: &mut impl Write, prefix: &[u8], start: u32, len: u32) -> std::io::Result<()> {
+    out.write_all(prefix)?;
+    // Empty ranges refer to the line before them, with `0` being the position before the first line.
+    let start = if len == 0 { start } else { start + 1 };
+    if len == 1 {
+        write!(out, "{start}")
+    } else {
+        write!(out, "{start},{len}")
+    }
+}
+
+impl<W: Write> Sink for UnifiedDiff<'_, W> {
+    /// The writer after all hunks were written to it, along with the amount of written hunks.
+    type Out = std::io::Result<(W, usize)>;
+
+    fn process_change(&mut self, before: Range<u32>, after: Range<u32>) {
+        if self.err.is_some() {
+            return;
+        }
+        if let Some((last_before, _)) = self.changes.last() {
+            if before.start - last_before.end > 2 * self.context_size.symmetrical {
+                if let Err(err) = self.write_hunk() {
+                    self.err = Some(err);
+                    return;
+                }
+            }
+        }
+        self.changes.push((before, after));
+    }
+
+    fn finish(mut self) -> Self::Out {
+        if let Some(err) = self.err.take() {
+            return Err(err);
+        }
+        self.write_hunk()?;
+        Ok((self.out, self.num_hunks))
+    }
+}
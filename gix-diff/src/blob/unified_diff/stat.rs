@@ -0,0 +1,220 @@
+use std::io::Write;
+
+use bstr::{BStr, BString, ByteSlice};
+
+/// How a single resource changed, for use in [`write_stat()`] and [`write_numstat()`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Change {
+    /// The amount of lines that were added and removed in a textual diff.
+    Lines {
+        /// The amount of added lines.
+        insertions: u32,
+        /// The amount of removed lines.
+        removals: u32,
+    },
+    /// The sizes of a resource that is considered binary.
+    Binary {
+        /// The size of the previous version in bytes, or 0 if it was added.
+        old_size: u64,
+        /// The size of the current version in bytes, or 0 if it was deleted.
+        new_size: u64,
+    },
+}
+
+/// A resource along with its changes, as summarized by [`write_stat()`] and [`write_numstat()`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Entry {
+    /// The name to display for the resource, which is typically its path, or what [`rename_path()`] returns for rewrites.
+    pub name: BString,
+    /// How the resource changed.
+    pub change: Change,
+}
+
+/// Write a summary of all `entries` to `out` just like `git diff --stat`, with each line being at most `width`
+/// characters long, which is `80` in `git` unless the output is a terminal.
+pub fn write_stat(mut out: impl Write, entries: &[Entry], width: usize) -> std::io::Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let (mut max_name_len, mut max_change, mut number_width, mut bin_width) = (0, 0, 0, 0);
+    for entry in entries {
+        max_name_len = max_name_len.max(display_width(entry.name.as_bstr()));
+        match entry.change {
+            Change::Lines { insertions, removals } => {
+                max_change = max_change.max(insertions as usize + removals as usize)
+            }
+            Change::Binary { old_size, new_size } => {
+                // "Bin XXX -> YYY bytes"
+                bin_width = bin_width.max(14 + decimal_width(old_size) + decimal_width(new_size));
+                // Align the change counts with "Bin".
+                number_width = 3;
+            }
+        }
+    }
+
+    let number_width = number_width.max(decimal_width(max_change as u64));
+    let width = width.max(16 + 6 + number_width);
+    let mut graph_width = if max_change + 4 > bin_width {
+        max_change
+    } else {
+        bin_width - 4
+    };
+    let mut name_width = max_name_len;
+    if name_width + number_width + 6 + graph_width > width {
+        let max_graph_width = (width * 3 / 8).saturating_sub(number_width + 6);
+        if graph_width > max_graph_width {
+            graph_width = max_graph_width.max(6);
+        }
+        if name_width > width - number_width - 6 - graph_width {
+            name_width = width - number_width - 6 - graph_width;
+        } else {
+            graph_width = width - number_width - 6 - name_width;
+        }
+    }
+
+    let (mut total_insertions, mut total_removals) = (0, 0);
+    for entry in entries {
+        let (prefix, name) = truncate_name(entry.name.as_bstr(), name_width);
+        let padding = name_width.saturating_sub(prefix.len() + display_width(name));
+        write!(out, " {prefix}{name}{:padding$} | ", "")?;
+        match entry.change {
+            Change::Binary { old_size, new_size } => {
+                write!(out, "{:>number_width$}", "Bin")?;
+                if old_size == 0 && new_size == 0 {
+                    writeln!(out)?;
+                } else {
+                    writeln!(out, " {old_size} -> {new_size} bytes")?;
+                }
+            }
+            Change::Lines { insertions, removals } => {
+                total_insertions += insertions as usize;
+                total_removals += removals as usize;
+                let (mut plus, mut minus) = (insertions as usize, removals as usize);
+                if graph_width <= max_change {
+                    let mut total = scale_linear(plus + minus, graph_width, max_change);
+                    if total < 2 && plus != 0 && minus != 0 {
+                        total = 2;
+                    }
+                    if plus < minus {
+                        plus = scale_linear(plus, graph_width, max_change);
+                        minus = total - plus;
+                    } else {
+                        minus = scale_linear(minus, graph_width, max_change);
+                        plus = total - minus;
+                    }
+                }
+                let changes = insertions as usize + removals as usize;
+                write!(out, "{changes:>number_width$}")?;
+                if changes != 0 {
+                    write!(out, " ")?;
+                }
+                writeln!(out, "{}{}", "+".repeat(plus), "-".repeat(minus))?;
+            }
+        }
+    }
+    write_summary(out, entries.len(), total_insertions, total_removals)
+}
+
+/// Write the amount of added and removed lines of each of the `entries` to `out` just like `git diff --numstat`.
+pub fn write_numstat(mut out: impl Write, entries: &[Entry]) -> std::io::Result<()> {
+    for entry in entries {
+        match entry.change {
+            Change::Lines { insertions, removals } => writeln!(out, "{insertions}\t{removals}\t{}", entry.name)?,
+            Change::Binary { .. } => writeln!(out, "-\t-\t{}", entry.name)?,
+        }
+    }
+    Ok(())
+}
+
+/// Produce the name `git` displays for a resource that was rewritten from `old` to `new`, like `dir/{old => new}.rs`.
+pub fn rename_path(old: &BStr, new: &BStr) -> BString {
+    let (old, new) = (old.as_bytes(), new.as_bytes());
+    let prefix_len = old
+        .iter()
+        .zip(new)
+        .take_while(|(a, b)| a == b)
+        .enumerate()
+        .filter(|(_, (a, _))| **a == b'/')
+        .last()
+        .map_or(0, |(pos, _)| pos + 1);
+
+    // If there is a common prefix, it ends with a slash which may also be the start of the common suffix.
+    let min_len = prefix_len.saturating_sub(1);
+    let mut suffix_len = 0;
+    let (mut old_end, mut new_end) = (old.len(), new.len());
+    while old_end > min_len && new_end > min_len && old[old_end - 1] == new[new_end - 1] {
+        old_end -= 1;
+        new_end -= 1;
+        if old[old_end] == b'/' {
+            suffix_len = old.len() - old_end;
+        }
+    }
+
+    let old_mid = &old[prefix_len..old.len().saturating_sub(suffix_len).max(prefix_len)];
+    let new_mid = &new[prefix_len..new.len().saturating_sub(suffix_len).max(prefix_len)];
+    let mut out = BString::default();
+    let is_compressed = prefix_len + suffix_len != 0;
+    if is_compressed {
+        out.extend_from_slice(&old[..prefix_len]);
+        out.push(b'{');
+    }
+    out.extend_from_slice(old_mid);
+    out.extend_from_slice(b" => ");
+    out.extend_from_slice(new_mid);
+    if is_compressed {
+        out.push(b'}');
+        out.extend_from_slice(&old[old.len() - suffix_len..]);
+    }
+    out
+}
+
+fn write_summary(mut out: impl Write, files: usize, insertions: usize, removals: usize) -> std::io::Result<()> {
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    write!(out, " {files} file{} changed", plural(files))?;
+    if insertions != 0 || removals == 0 {
+        write!(out, ", {insertions} insertion{}(+)", plural(insertions))?;
+    }
+    if removals != 0 || insertions == 0 {
+        write!(out, ", {removals} deletion{}(-)", plural(removals))?;
+    }
+    writeln!(out)
+}
+
+/// Make sure that at least one `+` or `-` is shown for any change by scaling as if `width` was one column shorter,
+/// and adding 1 to the result.
+fn scale_linear(count: usize, width: usize, max_change: usize) -> usize {
+    if count == 0 {
+        return 0;
+    }
+    1 + (count * (width - 1) / max_change)
+}
+
+/// Shorten `name` from the front to fit into `width` and return the prefix to show before it to indicate that.
+fn truncate_name(name: &BStr, width: usize) -> (&'static str, &BStr) {
+    if display_width(name) <= width {
+        return ("", name);
+    }
+    let width = width.saturating_sub(3);
+    let mut name = name;
+    while display_width(name) > width {
+        let (_, end, _) = name.char_indices().next().expect("non-empty as width is smaller");
+        name = name[end..].as_bstr();
+    }
+    if let Some(slash) = name.find_byte(b'/') {
+        name = name[slash..].as_bstr();
+    }
+    ("...", name)
+}
+
+fn display_width(name: &BStr) -> usize {
+    name.chars().count()
+}
+
+fn decimal_width(mut n: u64) -> usize {
+    let mut width = 1;
+    while n >= 10 {
+        n /= 10;
+        width += 1;
+    }
+    width
+}
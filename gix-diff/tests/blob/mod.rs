@@ -1,2 +1,4 @@
+mod patience;
 pub(crate) mod pipeline;
 mod platform;
+mod unified_diff;
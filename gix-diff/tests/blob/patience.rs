@@ -0,0 +1,88 @@
+use std::ops::Range;
+
+use gix_diff::blob::{intern::InternedInput, sources::lines_with_terminator, Algorithm};
+
+/// Cases in which `git` places hunks differently by default, as it applies the indent heuristic.
+const SLID_BY_INDENT_HEURISTIC: &[&str] = &["indent-heuristic"];
+
+#[test]
+fn hunks_match_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_diff_patience.sh")?;
+    for name in [
+        "functions",
+        "moved",
+        "repeated",
+        "interleaved",
+        "crossing",
+        "disjoint",
+        "empty",
+        "to-empty",
+        "no-newline",
+        "numbers",
+        "numbers-rotated",
+        "indent-heuristic",
+    ] {
+        let before = std::fs::read_to_string(root.join(format!("{name}.before")))?;
+        let after = std::fs::read_to_string(root.join(format!("{name}.after")))?;
+        let baseline = |suffix: &str| -> crate::Result<Vec<_>> {
+            Ok(std::fs::read_to_string(root.join(format!("{name}{suffix}.baseline")))?
+                .lines()
+                .map(parse_hunk_header)
+                .collect())
+        };
+
+        let input = InternedInput::new(lines_with_terminator(&before), lines_with_terminator(&after));
+        let mut actual = Vec::new();
+        gix_diff::blob::diff(Algorithm::Patience, &input, |before: Range<u32>, after: Range<u32>| {
+            actual.push((before, after))
+        });
+        assert_eq!(
+            actual,
+            baseline(".no-indent-heuristic")?,
+            "{name}: hunks must be the same as the ones produced by git without indent heuristic"
+        );
+
+        let expected = baseline("")?;
+        if SLID_BY_INDENT_HEURISTIC.contains(&name) {
+            assert_ne!(actual, expected, "{name}: git's default places hunks differently");
+            let sizes = |hunks: &[(Range<u32>, Range<u32>)]| -> Vec<_> {
+                hunks.iter().map(|(before, after)| (before.len(), after.len())).collect()
+            };
+            assert_eq!(
+                sizes(&actual),
+                sizes(&expected),
+                "{name}: the indent heuristic only slides hunks"
+            );
+        } else {
+            assert_eq!(actual, expected, "{name}: hunks must be the same as the ones produced by git");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn identical_inputs_have_no_changes() {
+    let input = InternedInput::new("a\nb\na\n", "a\nb\na\n");
+    let mut changes = 0;
+    gix_diff::blob::diff(Algorithm::Patience, &input, |_: Range<u32>, _: Range<u32>| {
+        changes += 1;
+    });
+    assert_eq!(changes, 0);
+}
+
+/// Turn a hunk header like `@@ -1,2 +3 @@` into zero-based line ranges.
+fn parse_hunk_header(line: &str) -> (Range<u32>, Range<u32>) {
+    let mut tokens = line.split(' ').skip(1);
+    let mut range = |prefix: char| {
+        let token = tokens.next().expect("range present");
+        let token = token.strip_prefix(prefix).expect("valid prefix");
+        let (start, len) = token.split_once(',').unwrap_or((token, "1"));
+        let (start, len): (u32, u32) = (start.parse().expect("number"), len.parse().expect("number"));
+        if len == 0 {
+            start..start
+        } else {
+            start - 1..start - 1 + len
+        }
+    };
+    (range('-'), range('+'))
+}
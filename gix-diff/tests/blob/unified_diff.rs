@@ -0,0 +1,238 @@
+use std::{collections::HashMap, path::Path};
+
+use gix_diff::blob::{
+    intern::InternedInput,
+    unified_diff::{
+        header::{Location, Rewrite},
+        stat, ContextSize, FunctionName, Header,
+    },
+    Algorithm, UnifiedDiff,
+};
+use gix_object::{bstr::ByteSlice, tree::EntryKind};
+
+#[test]
+fn hunks_match_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_diff_unified.sh")?.join("hunks");
+    let custom = FunctionName::from_pattern("!^fn secret\n^[a-z]+ ([a-z]+)".into())?;
+    for name in [
+        "numbers",
+        "no-newline",
+        "add-newline",
+        "empty",
+        "functions",
+        "functions-custom",
+        "perl-custom",
+        "long",
+        "crlf",
+    ] {
+        let before = std::fs::read(root.join(format!("{name}.before")))?;
+        let after = std::fs::read(root.join(format!("{name}.after")))?;
+        let function_name = if name.ends_with("-custom") {
+            &custom
+        } else {
+            &FunctionName::Default
+        };
+        for context in [0, 1, 3] {
+            let expected = std::fs::read(root.join(format!("{name}.{context}.expected")))?;
+            let actual = unified_diff(&before, &after, ContextSize::symmetrical(context), Some(function_name))?;
+            assert_eq!(
+                actual.as_bstr(),
+                expected.as_bstr(),
+                "{name} with {context} lines of context: the output must be the same as the one of git"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn without_function_names_hunk_headers_have_no_context() -> crate::Result {
+    let input = b"fn a() {\n1\n2\n3\n4\n}\n";
+    let actual = unified_diff(
+        input,
+        b"fn a() {\n1\n2\n3\nfour\n}\n",
+        ContextSize::symmetrical(1),
+        None,
+    )?;
+    assert_eq!(actual.as_bstr(), "@@ -4,3 +4,3 @@\n 3\n-4\n+four\n }\n");
+    Ok(())
+}
+
+#[test]
+fn invalid_function_name_patterns_are_an_error() {
+    assert!(FunctionName::from_pattern("^(unclosed".into()).is_err());
+    assert!(FunctionName::from_pattern("!^fn secret\n[".into()).is_err());
+}
+
+#[test]
+fn headers_and_summaries_match_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only_standalone("make_diff_unified.sh")?;
+    let db = gix_odb::at(root.join("repo").join(".git").join("objects"))?;
+    let before = read_tree(&root.join("repo.before.tree"))?;
+    let after = read_tree(&root.join("repo.after.tree"))?;
+    let (before, after) = (&before, &after);
+    let rename = |similarity| {
+        Some(Rewrite {
+            similarity,
+            is_copy: false,
+        })
+    };
+
+    let mut out = Vec::new();
+    let mut entries = Vec::new();
+    for (old, new, rewrite) in [
+        (None, Some(location(after, "added")), None),
+        (Some(location(before, "binary")), Some(location(after, "binary")), None),
+        (Some(location(before, "deleted")), None, None),
+        (
+            Some(location(before, "dir/renamed-modified")),
+            Some(location(after, "dir/renamed-and-modified")),
+            rename(95),
+        ),
+        (
+            Some(location(before, "executable")),
+            Some(location(after, "executable")),
+            None,
+        ),
+        (
+            Some(location(before, "renamed-identical")),
+            Some(location(after, "identical-after-rename")),
+            rename(100),
+        ),
+        (
+            Some(location(before, "mode-and-content")),
+            Some(location(after, "mode-and-content")),
+            None,
+        ),
+        (
+            Some(location(before, "modified")),
+            Some(location(after, "modified")),
+            None,
+        ),
+    ] {
+        let header = Header { old, new, rewrite };
+        header.write_to(&mut out, 7)?;
+
+        let old_data = old.map(|old| blob(&db, old.id)).transpose()?.unwrap_or_default();
+        let new_data = new.map(|new| blob(&db, new.id)).transpose()?.unwrap_or_default();
+        let name = match (old, new) {
+            (Some(old), Some(new)) if old.path != new.path => stat::rename_path(old.path, new.path),
+            (Some(location), _) | (None, Some(location)) => location.path.to_owned(),
+            (None, None) => unreachable!("one side is always set"),
+        };
+        let change = if old_data.contains(&0) || new_data.contains(&0) {
+            header.write_binary_marker_to(&mut out)?;
+            stat::Change::Binary {
+                old_size: old_data.len() as u64,
+                new_size: new_data.len() as u64,
+            }
+        } else {
+            let input = InternedInput::new(
+                gix_diff::blob::sources::byte_lines_with_terminator(&old_data),
+                gix_diff::blob::sources::byte_lines_with_terminator(&new_data),
+            );
+            let counts = gix_diff::blob::diff(Algorithm::Myers, &input, gix_diff::blob::sink::Counter::default());
+            let hunks = unified_diff(
+                &old_data,
+                &new_data,
+                ContextSize::default(),
+                Some(&FunctionName::Default),
+            )?;
+            if !hunks.is_empty() {
+                header.write_paths_to(&mut out)?;
+                out.extend_from_slice(&hunks);
+            }
+            stat::Change::Lines {
+                insertions: counts.insertions,
+                removals: counts.removals,
+            }
+        };
+        entries.push(stat::Entry { name, change });
+    }
+    assert_eq!(out.as_bstr(), std::fs::read(root.join("repo.expected"))?.as_bstr());
+
+    for (width, expected) in [(80, "repo.stat.expected"), (40, "repo.stat-40.expected")] {
+        let mut out = Vec::new();
+        stat::write_stat(&mut out, &entries, width)?;
+        assert_eq!(
+            out.as_bstr(),
+            std::fs::read(root.join(expected))?.as_bstr(),
+            "{expected}"
+        );
+    }
+
+    let mut out = Vec::new();
+    stat::write_numstat(&mut out, &entries)?;
+    assert_eq!(
+        out.as_bstr(),
+        std::fs::read(root.join("repo.numstat.expected"))?.as_bstr()
+    );
+    Ok(())
+}
+
+#[test]
+fn rename_paths_are_compressed_like_git_does() {
+    for (old, new, expected) in [
+        ("a", "b", "a => b"),
+        ("dir/a", "dir/b", "dir/{a => b}"),
+        ("a/file", "b/file", "{a => b}/file"),
+        ("dir/a/file", "dir/b/file", "dir/{a => b}/file"),
+        ("dir/file", "file", "dir/file => file"),
+        ("file", "dir/file", "file => dir/file"),
+        ("dir/file", "dir/sub/file", "dir/{ => sub}/file"),
+        ("a/b/c/file", "a/b/c/d/file", "a/b/c/{ => d}/file"),
+    ] {
+        assert_eq!(stat::rename_path(old.into(), new.into()), expected, "{old} -> {new}");
+    }
+}
+
+fn unified_diff(
+    before: &[u8],
+    after: &[u8],
+    context_size: ContextSize,
+    function_name: Option<&FunctionName>,
+) -> std::io::Result<Vec<u8>> {
+    let input = InternedInput::new(
+        gix_diff::blob::sources::byte_lines_with_terminator(before),
+        gix_diff::blob::sources::byte_lines_with_terminator(after),
+    );
+    let (out, _num_hunks) = gix_diff::blob::diff(
+        Algorithm::Myers,
+        &input,
+        UnifiedDiff::new(&input, Vec::new(), context_size, function_name),
+    )?;
+    Ok(out)
+}
+
+type Tree = HashMap<String, (EntryKind, gix_hash::ObjectId)>;
+
+fn read_tree(path: &Path) -> crate::Result<Tree> {
+    let mut out = HashMap::new();
+    for line in std::fs::read_to_string(path)?.lines() {
+        let (info, path) = line.split_once('\t').expect("ls-tree format");
+        let mut tokens = info.split(' ');
+        let mode = match tokens.next().expect("mode") {
+            "100644" => EntryKind::Blob,
+            "100755" => EntryKind::BlobExecutable,
+            mode => unreachable!("unexpected mode {mode}"),
+        };
+        let id = gix_hash::ObjectId::from_hex(tokens.nth(1).expect("id").as_bytes())?;
+        out.insert(path.to_owned(), (mode, id));
+    }
+    Ok(out)
+}
+
+fn blob(db: &gix_odb::Handle, id: &gix_hash::oid) -> crate::Result<Vec<u8>> {
+    use gix_object::FindExt;
+    let mut buf = Vec::new();
+    Ok(db.find_blob(id, &mut buf)?.data.to_owned())
+}
+
+fn location<'a>(tree: &'a Tree, path: &'a str) -> Location<'a> {
+    let (mode, id) = tree.get(path).expect("path present");
+    Location {
+        path: path.into(),
+        id,
+        mode: *mode,
+    }
+}
@@ -0,0 +1,120 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Each case consists of `<name>.before` and `<name>.after`, along with `<name>.baseline` which holds
+# the hunk headers of `git diff --patience` without context lines, and `<name>.no-indent-heuristic.baseline`
+# which holds them without the indent heuristic that git applies by default.
+function baseline() {
+  local name=${1:?need name}
+  { git diff --no-index --no-color --patience -U0 "$name.before" "$name.after" || true; } \
+    | { grep '^@@' || true; } > "$name.baseline"
+  { git -c diff.indentHeuristic=false diff --no-index --no-color --patience -U0 "$name.before" "$name.after" || true; } \
+    | { grep '^@@' || true; } > "$name.no-indent-heuristic.baseline"
+}
+
+# The classic example where unique lines align the functions instead of their braces.
+cat <<EOF >functions.before
+#include <stdio.h>
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf("Your answer is: ");
+        printf("%d\n", foo);
+    }
+}
+
+int fact(int n)
+{
+    if(n > 1)
+    {
+        return fact(n-1) * n;
+    }
+    return 1;
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fact(10));
+}
+EOF
+cat <<EOF >functions.after
+#include <stdio.h>
+
+int fib(int n)
+{
+    if(n > 2)
+    {
+        return fib(n-1) + fib(n-2);
+    }
+    return 1;
+}
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf("%d\n", foo);
+    }
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fib(10));
+}
+EOF
+baseline functions
+
+# Blocks of lines that moved around.
+printf '%s\n' a b c d e f g h i j > moved.before
+printf '%s\n' f g h a b c d e i j > moved.after
+baseline moved
+
+# Without any unique lines, the fallback algorithm is used.
+printf '%s\n' a b a b a b c c a b > repeated.before
+printf '%s\n' b a b a c c b a b a > repeated.after
+baseline repeated
+
+# Unique lines that are interleaved with repeated ones.
+printf '%s\n' x 1 x 2 x 3 x 4 x 5 x > interleaved.before
+printf '%s\n' x 5 x 4 x 3 x 2 y 1 x > interleaved.after
+baseline interleaved
+
+# Unique lines whose order differs between both sides, forcing a choice of the longest common sequence.
+printf '%s\n' a 1 2 3 b 4 5 c 6 7 8 9 d > crossing.before
+printf '%s\n' 4 5 a 8 1 2 9 3 c 6 b 7 d > crossing.after
+baseline crossing
+
+printf '%s\n' one two three > disjoint.before
+printf '%s\n' four five > disjoint.after
+baseline disjoint
+
+: > empty.before
+printf '%s\n' a b > empty.after
+baseline empty
+
+printf '%s\n' a b > to-empty.before
+: > to-empty.after
+baseline to-empty
+
+printf 'a\nb\nc' > no-newline.before
+printf 'a\nb\nc\nd' > no-newline.after
+baseline no-newline
+
+seq 1 300 > numbers.before
+seq 1 300 | sed -e '5d' -e '20s/$/ changed/' -e '100,120d' -e '200a inserted' -e '250,260s/^/x/' > numbers.after
+baseline numbers
+
+seq 1 300 > numbers-rotated.before
+{ seq 150 300; seq 1 149; } > numbers-rotated.after
+baseline numbers-rotated
+
+# An insertion that can be placed in two ways, where the indent heuristic picks the earlier one.
+printf '%s\n' 1 2 a '' b 3 4 > indent-heuristic.before
+printf '%s\n' 1 2 a '' b a '' b 3 4 > indent-heuristic.after
+baseline indent-heuristic
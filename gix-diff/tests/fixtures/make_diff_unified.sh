@@ -0,0 +1,126 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+# Hunks of textual diffs, each case consisting of `<name>.before` and `<name>.after`, along with `<name>.<context>.expected`
+# which holds the output of `git diff` without its file header.
+mkdir hunks
+(cd hunks
+  echo '*-custom.* diff=custom' > ../.gitattributes
+  # Lines defining the function `secret` are never shown, and of all others only the name is shown.
+  function_name_pattern=$'!^fn secret\n^[a-z]+ ([a-z]+)'
+
+  function baseline() {
+    local name=${1:?need name}
+    for context in 0 1 3; do
+      { git -c diff.custom.xfuncname="$function_name_pattern" diff --no-index --no-color -U$context "$name.before" "$name.after" || true; } \
+        | sed -n '/^@@/,$p' > "$name.$context.expected"
+    done
+  }
+
+  seq 1 30 > numbers.before
+  seq 1 30 | sed -e '2d' -e '10s/$/ changed/' -e '16a inserted' -e '29,30d' > numbers.after
+  baseline numbers
+
+  printf 'a\nb\nc' > no-newline.before
+  printf 'a\nb\nc\nd' > no-newline.after
+  baseline no-newline
+
+  printf 'a\nb\nc\n' > add-newline.before
+  printf 'a\nb\nc' > add-newline.after
+  baseline add-newline
+
+  : > empty.before
+  printf '%s\n' a b c > empty.after
+  baseline empty
+
+  cat <<EOF > functions.before
+int a(int x)
+{
+    int i = 0;
+    int j = 1;
+    int k = 2;
+    return x;
+}
+
+  static int not_a_function = 0;
+  int b(void)
+  {
+      return 1;
+  }
+EOF
+  sed -e 's/return x/return x + 1/' -e 's/return 1/return 2/' functions.before > functions.after
+  baseline functions
+
+  cp functions.before functions-custom.before
+  cp functions.after functions-custom.after
+  baseline functions-custom
+
+  cat <<EOF > perl-custom.before
+sub first {
+  my \$x = 1;
+  my \$y = 2;
+  my \$z = 3;
+  return \$x;
+}
+
+fn second {
+  one
+  two
+  three
+  four
+}
+
+fn secret {
+  one
+  two
+  three
+  four
+}
+EOF
+  sed -e 's/return \$x/return \$y/' -e 's/three/drei/' perl-custom.before > perl-custom.after
+  baseline perl-custom
+
+  printf '%s\n' "a-really-long-function-name-which-will-be-truncated-as-it-has-more-than-eighty-characters() {" 1 2 3 4 5 > long.before
+  printf '%s\n' "a-really-long-function-name-which-will-be-truncated-as-it-has-more-than-eighty-characters() {" 1 2 3 4 five > long.after
+  baseline long
+
+  printf 'crlf\r\nx\r\ny\r\nz\r\n' > crlf.before
+  printf 'crlf\r\nx\r\nY\r\nz\r\n' > crlf.after
+  baseline crlf
+)
+rm .gitattributes
+
+# File headers and summaries of changes between two commits.
+mkdir repo && (cd repo
+  git init -q
+  mkdir dir
+  seq 1 10 > modified
+  seq 1 20 > renamed-identical
+  seq 100 120 > dir/renamed-modified
+  seq 1 5 > deleted
+  printf 'exec\n' > executable
+  printf 'binary\0data' > binary
+  printf 'mode and content\n' > mode-and-content
+  git add . && git commit -q -m "initial"
+
+  seq 1 11 > modified
+  git mv renamed-identical identical-after-rename
+  git mv dir/renamed-modified dir/renamed-and-modified
+  echo 121 >> dir/renamed-and-modified
+  git rm -q deleted
+  chmod +x executable
+  printf 'other\0binary data' > binary
+  printf 'mode and content changed\n' > mode-and-content
+  chmod +x mode-and-content
+  printf '%s\n' a b > added
+  git add . && git commit -q -m "changes"
+
+  git diff --no-color -M HEAD~1 HEAD > ../repo.expected
+  git diff --no-color -M --stat HEAD~1 HEAD > ../repo.stat.expected
+  git diff --no-color -M --stat=40 HEAD~1 HEAD > ../repo.stat-40.expected
+  git diff --no-color -M --numstat HEAD~1 HEAD > ../repo.numstat.expected
+  git ls-tree -r HEAD~1 > ../repo.before.tree
+  git ls-tree -r HEAD > ../repo.after.tree
+)
@@ -1,3 +1,12 @@
 //! A crate for handling a git-style directory walk.
-#![deny(rust_2018_idioms)]
+//!
+//! Use [`walk()`] to find all untracked files and directories in a worktree, just like `git status` does,
+//! optionally accelerated by the [untracked cache](untracked_cache) that is stored as `UNTR` extension in the index.
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+///
+pub mod walk;
+pub use walk::function::walk;
+
+pub mod untracked_cache;
@@ -0,0 +1,302 @@
+//! Use the untracked cache index extension to learn about untracked files in directories without reading them.
+//!
+//! A directory's cached list of untracked entries can be used as long as the directory itself didn't change as per
+//! its stat information, and as long as its exclude file and the exclude files of all its parent directories didn't change either.
+use std::path::{Path, PathBuf};
+
+use bstr::{BStr, BString};
+use filetime::FileTime;
+use gix_index::extension::{
+    untracked_cache::{Directory, OidStat},
+    UntrackedCache,
+};
+
+/// Options for use in [`Lookup::new()`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options {
+    /// Options that control how stat comparisons are made when checking if a directory is unchanged.
+    pub stat: gix_index::entry::stat::Options,
+    /// The kind of hash to use when computing the id of exclude files.
+    pub object_hash: gix_hash::Kind,
+}
+
+/// Statistics collected while performing lookups.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Outcome {
+    /// The amount of directories whose untracked entries could be obtained from the cache, which saves reading them.
+    pub directories_reused: usize,
+    /// The amount of directories which were present in the cache, but changed so that they have to be read again.
+    pub directories_changed: usize,
+    /// The amount of directories that weren't present in the cache at all.
+    pub directories_missing: usize,
+}
+
+/// A utility to obtain cached untracked entries for directories that didn't change since the cache was written,
+/// and to record a new untracked cache during a [walk](crate::walk()).
+pub struct Lookup<'a> {
+    /// The cache to use, or `None` if there is no cache or if it's entirely invalid.
+    cache: Option<&'a UntrackedCache>,
+    worktree: PathBuf,
+    index_timestamp: FileTime,
+    options: Options,
+    identifier: BString,
+    info_exclude: Option<OidStat>,
+    excludes_file: Option<OidStat>,
+    dir_flags: u32,
+    /// The directories recorded during the walk, with the root first, or `None` if nothing was recorded yet
+    /// or if the recording was discarded.
+    directories: Option<Vec<Directory>>,
+    /// Statistics about all lookups so far.
+    pub outcome: Outcome,
+}
+
+/// What's known about a directory in the worktree as compared to its cached version.
+pub(crate) struct Cached<'a> {
+    /// The cached directory if its untracked entries can be used as they are.
+    pub directory: Option<&'a Directory>,
+    /// `true` if the exclude file of this directory and of all its parents didn't change.
+    pub excludes_unchanged: bool,
+    /// The current stat information of the directory.
+    stat: Option<gix_index::entry::Stat>,
+    /// The current id of the exclude file in the directory.
+    exclude_file_oid: Option<gix_hash::ObjectId>,
+}
+
+/// The name of the file to read exclude patterns from in each directory.
+const EXCLUDE_FILENAME_PER_DIR: &str = ".gitignore";
+
+/// Lifecycle
+impl<'a> Lookup<'a> {
+    /// Create a new instance to lookup cached untracked entries of `index` for directories in `worktree`.
+    ///
+    /// `info_exclude` is the path to the `.git/info/exclude` file and `excludes_file` is the file configured via `core.excludesFile`.
+    /// If either of these changed since the cache was written, or if the cache was created for a different worktree or machine,
+    /// the cache is entirely invalid and won't be used, just as if there was no untracked cache extension in `index`.
+    pub fn new(
+        index: &'a gix_index::State,
+        worktree: impl Into<PathBuf>,
+        info_exclude: Option<&Path>,
+        excludes_file: Option<&Path>,
+        options: Options,
+    ) -> std::io::Result<Self> {
+        let worktree = worktree.into();
+        let mut lookup = Lookup {
+            cache: None,
+            identifier: identifier(
+                &gix_path::realpath(&worktree).map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?,
+            ),
+            worktree,
+            index_timestamp: index.timestamp(),
+            options,
+            info_exclude: None,
+            excludes_file: None,
+            dir_flags: 0,
+            directories: None,
+            outcome: Default::default(),
+        };
+        lookup.cache = index
+            .untracked()
+            .filter(|cache| cache.identifier() == lookup.identifier && cache.exclude_filename_per_dir() == EXCLUDE_FILENAME_PER_DIR);
+        let mut is_unchanged = true;
+        for (path, cached, current) in [
+            (info_exclude, lookup.cache.and_then(|c| c.info_exclude()), &mut lookup.info_exclude),
+            (excludes_file, lookup.cache.and_then(|c| c.excludes_file()), &mut lookup.excludes_file),
+        ] {
+            *current = match path {
+                Some(path) => oid_stat(path, options, lookup.index_timestamp, cached)?,
+                None => None,
+            };
+            is_unchanged &= current.as_ref().map(|c| c.id) == cached.map(|c| c.id);
+        }
+        if !is_unchanged {
+            lookup.cache = None;
+        }
+        Ok(lookup)
+    }
+
+    /// Return `true` if the untracked cache is present and may be used, as none of the global exclude files changed
+    /// since it was written.
+    pub fn is_valid(&self) -> bool {
+        self.cache.is_some()
+    }
+
+    /// Return the untracked cache that was recorded during the last complete [walk](crate::walk()), or `None` if
+    /// there was no walk or if it was cancelled.
+    ///
+    /// Store it in the index with [`set_untracked()`](gix_index::State::set_untracked()) to speed up the next walk.
+    pub fn into_untracked_cache(self) -> Option<UntrackedCache> {
+        Some(UntrackedCache::new(
+            self.identifier,
+            self.info_exclude,
+            self.excludes_file,
+            EXCLUDE_FILENAME_PER_DIR.into(),
+            self.dir_flags,
+            self.directories?,
+        ))
+    }
+}
+
+/// Walk support
+impl<'a> Lookup<'a> {
+    /// Prepare a walk with the given `dir_flags`, which invalidates the cache if it was written with different flags.
+    pub(crate) fn start(&mut self, dir_flags: u32) {
+        if self.cache.map_or(false, |cache| cache.dir_flags() != dir_flags) {
+            self.cache = None;
+        }
+        self.dir_flags = dir_flags;
+        self.directories = Some(Vec::new());
+    }
+
+    /// Forget everything that was recorded, as the walk didn't complete.
+    pub(crate) fn discard(&mut self) {
+        self.directories = None;
+    }
+
+    /// Compare the directory at `rela_dir_path`, a slash-separated path relative to the worktree root, with its cached version,
+    /// knowing that the exclude files of all its parent directories are unchanged if `parent_excludes_unchanged` is `true`.
+    pub(crate) fn directory(
+        &mut self,
+        rela_dir_path: &BStr,
+        parent_excludes_unchanged: bool,
+    ) -> std::io::Result<Cached<'a>> {
+        let dir_path = self.worktree.join(gix_path::from_bstr(rela_dir_path));
+        let stat = match gix_index::fs::Metadata::from_path_no_follow(&dir_path) {
+            Ok(md) if md.is_dir() => Some(
+                gix_index::entry::Stat::from_fs(&md)
+                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?,
+            ),
+            Ok(_) => None,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err),
+        };
+        let exclude_file_oid = oid_stat(
+            &dir_path.join(EXCLUDE_FILENAME_PER_DIR),
+            self.options,
+            self.index_timestamp,
+            None,
+        )?
+        .map(|oid_stat| oid_stat.id);
+
+        let Some(dir) = self.cache.and_then(|cache| cache.directory_by_path(rela_dir_path)) else {
+            self.outcome.directories_missing += 1;
+            return Ok(Cached {
+                directory: None,
+                excludes_unchanged: false,
+                stat,
+                exclude_file_oid,
+            });
+        };
+        let excludes_unchanged =
+            parent_excludes_unchanged && dir.exclude_file_oid.filter(|id| !id.is_null()) == exclude_file_oid;
+        let is_unchanged = excludes_unchanged
+            && !dir.check_only
+            && match (&stat, &dir.stat) {
+                (Some(current), Some(cached)) => {
+                    current.matches(cached, self.options.stat)
+                        && !current.is_racy(self.index_timestamp, self.options.stat)
+                }
+                _ => false,
+            };
+        if is_unchanged {
+            self.outcome.directories_reused += 1;
+        } else {
+            self.outcome.directories_changed += 1;
+        }
+        Ok(Cached {
+            directory: is_unchanged.then_some(dir),
+            excludes_unchanged,
+            stat,
+            exclude_file_oid,
+        })
+    }
+
+    /// Return the cache that is used for lookups, if it is valid.
+    pub(crate) fn cache(&self) -> Option<&'a UntrackedCache> {
+        self.cache
+    }
+
+    /// Start recording the directory with the given `name`, and return its index to [finish](Self::finish_directory()) it later.
+    pub(crate) fn record_directory(&mut self, name: &BStr) -> usize {
+        let directories = self.directories.get_or_insert_with(Vec::new);
+        directories.push(Directory {
+            name: name.to_owned(),
+            untracked_entries: Vec::new(),
+            sub_directories: Vec::new(),
+            stat: None,
+            exclude_file_oid: None,
+            check_only: false,
+        });
+        directories.len() - 1
+    }
+
+    /// Set the state of the recorded directory at `index` once all of its entries are known.
+    pub(crate) fn finish_directory(
+        &mut self,
+        index: usize,
+        cached: Cached<'_>,
+        untracked_entries: Vec<BString>,
+        sub_directories: Vec<usize>,
+    ) {
+        if let Some(dir) = self.directories.as_mut().and_then(|dirs| dirs.get_mut(index)) {
+            dir.untracked_entries = untracked_entries;
+            dir.sub_directories = sub_directories;
+            dir.stat = cached.stat;
+            dir.exclude_file_oid = cached.exclude_file_oid;
+        }
+    }
+}
+
+/// Return the identifier of the untracked cache for the absolute `worktree` path, just like `git` creates it, so each can use
+/// the cache of the other.
+fn identifier(worktree: &Path) -> BString {
+    let system = match std::env::consts::OS {
+        "linux" | "android" => "Linux",
+        "macos" | "ios" => "Darwin",
+        "windows" => "Windows",
+        "freebsd" => "FreeBSD",
+        "netbsd" => "NetBSD",
+        "openbsd" => "OpenBSD",
+        "dragonfly" => "DragonFly",
+        "solaris" | "illumos" => "SunOS",
+        other => other,
+    };
+    let mut out = BString::from("Location ");
+    out.extend_from_slice(&gix_path::into_bstr(worktree));
+    out.extend_from_slice(b", system ");
+    out.extend_from_slice(system.as_bytes());
+    out.push(0);
+    out
+}
+
+/// Return the stat and id of the exclude file at `path`, or `None` if it doesn't exist.
+/// Avoid hashing the file if it is unchanged compared to `cached`.
+///
+/// Note that `git` may also use the id of the file as stored in the index if it is tracked, which isn't done here.
+fn oid_stat(
+    path: &Path,
+    options: Options,
+    index_timestamp: FileTime,
+    cached: Option<&OidStat>,
+) -> std::io::Result<Option<OidStat>> {
+    let md = match gix_index::fs::Metadata::from_path_no_follow(path) {
+        Ok(md) => md,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let stat =
+        gix_index::entry::Stat::from_fs(&md).map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+    if let Some(cached) = cached {
+        if stat.matches(&cached.stat, options.stat) && !stat.is_racy(index_timestamp, options.stat) {
+            return Ok(Some(cached.clone()));
+        }
+    }
+    let mut data = std::fs::read(path)?;
+    // Git hashes exclude files with a newline appended, unless they are empty.
+    if !data.is_empty() {
+        data.push(b'\n');
+    }
+    Ok(Some(OidStat {
+        stat,
+        id: gix_object::compute_hash(options.object_hash, gix_object::Kind::Blob, &data),
+    }))
+}
@@ -0,0 +1,327 @@
+use std::path::{Path, PathBuf};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_index::DirectoryKind;
+
+use crate::{
+    untracked_cache::Lookup,
+    walk::{Action, Context, Delegate, Entry, Error, Options, Outcome},
+};
+
+/// Find all untracked files and directories in the worktree at `worktree_root` and pass them to `delegate`, similar to
+/// what `git status` does, using `ctx` to learn which files are tracked or excluded and `options` to configure the walk.
+///
+/// Entries are emitted in order of their path within each directory. Directories named `.git`, excluded entries,
+/// [sparse directories](gix_index::entry::Mode::DIR) and submodules are skipped.
+/// Untracked repositories nested in the worktree are emitted as directory without entering them.
+///
+/// If an untracked cache is provided in `ctx`, directories that didn't change since the cache was written aren't read,
+/// and their untracked entries are taken from the cache instead. At the same time, an up-to-date cache is recorded.
+pub fn walk(
+    worktree_root: &Path,
+    ctx: Context<'_, '_>,
+    options: Options,
+    delegate: &mut dyn Delegate,
+) -> Result<Outcome, Error> {
+    let Context {
+        index,
+        excludes,
+        objects,
+        mut untracked_cache,
+    } = ctx;
+    if let Some(lookup) = untracked_cache.as_deref_mut() {
+        lookup.start(options.dir_flags());
+    }
+    let mut state = State {
+        root: worktree_root,
+        index,
+        excludes,
+        objects,
+        untracked_cache,
+        options,
+        delegate,
+        out: Outcome::default(),
+    };
+    let mut rela_dir = BString::default();
+    state.directory(&mut rela_dir, true)?;
+    if state.out.cancelled {
+        if let Some(lookup) = state.untracked_cache {
+            lookup.discard();
+        }
+    }
+    Ok(state.out)
+}
+
+struct State<'a, 'cache> {
+    root: &'a Path,
+    index: &'a gix_index::State,
+    excludes: &'a mut gix_worktree::Stack,
+    objects: &'a dyn gix_object::Find,
+    untracked_cache: Option<&'a mut Lookup<'cache>>,
+    options: Options,
+    delegate: &'a mut dyn Delegate,
+    out: Outcome,
+}
+
+/// What we know about an entry of a directory before classifying it.
+enum Kind {
+    /// The entry is a file or a directory as seen on disk, and it's yet to be determined if it's tracked or excluded.
+    Unknown { is_dir: bool },
+    /// The entry is known to be untracked and not excluded as it was obtained from the untracked cache.
+    CachedUntracked { is_dir: bool },
+}
+
+impl State<'_, '_> {
+    /// Walk the directory at `rela_dir` which is known to contain tracked files, or is the root of the worktree, and return
+    /// the index of the recorded untracked cache directory, if there is one.
+    /// `excludes_unchanged` is `true` if no exclude file of any parent directory changed since the untracked cache was written.
+    fn directory(&mut self, rela_dir: &mut BString, excludes_unchanged: bool) -> Result<Option<usize>, Error> {
+        let cached = match self.untracked_cache.as_deref_mut() {
+            Some(lookup) => Some(
+                lookup
+                    .directory(rela_dir.as_bstr(), excludes_unchanged)
+                    .map_err(|source| Error::UntrackedCache {
+                        path: self.root.join(gix_path::from_bstr(rela_dir.as_bstr())),
+                        source,
+                    })?,
+            ),
+            None => None,
+        };
+        let excludes_unchanged = cached.as_ref().map_or(false, |c| c.excludes_unchanged);
+        let mut entries = match cached
+            .as_ref()
+            .and_then(|cached| cached.directory)
+            .and_then(|dir| self.entries_from_cache(rela_dir.as_bstr(), dir))
+        {
+            Some(entries) => {
+                self.out.cached_directories += 1;
+                entries
+            }
+            None => self.read_dir(rela_dir.as_bstr())?,
+        };
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let record_index = self
+            .untracked_cache
+            .as_deref_mut()
+            .map(|lookup| lookup.record_directory(dir_name(rela_dir.as_bstr())));
+        let mut untracked_names = Vec::new();
+        let mut sub_directories = Vec::new();
+        for (name, kind) in entries {
+            let prev_len = rela_dir.len();
+            if !rela_dir.is_empty() {
+                rela_dir.push(b'/');
+            }
+            rela_dir.push_str(&name);
+            let res = self.entry(rela_dir, kind, excludes_unchanged);
+            rela_dir.truncate(prev_len);
+            match res? {
+                Visit::Untracked { is_dir } => {
+                    let mut name = name;
+                    if is_dir {
+                        name.push(b'/');
+                    }
+                    untracked_names.push(name);
+                }
+                Visit::Directory { record_index } => sub_directories.extend(record_index),
+                Visit::Skipped => {}
+            }
+            if self.out.cancelled {
+                return Ok(None);
+            }
+        }
+
+        if let (Some(lookup), Some(record_index), Some(cached)) =
+            (self.untracked_cache.as_deref_mut(), record_index, cached)
+        {
+            lookup.finish_directory(record_index, cached, untracked_names, sub_directories);
+        }
+        Ok(record_index)
+    }
+
+    fn entry(&mut self, rela_path: &mut BString, kind: Kind, excludes_unchanged: bool) -> Result<Visit, Error> {
+        Ok(match kind {
+            Kind::CachedUntracked { is_dir: false } => self.emit(rela_path.as_bstr(), false),
+            Kind::CachedUntracked { is_dir: true } => {
+                // The directory itself may have changed without the parent directory noticing.
+                if self.is_untracked_directory(rela_path)? {
+                    self.emit(rela_path.as_bstr(), true)
+                } else {
+                    Visit::Skipped
+                }
+            }
+            Kind::Unknown { is_dir: false } => {
+                if self.index.entry_range(rela_path.as_bstr()).is_some() || self.is_excluded(rela_path.as_bstr(), false)? {
+                    Visit::Skipped
+                } else {
+                    self.emit(rela_path.as_bstr(), false)
+                }
+            }
+            Kind::Unknown { is_dir: true } => {
+                match self.index.directory_kind_by_path_icase(rela_path.as_bstr(), false) {
+                    Some(DirectoryKind::Inferred) => Visit::Directory {
+                        record_index: self.directory(rela_path, excludes_unchanged)?,
+                    },
+                    Some(DirectoryKind::SparseDir | DirectoryKind::Submodule) => Visit::Skipped,
+                    None => {
+                        if self.is_excluded(rela_path.as_bstr(), true)? {
+                            Visit::Skipped
+                        } else if self.options.recurse_untracked_directories && !self.is_repository(rela_path.as_bstr()) {
+                            Visit::Directory {
+                                record_index: self.directory(rela_path, excludes_unchanged)?,
+                            }
+                        } else if self.is_untracked_directory(rela_path)? {
+                            self.emit(rela_path.as_bstr(), true)
+                        } else {
+                            Visit::Skipped
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    fn emit(&mut self, rela_path: &BStr, is_dir: bool) -> Visit {
+        self.out.returned_entries += 1;
+        let action = self.delegate.visit(Entry {
+            rela_path: rela_path.to_owned(),
+            is_dir,
+        });
+        if action == Action::Cancel {
+            self.out.cancelled = true;
+        }
+        Visit::Untracked { is_dir }
+    }
+
+    /// Return the names of all entries in the directory at `rela_dir` as listed in the `cached` untracked cache directory,
+    /// or `None` if one of the cached untracked entries is tracked by now, which means the directory has to be read.
+    fn entries_from_cache(
+        &self,
+        rela_dir: &BStr,
+        cached: &gix_index::extension::untracked_cache::Directory,
+    ) -> Option<Vec<(BString, Kind)>> {
+        let mut entries = Vec::new();
+        let mut rela_path = BString::from(rela_dir);
+        for name in &cached.untracked_entries {
+            let (name, is_dir) = match name.strip_suffix(b"/") {
+                Some(name) => (name.as_bstr(), true),
+                None => (name.as_bstr(), false),
+            };
+            rela_path.truncate(rela_dir.len());
+            if !rela_path.is_empty() {
+                rela_path.push(b'/');
+            }
+            rela_path.push_str(name);
+            let is_tracked = if is_dir {
+                self.index
+                    .directory_kind_by_path_icase(rela_path.as_bstr(), false)
+                    .is_some()
+            } else {
+                self.index.entry_range(rela_path.as_bstr()).is_some()
+            };
+            if is_tracked {
+                return None;
+            }
+            entries.push((name.to_owned(), Kind::CachedUntracked { is_dir }));
+        }
+        let cache = self.untracked_cache.as_deref()?.cache()?;
+        for sub_dir in cached.sub_directories.iter().map(|idx| &cache.directories()[*idx]) {
+            if entries.iter().any(|(name, _)| *name == sub_dir.name) {
+                continue;
+            }
+            entries.push((sub_dir.name.clone(), Kind::Unknown { is_dir: true }));
+        }
+        Some(entries)
+    }
+
+    fn read_dir(&mut self, rela_dir: &BStr) -> Result<Vec<(BString, Kind)>, Error> {
+        let path = self.root.join(gix_path::from_bstr(rela_dir));
+        Ok(self
+            .read_dir_entries(path)?
+            .into_iter()
+            .map(|(name, is_dir)| (name, Kind::Unknown { is_dir }))
+            .collect())
+    }
+
+    /// Read all entries of the directory at `path`, except for `.git`, and return their names along with whether they are directories.
+    fn read_dir_entries(&mut self, path: PathBuf) -> Result<Vec<(BString, bool)>, Error> {
+        self.out.read_dir_calls += 1;
+        let mut entries = Vec::new();
+        let dir = match std::fs::read_dir(&path) {
+            Ok(dir) => dir,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(entries),
+            Err(source) => return Err(Error::ReadDir { path, source }),
+        };
+        for entry in dir {
+            let (name, file_type) = match entry.and_then(|entry| Ok((entry.file_name(), entry.file_type()?))) {
+                Ok(v) => v,
+                Err(source) => return Err(Error::ReadDir { path, source }),
+            };
+            if name == ".git" {
+                continue;
+            }
+            entries.push((
+                gix_path::into_bstr(PathBuf::from(name)).into_owned(),
+                file_type.is_dir(),
+            ));
+        }
+        Ok(entries)
+    }
+
+    /// Return `true` if the untracked directory at `rela_dir` is a repository, or contains at least one file that isn't excluded.
+    fn is_untracked_directory(&mut self, rela_dir: &mut BString) -> Result<bool, Error> {
+        if self.is_repository(rela_dir.as_bstr()) {
+            return Ok(true);
+        }
+        let path = self.root.join(gix_path::from_bstr(rela_dir.as_bstr()));
+        let mut entries = self.read_dir_entries(path)?;
+        entries.sort();
+        for (name, is_dir) in entries {
+            let prev_len = rela_dir.len();
+            rela_dir.push(b'/');
+            rela_dir.push_str(&name);
+            let res = if self.is_excluded(rela_dir.as_bstr(), is_dir)? {
+                false
+            } else if is_dir {
+                self.is_untracked_directory(rela_dir)?
+            } else {
+                true
+            };
+            rela_dir.truncate(prev_len);
+            if res {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn is_repository(&self, rela_dir: &BStr) -> bool {
+        self.root.join(gix_path::from_bstr(rela_dir)).join(".git").exists()
+    }
+
+    fn is_excluded(&mut self, rela_path: &BStr, is_dir: bool) -> Result<bool, Error> {
+        Ok(self
+            .excludes
+            .at_entry(rela_path, Some(is_dir), self.objects)
+            .map_err(|source| Error::ExcludesAccess {
+                rela_path: rela_path.to_owned(),
+                source,
+            })?
+            .is_excluded())
+    }
+}
+
+enum Visit {
+    Untracked {
+        is_dir: bool,
+    },
+    Directory {
+        /// The index of the directory in the untracked cache that is being recorded.
+        record_index: Option<usize>,
+    },
+    Skipped,
+}
+
+fn dir_name(rela_dir: &BStr) -> &BStr {
+    rela_dir.rfind_byte(b'/').map_or(rela_dir, |pos| rela_dir[pos + 1..].as_bstr())
+}
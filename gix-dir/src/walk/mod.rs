@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use bstr::BString;
+
+/// An untracked entry in the worktree as found by [`walk()`](crate::walk()).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Entry {
+    /// The path of the entry relative to the worktree root, slash-separated and without trailing slash.
+    pub rela_path: BString,
+    /// If `true`, the entry is a directory which contains untracked files but no tracked ones, or a repository
+    /// nested in the worktree. Otherwise, it's a file or symlink.
+    pub is_dir: bool,
+}
+
+/// What to do after an entry was passed to the [`Delegate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Action {
+    /// Continue the walk.
+    Continue,
+    /// Stop the walk, for example because the delegate already knows that there are untracked files.
+    Cancel,
+}
+
+/// A type receiving untracked entries as they are found during the [`walk()`](crate::walk()).
+pub trait Delegate {
+    /// Receive the untracked `entry` and decide whether to continue the walk.
+    fn visit(&mut self, entry: Entry) -> Action;
+}
+
+impl<F> Delegate for F
+where
+    F: FnMut(Entry) -> Action,
+{
+    fn visit(&mut self, entry: Entry) -> Action {
+        self(entry)
+    }
+}
+
+/// Options for use in [`walk()`](crate::walk()).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, directories without tracked files are entered and each untracked file in them is emitted, like
+    /// `git status --untracked-files=all` does.
+    ///
+    /// Otherwise, like `git status --untracked-files=normal`, such directories are emitted as a whole if they contain at least
+    /// one untracked file that isn't excluded, and empty directories aren't emitted at all.
+    pub recurse_untracked_directories: bool,
+}
+
+impl Options {
+    /// The flags `git` uses for its directory walk when configured like this instance, as stored in the untracked cache.
+    pub fn dir_flags(&self) -> u32 {
+        const DIR_SHOW_OTHER_DIRECTORIES: u32 = 1 << 1;
+        const DIR_HIDE_EMPTY_DIRECTORIES: u32 = 1 << 2;
+        if self.recurse_untracked_directories {
+            0
+        } else {
+            DIR_SHOW_OTHER_DIRECTORIES | DIR_HIDE_EMPTY_DIRECTORIES
+        }
+    }
+}
+
+/// All information that is required to perform a [`walk()`](crate::walk()).
+pub struct Context<'a, 'cache> {
+    /// The index to learn which files are tracked.
+    pub index: &'a gix_index::State,
+    /// A stack configured to check for excluded paths, rooted at the worktree root.
+    pub excludes: &'a mut gix_worktree::Stack,
+    /// Access to the object database, to read exclude files from the index if the stack is configured to do so.
+    pub objects: &'a dyn gix_object::Find,
+    /// The untracked cache to reuse untracked entries of unchanged directories from, and to record a new untracked cache with.
+    ///
+    /// After the walk, use [`Lookup::into_untracked_cache()`](crate::untracked_cache::Lookup::into_untracked_cache()) to
+    /// obtain the new cache.
+    pub untracked_cache: Option<&'a mut crate::untracked_cache::Lookup<'cache>>,
+}
+
+/// Statistics collected during the [`walk()`](crate::walk()).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Outcome {
+    /// The amount of directories that were read from disk.
+    pub read_dir_calls: usize,
+    /// The amount of directories whose untracked entries were obtained from the untracked cache instead.
+    pub cached_directories: usize,
+    /// The amount of untracked entries that were passed to the delegate.
+    pub returned_entries: usize,
+    /// If `true`, the delegate cancelled the walk, which means not all untracked entries were seen.
+    pub cancelled: bool,
+}
+
+/// The error returned by [`walk()`](crate::walk()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read directory at '{}'", path.display())]
+    ReadDir { path: PathBuf, source: std::io::Error },
+    #[error("Could not check if '{rela_path}' is excluded")]
+    ExcludesAccess { rela_path: BString, source: std::io::Error },
+    #[error("Could not check if the directory at '{}' changed", path.display())]
+    UntrackedCache { path: PathBuf, source: std::io::Error },
+}
+
+pub(crate) mod function;
@@ -0,0 +1,60 @@
+pub use gix_testtools::Result;
+
+mod untracked_cache;
+mod walk;
+
+/// Options to compare stat information that are independent of the platform and filesystem.
+pub const TEST_STAT_OPTIONS: gix_index::entry::stat::Options = gix_index::entry::stat::Options {
+    trust_ctime: false,
+    check_stat: false,
+    use_nsec: false,
+    use_stdev: false,
+};
+
+pub fn index_at(worktree: &std::path::Path) -> gix_index::File {
+    gix_index::File::at(
+        worktree.join(".git/index"),
+        gix_hash::Kind::Sha1,
+        false,
+        Default::default(),
+    )
+    .expect("index is valid")
+}
+
+/// Return a stack to check for excluded files in `worktree`, using the same exclude files as `git` by default.
+pub fn excludes(worktree: &std::path::Path) -> gix_worktree::Stack {
+    let mut buf = Vec::new();
+    let state = gix_worktree::stack::State::IgnoreStack(gix_worktree::stack::state::Ignore::new(
+        Default::default(),
+        gix_ignore::Search::from_git_dir(&worktree.join(".git"), None, &mut buf).expect("valid exclude files"),
+        None,
+        gix_worktree::stack::state::ignore::Source::WorktreeThenIdMappingIfNotSkipped,
+    ));
+    gix_worktree::Stack::new(worktree, state, gix_glob::pattern::Case::Sensitive, buf, Vec::new())
+}
+
+/// Walk `worktree` with `index` and `options`, and return all untracked entries as they would be printed by `git status`,
+/// along with the outcome.
+pub fn walk(
+    worktree: &std::path::Path,
+    index: &gix_index::State,
+    untracked_cache: Option<&mut gix_dir::untracked_cache::Lookup<'_>>,
+    options: gix_dir::walk::Options,
+) -> crate::Result<(Vec<String>, gix_dir::walk::Outcome)> {
+    let mut entries = Vec::new();
+    let outcome = gix_dir::walk(
+        worktree,
+        gix_dir::walk::Context {
+            index,
+            excludes: &mut excludes(worktree),
+            objects: &gix_object::find::Never,
+            untracked_cache,
+        },
+        options,
+        &mut |entry: gix_dir::walk::Entry| {
+            entries.push(format!("{}{}", entry.rela_path, if entry.is_dir { "/" } else { "" }));
+            gix_dir::walk::Action::Continue
+        },
+    )?;
+    Ok((entries, outcome))
+}
@@ -0,0 +1,19 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config core.untrackedCache true
+
+mkdir -p dir/sub empty-dir
+touch tracked dir/tracked
+git add tracked dir/tracked
+git commit -q -m "init"
+
+touch untracked dir/untracked dir/sub/untracked
+echo "ignored" > dir/.gitignore
+touch dir/ignored
+
+# assure directories aren't racy when compared to the index
+touch -d "2020-01-01 00:00:00" . dir dir/sub empty-dir
+
+git status --porcelain >/dev/null
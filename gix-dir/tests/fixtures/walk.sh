@@ -0,0 +1,22 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q repo
+cd repo
+git config core.untrackedCache false
+
+mkdir -p tracked-dir/sub untracked-dir/sub ignored-dir only-ignored empty-dir
+touch tracked tracked-dir/file tracked-dir/sub/file
+echo "*.o" > .gitignore
+echo "ignored-dir/" >> .gitignore
+git add .
+git commit -q -m "init"
+
+touch untracked tracked-dir/untracked tracked-dir/sub/untracked.o tracked-dir/sub/untracked
+touch untracked-dir/file untracked-dir/sub/file ignored-dir/file only-ignored/file.o
+
+git init -q nested-repo
+touch nested-repo/file
+
+git status --porcelain --untracked-files=normal | grep '^??' | cut -c4- > ../status-normal.baseline
+git status --porcelain --untracked-files=all | grep '^??' | cut -c4- > ../status-all.baseline
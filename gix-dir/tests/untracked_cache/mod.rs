@@ -0,0 +1,154 @@
+use filetime::{set_file_mtime, FileTime};
+use gix_dir::untracked_cache::{Lookup, Options, Outcome};
+
+use crate::{index_at, walk, TEST_STAT_OPTIONS};
+
+fn options() -> Options {
+    Options {
+        stat: TEST_STAT_OPTIONS,
+        object_hash: gix_hash::Kind::Sha1,
+    }
+}
+
+fn lookup<'a>(index: &'a gix_index::State, worktree: &std::path::Path) -> crate::Result<Lookup<'a>> {
+    Ok(Lookup::new(
+        index,
+        worktree,
+        Some(&worktree.join(".git/info/exclude")),
+        None,
+        options(),
+    )?)
+}
+
+/// Assure all directories of the `untracked_cache.sh` fixture aren't racy when compared to the index.
+fn make_directories_old(worktree: &std::path::Path) -> crate::Result {
+    let mtime = FileTime::from_unix_time(1577836800, 0);
+    for dir in [".", "dir", "dir/sub", "empty-dir"] {
+        set_file_mtime(worktree.join(dir), mtime)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn the_untracked_cache_written_by_git_avoids_reading_unchanged_directories() -> crate::Result {
+    let worktree = gix_testtools::scripted_fixture_read_only("untracked_cache.sh")?.canonicalize()?;
+    let index = index_at(&worktree);
+    let (expected, _) = walk(&worktree, &index, None, Default::default())?;
+    assert_eq!(expected, ["dir/.gitignore", "dir/sub/", "dir/untracked", "untracked"]);
+
+    let mut lookup = lookup(&index, &worktree)?;
+    assert!(lookup.is_valid(), "the cache was written for this worktree");
+    let (actual, outcome) = walk(&worktree, &index, Some(&mut lookup), Default::default())?;
+    assert_eq!(actual, expected);
+    assert_eq!(outcome.cached_directories, 2, "both directories with tracked files");
+    assert_eq!(
+        outcome.read_dir_calls, 2,
+        "only untracked directories are read to learn if they contain untracked files"
+    );
+    assert_eq!(
+        lookup.outcome,
+        Outcome {
+            directories_reused: 2,
+            directories_changed: 0,
+            directories_missing: 0,
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn changed_global_excludes_invalidate_the_entire_cache() -> crate::Result {
+    let worktree = gix_testtools::scripted_fixture_read_only("untracked_cache.sh")?.canonicalize()?;
+    let index = index_at(&worktree);
+    let tmp = gix_testtools::tempfile::NamedTempFile::new()?;
+    std::fs::write(tmp.path(), "*.o\n")?;
+    assert!(
+        !Lookup::new(&index, &worktree, None, Some(tmp.path()), options())?.is_valid(),
+        "the excludes file wasn't present when the cache was written"
+    );
+    Ok(())
+}
+
+#[test]
+fn a_recorded_cache_is_used_until_directories_or_exclude_files_change() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable("untracked_cache.sh")?;
+    let worktree = tmp.path().canonicalize()?;
+    make_directories_old(&worktree)?;
+
+    let mut index = index_at(&worktree);
+    let (expected, _) = walk(&worktree, &index, None, Default::default())?;
+    let mut lookup = lookup(&index, &worktree)?;
+    assert!(
+        !lookup.is_valid(),
+        "the cache written by git is for the location the fixture was created in"
+    );
+    let (actual, outcome) = walk(&worktree, &index, Some(&mut lookup), Default::default())?;
+    assert_eq!(actual, expected);
+    assert_eq!(outcome.cached_directories, 0);
+    let cache = lookup.into_untracked_cache().expect("a complete walk records a cache");
+    index.set_untracked(Some(cache));
+
+    let mut lookup = self::lookup(&index, &worktree)?;
+    assert!(lookup.is_valid(), "our own cache is valid for this worktree");
+    let (actual, outcome) = walk(&worktree, &index, Some(&mut lookup), Default::default())?;
+    assert_eq!(actual, expected);
+    assert_eq!(outcome.cached_directories, 2);
+    assert_eq!(
+        lookup.outcome,
+        Outcome {
+            directories_reused: 2,
+            directories_changed: 0,
+            directories_missing: 0,
+        }
+    );
+
+    std::fs::write(worktree.join("dir/.gitignore"), "untracked\n")?;
+    std::fs::write(worktree.join("new"), "")?;
+    let (expected, _) = walk(&worktree, &index, None, Default::default())?;
+    assert_eq!(
+        expected,
+        ["dir/.gitignore", "dir/ignored", "new", "untracked"],
+        "the change to the exclude file is visible, and `dir/sub` now only contains excluded files"
+    );
+    let mut lookup = self::lookup(&index, &worktree)?;
+    let (actual, outcome) = walk(&worktree, &index, Some(&mut lookup), Default::default())?;
+    assert_eq!(actual, expected);
+    assert_eq!(outcome.cached_directories, 0);
+    assert_eq!(
+        lookup.outcome,
+        Outcome {
+            directories_reused: 0,
+            directories_changed: 2,
+            directories_missing: 0,
+        },
+        "a new file changed the root directory, and the exclude file in `dir` changed"
+    );
+    Ok(())
+}
+
+#[test]
+fn invalidated_paths_are_read_again() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable("untracked_cache.sh")?;
+    let worktree = tmp.path().canonicalize()?;
+    make_directories_old(&worktree)?;
+
+    let mut index = index_at(&worktree);
+    let mut lookup = lookup(&index, &worktree)?;
+    walk(&worktree, &index, Some(&mut lookup), Default::default())?;
+    let mut cache = lookup.into_untracked_cache().expect("a complete walk records a cache");
+    cache.invalidate_path("dir/untracked".into());
+    index.set_untracked(Some(cache));
+
+    let mut lookup = self::lookup(&index, &worktree)?;
+    walk(&worktree, &index, Some(&mut lookup), Default::default())?;
+    assert_eq!(
+        lookup.outcome,
+        Outcome {
+            directories_reused: 0,
+            directories_changed: 2,
+            directories_missing: 0,
+        },
+        "the directory containing the path and all of its parents are invalidated"
+    );
+    Ok(())
+}
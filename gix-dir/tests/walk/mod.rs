@@ -0,0 +1,65 @@
+use gix_dir::walk::{Action, Options};
+
+use crate::{index_at, walk};
+
+fn baseline(name: &str) -> crate::Result<(std::path::PathBuf, Vec<String>)> {
+    let root = gix_testtools::scripted_fixture_read_only("walk.sh")?;
+    let expected = std::fs::read_to_string(root.join(name))?
+        .lines()
+        .map(ToOwned::to_owned)
+        .collect();
+    Ok((root.join("repo"), expected))
+}
+
+#[test]
+fn untracked_directories_are_collapsed_like_git_status() -> crate::Result {
+    let (worktree, expected) = baseline("status-normal.baseline")?;
+    let (actual, outcome) = walk(&worktree, &index_at(&worktree), None, Options::default())?;
+    assert_eq!(
+        actual, expected,
+        "excluded files are skipped, and directories with only excluded files aren't shown"
+    );
+    assert_eq!(outcome.returned_entries, expected.len());
+    assert_eq!(outcome.cached_directories, 0);
+    assert!(!outcome.cancelled);
+    Ok(())
+}
+
+#[test]
+fn untracked_directories_can_be_entered_like_git_status_with_all_untracked_files() -> crate::Result {
+    let (worktree, expected) = baseline("status-all.baseline")?;
+    let (actual, _outcome) = walk(
+        &worktree,
+        &index_at(&worktree),
+        None,
+        Options {
+            recurse_untracked_directories: true,
+        },
+    )?;
+    assert_eq!(actual, expected, "nested repositories are never entered");
+    Ok(())
+}
+
+#[test]
+fn the_delegate_can_cancel_the_walk() -> crate::Result {
+    let (worktree, _expected) = baseline("status-normal.baseline")?;
+    let index = index_at(&worktree);
+    let mut count = 0;
+    let outcome = gix_dir::walk(
+        &worktree,
+        gix_dir::walk::Context {
+            index: &index,
+            excludes: &mut crate::excludes(&worktree),
+            objects: &gix_object::find::Never,
+            untracked_cache: None,
+        },
+        Options::default(),
+        &mut |_entry: gix_dir::walk::Entry| {
+            count += 1;
+            Action::Cancel
+        },
+    )?;
+    assert_eq!(count, 1);
+    assert!(outcome.cancelled);
+    Ok(())
+}
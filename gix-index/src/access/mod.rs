@@ -632,6 +632,11 @@ impl State {
     pub fn tree(&self) -> Option<&extension::Tree> {
         self.tree.as_ref()
     }
+    /// Access the `tree` extension for modification, for instance to [invalidate](extension::Tree::invalidate_path()) the
+    /// trees leading to changed entries.
+    pub fn tree_mut(&mut self) -> Option<&mut extension::Tree> {
+        self.tree.as_mut()
+    }
     /// Access the `link` extension.
     pub fn link(&self) -> Option<&extension::Link> {
         self.link.as_ref()
@@ -640,14 +645,121 @@ impl State {
     pub fn resolve_undo(&self) -> Option<&extension::resolve_undo::Paths> {
         self.resolve_undo.as_ref()
     }
+    /// Obtain the resolve-undo extension for modification.
+    pub fn resolve_undo_mut(&mut self) -> Option<&mut extension::resolve_undo::Paths> {
+        self.resolve_undo.as_mut()
+    }
     /// Obtain the untracked extension.
     pub fn untracked(&self) -> Option<&extension::UntrackedCache> {
         self.untracked.as_ref()
     }
+    /// Obtain the untracked extension for modification, for instance to [invalidate](extension::UntrackedCache::invalidate_path())
+    /// the directories of entries that were added or removed.
+    pub fn untracked_mut(&mut self) -> Option<&mut extension::UntrackedCache> {
+        self.untracked.as_mut()
+    }
     /// Obtain the fsmonitor extension.
     pub fn fs_monitor(&self) -> Option<&extension::FsMonitor> {
         self.fs_monitor.as_ref()
     }
+    /// Obtain the fsmonitor extension for modification.
+    pub fn fs_monitor_mut(&mut self) -> Option<&mut extension::FsMonitor> {
+        self.fs_monitor.as_mut()
+    }
+}
+
+/// Extension Mutation
+impl State {
+    /// Set the tree extension to `tree`, returning the previous value.
+    pub fn set_tree(&mut self, tree: Option<extension::Tree>) -> Option<extension::Tree> {
+        std::mem::replace(&mut self.tree, tree)
+    }
+
+    /// Set the resolve-undo extension to `paths`, returning the previous value.
+    ///
+    /// Note that `paths` are expected to be sorted by [name](extension::resolve_undo::ResolvePath::name).
+    pub fn set_resolve_undo(
+        &mut self,
+        paths: Option<extension::resolve_undo::Paths>,
+    ) -> Option<extension::resolve_undo::Paths> {
+        std::mem::replace(&mut self.resolve_undo, paths)
+    }
+
+    /// Remember the conflicting entries at stage 1 to 3 of `path` in the resolve-undo extension, so the conflict can be
+    /// restored later, typically right before they are replaced with a resolved entry.
+    ///
+    /// Returns `true` if there were conflicting entries to remember.
+    /// Note that the conflicting entries themselves are not removed.
+    pub fn record_resolve_undo(&mut self, path: &BStr) -> bool {
+        let Some(range) = self.entry_range(path) else {
+            return false;
+        };
+        let mut stages = [None, None, None];
+        for entry in &self.entries[range] {
+            let stage = entry.stage();
+            if stage == 0 || entry.flags.contains(entry::Flags::REMOVE) {
+                continue;
+            }
+            stages[stage as usize - 1] = Some(extension::resolve_undo::Stage {
+                mode: entry.mode,
+                id: entry.id,
+            });
+        }
+        if stages.iter().all(Option::is_none) {
+            return false;
+        }
+
+        let paths = self.resolve_undo.get_or_insert_with(Default::default);
+        let record = extension::resolve_undo::ResolvePath {
+            name: path.to_owned(),
+            stages,
+        };
+        match paths.binary_search_by(|p| p.name.as_slice().cmp(path.as_ref())) {
+            Ok(idx) => paths[idx] = record,
+            Err(idx) => paths.insert(idx, record),
+        }
+        true
+    }
+
+    /// Set the untracked extension to `untracked`, returning the previous value.
+    pub fn set_untracked(
+        &mut self,
+        untracked: Option<extension::UntrackedCache>,
+    ) -> Option<extension::UntrackedCache> {
+        std::mem::replace(&mut self.untracked, untracked)
+    }
+
+    /// Set the fsmonitor extension to `fs_monitor`, returning the previous value.
+    ///
+    /// Note that when writing the extension, entries are considered valid only if they have the
+    /// [`FSMONITOR_VALID`](entry::Flags::FSMONITOR_VALID) flag set.
+    pub fn set_fs_monitor(&mut self, fs_monitor: Option<extension::FsMonitor>) -> Option<extension::FsMonitor> {
+        std::mem::replace(&mut self.fs_monitor, fs_monitor)
+    }
+
+    /// If the fsmonitor extension is present, set the [`FSMONITOR_VALID`](entry::Flags::FSMONITOR_VALID) flag on all
+    /// entries that it doesn't consider dirty, and clear it on all others, just like `git` does when a filesystem monitor
+    /// is configured.
+    ///
+    /// Returns `true` if the extension was present and flags were changed.
+    ///
+    /// Note that this should only be done if the filesystem monitor is queried for changes afterwards, as otherwise
+    /// changes since it was last queried are missed.
+    pub fn apply_fs_monitor_dirty_bitmap(&mut self) -> bool {
+        let Some(fs_monitor) = self.fs_monitor.as_ref() else {
+            return false;
+        };
+        for entry in &mut self.entries {
+            entry.flags.insert(entry::Flags::FSMONITOR_VALID);
+        }
+        let entries = &mut self.entries;
+        fs_monitor.for_each_dirty_entry(|idx| {
+            if let Some(entry) = entries.get_mut(idx) {
+                entry.flags.remove(entry::Flags::FSMONITOR_VALID);
+            }
+        });
+        true
+    }
 }
 
 #[cfg(test)]
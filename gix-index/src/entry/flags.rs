@@ -60,6 +60,11 @@ bitflags! {
 }
 
 impl Flags {
+    /// Create flags that only encode `stage`, which must be a valid [stage][Stage] between 0 and 3.
+    pub fn from_stage(stage: Stage) -> Self {
+        Flags::from_bits_retain((stage << 12) & Flags::STAGE_MASK.bits())
+    }
+
     /// Return the stage as extracted from the bits of this instance.
     pub fn stage(&self) -> Stage {
         (*self & Flags::STAGE_MASK).bits() >> 12
@@ -1,10 +1,34 @@
 use std::convert::TryInto;
 
-use crate::{entry, Entry, State};
+use bstr::BStr;
+
+use crate::{entry, util::write_var_int, Entry, State};
 
 impl Entry {
     /// Serialize ourselves to `out` with path access via `state`, without padding.
     pub fn write_to(&self, mut out: impl std::io::Write, state: &State) -> std::io::Result<()> {
+        self.write_sans_path_to(&mut out, state)?;
+        out.write_all(self.path(state))?;
+        out.write_all(b"\0")
+    }
+
+    /// Serialize ourselves to `out` with path access via `state` in the format of index V4, which stores our path
+    /// relative to the path of the previous entry, `prev_path`.
+    pub fn write_to_with_prev_path(
+        &self,
+        mut out: impl std::io::Write,
+        state: &State,
+        prev_path: &BStr,
+    ) -> std::io::Result<()> {
+        self.write_sans_path_to(&mut out, state)?;
+        let path = self.path(state);
+        let common_prefix_len = prev_path.iter().zip(path.iter()).take_while(|(a, b)| a == b).count();
+        write_var_int(&mut out, (prev_path.len() - common_prefix_len) as u64)?;
+        out.write_all(&path[common_prefix_len..])?;
+        out.write_all(b"\0")
+    }
+
+    fn write_sans_path_to(&self, mut out: impl std::io::Write, state: &State) -> std::io::Result<()> {
         let stat = self.stat;
         out.write_all(&stat.ctime.secs.to_be_bytes())?;
         out.write_all(&stat.ctime.nsecs.to_be_bytes())?;
@@ -33,7 +57,6 @@ impl Entry {
                     .to_be_bytes(),
             )?;
         }
-        out.write_all(path)?;
-        out.write_all(b"\0")
+        Ok(())
     }
 }
@@ -1,18 +1,33 @@
+use std::convert::TryFrom;
+
 use bstr::BString;
 
 use crate::{
+    entry,
     extension::{FsMonitor, Signature},
-    util::{read_u32, read_u64, split_at_byte_exclusive},
+    util::{read_u32, read_u64, split_at_byte_exclusive, split_at_pos},
+    Entry,
 };
 
-#[derive(Clone)]
+/// The token with which the filesystem monitor was last queried, in one of the supported protocol versions.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Token {
-    V1 { nanos_since_1970: u64 },
-    V2 { token: BString },
+    /// The time at which the filesystem monitor was last queried, as used by version 1 of the hook protocol.
+    V1 {
+        /// The amount of nanoseconds since the unix epoch.
+        nanos_since_1970: u64,
+    },
+    /// An opaque token as returned by the filesystem monitor, as used by version 2 of the hook protocol.
+    V2 {
+        /// The token as provided by the filesystem monitor.
+        token: BString,
+    },
 }
 
+/// The signature of the filesystem monitor extension.
 pub const SIGNATURE: Signature = *b"FSMN";
 
+/// Decode the filesystem monitor extension from `data`.
 pub fn decode(data: &[u8]) -> Option<FsMonitor> {
     let (version, data) = read_u32(data)?;
     let (token, data) = match version {
@@ -28,7 +43,8 @@ pub fn decode(data: &[u8]) -> Option<FsMonitor> {
     };
 
     let (ewah_size, data) = read_u32(data)?;
-    let (entry_dirty, data) = gix_bitmap::ewah::decode(&data[..ewah_size as usize]).ok()?;
+    let (ewah, data) = split_at_pos(data, ewah_size as usize)?;
+    let (entry_dirty, _) = gix_bitmap::ewah::decode(ewah).ok()?;
 
     if !data.is_empty() {
         return None;
@@ -36,3 +52,85 @@ pub fn decode(data: &[u8]) -> Option<FsMonitor> {
 
     FsMonitor { token, entry_dirty }.into()
 }
+
+/// Lifecycle
+impl FsMonitor {
+    /// Create a new instance from the `token` that was last obtained from the filesystem monitor.
+    ///
+    /// Which entries are considered valid is determined by [`entry::Flags::FSMONITOR_VALID`] when writing.
+    pub fn new(token: Token) -> Self {
+        FsMonitor {
+            token,
+            entry_dirty: Default::default(),
+        }
+    }
+}
+
+/// Access
+impl FsMonitor {
+    /// The token with which the filesystem monitor was last queried.
+    pub fn token(&self) -> &Token {
+        &self.token
+    }
+
+    /// Set the token with which the filesystem monitor was last queried to `token`.
+    pub fn set_token(&mut self, token: Token) {
+        self.token = token;
+    }
+
+    /// A bitmap with a bit set for each entry that is not valid as per the filesystem monitor, as read from disk.
+    ///
+    /// Note that it's only representing the state at the time the extension was read and that it isn't updated
+    /// when entries change.
+    pub fn entry_dirty(&self) -> &gix_bitmap::ewah::Vec {
+        &self.entry_dirty
+    }
+
+    /// Call `f(index)` with the index of each entry that is marked as dirty by the filesystem monitor at the time
+    /// this extension was read.
+    pub fn for_each_dirty_entry(&self, mut f: impl FnMut(usize)) {
+        self.entry_dirty.for_each_set_bit(|idx| {
+            f(idx);
+            Some(())
+        });
+    }
+}
+
+/// Serialization
+impl FsMonitor {
+    /// Serialize this instance to `out`, with `entries` providing the information about which entry is valid through
+    /// the [`entry::Flags::FSMONITOR_VALID`] flag. Entries marked for removal are skipped.
+    pub fn write_to(&self, mut out: impl std::io::Write, entries: &[Entry]) -> std::io::Result<()> {
+        let mut dirty = gix_bitmap::ewah::Vec::new();
+        for (idx, entry) in entries
+            .iter()
+            .filter(|e| !e.flags.contains(entry::Flags::REMOVE))
+            .enumerate()
+        {
+            if !entry.flags.contains(entry::Flags::FSMONITOR_VALID) {
+                dirty.set(idx);
+            }
+        }
+
+        let mut buf = Vec::new();
+        match &self.token {
+            Token::V1 { nanos_since_1970 } => {
+                buf.extend_from_slice(&1_u32.to_be_bytes());
+                buf.extend_from_slice(&nanos_since_1970.to_be_bytes());
+            }
+            Token::V2 { token } => {
+                buf.extend_from_slice(&2_u32.to_be_bytes());
+                buf.extend_from_slice(token);
+                buf.push(0);
+            }
+        }
+        let mut bitmap = Vec::new();
+        dirty.write_to(&mut bitmap)?;
+        buf.extend_from_slice(&(u32::try_from(bitmap.len()).expect("less than 4GB bitmap")).to_be_bytes());
+        buf.extend_from_slice(&bitmap);
+
+        out.write_all(&SIGNATURE)?;
+        out.write_all(&(u32::try_from(buf.len()).expect("less than 4GB fsmonitor extension")).to_be_bytes())?;
+        out.write_all(&buf)
+    }
+}
@@ -36,6 +36,20 @@ pub fn decode(data: &[u8]) -> Option<Vec<Offset>> {
     out.into()
 }
 
+/// Write the offsets of all blocks of entries in `offsets` to `out`, including the extension header.
+pub fn write_to(mut out: impl std::io::Write, offsets: &[Offset]) -> std::io::Result<()> {
+    out.write_all(&SIGNATURE)?;
+    let extension_size: u32 = 4 + (offsets.len() * (4 + 4)) as u32;
+    out.write_all(&extension_size.to_be_bytes())?;
+
+    out.write_all(&1_u32.to_be_bytes())?;
+    for offset in offsets {
+        out.write_all(&offset.from_beginning_of_file.to_be_bytes())?;
+        out.write_all(&offset.num_entries.to_be_bytes())?;
+    }
+    Ok(())
+}
+
 pub fn find(extensions: &[u8], object_hash: gix_hash::Kind) -> Option<Vec<Offset>> {
     extension::Iter::new_without_checksum(extensions, object_hash)?
         .find_map(|(sig, ext_data)| (sig == SIGNATURE).then_some(ext_data))
@@ -1,32 +1,40 @@
+use std::convert::TryFrom;
+
 use bstr::BString;
 use gix_hash::ObjectId;
 
 use crate::{
+    entry,
     extension::Signature,
     util::{split_at_byte_exclusive, split_at_pos},
 };
 
+/// All paths for which conflicts were resolved, along with the stages they had before resolution, sorted by path.
 pub type Paths = Vec<ResolvePath>;
 
-#[allow(dead_code)]
-#[derive(Clone)]
+/// A path whose conflict was resolved, and the entries it had in the index before that.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ResolvePath {
     /// relative to the root of the repository, or what would be stored in the index
-    name: BString,
+    pub name: BString,
 
     /// 0 = ancestor/common, 1 = ours, 2 = theirs
-    stages: [Option<Stage>; 3],
+    pub stages: [Option<Stage>; 3],
 }
 
-#[allow(dead_code)]
-#[derive(Clone, Copy)]
+/// An entry at one of the conflict stages before the conflict was resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Stage {
-    mode: u32,
-    id: ObjectId,
+    /// The mode of the entry.
+    pub mode: entry::Mode,
+    /// The id of the object the entry pointed to.
+    pub id: ObjectId,
 }
 
+/// The signature of the resolve-undo extension.
 pub const SIGNATURE: Signature = *b"REUC";
 
+/// Decode the resolve-undo extension from `data` with ids of kind `object_hash`.
 pub fn decode(mut data: &[u8], object_hash: gix_hash::Kind) -> Option<Paths> {
     let hash_len = object_hash.len_in_bytes();
     let mut out = Vec::new();
@@ -50,7 +58,7 @@ pub fn decode(mut data: &[u8], object_hash: gix_hash::Kind) -> Option<Paths> {
             let (hash, rest) = split_at_pos(data, hash_len)?;
             data = rest;
             *stage = Some(Stage {
-                mode: *mode,
+                mode: entry::Mode::from_bits_truncate(*mode),
                 id: ObjectId::from_bytes_or_panic(hash),
             });
         }
@@ -62,3 +70,24 @@ pub fn decode(mut data: &[u8], object_hash: gix_hash::Kind) -> Option<Paths> {
     }
     out.into()
 }
+
+/// Serialize all `paths` as resolve-undo extension to `out`.
+pub fn write_to(mut out: impl std::io::Write, paths: &[ResolvePath]) -> std::io::Result<()> {
+    let mut buf = Vec::new();
+    for path in paths {
+        buf.extend_from_slice(&path.name);
+        buf.push(0);
+        for stage in &path.stages {
+            let mode = stage.map_or(0, |stage| stage.mode.bits());
+            buf.extend_from_slice(format!("{mode:o}").as_bytes());
+            buf.push(0);
+        }
+        for stage in path.stages.iter().flatten() {
+            buf.extend_from_slice(stage.id.as_bytes());
+        }
+    }
+
+    out.write_all(&SIGNATURE)?;
+    out.write_all(&(u32::try_from(buf.len()).expect("less than 4GB resolve-undo extension")).to_be_bytes())?;
+    out.write_all(&buf)
+}
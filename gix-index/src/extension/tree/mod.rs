@@ -1,8 +1,34 @@
-use crate::extension::Signature;
+use bstr::{BStr, ByteSlice};
+
+use crate::extension::{Signature, Tree};
 
 /// The signature for tree extensions
 pub const SIGNATURE: Signature = *b"TREE";
 
+/// Mutation
+impl Tree {
+    /// Mark this tree and all trees leading to `path`, a slash-separated path relative to this tree, as invalid so their
+    /// ids will not be used anymore, just like `git` does when the entry at `path` was added, changed or removed.
+    ///
+    /// If `path` names a tree itself, it will be removed as it can't be valid anymore.
+    pub fn invalidate_path(&mut self, path: &BStr) {
+        self.num_entries = None;
+        let (name, rest) = match path.find_byte(b'/') {
+            Some(pos) => (&path[..pos], Some(path[pos + 1..].as_bstr())),
+            None => (path, None),
+        };
+        let Some(pos) = self.children.iter().position(|child| child.name.as_slice() == name) else {
+            return;
+        };
+        match rest {
+            Some(rest) => self.children[pos].invalidate_path(rest),
+            None => {
+                self.children.remove(pos);
+            }
+        }
+    }
+}
+
 ///
 pub mod verify;
 
@@ -1,16 +1,16 @@
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 
-use bstr::BString;
+use bstr::{BStr, BString};
 use gix_hash::ObjectId;
 
 use crate::{
     entry,
     extension::{Signature, UntrackedCache},
-    util::{read_u32, split_at_byte_exclusive, split_at_pos, var_int},
+    util::{read_u32, split_at_byte_exclusive, split_at_pos, var_int, write_var_int},
 };
 
 /// A structure to track filesystem stat information along with an object id, linking a worktree file with what's in our ODB.
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct OidStat {
     /// The file system stat information
     pub stat: entry::Stat,
@@ -19,7 +19,7 @@ pub struct OidStat {
 }
 
 /// A directory with information about its untracked files, and its sub-directories
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Directory {
     /// The directories name, or an empty string if this is the root directory.
     pub name: BString,
@@ -28,18 +28,20 @@ pub struct Directory {
     /// indices for sub-directories similar to this one.
     pub sub_directories: Vec<usize>,
 
-    /// The directories stat data, if available or valid // TODO: or is it the exclude file?
+    /// The directories stat data, if available and valid.
+    ///
+    /// If the directory's current stat information matches, the list of `untracked_entries` can be used
+    /// without reading the directory again.
     pub stat: Option<entry::Stat>,
     /// The oid of a .gitignore file, if it exists
     pub exclude_file_oid: Option<ObjectId>,
-    /// TODO: figure out what this really does
+    /// If `true`, the directory was only checked for containing untracked files, without listing them all.
     pub check_only: bool,
 }
 
 /// Only used as an indicator
 pub const SIGNATURE: Signature = *b"UNTR";
 
-// #[allow(unused)]
 /// Decode an untracked cache extension from `data`, assuming object hashes are of type `object_hash`.
 pub fn decode(data: &[u8], object_hash: gix_hash::Kind) -> Option<UntrackedCache> {
     if !data.last().map_or(false, |b| *b == 0) {
@@ -49,9 +51,11 @@ pub fn decode(data: &[u8], object_hash: gix_hash::Kind) -> Option<UntrackedCache
     let (identifier, data) = split_at_pos(data, identifier_len.try_into().ok()?)?;
 
     let hash_len = object_hash.len_in_bytes();
-    let (info_exclude, data) = decode_oid_stat(data, hash_len)?;
-    let (excludes_file, data) = decode_oid_stat(data, hash_len)?;
+    let (info_exclude_stat, data) = crate::decode::stat(data)?;
+    let (excludes_file_stat, data) = crate::decode::stat(data)?;
     let (dir_flags, data) = read_u32(data)?;
+    let (info_exclude, data) = decode_oid_stat(info_exclude_stat, data, hash_len)?;
+    let (excludes_file, data) = decode_oid_stat(excludes_file_stat, data, hash_len)?;
     let (exclude_filename_per_dir, data) = split_at_byte_exclusive(data, 0)?;
 
     let (num_directory_blocks, data) = var_int(data)?;
@@ -143,8 +147,7 @@ fn decode_directory_block<'a>(data: &'a [u8], directories: &mut Vec<Directory>)
     data.into()
 }
 
-fn decode_oid_stat(data: &[u8], hash_len: usize) -> Option<(OidStat, &[u8])> {
-    let (stat, data) = crate::decode::stat(data)?;
+fn decode_oid_stat(stat: entry::Stat, data: &[u8], hash_len: usize) -> Option<(OidStat, &[u8])> {
     let (hash, data) = split_at_pos(data, hash_len)?;
     Some((
         OidStat {
@@ -154,3 +157,217 @@ fn decode_oid_stat(data: &[u8], hash_len: usize) -> Option<(OidStat, &[u8])> {
         data,
     ))
 }
+
+/// Lifecycle
+impl UntrackedCache {
+    /// Create a new instance for the worktree and machine described by `identifier`, from the current state of
+    /// the `.git/info/exclude` file and the file configured in `core.excludesFile` (`info_exclude` and `excludes_file`),
+    /// the name of the per-directory exclude files (usually `.gitignore`), the `dir_flags` of the directory walk that
+    /// produced the cache, and all `directories` that were seen, with the root directory first.
+    pub fn new(
+        identifier: BString,
+        info_exclude: Option<OidStat>,
+        excludes_file: Option<OidStat>,
+        exclude_filename_per_dir: BString,
+        dir_flags: u32,
+        directories: Vec<Directory>,
+    ) -> Self {
+        UntrackedCache {
+            identifier,
+            info_exclude,
+            excludes_file,
+            exclude_filename_per_dir,
+            dir_flags,
+            directories,
+        }
+    }
+}
+
+/// Mutation
+impl UntrackedCache {
+    /// Mark the directory containing `path`, a slash-separated path relative to the worktree root, and all directories
+    /// leading to it as invalid so their untracked entries will not be used anymore, just like `git` does when the entry
+    /// at `path` was added to or removed from the index.
+    pub fn invalidate_path(&mut self, path: &BStr) {
+        if self.directories.is_empty() {
+            return;
+        }
+        let mut dir_index = 0;
+        let mut components = path.split(|b| *b == b'/').peekable();
+        loop {
+            let dir = &mut self.directories[dir_index];
+            dir.stat = None;
+            dir.untracked_entries.clear();
+            let Some(component) = components.next() else { break };
+            if components.peek().is_none() {
+                break;
+            }
+            let Some(sub_dir) = self.directories[dir_index]
+                .sub_directories
+                .iter()
+                .copied()
+                .find(|idx| self.directories[*idx].name == component)
+            else {
+                break;
+            };
+            dir_index = sub_dir;
+        }
+    }
+}
+
+/// Access
+impl UntrackedCache {
+    /// Return the identifier of the location and machine this cache was created for, typically
+    /// `Location <worktree>, system <os>` followed by a null-byte.
+    pub fn identifier(&self) -> &BStr {
+        self.identifier.as_ref()
+    }
+
+    /// The stat and id of the `.git/info/exclude` file, if it was present.
+    pub fn info_exclude(&self) -> Option<&OidStat> {
+        self.info_exclude.as_ref()
+    }
+
+    /// The stat and id of the file configured in `core.excludesFile`, if it was present.
+    pub fn excludes_file(&self) -> Option<&OidStat> {
+        self.excludes_file.as_ref()
+    }
+
+    /// The name of the file to read exclude patterns from in each directory, usually `.gitignore`.
+    pub fn exclude_filename_per_dir(&self) -> &BStr {
+        self.exclude_filename_per_dir.as_ref()
+    }
+
+    /// The flags of `git`s directory walk that were active when the cache was created.
+    pub fn dir_flags(&self) -> u32 {
+        self.dir_flags
+    }
+
+    /// All directories, with the first one being the root directory of the worktree, if there is any directory at all.
+    ///
+    /// Sub-directories refer to each other by their index in this list.
+    pub fn directories(&self) -> &[Directory] {
+        &self.directories
+    }
+
+    /// Find the directory at the slash-separated `rela_path`, relative to the worktree root, or `None` if it isn't cached.
+    ///
+    /// Use an empty path to obtain the root directory.
+    pub fn directory_by_path(&self, rela_path: &BStr) -> Option<&Directory> {
+        let mut dir = self.directories.first()?;
+        for component in rela_path.split(|b| *b == b'/').filter(|c| !c.is_empty()) {
+            dir = dir
+                .sub_directories
+                .iter()
+                .map(|idx| &self.directories[*idx])
+                .find(|d| d.name == component)?;
+        }
+        Some(dir)
+    }
+}
+
+/// Serialize this instance to `out`.
+impl UntrackedCache {
+    /// Serialize this instance to `out` in a way that is compatible with `git`.
+    pub fn write_to(&self, mut out: impl std::io::Write, object_hash: gix_hash::Kind) -> std::io::Result<()> {
+        let mut buf = Vec::with_capacity(self.identifier.len() + 64 * self.directories.len());
+        let no_stat = entry::Stat::default();
+
+        write_var_int(&mut buf, self.identifier.len() as u64)?;
+        buf.extend_from_slice(&self.identifier);
+        crate::write::stat(&mut buf, self.info_exclude.as_ref().map_or(&no_stat, |s| &s.stat))?;
+        crate::write::stat(&mut buf, self.excludes_file.as_ref().map_or(&no_stat, |s| &s.stat))?;
+        buf.extend_from_slice(&self.dir_flags.to_be_bytes());
+        for oid_stat in [&self.info_exclude, &self.excludes_file] {
+            match oid_stat {
+                Some(oid_stat) => buf.extend_from_slice(oid_stat.id.as_bytes()),
+                None => buf.extend(std::iter::repeat(0).take(object_hash.len_in_bytes())),
+            }
+        }
+        buf.extend_from_slice(&self.exclude_filename_per_dir);
+        buf.push(0);
+
+        if self.directories.is_empty() {
+            write_var_int(&mut buf, 0)?;
+        } else {
+            let mut valid = gix_bitmap::ewah::Vec::new();
+            let mut check_only = gix_bitmap::ewah::Vec::new();
+            let mut hash_valid = gix_bitmap::ewah::Vec::new();
+            let mut stats = Vec::new();
+            let mut hashes = Vec::new();
+            let mut blocks = Vec::new();
+            let mut index = 0;
+            write_directory_block(
+                &self.directories,
+                0,
+                &mut index,
+                &mut blocks,
+                &mut Bitmaps {
+                    valid: &mut valid,
+                    check_only: &mut check_only,
+                    hash_valid: &mut hash_valid,
+                    stats: &mut stats,
+                    hashes: &mut hashes,
+                },
+            )?;
+
+            write_var_int(&mut buf, index as u64)?;
+            buf.extend_from_slice(&blocks);
+            valid.write_to(&mut buf)?;
+            check_only.write_to(&mut buf)?;
+            hash_valid.write_to(&mut buf)?;
+            buf.extend_from_slice(&stats);
+            buf.extend_from_slice(&hashes);
+        }
+        buf.push(0);
+
+        out.write_all(&SIGNATURE)?;
+        out.write_all(&(u32::try_from(buf.len()).expect("less than 4GB untracked cache extension")).to_be_bytes())?;
+        out.write_all(&buf)
+    }
+}
+
+struct Bitmaps<'a> {
+    valid: &'a mut gix_bitmap::ewah::Vec,
+    check_only: &'a mut gix_bitmap::ewah::Vec,
+    hash_valid: &'a mut gix_bitmap::ewah::Vec,
+    stats: &'a mut Vec<u8>,
+    hashes: &'a mut Vec<u8>,
+}
+
+fn write_directory_block(
+    directories: &[Directory],
+    dir_index: usize,
+    index: &mut usize,
+    out: &mut Vec<u8>,
+    bitmaps: &mut Bitmaps<'_>,
+) -> std::io::Result<()> {
+    let dir = &directories[dir_index];
+    let bit = *index;
+    *index += 1;
+
+    if let Some(stat) = &dir.stat {
+        bitmaps.valid.set(bit);
+        crate::write::stat(&mut *bitmaps.stats, stat)?;
+    }
+    if dir.check_only {
+        bitmaps.check_only.set(bit);
+    }
+    if let Some(id) = &dir.exclude_file_oid {
+        bitmaps.hash_valid.set(bit);
+        bitmaps.hashes.extend_from_slice(id.as_bytes());
+    }
+
+    write_var_int(&mut *out, dir.untracked_entries.len() as u64)?;
+    write_var_int(&mut *out, dir.sub_directories.len() as u64)?;
+    out.extend_from_slice(&dir.name);
+    out.push(0);
+    for name in &dir.untracked_entries {
+        out.extend_from_slice(name);
+        out.push(0);
+    }
+    for sub_dir_index in &dir.sub_directories {
+        write_directory_block(directories, *sub_dir_index, index, out, bitmaps)?;
+    }
+    Ok(())
+}
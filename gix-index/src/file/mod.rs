@@ -28,6 +28,7 @@ mod impl_ {
             f.debug_struct("File")
                 .field("path", &self.path.display())
                 .field("checksum", &self.checksum)
+                .field("shared_index_checksum", &self.shared_index_checksum)
                 .finish_non_exhaustive()
         }
     }
@@ -63,6 +64,14 @@ mod access {
         pub fn checksum(&self) -> Option<gix_hash::ObjectId> {
             self.checksum
         }
+
+        /// The checksum of the shared index this split index is based on, as stored in its `link` extension,
+        /// or `None` if this isn't a split index.
+        ///
+        /// It's used to only store changes compared to the shared index when [writing a split index][crate::write::Options::split_index].
+        pub fn shared_index_checksum(&self) -> Option<gix_hash::ObjectId> {
+            self.shared_index_checksum
+        }
     }
 }
 
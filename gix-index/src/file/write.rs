@@ -1,6 +1,8 @@
+use std::{cmp::Ordering, path::Path};
+
 use gix_features::hash;
 
-use crate::{write, File, Version};
+use crate::{entry, extension, write, Entry, File, State, Version};
 
 /// The error produced by [`File::write()`].
 #[derive(Debug, thiserror::Error)]
@@ -18,19 +20,29 @@ impl File {
     /// Write the index to `out` with `options`, to be readable by [`File::at()`], returning the version that was actually written
     /// to retain all information of this index.
     pub fn write_to(
+        &self,
+        out: impl std::io::Write,
+        options: write::Options,
+    ) -> std::io::Result<(Version, gix_hash::ObjectId)> {
+        self.write_entries_to(out, options, self.entries(), None)
+    }
+
+    fn write_entries_to(
         &self,
         mut out: impl std::io::Write,
         options: write::Options,
+        entries: &[Entry],
+        link: Option<&extension::Link>,
     ) -> std::io::Result<(Version, gix_hash::ObjectId)> {
         let _span = gix_features::trace::detail!("gix_index::File::write_to()", skip_hash = options.skip_hash);
         let (version, hash) = if options.skip_hash {
             let out: &mut dyn std::io::Write = &mut out;
-            let version = self.state.write_to(out, options)?;
+            let version = self.state.write_entries_to(out, options, entries, link)?;
             (version, self.state.object_hash.null())
         } else {
             let mut hasher = hash::Write::new(&mut out, self.state.object_hash);
             let out: &mut dyn std::io::Write = &mut hasher;
-            let version = self.state.write_to(out, options)?;
+            let version = self.state.write_entries_to(out, options, entries, link)?;
             (version, gix_hash::ObjectId::from(hasher.hash.digest()))
         };
         out.write_all(hash.as_slice())?;
@@ -39,20 +51,219 @@ impl File {
 
     /// Write ourselves to the path we were read from after acquiring a lock, using `options`.
     ///
+    /// If [`options.split_index`](write::Options::split_index) is set, a split index is written along with a shared index
+    /// if needed, otherwise a complete index is written even if it was read from a split index.
+    ///
     /// Note that the hash produced will be stored which is why we need to be mutable.
     pub fn write(&mut self, options: write::Options) -> Result<(), Error> {
         let _span = gix_features::trace::detail!("gix_index::File::write()", path = ?self.path);
-        let mut lock = std::io::BufWriter::with_capacity(
-            64 * 1024,
-            gix_lock::File::acquire_to_update_resource(&self.path, gix_lock::acquire::Fail::Immediately, None)?,
-        );
-        let (version, digest) = self.write_to(&mut lock, options)?;
-        match lock.into_inner() {
-            Ok(lock) => lock.commit()?,
-            Err(err) => return Err(err.into_error().into()),
+        let (version, digest, shared_index_checksum) = match options.split_index {
+            Some(split_index) => {
+                let (shared_index_checksum, delta) = self.prepare_split_index(split_index, options.version)?;
+                let link = extension::Link {
+                    shared_index_checksum,
+                    bitmaps: Some(delta.bitmaps),
+                };
+                let (version, digest) = write_locked(&self.path, |out| {
+                    self.write_entries_to(out, options, &delta.entries, Some(&link))
+                })?;
+                (version, digest, Some(shared_index_checksum))
+            }
+            None => {
+                let (version, digest) = write_locked(&self.path, |out| self.write_to(out, options))?;
+                (version, digest, None)
+            }
         };
         self.state.version = version;
         self.checksum = Some(digest);
+        self.shared_index_checksum = shared_index_checksum;
         Ok(())
     }
 }
+
+/// Split index support
+impl File {
+    /// Return the checksum of the shared index to use for writing a split index along with the changes compared to it.
+    ///
+    /// A new shared index is written if there is none, or if too many entries would have to be stored in the split index.
+    fn prepare_split_index(
+        &self,
+        options: write::SplitIndex,
+        version: Option<Version>,
+    ) -> Result<(gix_hash::ObjectId, SplitDelta), Error> {
+        let dir = self.path.parent().expect("index files are always in a directory");
+        if let Some(checksum) = self.shared_index_checksum {
+            if let Some(shared) = self.load_shared_index(dir, checksum) {
+                let delta = SplitDelta::new(&shared, &self.state);
+                let num_entries = self
+                    .entries()
+                    .iter()
+                    .filter(|e| !e.flags.contains(entry::Flags::REMOVE))
+                    .count();
+                let too_many_not_shared = match options.max_percent_change {
+                    0 => true,
+                    100.. => false,
+                    max => delta.num_not_shared * 100 > usize::from(max) * num_entries,
+                };
+                if !too_many_not_shared {
+                    filetime::set_file_mtime(shared_index_path(dir, checksum), filetime::FileTime::now()).ok();
+                    return Ok((checksum, delta));
+                }
+            }
+        }
+
+        let mut buf = Vec::new();
+        let (_version, checksum) = self.write_to(
+            &mut buf,
+            write::Options {
+                extensions: write::Extensions::None,
+                version,
+                thread_limit: None,
+                skip_hash: false,
+                split_index: None,
+            },
+        )?;
+        let path = shared_index_path(dir, checksum);
+        if !path.is_file() {
+            write_locked(&path, |out| out.write_all(&buf))?;
+        }
+        if let Some(expire) = options.shared_index_expire {
+            remove_expired_shared_indices(dir, &path, expire)?;
+        }
+        Ok((checksum, SplitDelta::default()))
+    }
+
+    fn load_shared_index(&self, dir: &Path, checksum: gix_hash::ObjectId) -> Option<State> {
+        File::at(
+            shared_index_path(dir, checksum),
+            self.object_hash,
+            false,
+            crate::decode::Options {
+                expected_checksum: Some(checksum),
+                ..Default::default()
+            },
+        )
+        .ok()
+        .map(Into::into)
+    }
+}
+
+/// The changes of an index compared to its shared index, as stored in a split index.
+#[derive(Default)]
+struct SplitDelta {
+    /// Entries that replace shared entries, with empty paths and in the order of the replace bitmap,
+    /// followed by all entries that aren't present in the shared index.
+    entries: Vec<Entry>,
+    bitmaps: extension::link::Bitmaps,
+    /// The amount of entries that aren't present in the shared index at all.
+    num_not_shared: usize,
+}
+
+impl SplitDelta {
+    fn new(shared: &State, current: &State) -> Self {
+        let mut replaced = Vec::new();
+        let mut added = Vec::new();
+        let mut bitmaps = extension::link::Bitmaps::default();
+
+        let mut shared_entries = shared.entries().iter().enumerate().peekable();
+        let mut current_entries = current
+            .entries()
+            .iter()
+            .filter(|e| !e.flags.contains(entry::Flags::REMOVE))
+            .peekable();
+        loop {
+            let ordering = match (shared_entries.peek(), current_entries.peek()) {
+                (Some((_, shared_entry)), Some(current_entry)) => shared_entry
+                    .path(shared)
+                    .cmp(current_entry.path(current))
+                    .then_with(|| shared_entry.stage().cmp(&current_entry.stage())),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            match ordering {
+                Ordering::Less => {
+                    let (idx, _) = shared_entries.next().expect("peeked");
+                    bitmaps.delete.set(idx);
+                }
+                Ordering::Greater => {
+                    added.push(current_entries.next().expect("peeked").clone());
+                }
+                Ordering::Equal => {
+                    let (idx, shared_entry) = shared_entries.next().expect("peeked");
+                    let current_entry = current_entries.next().expect("peeked");
+                    if !is_same_at_rest(shared_entry, current_entry) {
+                        bitmaps.replace.set(idx);
+                        replaced.push(Entry {
+                            path: 0..0,
+                            ..current_entry.clone()
+                        });
+                    }
+                }
+            }
+        }
+
+        let num_not_shared = added.len();
+        replaced.extend(added);
+        SplitDelta {
+            entries: replaced,
+            bitmaps,
+            num_not_shared,
+        }
+    }
+}
+
+/// Return `true` if `a` and `b` would be serialized the same, ignoring their path.
+fn is_same_at_rest(a: &Entry, b: &Entry) -> bool {
+    a.id == b.id
+        && a.mode == b.mode
+        && a.stat == b.stat
+        && a.flags.to_storage().bits() == b.flags.to_storage().bits()
+        && entry::at_rest::FlagsExtended::from_flags(a.flags).bits()
+            == entry::at_rest::FlagsExtended::from_flags(b.flags).bits()
+}
+
+fn shared_index_path(dir: &Path, checksum: gix_hash::ObjectId) -> std::path::PathBuf {
+    dir.join(format!("sharedindex.{checksum}"))
+}
+
+/// Delete all shared indices in `dir` except for `current` which weren't modified since `expire`.
+fn remove_expired_shared_indices(dir: &Path, current: &Path, expire: std::time::SystemTime) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let is_shared_index = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix("sharedindex."))
+            .map_or(false, |checksum| !checksum.ends_with(".lock"));
+        if !is_shared_index || path == current {
+            continue;
+        }
+        let is_expired = entry.metadata()?.modified()? < expire;
+        if is_expired {
+            match std::fs::remove_file(&path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write to `path` through a lock file with `write`, and commit it when done.
+fn write_locked<T>(
+    path: &Path,
+    write: impl FnOnce(&mut dyn std::io::Write) -> std::io::Result<T>,
+) -> Result<T, Error> {
+    let mut lock = std::io::BufWriter::with_capacity(
+        64 * 1024,
+        gix_lock::File::acquire_to_update_resource(path, gix_lock::acquire::Fail::Immediately, None)?,
+    );
+    let res = write(&mut lock)?;
+    match lock.into_inner() {
+        Ok(lock) => lock.commit()?,
+        Err(err) => return Err(err.into_error().into()),
+    };
+    Ok(res)
+}
@@ -33,6 +33,12 @@ pub mod verify;
 ///
 pub mod write;
 
+///
+pub mod sparse;
+
+///
+pub mod write_tree;
+
 pub mod fs;
 
 /// All known versions of a git index file.
@@ -88,6 +94,8 @@ pub struct File {
     pub(crate) path: PathBuf,
     /// The checksum of all bytes prior to the checksum itself.
     pub(crate) checksum: Option<gix_hash::ObjectId>,
+    /// The checksum of the shared index this index was split from when it was read or written, if it was a split index.
+    pub(crate) shared_index_checksum: Option<gix_hash::ObjectId>,
 }
 
 /// The type to use and store paths to all entries.
@@ -170,6 +178,24 @@ pub(crate) mod util {
         (num, data).into()
     }
 
+    /// Write `n` as variable-length integer in the format understood by [`var_int()`].
+    #[inline]
+    pub fn write_var_int(mut out: impl std::io::Write, mut n: u64) -> std::io::Result<()> {
+        let mut buf = [0u8; 10];
+        let mut pos = buf.len() - 1;
+        buf[pos] = n as u8 & 0b0111_1111;
+        loop {
+            n >>= 7;
+            if n == 0 {
+                break;
+            }
+            n -= 1;
+            pos -= 1;
+            buf[pos] = 0b1000_0000 | (n as u8 & 0b0111_1111);
+        }
+        out.write_all(&buf[pos..])
+    }
+
     #[inline]
     pub fn read_u32(data: &[u8]) -> Option<(u32, &[u8])> {
         split_at_pos(data, 4).map(|(num, data)| (u32::from_be_bytes(num.try_into().unwrap()), data))
@@ -0,0 +1,354 @@
+use std::{collections::BTreeSet, ops::Range};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_object::{tree::EntryKind, FindExt};
+
+use crate::{
+    entry::{Flags, Mode, Stat},
+    write_tree::{directory_entries, write_trees},
+    Entry, PathStorageRef, State,
+};
+
+/// The directories included in a sparse checkout in *cone mode*, which is required for sparse indices.
+///
+/// All files in the root of the worktree are always included.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cone {
+    /// Directories whose entire content is included, without leading or trailing slash.
+    recursive: BTreeSet<BString>,
+    /// Directories whose files are included, but not their sub-directories unless these are included as well.
+    parents: BTreeSet<BString>,
+}
+
+/// How a directory relates to the [cone](Cone) of a sparse checkout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum DirectoryMatch {
+    /// Nothing in the directory is included.
+    Excluded,
+    /// Some of its content is included, but not all.
+    Partial,
+    /// The directory and all its content is included.
+    Recursive,
+}
+
+/// Lifecycle
+impl Cone {
+    /// Create a new instance which includes all given `directories` recursively, which are slash-separated and relative to
+    /// the worktree root.
+    pub fn from_directories(directories: impl IntoIterator<Item = impl AsRef<BStr>>) -> Self {
+        let mut cone = Cone::default();
+        for dir in directories {
+            cone.add_directory(dir.as_ref());
+        }
+        cone
+    }
+
+    /// Parse cone-mode patterns from `data`, the content of a `sparse-checkout` file, or return `None` if they contain a
+    /// pattern that can't be used in cone-mode.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let mut cone = Cone::default();
+        for line in data.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(b"#") || line == b"/*" || line == b"!/*/" {
+                continue;
+            }
+            if let Some(parent) = line.strip_prefix(b"!").and_then(|l| l.strip_suffix(b"/*/")) {
+                let dir = directory_from_pattern(parent)?;
+                if !cone.recursive.remove(dir) {
+                    return None;
+                }
+                cone.parents.insert(dir.to_owned());
+            } else {
+                let dir = line.strip_suffix(b"/").and_then(directory_from_pattern)?;
+                cone.add_directory(dir);
+            }
+        }
+        Some(cone)
+    }
+}
+
+/// Mutation
+impl Cone {
+    /// Include the slash-separated `directory` along with all of its content, and all files in its parent directories.
+    pub fn add_directory(&mut self, directory: &BStr) {
+        let directory = directory.trim_with(|c| c == '/').as_bstr();
+        if directory.is_empty() {
+            return;
+        }
+        self.recursive.insert(directory.to_owned());
+        let mut parent = directory;
+        while let Some(pos) = parent.rfind_byte(b'/') {
+            parent = parent[..pos].as_bstr();
+            self.parents.insert(parent.to_owned());
+        }
+    }
+}
+
+/// Access
+impl Cone {
+    /// Return all directories whose content is included entirely, without any of their sub-directories.
+    pub fn directories(&self) -> impl Iterator<Item = &BStr> + '_ {
+        self.recursive
+            .iter()
+            .filter(|dir| !self.has_recursive_parent(dir.as_bstr()))
+            .map(|dir| dir.as_bstr())
+    }
+
+    /// Return `true` if the file at `rela_path` is included in this cone.
+    pub fn includes_file(&self, rela_path: &BStr) -> bool {
+        match rela_path.rfind_byte(b'/') {
+            None => true,
+            Some(pos) => {
+                let dir = rela_path[..pos].as_bstr();
+                self.parents.contains(dir) || self.recursive.contains(dir) || self.has_recursive_parent(dir)
+            }
+        }
+    }
+
+    /// Determine how the directory at `rela_path` relates to this cone.
+    pub fn directory_match(&self, rela_path: &BStr) -> DirectoryMatch {
+        if self.recursive.contains(rela_path) || self.has_recursive_parent(rela_path) {
+            DirectoryMatch::Recursive
+        } else if self.parents.contains(rela_path) {
+            DirectoryMatch::Partial
+        } else {
+            DirectoryMatch::Excluded
+        }
+    }
+
+    /// Serialize this instance to `out` in the format of a `sparse-checkout` file, just like `git` would.
+    pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        out.write_all(b"/*\n!/*/\n")?;
+        for parent in self
+            .parents
+            .iter()
+            .filter(|dir| !self.recursive.contains(*dir) && !self.has_recursive_parent(dir.as_bstr()))
+        {
+            writeln!(out, "/{parent}/\n!/{parent}/*/")?;
+        }
+        for dir in self.directories() {
+            writeln!(out, "/{dir}/")?;
+        }
+        Ok(())
+    }
+
+    fn has_recursive_parent(&self, mut dir: &BStr) -> bool {
+        while let Some(pos) = dir.rfind_byte(b'/') {
+            dir = dir[..pos].as_bstr();
+            if self.recursive.contains(dir) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Turn `/dir` into `dir` if it is a cone-mode pattern, which is anchored and without wildcards.
+fn directory_from_pattern(pattern: &[u8]) -> Option<&BStr> {
+    let dir = pattern.strip_prefix(b"/")?;
+    (!dir.is_empty() && !dir.iter().any(|b| matches!(b, b'*' | b'?' | b'[' | b'\\' | b'!'))).then(|| dir.as_bstr())
+}
+
+///
+pub mod expand {
+    /// The error returned by [`State::expand_sparse()`](crate::State::expand_sparse()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindTree(#[from] gix_object::find::existing_iter::Error),
+        #[error(transparent)]
+        DecodeTree(#[from] gix_object::decode::Error),
+    }
+}
+
+///
+pub mod collapse {
+    /// The error returned by [`State::collapse_sparse()`](crate::State::collapse_sparse()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not write the tree for sparse directory '{rela_path}'")]
+        WriteTree {
+            rela_path: bstr::BString,
+            source: Box<dyn std::error::Error + Send + Sync + 'static>,
+        },
+    }
+}
+
+/// Sparse indices
+impl State {
+    /// Replace all [sparse directory entries](Mode::DIR) with the entries of the trees they point to, which are
+    /// obtained from `objects`, turning this instance into a regular index. Return the amount of expanded directories.
+    ///
+    /// All new entries are marked as *skip-worktree*, and have no stat information as they don't exist in the worktree.
+    /// This is what `git` does before running commands that can't handle sparse indices.
+    pub fn expand_sparse<Find>(&mut self, objects: Find) -> Result<usize, expand::Error>
+    where
+        Find: gix_object::Find,
+    {
+        let sparse_dirs: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|e| e.mode.is_sparse())
+            .map(|e| (e.id, e.path_in(&self.path_backing).to_owned()))
+            .collect();
+        self.is_sparse = false;
+        if sparse_dirs.is_empty() {
+            return Ok(0);
+        }
+
+        let mut buf = Vec::new();
+        let mut new_entries = Vec::new();
+        let mut trees = Vec::new();
+        for (tree_id, dir) in &sparse_dirs {
+            trees.push((*tree_id, dir.clone()));
+            while let Some((tree_id, prefix)) = trees.pop() {
+                for entry in objects.find_tree_iter(&tree_id, &mut buf)? {
+                    let entry = entry?;
+                    let mut path = prefix.clone();
+                    path.push_str(entry.filename);
+                    let mode = match entry.mode.kind() {
+                        EntryKind::Tree => {
+                            path.push(b'/');
+                            trees.push((entry.oid.to_owned(), path));
+                            continue;
+                        }
+                        EntryKind::Blob => Mode::FILE,
+                        EntryKind::BlobExecutable => Mode::FILE_EXECUTABLE,
+                        EntryKind::Link => Mode::SYMLINK,
+                        EntryKind::Commit => Mode::COMMIT,
+                    };
+                    let path_start = self.path_backing.len();
+                    self.path_backing.extend_from_slice(&path);
+                    new_entries.push(Entry {
+                        stat: Stat::default(),
+                        id: entry.oid.to_owned(),
+                        flags: Flags::SKIP_WORKTREE | Flags::EXTENDED,
+                        mode,
+                        path: path_start..self.path_backing.len(),
+                    });
+                }
+            }
+        }
+
+        self.entries.retain(|e| !e.mode.is_sparse());
+        self.entries.extend(new_entries);
+        self.sort_entries();
+        if let Some(tree) = self.tree.as_mut() {
+            for (_, dir) in &sparse_dirs {
+                tree.invalidate_path(dir[..dir.len() - 1].as_bstr());
+            }
+        }
+        Ok(sparse_dirs.len())
+    }
+
+    /// Replace all entries in directories outside of `cone` with [sparse directory entries](Mode::DIR) that point to
+    /// the tree of each directory, turning this instance into a sparse index. Return the amount of new sparse directory entries.
+    ///
+    /// `write_tree` is called to store each tree that is needed to represent a directory, and should return its id.
+    /// Note that trees are also written if they already exist in the object database.
+    ///
+    /// Just like `git`, a directory is only collapsed if all of its entries are marked as *skip-worktree*, and nothing is
+    /// collapsed if there are conflicting entries.
+    pub fn collapse_sparse<E>(
+        &mut self,
+        cone: &Cone,
+        mut write_tree: impl FnMut(&gix_object::Tree) -> Result<gix_hash::ObjectId, E>,
+    ) -> Result<usize, collapse::Error>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        if self.entries.iter().any(|e| e.stage() != 0) {
+            return Ok(0);
+        }
+        let mut collapsible = Vec::new();
+        find_collapsible(&self.entries, &self.path_backing, 0, 0, cone, &mut collapsible);
+        if collapsible.is_empty() {
+            return Ok(0);
+        }
+
+        let mut sparse_dirs = Vec::with_capacity(collapsible.len());
+        for (range, dir) in &collapsible {
+            let id = write_trees(
+                &self.entries[range.clone()],
+                &self.path_backing,
+                dir.len() + 1,
+                &mut write_tree,
+            )
+            .map_err(|source| collapse::Error::WriteTree {
+                rela_path: dir.clone(),
+                source: Box::new(source),
+            })?;
+            sparse_dirs.push(id);
+        }
+
+        let old_entries = std::mem::take(&mut self.entries);
+        let mut entries = Vec::with_capacity(old_entries.len());
+        let mut start = 0;
+        for ((range, dir), id) in collapsible.iter().zip(sparse_dirs) {
+            entries.extend_from_slice(&old_entries[start..range.start]);
+            start = range.end;
+            let path_start = self.path_backing.len();
+            self.path_backing.extend_from_slice(dir);
+            self.path_backing.push(b'/');
+            entries.push(Entry {
+                stat: Stat::default(),
+                id,
+                flags: Flags::SKIP_WORKTREE | Flags::EXTENDED,
+                mode: Mode::DIR,
+                path: path_start..self.path_backing.len(),
+            });
+        }
+        entries.extend_from_slice(&old_entries[start..]);
+        self.entries = entries;
+        if let Some(tree) = self.tree.as_mut() {
+            for (_, dir) in &collapsible {
+                tree.invalidate_path(dir.as_bstr());
+            }
+        }
+        self.is_sparse = true;
+        Ok(collapsible.len())
+    }
+}
+
+/// Find all ranges of `entries` along with their directory which can be collapsed into a sparse directory entry
+/// as they are excluded by `cone`, with `offset` being the index of the first entry and `prefix_len` being the length
+/// of the directory containing all `entries`, including its trailing slash.
+fn find_collapsible(
+    entries: &[Entry],
+    paths: &PathStorageRef,
+    offset: usize,
+    prefix_len: usize,
+    cone: &Cone,
+    out: &mut Vec<(Range<usize>, BString)>,
+) {
+    let mut idx = 0;
+    while idx < entries.len() {
+        let path = entries[idx].path_in(paths);
+        let Some(pos) = path[prefix_len..].find_byte(b'/') else {
+            idx += 1;
+            continue;
+        };
+        let dir = &path[..prefix_len + pos];
+        let len = directory_entries(&entries[idx..], paths, dir);
+        let dir_entries = &entries[idx..][..len];
+        let is_sparse_dir = len == 1 && dir_entries[0].mode.is_sparse();
+        if !is_sparse_dir {
+            match cone.directory_match(dir.as_bstr()) {
+                DirectoryMatch::Recursive => {}
+                DirectoryMatch::Excluded
+                    if dir_entries
+                        .iter()
+                        .all(|e| e.flags.contains(Flags::SKIP_WORKTREE) && e.stage() == 0) =>
+                {
+                    out.push((offset + idx..offset + idx + len, dir.to_owned()));
+                }
+                DirectoryMatch::Excluded | DirectoryMatch::Partial => {
+                    find_collapsible(dir_entries, paths, offset + idx, dir.len() + 1, cone, out);
+                }
+            }
+        }
+        idx += len;
+    }
+}
@@ -1,6 +1,6 @@
 use std::{convert::TryInto, io::Write};
 
-use crate::{entry, extension, write::util::CountBytes, State, Version};
+use crate::{entry, extension, write::util::CountBytes, Entry, State, Version};
 
 /// A way to specify which of the optional extensions to write.
 #[derive(Default, Debug, Copy, Clone)]
@@ -17,8 +17,19 @@ pub enum Extensions {
     Given {
         /// Write the tree-cache extension, if present.
         tree_cache: bool,
+        /// Write the untracked-cache extension, if present.
+        untracked_cache: bool,
+        /// Write the filesystem monitor extension, if present.
+        fs_monitor: bool,
+        /// Write the resolve-undo extension, if present.
+        resolve_undo: bool,
         /// Write the end-of-index-entry extension.
         end_of_index_entry: bool,
+        /// Write the index-entry-offset-table extension if there are enough entries to make multi-threaded decoding worthwhile,
+        /// typically controlled by `index.recordOffsetTable`.
+        ///
+        /// Note that it can only be used by readers if the end-of-index-entry extension is written as well.
+        index_entry_offset_table: bool,
     },
     /// Write no optional extension at all for what should be the smallest possible index
     None,
@@ -32,10 +43,18 @@ impl Extensions {
             Extensions::All => Some(signature),
             Extensions::Given {
                 tree_cache,
+                untracked_cache,
+                fs_monitor,
+                resolve_undo,
                 end_of_index_entry,
+                index_entry_offset_table,
             } => match signature {
                 extension::tree::SIGNATURE => tree_cache,
+                extension::untracked_cache::SIGNATURE => untracked_cache,
+                extension::fs_monitor::SIGNATURE => fs_monitor,
+                extension::resolve_undo::SIGNATURE => resolve_undo,
                 extension::end_of_index_entry::SIGNATURE => end_of_index_entry,
+                extension::index_entry_offset_table::SIGNATURE => index_entry_offset_table,
                 _ => &false,
             }
             .then(|| signature),
@@ -50,44 +69,115 @@ impl Extensions {
 pub struct Options {
     /// Configures which extensions to write.
     pub extensions: Extensions,
+    /// The version of the index to write, typically controlled by `index.version` or `feature.manyFiles`.
+    ///
+    /// With [`Version::V4`], paths are stored prefix-compressed relative to the previous entry's path, which typically
+    /// makes the index much smaller. Otherwise, and just like `git` does, either V2 or V3 are written depending
+    /// on whether or not extended entry flags are needed.
+    pub version: Option<Version>,
+    /// The amount of threads readers of the index are expected to use, typically controlled by `index.threads`.
+    ///
+    /// It determines into how many blocks entries are split in the index-entry-offset-table extension, so they can be
+    /// decoded in parallel.
+    /// If `Some(1)`, the extension isn't written. If `None` or `Some(0)`, the amount of blocks depends on the amount
+    /// of entries and on the amount of logical cores, just like `git` does it.
+    pub thread_limit: Option<usize>,
     /// Set the trailing hash of the produced index to all zeroes to save some time.
     ///
     /// This value is typically controlled by `index.skipHash` and is respected when the index is written
     /// via [`File::write()`](crate::File::write()) and [`File::write_to()`](crate::File::write_to()).
     /// Note that
     pub skip_hash: bool,
+    /// If set, write a split index that only stores changes compared to a shared index, typically controlled by `core.splitIndex`.
+    ///
+    /// This is only respected by [`File::write()`](crate::File::write()) as it needs to know where to place the shared index.
+    /// If `None`, the shared index of a split index is dissolved and a complete index is written.
+    pub split_index: Option<SplitIndex>,
+}
+
+/// Options to control how a split index is written with [`File::write()`](crate::File::write()).
+///
+/// The split index is written to the path of the index, and the shared index is written to a `sharedindex.<checksum>` file next to it.
+#[derive(Debug, Clone, Copy)]
+pub struct SplitIndex {
+    /// The maximum percentage of entries that may be stored only in the split index before a new shared index is written,
+    /// as controlled by `splitIndex.maxPercentChange`.
+    ///
+    /// With `0`, a new shared index is written each time, and with `100` it's only written if there is no usable shared index.
+    pub max_percent_change: u8,
+    /// Shared index files that weren't modified since this time are deleted when a new shared index is written,
+    /// as controlled by `splitIndex.sharedIndexExpire`. If `None`, they are never deleted.
+    ///
+    /// Note that the shared index in use is touched each time the split index is written, so it doesn't expire.
+    pub shared_index_expire: Option<std::time::SystemTime>,
+}
+
+impl Default for SplitIndex {
+    /// Use the same defaults as `git`, which writes a new shared index when 20% of all entries aren't shared,
+    /// and expires shared indices after two weeks.
+    fn default() -> Self {
+        SplitIndex {
+            max_percent_change: 20,
+            shared_index_expire: std::time::SystemTime::now().checked_sub(std::time::Duration::from_secs(14 * 24 * 60 * 60)),
+        }
+    }
 }
 
 impl State {
     /// Serialize this instance to `out` with [`options`][Options].
-    pub fn write_to(
+    pub fn write_to(&self, out: impl std::io::Write, options: Options) -> std::io::Result<Version> {
+        self.write_entries_to(out, options, self.entries(), None)
+    }
+
+    /// Serialize `entries` with paths from this instance to `out`, along with all extensions of this instance and `link`.
+    ///
+    /// Note that replaced entries of a split index have an empty path.
+    pub(crate) fn write_entries_to(
         &self,
         out: impl std::io::Write,
         Options {
             extensions,
-            skip_hash: _,
+            version: desired_version,
+            thread_limit,
+            ..
         }: Options,
+        entries_to_write: &[Entry],
+        link: Option<&extension::Link>,
     ) -> std::io::Result<Version> {
         let _span = gix_features::trace::detail!("gix_index::State::write()");
-        let version = self.detect_required_version();
+        let version = match desired_version {
+            Some(Version::V4) => Version::V4,
+            Some(Version::V2 | Version::V3) | None => detect_required_version(entries_to_write),
+        };
 
         let mut write = CountBytes::new(out);
-        let num_entries: u32 = self
-            .entries()
+        let num_entries: u32 = entries_to_write
             .len()
             .try_into()
             .expect("definitely not 4billion entries");
-        let removed_entries: u32 = self
-            .entries()
+        let removed_entries: u32 = entries_to_write
             .iter()
             .filter(|e| e.flags.contains(entry::Flags::REMOVE))
             .count()
             .try_into()
             .expect("definitely not too many entries");
 
-        let offset_to_entries = header(&mut write, version, num_entries - removed_entries)?;
-        let offset_to_extensions = entries(&mut write, self, offset_to_entries)?;
-        let (extension_toc, out) = self.write_extensions(write, offset_to_extensions, extensions)?;
+        let num_entries = num_entries - removed_entries;
+        let entries_per_block = extensions
+            .should_write(extension::index_entry_offset_table::SIGNATURE)
+            .and_then(|_| entries_per_block(num_entries, thread_limit));
+
+        let offset_to_entries = header(&mut write, version, num_entries)?;
+        let (offset_to_extensions, entry_offsets) = entries(
+            &mut write,
+            self,
+            entries_to_write,
+            version,
+            offset_to_entries,
+            entries_per_block,
+        )?;
+        let (extension_toc, out) =
+            self.write_extensions(write, offset_to_extensions, extensions, link, &entry_offsets)?;
 
         if num_entries > 0
             && extensions
@@ -106,17 +196,50 @@ impl State {
         mut write: CountBytes<T>,
         offset_to_extensions: u32,
         extensions: Extensions,
+        link: Option<&extension::Link>,
+        entry_offsets: &[extension::index_entry_offset_table::Offset],
     ) -> std::io::Result<(Vec<(extension::Signature, u32)>, T)>
     where
         T: std::io::Write,
     {
         type WriteExtFn<'a> = &'a dyn Fn(&mut dyn std::io::Write) -> Option<std::io::Result<extension::Signature>>;
         let extensions: &[WriteExtFn<'_>] = &[
+            &|write| {
+                (!entry_offsets.is_empty()).then(|| {
+                    extension::index_entry_offset_table::write_to(write, entry_offsets)
+                        .map(|_| extension::index_entry_offset_table::SIGNATURE)
+                })
+            },
+            &|write| link.map(|link| link.write_to(write).map(|_| extension::link::SIGNATURE)),
             &|write| {
                 extensions
                     .should_write(extension::tree::SIGNATURE)
                     .and_then(|signature| self.tree().map(|tree| tree.write_to(write).map(|_| signature)))
             },
+            &|write| {
+                extensions
+                    .should_write(extension::resolve_undo::SIGNATURE)
+                    .and_then(|signature| {
+                        self.resolve_undo()
+                            .map(|paths| extension::resolve_undo::write_to(write, paths).map(|_| signature))
+                    })
+            },
+            &|write| {
+                extensions
+                    .should_write(extension::untracked_cache::SIGNATURE)
+                    .and_then(|signature| {
+                        self.untracked()
+                            .map(|untracked| untracked.write_to(write, self.object_hash).map(|_| signature))
+                    })
+            },
+            &|write| {
+                extensions
+                    .should_write(extension::fs_monitor::SIGNATURE)
+                    .and_then(|signature| {
+                        self.fs_monitor()
+                            .map(|fs_monitor| fs_monitor.write_to(write, self.entries()).map(|_| signature))
+                    })
+            },
             &|write| {
                 self.is_sparse()
                     .then(|| extension::sparse::write_to(write).map(|_| extension::sparse::SIGNATURE))
@@ -137,13 +260,38 @@ impl State {
     }
 }
 
-impl State {
-    fn detect_required_version(&self) -> Version {
-        self.entries
-            .iter()
-            .find_map(|e| e.flags.contains(entry::Flags::EXTENDED).then_some(Version::V3))
-            .unwrap_or(Version::V2)
-    }
+/// Return the amount of entries to put into each block of the index-entry-offset-table extension, or `None` if
+/// it shouldn't be written as it wouldn't help readers.
+fn entries_per_block(num_entries: u32, thread_limit: Option<usize>) -> Option<u32> {
+    /// The amount of entries it takes for another thread to be worth it, as used by `git`.
+    const ENTRIES_PER_THREAD: u32 = 10_000;
+    let num_blocks = match thread_limit {
+        Some(1) => return None,
+        Some(0) | None => (num_entries / ENTRIES_PER_THREAD)
+            .min(gix_features::parallel::num_threads(None).saturating_sub(1) as u32),
+        Some(threads) => num_entries.min(threads.try_into().unwrap_or(u32::MAX)),
+    };
+    (num_blocks > 1).then(|| (num_entries + num_blocks - 1) / num_blocks)
+}
+
+fn detect_required_version(entries: &[Entry]) -> Version {
+    entries
+        .iter()
+        .find_map(|e| e.flags.contains(entry::Flags::EXTENDED).then_some(Version::V3))
+        .unwrap_or(Version::V2)
+}
+
+/// Write `stat` in the format understood by [`decode::stat()`](crate::decode::stat()), as used by extensions.
+pub(crate) fn stat(mut out: impl std::io::Write, stat: &entry::Stat) -> std::io::Result<()> {
+    out.write_all(&stat.ctime.secs.to_be_bytes())?;
+    out.write_all(&stat.ctime.nsecs.to_be_bytes())?;
+    out.write_all(&stat.mtime.secs.to_be_bytes())?;
+    out.write_all(&stat.mtime.nsecs.to_be_bytes())?;
+    out.write_all(&stat.dev.to_be_bytes())?;
+    out.write_all(&stat.ino.to_be_bytes())?;
+    out.write_all(&stat.uid.to_be_bytes())?;
+    out.write_all(&stat.gid.to_be_bytes())?;
+    out.write_all(&stat.size.to_be_bytes())
 }
 
 fn header<T: std::io::Write>(
@@ -164,22 +312,62 @@ fn header<T: std::io::Write>(
     Ok(out.count)
 }
 
-fn entries<T: std::io::Write>(out: &mut CountBytes<T>, state: &State, header_size: u32) -> Result<u32, std::io::Error> {
-    for entry in state.entries() {
-        if entry.flags.contains(entry::Flags::REMOVE) {
-            continue;
+/// Write all `entries` and return the offset past the last one, along with the offsets of blocks of `entries_per_block` entries.
+fn entries<T: std::io::Write>(
+    out: &mut CountBytes<T>,
+    state: &State,
+    entries: &[Entry],
+    version: Version,
+    header_size: u32,
+    entries_per_block: Option<u32>,
+) -> Result<(u32, Vec<extension::index_entry_offset_table::Offset>), std::io::Error> {
+    let mut offsets = Vec::new();
+    let mut prev_path: &[u8] = &[];
+    let mut unrelated_prev_path = Vec::new();
+    for (idx, entry) in entries
+        .iter()
+        .filter(|e| !e.flags.contains(entry::Flags::REMOVE))
+        .enumerate()
+    {
+        let is_start_of_block = entries_per_block.map_or(false, |n| idx as u32 % n == 0);
+        if is_start_of_block {
+            offsets.push(extension::index_entry_offset_table::Offset {
+                from_beginning_of_file: out.count,
+                num_entries: 0,
+            });
         }
-        entry.write_to(&mut *out, state)?;
-        match (out.count - header_size) % 8 {
-            0 => {}
-            n => {
-                let eight_null_bytes = [0u8; 8];
-                out.write_all(&eight_null_bytes[n as usize..])?;
+        if let Some(block) = offsets.last_mut() {
+            block.num_entries += 1;
+        }
+        match version {
+            Version::V4 => {
+                // Like `git`, assure the first path of a block shares no prefix with the previous one so it can be
+                // decoded on its own, while remaining decodable by readers that don't know about blocks.
+                let relative_to = if is_start_of_block && !prev_path.is_empty() {
+                    unrelated_prev_path.clear();
+                    unrelated_prev_path.extend_from_slice(prev_path);
+                    unrelated_prev_path[0] = 0;
+                    unrelated_prev_path.as_slice()
+                } else {
+                    prev_path
+                };
+                entry.write_to_with_prev_path(&mut *out, state, relative_to.into())?;
+                prev_path = entry.path(state);
             }
-        };
+            Version::V2 | Version::V3 => {
+                entry.write_to(&mut *out, state)?;
+                match (out.count - header_size) % 8 {
+                    0 => {}
+                    n => {
+                        let eight_null_bytes = [0u8; 8];
+                        out.write_all(&eight_null_bytes[n as usize..])?;
+                    }
+                };
+            }
+        }
     }
 
-    Ok(out.count)
+    Ok((out.count, offsets))
 }
 
 mod util {
@@ -0,0 +1,100 @@
+use bstr::ByteSlice;
+use gix_object::tree::EntryKind;
+
+use crate::{entry::Flags, Entry, PathStorageRef, State};
+
+/// The error returned by [`State::write_tree()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Cannot write a tree from an index with conflicts, but '{rela_path}' is conflicted")]
+    Conflict { rela_path: bstr::BString },
+    #[error("Could not write a tree")]
+    WriteTree {
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+}
+
+/// Trees
+impl State {
+    /// Write the trees needed to represent all entries by calling `write_tree` for each of them, and return the id of the
+    /// root tree, just like `git write-tree` does.
+    ///
+    /// Entries that are marked for removal or that are only *intended to be added* are ignored, and conflicts are an error.
+    /// Note that trees are also written if they already exist in the object database.
+    pub fn write_tree<E>(
+        &self,
+        mut write_tree: impl FnMut(&gix_object::Tree) -> Result<gix_hash::ObjectId, E>,
+    ) -> Result<gix_hash::ObjectId, Error>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        if let Some(conflict) = self.entries.iter().find(|e| e.stage() != 0) {
+            return Err(Error::Conflict {
+                rela_path: conflict.path(self).to_owned(),
+            });
+        }
+        let entries: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|e| !e.flags.intersects(Flags::REMOVE | Flags::INTENT_TO_ADD))
+            .cloned()
+            .collect();
+        write_trees(&entries, &self.path_backing, 0, &mut write_tree)
+            .map_err(|err| Error::WriteTree { source: Box::new(err) })
+    }
+}
+
+/// Return the length of the range of `entries` whose paths start with `dir/`, where `entries` is sorted by path.
+pub(crate) fn directory_entries(entries: &[Entry], paths: &PathStorageRef, dir: &[u8]) -> usize {
+    entries
+        .iter()
+        .take_while(|e| {
+            let path = e.path_in(paths);
+            path.len() > dir.len() && path.starts_with(dir) && path[dir.len()] == b'/'
+        })
+        .count()
+}
+
+/// Write the trees for all `entries` whose paths are stripped by `prefix_len` bytes, and return the id of the top-most tree.
+pub(crate) fn write_trees<E>(
+    entries: &[Entry],
+    paths: &PathStorageRef,
+    prefix_len: usize,
+    write_tree: &mut impl FnMut(&gix_object::Tree) -> Result<gix_hash::ObjectId, E>,
+) -> Result<gix_hash::ObjectId, E> {
+    let mut tree = gix_object::Tree::empty();
+    let mut idx = 0;
+    while idx < entries.len() {
+        let entry = &entries[idx];
+        let path = entry.path_in(paths);
+        let name = &path[prefix_len..];
+        match name.find_byte(b'/') {
+            Some(pos) if !(entry.mode.is_sparse() && pos + 1 == name.len()) => {
+                let dir = &path[..prefix_len + pos];
+                let len = directory_entries(&entries[idx..], paths, dir);
+                let id = write_trees(&entries[idx..][..len], paths, dir.len() + 1, write_tree)?;
+                tree.entries.push(gix_object::tree::Entry {
+                    mode: EntryKind::Tree.into(),
+                    filename: name[..pos].into(),
+                    oid: id,
+                });
+                idx += len;
+            }
+            _ => {
+                tree.entries.push(gix_object::tree::Entry {
+                    mode: entry
+                        .mode
+                        .to_tree_entry_mode()
+                        .unwrap_or_else(|| EntryKind::Blob.into()),
+                    filename: name.trim_end_with(|c| c == '/').into(),
+                    oid: entry.id,
+                });
+                idx += 1;
+            }
+        }
+    }
+    tree.entries.sort();
+    write_tree(&tree)
+}
@@ -0,0 +1,13 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+touch a b
+mkdir -p dir/sub/deeper other
+(cd dir && touch a-file another-file && cd sub && touch file && cd deeper && touch x y z)
+touch other/file x
+
+git add .
+git commit -m "empty"
+git update-index --index-version 4
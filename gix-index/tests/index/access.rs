@@ -391,3 +391,21 @@ fn check_prefix_icase(index: &gix_index::State, prefix: &str, expected: &[&str])
         "{prefix:?}"
     );
 }
+
+#[test]
+fn tree_invalidate_path() {
+    let mut file = Fixture::Generated("v2_more_files").open();
+    let tree = file.tree_mut().expect("tree extension is present");
+    assert_eq!(tree.num_entries, Some(6));
+    assert_eq!(tree.children.len(), 1);
+
+    tree.invalidate_path("d/a".into());
+    assert_eq!(tree.num_entries, None, "all trees leading to the path are invalidated");
+    assert_eq!(tree.children[0].num_entries, None);
+
+    tree.invalidate_path("d".into());
+    assert!(tree.children.is_empty(), "trees at the path itself are removed");
+
+    tree.invalidate_path("a".into());
+    assert_eq!(tree.num_entries, None, "invalidating the root is idempotent");
+}
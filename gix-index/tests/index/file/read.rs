@@ -189,7 +189,43 @@ fn untr_extension() {
     let file = loose_file("UNTR");
     assert_eq!(file.version(), Version::V2);
 
-    assert!(file.untracked().is_some());
+    let untracked = file.untracked().expect("present");
+    assert_eq!(untracked.exclude_filename_per_dir(), ".gitignore");
+    assert!(untracked.identifier().starts_with(b"Location "));
+    assert_eq!(
+        untracked.info_exclude().map(|s| s.id),
+        Some(hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391")),
+        "the empty blob is the id of the default info/exclude file"
+    );
+    assert_eq!(untracked.excludes_file(), None, "no global excludes file was configured");
+    assert_eq!(untracked.dir_flags(), 6);
+    assert_eq!(untracked.directories().len(), 4);
+
+    let root = untracked.directory_by_path("".into()).expect("root is always present");
+    assert_eq!(root.untracked_entries, ["three", "dtwo/", "dthree/"]);
+    let sub_dir = untracked
+        .directory_by_path("dthree".into())
+        .expect("sub-directories can be found");
+    assert_eq!(sub_dir.untracked_entries, ["three"]);
+    assert!(sub_dir.stat.is_some(), "the stat is recorded for later comparison");
+    assert!(untracked.directory_by_path("missing".into()).is_none());
+}
+
+#[test]
+fn untr_extension_invalidate_path() {
+    let mut file = loose_file("UNTR");
+    let mut untracked = file.set_untracked(None).expect("present");
+    untracked.invalidate_path("dthree/three".into());
+
+    for dir in ["", "dthree"] {
+        let dir = untracked.directory_by_path(dir.into()).expect("present");
+        assert!(dir.stat.is_none(), "directories leading to the path are invalidated");
+        assert!(dir.untracked_entries.is_empty());
+    }
+    assert!(
+        untracked.directories().iter().any(|d| d.stat.is_some()),
+        "other directories stay valid"
+    );
 }
 
 #[test]
@@ -349,7 +385,11 @@ fn split_index_and_regular_index_of_same_content_are_indeed_the_same() {
 
     assert!(
         split.link().is_none(),
-        "link extension is dissolved, merging the shared index permanently into the split one (for now)"
+        "link extension is dissolved, merging the shared index into the split one"
+    );
+    assert!(
+        split.shared_index_checksum().is_some(),
+        "the shared index is remembered to be able to write a split index again"
     );
 
     let regular = verify(
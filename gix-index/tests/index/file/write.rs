@@ -14,12 +14,36 @@ fn roundtrips() -> crate::Result {
             Generated("v2"),
             options_with(write::Extensions::Given {
                 tree_cache: true,
+                untracked_cache: false,
+                fs_monitor: false,
+                resolve_undo: false,
                 end_of_index_entry: true,
+                index_entry_offset_table: false,
             }),
         ),
         (Generated("V2_empty"), only_tree_ext()),
         (Generated("v2_more_files"), only_tree_ext()),
         (Generated("v2_all_file_kinds"), only_tree_ext()),
+        (Loose("UNTR"), only_untracked_ext()),
+        (Loose("UNTR-with-oids"), only_untracked_ext()),
+        (
+            Loose("REUC"),
+            options_with(write::Extensions::Given {
+                tree_cache: true,
+                untracked_cache: false,
+                fs_monitor: false,
+                resolve_undo: true,
+                end_of_index_entry: false,
+                index_entry_offset_table: false,
+            }),
+        ),
+        (
+            Generated("v4_path_compression"),
+            Options {
+                version: Some(Version::V4),
+                ..only_tree_ext()
+            },
+        ),
     ];
 
     for (fixture, options) in input {
@@ -37,6 +61,88 @@ fn roundtrips() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn roundtrips_fs_monitor() -> crate::Result {
+    let fixture = Loose("FSMN");
+    let mut expected = fixture.open();
+    let expected_bytes = std::fs::read(fixture.to_path())?;
+    assert!(
+        expected.apply_fs_monitor_dirty_bitmap(),
+        "the extension is present, and validity is transferred to entries"
+    );
+    assert_eq!(
+        expected
+            .entries()
+            .iter()
+            .filter(|e| e.flags.contains(entry::Flags::FSMONITOR_VALID))
+            .count(),
+        0,
+        "all entries are dirty as the monitor was never queried after refreshing the index"
+    );
+
+    let options = options_with(write::Extensions::Given {
+        tree_cache: true,
+        untracked_cache: false,
+        fs_monitor: true,
+        resolve_undo: false,
+        end_of_index_entry: false,
+        index_entry_offset_table: false,
+    });
+    let mut out_bytes = Vec::new();
+    let (actual_version, _digest) = expected.write_to(&mut out_bytes, options)?;
+    let (actual, _) = State::from_bytes(&out_bytes, FileTime::now(), gix_hash::Kind::Sha1, Default::default())?;
+
+    compare_states_against_baseline(&actual, actual_version, &expected, options, fixture.to_name());
+    compare_raw_bytes(&out_bytes, &expected_bytes, fixture.to_name());
+    assert_eq!(
+        actual.fs_monitor(),
+        expected.fs_monitor(),
+        "the dirty bitmap is recreated from entry flags"
+    );
+    Ok(())
+}
+
+#[test]
+fn fs_monitor_dirty_entries_are_derived_from_entry_flags() -> crate::Result {
+    let mut expected = Loose("FSMN").open();
+    assert!(expected.fs_monitor().is_some());
+    expected.entries_mut()[1].flags.insert(entry::Flags::FSMONITOR_VALID);
+
+    let mut out_bytes = Vec::new();
+    expected.write_to(&mut out_bytes, Default::default())?;
+    let (mut actual, _) = State::from_bytes(&out_bytes, FileTime::now(), gix_hash::Kind::Sha1, Default::default())?;
+
+    assert_eq!(
+        actual.fs_monitor().map(extension::FsMonitor::token),
+        expected.fs_monitor().map(extension::FsMonitor::token),
+        "the token is retained"
+    );
+    assert!(actual.apply_fs_monitor_dirty_bitmap());
+    assert_eq!(
+        actual
+            .entries()
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, e)| e.flags.contains(entry::Flags::FSMONITOR_VALID).then_some(idx))
+            .collect::<Vec<_>>(),
+        [1],
+        "only the entry we marked is valid"
+    );
+    Ok(())
+}
+
+#[test]
+fn untracked_cache_can_be_removed() -> crate::Result {
+    let mut expected = Loose("UNTR").open();
+    assert!(expected.set_untracked(None).is_some());
+
+    let mut out_bytes = Vec::new();
+    expected.write_to(&mut out_bytes, Default::default())?;
+    let (actual, _) = State::from_bytes(&out_bytes, FileTime::now(), gix_hash::Kind::Sha1, Default::default())?;
+    assert!(actual.untracked().is_none());
+    Ok(())
+}
+
 #[test]
 fn skip_hash() -> crate::Result {
     let tmp = gix_testtools::tempfile::TempDir::new()?;
@@ -48,6 +154,9 @@ fn skip_hash() -> crate::Result {
     expected.write(Options {
         extensions: Default::default(),
         skip_hash: false,
+        version: None,
+        thread_limit: None,
+        split_index: None,
     })?;
 
     let actual = gix_index::File::at(
@@ -65,6 +174,9 @@ fn skip_hash() -> crate::Result {
     expected.write(Options {
         extensions: Default::default(),
         skip_hash: true,
+        version: None,
+        thread_limit: None,
+        split_index: None,
     })?;
 
     let actual = gix_index::File::at(
@@ -133,9 +245,8 @@ fn state_comparisons_with_various_extension_configurations() {
         Generated("v2_more_files"),
         Generated("v2_all_file_kinds"),
         Generated("v2_split_index"),
-        // TODO: this fails because git allows to configure the index version while gitoxide doesn't
-        //       the fixture artificially sets the version to V4 and gitoxide writes it back out as the lowest required version, V2
-        // Generated("v4_more_files_IEOT"),
+        Generated("v4_more_files_IEOT"),
+        Generated("v4_path_compression"),
         Generated("v3_skip_worktree"),
         Generated("v3_added_files"),
         Generated("v3_sparse_index_non_cone"),
@@ -148,15 +259,27 @@ fn state_comparisons_with_various_extension_configurations() {
             options_with(write::Extensions::All),
             options_with(write::Extensions::Given {
                 tree_cache: true,
+                untracked_cache: false,
+                fs_monitor: false,
+                resolve_undo: false,
                 end_of_index_entry: false,
+                index_entry_offset_table: false,
             }),
             options_with(write::Extensions::Given {
                 tree_cache: false,
+                untracked_cache: true,
+                fs_monitor: true,
+                resolve_undo: false,
                 end_of_index_entry: true,
+                index_entry_offset_table: false,
             }),
         ] {
             let expected = fixture.open();
             let fixture = fixture.to_name();
+            let options = Options {
+                version: Some(expected.version()),
+                ..options
+            };
 
             let mut out = Vec::<u8>::new();
             let (actual_version, _digest) = expected.write_to(&mut out, options).unwrap();
@@ -181,6 +304,166 @@ fn extended_flags_automatically_upgrade_the_version_to_avoid_data_loss() -> crat
     Ok(())
 }
 
+#[test]
+fn v4_paths_are_compressed_and_can_be_read_back() -> crate::Result {
+    let expected = Generated("v2_more_files").open();
+    assert_eq!(expected.version(), Version::V2);
+
+    let mut v2 = Vec::new();
+    expected.write_to(&mut v2, options_with(write::Extensions::None))?;
+    let mut v4 = Vec::new();
+    let (actual_version, _digest) = expected.write_to(
+        &mut v4,
+        Options {
+            version: Some(Version::V4),
+            ..options_with(write::Extensions::None)
+        },
+    )?;
+    assert_eq!(actual_version, Version::V4, "V4 is written on request");
+    assert!(v4.len() < v2.len(), "paths are prefix-compressed and entries aren't padded");
+
+    let (actual, _) = State::from_bytes(&v4, FileTime::now(), gix_hash::Kind::Sha1, Default::default())?;
+    assert_eq!(actual.version(), Version::V4);
+    assert_eq!(actual.entries(), expected.entries());
+    assert_eq!(actual.path_backing(), expected.path_backing());
+    Ok(())
+}
+
+#[test]
+fn v4_retains_extended_flags() -> crate::Result {
+    let mut expected = Generated("v2").open();
+    expected.entries_mut()[0].flags.insert(entry::Flags::EXTENDED | entry::Flags::INTENT_TO_ADD);
+
+    let mut buf = Vec::new();
+    let (actual_version, _digest) = expected.write_to(
+        &mut buf,
+        Options {
+            version: Some(Version::V4),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(actual_version, Version::V4, "V4 supports extended flags as well");
+
+    let (actual, _) = State::from_bytes(&buf, FileTime::now(), gix_hash::Kind::Sha1, Default::default())?;
+    assert_eq!(actual.entries(), expected.entries());
+    Ok(())
+}
+
+#[test]
+fn resolve_undo_can_be_recorded_and_written() -> crate::Result {
+    let mut expected = Loose("conflicting-file").open();
+    assert!(expected.resolve_undo().is_none());
+    assert!(expected.record_resolve_undo("file".into()), "there are conflicting entries");
+    assert!(!expected.record_resolve_undo("missing".into()));
+
+    let mut out_bytes = Vec::new();
+    expected.write_to(&mut out_bytes, Default::default())?;
+    let (actual, _) = State::from_bytes(&out_bytes, FileTime::now(), gix_hash::Kind::Sha1, Default::default())?;
+
+    let paths = actual.resolve_undo().expect("written and read back");
+    assert_eq!(paths, expected.resolve_undo().expect("present"));
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths[0].name, "file");
+    assert_eq!(
+        paths[0].stages.iter().map(Option::is_some).collect::<Vec<_>>(),
+        [true, true, true],
+        "all stages are recorded"
+    );
+    Ok(())
+}
+
+#[test]
+fn index_entry_offset_table_allows_reading_entries_in_parallel() -> crate::Result {
+    for version in [Version::V2, Version::V4] {
+        let fixture = Generated("v2_more_files");
+        let expected = fixture.open();
+        let options = Options {
+            version: Some(version),
+            thread_limit: Some(3),
+            ..Default::default()
+        };
+        let mut out_bytes = Vec::new();
+        expected.write_to(&mut out_bytes, options)?;
+        assert!(
+            out_bytes.windows(4).any(|w| w == b"IEOT"),
+            "the extension is written with three blocks"
+        );
+
+        let (actual, _) = State::from_bytes(
+            &out_bytes,
+            FileTime::now(),
+            gix_hash::Kind::Sha1,
+            gix_index::decode::Options {
+                thread_limit: Some(3),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(actual.version(), version);
+        assert_eq!(actual.entries().len(), expected.entries().len());
+        for (actual_entry, expected_entry) in actual.entries().iter().zip(expected.entries()) {
+            assert_eq!(actual_entry.path(&actual), expected_entry.path(&expected));
+            assert_eq!(actual_entry.id, expected_entry.id);
+        }
+
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let index_path = tmp.path().join("index");
+        std::fs::write(&index_path, &out_bytes)?;
+        let git_dir = fixture.to_path().parent().expect("index is in git dir").to_owned();
+        let ls_files = |index: &std::path::Path| -> crate::Result<Vec<u8>> {
+            let out = std::process::Command::new("git")
+                .args(["-c", "index.threads=3", "ls-files", "--stage"])
+                .env("GIT_DIR", &git_dir)
+                .env("GIT_INDEX_FILE", index)
+                .output()?;
+            assert!(out.status.success(), "git can read {index:?}");
+            Ok(out.stdout)
+        };
+        assert_eq!(
+            ls_files(&index_path)?,
+            ls_files(&fixture.to_path())?,
+            "git reads the same entries from our index in {version:?}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn index_entry_offset_table_is_not_written_for_small_indices_by_default() -> crate::Result {
+    let expected = Generated("v2_more_files").open();
+    let mut out_bytes = Vec::new();
+    expected.write_to(&mut out_bytes, Default::default())?;
+    assert!(
+        !out_bytes.windows(4).any(|w| w == b"IEOT"),
+        "the amount of entries is too small to be worth multiple threads"
+    );
+    Ok(())
+}
+
+#[test]
+fn index_entry_offset_table_is_only_written_if_enabled() -> crate::Result {
+    let expected = Generated("v2_more_files").open();
+    let mut out_bytes = Vec::new();
+    expected.write_to(
+        &mut out_bytes,
+        Options {
+            thread_limit: Some(3),
+            ..options_with(write::Extensions::Given {
+                tree_cache: true,
+                untracked_cache: true,
+                fs_monitor: true,
+                resolve_undo: true,
+                end_of_index_entry: true,
+                index_entry_offset_table: false,
+            })
+        },
+    )?;
+    assert!(
+        !out_bytes.windows(4).any(|w| w == b"IEOT"),
+        "the extension isn't written if disabled, even if it would be useful"
+    );
+    Ok(())
+}
+
 #[test]
 fn remove_flag_is_respected() -> crate::Result {
     let mut index = Generated("v4_more_files_IEOT").open();
@@ -210,6 +493,186 @@ fn remove_flag_is_respected() -> crate::Result {
     Ok(())
 }
 
+mod split_index {
+    use std::path::Path;
+
+    use gix_index::{entry, write};
+
+    fn fixture() -> crate::Result<gix_testtools::tempfile::TempDir> {
+        gix_testtools::scripted_fixture_writable_standalone("make_index/v2_split_vs_regular_index.sh")
+    }
+
+    fn open(index_path: &Path) -> crate::Result<gix_index::File> {
+        Ok(gix_index::File::at(
+            index_path,
+            gix_hash::Kind::Sha1,
+            false,
+            Default::default(),
+        )?)
+    }
+
+    fn split_options(split_index: write::SplitIndex) -> write::Options {
+        write::Options {
+            split_index: Some(split_index),
+            ..Default::default()
+        }
+    }
+
+    fn entries(index: &gix_index::State) -> Vec<(String, gix_hash::ObjectId, entry::Stat, entry::Flags, entry::Mode)> {
+        index
+            .entries()
+            .iter()
+            .map(|e| (e.path(index).to_string(), e.id, e.stat, e.flags, e.mode))
+            .collect()
+    }
+
+    fn git_ls_files(worktree: &Path) -> crate::Result<String> {
+        let out = std::process::Command::new("git")
+            .args(["ls-files", "--stage"])
+            .current_dir(worktree)
+            .output()?;
+        assert!(out.status.success(), "git can read the index");
+        Ok(String::from_utf8(out.stdout)?)
+    }
+
+    fn shared_indices(git_dir: &Path) -> crate::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(git_dir)? {
+            let name = entry?.file_name().into_string().expect("valid UTF-8");
+            if name.starts_with("sharedindex.") {
+                names.push(name);
+            }
+        }
+        Ok(names)
+    }
+
+    #[test]
+    fn unchanged_indices_keep_their_shared_index() -> crate::Result {
+        let tmp = fixture()?;
+        let split = tmp.path().join("split");
+        let mut index = open(&split.join(".git/index"))?;
+        let shared_index_checksum = index.shared_index_checksum().expect("this is a split index");
+
+        index.write(split_options(write::SplitIndex {
+            max_percent_change: 100,
+            shared_index_expire: None,
+        }))?;
+        assert_eq!(index.shared_index_checksum(), Some(shared_index_checksum));
+        assert_eq!(
+            shared_indices(&split.join(".git"))?,
+            [format!("sharedindex.{shared_index_checksum}")]
+        );
+
+        let actual = open(index.path())?;
+        assert_eq!(actual.shared_index_checksum(), Some(shared_index_checksum));
+        assert_eq!(entries(&actual), entries(&index), "the split index is merged with the shared one");
+        assert_eq!(
+            git_ls_files(&split)?,
+            git_ls_files(&tmp.path().join("regular"))?,
+            "git reads the same content from our split index"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn changes_are_stored_as_replacements_deletions_and_additions() -> crate::Result {
+        let tmp = fixture()?;
+        let split = tmp.path().join("split");
+        let mut index = open(&split.join(".git/index"))?;
+        let shared_index_checksum = index.shared_index_checksum().expect("this is a split index");
+
+        let id_of_b = index.entry_by_path("b".into()).expect("present").id;
+        let z = index.entry_index_by_path_and_stage("z".into(), 0).expect("present");
+        index.entries_mut()[z].id = id_of_b;
+        let y = index.entry_index_by_path_and_stage("y".into(), 0).expect("present");
+        index.entries_mut()[y].flags.insert(entry::Flags::REMOVE);
+
+        index.write(split_options(write::SplitIndex {
+            max_percent_change: 100,
+            shared_index_expire: None,
+        }))?;
+        assert_eq!(
+            index.shared_index_checksum(),
+            Some(shared_index_checksum),
+            "the shared index stays the same"
+        );
+
+        let actual = open(index.path())?;
+        assert_eq!(
+            actual
+                .entries()
+                .iter()
+                .map(|e| e.path(&actual).to_string())
+                .collect::<Vec<_>>(),
+            ["b", "d", "e", "z"]
+        );
+        assert_eq!(actual.entry_by_path("z".into()).expect("present").id, id_of_b);
+        let git_ls_files = git_ls_files(&split)?;
+        assert!(!git_ls_files.contains("\ty\n"), "git sees the deletion");
+        assert!(
+            git_ls_files.contains(&format!("{id_of_b} 0\tz\n")),
+            "git sees the replacement: {git_ls_files}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn too_many_changes_write_a_new_shared_index_and_expire_old_ones() -> crate::Result {
+        let tmp = fixture()?;
+        let split = tmp.path().join("split");
+        let git_dir = split.join(".git");
+        let mut index = open(&git_dir.join("index"))?;
+        let previous_checksum = index.shared_index_checksum().expect("this is a split index");
+
+        index.write(split_options(write::SplitIndex {
+            max_percent_change: 0,
+            shared_index_expire: None,
+        }))?;
+        let checksum = index.shared_index_checksum().expect("still a split index");
+        assert_ne!(checksum, previous_checksum, "a new shared index was written");
+        let mut names = shared_indices(&git_dir)?;
+        names.sort();
+        let mut expected = vec![
+            format!("sharedindex.{previous_checksum}"),
+            format!("sharedindex.{checksum}"),
+        ];
+        expected.sort();
+        assert_eq!(names, expected, "shared indices don't expire if not configured");
+        assert_eq!(entries(&*open(index.path())?), entries(&index));
+
+        index.entries_mut()[0].stat.size += 1;
+        index.write(split_options(write::SplitIndex {
+            max_percent_change: 0,
+            shared_index_expire: Some(std::time::SystemTime::now() + std::time::Duration::from_secs(60)),
+        }))?;
+        let checksum = index.shared_index_checksum().expect("still a split index");
+        assert_eq!(
+            shared_indices(&git_dir)?,
+            [format!("sharedindex.{checksum}")],
+            "all other shared indices expired"
+        );
+        assert_eq!(entries(&*open(index.path())?), entries(&index));
+        assert_eq!(git_ls_files(&split)?, git_ls_files(&tmp.path().join("regular"))?);
+        Ok(())
+    }
+
+    #[test]
+    fn writing_without_split_index_dissolves_the_shared_index() -> crate::Result {
+        let tmp = fixture()?;
+        let split = tmp.path().join("split");
+        let mut index = open(&split.join(".git/index"))?;
+        assert!(index.shared_index_checksum().is_some());
+
+        index.write(Default::default())?;
+        assert_eq!(index.shared_index_checksum(), None);
+        let actual = open(index.path())?;
+        assert_eq!(actual.shared_index_checksum(), None, "there is no link extension anymore");
+        assert_eq!(entries(&actual), entries(&index));
+        assert_eq!(git_ls_files(&split)?, git_ls_files(&tmp.path().join("regular"))?);
+        Ok(())
+    }
+}
+
 fn compare_states_against_baseline(
     actual: &State,
     actual_version: Version,
@@ -224,6 +687,28 @@ fn compare_states_against_baseline(
         expected.tree(),
         "tree extension mismatch, actual vs expected in {fixture:?}"
     );
+    if options
+        .extensions
+        .should_write(extension::untracked_cache::SIGNATURE)
+        .is_some()
+    {
+        assert_eq!(
+            actual.untracked(),
+            expected.untracked(),
+            "untracked cache extension mismatch, actual vs expected in {fixture:?}"
+        );
+    }
+    if options
+        .extensions
+        .should_write(extension::resolve_undo::SIGNATURE)
+        .is_some()
+    {
+        assert_eq!(
+            actual.resolve_undo(),
+            expected.resolve_undo(),
+            "resolve-undo extension mismatch, actual vs expected in {fixture:?}"
+        );
+    }
 }
 
 fn compare_states(actual: &State, actual_version: Version, expected: &State, options: Options, fixture: &str) {
@@ -292,9 +777,33 @@ fn only_tree_ext() -> Options {
     Options {
         extensions: write::Extensions::Given {
             end_of_index_entry: false,
+            untracked_cache: false,
+            fs_monitor: false,
+            resolve_undo: false,
+            index_entry_offset_table: false,
             tree_cache: true,
         },
         skip_hash: false,
+        version: None,
+        thread_limit: None,
+        split_index: None,
+    }
+}
+
+fn only_untracked_ext() -> Options {
+    Options {
+        extensions: write::Extensions::Given {
+            end_of_index_entry: false,
+            untracked_cache: true,
+            fs_monitor: false,
+            resolve_undo: false,
+            index_entry_offset_table: false,
+            tree_cache: false,
+        },
+        skip_hash: false,
+        version: None,
+        thread_limit: None,
+        split_index: None,
     }
 }
 
@@ -302,5 +811,8 @@ fn options_with(extensions: write::Extensions) -> Options {
     Options {
         extensions,
         skip_hash: false,
+        version: None,
+        thread_limit: None,
+        split_index: None,
     }
 }
@@ -0,0 +1,76 @@
+use bstr::ByteSlice;
+use gix_index::{entry, sparse::Cone, State};
+use gix_testtools::scripted_fixture_read_only_standalone;
+
+fn open(name: &str) -> crate::Result<(gix::Repository, gix_index::File)> {
+    let repo = gix::open(scripted_fixture_read_only_standalone(format!("make_index/{name}.sh"))?)?;
+    let index = repo.open_index()?;
+    Ok((repo, index))
+}
+
+fn entries(state: &State) -> Vec<(String, entry::Mode, gix_hash::ObjectId, entry::Flags)> {
+    state
+        .entries()
+        .iter()
+        .map(|e| (e.path(state).to_str_lossy().into_owned(), e.mode, e.id, e.flags))
+        .collect()
+}
+
+/// Compute the id of `tree` without writing it, as all trees are already present in the fixture.
+fn hash_tree(tree: &gix::objs::Tree) -> std::io::Result<gix_hash::ObjectId> {
+    use gix::objs::WriteTo;
+    let mut buf = Vec::new();
+    tree.write_to(&mut buf)?;
+    Ok(gix::objs::compute_hash(gix_hash::Kind::Sha1, gix::objs::Kind::Tree, &buf))
+}
+
+#[test]
+fn expand_turns_sparse_directories_into_skipped_entries() -> crate::Result {
+    let (repo, mut index) = open("v3_sparse_index")?;
+    let (_, expected) = open("v3_skip_worktree")?;
+    assert!(index.is_sparse());
+
+    assert_eq!(index.expand_sparse(&repo.objects)?, 2, "c1/c3 and d");
+    assert!(!index.is_sparse());
+    assert_eq!(entries(&index), entries(&expected), "it's what git would have without a sparse index");
+
+    assert_eq!(index.expand_sparse(&repo.objects)?, 0, "nothing left to expand");
+    Ok(())
+}
+
+#[test]
+fn collapse_replaces_excluded_directories_and_round_trips() -> crate::Result {
+    let (repo, mut index) = open("v3_skip_worktree")?;
+    let (_, expected) = open("v3_sparse_index")?;
+    let original = entries(&index);
+    let cone = Cone::from_directories(Some("c1/c2"));
+
+    assert_eq!(index.collapse_sparse(&cone, hash_tree)?, 2, "c1/c3 and d");
+    assert!(index.is_sparse());
+    assert_eq!(entries(&index), entries(&expected), "trees are the ones git computes");
+    assert_eq!(index.collapse_sparse(&cone, hash_tree)?, 0, "sparse directories are kept");
+
+    index.expand_sparse(&repo.objects)?;
+    assert_eq!(entries(&index), original);
+    Ok(())
+}
+
+#[test]
+fn collapse_keeps_directories_with_entries_in_the_worktree() -> crate::Result {
+    let (_, mut index) = open("v3_skip_worktree")?;
+    let kept = index
+        .entries()
+        .iter()
+        .position(|e| e.path(&index) == "d/c4/a")
+        .expect("present");
+    index.entries_mut()[kept].flags.remove(entry::Flags::SKIP_WORKTREE);
+
+    let cone = Cone::from_directories(Some("c1/c2"));
+    assert_eq!(index.collapse_sparse(&cone, hash_tree)?, 1, "only c1/c3");
+    let sparse_dirs: Vec<_> = entries(&index)
+        .into_iter()
+        .filter_map(|(path, mode, _, _)| mode.is_sparse().then_some(path))
+        .collect();
+    assert_eq!(sparse_dirs, ["c1/c3/"]);
+    Ok(())
+}
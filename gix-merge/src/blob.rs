@@ -0,0 +1,266 @@
+use std::ops::Range;
+
+use bstr::BStr;
+use imara_diff::{
+    intern::{InternedInput, Token},
+    sources::byte_lines_with_terminator,
+    Algorithm,
+};
+
+/// The way conflicts are presented in the merged output.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ConflictStyle {
+    /// Show the conflicting lines of *ours* and *theirs*, as configured with `merge.conflictStyle = merge`.
+    /// Lines that both sides have in common at the beginning and the end of a conflict are moved out of it.
+    #[default]
+    Merge,
+    /// Show the conflicting lines of *ours* and *theirs* along with the lines of the base they replaced,
+    /// as configured with `merge.conflictStyle = diff3`.
+    Diff3,
+}
+
+/// The labels to show next to the conflict markers, typically the names of the revisions that are merged.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Labels<'a> {
+    /// The label of the common ancestor, shown in [`ConflictStyle::Diff3`].
+    pub base: Option<&'a BStr>,
+    /// The label of our side.
+    pub ours: Option<&'a BStr>,
+    /// The label of their side.
+    pub theirs: Option<&'a BStr>,
+}
+
+/// Options for use in [`merge()`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Options {
+    /// How to present conflicts.
+    pub conflict_style: ConflictStyle,
+    /// The amount of characters of each conflict marker, `7` by default just like in `git`.
+    pub marker_size: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            conflict_style: Default::default(),
+            marker_size: 7,
+        }
+    }
+}
+
+/// The outcome of a [`merge()`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Resolution {
+    /// All changes could be merged and the output is free of conflict markers.
+    Complete,
+    /// There were changes on both sides that touched the same lines, and the output contains conflict markers.
+    Conflict,
+}
+
+/// Merge the changes of `ours` and `theirs` relative to `base` line by line and write the result to `out`,
+/// using `labels` and `options` to present conflicts if both sides changed the same or adjacent lines.
+///
+/// Note that `out` is cleared before writing to it.
+pub fn merge(
+    base: &[u8],
+    ours: &[u8],
+    theirs: &[u8],
+    labels: Labels<'_>,
+    options: Options,
+    out: &mut Vec<u8>,
+) -> Resolution {
+    out.clear();
+    if ours == theirs || base == theirs {
+        out.extend_from_slice(ours);
+        return Resolution::Complete;
+    }
+    if base == ours {
+        out.extend_from_slice(theirs);
+        return Resolution::Complete;
+    }
+
+    let mut input = InternedInput::new(byte_lines_with_terminator(base), byte_lines_with_terminator(ours));
+    let theirs_tokens: Vec<_> = byte_lines_with_terminator(theirs)
+        .map(|line| input.interner.intern(line))
+        .collect();
+    let mut ours_changes = Vec::new();
+    imara_diff::diff(Algorithm::Histogram, &input, |before: Range<u32>, after: Range<u32>| {
+        ours_changes.push((before, after))
+    });
+    let mut theirs_changes = Vec::new();
+    imara_diff::diff_with_tokens(
+        Algorithm::Histogram,
+        &input.before,
+        &theirs_tokens,
+        input.interner.num_tokens(),
+        |before: Range<u32>, after: Range<u32>| theirs_changes.push((before, after)),
+    );
+
+    let mut writer = Writer {
+        input: &input,
+        labels,
+        options,
+        out,
+        resolution: Resolution::Complete,
+    };
+    let mut ours = Side::new(&input.after, &ours_changes);
+    let mut theirs = Side::new(&theirs_tokens, &theirs_changes);
+    let mut base_pos = 0;
+    loop {
+        let start = match (ours.next_start(), theirs.next_start()) {
+            (None, None) => break,
+            (Some(start), None) | (None, Some(start)) => start,
+            (Some(a), Some(b)) => a.min(b),
+        };
+        writer.write_lines(&input.before[base_pos as usize..start as usize]);
+
+        // Collect all changes of both sides that overlap or touch each other into one region of the base.
+        let mut end = start;
+        let (ours_first, theirs_first) = (ours.idx, theirs.idx);
+        loop {
+            let extended_ours = ours.take_changes_until(&mut end);
+            let extended_theirs = theirs.take_changes_until(&mut end);
+            if !extended_ours && !extended_theirs {
+                break;
+            }
+        }
+        let region = start..end;
+        let ours_range = ours.range_of(ours_first, region.clone());
+        let theirs_range = theirs.range_of(theirs_first, region.clone());
+        match (ours_first == ours.idx, theirs_first == theirs.idx) {
+            (false, true) => writer.write_lines(&ours.tokens[as_usize(ours_range)]),
+            (true, false) => writer.write_lines(&theirs.tokens[as_usize(theirs_range)]),
+            _ => writer.write_conflict(
+                &input.before[as_usize(region)],
+                &ours.tokens[as_usize(ours_range)],
+                &theirs.tokens[as_usize(theirs_range)],
+            ),
+        }
+        base_pos = end;
+    }
+    writer.write_lines(&input.before[base_pos as usize..]);
+    writer.resolution
+}
+
+fn as_usize(range: Range<u32>) -> Range<usize> {
+    range.start as usize..range.end as usize
+}
+
+/// The changes of one side relative to the base.
+struct Side<'a> {
+    tokens: &'a [Token],
+    changes: &'a [(Range<u32>, Range<u32>)],
+    /// The index of the next change to process.
+    idx: usize,
+    /// The difference between positions in this side and positions in the base, outside of changes.
+    offset: i64,
+}
+
+impl<'a> Side<'a> {
+    fn new(tokens: &'a [Token], changes: &'a [(Range<u32>, Range<u32>)]) -> Self {
+        Side {
+            tokens,
+            changes,
+            idx: 0,
+            offset: 0,
+        }
+    }
+
+    fn next_start(&self) -> Option<u32> {
+        self.changes.get(self.idx).map(|(base, _)| base.start)
+    }
+
+    /// Take all changes that start before or at `end` and extend `end` to their end.
+    /// Return `true` if at least one change was taken.
+    fn take_changes_until(&mut self, end: &mut u32) -> bool {
+        let mut extended = false;
+        while let Some((base, _)) = self.changes.get(self.idx).filter(|(base, _)| base.start <= *end) {
+            *end = (*end).max(base.end);
+            self.idx += 1;
+            extended = true;
+        }
+        extended
+    }
+
+    /// Return the range of this side that corresponds to `region` of the base, with `first` being the index of the
+    /// first change in the region, and update our offset for regions that follow.
+    fn range_of(&mut self, first: usize, region: Range<u32>) -> Range<u32> {
+        if first == self.idx {
+            return (region.start as i64 + self.offset) as u32..(region.end as i64 + self.offset) as u32;
+        }
+        let (first_base, first_side) = &self.changes[first];
+        let (last_base, last_side) = &self.changes[self.idx - 1];
+        self.offset = last_side.end as i64 - last_base.end as i64;
+        first_side.start - (first_base.start - region.start)..last_side.end + (region.end - last_base.end)
+    }
+}
+
+struct Writer<'a, 'b> {
+    input: &'a InternedInput<&'a [u8]>,
+    labels: Labels<'a>,
+    options: Options,
+    out: &'b mut Vec<u8>,
+    resolution: Resolution,
+}
+
+impl Writer<'_, '_> {
+    fn write_lines(&mut self, tokens: &[Token]) {
+        for token in tokens {
+            self.out.extend_from_slice(self.input.interner[*token]);
+        }
+    }
+
+    fn write_conflict(&mut self, base: &[Token], mut ours: &[Token], mut theirs: &[Token]) {
+        if ours == theirs {
+            self.write_lines(ours);
+            return;
+        }
+        let mut suffix: &[Token] = &[];
+        if self.options.conflict_style == ConflictStyle::Merge {
+            let common_prefix = ours.iter().zip(theirs).take_while(|(a, b)| a == b).count();
+            self.write_lines(&ours[..common_prefix]);
+            (ours, theirs) = (&ours[common_prefix..], &theirs[common_prefix..]);
+            let common_suffix = ours
+                .iter()
+                .rev()
+                .zip(theirs.iter().rev())
+                .take_while(|(a, b)| a == b)
+                .count();
+            suffix = &ours[ours.len() - common_suffix..];
+            (ours, theirs) = (
+                &ours[..ours.len() - common_suffix],
+                &theirs[..theirs.len() - common_suffix],
+            );
+        }
+
+        self.resolution = Resolution::Conflict;
+        self.write_marker(b'<', self.labels.ours);
+        self.write_lines_with_newline(ours);
+        if self.options.conflict_style == ConflictStyle::Diff3 {
+            self.write_marker(b'|', self.labels.base);
+            self.write_lines_with_newline(base);
+        }
+        self.write_marker(b'=', None);
+        self.write_lines_with_newline(theirs);
+        self.write_marker(b'>', self.labels.theirs);
+        self.write_lines(suffix);
+    }
+
+    /// Write `tokens` and make sure the output ends with a newline so conflict markers are on their own line.
+    fn write_lines_with_newline(&mut self, tokens: &[Token]) {
+        self.write_lines(tokens);
+        if !tokens.is_empty() && !self.out.ends_with(b"\n") {
+            self.out.push(b'\n');
+        }
+    }
+
+    fn write_marker(&mut self, marker: u8, label: Option<&BStr>) {
+        self.out
+            .extend(std::iter::repeat(marker).take(self.options.marker_size));
+        if let Some(label) = label {
+            self.out.push(b' ');
+            self.out.extend_from_slice(label);
+        }
+        self.out.push(b'\n');
+    }
+}
@@ -0,0 +1,8 @@
+//! Merge algorithms to combine the changes of two sides that diverged from a common base.
+#![deny(missing_docs, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+///
+pub mod blob;
+///
+pub mod tree;
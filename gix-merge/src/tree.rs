@@ -0,0 +1,257 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_hash::{oid, ObjectId};
+use gix_index::entry::{Flags, Mode, Stat};
+use gix_object::FindExt;
+
+use crate::blob;
+
+/// Options for use in [`merge()`].
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Options {
+    /// How to merge files that were changed on both sides.
+    pub blob: blob::Options,
+}
+
+/// The kind of conflict that prevented a path from being merged.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ConflictKind {
+    /// Both sides changed or added the file in ways that couldn't be merged.
+    Content,
+    /// One side changed the file while the other side deleted it.
+    ModifyDelete,
+    /// One side placed a file where the other side has a directory.
+    DirectoryFile,
+}
+
+/// A path that could not be merged.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Conflict {
+    /// The path of the file that could not be merged.
+    pub path: BString,
+    /// The kind of conflict.
+    pub kind: ConflictKind,
+    /// The blob with the merged content and conflict markers, if both sides changed a file's content.
+    ///
+    /// This is what would be written to the worktree.
+    pub merged_blob: Option<ObjectId>,
+}
+
+/// The outcome of [`merge()`].
+#[derive(Clone)]
+pub struct Outcome {
+    /// The merged entries, with entries at stage 1 (the base), 2 (ours) and 3 (theirs) for each conflicting path,
+    /// like `git` records them in its index.
+    pub index: gix_index::State,
+    /// All paths that couldn't be merged, in order.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Outcome {
+    /// Return `true` if there are conflicts.
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+
+    /// Return the entries as they should be checked out into a worktree, which are all merged entries along with the
+    /// merged version of each conflicting file with conflict markers, or our version of it if it couldn't be merged.
+    ///
+    /// Paths of conflicts between directories and files are left out, as the directory is kept.
+    pub fn worktree_state(&self) -> gix_index::State {
+        let mut state = gix_index::State::new(self.index.object_hash());
+        for entry in self.index.entries().iter().filter(|entry| entry.stage() == 0) {
+            state.dangerously_push_entry(entry.stat, entry.id, Flags::empty(), entry.mode, entry.path(&self.index));
+        }
+        for conflict in self
+            .conflicts
+            .iter()
+            .filter(|conflict| conflict.kind != ConflictKind::DirectoryFile)
+        {
+            let stage = |stage| self.index.entry_by_path_and_stage(conflict.path.as_bstr(), stage);
+            let Some(side) = stage(2).or_else(|| stage(3)) else {
+                continue;
+            };
+            state.dangerously_push_entry(
+                Stat::default(),
+                conflict.merged_blob.unwrap_or(side.id),
+                Flags::empty(),
+                side.mode,
+                conflict.path.as_bstr(),
+            );
+        }
+        state.sort_entries();
+        state
+    }
+}
+
+/// The error returned by [`merge()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    TreeTraversal(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    FindBlob(#[from] gix_object::find::existing_object::Error),
+    #[error("Could not write merged blob")]
+    WriteBlob(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+/// An entry of a tree, flattened into its mode and id.
+type Entry = (Mode, ObjectId);
+
+/// Merge the changes between the trees `base` and `ours` and between `base` and `theirs` with a three-way merge,
+/// reading objects from `objects` and writing merged blobs with `write_blob`, and configure the operation with `options`.
+///
+/// If `base` is `None`, the empty tree is used instead, which happens if two histories have no common ancestor.
+/// Files that changed on both sides are merged line by line, with `labels` being used for conflict markers.
+///
+/// Note that renames are not detected, so renamed files are merged as if they were deleted and added.
+pub fn merge<E>(
+    base: Option<&oid>,
+    ours: &oid,
+    theirs: &oid,
+    objects: impl gix_object::Find,
+    mut write_blob: impl FnMut(&[u8]) -> Result<ObjectId, E>,
+    labels: blob::Labels<'_>,
+    options: Options,
+) -> Result<Outcome, Error>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let object_hash = ours.kind();
+    let states = [
+        flatten(base, &objects, object_hash)?,
+        flatten(Some(ours), &objects, object_hash)?,
+        flatten(Some(theirs), &objects, object_hash)?,
+    ];
+    let mut paths = BTreeMap::<&BStr, [Option<Entry>; 3]>::new();
+    for (side, state) in states.iter().enumerate() {
+        for entry in state.entries() {
+            paths.entry(entry.path(state)).or_default()[side] = Some((entry.mode, entry.id));
+        }
+    }
+
+    let mut merged = BTreeMap::<&BStr, (Entry, [Option<Entry>; 3])>::new();
+    let mut conflicts = BTreeMap::<&BStr, (Conflict, [Option<Entry>; 3])>::new();
+    let (mut base_buf, mut ours_buf, mut theirs_buf) = (Vec::new(), Vec::new(), Vec::new());
+    let mut out = Vec::new();
+    for (path, stages @ [base, ours, theirs]) in paths {
+        let resolved = if ours == theirs || base == theirs {
+            ours
+        } else if base == ours {
+            theirs
+        } else {
+            let conflict = |kind, merged_blob| {
+                let conflict = Conflict {
+                    path: path.to_owned(),
+                    kind,
+                    merged_blob,
+                };
+                (conflict, stages)
+            };
+            match (ours, theirs) {
+                (Some(ours), Some(theirs)) if is_file(ours.0) && is_file(theirs.0) => {
+                    let base = base.filter(|base| is_file(base.0));
+                    let mode = if ours.0 == theirs.0 || base.map(|b| b.0) == Some(theirs.0) {
+                        Some(ours.0)
+                    } else if base.map(|b| b.0) == Some(ours.0) {
+                        Some(theirs.0)
+                    } else {
+                        None
+                    };
+                    let (resolution, id) = if ours.1 == theirs.1 {
+                        (blob::Resolution::Complete, ours.1)
+                    } else {
+                        let base_data = match base {
+                            Some(base) => objects.find_blob(&base.1, &mut base_buf)?.data,
+                            None => &[],
+                        };
+                        let ours_data = objects.find_blob(&ours.1, &mut ours_buf)?.data;
+                        let theirs_data = objects.find_blob(&theirs.1, &mut theirs_buf)?.data;
+                        let resolution = blob::merge(base_data, ours_data, theirs_data, labels, options.blob, &mut out);
+                        let id = write_blob(&out).map_err(|err| Error::WriteBlob(Box::new(err)))?;
+                        (resolution, id)
+                    };
+                    match (resolution, mode) {
+                        (blob::Resolution::Complete, Some(mode)) => Some((mode, id)),
+                        _ => {
+                            let (conflict, stages) = conflict(ConflictKind::Content, Some(id));
+                            conflicts.insert(path, (conflict, stages));
+                            continue;
+                        }
+                    }
+                }
+                (Some(_), Some(_)) => {
+                    conflicts.insert(path, conflict(ConflictKind::Content, None));
+                    continue;
+                }
+                (Some(_), None) | (None, Some(_)) => {
+                    conflicts.insert(path, conflict(ConflictKind::ModifyDelete, None));
+                    continue;
+                }
+                (None, None) => unreachable!("both sides are equal if the path was deleted on both"),
+            }
+        };
+        if let Some(entry) = resolved {
+            merged.insert(path, (entry, stages));
+        }
+    }
+
+    // A file can't be merged if there is a directory of the same name on the other side.
+    let all_paths: BTreeSet<&BStr> = merged.keys().chain(conflicts.keys()).copied().collect();
+    for (path, (_entry, stages)) in merged.clone() {
+        let mut dir = BString::from(path);
+        dir.push(b'/');
+        let is_directory_elsewhere = all_paths
+            .range::<&BStr, _>(dir.as_bstr()..)
+            .next()
+            .map_or(false, |other| other.starts_with(&dir));
+        if !is_directory_elsewhere {
+            continue;
+        }
+        merged.remove(path);
+        conflicts.insert(
+            path,
+            (
+                Conflict {
+                    path: path.to_owned(),
+                    kind: ConflictKind::DirectoryFile,
+                    merged_blob: None,
+                },
+                stages,
+            ),
+        );
+    }
+
+    let mut index = gix_index::State::new(object_hash);
+    for (path, ((mode, id), _stages)) in &merged {
+        index.dangerously_push_entry(Stat::default(), *id, Flags::empty(), *mode, path);
+    }
+    for (path, (_conflict, stages)) in &conflicts {
+        for (stage, (mode, id)) in (1..).zip(stages).filter_map(|(stage, entry)| entry.map(|e| (stage, e))) {
+            index.dangerously_push_entry(Stat::default(), id, Flags::from_stage(stage), mode, path);
+        }
+    }
+    index.sort_entries();
+    Ok(Outcome {
+        index,
+        conflicts: conflicts.into_values().map(|(conflict, _stages)| conflict).collect(),
+    })
+}
+
+/// Return all entries of `tree`, or no entries if it is `None` or the empty tree.
+fn flatten(
+    tree: Option<&oid>,
+    objects: &impl gix_object::Find,
+    object_hash: gix_hash::Kind,
+) -> Result<gix_index::State, Error> {
+    Ok(match tree.filter(|tree| *tree != ObjectId::empty_tree(object_hash)) {
+        Some(tree) => gix_index::State::from_tree(tree, objects)?,
+        None => gix_index::State::new(object_hash),
+    })
+}
+
+fn is_file(mode: Mode) -> bool {
+    mode == Mode::FILE || mode == Mode::FILE_EXECUTABLE
+}
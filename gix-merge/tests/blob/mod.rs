@@ -0,0 +1,75 @@
+use gix_merge::blob::{merge, ConflictStyle, Labels, Options, Resolution};
+
+#[test]
+fn merges_match_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_blob_merges.sh")?;
+    let labels = Labels {
+        base: Some("base".into()),
+        ours: Some("ours".into()),
+        theirs: Some("theirs".into()),
+    };
+    for (name, expected_resolution) in [
+        ("disjoint", Resolution::Complete),
+        ("same-change", Resolution::Complete),
+        ("conflict", Resolution::Conflict),
+        ("adjacent", Resolution::Conflict),
+        ("common-ends", Resolution::Conflict),
+        ("insertions", Resolution::Conflict),
+        ("deletions", Resolution::Complete),
+        ("no-newline", Resolution::Conflict),
+    ] {
+        let read = |ext: &str| std::fs::read(root.join(format!("{name}.{ext}")));
+        let (base, ours, theirs) = (read("base")?, read("ours")?, read("theirs")?);
+        for (style, conflict_style) in [("merge", ConflictStyle::Merge), ("diff3", ConflictStyle::Diff3)] {
+            let mut out = Vec::new();
+            let resolution = merge(
+                &base,
+                &ours,
+                &theirs,
+                labels,
+                Options {
+                    conflict_style,
+                    ..Default::default()
+                },
+                &mut out,
+            );
+            assert_eq!(resolution, expected_resolution, "{name} ({style})");
+            assert_eq!(
+                gix_testtools::bstr::BStr::new(&out),
+                gix_testtools::bstr::BStr::new(&read(&format!("{style}.expected"))?),
+                "{name} ({style}): the output must be the same as the one of git"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn one_sided_changes_are_taken_verbatim() {
+    let mut out = Vec::new();
+    let base = b"a\nb\n";
+    let changed = b"a\nchanged\n";
+    for (ours, theirs) in [(&base[..], &changed[..]), (&changed[..], &base[..])] {
+        let resolution = merge(base, ours, theirs, Labels::default(), Options::default(), &mut out);
+        assert_eq!(resolution, Resolution::Complete);
+        assert_eq!(out, changed);
+    }
+}
+
+#[test]
+fn markers_without_labels_and_custom_size() {
+    let mut out = Vec::new();
+    let resolution = merge(
+        b"a\n",
+        b"ours\n",
+        b"theirs\n",
+        Labels::default(),
+        Options {
+            marker_size: 3,
+            ..Default::default()
+        },
+        &mut out,
+    );
+    assert_eq!(resolution, Resolution::Conflict);
+    assert_eq!(out, b"<<<\nours\n===\ntheirs\n>>>\n");
+}
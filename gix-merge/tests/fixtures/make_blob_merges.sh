@@ -0,0 +1,55 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Each case consists of `<name>.base`, `<name>.ours` and `<name>.theirs`, along with the output of `git merge-file`
+# in `<name>.merge.expected` and `<name>.diff3.expected`.
+function baseline() {
+  local name=${1:?need name}
+  for style in merge diff3; do
+    local args=()
+    if [ $style = diff3 ]; then
+      args=(--diff3)
+    fi
+    git merge-file -p "${args[@]}" -L ours -L base -L theirs "$name.ours" "$name.base" "$name.theirs" > "$name.$style.expected" || true
+  done
+}
+
+seq 1 10 > disjoint.base
+seq 1 10 | sed -e '2s/$/ ours/' > disjoint.ours
+seq 1 10 | sed -e '9s/$/ theirs/' > disjoint.theirs
+baseline disjoint
+
+seq 1 10 > same-change.base
+seq 1 10 | sed -e '5s/$/ both/' > same-change.ours
+cp same-change.ours same-change.theirs
+baseline same-change
+
+seq 1 10 > conflict.base
+seq 1 10 | sed -e '5s/$/ ours/' > conflict.ours
+seq 1 10 | sed -e '5s/$/ theirs/' > conflict.theirs
+baseline conflict
+
+seq 1 10 > adjacent.base
+seq 1 10 | sed -e '4s/$/ ours/' > adjacent.ours
+seq 1 10 | sed -e '5s/$/ theirs/' > adjacent.theirs
+baseline adjacent
+
+seq 1 10 > common-ends.base
+seq 1 10 | sed -e '4,6d' -e '3a same\nours\nsame-too' > common-ends.ours
+seq 1 10 | sed -e '4,6d' -e '3a same\ntheirs\nsame-too' > common-ends.theirs
+baseline common-ends
+
+seq 1 10 > insertions.base
+seq 1 10 | sed -e '1i first' -e '$a last-ours' > insertions.ours
+seq 1 10 | sed -e '5a middle' -e '$a last-theirs' > insertions.theirs
+baseline insertions
+
+seq 1 5 > deletions.base
+seq 1 5 | sed -e '2d' > deletions.ours
+seq 1 5 | sed -e '4d' > deletions.theirs
+baseline deletions
+
+printf 'a\nb\nc' > no-newline.base
+printf 'a\nb\nours' > no-newline.ours
+printf 'a\nb\ntheirs' > no-newline.theirs
+baseline no-newline
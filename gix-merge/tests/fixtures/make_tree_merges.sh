@@ -0,0 +1,77 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Each repository has the branches `ours` and `theirs` which diverged from `base`, with the trees of `base`, `ours`
+# and `theirs` in `<name>.trees`, along with the output of `git merge-tree --write-tree ours theirs` in `<name>.expected`.
+function trees() {
+  local name=${1:?need name}
+  git -C "$name" rev-parse 'base^{tree}' 'ours^{tree}' 'theirs^{tree}' > "$name.trees"
+}
+
+function baseline() {
+  local name=${1:?need name}
+  trees "$name"
+  git -C "$name" merge-tree --write-tree ours theirs > "$name.expected" || true
+}
+
+git init -q clean
+(cd clean
+  seq 1 10 > a
+  echo b > b
+  mkdir dir && echo c > dir/c
+  echo deleted > deleted
+  echo same > same-change
+  git add . && git commit -q -m base && git branch base
+
+  git checkout -q -b ours base
+  sed -i -e 's/^2$/ours/' a
+  echo new > ours-new
+  git rm -q deleted
+  chmod +x b
+  echo changed > same-change
+  git add . && git commit -q -m ours
+
+  git checkout -q -b theirs base
+  sed -i -e 's/^9$/theirs/' a
+  echo new > dir/theirs-new
+  echo changed >> dir/c
+  echo changed > same-change
+  git add . && git commit -q -m theirs
+)
+baseline clean
+
+git init -q conflicts
+(cd conflicts
+  seq 1 10 > content
+  seq 1 10 > modify-delete
+  seq 1 10 > delete-modify
+  git add . && git commit -q -m base && git branch base
+
+  git checkout -q -b ours base
+  sed -i -e 's/^5$/ours/' content modify-delete
+  git rm -q delete-modify
+  echo ours > add-add
+  git add . && git commit -q -m ours
+
+  git checkout -q -b theirs base
+  sed -i -e 's/^5$/theirs/' content delete-modify
+  git rm -q modify-delete
+  echo theirs > add-add
+  git add . && git commit -q -m theirs
+)
+baseline conflicts
+
+git init -q directory-file
+(cd directory-file
+  echo base > file
+  git add . && git commit -q -m base && git branch base
+
+  git checkout -q -b ours base
+  echo ours > df
+  git add . && git commit -q -m ours
+
+  git checkout -q -b theirs base
+  mkdir df && echo theirs > df/file
+  git add . && git commit -q -m theirs
+)
+trees directory-file
@@ -0,0 +1,119 @@
+use gix_merge::{
+    blob::Labels,
+    tree::{merge, ConflictKind, Options, Outcome},
+};
+use gix_odb::Write;
+use gix_testtools::bstr::ByteSlice;
+
+/// Merge the `ours` and `theirs` trees of the repository `name`, returning the outcome along with the object database
+/// and the directory containing all repositories and baselines.
+fn merge_trees(name: &str) -> crate::Result<(Outcome, gix_odb::Handle, gix_testtools::tempfile::TempDir)> {
+    let tmp = gix_testtools::scripted_fixture_writable("make_tree_merges.sh")?;
+    let root = tmp.path();
+    let trees = std::fs::read(root.join(format!("{name}.trees")))?;
+    let trees = trees
+        .lines()
+        .map(gix_hash::ObjectId::from_hex)
+        .collect::<Result<Vec<_>, _>>()?;
+    let odb = gix_odb::at(root.join(name).join(".git").join("objects"))?;
+    let outcome = merge(
+        Some(&trees[0]),
+        &trees[1],
+        &trees[2],
+        &odb,
+        |data| {
+            odb.write_buf(gix_object::Kind::Blob, data)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
+        },
+        Labels {
+            base: None,
+            ours: Some("ours".into()),
+            theirs: Some("theirs".into()),
+        },
+        Options::default(),
+    )?;
+    Ok((outcome, odb, tmp))
+}
+
+#[test]
+fn clean_merges_match_git() -> crate::Result {
+    let (outcome, odb, root) = merge_trees("clean")?;
+    assert!(!outcome.has_conflicts());
+    let tree = outcome.index.write_tree(|tree| {
+        odb.write(tree)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
+    })?;
+    let expected = std::fs::read(root.path().join("clean.expected"))?;
+    assert_eq!(
+        tree.to_string(),
+        expected.trim().to_str_lossy(),
+        "the tree must be the same as the one of git"
+    );
+    Ok(())
+}
+
+#[test]
+fn conflicts_are_recorded_in_stages_like_git() -> crate::Result {
+    let (outcome, odb, root) = merge_trees("conflicts")?;
+    assert_eq!(
+        outcome
+            .conflicts
+            .iter()
+            .map(|conflict| (conflict.path.to_str_lossy().into_owned(), conflict.kind))
+            .collect::<Vec<_>>(),
+        [
+            ("add-add".into(), ConflictKind::Content),
+            ("content".into(), ConflictKind::Content),
+            ("delete-modify".into(), ConflictKind::ModifyDelete),
+            ("modify-delete".into(), ConflictKind::ModifyDelete),
+        ]
+    );
+
+    let actual: Vec<_> = outcome
+        .index
+        .entries()
+        .iter()
+        .map(|entry| {
+            format!(
+                "{:o} {} {}\t{}",
+                entry.mode.bits(),
+                entry.id,
+                entry.stage(),
+                entry.path(&outcome.index)
+            )
+        })
+        .collect();
+    let expected = std::fs::read(root.path().join("conflicts.expected"))?;
+    let expected: Vec<_> = expected
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .map(|line| line.to_str_lossy().into_owned())
+        .collect();
+    assert_eq!(actual, expected, "the stages must be the same as the ones of git");
+
+    let content = outcome.conflicts[1]
+        .merged_blob
+        .expect("both sides changed the content");
+    let mut buf = Vec::new();
+    let data = gix_object::FindExt::find_blob(&odb, &content, &mut buf)?.data;
+    assert_eq!(
+        data.as_bstr(),
+        "1\n2\n3\n4\n<<<<<<< ours\nours\n=======\ntheirs\n>>>>>>> theirs\n6\n7\n8\n9\n10\n",
+        "the merged blob has conflict markers"
+    );
+    Ok(())
+}
+
+#[test]
+fn directory_file_conflicts() -> crate::Result {
+    let (outcome, _odb, _root) = merge_trees("directory-file")?;
+    assert_eq!(outcome.conflicts.len(), 1);
+    assert_eq!(outcome.conflicts[0].path, "df");
+    assert_eq!(outcome.conflicts[0].kind, ConflictKind::DirectoryFile);
+    assert!(
+        outcome.index.entry_by_path("df/file".into()).is_some(),
+        "the directory is kept as is"
+    );
+    Ok(())
+}
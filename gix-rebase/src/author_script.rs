@@ -0,0 +1,105 @@
+//! The author of the commit that is currently applied, as stored in `author-script` in the format of a shell script
+//! that sets `GIT_AUTHOR_NAME`, `GIT_AUTHOR_EMAIL` and `GIT_AUTHOR_DATE`.
+use bstr::{BString, ByteSlice, ByteVec};
+
+///
+pub mod decode {
+    use bstr::BString;
+
+    /// The error returned by [`decode()`](super::decode()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The line '{line}' is not a variable assignment with a quoted value")]
+        InvalidLine { line: BString },
+        #[error("The variable '{name}' is missing")]
+        MissingVariable { name: &'static str },
+        #[error("The author date '{date}' could not be parsed")]
+        InvalidDate {
+            date: BString,
+            source: Option<gix_date::parse::Error>,
+        },
+    }
+}
+
+/// Decode the author script in `input` into the signature of the author.
+///
+/// Dates are expected in the format `@<seconds> <offset>`, which is what `git` writes.
+pub fn decode(input: &[u8]) -> Result<gix_actor::Signature, decode::Error> {
+    let (mut name, mut email, mut date) = (None, None, None);
+    for line in input.lines().filter(|line| !line.is_empty()) {
+        let invalid = || decode::Error::InvalidLine { line: line.into() };
+        let (variable, value) = line.split_once_str("=").ok_or_else(invalid)?;
+        let value = unquote(value).ok_or_else(invalid)?;
+        match variable {
+            b"GIT_AUTHOR_NAME" => name = Some(value),
+            b"GIT_AUTHOR_EMAIL" => email = Some(value),
+            b"GIT_AUTHOR_DATE" => date = Some(value),
+            _ => {}
+        }
+    }
+    let date = date.ok_or(decode::Error::MissingVariable {
+        name: "GIT_AUTHOR_DATE",
+    })?;
+    let time = date
+        .to_str()
+        .map_err(|_| decode::Error::InvalidDate {
+            date: date.clone(),
+            source: None,
+        })
+        .and_then(|raw| {
+            gix_date::parse(raw.strip_prefix('@').unwrap_or(raw), None).map_err(|err| decode::Error::InvalidDate {
+                date: date.clone(),
+                source: Some(err),
+            })
+        })?;
+    Ok(gix_actor::Signature {
+        name: name.ok_or(decode::Error::MissingVariable {
+            name: "GIT_AUTHOR_NAME",
+        })?,
+        email: email.ok_or(decode::Error::MissingVariable {
+            name: "GIT_AUTHOR_EMAIL",
+        })?,
+        time,
+    })
+}
+
+/// Encode `author` as author script, just like `git` does.
+pub fn encode(author: gix_actor::SignatureRef<'_>) -> BString {
+    let mut out = BString::default();
+    for (variable, value) in [
+        ("GIT_AUTHOR_NAME", author.name.to_owned()),
+        ("GIT_AUTHOR_EMAIL", author.email.to_owned()),
+        ("GIT_AUTHOR_DATE", format!("@{}", author.time.to_bstring()).into()),
+    ] {
+        out.push_str(variable);
+        out.push(b'=');
+        out.extend_from_slice(&gix_quote::single(value.as_ref()));
+        out.push(b'\n');
+    }
+    out
+}
+
+/// Undo the quoting of a value as done by [`gix_quote::single()`], or return `None` if the quotes aren't balanced.
+fn unquote(mut input: &[u8]) -> Option<BString> {
+    let mut out = BString::default();
+    while let Some((&first, rest)) = input.split_first() {
+        match first {
+            b'\'' => {
+                let end = rest.find_byte(b'\'')?;
+                out.extend_from_slice(&rest[..end]);
+                input = &rest[end + 1..];
+            }
+            b'\\' => {
+                let (&escaped, rest) = rest.split_first()?;
+                out.push(escaped);
+                input = rest;
+            }
+            _ => {
+                out.push(first);
+                input = rest;
+            }
+        }
+    }
+    Some(out)
+}
@@ -1,2 +1,13 @@
-#![deny(rust_2018_idioms)]
+//! Read and write the state of rebases in progress compatibly with `git`, to obtain their status and to drive them.
+//!
+//! The sequence of instructions to execute is a [todo list](gix_sequencer::todo::List), which is executed by higher-level
+//! crates that can apply commits.
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+///
+pub mod author_script;
+
+///
+pub mod state;
+pub use state::{Backend, State};
@@ -0,0 +1,380 @@
+use std::path::{Path, PathBuf};
+
+use bstr::{BString, ByteSlice, ByteVec};
+use gix_hash::ObjectId;
+use gix_ref::FullName;
+use gix_sequencer::{file, todo};
+
+/// The way a rebase is performed, which determines where and how its state is stored.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Backend {
+    /// Commits are applied by merging them, with the state stored in `.git/rebase-merge`.
+    ///
+    /// This is the default backend of `git`, and the one used by interactive rebases.
+    Merge,
+    /// Commits are applied as patches, with the state stored in `.git/rebase-apply`, as done by `git rebase --apply`.
+    Apply,
+}
+
+impl Backend {
+    /// Return the name of the directory within the `.git` directory in which the state is stored.
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            Backend::Merge => "rebase-merge",
+            Backend::Apply => "rebase-apply",
+        }
+    }
+}
+
+/// How far a rebase has progressed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Progress {
+    /// The one-based number of the current step.
+    pub current: usize,
+    /// The total amount of steps.
+    pub total: usize,
+}
+
+/// A reference to update once a rebase is done, as stored in `update-refs`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct UpdateRef {
+    /// The name of the reference to update.
+    pub name: FullName,
+    /// The commit the reference pointed to when the rebase started.
+    pub before: ObjectId,
+    /// The commit the reference will point to, or `None` if the instruction to update it wasn't executed yet.
+    pub after: Option<ObjectId>,
+}
+
+/// The state of a rebase in progress.
+///
+/// Some of the information is only available with the [merge backend](Backend::Merge), and fields that are only set while
+/// the rebase is stopped are `None` otherwise.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct State {
+    /// The way the rebase is performed.
+    pub backend: Backend,
+    /// The name of the branch that is rebased, or `None` if `HEAD` was detached, as stored in `head-name`.
+    pub head_name: Option<FullName>,
+    /// The commit onto which the commits are applied, as stored in `onto`.
+    pub onto: ObjectId,
+    /// The commit `HEAD` pointed to when the rebase started, as stored in `orig-head`.
+    pub orig_head: ObjectId,
+    /// If `true`, the rebase is interactive, and the marker file `interactive` exists.
+    pub interactive: bool,
+    /// The instructions that are yet to be executed, as stored in `git-rebase-todo`.
+    pub todo: todo::List,
+    /// The instructions that were executed, with the last one being the current one, as stored in `done`.
+    pub done: todo::List,
+    /// How far the rebase has progressed, as stored in `msgnum` and `end`, or `next` and `last` with the apply backend.
+    pub progress: Option<Progress>,
+    /// The original commit that is currently applied, as stored in `stopped-sha`, or `original-commit` with the apply backend.
+    pub stopped_at: Option<ObjectId>,
+    /// The commit to amend when continuing, as stored in `amend`.
+    pub amend: Option<ObjectId>,
+    /// The message of the commit to create when continuing, as stored in `message`.
+    pub message: Option<BString>,
+    /// The author of the commit to create when continuing, as stored in `author-script`.
+    pub author: Option<gix_actor::Signature>,
+    /// References to update once the rebase is done, as stored in `update-refs`.
+    pub update_refs: Vec<UpdateRef>,
+}
+
+impl State {
+    /// Create the state of a new rebase with `backend` that will apply the commits in `todo` onto `onto`, starting at
+    /// `orig_head` on the branch `head_name`.
+    pub fn new(
+        backend: Backend,
+        head_name: Option<FullName>,
+        onto: ObjectId,
+        orig_head: ObjectId,
+        todo: todo::List,
+    ) -> Self {
+        State {
+            backend,
+            head_name,
+            onto,
+            orig_head,
+            interactive: false,
+            todo,
+            done: Default::default(),
+            progress: None,
+            stopped_at: None,
+            amend: None,
+            message: None,
+            author: None,
+            update_refs: Vec::new(),
+        }
+    }
+
+    /// Return the path to the directory holding the state of a rebase with `backend` within `git_dir`.
+    pub fn dir(git_dir: &Path, backend: Backend) -> PathBuf {
+        git_dir.join(backend.dir_name())
+    }
+
+    /// Return the amount of instructions that were executed and the total amount of instructions, which is what `git`
+    /// stores in `msgnum` and `end`.
+    pub fn todo_progress(&self) -> Progress {
+        let current = self.done.instructions.len();
+        Progress {
+            current,
+            total: current + self.todo.instructions.len(),
+        }
+    }
+}
+
+///
+pub mod read {
+    use std::path::PathBuf;
+
+    use bstr::BString;
+
+    /// The error returned by [`State::read_from()`](super::State::read_from()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        File(#[from] gix_sequencer::file::Error),
+        #[error("The required file '{}' is missing", path.display())]
+        Missing { path: PathBuf },
+        #[error("Could not parse the number in '{}'", path.display())]
+        Number { path: PathBuf },
+        #[error("'{name}' in '{}' is not a valid reference name", path.display())]
+        RefName {
+            path: PathBuf,
+            name: BString,
+            source: gix_validate::reference::name::Error,
+        },
+        #[error("The file '{}' is malformed", path.display())]
+        Malformed { path: PathBuf },
+        #[error("Could not parse the todo list in '{}'", path.display())]
+        Todo {
+            path: PathBuf,
+            source: gix_sequencer::todo::parse::Error,
+        },
+        #[error("Could not parse the author script in '{}'", path.display())]
+        AuthorScript {
+            path: PathBuf,
+            source: crate::author_script::decode::Error,
+        },
+    }
+}
+
+/// Reading and writing
+impl State {
+    /// Read the state of the rebase in progress in `git_dir`, or return `None` if there is none.
+    ///
+    /// Note that `.git/rebase-apply` is also used by `git am`, which isn't considered a rebase.
+    pub fn read_from(git_dir: &Path) -> Result<Option<Self>, read::Error> {
+        let merge_dir = Self::dir(git_dir, Backend::Merge);
+        let apply_dir = Self::dir(git_dir, Backend::Apply);
+        if merge_dir.is_dir() {
+            read_merge(&merge_dir).map(Some)
+        } else if apply_dir.join("rebasing").is_file() {
+            read_apply(&apply_dir).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Write this state into its directory in `git_dir`, creating it if needed, and removing files for fields that
+    /// aren't set.
+    pub fn write_to(&self, git_dir: &Path) -> std::io::Result<()> {
+        let dir = Self::dir(git_dir, self.backend);
+        std::fs::create_dir_all(&dir)?;
+        let write = |name: &str, data: Option<&[u8]>| -> std::io::Result<()> {
+            let path = dir.join(name);
+            match data {
+                Some(data) => std::fs::write(path, data),
+                None => file::remove(&path),
+            }
+        };
+        let with_newline = |value: &dyn std::fmt::Display| format!("{value}\n").into_bytes();
+        let head_name = match &self.head_name {
+            Some(name) => name.as_bstr().to_owned(),
+            None => "detached HEAD".into(),
+        };
+        write("head-name", Some(&with_newline(&head_name)))?;
+        write("onto", Some(&with_newline(&self.onto)))?;
+        write("orig-head", Some(&with_newline(&self.orig_head)))?;
+        match self.backend {
+            Backend::Merge => {
+                write("interactive", self.interactive.then_some(&[][..]))?;
+                write("git-rebase-todo", Some(&self.todo.to_bstring()))?;
+                write("done", Some(&self.done.to_bstring()))?;
+                let progress = self.progress.unwrap_or_else(|| self.todo_progress());
+                write("msgnum", Some(&with_newline(&progress.current)))?;
+                write("end", Some(&with_newline(&progress.total)))?;
+                write("stopped-sha", self.stopped_at.map(|id| with_newline(&id)).as_deref())?;
+                write("amend", self.amend.map(|id| with_newline(&id)).as_deref())?;
+                write("message", self.message.as_ref().map(|message| message.as_bytes()))?;
+                write(
+                    "author-script",
+                    self.author
+                        .as_ref()
+                        .map(|author| crate::author_script::encode(author.to_ref()))
+                        .as_ref()
+                        .map(|script| script.as_bytes()),
+                )?;
+                let update_refs = (!self.update_refs.is_empty()).then(|| {
+                    let mut out = BString::default();
+                    for update in &self.update_refs {
+                        out.push_str(update.name.as_bstr());
+                        out.push(b'\n');
+                        out.push_str(format!("{}\n", update.before));
+                        out.push_str(format!("{}\n", update.after.unwrap_or_else(|| self.onto.kind().null())));
+                    }
+                    out
+                });
+                write("update-refs", update_refs.as_ref().map(|refs| refs.as_bytes()))?;
+            }
+            Backend::Apply => {
+                write("rebasing", Some(&[]))?;
+                let progress = self.progress.unwrap_or(Progress { current: 1, total: 0 });
+                write("next", Some(&with_newline(&progress.current)))?;
+                write("last", Some(&with_newline(&progress.total)))?;
+                write(
+                    "original-commit",
+                    self.stopped_at.map(|id| with_newline(&id)).as_deref(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the directories of all rebase backends in `git_dir` along with all of their contents, if they exist.
+    pub fn remove(git_dir: &Path) -> std::io::Result<()> {
+        for backend in [Backend::Merge, Backend::Apply] {
+            match std::fs::remove_dir_all(Self::dir(git_dir, backend)) {
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                res => res?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_merge(dir: &Path) -> Result<State, read::Error> {
+    let todo = read_todo(&dir.join("git-rebase-todo"))?;
+    let done = read_todo(&dir.join("done"))?;
+    let progress = match (read_number(&dir.join("msgnum"))?, read_number(&dir.join("end"))?) {
+        (Some(current), Some(total)) => Some(Progress { current, total }),
+        _ => None,
+    };
+    let author_path = dir.join("author-script");
+    let author = file::read(&author_path)?
+        .map(|script| {
+            crate::author_script::decode(&script).map_err(|source| read::Error::AuthorScript {
+                path: author_path.clone(),
+                source,
+            })
+        })
+        .transpose()?;
+    Ok(State {
+        backend: Backend::Merge,
+        head_name: read_head_name(dir)?,
+        onto: read_required_id(&dir.join("onto"))?,
+        orig_head: read_required_id(&dir.join("orig-head"))?,
+        interactive: dir.join("interactive").is_file(),
+        todo,
+        done,
+        progress,
+        stopped_at: file::read_id(&dir.join("stopped-sha"))?,
+        amend: file::read_id(&dir.join("amend"))?,
+        message: file::read(&dir.join("message"))?.map(Into::into),
+        author,
+        update_refs: read_update_refs(&dir.join("update-refs"))?,
+    })
+}
+
+fn read_apply(dir: &Path) -> Result<State, read::Error> {
+    let progress = match (read_number(&dir.join("next"))?, read_number(&dir.join("last"))?) {
+        (Some(current), Some(total)) => Some(Progress { current, total }),
+        _ => None,
+    };
+    Ok(State {
+        backend: Backend::Apply,
+        head_name: read_head_name(dir)?,
+        onto: read_required_id(&dir.join("onto"))?,
+        orig_head: read_required_id(&dir.join("orig-head"))?,
+        interactive: false,
+        todo: Default::default(),
+        done: Default::default(),
+        progress,
+        stopped_at: file::read_id(&dir.join("original-commit"))?,
+        amend: None,
+        message: None,
+        author: None,
+        update_refs: Vec::new(),
+    })
+}
+
+fn read_head_name(dir: &Path) -> Result<Option<FullName>, read::Error> {
+    let path = dir.join("head-name");
+    let name = file::read(&path)?.ok_or_else(|| read::Error::Missing { path: path.clone() })?;
+    let name = file::trim(&name);
+    if name == b"detached HEAD" {
+        return Ok(None);
+    }
+    FullName::try_from(name.as_bstr())
+        .map(Some)
+        .map_err(|source| read::Error::RefName {
+            path,
+            name: name.into(),
+            source,
+        })
+}
+
+fn read_update_refs(path: &Path) -> Result<Vec<UpdateRef>, read::Error> {
+    let Some(data) = file::read(path)? else {
+        return Ok(Vec::new());
+    };
+    let malformed = || read::Error::Malformed { path: path.to_owned() };
+    let lines: Vec<_> = data.lines().collect();
+    if lines.len() % 3 != 0 {
+        return Err(malformed());
+    }
+    lines
+        .chunks(3)
+        .map(|chunk| {
+            let name = FullName::try_from(chunk[0].as_bstr()).map_err(|source| read::Error::RefName {
+                path: path.to_owned(),
+                name: chunk[0].into(),
+                source,
+            })?;
+            let id = |hex: &[u8]| ObjectId::from_hex(hex).map_err(|_| malformed());
+            let after = id(chunk[2])?;
+            Ok(UpdateRef {
+                name,
+                before: id(chunk[1])?,
+                after: (!after.is_null()).then_some(after),
+            })
+        })
+        .collect()
+}
+
+fn read_todo(path: &Path) -> Result<todo::List, read::Error> {
+    match file::read(path)? {
+        Some(data) => todo::List::from_bytes(&data).map_err(|source| read::Error::Todo {
+            path: path.to_owned(),
+            source,
+        }),
+        None => Ok(Default::default()),
+    }
+}
+
+fn read_number(path: &Path) -> Result<Option<usize>, read::Error> {
+    file::read(path)?
+        .map(|data| {
+            file::trim(&data)
+                .to_str()
+                .ok()
+                .and_then(|number| number.parse().ok())
+                .ok_or_else(|| read::Error::Number { path: path.to_owned() })
+        })
+        .transpose()
+}
+
+fn read_required_id(path: &Path) -> Result<ObjectId, read::Error> {
+    file::read_id(path)?.ok_or_else(|| read::Error::Missing { path: path.to_owned() })
+}
@@ -0,0 +1,43 @@
+use gix_rebase::author_script::{decode, encode};
+
+#[test]
+fn round_trip_with_quoting() -> crate::Result {
+    let input =
+        "GIT_AUTHOR_NAME='O'\\''Neil!'\\!''\nGIT_AUTHOR_EMAIL='o@example.com'\nGIT_AUTHOR_DATE='@1792357221 -0130'\n";
+    let author = decode(input.as_bytes())?;
+    assert_eq!(author.name, "O'Neil!!");
+    assert_eq!(author.email, "o@example.com");
+    assert_eq!(author.time.seconds, 1792357221);
+    assert_eq!(author.time.offset, -5400);
+    assert_eq!(
+        encode(author.to_ref()),
+        "GIT_AUTHOR_NAME='O'\\''Neil'\\!''\\!''\nGIT_AUTHOR_EMAIL='o@example.com'\nGIT_AUTHOR_DATE='@1792357221 -0130'\n"
+    );
+    assert_eq!(decode(&encode(author.to_ref()))?, author);
+    Ok(())
+}
+
+#[test]
+fn errors() {
+    for (input, expected) in [
+        (
+            "GIT_AUTHOR_NAME='a'\nGIT_AUTHOR_EMAIL='e'\n",
+            "The variable 'GIT_AUTHOR_DATE' is missing",
+        ),
+        (
+            "GIT_AUTHOR_EMAIL='e'\nGIT_AUTHOR_DATE='@0 +0000'\n",
+            "The variable 'GIT_AUTHOR_NAME' is missing",
+        ),
+        (
+            "GIT_AUTHOR_NAME='a\n",
+            "The line 'GIT_AUTHOR_NAME='a' is not a variable assignment with a quoted value",
+        ),
+        (
+            "GIT_AUTHOR_NAME='a'\nGIT_AUTHOR_EMAIL='e'\nGIT_AUTHOR_DATE='yesterday-ish'\n",
+            "The author date 'yesterday-ish' could not be parsed",
+        ),
+    ] {
+        let err = decode(input.as_bytes()).expect_err("invalid");
+        assert_eq!(err.to_string(), expected, "{input:?}");
+    }
+}
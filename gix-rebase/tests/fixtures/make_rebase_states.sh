@@ -0,0 +1,38 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Create a repository named `$1` in which the branch `topic` conflicts with `main`.
+function conflicting_repo() {
+  git init -q "$1"
+  (cd "$1"
+    echo base > base && git add base && git commit -q -m "base"
+    git checkout -q -b topic
+    echo topic > file && git add file && git commit -q -m "add file on topic" -m "with body"
+    echo other > other && git add other && git commit -q -m "fixup! add file on topic"
+    git branch at-tip
+    git checkout -q main
+    echo main > file && git add file && git commit -q -m "add file on main"
+    git checkout -q topic
+  )
+}
+
+conflicting_repo merge
+(cd merge
+  GIT_SEQUENCE_EDITOR=: git rebase -i --autosquash --update-refs main >/dev/null 2>&1 || :
+)
+
+conflicting_repo edit
+(cd edit
+  GIT_SEQUENCE_EDITOR="sed -i -e '1s/^pick/edit/'" git rebase -i --no-autosquash main~1 >/dev/null 2>&1
+)
+
+conflicting_repo detached
+(cd detached
+  git checkout -q --detach topic
+  git rebase --merge main >/dev/null 2>&1 || :
+)
+
+conflicting_repo apply
+(cd apply
+  git rebase --apply main >/dev/null 2>&1 || :
+)
@@ -0,0 +1,4 @@
+pub use gix_testtools::Result;
+
+mod author_script;
+mod state;
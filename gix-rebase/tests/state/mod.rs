@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+
+use gix_rebase::{
+    state::{Progress, UpdateRef},
+    Backend, State,
+};
+use gix_sequencer::todo::Instruction;
+use gix_testtools::bstr::ByteSlice;
+
+fn git_dir(name: &str) -> crate::Result<PathBuf> {
+    Ok(gix_testtools::scripted_fixture_read_only("make_rebase_states.sh")?
+        .join(name)
+        .join(".git"))
+}
+
+fn rev_parse(git_dir: &Path, rev: &str) -> crate::Result<gix_hash::ObjectId> {
+    let out = std::process::Command::new("git")
+        .args(["rev-parse", rev])
+        .current_dir(git_dir)
+        .output()?;
+    assert!(out.status.success());
+    Ok(gix_hash::ObjectId::from_hex(out.stdout.trim())?)
+}
+
+/// Write `state` into a new directory and assert that the files named `files` are the same as the ones `git` wrote
+/// into `git_dir`, and that the written state can be read back.
+fn assert_written_like_git(state: &State, git_dir: &Path, files: &[&str]) -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    state.write_to(tmp.path())?;
+    let dir_name = state.backend.dir_name();
+    for name in files {
+        assert_eq!(
+            std::fs::read(tmp.path().join(dir_name).join(name))?.as_bstr(),
+            std::fs::read(git_dir.join(dir_name).join(name))?.as_bstr(),
+            "{name} is written like git does"
+        );
+    }
+    assert_eq!(State::read_from(tmp.path())?.as_ref(), Some(state));
+    State::remove(tmp.path())?;
+    assert_eq!(State::read_from(tmp.path())?, None);
+    Ok(())
+}
+
+#[test]
+fn merge_backend_stopped_at_conflict() -> crate::Result {
+    let git_dir = git_dir("merge")?;
+    let state = State::read_from(&git_dir)?.expect("rebase in progress");
+    assert_eq!(state.backend, Backend::Merge);
+    assert_eq!(state.head_name, Some("refs/heads/topic".try_into()?));
+    assert_eq!(state.onto, rev_parse(&git_dir, "main")?);
+    assert_eq!(state.orig_head, rev_parse(&git_dir, "topic")?);
+    assert!(state.interactive);
+    let topic_first = rev_parse(&git_dir, "topic~1")?;
+    assert_eq!(
+        state.done.instructions,
+        [Instruction::Pick {
+            commit: topic_first.into(),
+            summary: "add file on topic".into()
+        }]
+    );
+    assert_eq!(
+        state
+            .todo
+            .instructions
+            .iter()
+            .map(Instruction::name)
+            .collect::<Vec<_>>(),
+        ["fixup", "update-ref"],
+        "the todo list was autosquashed"
+    );
+    assert_eq!(state.progress, Some(Progress { current: 1, total: 3 }));
+    assert_eq!(
+        state.todo_progress(),
+        Progress { current: 1, total: 3 },
+        "all instructions are counted, just like git does"
+    );
+    assert_eq!(state.stopped_at, Some(topic_first));
+    assert_eq!(state.amend, None);
+    assert_eq!(state.message, Some("add file on topic\n\nwith body\n\n".into()));
+    assert_eq!(state.author.as_ref().expect("present").name, "author");
+    assert_eq!(
+        state.update_refs,
+        [UpdateRef {
+            name: "refs/heads/at-tip".try_into()?,
+            before: rev_parse(&git_dir, "at-tip")?,
+            after: None
+        }]
+    );
+
+    assert_written_like_git(
+        &state,
+        &git_dir,
+        &[
+            "head-name",
+            "onto",
+            "orig-head",
+            "interactive",
+            "git-rebase-todo",
+            "done",
+            "msgnum",
+            "end",
+            "stopped-sha",
+            "message",
+            "author-script",
+            "update-refs",
+        ],
+    )
+}
+
+#[test]
+fn merge_backend_stopped_for_editing() -> crate::Result {
+    let git_dir = git_dir("edit")?;
+    let state = State::read_from(&git_dir)?.expect("rebase in progress");
+    let edited = rev_parse(&git_dir, "topic~1")?;
+    assert_eq!(state.amend, Some(edited));
+    assert_eq!(state.stopped_at, Some(edited));
+    assert!(state.update_refs.is_empty());
+    assert!(matches!(state.done.instructions[0], Instruction::Edit { .. }));
+    assert_written_like_git(&state, &git_dir, &["done", "git-rebase-todo", "amend", "stopped-sha"])
+}
+
+#[test]
+fn merge_backend_with_detached_head() -> crate::Result {
+    let git_dir = git_dir("detached")?;
+    let state = State::read_from(&git_dir)?.expect("rebase in progress");
+    assert_eq!(state.head_name, None);
+    assert_written_like_git(&state, &git_dir, &["head-name", "onto", "orig-head"])
+}
+
+#[test]
+fn apply_backend() -> crate::Result {
+    let git_dir = git_dir("apply")?;
+    let state = State::read_from(&git_dir)?.expect("rebase in progress");
+    assert_eq!(state.backend, Backend::Apply);
+    assert_eq!(state.head_name, Some("refs/heads/topic".try_into()?));
+    assert_eq!(state.onto, rev_parse(&git_dir, "main")?);
+    assert_eq!(state.progress, Some(Progress { current: 1, total: 2 }));
+    assert_eq!(state.stopped_at, Some(rev_parse(&git_dir, "topic~1")?));
+    assert!(state.todo.instructions.is_empty() && state.done.instructions.is_empty());
+    assert_written_like_git(
+        &state,
+        &git_dir,
+        &[
+            "head-name",
+            "onto",
+            "orig-head",
+            "next",
+            "last",
+            "original-commit",
+            "rebasing",
+        ],
+    )
+}
+
+#[test]
+fn no_rebase_in_progress() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    assert_eq!(State::read_from(tmp.path())?, None);
+    std::fs::create_dir(tmp.path().join("rebase-apply"))?;
+    std::fs::write(tmp.path().join("rebase-apply").join("applying"), "")?;
+    assert_eq!(State::read_from(tmp.path())?, None, "git am isn't a rebase");
+    Ok(())
+}
@@ -0,0 +1,412 @@
+use gix_hash::ObjectId;
+
+bitflags::bitflags! {
+    /// The flags used in the graph when [bisecting](crate::bisect()).
+    #[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+    pub struct Flags: u8 {
+        /// The commit is reachable from one of the good commits, and thus can't be the first bad commit.
+        const GOOD = 1 << 0;
+        /// The commit was already put into the traversal queue.
+        const SEEN = 1 << 1;
+    }
+}
+
+/// The outcome of a single [bisection step](crate::bisect()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// `commit` should be tested next as it splits the remaining commits most evenly.
+    Next {
+        /// The commit to test next.
+        commit: ObjectId,
+        /// The amount of commits that are left to test after `commit` was tested, assuming it's good.
+        remaining: usize,
+        /// A rough estimate of how many steps it will take to find the first bad commit.
+        steps: usize,
+    },
+    /// All commits were tested, and `commit` is the first bad commit.
+    FirstBad {
+        /// The first bad commit.
+        commit: ObjectId,
+    },
+    /// Only skipped commits are left to test, so the first bad commit could be any of `candidates`.
+    OnlySkipped {
+        /// The commits that could be the first bad commit, most recent first, which includes the bad commit.
+        candidates: Vec<ObjectId>,
+    },
+}
+
+/// The error returned by the [`bisect()`][function::bisect()] function.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("A commit could not be inserted into the graph")]
+    InsertCommit(#[from] crate::graph::try_lookup_or_insert_default::Error),
+    #[error("The commit {id} could not be found")]
+    NotFound { id: ObjectId },
+    #[error("The bad commit {bad} is reachable from a good commit, so it is both bad and good")]
+    BadIsGood { bad: ObjectId },
+}
+
+/// Estimate the amount of steps it takes to bisect `all` commits, just like `git` does it.
+///
+/// It's `log2(all) - 1` for the most part, but accounts for the extra step that is needed if `all`
+/// is closer to the next power of two.
+pub fn estimate_steps(all: usize) -> usize {
+    if all < 3 {
+        return 0;
+    }
+    let n = (usize::BITS - 1 - all.leading_zeros()) as usize;
+    let e = 1 << n;
+    let x = all - e;
+    if e < 3 * x {
+        n
+    } else {
+        n - 1
+    }
+}
+
+pub(crate) mod function {
+    use std::cmp::Reverse;
+
+    use gix_hash::ObjectId;
+    use gix_hashtable::HashMap;
+
+    use super::{estimate_steps, Error, Flags, Outcome};
+    use crate::{graph::Commit, Graph, PriorityQueue};
+
+    /// The amount of additional uninteresting commits to traverse before stopping, to deal with clock skew like `git` does.
+    const SLOP: usize = 5;
+
+    /// Find the commit to test next to find the first commit in the history of the `bad` commit that isn't in the history of
+    /// any of the `good` commits, while avoiding all `skipped` commits.
+    ///
+    /// The commit is chosen so that the remaining commits are split as evenly as possible, like `git bisect` does it,
+    /// and the same commit will be chosen as long as the same commit `graph` is seen.
+    /// If `skipped` commits are present, a commit further away from the ideal one will be chosen pseudo-randomly
+    /// in the same way as `git` does it.
+    ///
+    /// Note that the `graph` is cleared before it is used, and that missing parents, as in shallow clones, are skipped.
+    pub fn bisect(
+        bad: ObjectId,
+        good: &[ObjectId],
+        skipped: &[ObjectId],
+        graph: &mut Graph<'_, Commit<Flags>>,
+    ) -> Result<Outcome, Error> {
+        let _span = gix_trace::coarse!("gix_revision::bisect()", ?bad, ?good, ?skipped);
+        graph.clear();
+        let candidates = candidates_oldest_first(bad, good, graph)?;
+        if candidates.is_empty() {
+            return Err(Error::BadIsGood { bad });
+        }
+
+        let all = candidates.len();
+        let find_all = !skipped.is_empty();
+        let weights = Weights::compute(&candidates, graph, find_all);
+        let (list, reaches) = match weights.halfway {
+            Some(idx) => (vec![candidates[idx]], weights.get(idx)),
+            None if find_all => {
+                let mut by_distance: Vec<_> = (0..all)
+                    .map(|idx| (weights.distance(idx), candidates[idx], idx))
+                    .collect();
+                by_distance.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+                let reaches = weights.get(by_distance[0].2);
+                (by_distance.into_iter().map(|(_, id, _)| id).collect(), reaches)
+            }
+            None => {
+                let mut best = None;
+                for idx in 0..all {
+                    let distance = weights.distance(idx);
+                    if best.map_or(true, |(_, best_distance)| distance > best_distance) {
+                        best = Some((idx, distance));
+                    }
+                }
+                let idx = best.expect("at least one candidate").0;
+                (vec![candidates[idx]], weights.get(idx))
+            }
+        };
+
+        let (chosen, tried_any) = match without_skipped(list, skipped) {
+            Skipped::NotFirst(first) => (Some(first), false),
+            Skipped::First { filtered } => (skip_away(&filtered, bad), true),
+        };
+        let only_skipped = || Outcome::OnlySkipped {
+            candidates: candidates.iter().rev().copied().collect(),
+        };
+        Ok(match chosen {
+            None => only_skipped(),
+            Some(commit) if commit == bad => {
+                if tried_any {
+                    only_skipped()
+                } else {
+                    Outcome::FirstBad { commit }
+                }
+            }
+            Some(commit) => Outcome::Next {
+                commit,
+                remaining: all.saturating_sub(reaches + 1),
+                steps: estimate_steps(all),
+            },
+        })
+    }
+
+    /// Return all commits reachable from `bad` but not from any of the `good` ones, with the oldest commits first.
+    ///
+    /// The order matters as it's used to break ties, which is why the traversal is done exactly like `git` does it.
+    fn candidates_oldest_first(
+        bad: ObjectId,
+        good: &[ObjectId],
+        graph: &mut Graph<'_, Commit<Flags>>,
+    ) -> Result<Vec<ObjectId>, Error> {
+        let mut queue = Queue::new();
+        for (id, flags) in
+            std::iter::once((bad, Flags::SEEN)).chain(good.iter().map(|id| (*id, Flags::SEEN | Flags::GOOD)))
+        {
+            let commit = graph
+                .try_lookup_or_insert_commit(id, |existing| *existing |= flags)?
+                .ok_or(Error::NotFound { id })?;
+            let time = commit.commit_time;
+            if flags.contains(Flags::GOOD) {
+                mark_parents_good(id, graph)?;
+            }
+            queue.insert(time, id);
+        }
+
+        let mut out = Vec::new();
+        let mut last_interesting_time = gix_date::SecondsSinceUnixEpoch::MAX;
+        let mut slop = SLOP;
+        while let Some(id) = queue.pop() {
+            let commit = &graph[&id];
+            let is_good = commit.data.contains(Flags::GOOD);
+            let (time, parents) = (commit.commit_time, commit.parents.clone());
+            for parent_id in parents {
+                let Some(parent) = graph.try_lookup_or_insert_commit(parent_id, |_| {})? else {
+                    continue;
+                };
+                if is_good {
+                    parent.data |= Flags::GOOD;
+                    mark_parents_good(parent_id, graph)?;
+                }
+                let parent = graph.get_mut(&parent_id).expect("just inserted");
+                if !parent.data.contains(Flags::SEEN) {
+                    parent.data |= Flags::SEEN;
+                    queue.insert(parent.commit_time, parent_id);
+                }
+            }
+            if is_good {
+                mark_parents_good(id, graph)?;
+                slop = if queue.is_done(last_interesting_time, graph) {
+                    slop - 1
+                } else {
+                    SLOP
+                };
+                if slop == 0 {
+                    break;
+                }
+                continue;
+            }
+            last_interesting_time = time;
+            out.push(id);
+        }
+
+        out.retain(|id| !graph[id].data.contains(Flags::GOOD));
+        out.reverse();
+        Ok(out)
+    }
+
+    /// Mark all parents of `id` as good, and if they were seen before, their parents as well.
+    fn mark_parents_good(id: ObjectId, graph: &mut Graph<'_, Commit<Flags>>) -> Result<(), Error> {
+        let mut stack: Vec<_> = graph[&id].parents.to_vec();
+        while let Some(id) = stack.pop() {
+            if !graph.contains(&id) {
+                // We don't know the parents yet, they will be marked once this commit is traversed.
+                graph.try_lookup_or_insert_commit(id, |flags| *flags |= Flags::GOOD)?;
+                continue;
+            }
+            let commit = graph.get_mut(&id).expect("present");
+            if commit.data.contains(Flags::GOOD) {
+                continue;
+            }
+            commit.data |= Flags::GOOD;
+            stack.extend(commit.parents.iter().copied());
+        }
+        Ok(())
+    }
+
+    /// A queue of commits ordered by commit time, most recent first, and by insertion order if times are equal.
+    struct Queue {
+        inner: PriorityQueue<(gix_date::SecondsSinceUnixEpoch, Reverse<usize>), ObjectId>,
+        seq: usize,
+    }
+
+    impl Queue {
+        fn new() -> Self {
+            Queue {
+                inner: PriorityQueue::new(),
+                seq: 0,
+            }
+        }
+
+        fn insert(&mut self, time: gix_date::SecondsSinceUnixEpoch, id: ObjectId) {
+            self.inner.insert((time, Reverse(self.seq)), id);
+            self.seq += 1;
+        }
+
+        fn pop(&mut self) -> Option<ObjectId> {
+            self.inner.pop().map(|(_, id)| id)
+        }
+
+        /// Return `true` if no interesting commits are left, and none of them is older than the last interesting commit.
+        fn is_done(
+            &self,
+            last_interesting_time: gix_date::SecondsSinceUnixEpoch,
+            graph: &Graph<'_, Commit<Flags>>,
+        ) -> bool {
+            let Some(((most_recent, _), _)) = self.inner.peek() else {
+                return true;
+            };
+            last_interesting_time > *most_recent
+                && self
+                    .inner
+                    .iter_unordered()
+                    .all(|id| graph[id].data.contains(Flags::GOOD))
+        }
+    }
+
+    /// The amount of candidates each candidate can reach, including itself.
+    struct Weights {
+        weights: Vec<usize>,
+        /// The index of a candidate that splits all candidates in half, if one was found early.
+        halfway: Option<usize>,
+    }
+
+    impl Weights {
+        /// Compute the weights of all `candidates`, oldest first, or stop early if a commit is found that splits the candidates
+        /// in half, unless `find_all` is set.
+        fn compute(candidates: &[ObjectId], graph: &Graph<'_, Commit<Flags>>, find_all: bool) -> Self {
+            let nr = candidates.len();
+            let index_by_id: HashMap<_, _> = candidates.iter().enumerate().map(|(idx, id)| (*id, idx)).collect();
+            let parents: Vec<Vec<usize>> = candidates
+                .iter()
+                .map(|id| {
+                    graph[id]
+                        .parents
+                        .iter()
+                        .filter_map(|parent| index_by_id.get(parent).copied())
+                        .collect()
+                })
+                .collect();
+            let is_halfway = |weight: usize| (2 * weight as isize - nr as isize).abs() <= 1;
+
+            let mut weights: Vec<Option<usize>> = parents.iter().map(|p| p.is_empty().then_some(1)).collect();
+            let mut counted = weights.iter().filter(|w| w.is_some()).count();
+            let out = |weights: Vec<Option<usize>>, halfway| Weights {
+                weights: weights.into_iter().map(Option::unwrap_or_default).collect(),
+                halfway,
+            };
+
+            // Merges can reach the same commits through multiple parents, so count them the expensive way.
+            for idx in 0..nr {
+                if parents[idx].len() < 2 {
+                    continue;
+                }
+                let weight = reachable_count(idx, &parents);
+                weights[idx] = Some(weight);
+                if !find_all && is_halfway(weight) {
+                    return out(weights, Some(idx));
+                }
+                counted += 1;
+            }
+
+            // Everything else has a single parent and reaches one more commit than it.
+            while counted < nr {
+                for idx in 0..nr {
+                    if weights[idx].is_some() {
+                        continue;
+                    }
+                    let Some(parent_weight) = parents[idx].iter().find_map(|parent| weights[*parent]) else {
+                        continue;
+                    };
+                    let weight = parent_weight + 1;
+                    weights[idx] = Some(weight);
+                    counted += 1;
+                    if !find_all && is_halfway(weight) {
+                        return out(weights, Some(idx));
+                    }
+                }
+            }
+            out(weights, None)
+        }
+
+        fn get(&self, idx: usize) -> usize {
+            self.weights[idx]
+        }
+
+        /// The amount of commits that can be excluded in the worst case when testing the candidate at `idx`.
+        fn distance(&self, idx: usize) -> usize {
+            let weight = self.weights[idx];
+            weight.min(self.weights.len() - weight)
+        }
+    }
+
+    /// Count all candidates reachable from the candidate at `idx`, including itself.
+    fn reachable_count(idx: usize, parents: &[Vec<usize>]) -> usize {
+        let mut seen = vec![false; parents.len()];
+        let mut count = 0;
+        let mut stack = vec![idx];
+        while let Some(idx) = stack.pop() {
+            if !std::mem::replace(&mut seen[idx], true) {
+                count += 1;
+                stack.extend(parents[idx].iter().copied());
+            }
+        }
+        count
+    }
+
+    enum Skipped {
+        /// The first commit wasn't skipped, and it's the one to use.
+        NotFirst(ObjectId),
+        /// The first commit was skipped, and these are all commits that aren't skipped, in order.
+        First { filtered: Vec<ObjectId> },
+    }
+
+    fn without_skipped(list: Vec<ObjectId>, skipped: &[ObjectId]) -> Skipped {
+        match list.first() {
+            Some(first) if !skipped.contains(first) => Skipped::NotFirst(*first),
+            _ => Skipped::First {
+                filtered: list.into_iter().filter(|id| !skipped.contains(id)).collect(),
+            },
+        }
+    }
+
+    /// Pick a commit from `list` that is pseudo-randomly away from the best one, but never the `bad` one unless there is
+    /// no other choice. This is exactly what `git` does, to produce the same results.
+    fn skip_away(list: &[ObjectId], bad: ObjectId) -> Option<ObjectId> {
+        const PRN_MODULO: u32 = 32768;
+        let count = list.len();
+        let prn = ((count as u32).wrapping_mul(1103515245).wrapping_add(12345) / 65536) % PRN_MODULO;
+        let index = (count as u64 * u64::from(prn) / u64::from(PRN_MODULO)) as usize * sqrti(prn) / sqrti(PRN_MODULO);
+
+        match list.get(index) {
+            Some(id) if *id != bad => Some(*id),
+            Some(_) if index > 0 => Some(list[index - 1]),
+            _ => list.first().copied(),
+        }
+    }
+
+    /// An integer square root computed the same way as `git` does it to produce the same rounding.
+    fn sqrti(val: u32) -> usize {
+        if val == 0 {
+            return 0;
+        }
+        let val = val as f32;
+        let mut x = val;
+        loop {
+            let y = (x + val / x) / 2.0;
+            let d = (y - x).abs();
+            x = y;
+            if d < 0.5 {
+                return x as usize;
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-//! Interact with git revisions by parsing them from rev-specs and describing them in terms of reference names.
+//! Interact with git revisions by parsing them from rev-specs, finding merge-bases and describing or naming them in terms of reference names.
 //!
 //! ## Feature Flags
 #![cfg_attr(
@@ -8,12 +8,30 @@
 #![cfg_attr(all(doc, feature = "document-features"), feature(doc_cfg, doc_auto_cfg))]
 #![deny(missing_docs, rust_2018_idioms, unsafe_code)]
 
+///
+#[cfg(feature = "bisect")]
+pub mod bisect;
+#[cfg(feature = "bisect")]
+pub use bisect::function::bisect;
+
 ///
 #[cfg(feature = "describe")]
 pub mod describe;
 #[cfg(feature = "describe")]
 pub use describe::function::describe;
 
+///
+#[cfg(feature = "merge_base")]
+pub mod merge_base;
+#[cfg(feature = "merge_base")]
+pub use merge_base::function::merge_base;
+
+///
+#[cfg(feature = "name_rev")]
+pub mod name_rev;
+#[cfg(feature = "name_rev")]
+pub use name_rev::function::name_rev;
+
 ///
 pub mod spec;
 pub use gix_revwalk::{graph, Graph, PriorityQueue};
@@ -0,0 +1,181 @@
+bitflags::bitflags! {
+    /// The flags used in the graph for finding [merge bases](crate::merge_base()).
+    #[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+    pub struct Flags: u8 {
+        /// The commit belongs to the graph reachable by the first commit.
+        const COMMIT1 = 1 << 0;
+        /// The commit belongs to the graph reachable by all other commits.
+        const COMMIT2 = 1 << 1;
+
+        /// Marks the commit as done, it's reachable by both COMMIT1 and COMMIT2.
+        const STALE = 1 << 2;
+        /// The commit was already put onto the results list.
+        const RESULT = 1 << 3;
+    }
+}
+
+/// The error returned by the [`merge_base()`][function::merge_base()] function.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("A commit could not be inserted into the graph")]
+    InsertCommit(#[from] crate::graph::try_lookup_or_insert_default::Error),
+    #[error("The commit {id} could not be found")]
+    NotFound { id: gix_hash::ObjectId },
+}
+
+pub(crate) mod function {
+    use std::cmp::Ordering;
+
+    use gix_hash::ObjectId;
+
+    use super::{Error, Flags};
+    use crate::{
+        graph::{Commit, Generation},
+        Graph, PriorityQueue,
+    };
+
+    /// Given a commit at `first` id, traverse the commit `graph` and return all possible merge-base between it and `others`,
+    /// sorted from best to worst. Returns `None` if there is no merge-base as `first` and `others` don't share history.
+    /// If `others` is empty, `Some(first)` is returned.
+    ///
+    /// Note that this function doesn't do any work if `first` is contained in `others`, which is when `first` will be returned
+    /// as only merge-base right away. This is even the case if some commits of `others` are disjoint.
+    ///
+    /// Also note that the `graph` is cleared before it is used, and that missing parents, as in shallow clones, are skipped.
+    pub fn merge_base(
+        first: ObjectId,
+        others: &[ObjectId],
+        graph: &mut Graph<'_, Commit<Flags>>,
+    ) -> Result<Option<Vec<ObjectId>>, Error> {
+        let _span = gix_trace::coarse!("gix_revision::merge_base()", ?first, ?others);
+        if others.is_empty() || others.contains(&first) {
+            return Ok(Some(vec![first]));
+        }
+
+        graph.clear();
+        let bases = paint_down_to_common(first, others, graph)?;
+        let bases = remove_redundant(&bases, graph)?;
+        Ok((!bases.is_empty()).then_some(bases))
+    }
+
+    /// Remove all those commits from `commits` if they are in the history of another commit in `commits`.
+    /// That way, we return only the topologically most recent commits in `commits`.
+    fn remove_redundant(commits: &[ObjectId], graph: &mut Graph<'_, Commit<Flags>>) -> Result<Vec<ObjectId>, Error> {
+        if commits.len() < 2 {
+            return Ok(commits.to_vec());
+        }
+        let mut redundant = vec![false; commits.len()];
+        for (idx, id) in commits.iter().enumerate() {
+            if redundant[idx] {
+                continue;
+            }
+            let others: Vec<_> = commits
+                .iter()
+                .enumerate()
+                .filter(|(other_idx, _)| *other_idx != idx && !redundant[*other_idx])
+                .map(|(_, id)| *id)
+                .collect();
+            if others.is_empty() {
+                break;
+            }
+            graph.clear();
+            paint_down_to_common(*id, &others, graph)?;
+            if graph[id].data.contains(Flags::COMMIT2) {
+                redundant[idx] = true;
+            }
+            for (other_idx, other) in commits.iter().enumerate() {
+                if other_idx != idx && graph.get(other).map_or(false, |c| c.data.contains(Flags::COMMIT1)) {
+                    redundant[other_idx] = true;
+                }
+            }
+        }
+        Ok(commits
+            .iter()
+            .zip(redundant)
+            .filter_map(|(id, is_redundant)| (!is_redundant).then_some(*id))
+            .collect())
+    }
+
+    /// Paint all commits reachable from `first` with [`Flags::COMMIT1`] and all reachable from `others` with
+    /// [`Flags::COMMIT2`], and return the commits that are reachable by both, sorted from most recent to least recent.
+    fn paint_down_to_common(
+        first: ObjectId,
+        others: &[ObjectId],
+        graph: &mut Graph<'_, Commit<Flags>>,
+    ) -> Result<Vec<ObjectId>, Error> {
+        let mut queue = PriorityQueue::<GenThenTime, ObjectId>::new();
+        for (id, flags) in std::iter::once((first, Flags::COMMIT1)).chain(others.iter().map(|id| (*id, Flags::COMMIT2)))
+        {
+            let commit = graph
+                .try_lookup_or_insert_commit(id, |existing| *existing |= flags)?
+                .ok_or(Error::NotFound { id })?;
+            queue.insert(GenThenTime::from(&*commit), id);
+        }
+
+        let mut out = Vec::new();
+        while queue.iter_unordered().any(|id| {
+            graph
+                .get(id)
+                .map_or(false, |commit| !commit.data.contains(Flags::STALE))
+        }) {
+            let (info, id) = queue.pop().expect("we have non-stale");
+            let commit = graph.get_mut(&id).expect("everything queued is in graph");
+            let mut flags_without_result = commit.data & (Flags::COMMIT1 | Flags::COMMIT2 | Flags::STALE);
+            if flags_without_result == (Flags::COMMIT1 | Flags::COMMIT2) {
+                if !commit.data.contains(Flags::RESULT) {
+                    commit.data |= Flags::RESULT;
+                    out.push((id, info));
+                }
+                flags_without_result |= Flags::STALE;
+            }
+
+            for parent_id in commit.parents.clone() {
+                let Some(parent) = graph.try_lookup_or_insert_commit(parent_id, |_| {})? else {
+                    continue;
+                };
+                if parent.data.contains(flags_without_result) {
+                    continue;
+                }
+                parent.data |= flags_without_result;
+                queue.insert(GenThenTime::from(&*parent), parent_id);
+            }
+        }
+
+        out.retain(|(id, _)| !graph[id].data.contains(Flags::STALE));
+        out.sort_by_key(|(_, info)| std::cmp::Reverse(*info));
+        Ok(out.into_iter().map(|(id, _)| id).collect())
+    }
+
+    /// The generation of commits that aren't in the commit-graph, like `git` does it.
+    const GENERATION_NUMBER_INFINITY: Generation = Generation::MAX;
+
+    /// The ordering of commits in the queue, by generation first and commit time second, with commits outside of
+    /// the commit-graph being treated as if they had an infinite generation.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct GenThenTime {
+        generation: Generation,
+        time: gix_date::SecondsSinceUnixEpoch,
+    }
+
+    impl From<&Commit<Flags>> for GenThenTime {
+        fn from(commit: &Commit<Flags>) -> Self {
+            GenThenTime {
+                generation: commit.generation.unwrap_or(GENERATION_NUMBER_INFINITY),
+                time: commit.commit_time,
+            }
+        }
+    }
+
+    impl PartialOrd<Self> for GenThenTime {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for GenThenTime {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.generation.cmp(&other.generation).then(self.time.cmp(&other.time))
+        }
+    }
+}
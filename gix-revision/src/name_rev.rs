@@ -0,0 +1,243 @@
+use std::{
+    borrow::Cow,
+    fmt::{Display, Formatter},
+    rc::Rc,
+};
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_date::SecondsSinceUnixEpoch;
+use gix_hash::ObjectId;
+
+/// A name pointing to a commit, usually a reference, from which the commit and its ancestors can be named with [`name_rev()`](crate::name_rev()).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tip<'name> {
+    /// The name to use for the commit `id`, like `main` or `tags/v1.0`.
+    pub name: Cow<'name, BStr>,
+    /// The commit that `name` points to, after peeling tags.
+    pub id: ObjectId,
+    /// If `true`, `name` is a tag, and names derived from tags are preferred over all other names.
+    pub from_tag: bool,
+    /// The time at which the tag object that was peeled to reach `id` was created, or `None` to use the commit time of `id`.
+    ///
+    /// Older tips are preferred.
+    pub tagger_time: Option<SecondsSinceUnixEpoch>,
+    /// If `true`, `name` points to an annotated tag which was peeled to reach `id`, which is why `^0` is appended to it.
+    pub peeled: bool,
+}
+
+/// The name of a commit relative to a [`Tip`], as returned by [`name_rev()`](crate::name_rev()).
+///
+/// It displays itself like `git name-rev` does, for example as `main~3^2~1`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Name {
+    /// The name of the tip, followed by the path to the last merge commit whose parent other than the first was followed,
+    /// like `main~3^2` or `v1.0^0`.
+    pub tip: BString,
+    /// The amount of first parents to follow from the commit named by `tip` to reach the named commit.
+    pub generation: u32,
+}
+
+impl Display for Name {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.generation == 0 {
+            self.tip.fmt(f)
+        } else {
+            let tip = self.tip.strip_suffix(b"^0").unwrap_or(self.tip.as_slice());
+            write!(f, "{}~{}", tip.as_bstr(), self.generation)
+        }
+    }
+}
+
+/// The data attached to each commit in the graph while [naming commits](crate::name_rev()).
+#[derive(Debug, Clone, Default)]
+pub struct State(Option<Naming>);
+
+#[derive(Debug, Clone)]
+struct Naming {
+    tip: Rc<BString>,
+    tagger_time: SecondsSinceUnixEpoch,
+    generation: u32,
+    distance: u64,
+    from_tag: bool,
+}
+
+impl Naming {
+    /// Return `true` if the given properties of a name make it better than this one, using the same rules as `git`.
+    fn is_worse_than(&self, tagger_time: SecondsSinceUnixEpoch, distance: u64, from_tag: bool) -> bool {
+        if from_tag && self.from_tag {
+            return self.tagger_time > tagger_time || (self.tagger_time == tagger_time && self.distance > distance);
+        }
+        if self.from_tag != from_tag {
+            return from_tag;
+        }
+        if self.distance != distance {
+            return self.distance > distance;
+        }
+        self.tagger_time > tagger_time
+    }
+
+    fn to_name(&self) -> Name {
+        Name {
+            tip: self.tip.as_ref().clone(),
+            generation: self.generation,
+        }
+    }
+
+    /// Return the tip name for the parent with `parent_number` (starting at 1) of the commit with this name.
+    fn parent_tip(&self, parent_number: usize) -> BString {
+        let tip = self.tip.strip_suffix(b"^0").unwrap_or(self.tip.as_slice());
+        let mut out = BString::from(tip);
+        if self.generation > 0 {
+            out.extend_from_slice(format!("~{}", self.generation).as_bytes());
+        }
+        out.extend_from_slice(format!("^{parent_number}").as_bytes());
+        out
+    }
+}
+
+/// The error returned by the [`name_rev()`](crate::name_rev()) function.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("A commit could not be inserted into the graph")]
+    InsertCommit(#[from] crate::graph::try_lookup_or_insert_default::Error),
+    #[error("The commit {id} could not be found")]
+    NotFound { id: ObjectId },
+}
+
+pub(crate) mod function {
+    use std::rc::Rc;
+
+    use bstr::BString;
+    use gix_date::SecondsSinceUnixEpoch;
+    use gix_hash::ObjectId;
+
+    use super::{Error, Name, Naming, State, Tip};
+    use crate::{graph::Commit, Graph};
+
+    /// Commits older than the oldest commit to name by more than this amount of seconds are not traversed.
+    const CUTOFF_DATE_SLOP: SecondsSinceUnixEpoch = 86400;
+    /// The distance added when following a parent of a merge commit other than the first, to prefer names without merges.
+    const MERGE_TRAVERSAL_WEIGHT: u64 = 65535;
+
+    /// Name each of the given `commits` relative to the closest of all `tips`, using the commit `graph`, like `git name-rev` does.
+    ///
+    /// The returned names are in the same order as `commits`, and are `None` if a commit can't be reached from any of the `tips`.
+    /// Names derived from tags are preferred, followed by the ones that need to traverse the fewest merges and the fewest commits.
+    /// Among tags, the oldest one is used, even if it's farther away.
+    ///
+    /// Commits that are a day older than the oldest of `commits` are not traversed, and the `graph` is cleared beforehand.
+    pub fn name_rev<'name>(
+        commits: &[ObjectId],
+        tips: impl IntoIterator<Item = Tip<'name>>,
+        graph: &mut Graph<'_, Commit<State>>,
+    ) -> Result<Vec<Option<Name>>, Error> {
+        let _span = gix_trace::coarse!("gix_revision::name_rev()", commits = commits.len());
+        graph.clear();
+        let mut cutoff = SecondsSinceUnixEpoch::MAX;
+        for id in commits {
+            let commit = graph
+                .try_lookup_or_insert_commit(*id, |_| {})?
+                .ok_or(Error::NotFound { id: *id })?;
+            cutoff = cutoff.min(commit.commit_time);
+        }
+        let cutoff = cutoff.saturating_sub(CUTOFF_DATE_SLOP);
+
+        let mut tips_with_time = Vec::new();
+        for tip in tips {
+            let Some(commit) = graph.try_lookup_or_insert_commit(tip.id, |_| {})? else {
+                continue;
+            };
+            let time = tip.tagger_time.unwrap_or(commit.commit_time);
+            tips_with_time.push((tip, time));
+        }
+        // Name commits from the best tips first, so worse names spread less.
+        tips_with_time.sort_by(|(a, a_time), (b, b_time)| b.from_tag.cmp(&a.from_tag).then(a_time.cmp(b_time)));
+        for (tip, time) in tips_with_time {
+            let mut name = tip.name.into_owned();
+            if tip.peeled {
+                name.extend_from_slice(b"^0");
+            }
+            name_from_tip(tip.id, name, time, tip.from_tag, cutoff, graph)?;
+        }
+
+        Ok(commits
+            .iter()
+            .map(|id| graph[id].data.0.as_ref().map(Naming::to_name))
+            .collect())
+    }
+
+    fn name_from_tip(
+        tip: ObjectId,
+        tip_name: BString,
+        tagger_time: SecondsSinceUnixEpoch,
+        from_tag: bool,
+        cutoff: SecondsSinceUnixEpoch,
+        graph: &mut Graph<'_, Commit<State>>,
+    ) -> Result<(), Error> {
+        let commit = graph.get_mut(&tip).expect("tips were inserted");
+        if commit.commit_time < cutoff
+            || !update_name(&mut commit.data, tagger_time, 0, 0, from_tag, || Rc::new(tip_name))
+        {
+            return Ok(());
+        }
+
+        // A stack makes this a depth-first traversal which follows first parents first.
+        let mut stack = vec![tip];
+        let mut parents_to_queue = Vec::new();
+        while let Some(id) = stack.pop() {
+            let commit = &graph[&id];
+            let name = commit.data.0.clone().expect("commits on the stack are named");
+            let parents = commit.parents.clone();
+            for (parent_number, parent_id) in (1..).zip(parents) {
+                let Some(parent) = graph.try_lookup_or_insert_commit(parent_id, |_| {})? else {
+                    continue;
+                };
+                if parent.commit_time < cutoff {
+                    continue;
+                }
+                let (generation, distance) = if parent_number > 1 {
+                    (0, name.distance + MERGE_TRAVERSAL_WEIGHT)
+                } else {
+                    (name.generation + 1, name.distance + 1)
+                };
+                let updated = update_name(&mut parent.data, tagger_time, generation, distance, from_tag, || {
+                    if parent_number > 1 {
+                        Rc::new(name.parent_tip(parent_number))
+                    } else {
+                        name.tip.clone()
+                    }
+                });
+                if updated {
+                    parents_to_queue.push(parent_id);
+                }
+            }
+            stack.extend(parents_to_queue.drain(..).rev());
+        }
+        Ok(())
+    }
+
+    /// Set the name of the commit with `state` if it doesn't have one yet, or if the new one is better, and return `true` in that case.
+    fn update_name(
+        state: &mut State,
+        tagger_time: SecondsSinceUnixEpoch,
+        generation: u32,
+        distance: u64,
+        from_tag: bool,
+        tip: impl FnOnce() -> Rc<BString>,
+    ) -> bool {
+        if let Some(existing) = &state.0 {
+            if !existing.is_worse_than(tagger_time, distance, from_tag) {
+                return false;
+            }
+        }
+        state.0 = Some(Naming {
+            tip: tip(),
+            tagger_time,
+            generation,
+            distance,
+            from_tag,
+        });
+        true
+    }
+}
@@ -0,0 +1,112 @@
+use gix_hash::ObjectId;
+use gix_revision::bisect;
+
+#[test]
+fn validate() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_bisect_repos.sh").map_err(|err| err.to_string())?;
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let baseline = std::fs::read_to_string(root.join("baseline.git"))?;
+    let cases = parse_baseline(&baseline);
+    assert_eq!(cases.len(), 14, "all cases were parsed");
+
+    let mut graph = gix_revision::Graph::new(&odb, None);
+    for case in &cases {
+        let mut actual = gix_revision::bisect(case.bad, &case.good, &case.skip, &mut graph)?;
+        if let bisect::Outcome::OnlySkipped { candidates } = &mut actual {
+            candidates.sort();
+        }
+        assert_eq!(actual, case.expected, "{}", case.message);
+    }
+    Ok(())
+}
+
+#[test]
+fn bad_reachable_from_good_is_an_error() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_bisect_repos.sh").map_err(|err| err.to_string())?;
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let baseline = std::fs::read_to_string(root.join("baseline.git"))?;
+    let case = &parse_baseline(&baseline)[0];
+
+    let mut graph = gix_revision::Graph::new(&odb, None);
+    let err = gix_revision::bisect(case.good[0], &[case.bad], &[], &mut graph).unwrap_err();
+    assert!(matches!(err, bisect::Error::BadIsGood { bad } if bad == case.good[0]));
+    Ok(())
+}
+
+#[test]
+fn estimate_steps() {
+    for (all, expected) in [
+        (0, 0),
+        (2, 0),
+        (3, 1),
+        (4, 1),
+        (6, 2),
+        (7, 2),
+        (8, 2),
+        (12, 3),
+        (13, 3),
+        (1024, 9),
+    ] {
+        assert_eq!(bisect::estimate_steps(all), expected, "{all}");
+    }
+}
+
+struct Case<'a> {
+    message: &'a str,
+    bad: ObjectId,
+    good: Vec<ObjectId>,
+    skip: Vec<ObjectId>,
+    expected: bisect::Outcome,
+}
+
+fn parse_baseline(input: &str) -> Vec<Case<'_>> {
+    let id = |hex: &str| ObjectId::from_hex(hex.as_bytes()).expect("valid hex");
+    input
+        .split("\n\n")
+        .filter(|record| !record.trim().is_empty())
+        .map(|record| {
+            let mut lines = record.lines().peekable();
+            let message = lines.next().expect("name");
+            let bad = id(lines.next().and_then(|l| l.strip_prefix("bad ")).expect("bad commit"));
+            let mut good = Vec::new();
+            let mut skip = Vec::new();
+            while let Some(line) = lines.peek() {
+                if let Some(hex) = line.strip_prefix("good ") {
+                    good.push(id(hex));
+                } else if let Some(hex) = line.strip_prefix("skip ") {
+                    skip.push(id(hex));
+                } else {
+                    break;
+                }
+                lines.next();
+            }
+            let status = lines.next().expect("status line");
+            let expected = if let Some(counts) = status.strip_prefix("Bisecting: ") {
+                let mut numbers = counts
+                    .split(|c: char| !c.is_ascii_digit())
+                    .filter(|n| !n.is_empty())
+                    .map(|n| n.parse::<usize>().expect("number"));
+                bisect::Outcome::Next {
+                    remaining: numbers.next().expect("remaining"),
+                    steps: numbers.next().expect("steps"),
+                    commit: id(lines.next().expect("next commit")),
+                }
+            } else if status.starts_with("There are only") {
+                let mut candidates: Vec<_> = lines.map(id).collect();
+                candidates.sort();
+                bisect::Outcome::OnlySkipped { candidates }
+            } else {
+                bisect::Outcome::FirstBad {
+                    commit: id(status.split(' ').next().expect("hex")),
+                }
+            };
+            Case {
+                message,
+                bad,
+                good,
+                skip,
+                expected,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,87 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Start bisecting with `$1` as bad commit and all following commits as good ones, up to an optional `--`.
+# Commits after `--` are skipped, one by one.
+# Write the name of the case, the ids of all input commits and the first line of the output of the last `git bisect` invocation,
+# along with the commit to test next or the possible first bad commits, into `baseline.git`, with each record separated by an empty line.
+function baseline() {
+  local name=$1 bad=$2
+  shift 2
+  local good=() skip=()
+  while [ $# -gt 0 ] && [ "$1" != "--" ]; do
+    good+=("$1")
+    shift
+  done
+  [ $# -gt 0 ] && shift
+  skip=("$@")
+
+  local out
+  out=$(git bisect start --no-checkout "$bad" "${good[@]}")
+  for rev in "${skip[@]}"; do
+    out=$(git bisect skip "$rev" || :)
+  done
+  {
+    echo "$name"
+    echo "bad $(git rev-parse "$bad")"
+    for rev in "${good[@]}"; do
+      echo "good $(git rev-parse "$rev")"
+    done
+    for rev in "${skip[@]}"; do
+      echo "skip $(git rev-parse "$rev")"
+    done
+    echo "$out" | head -n 1
+    if [ "${out#There are only}" != "$out" ]; then
+      echo "$out" | sed -n '3,$p' | grep -v "^We cannot"
+    elif [ "${out#Bisecting}" != "$out" ]; then
+      git rev-parse BISECT_HEAD
+    fi
+    echo
+  } >> baseline.git
+  git bisect reset >/dev/null 2>&1
+}
+
+function commit() {
+  git commit -q --allow-empty -m "$1"
+  git tag "$1"
+}
+
+git init -q
+git config merge.ff false
+
+# c1--c2--c3--c4--c5--c6--c7--c8----m1--c9---c10--m2--c11
+#      \       \                   /              /
+#       \       s1--s2--s3--s4----               /
+#        o1--o2---------------------------------
+git checkout -q -b main
+for n in 1 2 3 4 5 6 7 8; do
+  commit c$n
+done
+git checkout -q -b side c4
+for n in 1 2 3 4; do
+  commit s$n
+done
+git checkout -q -b other c2
+commit o1
+commit o2
+git checkout -q main
+git merge -q -m m1 side && git tag m1
+commit c9
+commit c10
+git merge -q -m m2 other && git tag m2
+commit c11
+
+baseline "linear" c8 c1
+baseline "linear with skip" c8 c1 -- c4
+baseline "linear with multiple skips" c8 c1 -- c4 c5
+baseline "linear with only skipped left" c5 c3 -- c4
+baseline "linear with all skipped" c8 c5 -- c6 c7
+baseline "first bad" c2 c1
+baseline "merge" m1 c1
+baseline "merge with a side branch good" m1 c3 s2
+baseline "merge with skip" m1 c4 -- c5
+baseline "all" c11 c1
+baseline "all with skip" c11 c1 -- m1
+baseline "all with many skips" c11 c1 -- m1 c9 c10 s3
+baseline "multiple good" c11 c6 s4 o1
+baseline "multiple good with skip" c11 c6 s4 o1 -- m2
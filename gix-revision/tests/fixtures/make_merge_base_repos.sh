@@ -0,0 +1,69 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Write the names of the input commits, their ids, and the output of `git merge-base --all` into `baseline.git`,
+# with each record separated by an empty line.
+function baseline() {
+  {
+    echo "$@"
+    echo $(git rev-parse "$@")
+    git merge-base --all "$@" || :
+    echo
+  } >> baseline.git
+}
+
+function commit() {
+  git commit -q --allow-empty -m "$1"
+  git tag "$1"
+}
+
+git init -q
+git config merge.ff false
+
+# A---B---C
+#      \
+#       D---E
+git checkout -q -b main
+commit A
+commit B
+commit C
+git checkout -q -b branch B
+commit D
+commit E
+
+# criss-cross merges with two merge-bases, L1 and R1
+#   C---L1---LM1
+#    \  \   /
+#     \   X
+#      \ /  \
+#       R1---RM1
+git checkout -q -b left C
+commit L1
+git checkout -q -b right C
+commit R1
+git checkout -q left
+git merge -q --no-edit -m LM1 R1
+git tag LM1
+git checkout -q right
+git merge -q --no-edit -m RM1 L1
+git tag RM1
+
+git checkout -q --orphan disjoint
+commit Z
+
+baseline C E
+baseline E C
+baseline C B
+baseline B C
+baseline A E
+baseline C C
+baseline LM1 RM1
+baseline RM1 LM1
+baseline LM1 RM1 E
+baseline E LM1 RM1
+baseline C E D
+baseline Z A
+baseline LM1 L1 R1
+
+git commit-graph write --no-progress --reachable
+git repack -adq
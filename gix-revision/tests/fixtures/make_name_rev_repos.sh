@@ -0,0 +1,117 @@
+#!/bin/bash
+set -eu -o pipefail
+
+time=1000000000
+function tick() {
+  time=$((time + 60))
+  export GIT_AUTHOR_DATE="$time +0000" GIT_COMMITTER_DATE="$time +0000"
+}
+
+function commit() {
+  tick
+  git commit -q --allow-empty -m "$1"
+}
+
+function merge() {
+  tick
+  git merge -q --no-ff -m "$1" "$2"
+}
+
+function annotated_tag() {
+  tick
+  git tag -a -m "$1" "$@"
+}
+
+# Write all tips `git name-rev` would use in `mode`, which is `all` or `tags`, into `tips-$mode`,
+# one per line as `<name> <commit> <from-tag> <tagger-time or -> <peeled>`.
+function tips() {
+  local mode=$1 ref name commit from_tag tagger_time peeled
+  for ref in $(git for-each-ref --format='%(refname)'); do
+    from_tag=0
+    if [[ $ref == refs/tags/* ]]; then
+      from_tag=1
+    elif [ "$mode" = tags ]; then
+      continue
+    fi
+    if [ "$mode" = tags ]; then
+      name=${ref#refs/tags/}
+    elif [[ $ref == refs/heads/* ]]; then
+      name=${ref#refs/heads/}
+    else
+      name=${ref#refs/}
+    fi
+    commit=$(git rev-parse "$ref^{commit}")
+    if [ "$(git cat-file -t "$ref")" = tag ]; then
+      peeled=1
+      tagger_time=$(git for-each-ref --format='%(taggerdate:unix)' "$ref")
+    else
+      peeled=0
+      tagger_time=-
+    fi
+    echo "$name $commit $from_tag $tagger_time $peeled"
+  done > "tips-$mode"
+}
+
+# Write the name `git name-rev` gives to each commit, one by one, in `mode` into `baseline-$mode`, as `<commit> <name>`.
+function baseline() {
+  local mode=$1 args=() commit
+  if [ "$mode" = tags ]; then
+    args=(--tags)
+  fi
+  for commit in $(git rev-list --all --topo-order); do
+    echo "$commit $(git name-rev --name-only "${args[@]}" "$commit")"
+  done > "baseline-$mode"
+}
+
+git init -q
+git config merge.ff false
+
+#       v0.1            v1.0     light
+# c1--c2--c3--c4--m1--c5--c6--m2--c7       (main)
+#  \    \         / \          /
+#   \    f1--f2--f3   t1--t2   /           (feature, topic)
+#    \       v0.9             /
+#     o1--o2-----------------              (other)
+#
+# t1--x1
+#   \    \
+#    t2--n1--n2--n3                        (next)
+git checkout -q -b main
+commit c1
+git checkout -q -b other
+commit o1
+commit o2
+git checkout -q main
+commit c2
+git tag v0.1
+git checkout -q -b feature
+commit f1
+commit f2
+annotated_tag v0.9
+commit f3
+git checkout -q main
+commit c3
+commit c4
+merge m1 feature
+commit c5
+annotated_tag v1.0
+git checkout -q -b topic :/^m1
+commit t1
+commit t2
+git checkout -q main
+commit c6
+merge m2 other
+commit c7
+git tag light :/^m2
+git checkout -q -b fix :/^t1
+commit x1
+git checkout -q -b next topic
+commit n1
+merge n2 fix
+commit n3
+git branch -q -D fix
+
+for mode in all tags; do
+  tips $mode
+  baseline $mode
+done
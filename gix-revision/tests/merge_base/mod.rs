@@ -0,0 +1,59 @@
+use gix_hash::ObjectId;
+use gix_revision::merge_base;
+
+#[test]
+fn validate() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_merge_base_repos.sh").map_err(|err| err.to_string())?;
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let baseline = std::fs::read_to_string(root.join("baseline.git"))?;
+    let cases = parse_baseline(&baseline);
+    assert_eq!(cases.len(), 13, "all cases were parsed");
+
+    for use_commitgraph in [false, true] {
+        let cache = use_commitgraph
+            .then(|| gix_commitgraph::Graph::from_info_dir(&odb.store_ref().path().join("info")).ok())
+            .flatten();
+        let mut graph = gix_revision::Graph::new(&odb, cache);
+        for case in &cases {
+            let actual = merge_base(case.first, &case.others, &mut graph)?;
+            assert_eq!(
+                actual.unwrap_or_default(),
+                case.bases,
+                "{}: commit-graph = {use_commitgraph}",
+                case.message
+            );
+        }
+    }
+    Ok(())
+}
+
+struct Case<'a> {
+    message: &'a str,
+    first: ObjectId,
+    others: Vec<ObjectId>,
+    bases: Vec<ObjectId>,
+}
+
+fn parse_baseline(input: &str) -> Vec<Case<'_>> {
+    input
+        .split("\n\n")
+        .filter(|record| !record.trim().is_empty())
+        .map(|record| {
+            let mut lines = record.lines();
+            let message = lines.next().expect("names");
+            let mut inputs = lines
+                .next()
+                .expect("input ids")
+                .split(' ')
+                .map(|hex| ObjectId::from_hex(hex.as_bytes()).expect("valid hex"));
+            Case {
+                message,
+                first: inputs.next().expect("at least one"),
+                others: inputs.collect(),
+                bases: lines
+                    .map(|hex| ObjectId::from_hex(hex.as_bytes()).expect("valid hex"))
+                    .collect(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,101 @@
+use std::{borrow::Cow, path::Path};
+
+use gix_hash::ObjectId;
+use gix_revision::name_rev::{Name, Tip};
+
+use crate::hex_to_id;
+
+#[test]
+fn each_commit_like_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_name_rev_repos.sh").map_err(|err| err.to_string())?;
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let mut graph = gix_revision::Graph::new(&odb, None);
+    for mode in ["all", "tags"] {
+        let tips = tips(&root, mode)?;
+        let baseline = baseline(&root, mode)?;
+        assert_eq!(baseline.len(), 20, "all commits are named");
+        for (id, expected) in &baseline {
+            let actual = gix_revision::name_rev(&[*id], tips.iter().cloned(), &mut graph)?;
+            assert_eq!(actual[0].as_ref().map(ToString::to_string), *expected, "{mode}: {id}");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn all_commits_at_once() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_name_rev_repos.sh").map_err(|err| err.to_string())?;
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let mut graph = gix_revision::Graph::new(&odb, None);
+    let baseline = baseline(&root, "all")?;
+    let commits: Vec<_> = baseline.iter().map(|(id, _)| *id).collect();
+    let actual = gix_revision::name_rev(&commits, tips(&root, "all")?, &mut graph)?;
+    assert_eq!(
+        actual
+            .iter()
+            .map(|name| name.as_ref().map(ToString::to_string))
+            .collect::<Vec<_>>(),
+        baseline.into_iter().map(|(_, name)| name).collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[test]
+fn without_tips_nothing_is_named() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_name_rev_repos.sh").map_err(|err| err.to_string())?;
+    let odb = gix_odb::at(root.join(".git/objects"))?;
+    let mut graph = gix_revision::Graph::new(&odb, None);
+    let (id, _) = baseline(&root, "all")?[0];
+    assert_eq!(gix_revision::name_rev(&[id], None, &mut graph)?, [None]);
+
+    let missing = hex_to_id("0000000000000000000000000000000000000001");
+    assert!(matches!(
+        gix_revision::name_rev(&[missing], None, &mut graph),
+        Err(gix_revision::name_rev::Error::NotFound { id }) if id == missing
+    ));
+    Ok(())
+}
+
+#[test]
+fn display() {
+    let name = |tip: &str, generation| Name {
+        tip: tip.into(),
+        generation,
+    };
+    assert_eq!(name("main", 0).to_string(), "main");
+    assert_eq!(name("main~3^2", 0).to_string(), "main~3^2");
+    assert_eq!(name("main~3^2", 1).to_string(), "main~3^2~1");
+    assert_eq!(name("v1.0^0", 0).to_string(), "v1.0^0");
+    assert_eq!(
+        name("v1.0^0", 2).to_string(),
+        "v1.0~2",
+        "peeled tags drop their suffix when walking"
+    );
+}
+
+fn tips(root: &Path, mode: &str) -> crate::Result<Vec<Tip<'static>>> {
+    Ok(std::fs::read_to_string(root.join(format!("tips-{mode}")))?
+        .lines()
+        .map(|line| {
+            let mut tokens = line.split(' ');
+            let mut next = || tokens.next().expect("all fields are present");
+            Tip {
+                name: Cow::Owned(next().into()),
+                id: hex_to_id(next()),
+                from_tag: next() == "1",
+                tagger_time: next().parse().ok(),
+                peeled: next() == "1",
+            }
+        })
+        .collect())
+}
+
+fn baseline(root: &Path, mode: &str) -> crate::Result<Vec<(ObjectId, Option<String>)>> {
+    Ok(std::fs::read_to_string(root.join(format!("baseline-{mode}")))?
+        .lines()
+        .map(|line| {
+            let (id, name) = line.split_once(' ').expect("id and name");
+            (hex_to_id(id), (name != "undefined").then(|| name.to_owned()))
+        })
+        .collect())
+}
@@ -1,5 +1,11 @@
+#[cfg(feature = "bisect")]
+mod bisect;
 #[cfg(feature = "describe")]
 mod describe;
+#[cfg(feature = "merge_base")]
+mod merge_base;
+#[cfg(feature = "name_rev")]
+mod name_rev;
 mod spec;
 pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error + 'static>>;
 
@@ -0,0 +1,55 @@
+//! Utilities to read and remove the files that hold the state of sequences and rebases in the `.git` directory.
+use std::path::{Path, PathBuf};
+
+use bstr::ByteSlice;
+use gix_hash::ObjectId;
+
+/// The error returned by [`read()`] and [`read_id()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("Could not parse the object id in '{}'", path.display())]
+    ObjectId {
+        path: PathBuf,
+        source: gix_hash::decode::Error,
+    },
+}
+
+/// Read the file at `path`, or return `None` if it doesn't exist.
+pub fn read(path: &Path) -> Result<Option<Vec<u8>>, Error> {
+    match std::fs::read(path) {
+        Ok(data) => Ok(Some(data)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(Error::Io {
+            path: path.to_owned(),
+            source,
+        }),
+    }
+}
+
+/// Read the object id stored in the file at `path`, or return `None` if it doesn't exist.
+pub fn read_id(path: &Path) -> Result<Option<ObjectId>, Error> {
+    read(path)?
+        .map(|data| {
+            ObjectId::from_hex(trim(&data)).map_err(|source| Error::ObjectId {
+                path: path.to_owned(),
+                source,
+            })
+        })
+        .transpose()
+}
+
+/// Remove the file at `path`, which is not an error if it doesn't exist.
+pub fn remove(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        res => res,
+    }
+}
+
+/// Remove leading and trailing whitespace from `input`, like the trailing newline of files written by `git`.
+pub fn trim(input: &[u8]) -> &[u8] {
+    input.trim_with(|c| c.is_ascii_whitespace())
+}
@@ -1,2 +1,16 @@
-#![deny(rust_2018_idioms)]
+//! Handle sequences of operations which may need human interaction to complete, like rebases and sequences of cherry-picks.
+//!
+//! The [todo list](todo::List) is the sequence of instructions to execute, and it's stored along with other
+//! [state](State) in the `.git` directory so that operations can be paused, for instance to resolve conflicts,
+//! and be continued later, compatibly with `git`.
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+pub mod file;
+
+///
+pub mod todo;
+
+///
+pub mod state;
+pub use state::State;
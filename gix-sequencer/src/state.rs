@@ -0,0 +1,169 @@
+use std::path::{Path, PathBuf};
+
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+
+use crate::{file, todo};
+
+/// The name of the directory within the `.git` directory that holds the state of a sequence of cherry-picks or reverts.
+pub const DIR_NAME: &str = "sequencer";
+
+/// Options of a sequence of cherry-picks or reverts, as stored in `.git/sequencer/opts`.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Options {
+    /// If `true`, record the commit that was cherry-picked in the message, like `git cherry-pick -x` does.
+    pub record_origin: bool,
+    /// The one-based index of the parent of merge commits to apply the changes relative to, like `git cherry-pick -m` does.
+    pub mainline: Option<usize>,
+    /// If `true`, add a `Signed-off-by` trailer to each commit message.
+    pub signoff: bool,
+    /// If `true`, allow commits that don't change anything.
+    pub allow_empty: bool,
+    /// The name of the merge strategy to use, if it's not the default.
+    pub strategy: Option<BString>,
+}
+
+/// The state of a sequence of cherry-picks or reverts, as stored in `.git/sequencer` by `git cherry-pick` or `git revert`
+/// with multiple commits.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct State {
+    /// The commit `HEAD` pointed to before the sequence started, as stored in `head`, to allow aborting it.
+    pub head: ObjectId,
+    /// The commit `HEAD` pointed to after the last successful step, as stored in `abort-safety`. If `HEAD` changed
+    /// since, aborting must not reset it.
+    pub abort_safety: Option<ObjectId>,
+    /// The instructions that are yet to be executed, with the first one being the one that is currently executed,
+    /// as stored in `todo`.
+    pub todo: todo::List,
+    /// Options for each step of the sequence, as stored in `opts`.
+    pub options: Options,
+}
+
+///
+pub mod read {
+    use std::path::PathBuf;
+
+    /// The error returned by [`State::read_from()`](super::State::read_from()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        File(#[from] crate::file::Error),
+        #[error("Invalid value '{value}' for option '{name}' in '{}'", path.display())]
+        InvalidOption {
+            path: PathBuf,
+            name: bstr::BString,
+            value: bstr::BString,
+        },
+        #[error(transparent)]
+        Todo(#[from] crate::todo::parse::Error),
+    }
+}
+
+impl State {
+    /// Return the path to the directory holding the state within `git_dir`.
+    pub fn dir(git_dir: &Path) -> PathBuf {
+        git_dir.join(DIR_NAME)
+    }
+
+    /// Read the state from the `sequencer` directory in `git_dir`, or return `None` if there is no sequence in progress.
+    pub fn read_from(git_dir: &Path) -> Result<Option<Self>, read::Error> {
+        let dir = Self::dir(git_dir);
+        let Some(todo) = file::read(&dir.join("todo"))? else {
+            return Ok(None);
+        };
+        let todo = todo::List::from_bytes(&todo)?;
+        let head = file::read_id(&dir.join("head"))?.ok_or_else(|| file::Error::Io {
+            path: dir.join("head"),
+            source: std::io::ErrorKind::NotFound.into(),
+        })?;
+        let abort_safety = file::read_id(&dir.join("abort-safety"))?;
+        let options = match file::read(&dir.join("opts"))? {
+            Some(opts) => parse_options(&opts, &dir.join("opts"))?,
+            None => Options::default(),
+        };
+        Ok(Some(State {
+            head,
+            abort_safety,
+            todo,
+            options,
+        }))
+    }
+
+    /// Write this state into the `sequencer` directory in `git_dir`, creating it if needed.
+    pub fn write_to(&self, git_dir: &Path) -> std::io::Result<()> {
+        let dir = Self::dir(git_dir);
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join("head"), format!("{}\n", self.head))?;
+        match self.abort_safety {
+            Some(id) => std::fs::write(dir.join("abort-safety"), format!("{id}\n"))?,
+            None => file::remove(&dir.join("abort-safety"))?,
+        }
+        std::fs::write(dir.join("todo"), self.todo.to_bstring())?;
+
+        let options = &self.options;
+        let mut opts = String::from("[options]\n");
+        let mut push = |name: &str, value: &dyn std::fmt::Display| opts.push_str(&format!("\t{name} = {value}\n"));
+        if options.record_origin {
+            push("record-origin", &true);
+        }
+        if let Some(mainline) = options.mainline {
+            push("mainline", &mainline);
+        }
+        if options.signoff {
+            push("signoff", &true);
+        }
+        if options.allow_empty {
+            push("allow-empty", &true);
+        }
+        if let Some(strategy) = &options.strategy {
+            push("strategy", strategy);
+        }
+        std::fs::write(dir.join("opts"), opts)
+    }
+
+    /// Remove the `sequencer` directory in `git_dir` along with all of its contents, if it exists.
+    pub fn remove(git_dir: &Path) -> std::io::Result<()> {
+        match std::fs::remove_dir_all(Self::dir(git_dir)) {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            res => res,
+        }
+    }
+}
+
+fn parse_options(input: &[u8], path: &Path) -> Result<Options, read::Error> {
+    let mut options = Options::default();
+    for line in input.lines().map(file::trim) {
+        let Some((name, value)) = line.split_once_str("=") else {
+            continue;
+        };
+        let (name, value) = (file::trim(name), file::trim(value));
+        let invalid = || read::Error::InvalidOption {
+            path: path.to_owned(),
+            name: name.into(),
+            value: value.into(),
+        };
+        let boolean = || match value {
+            b"true" | b"yes" | b"on" | b"1" => Ok(true),
+            b"false" | b"no" | b"off" | b"0" | b"" => Ok(false),
+            _ => Err(invalid()),
+        };
+        match name {
+            b"record-origin" => options.record_origin = boolean()?,
+            b"signoff" => options.signoff = boolean()?,
+            b"allow-empty" => options.allow_empty = boolean()?,
+            b"mainline" => {
+                options.mainline = Some(
+                    value
+                        .to_str()
+                        .ok()
+                        .and_then(|value| value.parse().ok())
+                        .ok_or_else(invalid)?,
+                );
+            }
+            b"strategy" => options.strategy = Some(value.into()),
+            _ => {}
+        }
+    }
+    Ok(options)
+}
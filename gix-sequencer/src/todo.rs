@@ -0,0 +1,521 @@
+use std::collections::HashMap;
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_hash::Prefix;
+
+/// Determine which message a commit gets after [fixing it up](Instruction::Fixup).
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum FixupMessage {
+    /// Keep the message of the commit that is fixed up, like `fixup <commit>` does.
+    #[default]
+    Keep,
+    /// Use the message of the fixup commit instead, like `fixup -C <commit>` does.
+    Replace,
+    /// Use the message of the fixup commit instead and let the user edit it, like `fixup -c <commit>` does.
+    ReplaceAndEdit,
+}
+
+/// A single line of a todo list, as understood by `git rebase --interactive` and `git cherry-pick` or `git revert`
+/// with multiple commits.
+///
+/// Instructions that refer to commits store the possibly abbreviated commit id along with the `summary` of the commit,
+/// which is only informational.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[allow(missing_docs)]
+pub enum Instruction {
+    /// Apply the changes of `commit`.
+    Pick { commit: Prefix, summary: BString },
+    /// Apply the inverse of the changes of `commit`, as used in sequences of reverts.
+    Revert { commit: Prefix, summary: BString },
+    /// Apply the changes of `commit`, but change its message.
+    Reword { commit: Prefix, summary: BString },
+    /// Apply the changes of `commit`, then stop to allow amending it.
+    Edit { commit: Prefix, summary: BString },
+    /// Meld the changes of `commit` into the previous commit, and combine both commit messages.
+    Squash { commit: Prefix, summary: BString },
+    /// Meld the changes of `commit` into the previous commit, and pick the resulting commit message with `message`.
+    Fixup {
+        commit: Prefix,
+        summary: BString,
+        message: FixupMessage,
+    },
+    /// Skip `commit`.
+    Drop { commit: Prefix, summary: BString },
+    /// Run `command` with the shell, and stop if it fails.
+    Exec { command: BString },
+    /// Stop to let the user continue later.
+    Break,
+    /// Do nothing, which is the only instruction in a todo list with no commits to apply.
+    Noop,
+    /// Remember the current commit as label `name`.
+    Label { name: BString },
+    /// Reset the current commit to the one labelled `name`, or to the commit `name` refers to.
+    Reset { name: BString },
+    /// Create a merge commit of the current commit and the one labelled `label`, with the message of the original
+    /// merge `commit`, or `summary` if there is none. If `edit` is `true`, the message should be edited by the user.
+    Merge {
+        commit: Option<Prefix>,
+        edit: bool,
+        label: BString,
+        summary: BString,
+    },
+    /// Set the reference `name` to the current commit once the operation is done.
+    UpdateRef { name: BString },
+}
+
+impl Instruction {
+    /// Return the name of the command used for this instruction in todo lists, like `pick`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Instruction::Pick { .. } => "pick",
+            Instruction::Revert { .. } => "revert",
+            Instruction::Reword { .. } => "reword",
+            Instruction::Edit { .. } => "edit",
+            Instruction::Squash { .. } => "squash",
+            Instruction::Fixup { .. } => "fixup",
+            Instruction::Drop { .. } => "drop",
+            Instruction::Exec { .. } => "exec",
+            Instruction::Break => "break",
+            Instruction::Noop => "noop",
+            Instruction::Label { .. } => "label",
+            Instruction::Reset { .. } => "reset",
+            Instruction::Merge { .. } => "merge",
+            Instruction::UpdateRef { .. } => "update-ref",
+        }
+    }
+
+    /// Return the commit this instruction refers to, if there is one.
+    pub fn commit(&self) -> Option<&Prefix> {
+        match self {
+            Instruction::Pick { commit, .. }
+            | Instruction::Revert { commit, .. }
+            | Instruction::Reword { commit, .. }
+            | Instruction::Edit { commit, .. }
+            | Instruction::Squash { commit, .. }
+            | Instruction::Fixup { commit, .. }
+            | Instruction::Drop { commit, .. } => Some(commit),
+            Instruction::Merge { commit, .. } => commit.as_ref(),
+            Instruction::Exec { .. }
+            | Instruction::Break
+            | Instruction::Noop
+            | Instruction::Label { .. }
+            | Instruction::Reset { .. }
+            | Instruction::UpdateRef { .. } => None,
+        }
+    }
+
+    /// Return the summary of the commit this instruction refers to, if there is one.
+    pub fn summary(&self) -> Option<&BStr> {
+        match self {
+            Instruction::Pick { summary, .. }
+            | Instruction::Revert { summary, .. }
+            | Instruction::Reword { summary, .. }
+            | Instruction::Edit { summary, .. }
+            | Instruction::Squash { summary, .. }
+            | Instruction::Fixup { summary, .. }
+            | Instruction::Drop { summary, .. }
+            | Instruction::Merge { summary, .. } => Some(summary.as_bstr()),
+            Instruction::Exec { .. }
+            | Instruction::Break
+            | Instruction::Noop
+            | Instruction::Label { .. }
+            | Instruction::Reset { .. }
+            | Instruction::UpdateRef { .. } => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = self.name();
+        let with_summary = |f: &mut std::fmt::Formatter<'_>, summary: &BString| {
+            if summary.is_empty() {
+                Ok(())
+            } else {
+                write!(f, " {summary}")
+            }
+        };
+        match self {
+            Instruction::Pick { commit, summary }
+            | Instruction::Revert { commit, summary }
+            | Instruction::Reword { commit, summary }
+            | Instruction::Edit { commit, summary }
+            | Instruction::Squash { commit, summary }
+            | Instruction::Drop { commit, summary } => {
+                write!(f, "{name} {commit}")?;
+                with_summary(f, summary)
+            }
+            Instruction::Fixup {
+                commit,
+                summary,
+                message,
+            } => {
+                let flag = match message {
+                    FixupMessage::Keep => "",
+                    FixupMessage::Replace => " -C",
+                    FixupMessage::ReplaceAndEdit => " -c",
+                };
+                write!(f, "{name}{flag} {commit}")?;
+                with_summary(f, summary)
+            }
+            Instruction::Exec { command } => write!(f, "{name} {command}"),
+            Instruction::Break | Instruction::Noop => f.write_str(name),
+            Instruction::Label { name: label } | Instruction::Reset { name: label } => write!(f, "{name} {label}"),
+            Instruction::Merge {
+                commit,
+                edit,
+                label,
+                summary,
+            } => {
+                f.write_str(name)?;
+                if let Some(commit) = commit {
+                    write!(f, " {} {commit}", if *edit { "-c" } else { "-C" })?;
+                }
+                write!(f, " {label}")?;
+                if summary.is_empty() {
+                    Ok(())
+                } else {
+                    write!(f, " # {summary}")
+                }
+            }
+            Instruction::UpdateRef { name: reference } => write!(f, "{name} {reference}"),
+        }
+    }
+}
+
+/// A list of instructions, as stored in `.git/rebase-merge/git-rebase-todo` or `.git/sequencer/todo`.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct List {
+    /// The instructions to execute, from first to last.
+    pub instructions: Vec<Instruction>,
+}
+
+///
+pub mod parse {
+    use bstr::BString;
+
+    /// The error returned by [`List::from_bytes()`](super::List::from_bytes()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Line {line_number}: unknown command '{command}'")]
+        UnknownCommand { line_number: usize, command: BString },
+        #[error("Line {line_number}: '{command}' needs an argument")]
+        MissingArgument { line_number: usize, command: &'static str },
+        #[error("Line {line_number}: '{command}' does not take any arguments")]
+        UnexpectedArgument { line_number: usize, command: &'static str },
+        #[error("Line {line_number}: '{commit}' is not a valid commit")]
+        InvalidCommit {
+            line_number: usize,
+            commit: BString,
+            source: gix_hash::prefix::from_hex::Error,
+        },
+    }
+}
+
+/// Parsing and serialization
+impl List {
+    /// Parse a todo list from `input`, ignoring empty lines and comments starting with `#`, just like `git` does.
+    ///
+    /// Commands can be abbreviated by their first letter, like `p` for `pick`.
+    pub fn from_bytes(input: &[u8]) -> Result<Self, parse::Error> {
+        let mut instructions = Vec::new();
+        for (line_number, line) in input
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line.trim_with(|c| c.is_ascii_whitespace())))
+        {
+            if line.is_empty() || line.starts_with(b"#") {
+                continue;
+            }
+            instructions.push(parse_instruction(line.as_bstr(), line_number)?);
+        }
+        Ok(List { instructions })
+    }
+
+    /// Write all instructions to `out`, one per line.
+    pub fn write_to(&self, out: &mut dyn std::io::Write) -> std::io::Result<()> {
+        for instruction in &self.instructions {
+            writeln!(out, "{instruction}")?;
+        }
+        Ok(())
+    }
+
+    /// Return the instructions serialized as they would be [written](Self::write_to()).
+    pub fn to_bstring(&self) -> BString {
+        let mut buf = Vec::new();
+        self.write_to(&mut buf).expect("writing to a vector never fails");
+        buf.into()
+    }
+}
+
+fn parse_instruction(line: &BStr, line_number: usize) -> Result<Instruction, parse::Error> {
+    let (command, args) = split_word(line);
+    let name = match command.as_bytes() {
+        b"pick" | b"p" => "pick",
+        b"revert" => "revert",
+        b"reword" | b"r" => "reword",
+        b"edit" | b"e" => "edit",
+        b"squash" | b"s" => "squash",
+        b"fixup" | b"f" => "fixup",
+        b"drop" | b"d" => "drop",
+        b"exec" | b"x" => "exec",
+        b"break" | b"b" => "break",
+        b"noop" => "noop",
+        b"label" | b"l" => "label",
+        b"reset" | b"t" => "reset",
+        b"merge" | b"m" => "merge",
+        b"update-ref" | b"u" => "update-ref",
+        _ => {
+            return Err(parse::Error::UnknownCommand {
+                line_number,
+                command: command.to_owned(),
+            })
+        }
+    };
+    let missing_argument = || parse::Error::MissingArgument {
+        line_number,
+        command: name,
+    };
+    let parse_commit = |commit: &BStr| {
+        commit
+            .to_str()
+            .ok()
+            .map_or(Err(gix_hash::prefix::from_hex::Error::Invalid), Prefix::from_hex)
+            .map_err(|source| parse::Error::InvalidCommit {
+                line_number,
+                commit: commit.to_owned(),
+                source,
+            })
+    };
+    let commit_and_summary = |args: &BStr| {
+        let (commit, summary) = split_word(args);
+        if commit.is_empty() {
+            return Err(missing_argument());
+        }
+        Ok((parse_commit(commit)?, summary.to_owned()))
+    };
+
+    Ok(match name {
+        "break" | "noop" => {
+            if !args.is_empty() {
+                return Err(parse::Error::UnexpectedArgument {
+                    line_number,
+                    command: name,
+                });
+            }
+            if name == "break" {
+                Instruction::Break
+            } else {
+                Instruction::Noop
+            }
+        }
+        "exec" | "label" | "reset" | "update-ref" => {
+            if args.is_empty() {
+                return Err(missing_argument());
+            }
+            let arg = match name {
+                "exec" => args.to_owned(),
+                _ => split_word(args).0.to_owned(),
+            };
+            match name {
+                "exec" => Instruction::Exec { command: arg },
+                "label" => Instruction::Label { name: arg },
+                "reset" => Instruction::Reset { name: arg },
+                _ => Instruction::UpdateRef { name: arg },
+            }
+        }
+        "fixup" => {
+            let (flag, rest) = split_word(args);
+            let (message, args) = match flag.as_bytes() {
+                b"-C" => (FixupMessage::Replace, rest),
+                b"-c" => (FixupMessage::ReplaceAndEdit, rest),
+                _ => (FixupMessage::Keep, args),
+            };
+            let (commit, summary) = commit_and_summary(args)?;
+            Instruction::Fixup {
+                commit,
+                summary,
+                message,
+            }
+        }
+        "merge" => {
+            let (flag, rest) = split_word(args);
+            let (commit, edit, args) = match flag.as_bytes() {
+                b"-C" | b"-c" => {
+                    let (commit, rest) = split_word(rest);
+                    if commit.is_empty() {
+                        return Err(missing_argument());
+                    }
+                    (Some(parse_commit(commit)?), flag == "-c", rest)
+                }
+                _ => (None, false, args),
+            };
+            let (label, rest) = split_word(args);
+            if label.is_empty() {
+                return Err(missing_argument());
+            }
+            let summary = rest.strip_prefix(b"#").map_or(rest, |summary| {
+                summary.trim_start_with(|c| c.is_ascii_whitespace()).as_bstr()
+            });
+            Instruction::Merge {
+                commit,
+                edit,
+                label: label.to_owned(),
+                summary: summary.to_owned(),
+            }
+        }
+        _ => {
+            let (commit, summary) = commit_and_summary(args)?;
+            match name {
+                "pick" => Instruction::Pick { commit, summary },
+                "revert" => Instruction::Revert { commit, summary },
+                "reword" => Instruction::Reword { commit, summary },
+                "edit" => Instruction::Edit { commit, summary },
+                "squash" => Instruction::Squash { commit, summary },
+                _ => Instruction::Drop { commit, summary },
+            }
+        }
+    })
+}
+
+/// Split the first word off `input`, and return it along with the remainder without leading whitespace.
+fn split_word(input: &BStr) -> (&BStr, &BStr) {
+    match input.find_byteset(b" \t") {
+        Some(pos) => (
+            input[..pos].as_bstr(),
+            input[pos..].trim_start_with(|c| c.is_ascii_whitespace()).as_bstr(),
+        ),
+        None => (input, b"".as_bstr()),
+    }
+}
+
+/// Manipulation
+impl List {
+    /// Move commits whose summary starts with `fixup! `, `squash! ` or `amend! ` right after the commit they refer to and
+    /// change their command to `fixup`, `squash` or `fixup -C` respectively, just like `git rebase --autosquash` does.
+    ///
+    /// The commit that is referred to is the first one before the fixup commit whose summary is the rest of the summary
+    /// of the fixup commit, or whose id starts with it, or whose summary starts with it.
+    /// Multiple fixups of the same commit keep their order.
+    pub fn autosquash(&mut self) {
+        let num_instructions = self.instructions.len();
+        let mut fixups_of = vec![Vec::new(); num_instructions];
+        let mut is_moved = vec![false; num_instructions];
+        let mut fixup_target = vec![None::<usize>; num_instructions];
+        let mut first_with_summary = HashMap::<BString, usize>::new();
+
+        for idx in 0..num_instructions {
+            let Instruction::Pick { commit, summary } = &self.instructions[idx] else {
+                continue;
+            };
+            if let Some((kind, rest)) = fixup_kind(summary.as_bstr()) {
+                let target = first_with_summary
+                    .get(rest)
+                    .copied()
+                    .or_else(|| {
+                        rest.to_str()
+                            .ok()
+                            .filter(|rest| !rest.contains(' '))
+                            .and_then(|rest| Prefix::from_hex(rest).ok())
+                            .and_then(|prefix| {
+                                let prefix = prefix.to_string();
+                                self.instructions[..idx].iter().position(|instruction| {
+                                    instruction
+                                        .commit()
+                                        .map_or(false, |commit| commit.to_string().starts_with(&prefix))
+                                })
+                            })
+                    })
+                    .or_else(|| {
+                        self.instructions[..idx].iter().position(|instruction| {
+                            instruction
+                                .summary()
+                                .map_or(false, |summary| !rest.is_empty() && summary.starts_with(rest))
+                        })
+                    })
+                    .filter(|target| self.instructions[*target].commit().is_some());
+                if let Some(target) = target {
+                    let root = fixup_target[target].unwrap_or(target);
+                    fixup_target[idx] = Some(root);
+                    fixups_of[root].push(idx);
+                    is_moved[idx] = true;
+                    let (commit, summary) = (commit.clone(), summary.clone());
+                    self.instructions[idx] = match kind {
+                        FixupKind::Fixup => Instruction::Fixup {
+                            commit,
+                            summary,
+                            message: FixupMessage::Keep,
+                        },
+                        FixupKind::Amend => Instruction::Fixup {
+                            commit,
+                            summary,
+                            message: FixupMessage::Replace,
+                        },
+                        FixupKind::Squash => Instruction::Squash { commit, summary },
+                    };
+                }
+            }
+            if let Some(summary) = self.instructions[idx].summary() {
+                first_with_summary.entry(summary.to_owned()).or_insert(idx);
+            }
+        }
+
+        if !is_moved.contains(&true) {
+            return;
+        }
+        let mut instructions: Vec<_> = std::mem::take(&mut self.instructions).into_iter().map(Some).collect();
+        for idx in 0..num_instructions {
+            if is_moved[idx] {
+                continue;
+            }
+            self.instructions.extend(instructions[idx].take());
+            for fixup in &fixups_of[idx] {
+                self.instructions.extend(instructions[*fixup].take());
+            }
+        }
+    }
+
+    /// Insert an [`UpdateRef`](Instruction::UpdateRef) instruction after each instruction that applies a commit, for each
+    /// reference name that `refs_at(commit)` returns, similar to what `git rebase --update-refs` does.
+    ///
+    /// This way, references pointing to commits that are rewritten will point to the rewritten commits once the operation
+    /// is done. Call this before [`autosquash()`](Self::autosquash()) to have fixups land before the updated references.
+    pub fn insert_update_refs(&mut self, mut refs_at: impl FnMut(&Prefix) -> Vec<BString>) {
+        let instructions = std::mem::take(&mut self.instructions);
+        for instruction in instructions {
+            let names = match &instruction {
+                Instruction::Drop { .. } | Instruction::Merge { .. } => Vec::new(),
+                instruction => instruction.commit().map(&mut refs_at).unwrap_or_default(),
+            };
+            self.instructions.push(instruction);
+            self.instructions
+                .extend(names.into_iter().map(|name| Instruction::UpdateRef { name }));
+        }
+    }
+}
+
+enum FixupKind {
+    Fixup,
+    Squash,
+    Amend,
+}
+
+/// Return the kind of fixup `summary` indicates, along with the summary of the commit to fix up, with nested
+/// prefixes like in `fixup! fixup! summary` removed.
+fn fixup_kind(summary: &BStr) -> Option<(FixupKind, &BStr)> {
+    fn strip(summary: &BStr) -> Option<(FixupKind, &BStr)> {
+        [
+            (&b"fixup! "[..], FixupKind::Fixup),
+            (b"squash! ", FixupKind::Squash),
+            (b"amend! ", FixupKind::Amend),
+        ]
+        .into_iter()
+        .find_map(|(prefix, kind)| summary.strip_prefix(prefix).map(|rest| (kind, rest.as_bstr())))
+    }
+    let (kind, mut rest) = strip(summary)?;
+    while let Some((_, nested)) = strip(rest) {
+        rest = nested;
+    }
+    Some((kind, rest))
+}
@@ -0,0 +1,52 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# A sequence editor that saves the todo list it is given and aborts the rebase.
+cat > save-todo.sh <<'SCRIPT'
+#!/bin/sh
+grep -v '^#' "$1" | grep -v '^$' > "$TODO_OUT"
+exit 1
+SCRIPT
+chmod +x save-todo.sh
+export GIT_SEQUENCE_EDITOR="$PWD/save-todo.sh"
+
+git init -q repo
+(cd repo
+  echo base > file && git add file && git commit -q -m "base"
+  git tag base
+  function commit() {
+    local name=${1:?need file name}
+    shift
+    echo "$name" >> "$name" && git add "$name" && git commit -q "$@"
+  }
+  commit a -m "add a"
+  commit b -m "add b" -m "with body"
+  git branch at-b
+  commit c -m "fixup! add a"
+  commit d -m "squash! add b"
+  git branch at-d
+  commit e -m "amend! add a" -m "add a, amended"
+  commit f -m "fixup! fixup! add a"
+  commit g -m "fixup! $(git rev-parse --short HEAD~4)"
+  commit h -m "fixup! add"
+  commit i -m "fixup! does not exist"
+  git branch at-i
+
+  git for-each-ref --format='%(objectname) %(refname)' refs/heads/ > ../refs
+  git rev-parse --symbolic-full-name HEAD > ../head-name
+
+  TODO_OUT=../plain.todo git rebase -i --no-autosquash --no-update-refs base 2>/dev/null || :
+  TODO_OUT=../autosquash.todo git rebase -i --autosquash --no-update-refs base 2>/dev/null || :
+  TODO_OUT=../update-refs.todo git rebase -i --no-autosquash --update-refs base 2>/dev/null || :
+)
+
+git init -q sequence
+(cd sequence
+  echo base > file && git add file && git commit -q -m "base"
+  git checkout -q -b other
+  echo other > file && git commit -q -am "change file"
+  echo new > new && git add new && git commit -q -m "add new"
+  git checkout -q main
+  echo main > file && git commit -q -am "change file on main"
+  git cherry-pick -x other~1 other 2>/dev/null || :
+)
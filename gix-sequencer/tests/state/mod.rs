@@ -0,0 +1,74 @@
+use gix_sequencer::{
+    state::{Options, DIR_NAME},
+    todo::Instruction,
+    State,
+};
+
+#[test]
+fn read_and_write_state_written_by_git() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_todo_lists.sh")?;
+    let git_dir = root.join("sequence").join(".git");
+    let state = State::read_from(&git_dir)?.expect("a sequence is in progress");
+    assert_eq!(
+        state.options,
+        Options {
+            record_origin: true,
+            ..Default::default()
+        }
+    );
+    assert_eq!(state.abort_safety, Some(state.head));
+    assert_eq!(
+        state
+            .todo
+            .instructions
+            .iter()
+            .map(|instruction| instruction.summary().expect("present").to_string())
+            .collect::<Vec<_>>(),
+        ["change file", "add new"]
+    );
+    assert!(state
+        .todo
+        .instructions
+        .iter()
+        .all(|instruction| matches!(instruction, Instruction::Pick { .. })));
+
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    state.write_to(tmp.path())?;
+    for name in ["head", "abort-safety", "todo", "opts"] {
+        assert_eq!(
+            std::fs::read(tmp.path().join(DIR_NAME).join(name))?,
+            std::fs::read(git_dir.join(DIR_NAME).join(name))?,
+            "{name} is written like git does"
+        );
+    }
+    assert_eq!(State::read_from(tmp.path())?, Some(state));
+
+    State::remove(tmp.path())?;
+    assert_eq!(State::read_from(tmp.path())?, None);
+    State::remove(tmp.path())?;
+    Ok(())
+}
+
+#[test]
+fn options_round_trip() -> crate::Result {
+    let tmp = gix_testtools::tempfile::TempDir::new()?;
+    let state = State {
+        head: gix_hash::Kind::Sha1.null(),
+        abort_safety: None,
+        todo: Default::default(),
+        options: Options {
+            record_origin: false,
+            mainline: Some(2),
+            signoff: true,
+            allow_empty: true,
+            strategy: Some("ort".into()),
+        },
+    };
+    state.write_to(tmp.path())?;
+    assert_eq!(
+        std::fs::read_to_string(tmp.path().join(DIR_NAME).join("opts"))?,
+        "[options]\n\tmainline = 2\n\tsignoff = true\n\tallow-empty = true\n\tstrategy = ort\n"
+    );
+    assert_eq!(State::read_from(tmp.path())?, Some(state));
+    Ok(())
+}
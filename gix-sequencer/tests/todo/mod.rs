@@ -0,0 +1,195 @@
+use gix_hash::Prefix;
+use gix_sequencer::todo::{FixupMessage, Instruction, List};
+use gix_testtools::bstr::{BString, ByteSlice};
+
+fn fixture_file(name: &str) -> crate::Result<Vec<u8>> {
+    let root = gix_testtools::scripted_fixture_read_only("make_todo_lists.sh")?;
+    Ok(std::fs::read(root.join(name))?)
+}
+
+fn todo(name: &str) -> crate::Result<List> {
+    Ok(List::from_bytes(&fixture_file(name)?)?)
+}
+
+fn prefix(hex: &str) -> Prefix {
+    Prefix::from_hex(hex).expect("valid hex")
+}
+
+mod parse {
+    use super::prefix;
+    use gix_sequencer::todo::{FixupMessage, Instruction, List};
+
+    #[test]
+    fn all_commands_and_their_abbreviations() -> crate::Result {
+        let input = "# a comment\n\
+                     pick 28ae060 add a\n\
+                     p 28ae060\n\
+                     revert 81549da subject\n\
+                     r 81549da  reworded   subject \n\
+                     edit 0af7f93 e\n\
+                     s 538e839 squash! add b\n\
+                     f -C aaaf2a1 amend! add a\n\
+                     fixup -c aaaf2a1\n\
+                     d 5d7ef49 dropped\n\
+                     \n\
+                     exec make test  &&  echo done\n\
+                     break\n\
+                     noop\n\
+                     l onto\n\
+                     t onto\n\
+                     merge -C 7f0b69e topic # Merge branch 'topic'\n\
+                     m -c 7f0b69e topic\n\
+                     merge other\n\
+                     u refs/heads/main\r\n";
+        let list = List::from_bytes(input.as_bytes())?;
+        assert_eq!(
+            list.instructions,
+            [
+                Instruction::Pick {
+                    commit: prefix("28ae060"),
+                    summary: "add a".into()
+                },
+                Instruction::Pick {
+                    commit: prefix("28ae060"),
+                    summary: "".into()
+                },
+                Instruction::Revert {
+                    commit: prefix("81549da"),
+                    summary: "subject".into()
+                },
+                Instruction::Reword {
+                    commit: prefix("81549da"),
+                    summary: "reworded   subject".into()
+                },
+                Instruction::Edit {
+                    commit: prefix("0af7f93"),
+                    summary: "e".into()
+                },
+                Instruction::Squash {
+                    commit: prefix("538e839"),
+                    summary: "squash! add b".into()
+                },
+                Instruction::Fixup {
+                    commit: prefix("aaaf2a1"),
+                    summary: "amend! add a".into(),
+                    message: FixupMessage::Replace
+                },
+                Instruction::Fixup {
+                    commit: prefix("aaaf2a1"),
+                    summary: "".into(),
+                    message: FixupMessage::ReplaceAndEdit
+                },
+                Instruction::Drop {
+                    commit: prefix("5d7ef49"),
+                    summary: "dropped".into()
+                },
+                Instruction::Exec {
+                    command: "make test  &&  echo done".into()
+                },
+                Instruction::Break,
+                Instruction::Noop,
+                Instruction::Label { name: "onto".into() },
+                Instruction::Reset { name: "onto".into() },
+                Instruction::Merge {
+                    commit: Some(prefix("7f0b69e")),
+                    edit: false,
+                    label: "topic".into(),
+                    summary: "Merge branch 'topic'".into()
+                },
+                Instruction::Merge {
+                    commit: Some(prefix("7f0b69e")),
+                    edit: true,
+                    label: "topic".into(),
+                    summary: "".into()
+                },
+                Instruction::Merge {
+                    commit: None,
+                    edit: false,
+                    label: "other".into(),
+                    summary: "".into()
+                },
+                Instruction::UpdateRef {
+                    name: "refs/heads/main".into()
+                },
+            ]
+        );
+
+        let serialized = list.to_bstring();
+        assert_eq!(
+            List::from_bytes(&serialized)?,
+            list,
+            "serialization round-trips:\n{serialized}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn lists_written_by_git_round_trip() -> crate::Result {
+        for name in ["plain.todo", "autosquash.todo", "update-refs.todo"] {
+            let input = super::fixture_file(name)?;
+            let list = List::from_bytes(&input)?;
+            assert_eq!(list.to_bstring(), input, "{name}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        for (input, expected) in [
+            ("pick 28ae060\nunknown 28ae060", "Line 2: unknown command 'unknown'"),
+            ("pick", "Line 1: 'pick' needs an argument"),
+            ("fixup -C", "Line 1: 'fixup' needs an argument"),
+            ("exec", "Line 1: 'exec' needs an argument"),
+            ("merge -C 28ae060", "Line 1: 'merge' needs an argument"),
+            ("break now", "Line 1: 'break' does not take any arguments"),
+            ("pick HEAD~1", "Line 1: 'HEAD~1' is not a valid commit"),
+            ("pick 28a", "Line 1: '28a' is not a valid commit"),
+        ] {
+            let err = List::from_bytes(input.as_bytes()).expect_err("invalid");
+            assert_eq!(err.to_string(), expected, "{input:?}");
+        }
+    }
+}
+
+#[test]
+fn autosquash_matches_git() -> crate::Result {
+    let mut list = todo("plain.todo")?;
+    list.autosquash();
+    assert_eq!(list, todo("autosquash.todo")?);
+
+    let again = list.clone();
+    list.autosquash();
+    assert_eq!(list, again, "squashing is idempotent");
+    assert!(
+        list.instructions.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::Fixup {
+                message: FixupMessage::Replace,
+                ..
+            }
+        )),
+        "amend! turns into fixup -C"
+    );
+    Ok(())
+}
+
+#[test]
+fn update_refs_match_git() -> crate::Result {
+    let head_name = fixture_file("head-name")?;
+    let refs: Vec<(String, BString)> = fixture_file("refs")?
+        .lines()
+        .filter_map(|line| line.split_once_str(" "))
+        .filter(|(_, name)| *name != head_name.trim())
+        .map(|(id, name)| (id.to_str_lossy().into_owned(), name.into()))
+        .collect();
+    let mut list = todo("plain.todo")?;
+    list.insert_update_refs(|commit| {
+        let commit = commit.to_string();
+        refs.iter()
+            .filter(|(id, _)| id.starts_with(&commit))
+            .map(|(_, name)| name.clone())
+            .collect()
+    });
+    assert_eq!(list, todo("update-refs.todo")?);
+    Ok(())
+}
@@ -0,0 +1,205 @@
+//! Query a filesystem monitor through its hook to learn which paths changed since it was last asked,
+//! and use this information to mark index entries as valid so that they don't need to be `lstat()`ed.
+//!
+//! This is what `git` does when `core.fsmonitor` is set to the path of a hook program.
+use std::{ffi::OsString, path::Path};
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_index::{entry, extension::fs_monitor::Token};
+
+/// The version of the hook protocol to use when talking to the filesystem monitor, as configured by `core.fsmonitorHookVersion`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Version {
+    /// Pass the time of the last query in nanoseconds, and receive paths that changed since then.
+    V1,
+    /// Pass an opaque token, and receive a new token along with all paths that changed since the previous token was issued.
+    #[default]
+    V2,
+}
+
+/// The error returned by [`query()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not launch the filesystem monitor hook '{}'", hook.to_string_lossy())]
+    Spawn { hook: OsString, source: std::io::Error },
+    #[error("The filesystem monitor hook '{}' failed with {status}", hook.to_string_lossy())]
+    Failed {
+        hook: OsString,
+        status: std::process::ExitStatus,
+    },
+    #[error("The filesystem monitor hook didn't provide a new token")]
+    MissingToken,
+    #[error("The clock was off when obtaining the time for the query")]
+    Time(#[from] std::time::SystemTimeError),
+}
+
+/// The changes reported by the filesystem monitor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Changes {
+    /// The monitor couldn't provide information about what changed, so everything must be considered changed.
+    ///
+    /// This happens if the token passed was unknown to it, or if it was restarted.
+    All,
+    /// The paths that changed, relative to the root of the worktree.
+    ///
+    /// Paths that end with a slash denote directories in which everything is considered changed.
+    Paths(Vec<BString>),
+}
+
+/// The result of [`query()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// The token to store and pass the next time the monitor is queried.
+    pub token: Token,
+    /// All changes since the token passed to the query.
+    pub changes: Changes,
+}
+
+/// Information collected by [`apply()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Outcome {
+    /// The amount of entries that are considered valid by the filesystem monitor, and won't need to be checked for changes.
+    pub entries_valid: usize,
+    /// The amount of entries which have to be checked for changes.
+    pub entries_dirty: usize,
+}
+
+/// Run the filesystem monitor `hook` in `worktree` using protocol `version`, and pass `token` to learn about
+/// all changes since it was obtained.
+///
+/// If `token` is `None`, the monitor isn't asked about changes, and everything is considered changed
+/// while a new token is still obtained.
+pub fn query(
+    hook: impl Into<OsString>,
+    version: Version,
+    token: Option<&Token>,
+    worktree: &Path,
+) -> Result<Response, Error> {
+    let hook = hook.into();
+    let (version_arg, token_arg) = match version {
+        Version::V1 => (
+            "1",
+            match token {
+                Some(Token::V1 { nanos_since_1970 }) => nanos_since_1970.to_string().into(),
+                Some(Token::V2 { token }) => token.clone(),
+                None => "0".into(),
+            },
+        ),
+        Version::V2 => (
+            "2",
+            match token {
+                Some(Token::V1 { nanos_since_1970 }) => nanos_since_1970.to_string().into(),
+                Some(Token::V2 { token }) => token.clone(),
+                None => BString::default(),
+            },
+        ),
+    };
+    let query_time = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+    let mut cmd: std::process::Command = gix_command::prepare(hook.clone())
+        .with_shell()
+        .arg(version_arg)
+        .arg(gix_path::from_bstring(token_arg))
+        .stderr(std::process::Stdio::inherit())
+        .into();
+    cmd.current_dir(worktree);
+    gix_features::trace::debug!(cmd = ?cmd, "querying filesystem monitor");
+    let output = cmd.output().map_err(|source| Error::Spawn {
+        hook: hook.clone(),
+        source,
+    })?;
+    if !output.status.success() {
+        return Err(Error::Failed {
+            hook,
+            status: output.status,
+        });
+    }
+
+    let mut items = output.stdout.split(|b| *b == 0);
+    let new_token = match version {
+        Version::V1 => Token::V1 {
+            nanos_since_1970: query_time.as_nanos() as u64,
+        },
+        Version::V2 => Token::V2 {
+            token: items.next().filter(|t| !t.is_empty()).ok_or(Error::MissingToken)?.into(),
+        },
+    };
+    let mut paths = Vec::new();
+    let mut everything_changed = token.is_none();
+    for path in items.filter(|p| !p.is_empty()) {
+        if path == b"/" {
+            everything_changed = true;
+            break;
+        }
+        paths.push(path.into());
+    }
+    Ok(Response {
+        token: new_token,
+        changes: if everything_changed {
+            Changes::All
+        } else {
+            Changes::Paths(paths)
+        },
+    })
+}
+
+/// Update the [`FSMONITOR_VALID`](entry::Flags::FSMONITOR_VALID) flag of all entries in `index` with `response`,
+/// and store its token in the `index` for the next query.
+///
+/// Entries that were reported as changed by the filesystem monitor are marked dirty, all other entries keep their flag.
+/// Thus, right after reading `index`, [`State::apply_fs_monitor_dirty_bitmap()`](gix_index::State::apply_fs_monitor_dirty_bitmap())
+/// should be called to initialize the flags from the filesystem monitor extension, if present.
+/// Entries that remain valid will be skipped by [`index_as_worktree()`](crate::index_as_worktree()).
+///
+/// Note that entries that are found to be unchanged after checking them should be marked
+/// valid by the caller before writing the `index`, so they don't need to be checked next time.
+pub fn apply(index: &mut gix_index::State, response: Response) -> Outcome {
+    let Response { token, changes } = response;
+    match changes {
+        Changes::All => {
+            for entry in index.entries_mut() {
+                entry.flags.remove(entry::Flags::FSMONITOR_VALID);
+            }
+        }
+        Changes::Paths(paths) => {
+            for path in paths {
+                invalidate_path(index, path.as_bstr());
+            }
+        }
+    }
+
+    match index.fs_monitor_mut() {
+        Some(fs_monitor) => fs_monitor.set_token(token),
+        None => {
+            index.set_fs_monitor(Some(gix_index::extension::FsMonitor::new(token)));
+        }
+    }
+
+    let entries_valid = index
+        .entries()
+        .iter()
+        .filter(|e| e.flags.contains(entry::Flags::FSMONITOR_VALID))
+        .count();
+    Outcome {
+        entries_valid,
+        entries_dirty: index.entries().len() - entries_valid,
+    }
+}
+
+fn invalidate_path(index: &mut gix_index::State, path: &BStr) {
+    let dir = path.strip_suffix(b"/").map(ByteSlice::as_bstr);
+    let exact = match dir {
+        Some(_) => None,
+        None => index.entry_range(path),
+    };
+    let mut dir_prefix = BString::from(dir.unwrap_or(path));
+    dir_prefix.push(b'/');
+    let below = index.prefixed_entries_range(dir_prefix.as_bstr());
+
+    let entries = index.entries_mut();
+    for range in exact.into_iter().chain(below) {
+        for entry in &mut entries[range] {
+            entry.flags.remove(entry::Flags::FSMONITOR_VALID);
+        }
+    }
+}
@@ -13,6 +13,8 @@ use bstr::BStr;
 pub mod index_as_worktree;
 pub use index_as_worktree::function::index_as_worktree;
 
+pub mod fs_monitor;
+
 /// A trait to facilitate working working with pathspecs.
 pub trait Pathspec {
     /// Return the portion of the prefix among all of the pathspecs involved in this search, or an empty string if
@@ -0,0 +1,143 @@
+use bstr::BString;
+use gix_index::{entry, extension::fs_monitor::Token};
+use gix_status::fs_monitor::{apply, query, Changes, Error, Outcome, Response, Version};
+
+use crate::fixture_path;
+
+fn hook(dir: &std::path::Path, script: &str) -> std::ffi::OsString {
+    let path = dir.join("hook.sh");
+    std::fs::write(&path, script).expect("can write hook");
+    format!("sh {}", path.display()).into()
+}
+
+fn index_with_all_entries_valid() -> gix_index::File {
+    let mut index = gix_index::File::at(
+        fixture_path("status_unchanged").join(".git/index"),
+        gix_hash::Kind::Sha1,
+        false,
+        Default::default(),
+    )
+    .expect("index is valid");
+    for entry in index.entries_mut() {
+        entry.flags.insert(entry::Flags::FSMONITOR_VALID);
+    }
+    index
+}
+
+fn valid_paths(index: &gix_index::State) -> Vec<String> {
+    index
+        .entries()
+        .iter()
+        .filter(|e| e.flags.contains(entry::Flags::FSMONITOR_VALID))
+        .map(|e| e.path(index).to_string())
+        .collect()
+}
+
+#[test]
+fn query_without_token_considers_everything_changed() -> crate::Result {
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let hook = hook(tmp.path(), r#"printf 'new-token\0file\0'"#);
+    let res = query(hook, Version::V2, None, tmp.path())?;
+    assert_eq!(
+        res,
+        Response {
+            token: Token::V2 {
+                token: "new-token".into()
+            },
+            changes: Changes::All,
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn query_v2_receives_changed_paths_and_passes_token() -> crate::Result {
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let hook = hook(
+        tmp.path(),
+        r#"test "$1" = 2 && test "$2" = old-token && printf 'new-token\0empty\0dir/sub-dir/\0'"#,
+    );
+    let res = query(
+        hook,
+        Version::V2,
+        Some(&Token::V2 {
+            token: "old-token".into(),
+        }),
+        tmp.path(),
+    )?;
+    assert_eq!(res.token, Token::V2 { token: "new-token".into() });
+    assert_eq!(
+        res.changes,
+        Changes::Paths(vec![BString::from("empty"), "dir/sub-dir/".into()])
+    );
+
+    let mut index = index_with_all_entries_valid();
+    let out = apply(&mut index, res);
+    assert_eq!(
+        out,
+        Outcome {
+            entries_valid: 2,
+            entries_dirty: 2,
+        }
+    );
+    assert_eq!(
+        valid_paths(&index),
+        ["dir/content", "executable"],
+        "changed files and everything in changed directories are dirty"
+    );
+    assert_eq!(
+        index.fs_monitor().map(gix_index::extension::FsMonitor::token),
+        Some(&Token::V2 { token: "new-token".into() }),
+        "the extension is created to hold the token for the next query"
+    );
+    Ok(())
+}
+
+#[test]
+fn query_v1_receives_changed_paths_only() -> crate::Result {
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let hook = hook(tmp.path(), r#"test "$1" = 1 && test "$2" = 42 && printf 'executable\0'"#);
+    let res = query(hook, Version::V1, Some(&Token::V1 { nanos_since_1970: 42 }), tmp.path())?;
+    assert!(
+        matches!(res.token, Token::V1 { nanos_since_1970 } if nanos_since_1970 > 42),
+        "the new token is the time of the query"
+    );
+    assert_eq!(res.changes, Changes::Paths(vec!["executable".into()]));
+    Ok(())
+}
+
+#[test]
+fn a_slash_signals_that_everything_changed() -> crate::Result {
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let hook = hook(tmp.path(), r#"printf 'new-token\0empty\0/\0'"#);
+    let res = query(hook, Version::V2, Some(&Token::V2 { token: "old".into() }), tmp.path())?;
+    assert_eq!(res.changes, Changes::All);
+
+    let mut index = index_with_all_entries_valid();
+    let out = apply(&mut index, res);
+    assert_eq!(
+        out,
+        Outcome {
+            entries_valid: 0,
+            entries_dirty: 4,
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn failing_hooks_are_errors() -> crate::Result {
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let hook = hook(tmp.path(), "exit 1");
+    assert!(matches!(
+        query(hook, Version::V2, None, tmp.path()),
+        Err(Error::Failed { .. })
+    ));
+
+    let hook = self::hook(tmp.path(), "printf ''");
+    assert!(
+        matches!(query(hook, Version::V2, None, tmp.path()), Err(Error::MissingToken)),
+        "v2 hooks must provide a token"
+    );
+    Ok(())
+}
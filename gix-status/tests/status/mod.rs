@@ -1,3 +1,4 @@
+mod fs_monitor;
 mod index_as_worktree;
 
 pub fn fixture_path(name: &str) -> std::path::PathBuf {
@@ -9,6 +9,61 @@ pub struct Ancestors<Find, Predicate, StateMut> {
     state: StateMut,
     parents: Parents,
     sorting: Sorting,
+    simplification: Simplification,
+    reverse: bool,
+    buffered: Option<std::collections::VecDeque<Info>>,
+}
+
+/// Decide which commits to traverse, and compare commits to their parents for [history simplification](Simplification).
+///
+/// It's implemented for all `FnMut(&oid) -> bool`, which never consider a commit to be the same as its parents.
+pub trait Filter {
+    /// Return `true` if the commit `id` should be included in the traversal, along with its parents.
+    fn keep(&mut self, id: &gix_hash::oid) -> bool;
+
+    /// Return `true` if the commit `id` is the same as its `parent` in all paths that matter, or the same as the empty tree
+    /// if `parent` is `None` as `id` is a root commit. This is what `git` calls *TREESAME*.
+    ///
+    /// The default implementation returns `false`, which turns off history simplification.
+    fn is_treesame(
+        &mut self,
+        id: &gix_hash::oid,
+        parent: Option<&gix_hash::oid>,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        _ = (id, parent);
+        Ok(false)
+    }
+}
+
+impl<F> Filter for F
+where
+    F: FnMut(&gix_hash::oid) -> bool,
+{
+    fn keep(&mut self, id: &gix_hash::oid) -> bool {
+        self(id)
+    }
+}
+
+/// Specify how to simplify the history based on commits being the same as their parents, as determined by
+/// [`Filter::is_treesame()`], similar to `git log -- <pathspec>`.
+///
+/// Commits that are the same as their parents are never returned, unless they are needed to keep the topology
+/// with [`Simplification::SimplifyMerges`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Simplification {
+    /// If a merge commit is the same as one of its parents, only that parent is traversed as it explains
+    /// the state of the merge commit, like `git` does by default.
+    #[default]
+    Default,
+    /// Traverse all parents, and only skip merge commits that are the same as all of their parents,
+    /// like `git log --full-history` without parent rewriting.
+    FullHistory,
+    /// Like [`Simplification::FullHistory`], but remove merge commits that don't contribute to the history
+    /// after rewriting their parents to the next commit that is returned, like `git log --simplify-merges`.
+    ///
+    /// Note that this requires the entire traversal to be performed before the first commit can be returned,
+    /// and that `git` implies [topological sorting](Sorting::Topological) in this mode.
+    SimplifyMerges,
 }
 
 /// Specify how to handle commit parents during traversal.
@@ -43,7 +98,7 @@ pub enum Sorting {
     /// ### Note
     ///
     /// This is not to be confused with `git log/rev-list --topo-order`, which is notably different from
-    /// as it avoids overlapping branches.
+    /// as it avoids overlapping branches, and which is available as [`Sorting::Topological`].
     #[default]
     BreadthFirst,
     /// Commits are sorted by their commit time in descending order, that is newest first.
@@ -67,6 +122,22 @@ pub enum Sorting {
         /// The amount of seconds since unix epoch, the same value obtained by any `gix_date::Time` structure and the way git counts time.
         seconds: gix_date::SecondsSinceUnixEpoch,
     },
+    /// Commits are sorted such that no parent is shown before all of its children, and commits of different lines
+    /// of history aren't intermixed, just like `git log/rev-list --topo-order`.
+    ///
+    /// In the *sample history* the order would be `8, 7, 4, 2, 6, 5, 3, 1`
+    ///
+    /// # Performance
+    ///
+    /// The walk is incremental if a [commit-graph](Ancestors::commit_graph()) is present, as its generation numbers allow
+    /// to know when all children of a commit were seen. Without it, the entire history is traversed before the first
+    /// commit can be returned.
+    Topological,
+    /// Like [`Sorting::Topological`], but among the commits whose children were all returned already, the one with
+    /// the newest author date is returned first, just like `git log/rev-list --author-date-order`.
+    ///
+    /// In the *sample history*, with author dates being the same as commit dates, the order would be `8, 7, 6, 5, 4, 3, 2, 1`.
+    AuthorDateTopological,
 }
 
 /// The collection of parent ids we saw as part of the iteration.
@@ -95,11 +166,13 @@ pub mod ancestors {
 
     use gix_date::SecondsSinceUnixEpoch;
     use gix_hash::{oid, ObjectId};
-    use gix_hashtable::HashSet;
+    use gix_hashtable::{HashMap, HashSet};
     use gix_object::{CommitRefIter, FindExt};
     use smallvec::SmallVec;
 
-    use crate::commit::{collect_parents, Ancestors, Either, Info, ParentIds, Parents, Sorting};
+    use crate::commit::{
+        collect_parents, Ancestors, Either, Filter, Info, ParentIds, Parents, Simplification, Sorting,
+    };
 
     /// The error is part of the item returned by the [Ancestors] iterator.
     #[derive(Debug, thiserror::Error)]
@@ -109,6 +182,11 @@ pub mod ancestors {
         Find(#[from] gix_object::find::existing_iter::Error),
         #[error(transparent)]
         ObjectDecode(#[from] gix_object::decode::Error),
+        #[error("Could not compare commit {id} to its parents for history simplification")]
+        IsTreesame {
+            id: ObjectId,
+            source: Box<dyn std::error::Error + Send + Sync + 'static>,
+        },
     }
 
     /// The state used and potentially shared by multiple graph traversals.
@@ -120,8 +198,74 @@ pub mod ancestors {
         seen: HashSet<ObjectId>,
         parents_buf: Vec<u8>,
         parent_ids: SmallVec<[(ObjectId, SecondsSinceUnixEpoch); 2]>,
+        hidden: HashSet<ObjectId>,
+        /// The tips that were hidden, which are still relevant for history simplification.
+        hidden_tips: HashSet<ObjectId>,
+        topo: Topo,
+    }
+
+    /// The state of the incremental topological walk, as done by `git` as well.
+    #[derive(Clone)]
+    struct Topo {
+        /// `true` if the tips were already prepared for the walk.
+        initialized: bool,
+        /// The amount of children that weren't returned yet, plus one, along with the generation of each commit.
+        indegree: HashMap<ObjectId, (u32, Generation)>,
+        /// Commits whose parents still need to be counted in `indegree`, highest generation first.
+        indegree_queue: gix_revwalk::PriorityQueue<Generation, ObjectId>,
+        /// The lowest generation `indegree` is known to be complete for.
+        min_generation: Generation,
+        /// Commits that are ready to be returned as all of their children were returned.
+        queue: gix_revwalk::PriorityQueue<(i64, i64), ObjectId>,
+        /// The amount of insertions into `queue`, to keep its order stable.
+        insertions: i64,
+        /// The simplification of each commit whose parents were counted, until it's returned.
+        simplified: HashMap<ObjectId, Simplified>,
+    }
+
+    /// The outcome of simplifying the history at a single commit.
+    #[derive(Clone)]
+    struct Simplified {
+        /// The parents to traverse.
+        parents: ParentIds,
+        /// If `true`, the commit is the same as its parents in all paths that matter, and isn't returned.
+        treesame: bool,
+        /// For each of the parents, `true` if the commit is the same as it. It's incomplete if only one parent was kept.
+        treesame_to: SmallVec<[bool; 2]>,
     }
 
+    impl Default for Topo {
+        fn default() -> Self {
+            Topo {
+                initialized: false,
+                indegree: Default::default(),
+                indegree_queue: gix_revwalk::PriorityQueue::new(),
+                min_generation: GENERATION_NUMBER_INFINITY,
+                queue: gix_revwalk::PriorityQueue::new(),
+                insertions: 0,
+                simplified: Default::default(),
+            }
+        }
+    }
+
+    impl Topo {
+        fn clear(&mut self) {
+            self.initialized = false;
+            self.indegree.clear();
+            self.indegree_queue.clear();
+            self.min_generation = GENERATION_NUMBER_INFINITY;
+            self.queue.clear();
+            self.insertions = 0;
+            self.simplified.clear();
+        }
+    }
+
+    /// A commit generation as stored in the commit-graph.
+    type Generation = u32;
+
+    /// The generation of commits that aren't in the commit-graph, like `git` does it.
+    const GENERATION_NUMBER_INFINITY: Generation = Generation::MAX;
+
     impl Default for State {
         fn default() -> Self {
             State {
@@ -131,6 +275,9 @@ pub mod ancestors {
                 seen: Default::default(),
                 parents_buf: vec![],
                 parent_ids: Default::default(),
+                hidden: Default::default(),
+                hidden_tips: Default::default(),
+                topo: Default::default(),
             }
         }
     }
@@ -141,6 +288,14 @@ pub mod ancestors {
             self.queue.clear();
             self.buf.clear();
             self.seen.clear();
+            self.hidden.clear();
+            self.hidden_tips.clear();
+            self.topo.clear();
+        }
+
+        /// Return `true` if `id` isn't hidden or is one of the hidden tips, which is what `git` calls a *relevant* commit.
+        fn is_relevant(&self, id: &oid) -> bool {
+            !self.hidden.contains(id) || self.hidden_tips.contains(id)
         }
     }
 
@@ -154,7 +309,7 @@ pub mod ancestors {
         pub fn sorting(mut self, sorting: Sorting) -> Result<Self, Error> {
             self.sorting = sorting;
             match self.sorting {
-                Sorting::BreadthFirst => {
+                Sorting::BreadthFirst | Sorting::Topological | Sorting::AuthorDateTopological => {
                     self.queue_to_vecdeque();
                 }
                 Sorting::ByCommitTimeNewestFirst | Sorting::ByCommitTimeNewestFirstCutoffOlderThan { .. } => {
@@ -178,6 +333,15 @@ pub mod ancestors {
             Ok(self)
         }
 
+        /// Set how to simplify the history, which requires a [`Filter`] that implements [`Filter::is_treesame()`] to have an effect.
+        ///
+        /// Note that the simplification happens according to the configured [sorting](Self::sorting()) and
+        /// [parents mode](Self::parents()).
+        pub fn simplification(mut self, simplification: Simplification) -> Self {
+            self.simplification = simplification;
+            self
+        }
+
         /// Change our commit parent handling mode to the given one.
         pub fn parents(mut self, mode: Parents) -> Self {
             self.parents = mode;
@@ -187,6 +351,17 @@ pub mod ancestors {
             self
         }
 
+        /// If `toggle` is `true`, return commits in reverse order, like `git rev-list --reverse`, so the last commit
+        /// of the configured [sorting](Self::sorting()) is returned first.
+        ///
+        /// Note that this requires the entire traversal to be performed before the first commit can be returned,
+        /// and that [`commit_iter()`](Self::commit_iter()) and [`commit_data()`](Self::commit_data()) don't refer
+        /// to the returned commit anymore.
+        pub fn reverse(mut self, toggle: bool) -> Self {
+            self.reverse = toggle;
+            self
+        }
+
         /// Set the commitgraph as `cache` to greatly accelerate any traversal.
         ///
         /// The cache will be used if possible, but we will fall-back without error to using the object
@@ -196,6 +371,147 @@ pub mod ancestors {
             self
         }
 
+        /// Hide the given `tips`, along with all commits reachable by them, so that they will not be returned
+        /// by the traversal, similar to `git rev-list ^<tip>`.
+        ///
+        /// To do that, all commits reachable from the tips to traverse and from the hidden `tips` are painted, newest first,
+        /// until only hidden commits are left that can't reach any of the commits that aren't hidden.
+        /// With a [commit-graph](Self::commit_graph()), generation numbers are used to know that for sure,
+        /// otherwise commit times are used which can lead to showing hidden commits in case of clock skew, just like in `git`.
+        ///
+        /// Note that the [commit-graph](Self::commit_graph()) and the [parents mode](Self::parents()) are used while painting,
+        /// and must thus be set before calling this method. Missing parents, as in shallow clones, are ignored.
+        pub fn hide(mut self, tips: impl IntoIterator<Item = impl Into<ObjectId>>) -> Result<Self, Error> {
+            let tips: Vec<ObjectId> = tips.into_iter().map(Into::into).collect();
+            if tips.is_empty() {
+                return Ok(self);
+            }
+            let mut painted = HashMap::<ObjectId, bool>::default();
+            let mut processed = HashMap::<ObjectId, bool>::default();
+            let mut queue = gix_revwalk::PriorityQueue::<i64, (ObjectId, bool)>::new();
+            let (mut interesting_in_queue, mut hidden_in_queue) = (0_usize, 0_usize);
+            let mut min_interesting_key = None;
+
+            let interesting: Vec<_> = {
+                let state = self.state.borrow();
+                state.next.iter().chain(state.queue.iter_unordered()).copied().collect()
+            };
+            for (id, is_hidden) in interesting
+                .into_iter()
+                .map(|id| (id, false))
+                .chain(tips.iter().map(|id| (*id, true)))
+            {
+                if painted.get(&id) == Some(&true) {
+                    continue;
+                }
+                painted.insert(id, is_hidden);
+                let key = self
+                    .paint_key(&id)?
+                    .ok_or(gix_object::find::existing_iter::Error::NotFound { oid: id })?;
+                queue.insert(key, (id, is_hidden));
+                if is_hidden {
+                    hidden_in_queue += 1;
+                } else {
+                    interesting_in_queue += 1;
+                }
+            }
+
+            while hidden_in_queue != 0 {
+                if interesting_in_queue == 0 {
+                    // Only hidden commits are left, and unless one of them could still reach an interesting commit,
+                    // we are done.
+                    match (queue.peek(), min_interesting_key) {
+                        (Some((key, _)), Some(min_key)) if *key >= min_key => {}
+                        _ => break,
+                    }
+                }
+                let Some((key, (id, queued_as_hidden))) = queue.pop() else {
+                    break;
+                };
+                if queued_as_hidden {
+                    hidden_in_queue -= 1;
+                } else {
+                    interesting_in_queue -= 1;
+                }
+                let is_hidden = painted[&id];
+                if processed.insert(id, is_hidden) == Some(is_hidden) {
+                    continue;
+                }
+                if !is_hidden {
+                    min_interesting_key = Some(min_interesting_key.map_or(key, |min_key: i64| min_key.min(key)));
+                }
+
+                for parent_id in self.parents_of(&id)? {
+                    match painted.get(&parent_id) {
+                        Some(true) => continue,
+                        Some(false) if !is_hidden => continue,
+                        Some(false) | None => {}
+                    }
+                    let Some(key) = self.paint_key(&parent_id)? else {
+                        continue;
+                    };
+                    painted.insert(parent_id, is_hidden);
+                    queue.insert(key, (parent_id, is_hidden));
+                    if is_hidden {
+                        hidden_in_queue += 1;
+                    } else {
+                        interesting_in_queue += 1;
+                    }
+                }
+            }
+
+            self.state.borrow_mut().hidden = painted
+                .into_iter()
+                .filter_map(|(id, is_hidden)| is_hidden.then_some(id))
+                .collect();
+            self.state.borrow_mut().hidden_tips = tips.into_iter().collect();
+            Ok(self)
+        }
+
+        /// Return the key by which to order `id` when painting hidden commits, or `None` if it doesn't exist.
+        ///
+        /// It's the generation number if there is a commit-graph, or the commit time otherwise.
+        fn paint_key(&mut self, id: &oid) -> Result<Option<i64>, Error> {
+            let state = self.state.borrow_mut();
+            if let Some(commit) = self.cache.as_ref().and_then(|cache| cache.commit_by_id(id)) {
+                return Ok(Some(commit.generation() as i64));
+            }
+            match self.objects.find_commit_iter(id, &mut state.parents_buf) {
+                // Commits outside of the commit-graph are newer than everything in it.
+                Ok(_commit) if self.cache.is_some() => Ok(Some(i64::MAX)),
+                Ok(commit) => Ok(Some(commit.committer()?.time.seconds)),
+                Err(gix_object::find::existing_iter::Error::NotFound { .. }) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        }
+
+        /// Return the parents of `id`, observing our parents mode.
+        fn parents_of(&mut self, id: &oid) -> Result<ParentIds, Error> {
+            let state = self.state.borrow_mut();
+            let mut parents = ParentIds::new();
+            match super::find(self.cache.as_ref(), &self.objects, id, &mut state.parents_buf)? {
+                Either::CachedCommit(commit) => {
+                    let cache = self.cache.as_ref().expect("cached commits come from the cache");
+                    for pos in commit.iter_parents() {
+                        let Ok(pos) = pos else { break };
+                        parents.push(cache.commit_at(pos).id().to_owned());
+                        if matches!(self.parents, Parents::First) {
+                            break;
+                        }
+                    }
+                }
+                Either::CommitRefIter(commit) => {
+                    for parent_id in commit.parent_ids() {
+                        parents.push(parent_id);
+                        if matches!(self.parents, Parents::First) {
+                            break;
+                        }
+                    }
+                }
+            }
+            Ok(parents)
+        }
+
         fn queue_to_vecdeque(&mut self) {
             let state = self.state.borrow_mut();
             state.next.extend(
@@ -247,10 +563,29 @@ pub mod ancestors {
         /// * `predicate` - indicate whether a given commit should be included in the result as well
         ///   as whether its parent commits should be traversed.
         pub fn filtered(
+            tips: impl IntoIterator<Item = impl Into<ObjectId>>,
+            state: StateMut,
+            find: Find,
+            predicate: Predicate,
+        ) -> Self {
+            Self::with_filter(tips, state, find, predicate)
+        }
+    }
+
+    /// Initialization
+    impl<Find, Predicate, StateMut> Ancestors<Find, Predicate, StateMut>
+    where
+        Find: gix_object::Find,
+        Predicate: Filter,
+        StateMut: BorrowMut<State>,
+    {
+        /// Create a new instance like [`filtered()`](Self::filtered()), but with a `filter` that can also
+        /// compare commits to their parents for [history simplification](Self::simplification()).
+        pub fn with_filter(
             tips: impl IntoIterator<Item = impl Into<ObjectId>>,
             mut state: StateMut,
             find: Find,
-            mut predicate: Predicate,
+            mut filter: Predicate,
         ) -> Self {
             let tips = tips.into_iter();
             {
@@ -259,7 +594,7 @@ pub mod ancestors {
                 state.next.reserve(tips.size_hint().0);
                 for tip in tips.map(Into::into) {
                     let was_inserted = state.seen.insert(tip);
-                    if was_inserted && predicate(&tip) {
+                    if was_inserted && filter.keep(&tip) {
                         state.next.push_back(tip);
                     }
                 }
@@ -267,10 +602,13 @@ pub mod ancestors {
             Self {
                 objects: find,
                 cache: None,
-                predicate,
+                predicate: filter,
                 state,
                 parents: Default::default(),
                 sorting: Default::default(),
+                simplification: Default::default(),
+                reverse: false,
+                buffered: None,
             }
         }
     }
@@ -293,23 +631,360 @@ pub mod ancestors {
     impl<Find, Predicate, StateMut> Iterator for Ancestors<Find, Predicate, StateMut>
     where
         Find: gix_object::Find,
-        Predicate: FnMut(&oid) -> bool,
+        Predicate: Filter,
         StateMut: BorrowMut<State>,
     {
         type Item = Result<Info, Error>;
 
         fn next(&mut self) -> Option<Self::Item> {
-            if matches!(self.parents, Parents::First) {
-                self.next_by_topology()
+            if self.reverse || self.simplification == Simplification::SimplifyMerges {
+                if self.buffered.is_none() {
+                    match self.collect_all() {
+                        Ok(commits) => self.buffered = Some(commits),
+                        Err(err) => {
+                            self.buffered = Some(VecDeque::new());
+                            return Some(Err(err));
+                        }
+                    }
+                }
+                return self.buffered.as_mut().and_then(VecDeque::pop_front).map(Ok);
+            }
+            loop {
+                match self.next_in_order()? {
+                    Ok((_info, simplified)) if simplified.treesame => continue,
+                    Ok((info, _simplified)) => return Some(Ok(info)),
+                    Err(err) => return Some(Err(err)),
+                }
+            }
+        }
+    }
+
+    impl<Find, Predicate, StateMut> Ancestors<Find, Predicate, StateMut>
+    where
+        Find: gix_object::Find,
+        Predicate: Filter,
+        StateMut: BorrowMut<State>,
+    {
+        fn next_in_order(&mut self) -> Option<Result<(Info, Simplified), Error>> {
+            match self.sorting {
+                Sorting::Topological | Sorting::AuthorDateTopological => self.next_topologically(),
+                _ if matches!(self.parents, Parents::First) => self.next_by_topology(),
+                Sorting::BreadthFirst => self.next_by_topology(),
+                Sorting::ByCommitTimeNewestFirst => self.next_by_commit_date(None),
+                Sorting::ByCommitTimeNewestFirstCutoffOlderThan { seconds } => self.next_by_commit_date(seconds.into()),
+            }
+        }
+
+        /// Perform the entire traversal and return all commits that are to be returned, in order.
+        fn collect_all(&mut self) -> Result<VecDeque<Info>, Error> {
+            let mut commits = Vec::new();
+            while let Some(res) = self.next_in_order() {
+                commits.push(res?);
+            }
+            let mut commits: VecDeque<_> = if self.simplification == Simplification::SimplifyMerges {
+                self.simplify_merges(commits)?
             } else {
-                match self.sorting {
-                    Sorting::BreadthFirst => self.next_by_topology(),
-                    Sorting::ByCommitTimeNewestFirst => self.next_by_commit_date(None),
-                    Sorting::ByCommitTimeNewestFirstCutoffOlderThan { seconds } => {
-                        self.next_by_commit_date(seconds.into())
+                commits
+                    .into_iter()
+                    .filter_map(|(info, simplified)| (!simplified.treesame).then_some(info))
+                    .collect()
+            };
+            if self.reverse {
+                commits.make_contiguous().reverse();
+            }
+            Ok(commits)
+        }
+
+        /// Simplify the history at commit `id` with the given `parents`, like `git` does in `try_to_simplify_commit()`.
+        ///
+        /// Hidden parents are irrelevant unless they are hidden tips, so the commit is only simplified to them
+        /// if there are no other parents.
+        fn simplify(&mut self, id: &oid, parents: &ParentIds) -> Result<Simplified, Error> {
+            if parents.is_empty() {
+                return Ok(Simplified {
+                    parents: ParentIds::new(),
+                    treesame: self.is_treesame(id, None)?,
+                    treesame_to: SmallVec::new(),
+                });
+            }
+
+            let mut treesame_to = SmallVec::new();
+            let (mut relevant_parents, mut relevant_change, mut irrelevant_change) = (0, false, false);
+            for parent_id in parents {
+                let is_relevant = self.state.borrow().is_relevant(parent_id);
+                if is_relevant {
+                    relevant_parents += 1;
+                }
+                let is_same = self.is_treesame(id, Some(parent_id))?;
+                treesame_to.push(is_same);
+                if is_same {
+                    if self.simplification == Simplification::Default && is_relevant {
+                        return Ok(Simplified {
+                            parents: std::iter::once(*parent_id).collect(),
+                            treesame: true,
+                            treesame_to,
+                        });
                     }
+                } else if is_relevant {
+                    relevant_change = true;
+                } else {
+                    irrelevant_change = true;
                 }
             }
+            Ok(Simplified {
+                parents: parents.clone(),
+                treesame: if relevant_parents > 0 {
+                    !relevant_change
+                } else {
+                    !irrelevant_change
+                },
+                treesame_to,
+            })
+        }
+
+        fn is_treesame(&mut self, id: &oid, parent: Option<&oid>) -> Result<bool, Error> {
+            self.predicate
+                .is_treesame(id, parent)
+                .map_err(|source| Error::IsTreesame {
+                    id: id.to_owned(),
+                    source,
+                })
+        }
+
+        /// Remove all `commits` that don't contribute to the history after rewriting their parents to the closest
+        /// commits that are kept, like `git` does in `simplify_merges()`, and return the remaining commits in order.
+        fn simplify_merges(&mut self, commits: Vec<(Info, Simplified)>) -> Result<VecDeque<Info>, Error> {
+            let first_parent_only = matches!(self.parents, Parents::First);
+            let mut nodes: HashMap<ObjectId, Simplified> = commits
+                .iter()
+                .map(|(info, simplified)| (info.id, simplified.clone()))
+                .collect();
+            // What each commit simplifies to, with all commits that we didn't traverse simplifying to themselves.
+            let mut simplified_to = HashMap::<ObjectId, ObjectId>::default();
+            let mut todo: VecDeque<_> = commits.iter().rev().map(|(info, _)| info.id).collect();
+            while let Some(id) = todo.pop_front() {
+                if simplified_to.contains_key(&id) {
+                    continue;
+                }
+                let Some(node) = nodes.get(&id) else {
+                    simplified_to.insert(id, id);
+                    continue;
+                };
+                if node.parents.is_empty() {
+                    simplified_to.insert(id, id);
+                    continue;
+                }
+                let pending: ParentIds = node
+                    .parents
+                    .iter()
+                    .filter(|parent_id| !simplified_to.contains_key(*parent_id))
+                    .copied()
+                    .collect();
+                if !pending.is_empty() {
+                    todo.extend(pending);
+                    todo.push_back(id);
+                    continue;
+                }
+
+                let mut node = nodes.remove(&id).expect("present");
+                for parent_id in node.parents.iter_mut() {
+                    *parent_id = simplified_to[parent_id];
+                }
+                let mut count = if first_parent_only {
+                    1
+                } else {
+                    remove_duplicate_parents(&mut node)
+                };
+                if count > 1 {
+                    let mut marked = self.mark_redundant_parents(&node.parents)?;
+                    for (parent_id, marked) in node.parents.iter().zip(marked.iter_mut()) {
+                        if nodes
+                            .get(parent_id)
+                            .map_or(false, |parent| parent.parents.is_empty() && parent.treesame)
+                        {
+                            *marked = true;
+                        }
+                    }
+                    if marked.iter().any(|marked| *marked) {
+                        leave_one_treesame_to_parent(&node, &mut marked);
+                    }
+                    if marked.iter().any(|marked| *marked) {
+                        count = self.remove_marked_parents(&id, &mut node, &marked)?;
+                    }
+                }
+
+                let target = match self.one_relevant_parent(&node.parents) {
+                    Some(parent_id) if count != 0 && node.treesame => {
+                        simplified_to.get(&parent_id).copied().unwrap_or(parent_id)
+                    }
+                    _ => id,
+                };
+                simplified_to.insert(id, target);
+                nodes.insert(id, node);
+            }
+
+            let state = self.state.borrow();
+            Ok(commits
+                .into_iter()
+                .filter(|(info, _)| simplified_to.get(&info.id) == Some(&info.id))
+                .filter(|(info, _)| {
+                    let node = &nodes[&info.id];
+                    !node.treesame
+                        || node
+                            .parents
+                            .iter()
+                            .filter(|parent_id| state.is_relevant(parent_id))
+                            .count()
+                            >= 2
+                })
+                .map(|(info, _)| info)
+                .collect())
+        }
+
+        /// Return a mark for each of `parents` that is reachable from another one of `parents`.
+        fn mark_redundant_parents(&mut self, parents: &ParentIds) -> Result<SmallVec<[bool; 2]>, Error> {
+            let mut marked: SmallVec<[bool; 2]> = parents.iter().map(|_| false).collect();
+            for (idx, parent_id) in parents.iter().enumerate() {
+                for (other_idx, other_id) in parents.iter().enumerate() {
+                    if idx != other_idx && !marked[other_idx] && self.is_ancestor(parent_id, other_id)? {
+                        marked[idx] = true;
+                        break;
+                    }
+                }
+            }
+            Ok(marked)
+        }
+
+        /// Remove all `marked` parents from `node`, updating whether commit `id` is the same as its remaining parents,
+        /// and return the amount of remaining parents.
+        fn remove_marked_parents(&mut self, id: &oid, node: &mut Simplified, marked: &[bool]) -> Result<usize, Error> {
+            let mut nth_parent = 0;
+            for marked in marked {
+                if *marked {
+                    node.parents.remove(nth_parent);
+                    self.compact_treesame(id, node, nth_parent)?;
+                } else {
+                    nth_parent += 1;
+                }
+            }
+            if nth_parent != marked.len() && !node.treesame && node.parents.len() > 1 {
+                let state = self.state.borrow();
+                let (mut relevant_parents, mut relevant_change, mut irrelevant_change) = (0, false, false);
+                for (parent_id, is_same) in node.parents.iter().zip(node.treesame_to.iter()) {
+                    if state.is_relevant(parent_id) {
+                        relevant_change |= !is_same;
+                        relevant_parents += 1;
+                    } else {
+                        irrelevant_change |= !is_same;
+                    }
+                }
+                node.treesame = if relevant_parents > 0 {
+                    !relevant_change
+                } else {
+                    !irrelevant_change
+                };
+            }
+            Ok(nth_parent)
+        }
+
+        /// Forget whether `node` of commit `id` is the same as its `nth_parent` which was just removed.
+        fn compact_treesame(&mut self, id: &oid, node: &mut Simplified, nth_parent: usize) -> Result<(), Error> {
+            if node.parents.is_empty() {
+                node.treesame_to.clear();
+                node.treesame = self.is_treesame(id, None)?;
+                return Ok(());
+            }
+            compact_treesame(node, nth_parent);
+            Ok(())
+        }
+
+        /// Return the only parent of `parents` that isn't hidden, or the first parent if there is only one
+        /// or if only first parents are traversed.
+        fn one_relevant_parent(&self, parents: &ParentIds) -> Option<ObjectId> {
+            if matches!(self.parents, Parents::First) || parents.len() == 1 {
+                return parents.first().copied();
+            }
+            let state = self.state.borrow();
+            let mut relevant = parents.iter().filter(|parent_id| state.is_relevant(parent_id));
+            match (relevant.next(), relevant.next()) {
+                (Some(parent_id), None) => Some(*parent_id),
+                _ => None,
+            }
+        }
+
+        /// Return `true` if `ancestor` is reachable from `id`, using generation numbers to stop early if possible.
+        fn is_ancestor(&mut self, ancestor: &oid, id: &oid) -> Result<bool, Error> {
+            let min_generation = match self.generation_and_time(ancestor)? {
+                Some((generation, _commit_time)) if generation != GENERATION_NUMBER_INFINITY => Some(generation),
+                _ => None,
+            };
+            let mut seen = HashSet::<ObjectId>::default();
+            let mut queue = VecDeque::from([id.to_owned()]);
+            while let Some(id) = queue.pop_front() {
+                if id == ancestor {
+                    return Ok(true);
+                }
+                if let Some(min_generation) = min_generation {
+                    match self.generation_and_time(&id)? {
+                        Some((generation, _commit_time)) if generation <= min_generation => continue,
+                        None => continue,
+                        Some(_) => {}
+                    }
+                }
+                let parents = match self.parents_of(&id) {
+                    Ok(parents) => parents,
+                    Err(Error::Find(gix_object::find::existing_iter::Error::NotFound { .. })) => continue,
+                    Err(err) => return Err(err),
+                };
+                for parent_id in parents {
+                    if seen.insert(parent_id) {
+                        queue.push_back(parent_id);
+                    }
+                }
+            }
+            Ok(false)
+        }
+    }
+
+    /// Remove duplicates from the parents of `node`, and return the amount of remaining parents.
+    fn remove_duplicate_parents(node: &mut Simplified) -> usize {
+        let mut nth_parent = 0;
+        while nth_parent < node.parents.len() {
+            if node.parents[..nth_parent].contains(&node.parents[nth_parent]) {
+                node.parents.remove(nth_parent);
+                compact_treesame(node, nth_parent);
+            } else {
+                nth_parent += 1;
+            }
+        }
+        node.parents.len()
+    }
+
+    /// Forget whether `node` is the same as its `nth_parent`, which was just removed from it.
+    fn compact_treesame(node: &mut Simplified, nth_parent: usize) {
+        if nth_parent < node.treesame_to.len() {
+            node.treesame_to.remove(nth_parent);
+        }
+        if node.parents.len() == 1 {
+            node.treesame = node.treesame_to.first().copied().unwrap_or(false);
+        }
+    }
+
+    /// If the commit of `node` is the same as only `marked` parents, keep the first of these
+    /// so it's not lost from the history.
+    fn leave_one_treesame_to_parent(node: &Simplified, marked: &mut [bool]) {
+        let mut first_marked = None;
+        for (idx, is_same) in node.treesame_to.iter().enumerate() {
+            if !is_same {
+                continue;
+            }
+            if !marked[idx] {
+                return;
+            }
+            first_marked.get_or_insert(idx);
+        }
+        if let Some(idx) = first_marked {
+            marked[idx] = false;
         }
     }
 
@@ -327,70 +1002,83 @@ pub mod ancestors {
     impl<Find, Predicate, StateMut> Ancestors<Find, Predicate, StateMut>
     where
         Find: gix_object::Find,
-        Predicate: FnMut(&oid) -> bool,
+        Predicate: Filter,
         StateMut: BorrowMut<State>,
     {
         fn next_by_commit_date(
             &mut self,
             cutoff_older_than: Option<SecondsSinceUnixEpoch>,
-        ) -> Option<Result<Info, Error>> {
+        ) -> Option<Result<(Info, Simplified), Error>> {
             let state = self.state.borrow_mut();
 
-            let (commit_time, oid) = state.queue.pop()?;
+            let (commit_time, oid) = loop {
+                let (commit_time, oid) = state.queue.pop()?;
+                if !state.hidden.contains(&oid) {
+                    break (commit_time, oid);
+                }
+            };
             let mut parents: ParentIds = Default::default();
-            match super::find(self.cache.as_ref(), &self.objects, &oid, &mut state.buf) {
+            let is_cached = match super::find(self.cache.as_ref(), &self.objects, &oid, &mut state.buf) {
                 Ok(Either::CachedCommit(commit)) => {
                     if !collect_parents(&mut state.parent_ids, self.cache.as_ref(), commit.iter_parents()) {
                         // drop corrupt caches and try again with ODB
                         self.cache = None;
                         return self.next_by_commit_date(cutoff_older_than);
                     }
-                    for (id, parent_commit_time) in state.parent_ids.drain(..) {
-                        parents.push(id);
-                        let was_inserted = state.seen.insert(id);
-                        if !(was_inserted && (self.predicate)(&id)) {
-                            continue;
-                        }
-
-                        match cutoff_older_than {
-                            Some(cutoff_older_than) if parent_commit_time < cutoff_older_than => continue,
-                            Some(_) | None => state.queue.insert(parent_commit_time, id),
-                        }
-                    }
+                    parents.extend(state.parent_ids.iter().map(|(id, _commit_time)| *id));
+                    true
                 }
                 Ok(Either::CommitRefIter(commit_iter)) => {
                     for token in commit_iter {
                         match token {
                             Ok(gix_object::commit::ref_iter::Token::Tree { .. }) => continue,
-                            Ok(gix_object::commit::ref_iter::Token::Parent { id }) => {
-                                parents.push(id);
-                                let was_inserted = state.seen.insert(id);
-                                if !(was_inserted && (self.predicate)(&id)) {
-                                    continue;
-                                }
-
-                                let parent = self.objects.find_commit_iter(id.as_ref(), &mut state.parents_buf).ok();
-                                let parent_commit_time = parent
-                                    .and_then(|parent| parent.committer().ok().map(|committer| committer.time.seconds))
-                                    .unwrap_or_default();
-
-                                match cutoff_older_than {
-                                    Some(cutoff_older_than) if parent_commit_time < cutoff_older_than => continue,
-                                    Some(_) | None => state.queue.insert(parent_commit_time, id),
-                                }
-                            }
+                            Ok(gix_object::commit::ref_iter::Token::Parent { id }) => parents.push(id),
                             Ok(_unused_token) => break,
                             Err(err) => return Some(Err(err.into())),
                         }
                     }
+                    false
                 }
                 Err(err) => return Some(Err(err.into())),
+            };
+
+            let simplified = match self.simplify(&oid, &parents) {
+                Ok(simplified) => simplified,
+                Err(err) => return Some(Err(err)),
+            };
+            let state = self.state.borrow_mut();
+            for id in simplified.parents.iter().copied() {
+                let was_inserted = state.seen.insert(id);
+                if !(was_inserted && !state.hidden.contains(&id) && self.predicate.keep(&id)) {
+                    continue;
+                }
+
+                let parent_commit_time = if is_cached {
+                    state
+                        .parent_ids
+                        .iter()
+                        .find_map(|(parent_id, commit_time)| (*parent_id == id).then_some(*commit_time))
+                        .expect("simplified parents are a subset of all parents")
+                } else {
+                    let parent = self.objects.find_commit_iter(id.as_ref(), &mut state.parents_buf).ok();
+                    parent
+                        .and_then(|parent| parent.committer().ok().map(|committer| committer.time.seconds))
+                        .unwrap_or_default()
+                };
+
+                match cutoff_older_than {
+                    Some(cutoff_older_than) if parent_commit_time < cutoff_older_than => continue,
+                    Some(_) | None => state.queue.insert(parent_commit_time, id),
+                }
             }
-            Some(Ok(Info {
-                id: oid,
-                parent_ids: parents,
-                commit_time: Some(commit_time),
-            }))
+            Some(Ok((
+                Info {
+                    id: oid,
+                    parent_ids: parents,
+                    commit_time: Some(commit_time),
+                },
+                simplified,
+            )))
         }
     }
 
@@ -398,12 +1086,17 @@ pub mod ancestors {
     impl<Find, Predicate, StateMut> Ancestors<Find, Predicate, StateMut>
     where
         Find: gix_object::Find,
-        Predicate: FnMut(&oid) -> bool,
+        Predicate: Filter,
         StateMut: BorrowMut<State>,
     {
-        fn next_by_topology(&mut self) -> Option<Result<Info, Error>> {
+        fn next_by_topology(&mut self) -> Option<Result<(Info, Simplified), Error>> {
             let state = self.state.borrow_mut();
-            let oid = state.next.pop_front()?;
+            let oid = loop {
+                let oid = state.next.pop_front()?;
+                if !state.hidden.contains(&oid) {
+                    break oid;
+                }
+            };
             let mut parents: ParentIds = Default::default();
             match super::find(self.cache.as_ref(), &self.objects, &oid, &mut state.buf) {
                 Ok(Either::CachedCommit(commit)) => {
@@ -415,10 +1108,6 @@ pub mod ancestors {
 
                     for (id, _commit_time) in state.parent_ids.drain(..) {
                         parents.push(id);
-                        let was_inserted = state.seen.insert(id);
-                        if was_inserted && (self.predicate)(&id) {
-                            state.next.push_back(id);
-                        }
                         if matches!(self.parents, Parents::First) {
                             break;
                         }
@@ -430,10 +1119,6 @@ pub mod ancestors {
                             Ok(gix_object::commit::ref_iter::Token::Tree { .. }) => continue,
                             Ok(gix_object::commit::ref_iter::Token::Parent { id }) => {
                                 parents.push(id);
-                                let was_inserted = state.seen.insert(id);
-                                if was_inserted && (self.predicate)(&id) {
-                                    state.next.push_back(id);
-                                }
                                 if matches!(self.parents, Parents::First) {
                                     break;
                                 }
@@ -445,11 +1130,218 @@ pub mod ancestors {
                 }
                 Err(err) => return Some(Err(err.into())),
             }
-            Some(Ok(Info {
-                id: oid,
-                parent_ids: parents,
-                commit_time: None,
-            }))
+
+            let simplified = match self.simplify(&oid, &parents) {
+                Ok(simplified) => simplified,
+                Err(err) => return Some(Err(err)),
+            };
+            let state = self.state.borrow_mut();
+            for id in simplified.parents.iter().copied() {
+                let was_inserted = state.seen.insert(id);
+                if was_inserted && !state.hidden.contains(&id) && self.predicate.keep(&id) {
+                    state.next.push_back(id);
+                }
+            }
+            Some(Ok((
+                Info {
+                    id: oid,
+                    parent_ids: parents,
+                    commit_time: None,
+                },
+                simplified,
+            )))
+        }
+    }
+
+    /// Utilities
+    impl<Find, Predicate, StateMut> Ancestors<Find, Predicate, StateMut>
+    where
+        Find: gix_object::Find,
+        Predicate: Filter,
+        StateMut: BorrowMut<State>,
+    {
+        /// Return the next commit of an incremental topological walk, which is the one `git` uses as well.
+        ///
+        /// Each commit is assigned an indegree, the amount of its children plus one, and it's returned once all
+        /// of its children were returned. Indegrees are only computed down to the lowest generation of all commits
+        /// that were seen so far, which is what makes the walk incremental.
+        fn next_topologically(&mut self) -> Option<Result<(Info, Simplified), Error>> {
+            if !self.state.borrow().topo.initialized {
+                self.state.borrow_mut().topo.initialized = true;
+                if let Err(err) = self.init_topo() {
+                    return Some(Err(err));
+                }
+            }
+            let state = self.state.borrow_mut();
+            let (_key, oid) = state.topo.queue.pop()?;
+
+            let mut parents: ParentIds = Default::default();
+            let commit_time = match super::find(self.cache.as_ref(), &self.objects, &oid, &mut state.buf) {
+                Ok(Either::CachedCommit(commit)) => {
+                    let cache = self.cache.as_ref().expect("cached commits come from the cache");
+                    for pos in commit.iter_parents() {
+                        let Ok(pos) = pos else { break };
+                        parents.push(cache.commit_at(pos).id().to_owned());
+                    }
+                    commit.committer_timestamp() as SecondsSinceUnixEpoch
+                }
+                Ok(Either::CommitRefIter(commit_iter)) => {
+                    parents.extend(commit_iter.parent_ids());
+                    match commit_iter.committer() {
+                        Ok(committer) => committer.time.seconds,
+                        Err(err) => return Some(Err(err.into())),
+                    }
+                }
+                Err(err) => return Some(Err(err.into())),
+            };
+            if matches!(self.parents, Parents::First) {
+                parents.truncate(1);
+            }
+
+            let simplified = match self.state.borrow_mut().topo.simplified.remove(&oid) {
+                Some(simplified) => simplified,
+                None => match self.simplify(&oid, &parents) {
+                    Ok(simplified) => simplified,
+                    Err(err) => return Some(Err(err)),
+                },
+            };
+            for parent_id in &simplified.parents {
+                let state = self.state.borrow_mut();
+                let Some((_indegree, generation)) = state.topo.indegree.get(parent_id).copied() else {
+                    continue;
+                };
+                if generation < state.topo.min_generation {
+                    state.topo.min_generation = generation;
+                    if let Err(err) = self.compute_indegrees_to_depth(generation) {
+                        return Some(Err(err));
+                    }
+                }
+                let state = self.state.borrow_mut();
+                let indegree = &mut state.topo.indegree.get_mut(parent_id).expect("present").0;
+                *indegree -= 1;
+                if *indegree == 1 {
+                    if let Err(err) = self.topo_enqueue(*parent_id) {
+                        return Some(Err(err));
+                    }
+                }
+            }
+            Some(Ok((
+                Info {
+                    id: oid,
+                    parent_ids: parents,
+                    commit_time: Some(commit_time),
+                },
+                simplified,
+            )))
+        }
+
+        /// Prepare all tips for the topological walk, and queue those which aren't reachable from other tips.
+        fn init_topo(&mut self) -> Result<(), Error> {
+            let state = self.state.borrow_mut();
+            let tips: Vec<_> = state.next.drain(..).filter(|id| !state.hidden.contains(id)).collect();
+            let mut tips_with_time = Vec::with_capacity(tips.len());
+            for id in tips {
+                let (generation, commit_time) = self
+                    .generation_and_time(&id)?
+                    .ok_or(gix_object::find::existing_iter::Error::NotFound { oid: id })?;
+                let topo = &mut self.state.borrow_mut().topo;
+                topo.indegree.insert(id, (1, generation));
+                topo.indegree_queue.insert(generation, id);
+                topo.min_generation = topo.min_generation.min(generation);
+                tips_with_time.push((id, commit_time));
+            }
+            // Like `git`, start with the most recent tip.
+            tips_with_time.sort_by_key(|(_id, commit_time)| std::cmp::Reverse(*commit_time));
+
+            let min_generation = self.state.borrow().topo.min_generation;
+            self.compute_indegrees_to_depth(min_generation)?;
+            let tips = tips_with_time.into_iter().map(|(id, _)| id);
+            let tips: Vec<_> = if matches!(self.sorting, Sorting::Topological) {
+                // The queue is a stack, so the first tip needs to be pushed last.
+                tips.rev().collect()
+            } else {
+                tips.collect()
+            };
+            for id in tips {
+                if self.state.borrow().topo.indegree[&id].0 == 1 {
+                    self.topo_enqueue(id)?;
+                }
+            }
+            Ok(())
+        }
+
+        /// Count the children of all commits whose children have a generation of at least `min_generation`.
+        fn compute_indegrees_to_depth(&mut self, min_generation: Generation) -> Result<(), Error> {
+            loop {
+                let id = {
+                    let queue = &mut self.state.borrow_mut().topo.indegree_queue;
+                    match queue.peek() {
+                        Some((generation, _id)) if *generation >= min_generation => queue.pop().expect("just peeked").1,
+                        _ => break,
+                    }
+                };
+                let parents = self.parents_of(&id)?;
+                let simplified = self.simplify(&id, &parents)?;
+                for parent_id in simplified.parents.iter().copied() {
+                    let state = self.state.borrow_mut();
+                    if let Some((indegree, _generation)) = state.topo.indegree.get_mut(&parent_id) {
+                        *indegree += 1;
+                        continue;
+                    }
+                    if !state.seen.insert(parent_id) || state.hidden.contains(&parent_id) {
+                        continue;
+                    }
+                    let Some((generation, _commit_time)) = self.generation_and_time(&parent_id)? else {
+                        continue;
+                    };
+                    if !self.predicate.keep(&parent_id) {
+                        continue;
+                    }
+                    let topo = &mut self.state.borrow_mut().topo;
+                    topo.indegree.insert(parent_id, (2, generation));
+                    topo.indegree_queue.insert(generation, parent_id);
+                }
+                self.state.borrow_mut().topo.simplified.insert(id, simplified);
+            }
+            Ok(())
+        }
+
+        /// Put `id` into the queue of commits that are ready to be returned.
+        fn topo_enqueue(&mut self, id: ObjectId) -> Result<(), Error> {
+            let key = match self.sorting {
+                Sorting::AuthorDateTopological => {
+                    let state = self.state.borrow_mut();
+                    let author_time = self
+                        .objects
+                        .find_commit_iter(&id, &mut state.parents_buf)?
+                        .author()?
+                        .time
+                        .seconds;
+                    // Newest first, and the earliest insertion first among those with the same date.
+                    (author_time, -state.topo.insertions)
+                }
+                _ => (0, self.state.borrow().topo.insertions),
+            };
+            let topo = &mut self.state.borrow_mut().topo;
+            topo.insertions += 1;
+            topo.queue.insert(key, id);
+            Ok(())
+        }
+
+        /// Return the generation and commit time of `id`, or `None` if it doesn't exist.
+        fn generation_and_time(&mut self, id: &oid) -> Result<Option<(Generation, SecondsSinceUnixEpoch)>, Error> {
+            let state = self.state.borrow_mut();
+            if let Some(commit) = self.cache.as_ref().and_then(|cache| cache.commit_by_id(id)) {
+                return Ok(Some((
+                    commit.generation(),
+                    commit.committer_timestamp() as SecondsSinceUnixEpoch,
+                )));
+            }
+            match self.objects.find_commit_iter(id, &mut state.parents_buf) {
+                Ok(commit) => Ok(Some((GENERATION_NUMBER_INFINITY, commit.committer()?.time.seconds))),
+                Err(gix_object::find::existing_iter::Error::NotFound { .. }) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
         }
     }
 }
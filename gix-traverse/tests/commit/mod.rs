@@ -427,4 +427,213 @@ mod ancestor {
             .check()
         }
     }
+
+    mod hidden {
+        use gix_traverse::commit::{ancestors, Ancestors, Parents, Sorting};
+
+        #[test]
+        fn baseline() -> crate::Result {
+            let dir = gix_testtools::scripted_fixture_read_only_standalone("make_repo_for_hidden.sh")?;
+            let store = gix_odb::at(dir.join(".git").join("objects"))?;
+            let baseline = std::fs::read_to_string(dir.join("baseline.git"))?;
+            let mut cases = 0;
+            for record in baseline.split("\n\n").filter(|record| !record.trim().is_empty()) {
+                let mut lines = record.lines();
+                let args = lines.next().expect("arguments");
+                let mut parents = Parents::All;
+                let (mut tips, mut hidden) = (Vec::new(), Vec::new());
+                for arg in lines.next().expect("resolved arguments").split(' ') {
+                    match arg {
+                        "--first-parent" => parents = Parents::First,
+                        _ => match arg.strip_prefix('^') {
+                            Some(hex) => hidden.push(crate::hex_to_id(hex)),
+                            None => tips.push(crate::hex_to_id(arg)),
+                        },
+                    }
+                }
+                let expected: Vec<_> = lines.map(crate::hex_to_id).collect();
+
+                for use_commitgraph in [false, true] {
+                    for sorting in [Sorting::ByCommitTimeNewestFirst, Sorting::BreadthFirst] {
+                        let mut actual = Ancestors::new(tips.clone(), ancestors::State::default(), &store)
+                            .sorting(sorting)?
+                            .parents(parents)
+                            .commit_graph(
+                                use_commitgraph
+                                    .then(|| gix_commitgraph::at(store.store_ref().path().join("info")))
+                                    .transpose()?,
+                            )
+                            .hide(hidden.clone())?
+                            .map(|res| res.map(|info| info.id))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        let mut expected = expected.clone();
+                        if matches!(sorting, Sorting::BreadthFirst) {
+                            actual.sort();
+                            expected.sort();
+                        }
+                        assert_eq!(
+                            actual, expected,
+                            "git rev-list {args}: {sorting:?}, commit-graph = {use_commitgraph}"
+                        );
+                    }
+                }
+                cases += 1;
+            }
+            assert_eq!(cases, 9, "all cases were parsed");
+            Ok(())
+        }
+    }
+
+    mod topo {
+        use gix_traverse::commit::{ancestors, Ancestors, Parents, Sorting};
+
+        #[test]
+        fn baseline() -> crate::Result {
+            let dir = gix_testtools::scripted_fixture_read_only_standalone("make_repo_for_topo.sh")?;
+            let store = gix_odb::at(dir.join(".git").join("objects"))?;
+            let baseline = std::fs::read_to_string(dir.join("baseline.git"))?;
+            let mut cases = 0;
+            for record in baseline.split("\n\n").filter(|record| !record.trim().is_empty()) {
+                let mut lines = record.lines();
+                let args = lines.next().expect("arguments");
+                let (mut sorting, mut reverse, mut parents) = (None, false, Parents::All);
+                let (mut tips, mut hidden) = (Vec::new(), Vec::new());
+                for arg in lines.next().expect("resolved arguments").split(' ') {
+                    match arg {
+                        "--topo-order" => sorting = Some(Sorting::Topological),
+                        "--author-date-order" => sorting = Some(Sorting::AuthorDateTopological),
+                        "--reverse" => reverse = true,
+                        "--first-parent" => parents = Parents::First,
+                        _ => match arg.strip_prefix('^') {
+                            Some(hex) => hidden.push(crate::hex_to_id(hex)),
+                            None => tips.push(crate::hex_to_id(arg)),
+                        },
+                    }
+                }
+                let sorting = sorting.expect("every case sorts");
+                let expected: Vec<_> = lines.map(crate::hex_to_id).collect();
+
+                for use_commitgraph in [false, true] {
+                    let actual = Ancestors::new(tips.clone(), ancestors::State::default(), &store)
+                        .sorting(sorting)?
+                        .parents(parents)
+                        .commit_graph(
+                            use_commitgraph
+                                .then(|| gix_commitgraph::at(store.store_ref().path().join("info")))
+                                .transpose()?,
+                        )
+                        .hide(hidden.clone())?
+                        .reverse(reverse)
+                        .map(|res| res.map(|info| info.id))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    assert_eq!(
+                        actual, expected,
+                        "git rev-list {args}: commit-graph = {use_commitgraph}"
+                    );
+                }
+                cases += 1;
+            }
+            assert_eq!(cases, 12, "all cases were parsed");
+            Ok(())
+        }
+    }
+
+    mod simplification {
+        use gix_hash::{oid, ObjectId};
+        use gix_object::FindExt;
+        use gix_traverse::commit::{ancestors, Ancestors, Filter, Simplification, Sorting};
+
+        /// Consider commits to be the same if they have the same `file`.
+        struct ByFile<'a> {
+            store: &'a gix_odb::Handle,
+            buf: Vec<u8>,
+        }
+
+        impl ByFile<'_> {
+            fn file_at(
+                &mut self,
+                commit: Option<&oid>,
+            ) -> Result<Option<ObjectId>, gix_object::find::existing_object::Error> {
+                let Some(commit) = commit else { return Ok(None) };
+                let tree = self.store.find_commit(commit, &mut self.buf)?.tree();
+                let tree = self.store.find_tree(&tree, &mut self.buf)?;
+                Ok(tree
+                    .entries
+                    .iter()
+                    .find(|entry| entry.filename == "file")
+                    .map(|entry| entry.oid.to_owned()))
+            }
+        }
+
+        impl Filter for ByFile<'_> {
+            fn keep(&mut self, _id: &oid) -> bool {
+                true
+            }
+
+            fn is_treesame(
+                &mut self,
+                id: &oid,
+                parent: Option<&oid>,
+            ) -> Result<bool, Box<dyn std::error::Error + Send + Sync + 'static>> {
+                Ok(self.file_at(Some(id))? == self.file_at(parent)?)
+            }
+        }
+
+        #[test]
+        fn baseline() -> crate::Result {
+            let dir = gix_testtools::scripted_fixture_read_only_standalone("make_repo_for_path_simplification.sh")?;
+            let store = gix_odb::at(dir.join(".git").join("objects"))?;
+            let baseline = std::fs::read_to_string(dir.join("baseline.git"))?;
+            let mut cases = 0;
+            for record in baseline.split("\n\n").filter(|record| !record.trim().is_empty()) {
+                let mut lines = record.lines();
+                let args = lines.next().expect("arguments");
+                let (mut sorting, mut simplification) = (None, Simplification::Default);
+                let (mut tips, mut hidden) = (Vec::new(), Vec::new());
+                for arg in lines.next().expect("resolved arguments").split(' ') {
+                    match arg {
+                        "--topo-order" => sorting = Some(Sorting::Topological),
+                        "--date-order" => sorting = Some(Sorting::ByCommitTimeNewestFirst),
+                        "--full-history" => simplification = Simplification::FullHistory,
+                        "--simplify-merges" => simplification = Simplification::SimplifyMerges,
+                        _ => match arg.strip_prefix('^') {
+                            Some(hex) => hidden.push(crate::hex_to_id(hex)),
+                            None => tips.push(crate::hex_to_id(arg)),
+                        },
+                    }
+                }
+                let sorting = sorting.expect("every case sorts");
+                let expected: Vec<_> = lines.map(crate::hex_to_id).collect();
+
+                for use_commitgraph in [false, true] {
+                    let actual = Ancestors::with_filter(
+                        tips.clone(),
+                        ancestors::State::default(),
+                        &store,
+                        ByFile {
+                            store: &store,
+                            buf: Vec::new(),
+                        },
+                    )
+                    .sorting(sorting)?
+                    .simplification(simplification)
+                    .commit_graph(
+                        use_commitgraph
+                            .then(|| gix_commitgraph::at(store.store_ref().path().join("info")))
+                            .transpose()?,
+                    )
+                    .hide(hidden.clone())?
+                    .map(|res| res.map(|info| info.id))
+                    .collect::<Result<Vec<_>, _>>()?;
+                    assert_eq!(
+                        actual, expected,
+                        "git rev-list {args} -- file: commit-graph = {use_commitgraph}"
+                    );
+                }
+                cases += 1;
+            }
+            assert_eq!(cases, 12, "all cases were parsed");
+            Ok(())
+        }
+    }
 }
@@ -0,0 +1,61 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Write the arguments, the arguments with resolved revisions and the output of `git rev-list --date-order <args>`
+# into `baseline.git`, with each record separated by an empty line.
+function baseline() {
+  {
+    echo "$@"
+    echo $(git rev-parse "$@")
+    git rev-list --date-order "$@"
+    echo
+  } >> baseline.git
+}
+
+seconds=0
+function commit() {
+  seconds=$((seconds + 1))
+  GIT_COMMITTER_DATE="2000-01-01 00:00:$(printf %02d $seconds) +0000" git commit -q --allow-empty -m "$1"
+  git tag "$1"
+}
+
+function merge() {
+  seconds=$((seconds + 1))
+  GIT_COMMITTER_DATE="2000-01-01 00:00:$(printf %02d $seconds) +0000" git merge -q --no-edit -m "$1" "$2"
+  git tag "$1"
+}
+
+git init -q
+git config merge.ff false
+
+# c1---c2---c3---m1---c4
+#  \    \        /
+#   \    d1     /
+#    b1---b2---´
+git checkout -q -b main
+commit c1
+git checkout -q -b branch c1
+commit b1
+git checkout -q main
+commit c2
+git checkout -q -b other c2
+commit d1
+git checkout -q branch
+commit b2
+git checkout -q main
+commit c3
+merge m1 b2
+commit c4
+
+baseline c4 ^b2
+baseline c4 ^d1
+baseline c4 ^c3
+baseline d1 b2 ^c1
+baseline c4 ^c4
+baseline b2 ^c4
+baseline c4 d1 ^m1
+baseline c4 ^b1 ^d1
+baseline --first-parent c4 ^b2
+
+git commit-graph write --no-progress --reachable
+git repack -adq
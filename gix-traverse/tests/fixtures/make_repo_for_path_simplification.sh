@@ -0,0 +1,80 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Write the arguments, the arguments with resolved revisions and the output of `git rev-list <args> -- file`
+# into `baseline.git`, with each record separated by an empty line.
+function baseline() {
+  {
+    echo "$@"
+    echo $(git rev-parse "$@")
+    git rev-list "$@" -- file
+    echo
+  } >> baseline.git
+}
+
+seconds=0
+function tick() {
+  seconds=$((seconds + 1))
+  export GIT_COMMITTER_DATE="2000-01-01 00:00:$(printf %02d $seconds) +0000"
+  export GIT_AUTHOR_DATE="$GIT_COMMITTER_DATE"
+}
+
+function commit() {
+  tick
+  git add -A
+  git commit -q --allow-empty -m "$1"
+  git tag "$1"
+}
+
+function merge() {
+  local name=$1
+  shift
+  tick
+  git merge -q --no-edit -m "$name" "$@"
+  git tag "$name"
+}
+
+git init -q
+git config merge.ff false
+
+# Only commits marked with `*` change `file`, r1 is an unrelated root commit without it.
+#
+#       b1*--b2   s1        x1*
+#      /      \  /  \      /   \
+#   *c1---c2---m1----m2---m3----m4---c3*
+#                                  /
+#                                r1
+git checkout -q -b main
+echo 1 >file && commit c1
+git checkout -q -b branch c1
+echo 2 >file && commit b1
+echo 2 >other && commit b2
+git checkout -q main
+echo 1 >other && commit c2
+merge m1 -X theirs b2
+git checkout -q -b side
+echo 3 >other && commit s1
+git checkout -q main
+merge m2 side
+git checkout -q -b ours
+echo 3 >file && commit x1
+git checkout -q main
+merge m3 -s ours x1
+git checkout -q --orphan root
+git rm -q -rf .
+echo 1 >unrelated && commit r1
+git checkout -q main
+merge m4 --allow-unrelated-histories r1
+echo 4 >file && commit c3
+
+for mode in "" --full-history --simplify-merges; do
+  baseline --topo-order $mode c3
+  baseline --topo-order $mode m4 ^b2
+  baseline --topo-order $mode m2 x1
+done
+baseline --date-order c3
+baseline --date-order --full-history c3
+baseline --date-order --full-history m3 ^c1
+
+git commit-graph write --no-progress --reachable
+git repack -adq
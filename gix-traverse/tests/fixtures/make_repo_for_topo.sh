@@ -0,0 +1,84 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Write the arguments, the arguments with resolved revisions and the output of `git rev-list <args>`
+# into `baseline.git`, with each record separated by an empty line.
+function baseline() {
+  {
+    echo "$@"
+    echo $(git rev-parse "$@")
+    git rev-list "$@"
+    echo
+  } >> baseline.git
+}
+
+# Commit with increasing commit dates, and author dates that are older for commits on `branch`.
+seconds=0
+function commit() {
+  seconds=$((seconds + 1))
+  local author_seconds=$seconds
+  if [ "$(git branch --show-current)" = branch ]; then
+    author_seconds=$((seconds - 10))
+  fi
+  GIT_AUTHOR_DATE="2000-01-01 00:01:$(printf %02d $((author_seconds + 10))) +0000" \
+  GIT_COMMITTER_DATE="2000-01-01 00:01:$(printf %02d $((seconds + 10))) +0000" \
+    git commit -q --allow-empty -m "$1"
+  git tag "$1"
+}
+
+function merge() {
+  seconds=$((seconds + 1))
+  GIT_AUTHOR_DATE="2000-01-01 00:01:$(printf %02d $((seconds + 10))) +0000" \
+  GIT_COMMITTER_DATE="2000-01-01 00:01:$(printf %02d $((seconds + 10))) +0000" \
+    git merge -q --no-edit -m "$1" "$2"
+  git tag "$1"
+}
+
+git init -q
+git config merge.ff false
+
+# c1---c2---c3---m1---c4---m2---c5
+#  \    \        /         /
+#   \    d1---d2----------´
+#    \         /
+#     a1---a2-´---a3
+git checkout -q -b main
+commit c1
+git checkout -q -b branch c1
+commit a1
+git checkout -q main
+commit c2
+git checkout -q -b other c2
+commit d1
+git checkout -q branch
+commit a2
+git checkout -q other
+merge d2 a2
+git checkout -q main
+commit c3
+git checkout -q branch
+commit a3
+git checkout -q main
+merge m1 d2
+commit c4
+merge m2 a3
+commit c5
+
+baseline --topo-order c5
+baseline --topo-order a3 c3
+baseline --topo-order d1 c4 a1
+baseline --topo-order c5 ^d1
+baseline --topo-order c5 ^a2 ^c2
+baseline --topo-order --reverse c5
+baseline --author-date-order c5
+baseline --author-date-order c4 a3
+baseline --author-date-order c5 ^a1
+baseline --author-date-order --reverse c5 ^c3
+
+git commit-graph write --no-progress --reachable
+# Without generation numbers, `git` sorts all parents topologically even with `--first-parent`,
+# so these are only produced by its incremental walk with a commit-graph.
+baseline --topo-order --first-parent a3 d2 c5
+baseline --author-date-order --first-parent c4 a3 d2
+
+git repack -adq
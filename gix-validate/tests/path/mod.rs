@@ -0,0 +1,122 @@
+mod component {
+    use gix_validate::path::component;
+
+    const NO_OPTS: component::Options = component::Options {
+        protect_windows: false,
+        protect_hfs: false,
+        protect_ntfs: false,
+    };
+    const ALL_OPTS: component::Options = component::Options {
+        protect_windows: true,
+        protect_hfs: true,
+        protect_ntfs: true,
+    };
+
+    mod valid {
+        use bstr::ByteSlice;
+        use gix_validate::path::component;
+        use gix_validate::path::component::Mode::Symlink;
+
+        use super::{ALL_OPTS, NO_OPTS};
+
+        macro_rules! mktest {
+            ($name:ident, $input:expr) => {
+                mktest!($name, $input, ALL_OPTS);
+            };
+            ($name:ident, $input:expr, $opts:expr) => {
+                #[test]
+                fn $name() {
+                    assert!(gix_validate::path::component($input.as_bstr(), None, $opts).is_ok())
+                }
+            };
+            ($name:ident, $input:expr, $mode:expr, $opts:expr) => {
+                #[test]
+                fn $name() {
+                    assert!(gix_validate::path::component($input.as_bstr(), Some($mode), $opts).is_ok())
+                }
+            };
+        }
+
+        mktest!(ascii, b"ascii-only_and-that");
+        mktest!(unicode, "😁👍👌".as_bytes());
+        mktest!(backslashes_on_unix, b"\\", NO_OPTS);
+        mktest!(dot_in_middle, b"hello.world");
+        mktest!(dot_at_start, b".hello");
+        mktest!(dot_git_with_suffix, b".gitignore");
+        mktest!(dot_git_as_prefix_of_name, b".github");
+        mktest!(dot_gitmodules_as_file, b".gitmodules");
+        mktest!(
+            dot_gitmodules_as_symlink_without_protections,
+            b".gitmodules ",
+            Symlink,
+            NO_OPTS
+        );
+        mktest!(not_a_windows_device, b"comp");
+        mktest!(windows_device_on_unix, b"aux", NO_OPTS);
+        mktest!(
+            ntfs_short_name_without_ntfs_protection,
+            b"git~1",
+            component::Options {
+                protect_ntfs: false,
+                ..NO_OPTS
+            }
+        );
+    }
+
+    mod invalid {
+        use bstr::ByteSlice;
+        use gix_validate::path::component::Error;
+        use gix_validate::path::component::Mode::Symlink;
+
+        use super::{ALL_OPTS, NO_OPTS};
+
+        macro_rules! mktest {
+            ($name:ident, $input:expr, $expected:ident) => {
+                mktest!($name, $input, $expected, ALL_OPTS);
+            };
+            ($name:ident, $input:expr, $expected:ident, $opts:expr) => {
+                #[test]
+                fn $name() {
+                    match gix_validate::path::component($input.as_bstr(), None, $opts) {
+                        Err(Error::$expected) => {}
+                        got => panic!("Wanted {}, got {:?}", stringify!($expected), got),
+                    }
+                }
+            };
+            ($name:ident, $input:expr, $expected:ident, $mode:expr, $opts:expr) => {
+                #[test]
+                fn $name() {
+                    match gix_validate::path::component($input.as_bstr(), Some($mode), $opts) {
+                        Err(Error::$expected) => {}
+                        got => panic!("Wanted {}, got {:?}", stringify!($expected), got),
+                    }
+                }
+            };
+        }
+
+        mktest!(empty, b"", Empty);
+        mktest!(dot, b".", Relative, NO_OPTS);
+        mktest!(dot_dot, b"..", Relative, NO_OPTS);
+        mktest!(slash, b"a/b", PathSeparator, NO_OPTS);
+        mktest!(backslash_on_windows, b"a\\b", PathSeparator);
+        mktest!(windows_drive_prefix, b"c:", WindowsPathPrefix);
+        mktest!(dot_git, b".git", DotGitDir, NO_OPTS);
+        mktest!(dot_git_upper, b".GIT", DotGitDir, NO_OPTS);
+        mktest!(dot_git_ntfs_trailing_dots_and_spaces, b".git. . .", DotGitDir);
+        mktest!(dot_git_ntfs_stream, b".git::$INDEX_ALLOCATION", DotGitDir);
+        mktest!(dot_git_ntfs_short_name, b"GIT~1", DotGitDir);
+        mktest!(dot_git_hfs_ignorable, ".g\u{200c}it".as_bytes(), DotGitDir);
+        mktest!(dot_gitmodules_symlink, b".gitmodules", SymlinkedGitModules, Symlink, NO_OPTS);
+        mktest!(
+            dot_gitmodules_symlink_ntfs_short_name,
+            b"gitmod~1",
+            SymlinkedGitModules,
+            Symlink,
+            ALL_OPTS
+        );
+        mktest!(windows_device, b"aux.c", WindowsReservedName);
+        mktest!(windows_com_port, b"COM1", WindowsReservedName);
+        mktest!(windows_illegal_character, b"a?b", WindowsIllegalCharacter);
+        mktest!(windows_trailing_space, b"a ", WindowsIllegalCharacter);
+    }
+}
@@ -1,3 +1,4 @@
+mod path;
 mod reference;
 mod submodule;
 mod tag;
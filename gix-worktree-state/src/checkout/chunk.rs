@@ -116,8 +116,8 @@ where
     let mut files_in_chunk = 0;
 
     for (entry, entry_path) in entries_with_paths {
-        // TODO: write test for that
-        if entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE) {
+        // Entries excluded by a sparse checkout are never present in the worktree, and neither are sparse directories.
+        if entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE) || entry.mode.is_sparse() {
             files.fetch_add(1, Ordering::Relaxed);
             files_in_chunk += 1;
             continue;
@@ -165,17 +165,16 @@ where
             obj.data.len()
         }
         gix_index::entry::Mode::DIR => {
-            gix_features::trace::warn!(
-                "Skipped sparse directory at '{entry_path}' ({id}) as it cannot yet be handled",
-                id = entry.id
-            );
+            // Sparse directories represent content excluded from the worktree, and are usually skipped before getting here.
             0
         }
         gix_index::entry::Mode::COMMIT => {
-            gix_features::trace::warn!(
-                "Skipped submodule at '{entry_path}' ({id}) as it cannot yet be handled",
-                id = entry.id
-            );
+            // Like `git`, submodules are represented by an empty directory, which is populated once the submodule is checked out.
+            match std::fs::create_dir(dest) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists && dest.is_dir() => {}
+                Err(err) => return Err(err.into()),
+            }
             0
         }
         _ => unreachable!(),
@@ -238,6 +237,7 @@ fn open_options(path: &Path, destination_is_initially_empty: bool, overwrite_exi
     options
         .create_new(destination_is_initially_empty && !overwrite_existing)
         .create(!destination_is_initially_empty || overwrite_existing)
+        .truncate(!destination_is_initially_empty || overwrite_existing)
         .write(true);
     options
 }
@@ -4,3 +4,11 @@
 ///
 pub mod checkout;
 pub use checkout::function::checkout;
+
+///
+pub mod sparse_checkout;
+pub use sparse_checkout::function::sparse_checkout;
+
+///
+pub mod update;
+pub use update::function::update;
@@ -0,0 +1,184 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use gix_index::entry;
+
+use crate::sparse_checkout::{Error, Options, Outcome, Patterns};
+
+/// Update the *skip-worktree* flags of all entries in `index` to match `patterns`, and bring the worktree at `dir` in line with it.
+///
+/// Entries that are newly excluded have their files removed from the worktree, along with directories that become empty,
+/// unless they were modified in which case they are kept and not marked as *skip-worktree* either.
+/// Entries that are newly included are checked out with `objects` to find their blobs, updating the `files` and `bytes` counters.
+/// Conflicting entries are never excluded, and [sparse directory entries](gix_index::entry::Mode::DIR) are left untouched.
+/// If `should_interrupt` is `true`, checking out files will stop early.
+///
+/// `options` provide the checkout configuration, whose [`fs.ignore_case`](gix_fs::Capabilities::ignore_case) setting also
+/// controls how non-cone patterns are matched.
+///
+/// ### Deviation
+///
+/// `git` hashes files with racy stat information to see if they were modified, using the conversion to git, while we hash
+/// them without conversion, which leaves files in the worktree if conversions would have been applied.
+#[allow(clippy::too_many_arguments)]
+pub fn sparse_checkout<Find>(
+    index: &mut gix_index::State,
+    dir: impl Into<PathBuf>,
+    patterns: &Patterns,
+    objects: Find,
+    files: &dyn gix_features::progress::Count,
+    bytes: &dyn gix_features::progress::Count,
+    should_interrupt: &AtomicBool,
+    options: Options,
+) -> Result<Outcome, Error>
+where
+    Find: gix_object::Find + Send + Clone,
+{
+    let dir = dir.into();
+    let case = if options.checkout.fs.ignore_case {
+        gix_glob::pattern::Case::Fold
+    } else {
+        gix_glob::pattern::Case::Sensitive
+    };
+    let index_timestamp = index.timestamp();
+    let stat_options = options.checkout.stat_options;
+    let is_racy = |stat: &gix_index::entry::Stat| stat.is_racy(index_timestamp, stat_options);
+    let object_hash = index.object_hash();
+    let mut out = Outcome::default();
+    let mut to_checkout = Vec::new();
+    let mut removed_files = Vec::new();
+
+    let (entries, paths) = index.entries_mut_and_pathbacking();
+    for (idx, entry) in entries.iter_mut().enumerate() {
+        if entry.mode.is_sparse() {
+            continue;
+        }
+        let rela_path = entry.path_in(paths);
+        let is_included = entry.stage() != 0 || patterns.is_included(rela_path, case);
+        let is_skipped = entry.flags.contains(entry::Flags::SKIP_WORKTREE);
+        match (is_included, is_skipped) {
+            (true, true) => {
+                set_skip_worktree(entry, false);
+                to_checkout.push(idx);
+                out.entries_included += 1;
+            }
+            (false, false) => {
+                let path = dir.join(gix_path::from_bstr(rela_path));
+                let can_skip = if entry.mode == entry::Mode::COMMIT {
+                    match std::fs::remove_dir(&path) {
+                        Ok(()) => {
+                            removed_files.push(path);
+                            true
+                        }
+                        Err(err) if err.kind() == std::io::ErrorKind::NotFound => true,
+                        Err(_) => false,
+                    }
+                } else if is_unchanged(entry, &path, &is_racy, object_hash, stat_options)
+                    .map_err(|source| Error::Io {
+                        path: path.clone(),
+                        source,
+                    })?
+                {
+                    match std::fs::remove_file(&path) {
+                        Ok(()) => {
+                            removed_files.push(path);
+                            out.files_removed += 1;
+                        }
+                        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                        Err(source) => return Err(Error::Io { path, source }),
+                    }
+                    true
+                } else {
+                    false
+                };
+                if can_skip {
+                    set_skip_worktree(entry, true);
+                    out.entries_skipped += 1;
+                } else {
+                    out.files_kept.push(rela_path.to_owned());
+                }
+            }
+            (true, false) | (false, true) => {}
+        }
+    }
+    remove_empty_directories(&dir, removed_files);
+
+    if !to_checkout.is_empty() {
+        // Only check out the newly included entries by temporarily marking all others as skipped.
+        let mut is_checked_out = vec![false; index.entries().len()];
+        for idx in to_checkout {
+            is_checked_out[idx] = true;
+        }
+        let mut temporarily_skipped = Vec::new();
+        for (idx, entry) in index.entries_mut().iter_mut().enumerate() {
+            if !is_checked_out[idx] && !entry.flags.contains(entry::Flags::SKIP_WORKTREE) {
+                entry.flags.insert(entry::Flags::SKIP_WORKTREE);
+                temporarily_skipped.push(idx);
+            }
+        }
+        let res = crate::checkout(index, &dir, objects, files, bytes, should_interrupt, options.checkout);
+        let entries = index.entries_mut();
+        for idx in temporarily_skipped {
+            entries[idx].flags.remove(entry::Flags::SKIP_WORKTREE);
+        }
+        out.checkout = res?;
+    }
+    Ok(out)
+}
+
+/// Set or clear the *skip-worktree* flag of `entry`, along with the `EXTENDED` flag that is needed to write it to the index.
+fn set_skip_worktree(entry: &mut gix_index::Entry, skip: bool) {
+    if skip {
+        entry.flags.insert(entry::Flags::SKIP_WORKTREE | entry::Flags::EXTENDED);
+    } else {
+        entry.flags.remove(entry::Flags::SKIP_WORKTREE);
+        if !entry.flags.contains(entry::Flags::INTENT_TO_ADD) {
+            entry.flags.remove(entry::Flags::EXTENDED);
+        }
+    }
+}
+
+/// Return `true` if the file at `path` is the same as what's stored in `entry`, or if it doesn't exist.
+fn is_unchanged(
+    entry: &gix_index::Entry,
+    path: &Path,
+    is_racy: &dyn Fn(&gix_index::entry::Stat) -> bool,
+    object_hash: gix_hash::Kind,
+    stat_options: gix_index::entry::stat::Options,
+) -> std::io::Result<bool> {
+    let md = match gix_index::fs::Metadata::from_path_no_follow(path) {
+        Ok(md) => md,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(true),
+        Err(err) => return Err(err),
+    };
+    if md.is_dir() {
+        return Ok(false);
+    }
+    let stat = gix_index::entry::Stat::from_fs(&md).map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+    if entry.stat.matches(&stat, stat_options) && !is_racy(&entry.stat) {
+        return Ok(true);
+    }
+    let data = if md.is_symlink() {
+        gix_path::into_bstr(std::fs::read_link(path)?).into_owned().into()
+    } else {
+        std::fs::read(path)?
+    };
+    Ok(gix_object::compute_hash(object_hash, gix_object::Kind::Blob, &data) == entry.id)
+}
+
+/// Remove all directories leading to `removed_paths` if they are empty, up to but excluding `root`.
+pub(crate) fn remove_empty_directories(root: &Path, removed_paths: Vec<PathBuf>) {
+    let mut dirs: Vec<_> = removed_paths
+        .iter()
+        .flat_map(|path| path.ancestors().skip(1).take_while(|dir| *dir != root))
+        .map(ToOwned::to_owned)
+        .collect();
+    // Longer paths first so directories are emptied before their parents are tried.
+    dirs.sort_by(|a, b| b.as_os_str().len().cmp(&a.as_os_str().len()).then_with(|| a.cmp(b)));
+    dirs.dedup();
+    for dir in dirs {
+        std::fs::remove_dir(dir).ok();
+    }
+}
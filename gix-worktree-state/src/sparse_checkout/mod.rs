@@ -0,0 +1,122 @@
+use bstr::{BStr, BString, ByteSlice};
+pub use gix_index::sparse::{Cone, DirectoryMatch};
+
+/// The patterns of a `.git/info/sparse-checkout` file, which determine which index entries are present in the worktree.
+#[derive(Debug, Clone)]
+pub enum Patterns {
+    /// Patterns that only include whole directories along with all files in their parent directories, as used when
+    /// `core.sparseCheckoutCone` is enabled.
+    Cone(Cone),
+    /// Patterns that are matched like `.gitignore` patterns, with matching paths being included in the worktree.
+    NonCone(gix_glob::search::pattern::List<gix_ignore::search::Ignore>),
+}
+
+/// Lifecycle
+impl Patterns {
+    /// Parse the content of a `sparse-checkout` file from `data`, interpreting it in cone-mode if `cone` is `true`.
+    ///
+    /// Note that just like `git`, patterns that can't be interpreted in cone-mode cause them to be used in non-cone mode instead.
+    pub fn from_bytes(data: &[u8], cone: bool) -> Self {
+        if cone {
+            if let Some(cone) = Cone::from_bytes(data) {
+                return Patterns::Cone(cone);
+            }
+        }
+        Patterns::NonCone(gix_glob::search::pattern::List::from_bytes(data, "sparse-checkout".into(), None))
+    }
+}
+
+/// Access
+impl Patterns {
+    /// Return `true` if the file at `rela_path`, relative to the root of the worktree, should be present in the worktree.
+    /// `case` is used to control case-sensitivity when matching non-cone patterns.
+    pub fn is_included(&self, rela_path: &BStr, case: gix_glob::pattern::Case) -> bool {
+        match self {
+            Patterns::Cone(cone) => cone.includes_file(rela_path),
+            Patterns::NonCone(list) => {
+                // If nothing matches, use the match of the parent directories, or exclude the path if none of them match either.
+                let mut is_dir = false;
+                let mut path = rela_path;
+                loop {
+                    let basename_pos = path.rfind_byte(b'/').map(|pos| pos + 1);
+                    if let Some(m) = gix_ignore::search::pattern_matching_relative_path(
+                        list,
+                        path,
+                        basename_pos,
+                        Some(is_dir),
+                        case,
+                    ) {
+                        break !m.pattern.is_negative();
+                    }
+                    match basename_pos {
+                        Some(pos) => {
+                            path = path[..pos - 1].as_bstr();
+                            is_dir = true;
+                        }
+                        None => break false,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Serialize these patterns to `out` in the format of a `sparse-checkout` file.
+    pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        match self {
+            Patterns::Cone(cone) => cone.write_to(out),
+            Patterns::NonCone(list) => {
+                for mapping in &list.patterns {
+                    writeln!(out, "{}", mapping.pattern)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Options for use in [`sparse_checkout()`](crate::sparse_checkout()).
+#[derive(Clone, Default)]
+pub struct Options {
+    /// The options to use when checking out entries that are newly included in the worktree.
+    pub checkout: crate::checkout::Options,
+}
+
+/// The outcome of [`sparse_checkout()`](crate::sparse_checkout()).
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// The amount of entries that are now marked as *skip-worktree* as they are excluded by the patterns.
+    pub entries_skipped: usize,
+    /// The amount of entries that were marked as *skip-worktree* before, but are now included.
+    pub entries_included: usize,
+    /// The amount of files that were removed from the worktree.
+    pub files_removed: usize,
+    /// Paths of excluded entries that are still present in the worktree as they were modified, or were directories with content.
+    ///
+    /// These entries won't be marked as *skip-worktree*.
+    pub files_kept: Vec<BString>,
+    /// The outcome of checking out all entries that are newly included.
+    pub checkout: crate::checkout::Outcome,
+}
+
+/// The error returned by [`sparse_checkout()`](crate::sparse_checkout()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not remove or check '{}' in the worktree", path.display())]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    Time(#[from] std::time::SystemTimeError),
+    #[error(transparent)]
+    Checkout(#[from] crate::checkout::Error),
+}
+
+impl From<Cone> for Patterns {
+    fn from(cone: Cone) -> Self {
+        Patterns::Cone(cone)
+    }
+}
+
+pub(crate) mod function;
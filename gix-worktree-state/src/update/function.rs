@@ -0,0 +1,113 @@
+use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+use gix_index::entry;
+
+use crate::update::{Error, Options, Outcome};
+
+/// Bring the worktree at `dir`, which is assumed to match the index `from`, in line with the index `to`, similar to what
+/// `git read-tree --reset -u` does.
+///
+/// Files of entries in `from` that are not in `to` are removed along with directories that become empty, and entries of `to`
+/// that were added or whose id or mode changed are checked out with `objects` to find their blobs, updating the `files` and
+/// `bytes` counters along with their stat information. All other entries of `to` receive the stat information of their
+/// counterpart in `from` as their files remain untouched.
+/// If `should_interrupt` is `true`, checking out files will stop early.
+///
+/// Conflicting entries and entries marked as *skip-worktree* in `to` are not checked out, and the files of conflicting
+/// entries in `from` are kept if the path is still present in `to`.
+/// If `from` is a sparse index, entries of `to` within its [sparse directories](gix_index::entry::Mode::DIR) are marked as
+/// *skip-worktree* and aren't checked out either, as these directories are outside of the sparse checkout.
+/// Note that local modifications of files that are changed or removed are lost, so callers should make sure that there are none,
+/// or that they were saved.
+#[allow(clippy::too_many_arguments)]
+pub fn update<Find>(
+    from: &gix_index::State,
+    to: &mut gix_index::State,
+    dir: impl Into<PathBuf>,
+    objects: Find,
+    files: &dyn gix_features::progress::Count,
+    bytes: &dyn gix_features::progress::Count,
+    should_interrupt: &AtomicBool,
+    options: Options,
+) -> Result<Outcome, Error>
+where
+    Find: gix_object::Find + Send + Clone,
+{
+    let dir = dir.into();
+    let mut out = Outcome::default();
+    let mut removed_files = Vec::new();
+    for entry in from.entries() {
+        let rela_path = entry.path(from);
+        if entry.mode.is_sparse()
+            || entry.flags.contains(entry::Flags::SKIP_WORKTREE)
+            || to.entry_by_path(rela_path).is_some()
+        {
+            continue;
+        }
+        let path = dir.join(gix_path::from_bstr(rela_path));
+        let res = if entry.mode == entry::Mode::COMMIT {
+            std::fs::remove_dir(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        match res {
+            Ok(()) => {
+                removed_files.push(path);
+                out.files_removed += 1;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(_) if entry.mode == entry::Mode::COMMIT => {}
+            Err(source) => return Err(Error::Io { path, source }),
+        }
+    }
+    crate::sparse_checkout::function::remove_empty_directories(&dir, removed_files);
+
+    // Sparse directory paths end with a slash, so they are prefixes of the paths they contain.
+    let sparse_dirs: Vec<_> = from
+        .entries()
+        .iter()
+        .filter(|entry| entry.mode.is_sparse())
+        .map(|entry| entry.path(from))
+        .collect();
+
+    // Only check out changed entries by temporarily marking all others as skipped.
+    let mut temporarily_skipped = Vec::new();
+    let mut num_changed = 0;
+    let (entries, paths) = to.entries_mut_and_pathbacking();
+    for (idx, entry) in entries.iter_mut().enumerate() {
+        if entry.flags.contains(entry::Flags::SKIP_WORKTREE) {
+            continue;
+        }
+        let rela_path = entry.path_in(paths);
+        if sparse_dirs.iter().any(|dir| rela_path.starts_with(dir)) {
+            entry.flags.insert(entry::Flags::SKIP_WORKTREE | entry::Flags::EXTENDED);
+            continue;
+        }
+        let unchanged = entry.stage() != 0
+            || from
+                .entry_by_path_and_stage(rela_path, 0)
+                .filter(|previous| previous.id == entry.id && previous.mode == entry.mode)
+                .map(|previous| entry.stat = previous.stat)
+                .is_some();
+        if unchanged {
+            entry.flags.insert(entry::Flags::SKIP_WORKTREE);
+            temporarily_skipped.push(idx);
+        } else {
+            num_changed += 1;
+        }
+    }
+    let res = if num_changed == 0 {
+        Ok(Default::default())
+    } else {
+        let mut options = options.checkout;
+        options.overwrite_existing = true;
+        options.destination_is_initially_empty = false;
+        crate::checkout(to, &dir, objects, files, bytes, should_interrupt, options)
+    };
+    let entries = to.entries_mut();
+    for idx in temporarily_skipped {
+        entries[idx].flags.remove(entry::Flags::SKIP_WORKTREE);
+    }
+    out.checkout = res?;
+    Ok(out)
+}
@@ -0,0 +1,30 @@
+/// Options for use in [`update()`](crate::update()).
+#[derive(Clone, Default)]
+pub struct Options {
+    /// The options to use when checking out entries that changed.
+    pub checkout: crate::checkout::Options,
+}
+
+/// The outcome of [`update()`](crate::update()).
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// The amount of files that were removed from the worktree.
+    pub files_removed: usize,
+    /// The outcome of checking out all entries that were added or changed.
+    pub checkout: crate::checkout::Outcome,
+}
+
+/// The error returned by [`update()`](crate::update()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not remove '{}' from the worktree", path.display())]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    Checkout(#[from] crate::checkout::Error),
+}
+
+pub(crate) mod function;
@@ -1,4 +1,5 @@
 mod checkout;
+mod sparse_checkout;
 
 use std::path::{Path, PathBuf};
 
@@ -0,0 +1,93 @@
+use gix_glob::pattern::Case;
+use gix_object::bstr::{BStr, ByteSlice};
+use gix_worktree_state::sparse_checkout::{Cone, DirectoryMatch, Patterns};
+
+fn included(patterns: &Patterns, paths: &[&str]) -> Vec<bool> {
+    paths
+        .iter()
+        .map(|p| patterns.is_included(p.as_bytes().as_bstr(), Case::Sensitive))
+        .collect()
+}
+
+fn serialized(patterns: &Patterns) -> String {
+    let mut buf = Vec::new();
+    patterns.write_to(&mut buf).expect("in-memory writes don't fail");
+    buf.to_str().expect("valid UTF-8").to_owned()
+}
+
+mod cone {
+    use super::*;
+
+    #[test]
+    fn directories_are_written_like_git_and_can_be_read_back() {
+        let cone = Cone::from_directories(["a/b/", "d/e", "/a/b/c", "x"]);
+        let expected = "/*\n!/*/\n/a/\n!/a/*/\n/d/\n!/d/*/\n/a/b/\n/d/e/\n/x/\n";
+        let patterns = Patterns::from(cone.clone());
+        assert_eq!(serialized(&patterns), expected, "nested directories are subsumed");
+        assert_eq!(
+            Cone::from_bytes(expected.as_bytes()),
+            Some(Cone::from_directories(["a/b", "d/e", "x"]))
+        );
+        assert_eq!(cone.directories().collect::<Vec<_>>(), ["a/b", "d/e", "x"]);
+    }
+
+    #[test]
+    fn file_and_directory_matching() {
+        let cone = Cone::from_directories(["a/b"]);
+        let patterns = Patterns::from(cone.clone());
+        assert_eq!(
+            included(&patterns, &["root", "a/file", "a/b/file", "a/b/c/file", "a/other/file", "d/file"]),
+            [true, true, true, true, false, false]
+        );
+        for (dir, expected) in [
+            ("a", DirectoryMatch::Partial),
+            ("a/b", DirectoryMatch::Recursive),
+            ("a/b/c", DirectoryMatch::Recursive),
+            ("a/other", DirectoryMatch::Excluded),
+            ("d", DirectoryMatch::Excluded),
+        ] {
+            assert_eq!(cone.directory_match(<&BStr>::from(dir)), expected, "{dir}");
+        }
+    }
+
+    #[test]
+    fn empty_patterns_include_root_files_only() {
+        let patterns = Patterns::from_bytes(b"", true);
+        assert!(matches!(patterns, Patterns::Cone(_)));
+        assert_eq!(included(&patterns, &["root", "a/file"]), [true, false]);
+        assert_eq!(serialized(&patterns), "/*\n!/*/\n");
+    }
+
+    #[test]
+    fn non_cone_patterns_fall_back_to_non_cone_mode() {
+        for input in ["*.txt\n", "/a/b*/\n", "/*\n!/*/\n!/a/*/\n"] {
+            assert!(
+                matches!(Patterns::from_bytes(input.as_bytes(), true), Patterns::NonCone(_)),
+                "{input:?}"
+            );
+        }
+    }
+}
+
+mod non_cone {
+    use super::*;
+
+    #[test]
+    fn parent_directories_are_matched_if_the_path_is_undecided() {
+        let patterns = Patterns::from_bytes(b"/a/b/\n*.txt\n!d/e/\n", false);
+        assert_eq!(
+            included(
+                &patterns,
+                &["root", "a/file", "a/b/file", "a/b/c/file", "d/e/file", "d/e/file.txt", "d/file.txt"]
+            ),
+            [false, false, true, true, false, true, true]
+        );
+        assert_eq!(serialized(&patterns), "/a/b/\n*.txt\n!d/e/\n");
+    }
+
+    #[test]
+    fn negations_exclude_previously_included_paths() {
+        let patterns = Patterns::from_bytes(b"/*\n!/d/\n", false);
+        assert_eq!(included(&patterns, &["root", "a/file", "d/file", "d/e/file"]), [true, true, false, false]);
+    }
+}
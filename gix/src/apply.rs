@@ -0,0 +1,97 @@
+pub use gix_apply::*;
+
+use crate::bstr::BString;
+
+/// Where to apply patches with [`Repository::apply()`](crate::Repository::apply()).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Location {
+    /// Change files in the worktree, but leave the index untouched, like `git apply`.
+    Worktree,
+    /// Change entries in the index, but leave the worktree untouched, like `git apply --cached`.
+    ///
+    /// This also works in bare repositories.
+    Index,
+    /// Change both, files in the worktree and entries in the index, like `git apply --index`.
+    ///
+    /// Files in the worktree must have the same content as their entries in the index.
+    WorktreeAndIndex,
+}
+
+/// Options for use in [`Repository::apply()`](crate::Repository::apply()).
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Options {
+    /// Control how patches are applied to the content of individual files.
+    ///
+    /// Note that if [three-way merges](gix_apply::file::Options::three_way) are enabled, the index is always updated
+    /// so conflicts can be recorded, which makes [`Location::Worktree`] behave like [`Location::WorktreeAndIndex`].
+    pub file: gix_apply::file::Options,
+    /// If `true`, apply all hunks that apply and report the ones that don't, like `git apply --reject`.
+    /// Otherwise, nothing is changed if a single hunk doesn't apply.
+    pub reject: bool,
+}
+
+/// The outcome of applying a patch to a single file.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct File {
+    /// The path of the file after applying the patch, or the path of the deleted file.
+    pub path: BString,
+    /// The indices of the hunks of the patch that didn't apply, only set if [rejects](Options::reject) are allowed.
+    pub rejected_hunks: Vec<usize>,
+    /// Set if the patch was applied using a three-way merge, which may have conflicts.
+    pub merge: Option<gix_merge::blob::Resolution>,
+}
+
+/// The outcome of [`Repository::apply()`](crate::Repository::apply()).
+#[derive(Default, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Outcome {
+    /// One entry for each patch that was applied, in order.
+    pub files: Vec<File>,
+}
+
+impl Outcome {
+    /// Return `true` if all hunks were applied and no merge had conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.files
+            .iter()
+            .all(|file| file.rejected_hunks.is_empty() && file.merge != Some(gix_merge::blob::Resolution::Conflict))
+    }
+}
+
+/// The error returned by [`Repository::apply()`](crate::Repository::apply()) and
+/// [`Repository::apply_to_tree()`](crate::Repository::apply_to_tree()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Patches can only be applied to the index in a bare repository")]
+    MissingWorktree,
+    #[error(transparent)]
+    Apply(#[from] gix_apply::file::Error),
+    #[error("{path}: patch does not apply, hunks {hunks:?} were rejected")]
+    Rejected { path: BString, hunks: Vec<usize> },
+    #[error("{path}: does not match index")]
+    WorktreeMismatch { path: BString },
+    #[error("{path}: affected file is beyond a symbolic link")]
+    BeyondSymlink { path: BString },
+    #[error("{path}: cannot apply patches to trees or submodules")]
+    UnsupportedMode { path: BString },
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    WriteBlob(#[from] crate::object::write::Error),
+    #[error("Could not access '{}'", path.display())]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Could not obtain the modification time of '{}'", path.display())]
+    Time {
+        path: std::path::PathBuf,
+        source: std::time::SystemTimeError,
+    },
+    #[error(transparent)]
+    FsCapabilities(#[from] crate::config::boolean::Error),
+    #[error(transparent)]
+    TreeTraversal(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    WriteTree(#[from] gix_index::write_tree::Error),
+}
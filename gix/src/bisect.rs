@@ -0,0 +1,199 @@
+pub use gix_revision::bisect::estimate_steps;
+
+use crate::bstr::{BStr, BString};
+
+/// The names used to mark commits before and after the change that is searched for, like `git bisect --term-old --term-new`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Terms {
+    /// The name for commits that have the change, `bad` by default.
+    pub bad: BString,
+    /// The name for commits that don't have the change yet, `good` by default.
+    pub good: BString,
+}
+
+impl Default for Terms {
+    fn default() -> Self {
+        Terms {
+            bad: "bad".into(),
+            good: "good".into(),
+        }
+    }
+}
+
+impl Terms {
+    /// Return what marking a commit with `name` means, or `None` if `name` is neither one of our terms nor `skip`.
+    pub fn mark_by_name(&self, name: &BStr) -> Option<Mark> {
+        if name == self.bad {
+            Some(Mark::Bad)
+        } else if name == self.good {
+            Some(Mark::Good)
+        } else if name == "skip" {
+            Some(Mark::Skip)
+        } else {
+            None
+        }
+    }
+
+    /// Return `true` if these are the terms `git` uses by default.
+    pub fn is_default(&self) -> bool {
+        *self == Terms::default()
+    }
+}
+
+/// The way a commit can be marked while bisecting.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Mark {
+    /// The commit has the change, and the first commit that has it is searched for.
+    Bad,
+    /// The commit doesn't have the change yet.
+    Good,
+    /// It can't be determined if the commit has the change, so another one nearby should be tested instead.
+    Skip,
+}
+
+/// Options for use in [`Repository::bisect_start()`](crate::Repository::bisect_start()).
+#[derive(Default, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Options {
+    /// The names to use for marking commits.
+    pub terms: Terms,
+    /// If `true`, don't check out the commits to test, but point the `BISECT_HEAD` reference to them instead,
+    /// like `git bisect start --no-checkout` does. This is always the case in bare repositories.
+    pub no_checkout: bool,
+}
+
+/// The state of the bisection in progress, as returned by [`Repository::bisect_status()`](crate::Repository::bisect_status()).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct State {
+    /// The names used to mark commits.
+    pub terms: Terms,
+    /// The commit that was marked as bad, if there is one yet.
+    pub bad: Option<gix_hash::ObjectId>,
+    /// All commits that were marked as good.
+    pub good: Vec<gix_hash::ObjectId>,
+    /// All commits that were skipped.
+    pub skipped: Vec<gix_hash::ObjectId>,
+    /// The name of the branch, or the commit, that was checked out when bisecting started and that is restored on reset.
+    pub start: BString,
+    /// If `true`, commits to test are not checked out, but `BISECT_HEAD` points to them instead.
+    pub no_checkout: bool,
+}
+
+/// The outcome of starting to bisect, or of marking commits.
+#[derive(Debug, Clone)]
+pub enum Outcome<'repo> {
+    /// There are not enough marked commits to start bisecting.
+    Waiting {
+        /// If `true`, the bad commit is known.
+        bad: bool,
+        /// The amount of commits marked as good.
+        good: usize,
+    },
+    /// `commit` was checked out and should be tested and marked next.
+    Next {
+        /// The commit to test.
+        commit: crate::Id<'repo>,
+        /// The amount of commits that are left to test after `commit` was tested, assuming it's good.
+        remaining: usize,
+        /// A rough estimate of how many steps it will take to find the first bad commit.
+        steps: usize,
+    },
+    /// Not all good commits are in the history of the bad commit, so the merge-base `commit` between them was checked
+    /// out and has to be tested first.
+    MergeBase {
+        /// The merge-base to test.
+        commit: crate::Id<'repo>,
+    },
+    /// The search is over as `commit` is the first bad commit.
+    FirstBad {
+        /// The first bad commit.
+        commit: crate::Id<'repo>,
+    },
+    /// Only skipped commits are left to test, so the first bad commit could be any of `candidates`.
+    OnlySkipped {
+        /// The commits that could be the first bad commit, most recent first.
+        candidates: Vec<gix_hash::ObjectId>,
+    },
+}
+
+/// The error returned by the `bisect*()` methods of [`Repository`](crate::Repository).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Cannot bisect without an initial commit")]
+    UnbornHead,
+    #[error("No bisect in progress")]
+    NotInProgress,
+    #[error("Cannot bisect with uncommitted changes in the index or in the worktree")]
+    UncommittedChanges,
+    #[error("'{term}' can't be used as term: {reason}")]
+    InvalidTerm { term: BString, reason: &'static str },
+    #[error("Only a single commit can be marked as {term}")]
+    MultipleBad { term: BString },
+    #[error("The merge base {merge_base} is {term}, so the first '{term}' commit lies between it and the other commits instead")]
+    MergeBaseIsBad {
+        merge_base: gix_hash::ObjectId,
+        term: BString,
+    },
+    #[error("Cannot run the bisect command before a {bad} and a {good} commit are known")]
+    RunWithoutCommits { bad: BString, good: BString },
+    #[error("Could not run '{command}'")]
+    Run { command: BString, source: std::io::Error },
+    #[error("The command '{command}' exited with {status}, which aborts the bisection")]
+    RunFailed {
+        command: BString,
+        status: std::process::ExitStatus,
+    },
+    #[error("Could not read or write the bisect state in '{}'", path.display())]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    Bisect(#[from] gix_revision::bisect::Error),
+    #[error(transparent)]
+    MergeBase(#[from] gix_revision::merge_base::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    AddToIndex(#[from] crate::worktree::add::Error),
+    #[error(transparent)]
+    IndexWriteOptions(#[from] crate::config::index_write_options::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error(transparent)]
+    WriteTree(#[from] gix_index::write_tree::Error),
+    #[error(transparent)]
+    TreeTraversal(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    UpdateWorktree(#[from] gix_worktree_state::update::Error),
+    #[error("Could not obtain a thread-safe handle to the object database")]
+    ObjectStore(#[source] std::io::Error),
+    #[error(transparent)]
+    HeadId(#[from] crate::reference::head_id::Error),
+    #[error(transparent)]
+    HeadTreeId(#[from] crate::reference::head_tree_id::Error),
+    #[error(transparent)]
+    RevParse(#[from] crate::revision::spec::parse::single::Error),
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    FindOptionalReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    IterReferences(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    IterReferencesInit(#[from] crate::reference::iter::init::Error),
+    #[error("Could not obtain a reference during iteration")]
+    IterReference(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    PeelReference(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+}
@@ -0,0 +1,129 @@
+use std::ops::Range;
+
+use crate::bstr::{BStr, BString, ByteSlice};
+
+/// The default minimal amount of alphanumeric characters that lines moved within a file need to have, like in `git blame -M`.
+pub const DEFAULT_MOVE_SCORE: u32 = 20;
+/// The default minimal amount of alphanumeric characters that lines copied from other files need to have, like in `git blame -C`.
+pub const DEFAULT_COPY_SCORE: u32 = 40;
+
+/// The files to search for the origin of lines that were copied from other files, see [`Options::copies`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CopySource {
+    /// Search the files that were modified in the same commit, like `git blame -C`.
+    ModifiedFiles,
+    /// Like [`ModifiedFiles`](CopySource::ModifiedFiles), but search all files of the parent if the file was created
+    /// in the commit, like `git blame -C -C`.
+    AllFilesIfCreated,
+    /// Search all files of the parent of every commit, like `git blame -C -C -C`, which is expensive.
+    AllFiles,
+}
+
+/// Options for use in [`Repository::blame()`](crate::Repository::blame()).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Options {
+    /// The zero-based ranges of lines in the blamed file to blame, with the end being exclusive, or all lines if empty.
+    ///
+    /// Note that `git blame -L 3,5` translates to `2..5`.
+    pub ranges: Vec<Range<u32>>,
+    /// If `true`, find the origin of lines that were moved or copied within the same file, like `git blame -M`.
+    pub moves: bool,
+    /// The minimal amount of alphanumeric characters that moved lines need to have to be considered moved.
+    pub move_score: u32,
+    /// If set, find the origin of lines that were moved or copied from other files, like `git blame -C`.
+    pub copies: Option<CopySource>,
+    /// The minimal amount of alphanumeric characters that copied lines need to have to be considered copied.
+    pub copy_score: u32,
+    /// Commits whose changes should be attributed to the lines they replaced, like `git blame --ignore-rev`.
+    ///
+    /// They are used in addition to the commits listed in the files configured in `blame.ignoreRevsFile`.
+    pub ignore_revs: Vec<gix_hash::ObjectId>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            ranges: Vec::new(),
+            moves: false,
+            move_score: DEFAULT_MOVE_SCORE,
+            copies: None,
+            copy_score: DEFAULT_COPY_SCORE,
+            ignore_revs: Vec::new(),
+        }
+    }
+}
+
+/// A range of lines in the blamed file along with the commit that introduced them.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Entry {
+    /// The commit that introduced the lines.
+    pub commit_id: gix_hash::ObjectId,
+    /// The zero-based lines in the blamed file.
+    pub range_in_blamed_file: Range<u32>,
+    /// The zero-based lines in the file at [`source_path`](Entry::source_path) as of [`commit_id`](Entry::commit_id),
+    /// which are where the lines originally came from.
+    pub range_in_source: Range<u32>,
+    /// The path of the file the lines were introduced in, which differs from the blamed path if the file was renamed or
+    /// if the lines were copied from another file.
+    pub source_path: BString,
+}
+
+/// The outcome of [`Repository::blame()`](crate::Repository::blame()).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Outcome {
+    /// The entries for all blamed lines, ordered by their position in the blamed file.
+    pub entries: Vec<Entry>,
+    /// The data of the blamed file.
+    pub blob: Vec<u8>,
+}
+
+impl Outcome {
+    /// Return all entries along with the lines they refer to, without line terminator.
+    pub fn entries_with_lines(&self) -> impl Iterator<Item = (&Entry, Vec<&BStr>)> {
+        let lines: Vec<_> = self.blob.lines().map(ByteSlice::as_bstr).collect();
+        self.entries.iter().map(move |entry| {
+            let range = entry.range_in_blamed_file.start as usize..entry.range_in_blamed_file.end as usize;
+            (entry, lines[range].to_vec())
+        })
+    }
+}
+
+/// The error returned by [`Repository::blame()`](crate::Repository::blame()) and
+/// [`Repository::blame_incremental()`](crate::Repository::blame_incremental()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("There is no file at '{path}' in commit {commit}")]
+    MissingFile { path: BString, commit: gix_hash::ObjectId },
+    #[error("Line range {}..{} is invalid as the file has only {lines} lines", range.start + 1, range.end)]
+    InvalidRange { range: Range<u32>, lines: u32 },
+    #[error("Could not read ignored revisions from '{}'", path.display())]
+    IgnoreRevsFile {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error("'{line}' in '{}' is not a full object name", path.display())]
+    IgnoreRevsFileLine { path: std::path::PathBuf, line: BString },
+    #[error(transparent)]
+    IgnoreRevsFilePath(#[from] gix_config::path::interpolate::Error),
+    #[error(transparent)]
+    PeelToCommit(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::Error),
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    Commit(#[from] crate::object::commit::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+    #[error(transparent)]
+    DiffRenames(#[from] crate::diff::new_rewrites::Error),
+    #[error(transparent)]
+    DiffTree(#[from] crate::object::tree::diff::for_each::Error),
+    #[error(transparent)]
+    TreeTraversal(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error("The delegate receiving blame entries failed")]
+    Delegate(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+}
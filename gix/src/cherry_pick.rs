@@ -0,0 +1,110 @@
+use crate::bstr::BString;
+
+/// Options for use in [`Repository::cherry_pick()`](crate::Repository::cherry_pick()) and
+/// [`Repository::revert()`](crate::Repository::revert()).
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Options {
+    /// The one-based number of the parent of a merge commit whose changes should be picked or reverted, like `git cherry-pick -m <parent>`.
+    ///
+    /// It must be set for merge commits, and must not be set for other commits.
+    pub mainline: Option<usize>,
+    /// If `true`, add a line with the id of the picked commit to the message of the new commit, like `git cherry-pick -x`.
+    ///
+    /// This has no effect when reverting.
+    pub record_origin: bool,
+    /// Control how files that were changed in the picked commit and in the commit to apply it onto are merged.
+    pub merge: gix_merge::tree::Options,
+}
+
+/// The outcome of [`Repository::cherry_pick()`](crate::Repository::cherry_pick()) and
+/// [`Repository::revert()`](crate::Repository::revert()).
+#[derive(Clone)]
+pub enum Outcome<'repo> {
+    /// The changes could be applied, and a new commit with the commit to apply them onto as parent was written.
+    Commit(crate::Id<'repo>),
+    /// The changes couldn't be applied without conflicts, and no commit was written.
+    ///
+    /// The merge outcome contains the index with the conflicting paths, to be resolved and written to a tree and commit
+    /// by the caller.
+    Conflict(Box<gix_merge::tree::Outcome>),
+}
+
+impl<'repo> Outcome<'repo> {
+    /// Return the id of the new commit, or `None` if there were conflicts.
+    pub fn commit_id(&self) -> Option<crate::Id<'repo>> {
+        match self {
+            Outcome::Commit(id) => Some(*id),
+            Outcome::Conflict(_) => None,
+        }
+    }
+}
+
+/// The error returned by [`Repository::cherry_pick()`](crate::Repository::cherry_pick()) and
+/// [`Repository::revert()`](crate::Repository::revert()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Commit {id} is a merge with {parents} parents, but no mainline was specified")]
+    MissingMainline { id: gix_hash::ObjectId, parents: usize },
+    #[error("A mainline was specified, but commit {id} is not a merge")]
+    UnexpectedMainline { id: gix_hash::ObjectId },
+    #[error("Commit {id} does not have parent {mainline}")]
+    InvalidMainline { id: gix_hash::ObjectId, mainline: usize },
+    #[error("Cannot revert commit {id} as it has no parent")]
+    RevertRootCommit { id: gix_hash::ObjectId },
+    #[error(transparent)]
+    Merge(#[from] gix_merge::tree::Error),
+    #[error(transparent)]
+    WriteTree(#[from] gix_index::write_tree::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    Commit(#[from] crate::commit::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    Time(#[from] crate::config::time::Error),
+}
+
+/// Return `message` with a line that records `id` as the commit it was cherry-picked from, like `git cherry-pick -x` does.
+///
+/// Just like `git`, the line is added to the trailers if the message ends with them, and separated by an empty line otherwise.
+pub(crate) fn message_with_origin(message: &[u8], id: &gix_hash::oid) -> BString {
+    use crate::bstr::ByteSlice;
+    let mut out: BString = message.trim_end_with(|c| c == '\n').into();
+    let last_paragraph = out.rfind(b"\n\n").map_or(&[][..], |pos| &out[pos + 2..]);
+    let ends_with_trailers = !last_paragraph.is_empty() && last_paragraph.lines().all(is_trailer);
+    out.extend_from_slice(if ends_with_trailers { b"\n" } else { b"\n\n" });
+    out.extend_from_slice(format!("(cherry picked from commit {id})\n").as_bytes());
+    out
+}
+
+/// Return the message of a commit that reverts the commit `id` with `summary`, like `git revert` does, mentioning the
+/// `mainline_parent` whose changes are restored if `id` is a merge commit.
+pub(crate) fn revert_message(
+    summary: &crate::bstr::BStr,
+    id: &gix_hash::oid,
+    mainline_parent: Option<&gix_hash::oid>,
+) -> BString {
+    let mut message = BString::from(format!("Revert \"{summary}\"\n\nThis reverts commit {id}"));
+    if let Some(parent) = mainline_parent {
+        message.extend_from_slice(format!(", reversing\nchanges made to {parent}").as_bytes());
+    }
+    message.extend_from_slice(b".\n");
+    message
+}
+
+/// Return `true` if `line` looks like `Key: value` or was added by `git cherry-pick -x`.
+fn is_trailer(line: &[u8]) -> bool {
+    if line.starts_with(b"(cherry picked from commit ") {
+        return true;
+    }
+    let Some(pos) = line.iter().position(|b| *b == b':') else {
+        return false;
+    };
+    pos > 0 && line[..pos].iter().all(|b| b.is_ascii_alphanumeric() || *b == b'-')
+}
@@ -22,6 +22,8 @@ pub mod main_worktree {
         #[error(transparent)]
         WriteIndex(#[from] gix_index::file::write::Error),
         #[error(transparent)]
+        IndexWriteOptions(#[from] crate::config::index_write_options::Error),
+        #[error(transparent)]
         CheckoutOptions(#[from] crate::config::checkout_options::Error),
         #[error(transparent)]
         IndexCheckout(#[from] gix_worktree_state::checkout::Error),
@@ -31,6 +33,12 @@ pub mod main_worktree {
         FindHead(#[from] crate::reference::find::existing::Error),
         #[error("The HEAD reference could not be located")]
         PeelHeadToId(#[from] crate::head::peel::Error),
+        #[cfg(feature = "blocking-network-client")]
+        #[error(transparent)]
+        SubmoduleRecurse(#[from] crate::config::boolean::Error),
+        #[cfg(feature = "blocking-network-client")]
+        #[error(transparent)]
+        UpdateSubmodules(#[from] crate::submodule::update::Error),
     }
 
     /// The progress ids used in [`PrepareCheckout::main_worktree()`].
@@ -60,6 +68,9 @@ pub mod main_worktree {
         ///
         /// Note that this is a no-op if the remote was empty, leaving this repository empty as well. This can be validated by checking
         /// if the `head()` of the returned repository is not unborn.
+        ///
+        /// Submodules are initialized, cloned and checked out afterwards if configured with [`with_submodules()`](Self::with_submodules()),
+        /// or if `submodule.recurse` is set, which otherwise leaves them as empty directories.
         pub fn main_worktree<P>(
             &mut self,
             mut progress: P,
@@ -124,12 +135,44 @@ pub mod main_worktree {
             files.show_throughput(start);
             bytes.show_throughput(start);
 
-            index.write(Default::default())?;
+            index.write(repo.index_write_options()?)?;
+
+            #[cfg(feature = "blocking-network-client")]
+            {
+                let submodules = match self.submodules {
+                    Some(options) => options,
+                    None => repo
+                        .config
+                        .submodule_recurse()?
+                        .then(|| crate::submodule::update::Options {
+                            init: true,
+                            recursive: true,
+                            ..Default::default()
+                        }),
+                };
+                if let Some(options) = submodules {
+                    let mut progress = progress.add_child("submodules".into());
+                    repo.update_submodules_inner(options, &mut progress, should_interrupt)?;
+                }
+            }
             Ok((self.repo.take().expect("still present"), outcome))
         }
     }
 }
 
+/// Builder
+#[cfg(feature = "blocking-network-client")]
+impl PrepareCheckout {
+    /// Initialize, clone and check out submodules with `options` once the main worktree was checked out, or leave them
+    /// as empty directories if `None`.
+    ///
+    /// If not set here, submodules are updated recursively if `submodule.recurse` is `true`.
+    pub fn with_submodules(mut self, options: Option<crate::submodule::update::Options>) -> Self {
+        self.submodules = Some(options);
+        self
+    }
+}
+
 /// Access
 impl PrepareCheckout {
     /// Get access to the repository while the checkout isn't yet completed.
@@ -67,7 +67,7 @@ impl PrepareFetch {
     }
 
     #[gix_protocol::maybe_async::maybe_async]
-    async fn fetch_only_inner(
+    pub(crate) async fn fetch_only_inner(
         &mut self,
         progress: &mut dyn crate::DynNestedProgress,
         should_interrupt: &std::sync::atomic::AtomicBool,
@@ -180,7 +180,13 @@ impl PrepareFetch {
         P::SubProgress: 'static,
     {
         let (repo, fetch_outcome) = self.fetch_only(progress, should_interrupt)?;
-        Ok((crate::clone::PrepareCheckout { repo: repo.into() }, fetch_outcome))
+        Ok((
+            crate::clone::PrepareCheckout {
+                repo: repo.into(),
+                submodules: None,
+            },
+            fetch_outcome,
+        ))
     }
 }
 
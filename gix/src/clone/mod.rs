@@ -145,6 +145,10 @@ impl PrepareFetch {
 pub struct PrepareCheckout {
     /// A freshly initialized repository which is owned by us, or `None` if it was handed to the user
     pub(self) repo: Option<crate::Repository>,
+    /// How to update submodules after the main worktree was checked out, with `Some(None)` leaving them uninitialized,
+    /// or `None` to let `submodule.recurse` decide.
+    #[cfg(feature = "blocking-network-client")]
+    pub(self) submodules: Option<Option<crate::submodule::update::Options>>,
 }
 
 // This module encapsulates functionality that works with both feature toggles. Can be combined with `fetch`
@@ -29,7 +29,11 @@ pub mod describe {
     use gix_hash::ObjectId;
     use gix_hashtable::HashMap;
 
-    use crate::{bstr::BStr, ext::ObjectIdExt, Repository};
+    use crate::{
+        bstr::{BStr, BString, ByteSlice},
+        ext::ObjectIdExt,
+        Repository,
+    };
 
     /// The result of [`try_resolve()`][Platform::try_resolve()].
     pub struct Resolution<'repo> {
@@ -37,13 +41,17 @@ pub mod describe {
         pub outcome: gix_revision::describe::Outcome<'static>,
         /// The id to describe.
         pub id: crate::Id<'repo>,
+        /// The suffix to append to the describe string if the worktree is dirty or its state couldn't be determined.
+        pub dirty_suffix: Option<String>,
     }
 
     impl<'repo> Resolution<'repo> {
         /// Turn this instance into something displayable
         pub fn format(self) -> Result<gix_revision::describe::Format<'static>, Error> {
             let prefix = self.id.shorten()?;
-            Ok(self.outcome.into_format(prefix.hex_len()))
+            let mut format = self.outcome.into_format(prefix.hex_len());
+            format.dirty_suffix = self.dirty_suffix;
+            Ok(format)
         }
     }
 
@@ -59,6 +67,11 @@ pub mod describe {
         RefIter(#[from] crate::reference::iter::Error),
         #[error(transparent)]
         RefIterInit(#[from] crate::reference::iter::init::Error),
+        #[error(transparent)]
+        NameRev(#[from] super::name_rev::Error),
+        #[cfg(feature = "status")]
+        #[error(transparent)]
+        IsDirty(#[from] crate::worktree::is_dirty::Error),
     }
 
     /// A selector to choose what kind of references should contribute to names.
@@ -74,7 +87,11 @@ pub mod describe {
     }
 
     impl SelectRef {
-        fn names(&self, repo: &Repository) -> Result<HashMap<ObjectId, Cow<'static, BStr>>, Error> {
+        fn names(
+            &self,
+            repo: &Repository,
+            patterns: &Patterns,
+        ) -> Result<HashMap<ObjectId, Cow<'static, BStr>>, Error> {
             let platform = repo.references()?;
 
             Ok(match self {
@@ -85,6 +102,7 @@ pub mod describe {
                         _ => unreachable!(),
                     }
                     .filter_map(Result::ok)
+                    .filter(|r| patterns.select(r.name()))
                     .filter_map(|mut r: crate::Reference<'_>| {
                         let target_id = r.target().try_id().map(ToOwned::to_owned);
                         let peeled_id = r.peel_to_id_in_place().ok()?;
@@ -120,6 +138,7 @@ pub mod describe {
                     let mut peeled_commits_and_tag_date: Vec<_> = platform
                         .tags()?
                         .filter_map(Result::ok)
+                        .filter(|r| patterns.select(r.name()))
                         .filter_map(|r: crate::Reference<'_>| {
                             // TODO: we assume direct refs for tags, which is the common case, but it doesn't have to be
                             //       so rather follow symrefs till the first object and then peel tags after the first object was found.
@@ -143,6 +162,38 @@ pub mod describe {
         }
     }
 
+    /// Glob patterns to select the names to use, like `git describe --match` and `--exclude`.
+    #[derive(Default, Debug, Clone)]
+    pub(crate) struct Patterns {
+        pub(crate) matching: Vec<BString>,
+        pub(crate) excluding: Vec<BString>,
+    }
+
+    impl Patterns {
+        /// Return `true` if the reference `name` may be used for naming, matching patterns against the name without its
+        /// `refs/tags/`, `refs/heads/` or `refs/remotes/` prefix. References in other namespaces are rejected if there are patterns.
+        fn select(&self, name: &gix_ref::FullNameRef) -> bool {
+            if self.matching.is_empty() && self.excluding.is_empty() {
+                return true;
+            }
+            let name = name.as_bstr();
+            let Some(short_name) = ["refs/tags/", "refs/heads/", "refs/remotes/"]
+                .iter()
+                .find_map(|prefix| name.strip_prefix(prefix.as_bytes()))
+            else {
+                return false;
+            };
+            let is_match = |pattern: &BString| {
+                gix_glob::wildmatch(
+                    pattern.as_bstr(),
+                    short_name.as_bstr(),
+                    gix_glob::wildmatch::Mode::empty(),
+                )
+            };
+            !self.excluding.iter().any(is_match) && (self.matching.is_empty() || self.matching.iter().any(is_match))
+        }
+    }
+
     /// A support type to allow configuring a `git describe` operation
     pub struct Platform<'repo> {
         pub(crate) id: gix_hash::ObjectId,
@@ -151,6 +202,11 @@ pub mod describe {
         pub(crate) first_parent: bool,
         pub(crate) id_as_fallback: bool,
         pub(crate) max_candidates: usize,
+        pub(crate) patterns: Patterns,
+        #[cfg(feature = "excludes")]
+        pub(crate) dirty_suffix: Option<String>,
+        #[cfg(feature = "excludes")]
+        pub(crate) broken_suffix: Option<String>,
     }
 
     impl<'repo> Platform<'repo> {
@@ -178,6 +234,43 @@ pub mod describe {
             self
         }
 
+        /// Only use names that match any of the given glob `patterns`, like `git describe --match`.
+        ///
+        /// Patterns are matched against names without their `refs/tags/`, `refs/heads/` or `refs/remotes/` prefix,
+        /// and references outside of these namespaces aren't used if there are patterns.
+        pub fn matching(mut self, patterns: impl IntoIterator<Item = impl Into<BString>>) -> Self {
+            self.patterns.matching = patterns.into_iter().map(Into::into).collect();
+            self
+        }
+
+        /// Don't use names that match any of the given glob `patterns`, like `git describe --exclude`.
+        ///
+        /// See [`matching()`](Self::matching()) for details on how patterns are matched.
+        pub fn excluding(mut self, patterns: impl IntoIterator<Item = impl Into<BString>>) -> Self {
+            self.patterns.excluding = patterns.into_iter().map(Into::into).collect();
+            self
+        }
+
+        /// Append `suffix`, like `dirty`, to the describe string if tracked files in the worktree or the index differ from `HEAD`,
+        /// like `git describe --dirty` does.
+        ///
+        /// Note that this only makes sense when describing the `HEAD` commit, and that repositories without worktree are never dirty.
+        #[cfg(feature = "excludes")]
+        pub fn dirty_suffix(mut self, suffix: Option<String>) -> Self {
+            self.dirty_suffix = suffix;
+            self
+        }
+
+        /// Append `suffix`, like `broken`, to the describe string if it can't be determined if the worktree is dirty,
+        /// instead of failing, like `git describe --broken` does.
+        ///
+        /// If no [dirty suffix](Self::dirty_suffix()) is set, `dirty` is used.
+        #[cfg(feature = "excludes")]
+        pub fn broken_suffix(mut self, suffix: Option<String>) -> Self {
+            self.broken_suffix = suffix;
+            self
+        }
+
         /// Try to find a name for the configured commit id using all prior configuration, returning `Some(describe::Format)`
         /// if one was found.
         ///
@@ -196,7 +289,6 @@ pub mod describe {
         /// It is greatly recommended to [assure an object cache is set][crate::Repository::object_cache_size_if_unset()]
         /// to save ~40% of time.
         pub fn try_resolve(&self) -> Result<Option<Resolution<'repo>>, Error> {
-            // TODO: dirty suffix with respective dirty-detection
             let mut graph = gix_revwalk::Graph::new(
                 &self.repo.objects,
                 gix_commitgraph::Graph::from_info_dir(self.repo.objects.store_ref().path().join("info").as_ref()).ok(),
@@ -205,19 +297,70 @@ pub mod describe {
                 &self.id,
                 &mut graph,
                 gix_revision::describe::Options {
-                    name_by_oid: self.select.names(self.repo)?,
+                    name_by_oid: self.select.names(self.repo, &self.patterns)?,
                     fallback_to_oid: self.id_as_fallback,
                     first_parent: self.first_parent,
                     max_candidates: self.max_candidates,
                 },
             )?;
 
-            Ok(outcome.map(|outcome| Resolution {
+            let Some(outcome) = outcome else {
+                return Ok(None);
+            };
+            Ok(Some(Resolution {
                 outcome,
                 id: self.id.attach(self.repo),
+                dirty_suffix: self.worktree_suffix()?,
             }))
         }
 
+        /// Find the oldest tag that contains the configured commit and name the commit relative to it,
+        /// like `git describe --contains` does, for example as `v1.0~3^2`, or return `None` if no tag contains it.
+        ///
+        /// All tags are used, or all references if [all references are selected](SelectRef::AllRefs), in which case
+        /// the [patterns](Self::matching()) are ignored.
+        pub fn try_name_containing(&self) -> Result<Option<gix_revision::name_rev::Name>, Error> {
+            let tags_only = self.select != SelectRef::AllRefs;
+            let tag_patterns = |patterns: &[BString]| -> Vec<BString> {
+                if tags_only {
+                    patterns.iter().map(|p| format!("refs/tags/{p}").into()).collect()
+                } else {
+                    Vec::new()
+                }
+            };
+            let mut names = self.repo.name_rev(
+                &[self.id],
+                &super::name_rev::Options {
+                    tags_only,
+                    ref_patterns: tag_patterns(&self.patterns.matching),
+                    exclude_patterns: tag_patterns(&self.patterns.excluding),
+                    shorten_names: tags_only,
+                },
+            )?;
+            Ok(names.pop().flatten())
+        }
+
+        #[cfg(feature = "status")]
+        fn worktree_suffix(&self) -> Result<Option<String>, Error> {
+            if self.dirty_suffix.is_none() && self.broken_suffix.is_none() {
+                return Ok(None);
+            }
+            let Some(worktree) = self.repo.worktree() else {
+                return Ok(None);
+            };
+            match worktree.has_tracked_changes() {
+                Ok(true) => Ok(Some(self.dirty_suffix.clone().unwrap_or_else(|| "dirty".into()))),
+                Ok(false) => Ok(None),
+                Err(_) if self.broken_suffix.is_some() => Ok(self.broken_suffix.clone()),
+                Err(err) => Err(err.into()),
+            }
+        }
+
+        #[cfg(not(feature = "status"))]
+        fn worktree_suffix(&self) -> Result<Option<String>, Error> {
+            Ok(None)
+        }
+
         /// Like [`try_format()`][Platform::try_format()], but turns `id_as_fallback()` on to always produce a format.
         pub fn format(&mut self) -> Result<gix_revision::describe::Format<'static>, Error> {
             self.id_as_fallback = true;
@@ -225,3 +368,122 @@ pub mod describe {
         }
     }
 }
+
+///
+#[cfg(feature = "revision")]
+pub mod name_rev {
+    use std::borrow::Cow;
+
+    use gix_revision::name_rev::Tip;
+
+    use crate::{
+        bstr::{BStr, BString, ByteSlice},
+        Repository,
+    };
+
+    /// Options for use in [`Repository::name_rev()`](crate::Repository::name_rev()).
+    #[derive(Default, Debug, Clone)]
+    pub struct Options {
+        /// Only use tags to name commits, like `git name-rev --tags`.
+        pub tags_only: bool,
+        /// If not empty, only use references that match any of these glob patterns, like `git name-rev --refs`.
+        ///
+        /// Patterns match full reference names, or any of their trailing path components, in which case names are shortened.
+        pub ref_patterns: Vec<BString>,
+        /// Don't use references that match any of these glob patterns, like `git name-rev --exclude`.
+        pub exclude_patterns: Vec<BString>,
+        /// Shorten reference names as much as possible, so `refs/tags/v1.0` is used as `v1.0` instead of `tags/v1.0`,
+        /// which is what `git name-rev --tags --name-only` does.
+        pub shorten_names: bool,
+    }
+
+    /// The error returned by [`Repository::name_rev()`](crate::Repository::name_rev()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        NameRev(#[from] gix_revision::name_rev::Error),
+        #[error(transparent)]
+        RefIter(#[from] crate::reference::iter::Error),
+        #[error(transparent)]
+        RefIterInit(#[from] crate::reference::iter::init::Error),
+        #[error(transparent)]
+        FollowReference(#[from] gix_ref::file::find::existing::Error),
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        DecodeTag(#[from] gix_object::decode::Error),
+    }
+
+    /// Return all references that may be used to name commits according to `options`, peeled to the commits they point to.
+    pub(crate) fn tips(repo: &Repository, options: &Options) -> Result<Vec<Tip<'static>>, Error> {
+        let mut out = Vec::new();
+        for mut reference in repo.references()?.all()?.filter_map(Result::ok) {
+            let full_name = reference.name().as_bstr().to_owned();
+            let from_tag = full_name.starts_with(b"refs/tags/");
+            if (options.tags_only && !from_tag)
+                || options
+                    .exclude_patterns
+                    .iter()
+                    .any(|pattern| subpath_matches(full_name.as_ref(), pattern.as_ref()).is_some())
+            {
+                continue;
+            }
+            let mut shorten = options.shorten_names;
+            if !options.ref_patterns.is_empty() {
+                match options
+                    .ref_patterns
+                    .iter()
+                    .find_map(|pattern| subpath_matches(full_name.as_ref(), pattern.as_ref()))
+                {
+                    None => continue,
+                    Some(0) => {}
+                    Some(_) => shorten = true,
+                }
+            }
+            let name = if shorten {
+                reference.name().shorten()
+            } else {
+                full_name
+                    .strip_prefix(b"refs/heads/")
+                    .or_else(|| full_name.strip_prefix(b"refs/"))
+                    .unwrap_or(full_name.as_slice())
+                    .as_bstr()
+            }
+            .to_owned();
+
+            while let Some(target) = reference.follow() {
+                reference = target?;
+            }
+            let mut object = reference.id().object()?;
+            let mut tagger_time = None;
+            while object.kind == gix_object::Kind::Tag {
+                let tag = object.into_tag();
+                tagger_time = Some(tag.tagger()?.map_or(0, |signature| signature.time.seconds));
+                object = tag.target_id()?.object()?;
+            }
+            if object.kind != gix_object::Kind::Commit {
+                continue;
+            }
+            out.push(Tip {
+                name: Cow::Owned(name),
+                id: object.id,
+                from_tag,
+                tagger_time,
+                peeled: tagger_time.is_some(),
+            });
+        }
+        Ok(out)
+    }
+
+    /// Return the position in `name` at which `pattern` matches `name` or one of its trailing path components, like `git` does.
+    fn subpath_matches(name: &BStr, pattern: &BStr) -> Option<usize> {
+        let mut start = 0;
+        loop {
+            if gix_glob::wildmatch(pattern, name[start..].as_bstr(), gix_glob::wildmatch::Mode::empty()) {
+                return Some(start);
+            }
+            start += name[start..].find_byte(b'/')? + 1;
+        }
+    }
+}
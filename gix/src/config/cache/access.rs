@@ -20,7 +20,7 @@ use crate::{
 impl Cache {
     #[cfg(feature = "blob-diff")]
     pub(crate) fn diff_algorithm(&self) -> Result<gix_diff::blob::Algorithm, config::diff::algorithm::Error> {
-        use crate::config::{cache::util::ApplyLeniencyDefault, diff::algorithm::Error};
+        use crate::config::cache::util::ApplyLeniencyDefault;
         self.diff_algorithm
             .get_or_try_init(|| {
                 let name = self
@@ -29,10 +29,6 @@ impl Cache {
                     .unwrap_or_else(|| Cow::Borrowed("myers".into()));
                 config::tree::Diff::ALGORITHM
                     .try_into_algorithm(name)
-                    .or_else(|err| match err {
-                        Error::Unimplemented { .. } if self.lenient_config => Ok(gix_diff::blob::Algorithm::Histogram),
-                        err => Err(err),
-                    })
                     .with_lenient_default(self.lenient_config)
             })
             .copied()
@@ -80,15 +76,12 @@ impl Cache {
             if let Some(textconv) = section.value(config::tree::Diff::DRIVER_TEXTCONV.name) {
                 driver.binary_to_text_command = textconv.into_owned().into();
             }
+            if let Some(pattern) = section.value(config::tree::Diff::DRIVER_XFUNCNAME.name) {
+                driver.function_name_pattern = pattern.into_owned().into();
+            }
             if let Some(algorithm) = section.value("algorithm") {
                 driver.algorithm = config::tree::Diff::DRIVER_ALGORITHM
                     .try_into_algorithm(algorithm)
-                    .or_else(|err| match err {
-                        config::diff::algorithm::Error::Unimplemented { .. } if self.lenient_config => {
-                            Ok(gix_diff::blob::Algorithm::Histogram)
-                        }
-                        err => Err(err),
-                    })
                     .with_lenient_default(self.lenient_config)
                     .map_err(|err| config::diff::drivers::Error {
                         name: driver.name.clone(),
@@ -245,6 +238,27 @@ impl Cache {
         })
     }
 
+    /// Return `Some(cone)` if `core.sparseCheckout` is enabled, with `cone` being the value of `core.sparseCheckoutCone`,
+    /// or `None` if sparse checkouts are disabled.
+    #[cfg(feature = "worktree-mutation")]
+    pub(crate) fn sparse_checkout(&self) -> Result<Option<bool>, boolean::Error> {
+        boolean(self, "core.sparseCheckout", &Core::SPARSE_CHECKOUT, false)?
+            .then(|| boolean(self, "core.sparseCheckoutCone", &Core::SPARSE_CHECKOUT_CONE, false))
+            .transpose()
+    }
+
+    /// Return `true` if the index should be written as sparse index, which is only possible with cone-mode sparse checkouts.
+    #[cfg(feature = "worktree-mutation")]
+    pub(crate) fn sparse_index(&self) -> Result<bool, boolean::Error> {
+        boolean(self, "index.sparse", &config::tree::Index::SPARSE, false)
+    }
+
+    /// Return `true` if commands should recurse into submodules by default, as configured by `submodule.recurse`.
+    #[cfg(all(feature = "worktree-mutation", feature = "blocking-network-client"))]
+    pub(crate) fn submodule_recurse(&self) -> Result<bool, boolean::Error> {
+        boolean(self, "submodule.recurse", &config::tree::Submodule::RECURSE, false)
+    }
+
     #[cfg(feature = "index")]
     pub(crate) fn stat_options(&self) -> Result<gix_index::entry::stat::Options, config::stat_options::Error> {
         use crate::config::tree::gitoxide;
@@ -262,6 +276,63 @@ impl Cache {
         })
     }
 
+    #[cfg(feature = "index")]
+    pub(crate) fn index_write_options(&self) -> Result<gix_index::write::Options, config::index_write_options::Error> {
+        use crate::config::tree::{Feature, Index, SplitIndex};
+        let split_index = if boolean(self, "core.splitIndex", &Core::SPLIT_INDEX, false)? {
+            let max_percent_change = self
+                .apply_leniency(
+                    self.resolved
+                        .integer_by_key("splitIndex.maxPercentChange")
+                        .map(|value| SplitIndex::MAX_PERCENT_CHANGE.try_into_usize(value)),
+                )?
+                .map_or(20, |percent| percent.min(100) as u8);
+            let shared_index_expire = match self.resolved.string_by_key("splitIndex.sharedIndexExpire") {
+                Some(value) => self
+                    .apply_leniency(Some(
+                        SplitIndex::SHARED_INDEX_EXPIRE.try_into_expiry(value, std::time::SystemTime::now()),
+                    ))?
+                    .flatten(),
+                None => gix_index::write::SplitIndex::default().shared_index_expire,
+            };
+            Some(gix_index::write::SplitIndex {
+                max_percent_change,
+                shared_index_expire,
+            })
+        } else {
+            None
+        };
+        let version = match self.resolved.integer_by_key("index.version") {
+            Some(value) => self.apply_leniency(Some(Index::VERSION.try_into_index_version(value)))?,
+            None => boolean(self, "feature.manyFiles", &Feature::MANY_FILES, false)?.then_some(gix_index::Version::V4),
+        };
+        let thread_limit = self.apply_leniency(
+            self.resolved
+                .string_by_key("index.threads")
+                .map(|value| Index::THREADS.try_into_index_threads(value)),
+        )?;
+        // Just like `git`, don't write the index-entry-offset-table unless asked to.
+        let extensions = if boolean(self, "index.recordOffsetTable", &Index::RECORD_OFFSET_TABLE, false)? {
+            gix_index::write::Extensions::All
+        } else {
+            gix_index::write::Extensions::Given {
+                tree_cache: true,
+                untracked_cache: true,
+                fs_monitor: true,
+                resolve_undo: true,
+                end_of_index_entry: true,
+                index_entry_offset_table: false,
+            }
+        };
+        Ok(gix_index::write::Options {
+            extensions,
+            version,
+            thread_limit,
+            skip_hash: boolean(self, "index.skipHash", &Index::SKIP_HASH, false)?,
+            split_index,
+        })
+    }
+
     /// Collect everything needed to checkout files into a worktree.
     /// Note that some of the options being returned will be defaulted so safe settings, the caller might have to override them
     /// depending on the use-case.
@@ -0,0 +1,132 @@
+#![allow(clippy::result_large_err)]
+use std::{io::Write, path::PathBuf};
+
+use gix_macros::momo;
+
+use crate::{
+    bstr::{BStr, BString},
+    config::FileMut,
+};
+
+///
+pub mod open {
+    use std::path::PathBuf;
+
+    /// The error returned by [`Repository::config_file_mut()`][crate::Repository::config_file_mut()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Configuration files of source {config_source:?} can't be edited")]
+        UnsupportedSource { config_source: gix_config::Source },
+        #[error("Could not determine the location of the configuration file of source {config_source:?}")]
+        MissingLocation { config_source: gix_config::Source },
+        #[error("The configuration file at '{}' is not a file", path.display())]
+        NotAFile { path: PathBuf },
+        #[error(transparent)]
+        Load(#[from] gix_config::file::init::from_paths::Error),
+    }
+}
+
+///
+pub mod commit {
+    use std::path::PathBuf;
+
+    /// The error returned by [`FileMut::commit()`][crate::config::FileMut::commit()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Lock(#[from] gix_lock::acquire::Error),
+        #[error(transparent)]
+        Commit(#[from] gix_lock::commit::Error<gix_lock::File>),
+        #[error("Could not write '{}'", path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+    }
+}
+
+/// Lifecycle
+impl FileMut {
+    /// Load the configuration file at `path` without resolving includes, or create a new one if it doesn't exist.
+    /// `source` is the kind of configuration file at `path`.
+    pub(crate) fn at(path: PathBuf, source: gix_config::Source) -> Result<Self, open::Error> {
+        let config = if path.is_file() {
+            gix_config::File::from_path_no_includes(path.clone(), source)?
+        } else if path.exists() {
+            return Err(open::Error::NotAFile { path });
+        } else {
+            gix_config::File::new(gix_config::file::Metadata::from(source).at(&path))
+        };
+        Ok(FileMut { path, config })
+    }
+
+    /// Write all changes back to the file we were loaded from, or create it if it didn't exist yet,
+    /// replacing it atomically while it's locked.
+    ///
+    /// Note that the parent directory of the file must exist.
+    pub fn commit(self) -> Result<(), commit::Error> {
+        let FileMut { path, config } = self;
+        let mut file = gix_lock::File::acquire_to_update_resource(&path, gix_lock::acquire::Fail::Immediately, None)?;
+        config
+            .write_to(&mut file)
+            .and_then(|_| file.flush())
+            .map_err(|source| commit::Error::Io { path, source })?;
+        file.commit()?;
+        Ok(())
+    }
+
+    /// Don't write any of the changes and return the changed configuration.
+    pub fn forget(self) -> gix_config::File<'static> {
+        self.config
+    }
+}
+
+/// Access
+impl FileMut {
+    /// The path to the configuration file that will be written on [`commit()`][Self::commit()].
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Set the value at `key` to `new_value`, possibly creating the section if it doesn't exist yet, or overriding the most recent existing
+    /// value, which will be returned.
+    #[momo]
+    pub fn set_value<'b>(
+        &mut self,
+        key: &'static dyn crate::config::tree::Key,
+        new_value: impl Into<&'b BStr>,
+    ) -> Result<Option<BString>, crate::config::set_value::Error> {
+        crate::config::snapshot::set_value(&mut self.config, key, new_value.into())
+    }
+
+    /// Set the value at `key` to `new_value` in the given `subsection`, possibly creating the section and sub-section if it doesn't exist yet,
+    /// or overriding the most recent existing value, which will be returned.
+    #[momo]
+    pub fn set_subsection_value<'a, 'b>(
+        &mut self,
+        key: &'static dyn crate::config::tree::Key,
+        subsection: impl Into<&'a BStr>,
+        new_value: impl Into<&'b BStr>,
+    ) -> Result<Option<BString>, crate::config::set_value::Error> {
+        crate::config::snapshot::set_subsection_value(&mut self.config, key, subsection.into(), new_value.into())
+    }
+}
+
+impl std::fmt::Debug for FileMut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.config.to_string())
+    }
+}
+
+impl std::ops::Deref for FileMut {
+    type Target = gix_config::File<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.config
+    }
+}
+
+impl std::ops::DerefMut for FileMut {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.config
+    }
+}
@@ -4,6 +4,8 @@ use gix_features::threading::OnceCell;
 use crate::{bstr::BString, repository::identity, Repository};
 
 pub(crate) mod cache;
+///
+pub mod file_mut;
 mod snapshot;
 #[cfg(feature = "credentials")]
 pub use snapshot::credential_helpers;
@@ -28,13 +30,21 @@ pub struct Snapshot<'repo> {
 /// Note that these values won't update even if the underlying file(s) change.
 ///
 /// Use [`forget()`][Self::forget()] to not apply any of the changes.
-// TODO: make it possible to load snapshots with reloading via .config() and write mutated snapshots back to disk which should be the way
-//       to affect all instances of a repo, probably via `config_mut()` and `config_mut_at()`.
+/// To persist changes, use [`Repository::config_file_mut()`] instead.
 pub struct SnapshotMut<'repo> {
     pub(crate) repo: Option<&'repo mut Repository>,
     pub(crate) config: gix_config::File<'static>,
 }
 
+/// A single configuration file loaded without its includes for modification, with comments and formatting preserved.
+///
+/// Use [`commit()`][Self::commit()] to write the changes back to disk, which replaces the file atomically. Dropping it discards all changes.
+/// Note that changes won't be visible to repository instances that were opened before they were committed.
+pub struct FileMut {
+    pub(crate) path: std::path::PathBuf,
+    pub(crate) config: gix_config::File<'static>,
+}
+
 /// A utility structure created by [`SnapshotMut::commit_auto_rollback()`] that restores the previous configuration on drop.
 pub struct CommitAutoRollback<'repo> {
     pub(crate) repo: Option<&'repo mut Repository>,
@@ -64,6 +74,39 @@ pub mod set_value {
     }
 }
 
+///
+#[cfg(feature = "worktree-mutation")]
+pub mod update_file {
+    use std::path::PathBuf;
+
+    /// The error returned when changing values in a configuration file on disk.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Open(#[from] crate::config::file_mut::open::Error),
+        #[error(transparent)]
+        SetValue(#[from] gix_config::file::set_raw_value::Error),
+        #[error(transparent)]
+        Write(#[from] crate::config::file_mut::commit::Error),
+    }
+
+    /// Load the configuration file at `path` without resolving includes, or create a new one if it doesn't exist,
+    /// call `edit` on it and write it back atomically. `source` is the kind of configuration file at `path`.
+    pub(crate) fn update_file(
+        path: PathBuf,
+        source: gix_config::Source,
+        edit: impl FnOnce(&mut gix_config::File<'static>) -> Result<(), gix_config::file::set_raw_value::Error>,
+    ) -> Result<(), Error> {
+        let mut config = crate::config::FileMut::at(path, source)?;
+        edit(&mut config)?;
+        config.commit()?;
+        Ok(())
+    }
+}
+#[cfg(feature = "worktree-mutation")]
+pub(crate) use update_file::update_file;
+
 /// The error returned when failing to initialize the repository configuration.
 ///
 /// This configuration is on the critical path when opening a repository.
@@ -118,8 +161,6 @@ pub mod diff {
         pub enum Error {
             #[error("Unknown diff algorithm named '{name}'")]
             Unknown { name: BString },
-            #[error("The '{name}' algorithm is not yet implemented")]
-            Unimplemented { name: BString },
         }
     }
 
@@ -167,6 +208,27 @@ pub mod stat_options {
     }
 }
 
+///
+#[cfg(feature = "index")]
+pub mod index_write_options {
+    /// The error produced when collecting options for writing the index, and returned by
+    /// [Repository::index_write_options()](crate::Repository::index_write_options()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ConfigBoolean(#[from] super::boolean::Error),
+        #[error(transparent)]
+        ConfigUnsignedInteger(#[from] super::unsigned_integer::Error),
+        #[error(transparent)]
+        ConfigTime(#[from] super::time::Error),
+        #[error(transparent)]
+        IndexVersion(#[from] super::key::GenericError),
+        #[error(transparent)]
+        IndexThreads(#[from] super::key::GenericErrorWithValue),
+    }
+}
+
 ///
 #[cfg(feature = "attributes")]
 pub mod checkout_options {
@@ -600,7 +662,7 @@ pub(crate) struct Cache {
     pub lenient_config: bool,
     #[cfg_attr(not(feature = "worktree-mutation"), allow(dead_code))]
     attributes: crate::open::permissions::Attributes,
-    environment: crate::open::permissions::Environment,
+    pub(crate) environment: crate::open::permissions::Environment,
     // TODO: make core.precomposeUnicode available as well.
 }
 
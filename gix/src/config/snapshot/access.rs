@@ -128,19 +128,7 @@ impl<'repo> SnapshotMut<'repo> {
         key: &'static dyn crate::config::tree::Key,
         new_value: impl Into<&'b BStr>,
     ) -> Result<Option<BString>, crate::config::set_value::Error> {
-        if let Some(crate::config::tree::SubSectionRequirement::Parameter(_)) = key.subsection_requirement() {
-            return Err(crate::config::set_value::Error::SubSectionRequired);
-        }
-        let value = new_value.into();
-        key.validate(value)?;
-        let section = key.section();
-        let current = match section.parent() {
-            Some(parent) => self
-                .config
-                .set_raw_value(parent.name(), Some(section.name().into()), key.name(), value)?,
-            None => self.config.set_raw_value(section.name(), None, key.name(), value)?,
-        };
-        Ok(current.map(std::borrow::Cow::into_owned))
+        set_value(&mut self.config, key, new_value.into())
     }
 
     /// Set the value at `key` to `new_value` in the given `subsection`, possibly creating the section and sub-section if it doesn't exist yet,
@@ -152,20 +140,7 @@ impl<'repo> SnapshotMut<'repo> {
         subsection: impl Into<&'a BStr>,
         new_value: impl Into<&'b BStr>,
     ) -> Result<Option<BString>, crate::config::set_value::Error> {
-        if let Some(crate::config::tree::SubSectionRequirement::Never) = key.subsection_requirement() {
-            return Err(crate::config::set_value::Error::SubSectionForbidden);
-        }
-        let value = new_value.into();
-        key.validate(value)?;
-
-        let name = key
-            .full_name(Some(subsection.into()))
-            .expect("we know it needs a subsection");
-        let key = gix_config::parse::key((**name).as_bstr()).expect("statically known keys can always be parsed");
-        let current =
-            self.config
-                .set_raw_value(key.section_name, key.subsection_name, key.value_name.to_owned(), value)?;
-        Ok(current.map(std::borrow::Cow::into_owned))
+        set_subsection_value(&mut self.config, key, subsection.into(), new_value.into())
     }
 
     pub(crate) fn commit_inner(
@@ -210,3 +185,37 @@ impl<'repo> CommitAutoRollback<'repo> {
         Ok(repo)
     }
 }
+
+pub(crate) fn set_value(
+    config: &mut gix_config::File<'static>,
+    key: &'static dyn crate::config::tree::Key,
+    value: &BStr,
+) -> Result<Option<BString>, crate::config::set_value::Error> {
+    if let Some(crate::config::tree::SubSectionRequirement::Parameter(_)) = key.subsection_requirement() {
+        return Err(crate::config::set_value::Error::SubSectionRequired);
+    }
+    key.validate(value)?;
+    let section = key.section();
+    let current = match section.parent() {
+        Some(parent) => config.set_raw_value(parent.name(), Some(section.name().into()), key.name(), value)?,
+        None => config.set_raw_value(section.name(), None, key.name(), value)?,
+    };
+    Ok(current.map(std::borrow::Cow::into_owned))
+}
+
+pub(crate) fn set_subsection_value(
+    config: &mut gix_config::File<'static>,
+    key: &'static dyn crate::config::tree::Key,
+    subsection: &BStr,
+    value: &BStr,
+) -> Result<Option<BString>, crate::config::set_value::Error> {
+    if let Some(crate::config::tree::SubSectionRequirement::Never) = key.subsection_requirement() {
+        return Err(crate::config::set_value::Error::SubSectionForbidden);
+    }
+    key.validate(value)?;
+
+    let name = key.full_name(Some(subsection)).expect("we know it needs a subsection");
+    let key = gix_config::parse::key((**name).as_bstr()).expect("statically known keys can always be parsed");
+    let current = config.set_raw_value(key.section_name, key.subsection_name, key.value_name.to_owned(), value)?;
+    Ok(current.map(std::borrow::Cow::into_owned))
+}
@@ -4,3 +4,4 @@ mod access;
 ///
 #[cfg(feature = "credentials")]
 pub mod credential_helpers;
+pub(crate) use access::{set_subsection_value, set_value};
@@ -18,6 +18,9 @@ pub(crate) mod root {
     impl Tree {
         /// The `author` section.
         pub const AUTHOR: sections::Author = sections::Author;
+        /// The `blame` section.
+        #[cfg(feature = "blame")]
+        pub const BLAME: sections::Blame = sections::Blame;
         /// The `branch` section.
         pub const BRANCH: sections::Branch = sections::Branch;
         /// The `checkout` section.
@@ -35,6 +38,8 @@ pub(crate) mod root {
         pub const DIFF: sections::Diff = sections::Diff;
         /// The `extensions` section.
         pub const EXTENSIONS: sections::Extensions = sections::Extensions;
+        /// The `feature` section.
+        pub const FEATURE: sections::Feature = sections::Feature;
         /// The `fetch` section.
         pub const FETCH: sections::Fetch = sections::Fetch;
         /// The `gitoxide` section.
@@ -57,8 +62,12 @@ pub(crate) mod root {
         pub const REMOTE: sections::Remote = sections::Remote;
         /// The `safe` section.
         pub const SAFE: sections::Safe = sections::Safe;
+        /// The `splitIndex` section.
+        pub const SPLIT_INDEX: sections::SplitIndex = sections::SplitIndex;
         /// The `ssh` section.
         pub const SSH: sections::Ssh = sections::Ssh;
+        /// The `submodule` section.
+        pub const SUBMODULE: sections::Submodule = sections::Submodule;
         /// The `user` section.
         pub const USER: sections::User = sections::User;
         /// The `url` section.
@@ -68,6 +77,8 @@ pub(crate) mod root {
         pub fn sections(&self) -> &[&dyn Section] {
             &[
                 &Self::AUTHOR,
+                #[cfg(feature = "blame")]
+                &Self::BLAME,
                 &Self::BRANCH,
                 &Self::CHECKOUT,
                 &Self::CLONE,
@@ -77,6 +88,7 @@ pub(crate) mod root {
                 #[cfg(feature = "blob-diff")]
                 &Self::DIFF,
                 &Self::EXTENSIONS,
+                &Self::FEATURE,
                 &Self::FETCH,
                 &Self::GITOXIDE,
                 &Self::HTTP,
@@ -88,7 +100,9 @@ pub(crate) mod root {
                 &Self::PUSH,
                 &Self::REMOTE,
                 &Self::SAFE,
+                &Self::SPLIT_INDEX,
                 &Self::SSH,
+                &Self::SUBMODULE,
                 &Self::USER,
                 &Self::URL,
             ]
@@ -97,10 +111,12 @@ pub(crate) mod root {
 }
 
 mod sections;
+#[cfg(feature = "blame")]
+pub use sections::Blame;
 pub use sections::{
-    branch, checkout, core, credential, extensions, fetch, gitoxide, http, index, protocol, push, remote, ssh, Author,
-    Branch, Checkout, Clone, Committer, Core, Credential, Extensions, Fetch, Gitoxide, Http, Index, Init, Mailmap,
-    Pack, Protocol, Push, Remote, Safe, Ssh, Url, User,
+    branch, checkout, core, credential, extensions, fetch, gitoxide, http, index, protocol, push, remote, split_index,
+    ssh, Author, Branch, Checkout, Clone, Committer, Core, Credential, Extensions, Feature, Fetch, Gitoxide, Http,
+    Index, Init, Mailmap, Pack, Protocol, Push, Remote, Safe, SplitIndex, Ssh, Submodule, Url, User,
 };
 #[cfg(feature = "blob-diff")]
 pub use sections::{diff, Diff};
@@ -68,6 +68,21 @@ impl Core {
     /// The `core.sshCommand` key.
     pub const SSH_COMMAND: keys::Executable = keys::Executable::new_executable("sshCommand", &config::Tree::CORE)
         .with_environment_override("GIT_SSH_COMMAND");
+    /// The `core.fsmonitor` key.
+    pub const FSMONITOR: keys::Program = keys::Program::new_program("fsmonitor", &config::Tree::CORE)
+        .with_deviation("only hook programs are supported, the builtin filesystem monitor daemon isn't");
+    /// The `core.fsmonitorHookVersion` key.
+    pub const FSMONITOR_HOOK_VERSION: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("fsmonitorHookVersion", &config::Tree::CORE);
+    /// The `core.untrackedCache` key, which is `keep` or a boolean.
+    pub const UNTRACKED_CACHE: keys::Any = keys::Any::new("untrackedCache", &config::Tree::CORE);
+    /// The `core.sparseCheckout` key.
+    pub const SPARSE_CHECKOUT: keys::Boolean = keys::Boolean::new_boolean("sparseCheckout", &config::Tree::CORE);
+    /// The `core.sparseCheckoutCone` key.
+    pub const SPARSE_CHECKOUT_CONE: keys::Boolean =
+        keys::Boolean::new_boolean("sparseCheckoutCone", &config::Tree::CORE);
+    /// The `core.splitIndex` key.
+    pub const SPLIT_INDEX: keys::Boolean = keys::Boolean::new_boolean("splitIndex", &config::Tree::CORE);
     /// The `core.useReplaceRefs` key.
     pub const USE_REPLACE_REFS: keys::Boolean = keys::Boolean::new_boolean("useReplaceRefs", &config::Tree::CORE)
         .with_environment_override("GIT_NO_REPLACE_OBJECTS");
@@ -120,6 +135,12 @@ impl Section for Core {
             &Self::EXCLUDES_FILE,
             &Self::ATTRIBUTES_FILE,
             &Self::SSH_COMMAND,
+            &Self::FSMONITOR,
+            &Self::FSMONITOR_HOOK_VERSION,
+            &Self::UNTRACKED_CACHE,
+            &Self::SPARSE_CHECKOUT,
+            &Self::SPARSE_CHECKOUT_CONE,
+            &Self::SPLIT_INDEX,
             &Self::USE_REPLACE_REFS,
             &Self::COMMIT_GRAPH,
             #[cfg(feature = "attributes")]
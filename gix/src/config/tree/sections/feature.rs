@@ -0,0 +1,20 @@
+use crate::{
+    config,
+    config::tree::{keys, Feature, Key, Section},
+};
+
+impl Feature {
+    /// The `feature.manyFiles` key.
+    pub const MANY_FILES: keys::Boolean = keys::Boolean::new_boolean("manyFiles", &config::Tree::FEATURE)
+        .with_note("only `index.version=4` is implied for now, other implied values remain unaffected");
+}
+
+impl Section for Feature {
+    fn name(&self) -> &str {
+        "feature"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::MANY_FILES]
+    }
+}
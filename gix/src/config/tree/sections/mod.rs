@@ -5,6 +5,13 @@
 pub struct Author;
 mod author;
 
+/// The `blame` top-level section.
+#[derive(Copy, Clone, Default)]
+#[cfg(feature = "blame")]
+pub struct Blame;
+#[cfg(feature = "blame")]
+mod blame;
+
 /// The `branch` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Branch;
@@ -47,6 +54,11 @@ pub mod diff;
 pub struct Extensions;
 pub mod extensions;
 
+/// The `feature` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Feature;
+mod feature;
+
 /// The `fetch` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Fetch;
@@ -101,11 +113,21 @@ pub mod remote;
 pub struct Safe;
 mod safe;
 
+/// The `splitIndex` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct SplitIndex;
+pub mod split_index;
+
 /// The `ssh` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Ssh;
 pub mod ssh;
 
+/// The `submodule` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Submodule;
+mod submodule;
+
 /// The `user` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct User;
@@ -0,0 +1,78 @@
+use crate::{
+    config,
+    config::tree::{keys, Key, Section, SplitIndex},
+};
+
+impl SplitIndex {
+    /// The `splitIndex.maxPercentChange` key.
+    pub const MAX_PERCENT_CHANGE: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("maxPercentChange", &config::Tree::SPLIT_INDEX);
+    /// The `splitIndex.sharedIndexExpire` key.
+    pub const SHARED_INDEX_EXPIRE: SharedIndexExpire = SharedIndexExpire::new_with_validate(
+        "sharedIndexExpire",
+        &config::Tree::SPLIT_INDEX,
+        validate::SharedIndexExpire,
+    );
+}
+
+/// The `splitIndex.sharedIndexExpire` key.
+pub type SharedIndexExpire = keys::Any<validate::SharedIndexExpire>;
+
+mod shared_index_expire {
+    use std::{borrow::Cow, time::SystemTime};
+
+    use crate::{bstr::BStr, config, config::tree::split_index::SharedIndexExpire};
+
+    impl SharedIndexExpire {
+        /// Convert `value` into the time before which unused shared indices expire, with `now` as reference for relative dates,
+        /// or `None` if they never expire.
+        ///
+        /// Besides the usual date formats, `never` and `now` are supported, and dots may separate the words of relative dates
+        /// like in `2.weeks.ago`.
+        pub fn try_into_expiry(
+            &'static self,
+            value: Cow<'_, BStr>,
+            now: SystemTime,
+        ) -> Result<Option<SystemTime>, config::time::Error> {
+            let to_error = |err| config::time::Error::from_value(self, value.clone().into_owned()).with_source(err);
+            let input = std::str::from_utf8(value.as_ref()).map_err(|_| {
+                to_error(gix_date::parse::Error::InvalidDateString {
+                    input: value.to_string(),
+                })
+            })?;
+            Ok(match input {
+                "never" | "false" => None,
+                "now" => Some(now),
+                _ => {
+                    let time = gix_date::parse(input, Some(now))
+                        .or_else(|err| gix_date::parse(&input.replace('.', " "), Some(now)).map_err(|_| err))
+                        .map_err(to_error)?;
+                    let seconds = std::time::Duration::from_secs(time.seconds.max(0) as u64);
+                    Some(SystemTime::UNIX_EPOCH + seconds)
+                }
+            })
+        }
+    }
+}
+
+impl Section for SplitIndex {
+    fn name(&self) -> &str {
+        "splitIndex"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::MAX_PERCENT_CHANGE, &Self::SHARED_INDEX_EXPIRE]
+    }
+}
+
+mod validate {
+    use crate::{bstr::BStr, config::tree::keys};
+
+    pub struct SharedIndexExpire;
+    impl keys::Validate for SharedIndexExpire {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::SplitIndex::SHARED_INDEX_EXPIRE.try_into_expiry(value.into(), std::time::SystemTime::now())?;
+            Ok(())
+        }
+    }
+}
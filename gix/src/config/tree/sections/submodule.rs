@@ -0,0 +1,20 @@
+use crate::{
+    config,
+    config::tree::{keys, Key, Section, Submodule},
+};
+
+impl Submodule {
+    /// The `submodule.recurse` key.
+    pub const RECURSE: keys::Boolean = keys::Boolean::new_boolean("recurse", &config::Tree::SUBMODULE)
+        .with_note("also used to decide if submodules should be checked out when cloning");
+}
+
+impl Section for Submodule {
+    fn name(&self) -> &str {
+        "submodule"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::RECURSE]
+    }
+}
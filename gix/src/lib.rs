@@ -101,6 +101,8 @@ pub use gix_commitgraph as commitgraph;
 #[cfg(feature = "credentials")]
 pub use gix_credentials as credentials;
 pub use gix_date as date;
+#[cfg(feature = "status")]
+pub use gix_dir as dir;
 pub use gix_features as features;
 use gix_features::threading::OwnShared;
 pub use gix_features::{
@@ -118,6 +120,8 @@ pub use gix_ignore as ignore;
 #[cfg(feature = "index")]
 pub use gix_index as index;
 pub use gix_lock as lock;
+#[cfg(feature = "merge")]
+pub use gix_merge as merge;
 #[cfg(feature = "credentials")]
 pub use gix_negotiate as negotiate;
 pub use gix_object as objs;
@@ -131,6 +135,8 @@ pub use gix_ref as refs;
 pub use gix_refspec as refspec;
 pub use gix_revwalk as revwalk;
 pub use gix_sec as sec;
+#[cfg(feature = "rebase")]
+pub use gix_sequencer as sequencer;
 #[cfg(feature = "status")]
 pub use gix_status as status;
 pub use gix_tempfile as tempfile;
@@ -193,6 +199,34 @@ pub mod push;
 ///
 pub mod diff;
 
+///
+#[cfg(feature = "apply")]
+pub mod apply;
+
+///
+#[cfg(feature = "bisect")]
+pub mod bisect;
+
+///
+#[cfg(feature = "blame")]
+pub mod blame;
+
+///
+#[cfg(feature = "merge")]
+pub mod cherry_pick;
+
+///
+#[cfg(feature = "range-diff")]
+pub mod range_diff;
+
+///
+#[cfg(feature = "rebase")]
+pub mod rebase;
+
+///
+#[cfg(feature = "stash")]
+pub mod stash;
+
 /// See [`ThreadSafeRepository::discover()`], but returns a [`Repository`] instead.
 ///
 /// # Note
@@ -154,6 +154,11 @@ impl<'repo> Commit<'repo> {
             first_parent: false,
             id_as_fallback: false,
             max_candidates: 10,
+            patterns: Default::default(),
+            #[cfg(feature = "excludes")]
+            dirty_suffix: None,
+            #[cfg(feature = "excludes")]
+            broken_suffix: None,
         }
     }
 
@@ -0,0 +1,194 @@
+/// Assign each of `column_count` columns to one of `row_count` rows such that the sum of the `cost` of all assignments
+/// is minimal, with the cost of assigning `column` to `row` at `cost[column + column_count * row]`.
+///
+/// Return the row of each column and the column of each row, with `-1` for columns and rows that aren't assigned.
+///
+/// This is the algorithm by Jonker and Volgenant, implemented just like `git` does to produce the same assignments
+/// even if there are multiple optimal ones.
+#[allow(clippy::needless_range_loop, clippy::mut_range_bound)]
+pub(crate) fn compute_assignment(column_count: usize, row_count: usize, cost: &[i32]) -> (Vec<isize>, Vec<isize>) {
+    let cost_at = |column: usize, row: usize| cost[column + column_count * row];
+    if column_count < 2 {
+        return (vec![0; column_count], vec![0; row_count]);
+    }
+
+    let mut column2row = vec![-1isize; column_count];
+    let mut row2column = vec![-1isize; row_count];
+    let mut v = vec![0i32; column_count];
+
+    // Column reduction.
+    for j in (0..column_count).rev() {
+        let mut i1 = 0;
+        for i in 1..row_count {
+            if cost_at(j, i1) > cost_at(j, i) {
+                i1 = i;
+            }
+        }
+        v[j] = cost_at(j, i1);
+        if row2column[i1] == -1 {
+            row2column[i1] = j as isize;
+            column2row[j] = i1 as isize;
+        } else {
+            if row2column[i1] >= 0 {
+                row2column[i1] = -2 - row2column[i1];
+            }
+            column2row[j] = -1;
+        }
+    }
+
+    // Reduction transfer.
+    let mut free_row = Vec::with_capacity(row_count);
+    for i in 0..row_count {
+        let j1 = row2column[i];
+        if j1 == -1 {
+            free_row.push(i);
+        } else if j1 < -1 {
+            row2column[i] = -2 - j1;
+        } else {
+            let j1 = j1 as usize;
+            let mut min = cost_at(usize::from(j1 == 0), i);
+            for j in 1..column_count {
+                if j != j1 && min > cost_at(j, i) - v[j] {
+                    min = cost_at(j, i) - v[j];
+                }
+            }
+            v[j1] -= min;
+        }
+    }
+    if free_row.len() == row_count.saturating_sub(column_count) {
+        return (column2row, row2column);
+    }
+
+    // Augmenting row reduction.
+    for _phase in 0..2 {
+        let mut k = 0;
+        let saved_free_count = free_row.len();
+        let mut free_count = 0;
+        while k < saved_free_count {
+            let i = free_row[k];
+            k += 1;
+            let mut j1 = 0;
+            let mut u1 = cost_at(j1, i) - v[j1];
+            let mut j2 = None;
+            let mut u2 = i32::MAX;
+            for j in 1..column_count {
+                let c = cost_at(j, i) - v[j];
+                if u2 > c {
+                    if u1 < c {
+                        u2 = c;
+                        j2 = Some(j);
+                    } else {
+                        u2 = u1;
+                        u1 = c;
+                        j2 = Some(j1);
+                        j1 = j;
+                    }
+                }
+            }
+            let j2 = j2.unwrap_or_else(|| {
+                u2 = u1;
+                j1
+            });
+
+            let mut i0 = column2row[j1];
+            if u1 < u2 {
+                v[j1] -= u2 - u1;
+            } else if i0 >= 0 {
+                j1 = j2;
+                i0 = column2row[j1];
+            }
+
+            if i0 >= 0 {
+                if u1 < u2 {
+                    k -= 1;
+                    free_row[k] = i0 as usize;
+                } else {
+                    free_row[free_count] = i0 as usize;
+                    free_count += 1;
+                }
+            }
+            row2column[i] = j1 as isize;
+            column2row[j1] = i as isize;
+        }
+        free_row.truncate(free_count);
+    }
+
+    // Augmentation.
+    let mut d = vec![0i32; column_count];
+    let mut pred = vec![0usize; column_count];
+    let mut col = vec![0usize; column_count];
+    for &i1 in &free_row {
+        let (mut low, mut up) = (0, 0);
+        for j in 0..column_count {
+            d[j] = cost_at(j, i1) - v[j];
+            pred[j] = i1;
+            col[j] = j;
+        }
+
+        let (mut j, last, min) = 'search: loop {
+            let last = low;
+            let mut min = d[col[up]];
+            up += 1;
+            for k in up..column_count {
+                let j = col[k];
+                let c = d[j];
+                if c <= min {
+                    if c < min {
+                        up = low;
+                        min = c;
+                    }
+                    col[k] = col[up];
+                    col[up] = j;
+                    up += 1;
+                }
+            }
+            for &j in &col[low..up] {
+                if column2row[j] == -1 {
+                    break 'search (j, last, min);
+                }
+            }
+
+            // Scan a row.
+            while low != up {
+                let j1 = col[low];
+                low += 1;
+                let i = column2row[j1] as usize;
+                let u1 = cost_at(j1, i) - v[j1] - min;
+                for k in up..column_count {
+                    let j = col[k];
+                    let c = cost_at(j, i) - v[j] - u1;
+                    if c < d[j] {
+                        d[j] = c;
+                        pred[j] = i;
+                        if c == min {
+                            if column2row[j] == -1 {
+                                break 'search (j, last, min);
+                            }
+                            col[k] = col[up];
+                            col[up] = j;
+                            up += 1;
+                        }
+                    }
+                }
+            }
+        };
+
+        // Update the column prices.
+        for &j1 in &col[..last] {
+            v[j1] += d[j1] - min;
+        }
+
+        // Augment.
+        loop {
+            let i = pred[j];
+            column2row[j] = i as isize;
+            let previous = row2column[i];
+            row2column[i] = j as isize;
+            if i == i1 {
+                break;
+            }
+            j = previous as usize;
+        }
+    }
+    (column2row, row2column)
+}
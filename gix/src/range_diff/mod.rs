@@ -0,0 +1,168 @@
+use std::io::Write;
+
+use gix_hash::ObjectId;
+
+use crate::bstr::{BStr, BString, ByteSlice};
+
+mod assignment;
+pub(crate) use assignment::compute_assignment;
+
+/// The default percentage of the size of a patch that it may cost to pair it with another one, as used by `git range-diff`.
+pub const DEFAULT_CREATION_FACTOR: u32 = 60;
+
+/// Options for use in [`Repository::range_diff()`](crate::Repository::range_diff()).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Options {
+    /// The percentage of the amount of lines in the diff of a commit that may differ from the diff of another commit
+    /// for both to be paired instead of showing one as removed and the other as added, like `git range-diff --creation-factor`.
+    ///
+    /// Higher values pair more commits, even if their changes have little in common.
+    pub creation_factor: u32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            creation_factor: DEFAULT_CREATION_FACTOR,
+        }
+    }
+}
+
+/// A commit of a series, along with the textual representation of its changes that is compared to other commits.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Patch {
+    /// The id of the commit.
+    pub commit_id: ObjectId,
+    /// The shortest unambiguous prefix of [`commit_id`](Patch::commit_id), for display.
+    pub short_id: gix_hash::Prefix,
+    /// The subject of the commit message.
+    pub subject: BString,
+    /// The patch id of the commit as computed by [`Repository::patch_id()`](crate::Repository::patch_id()), or `None` if the
+    /// commit doesn't change anything.
+    pub patch_id: Option<ObjectId>,
+    /// The author, the commit message and the diff of the commit in the format `git range-diff` uses to compare commits,
+    /// with `## <section> ##` headers and hunk headers without line numbers.
+    pub text: BString,
+    /// The position in [`text`](Patch::text) at which the diff starts.
+    pub diff_start: usize,
+    /// The amount of lines in the diff of the commit, which determines what it costs to not pair it.
+    pub diff_lines: usize,
+}
+
+impl Patch {
+    /// Return the part of our [`text`](Patch::text) that contains the diff, without the author and the commit message.
+    pub fn diff(&self) -> &BStr {
+        self.text[self.diff_start..].as_bstr()
+    }
+}
+
+/// How two paired commits relate to each other.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Status {
+    /// Both commits have the same author, message and diff, shown as `=`.
+    Unchanged,
+    /// The commits differ in their author, message or diff, shown as `!`.
+    Changed,
+    /// The commit is only in the old series, shown as `<`.
+    Removed,
+    /// The commit is only in the new series, shown as `>`.
+    Added,
+}
+
+impl Status {
+    /// Return the character `git range-diff` uses for this status.
+    pub fn as_char(&self) -> char {
+        match self {
+            Status::Unchanged => '=',
+            Status::Changed => '!',
+            Status::Removed => '<',
+            Status::Added => '>',
+        }
+    }
+}
+
+/// A commit of the old series along with the commit of the new series it was paired with.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Pair {
+    /// The index of the commit in [`Outcome::old`], or `None` if the commit was [added](Status::Added).
+    pub old: Option<usize>,
+    /// The index of the commit in [`Outcome::new`], or `None` if the commit was [removed](Status::Removed).
+    pub new: Option<usize>,
+    /// How both commits relate to each other.
+    pub status: Status,
+    /// The diff between the [text](Patch::text) of both commits if they are [changed](Status::Changed), with hunk headers
+    /// showing the section they are in instead of line numbers, or an empty string otherwise.
+    pub diff: BString,
+}
+
+/// The outcome of [`Repository::range_diff()`](crate::Repository::range_diff()).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Outcome {
+    /// The commits of the old series, oldest first.
+    pub old: Vec<Patch>,
+    /// The commits of the new series, oldest first.
+    pub new: Vec<Patch>,
+    /// All commits of both series, paired if possible, in the order of the new series with commits that are only in the
+    /// old series shown once all commits before them were shown.
+    pub pairs: Vec<Pair>,
+}
+
+impl Outcome {
+    /// Write all pairs to `out` like `git range-diff` does, along with the diff of changed pairs if `with_diffs` is `true`.
+    pub fn write_to(&self, mut out: impl Write, with_diffs: bool) -> std::io::Result<()> {
+        let width = (1 + self.old.len().max(self.new.len())).to_string().len();
+        let mut dashes = None;
+        for pair in &self.pairs {
+            let old = pair.old.map(|index| (index, &self.old[index]));
+            let new = pair.new.map(|index| (index, &self.new[index]));
+            let (_, shown) = new.or(old).expect("pairs always have at least one side");
+            let dashes = dashes.get_or_insert_with(|| "-".repeat(shown.short_id.hex_len()));
+            write_side(&mut out, old, width, dashes)?;
+            write!(out, " {} ", pair.status.as_char())?;
+            write_side(&mut out, new, width, dashes)?;
+            out.write_all(b" ")?;
+            out.write_all(&shown.subject)?;
+            out.write_all(b"\n")?;
+            if with_diffs {
+                out.write_all(&pair.diff)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_side(out: &mut impl Write, side: Option<(usize, &Patch)>, width: usize, dashes: &str) -> std::io::Result<()> {
+    match side {
+        Some((index, patch)) => write!(out, "{:>width$}:  {}", index + 1, patch.short_id),
+        None => write!(out, "{:>width$}:  {dashes}", "-"),
+    }
+}
+
+/// The error returned by [`Repository::range_diff()`](crate::Repository::range_diff()) and
+/// [`Repository::patch_id()`](crate::Repository::patch_id()).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    RevWalk(#[from] crate::revision::walk::Error),
+    #[error(transparent)]
+    RevWalkIter(#[from] gix_traverse::commit::ancestors::Error),
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToCommit(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    Commit(#[from] crate::object::commit::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    Decode(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+    #[error(transparent)]
+    DiffRenames(#[from] crate::diff::new_rewrites::Error),
+    #[error(transparent)]
+    DiffTree(#[from] crate::object::tree::diff::for_each::Error),
+    #[error("Could not write a diff")]
+    Io(#[from] std::io::Error),
+}
@@ -0,0 +1,184 @@
+pub use gix_rebase::*;
+
+use crate::bstr::BString;
+
+/// Options for use in [`Repository::rebase()`](crate::Repository::rebase()) and
+/// [`Repository::rebase_todo()`](crate::Repository::rebase_todo()).
+#[derive(Default, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Options {
+    /// The commit to apply the commits onto, like `git rebase --onto <commit>`. If `None`, it's the upstream commit.
+    pub onto: Option<gix_hash::ObjectId>,
+    /// If `true`, mark the rebase as interactive, which is what `git rebase --interactive` does, as the todo list
+    /// is expected to be edited by the caller.
+    pub interactive: bool,
+    /// If `true`, move commits whose message starts with `fixup! `, `squash! ` or `amend! ` next to the commit they refer to,
+    /// like `git rebase --autosquash` does.
+    pub autosquash: bool,
+    /// If `true`, update local branches that point to commits that are rebased to point to the rewritten commits,
+    /// like `git rebase --update-refs` does.
+    pub update_refs: bool,
+    /// Control how commits are merged.
+    pub merge: gix_merge::tree::Options,
+}
+
+/// Options for use in [`Repository::rebase_continue()`](crate::Repository::rebase_continue()).
+#[derive(Default, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ContinueOptions {
+    /// The message to use for the commit that is created or amended when continuing, for instance after stopping to
+    /// reword a commit. If `None`, the message of the original commit is used.
+    pub message: Option<BString>,
+    /// Control how the remaining commits are merged.
+    pub merge: gix_merge::tree::Options,
+}
+
+/// The reason for a rebase to stop, which is continued with [`Repository::rebase_continue()`](crate::Repository::rebase_continue())
+/// or [`Repository::rebase_skip()`](crate::Repository::rebase_skip()).
+#[derive(Debug, Clone)]
+pub enum Stop {
+    /// The changes of `commit` couldn't be applied without conflicts, which are recorded in the index and marked in the worktree.
+    ///
+    /// Resolve them and add the resolved files to the index, then continue to commit the result.
+    Conflict {
+        /// The original commit whose changes couldn't be applied.
+        commit: gix_hash::ObjectId,
+        /// The paths that couldn't be merged.
+        conflicts: Vec<gix_merge::tree::Conflict>,
+    },
+    /// The original `commit` was applied and the rebase stopped for amending it, as requested with `edit`.
+    ///
+    /// Changes that are added to the index are amended to the commit when continuing.
+    Edit {
+        /// The original commit that was applied.
+        commit: gix_hash::ObjectId,
+    },
+    /// The original `commit` was applied and the rebase stopped for changing its message, as requested with `reword`.
+    ///
+    /// Pass the new message in [`ContinueOptions::message`] when continuing.
+    Reword {
+        /// The original commit that was applied.
+        commit: gix_hash::ObjectId,
+    },
+    /// The rebase stopped as requested with `break`.
+    Break,
+    /// The command that was requested with `exec` failed.
+    Exec {
+        /// The command that was run.
+        command: BString,
+        /// The status the command exited with.
+        status: std::process::ExitStatus,
+    },
+}
+
+/// The outcome of [`Repository::rebase()`](crate::Repository::rebase()) and the methods to continue it.
+#[derive(Debug, Clone)]
+pub enum Outcome<'repo> {
+    /// All instructions were executed, the rebased branch points to `head` and is checked out again.
+    Finished {
+        /// The commit the rebased branch points to.
+        head: crate::Id<'repo>,
+    },
+    /// The rebase stopped, and its state is stored in the `.git` directory so it can be continued.
+    Stopped(Stop),
+}
+
+/// The error returned by the `rebase*()` methods of [`Repository`](crate::Repository).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Rebases can only be performed in repositories with a worktree")]
+    MissingWorktree,
+    #[error("Cannot rebase without an initial commit")]
+    UnbornHead,
+    #[error("A rebase is already in progress")]
+    InProgress,
+    #[error("No rebase in progress")]
+    NotInProgress,
+    #[error("Only rebases that use the merge backend can be continued")]
+    ApplyBackend,
+    #[error("Cannot rebase with uncommitted changes in the index or in the worktree")]
+    UncommittedChanges,
+    #[error("Cannot continue with unstaged changes in the worktree, add them to the index first")]
+    UnstagedChanges,
+    #[error("Cannot continue while the index has unmerged paths, the first being '{path}'")]
+    Unmerged { path: BString },
+    #[error("The commit {commit} in the todo list doesn't exist or is ambiguous")]
+    UnknownCommit { commit: gix_hash::Prefix },
+    #[error("The label or revision '{name}' in the todo list could not be found")]
+    UnknownLabel { name: BString },
+    #[error("Cannot apply the merge commit {id} without recreating merges")]
+    MergeCommit { id: gix_hash::ObjectId },
+    #[error("Cannot squash or fixup {commit} without a commit to meld it into")]
+    NothingToSquashInto { commit: gix_hash::ObjectId },
+    #[error("'{name}' is not a valid reference name")]
+    InvalidRefName {
+        name: BString,
+        source: gix_validate::reference::name::Error,
+    },
+    #[error("Could not run '{command}'")]
+    Exec { command: BString, source: std::io::Error },
+    #[error("Could not read or write the rebase state in '{}'", path.display())]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    ReadState(#[from] gix_rebase::state::read::Error),
+    #[error(transparent)]
+    CherryPick(#[from] crate::cherry_pick::Error),
+    #[error(transparent)]
+    Merge(#[from] gix_merge::tree::Error),
+    #[error(transparent)]
+    MergeBase(#[from] crate::repository::merge_base::Error),
+    #[error(transparent)]
+    RevWalk(#[from] crate::revision::walk::Error),
+    #[error(transparent)]
+    RevWalkIter(#[from] gix_traverse::commit::ancestors::Error),
+    #[error(transparent)]
+    RevParse(#[from] crate::revision::spec::parse::single::Error),
+    #[error(transparent)]
+    LookupPrefix(#[from] gix_odb::store::prefix::lookup::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    AddToIndex(#[from] crate::worktree::add::Error),
+    #[error(transparent)]
+    IndexWriteOptions(#[from] crate::config::index_write_options::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error(transparent)]
+    WriteTree(#[from] gix_index::write_tree::Error),
+    #[error(transparent)]
+    TreeTraversal(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    UpdateWorktree(#[from] crate::repository::update_worktree::Error),
+    #[error(transparent)]
+    HeadCommit(#[from] crate::reference::head_commit::Error),
+    #[error(transparent)]
+    HeadId(#[from] crate::reference::head_id::Error),
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    FindOptionalReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    IterReferences(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    IterReferencesInit(#[from] crate::reference::iter::init::Error),
+    #[error("Could not obtain a reference during iteration")]
+    IterReference(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    PeelReference(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    Commit(#[from] crate::commit::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    Time(#[from] crate::config::time::Error),
+}
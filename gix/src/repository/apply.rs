@@ -0,0 +1,393 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use gix_index::entry;
+use gix_merge::blob::{Labels, Resolution};
+use gix_object::tree::EntryKind;
+
+use crate::{
+    apply::{Error, File, Location, Options, Outcome},
+    bstr::{BStr, BString, ByteSlice},
+    config::cache::util::ApplyLeniencyDefault,
+    worktree::add::Edit,
+    Repository,
+};
+
+/// The content of a file along with its kind.
+#[derive(Clone)]
+struct Blob {
+    data: Vec<u8>,
+    kind: EntryKind,
+}
+
+/// Patching
+impl Repository {
+    /// Apply `patches`, as obtained by [parsing](crate::apply::parse()) the output of `git diff` or `git format-patch`,
+    /// to the files at `location`, similar to `git apply`, and configure the operation with `options`.
+    ///
+    /// `index` is read and changed unless `location` is [`Location::Worktree`], and is used to record conflicts
+    /// if a [three-way merge](gix_apply::file::Options::three_way) fails to merge a file cleanly, in which case the
+    /// worktree file contains conflict markers. Patches are applied in order, so later patches see the changes of earlier ones.
+    /// Nothing is changed if one patch fails to apply, and unless [rejects](Options::reject) are allowed, this includes
+    /// a single hunk that doesn't apply.
+    ///
+    /// Like `git apply`, patches that affect files beyond a symbolic link are refused, whether the link is in the
+    /// worktree, the `index` or created by an earlier patch, so nothing is read or written outside of the worktree.
+    ///
+    /// Note that files in the worktree are read and written without conversion through the filter pipeline.
+    /// To persist the changes to `index`, [write](gix_index::File::write()) it afterwards using
+    /// [`Repository::index_write_options()`].
+    pub fn apply(
+        &self,
+        patches: &[gix_apply::Patch],
+        index: &mut gix_index::State,
+        location: Location,
+        options: Options,
+    ) -> Result<Outcome, Error> {
+        let location = if options.file.three_way && location == Location::Worktree {
+            Location::WorktreeAndIndex
+        } else {
+            location
+        };
+        let (root, use_index) = match location {
+            Location::Worktree => (Some(self.work_dir().ok_or(Error::MissingWorktree)?), false),
+            Location::Index => (None, true),
+            Location::WorktreeAndIndex => (Some(self.work_dir().ok_or(Error::MissingWorktree)?), true),
+        };
+        let capabilities = self
+            .config
+            .fs_capabilities()
+            .with_lenient_default(self.config.lenient_config)?;
+        let reader = Reader {
+            repo: self,
+            root,
+            use_index,
+            capabilities,
+        };
+
+        // The new state of each touched path, with `None` if it was deleted.
+        let mut changed = BTreeMap::<BString, Option<Blob>>::new();
+        let mut conflicts = Vec::new();
+        let mut outcome = Outcome::default();
+        for patch in patches {
+            for path in patch.old_path.iter().chain(patch.new_path.iter()) {
+                if reader.is_beyond_symlink(path.as_bstr(), &changed, index) {
+                    return Err(Error::BeyondSymlink { path: path.clone() });
+                }
+            }
+            let current = match patch.old_path.as_ref() {
+                Some(path) => reader.read(path.as_bstr(), &changed, index)?,
+                None => match patch.new_path.as_ref() {
+                    Some(path) => reader.read(path.as_bstr(), &changed, index)?,
+                    None => None,
+                },
+            };
+            let labels = Labels {
+                base: Some("base".into()),
+                ours: Some("ours".into()),
+                theirs: Some("theirs".into()),
+            };
+            let (content, file) = match gix_apply::file::apply(
+                patch,
+                current.as_ref().map(|blob| blob.data.as_slice()),
+                |prefix| self.find_blob_by_prefix(prefix),
+                labels,
+                options.file,
+            )? {
+                gix_apply::file::Outcome::Applied { content, rejected } => {
+                    if !rejected.is_empty() && !options.reject {
+                        return Err(Error::Rejected {
+                            path: patch.path().to_owned(),
+                            hunks: rejected,
+                        });
+                    }
+                    (
+                        content,
+                        File {
+                            path: patch.path().to_owned(),
+                            rejected_hunks: rejected,
+                            merge: None,
+                        },
+                    )
+                }
+                gix_apply::file::Outcome::Merged {
+                    content,
+                    resolution,
+                    base,
+                    theirs,
+                } => {
+                    if resolution == Resolution::Conflict {
+                        let kind = current.as_ref().map_or(EntryKind::Blob, |blob| blob.kind);
+                        let mode = mode_from_kind(patch.new_mode.unwrap_or(kind));
+                        let ours = current.as_ref().map(|blob| blob.data.as_slice()).unwrap_or_default();
+                        conflicts.push((
+                            patch.path().to_owned(),
+                            [
+                                Some((self.write_blob(base)?.detach(), mode_from_kind(kind))),
+                                Some((self.write_blob(ours)?.detach(), mode_from_kind(kind))),
+                                Some((self.write_blob(theirs)?.detach(), mode)),
+                            ],
+                        ));
+                    }
+                    (
+                        Some(content),
+                        File {
+                            path: patch.path().to_owned(),
+                            rejected_hunks: Vec::new(),
+                            merge: Some(resolution),
+                        },
+                    )
+                }
+            };
+
+            if let (gix_apply::Operation::Rename { .. }, Some(old_path)) = (patch.operation, patch.old_path.as_ref()) {
+                changed.insert(old_path.clone(), None);
+            }
+            match (content, patch.new_path.as_ref()) {
+                (Some(data), Some(path)) => {
+                    let kind = patch
+                        .new_mode
+                        .or(current.as_ref().map(|blob| blob.kind))
+                        .unwrap_or(EntryKind::Blob);
+                    changed.insert(path.clone(), Some(Blob { data, kind }));
+                }
+                _ => {
+                    changed.insert(patch.path().to_owned(), None);
+                }
+            }
+            outcome.files.push(file);
+        }
+
+        let mut edits = Vec::new();
+        if let Some(root) = root {
+            write_worktree(root, &changed, capabilities)?;
+        }
+        if use_index {
+            for (path, blob) in changed {
+                match blob {
+                    Some(blob) if !conflicts.iter().any(|(conflict, _)| *conflict == path) => {
+                        let stat = match root {
+                            Some(root) => {
+                                let path = root.join(gix_path::from_bstr(path.as_bstr()));
+                                let md = gix_index::fs::Metadata::from_path_no_follow(&path).map_err(|source| {
+                                    Error::Io {
+                                        path: path.clone(),
+                                        source,
+                                    }
+                                })?;
+                                entry::Stat::from_fs(&md).map_err(|source| Error::Time { path, source })?
+                            }
+                            None => entry::Stat::default(),
+                        };
+                        edits.push(Edit::Upsert {
+                            id: self.write_blob(&blob.data)?.detach(),
+                            mode: mode_from_kind(blob.kind),
+                            stat,
+                            path,
+                        });
+                    }
+                    Some(_) => {}
+                    None => edits.push(Edit::Remove { path }),
+                }
+            }
+            for (path, stages) in conflicts {
+                edits.push(Edit::Conflict { path, stages });
+            }
+            crate::worktree::add::apply(index, edits);
+        }
+        Ok(outcome)
+    }
+
+    /// Apply `patches` to the tree with `tree_id` and write all changed trees, returning the id of the new tree along with
+    /// information about each patched file, similar to `git apply --cached` with an index that matches the tree.
+    ///
+    /// This works without worktree, and is useful to apply patches on servers. Note that the tree can't be written
+    /// if a [three-way merge](gix_apply::file::Options::three_way) had conflicts.
+    pub fn apply_to_tree(
+        &self,
+        tree_id: impl Into<gix_hash::ObjectId>,
+        patches: &[gix_apply::Patch],
+        options: Options,
+    ) -> Result<(crate::Id<'_>, Outcome), Error> {
+        let mut index = gix_index::State::from_tree(&tree_id.into(), &self.objects)?;
+        let outcome = self.apply(patches, &mut index, Location::Index, options)?;
+        let tree_id = index.write_tree(|tree| self.write_object(tree).map(crate::Id::detach))?;
+        Ok((crate::Id::from_id(tree_id, self), outcome))
+    }
+
+    /// Return the content of the blob identified by `prefix`, or `None` if it doesn't exist or is ambiguous.
+    fn find_blob_by_prefix(&self, prefix: &gix_hash::Prefix) -> Option<Vec<u8>> {
+        let id = self.objects.lookup_prefix(*prefix, None).ok()??.ok()?;
+        let object = self.find_object(id).ok()?;
+        (object.kind == gix_object::Kind::Blob).then(|| object.detach().data)
+    }
+}
+
+/// Read files from the worktree or the index, taking into account previous changes.
+struct Reader<'a> {
+    repo: &'a Repository,
+    root: Option<&'a Path>,
+    use_index: bool,
+    capabilities: gix_fs::Capabilities,
+}
+
+impl Reader<'_> {
+    /// Return `true` if one of the leading directories of `path` is a symbolic link, like `git apply` checks
+    /// to not read or write outside of the worktree.
+    ///
+    /// Symbolic links created or deleted by previous patches are taken into account.
+    fn is_beyond_symlink(
+        &self,
+        path: &BStr,
+        changed: &BTreeMap<BString, Option<Blob>>,
+        index: &gix_index::State,
+    ) -> bool {
+        path.rfind_iter("/")
+            .map(|pos| path[..pos].as_bstr())
+            .any(|dir| match changed.get(dir) {
+                Some(blob) => blob.as_ref().map_or(false, |blob| blob.kind == EntryKind::Link),
+                None => {
+                    (self.use_index
+                        && index
+                            .entry_by_path_and_stage(dir, 0)
+                            .map_or(false, |entry| entry.mode == entry::Mode::SYMLINK))
+                        || self.root.map_or(false, |root| {
+                            std::fs::symlink_metadata(root.join(gix_path::from_bstr(dir)))
+                                .map_or(false, |md| md.file_type().is_symlink())
+                        })
+                }
+            })
+    }
+
+    fn read(
+        &self,
+        path: &BStr,
+        changed: &BTreeMap<BString, Option<Blob>>,
+        index: &gix_index::State,
+    ) -> Result<Option<Blob>, Error> {
+        if let Some(blob) = changed.get(path) {
+            return Ok(blob.clone());
+        }
+        let from_worktree = self.root.map(|root| self.read_worktree(root, path)).transpose()?;
+        if !self.use_index {
+            return Ok(from_worktree.flatten());
+        }
+        let from_index = match index.entry_by_path_and_stage(path, 0) {
+            Some(entry) => {
+                let kind = match entry.mode.to_tree_entry_mode().map(|mode| mode.kind()) {
+                    Some(kind @ (EntryKind::Blob | EntryKind::BlobExecutable | EntryKind::Link)) => kind,
+                    _ => return Err(Error::UnsupportedMode { path: path.to_owned() }),
+                };
+                let data = self.repo.find_object(entry.id)?.detach().data;
+                Some(Blob { data, kind })
+            }
+            None => None,
+        };
+        if let Some(from_worktree) = from_worktree {
+            if from_worktree.as_ref().map(|blob| &blob.data) != from_index.as_ref().map(|blob| &blob.data) {
+                return Err(Error::WorktreeMismatch { path: path.to_owned() });
+            }
+        }
+        Ok(from_index)
+    }
+
+    fn read_worktree(&self, root: &Path, rela_path: &BStr) -> Result<Option<Blob>, Error> {
+        let path = root.join(gix_path::from_bstr(rela_path));
+        let io_err = |source| Error::Io {
+            path: path.clone(),
+            source,
+        };
+        let md = match gix_index::fs::Metadata::from_path_no_follow(&path) {
+            Ok(md) => md,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(io_err(err)),
+        };
+        Ok(Some(if md.is_symlink() && self.capabilities.symlink {
+            let target = std::fs::read_link(&path).map_err(io_err)?;
+            Blob {
+                data: gix_path::into_bstr(target).into_owned().into(),
+                kind: EntryKind::Link,
+            }
+        } else if md.is_dir() {
+            return Err(Error::UnsupportedMode {
+                path: rela_path.to_owned(),
+            });
+        } else {
+            Blob {
+                data: std::fs::read(&path).map_err(io_err)?,
+                kind: if self.capabilities.executable_bit && md.is_executable() {
+                    EntryKind::BlobExecutable
+                } else {
+                    EntryKind::Blob
+                },
+            }
+        }))
+    }
+}
+
+/// Delete and write all `changed` files in the worktree at `root`, and remove directories that became empty.
+fn write_worktree(
+    root: &Path,
+    changed: &BTreeMap<BString, Option<Blob>>,
+    capabilities: gix_fs::Capabilities,
+) -> Result<(), Error> {
+    let io_err = |path: &Path| {
+        let path = path.to_owned();
+        move |source| Error::Io { path, source }
+    };
+    let remove = |path: &Path| match std::fs::symlink_metadata(path) {
+        Ok(_) => std::fs::remove_file(path).map_err(io_err(path)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(io_err(path)(err)),
+    };
+    for rela_path in changed.iter().filter_map(|(path, blob)| blob.is_none().then_some(path)) {
+        let path = root.join(gix_path::from_bstr(rela_path.as_bstr()));
+        remove(&path)?;
+        let mut dir = path.parent();
+        while let Some(parent) = dir.filter(|dir| *dir != root) {
+            if std::fs::remove_dir(parent).is_err() {
+                break;
+            }
+            dir = parent.parent();
+        }
+    }
+    for (rela_path, blob) in changed
+        .iter()
+        .filter_map(|(path, blob)| blob.as_ref().map(|blob| (path, blob)))
+    {
+        let path: PathBuf = root.join(gix_path::from_bstr(rela_path.as_bstr()));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err(parent))?;
+        }
+        remove(&path)?;
+        if blob.kind == EntryKind::Link && capabilities.symlink {
+            let target = gix_path::try_from_byte_slice(&blob.data).map_err(|_| {
+                io_err(&path)(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "symlink target is not a valid path",
+                ))
+            })?;
+            gix_fs::symlink::create(target, &path).map_err(io_err(&path))?;
+        } else {
+            std::fs::write(&path, &blob.data).map_err(io_err(&path))?;
+            #[cfg(unix)]
+            if blob.kind == EntryKind::BlobExecutable && capabilities.executable_bit {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).map_err(io_err(&path))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn mode_from_kind(kind: EntryKind) -> entry::Mode {
+    match kind {
+        EntryKind::BlobExecutable => entry::Mode::FILE_EXECUTABLE,
+        EntryKind::Link => entry::Mode::SYMLINK,
+        EntryKind::Commit => entry::Mode::COMMIT,
+        EntryKind::Tree => entry::Mode::DIR,
+        EntryKind::Blob => entry::Mode::FILE,
+    }
+}
@@ -0,0 +1,603 @@
+use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+use gix_hash::ObjectId;
+use gix_index::entry;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use crate::{
+    bisect::{Error, Mark, Options, Outcome, State, Terms},
+    bstr::{BStr, BString, ByteSlice},
+    worktree::add,
+    Id, Repository,
+};
+
+/// The prefix of all references that hold the marked commits.
+const REFS_PREFIX: &str = "refs/bisect/";
+
+/// The files in the `.git` directory that make up the bisect state, next to the references in [`REFS_PREFIX`].
+const STATE_FILES: &[&str] = &[
+    "BISECT_EXPECTED_REV",
+    "BISECT_ANCESTORS_OK",
+    "BISECT_LOG",
+    "BISECT_TERMS",
+    "BISECT_NAMES",
+    "BISECT_RUN",
+    "BISECT_FIRST_PARENT",
+    "BISECT_START",
+    "BISECT_HEAD",
+];
+
+/// Bisecting
+impl Repository {
+    /// Return the state of the bisection in progress, or `None` if there is none.
+    ///
+    /// This includes bisections started by `git`.
+    pub fn bisect_status(&self) -> Result<Option<State>, Error> {
+        let Some(start) = read_file(self.git_dir().join("BISECT_START"))? else {
+            return Ok(None);
+        };
+        let start = start.trim().as_bstr().to_owned();
+        if start.is_empty() {
+            return Ok(None);
+        }
+        let terms = match read_file(self.git_dir().join("BISECT_TERMS"))? {
+            Some(terms) => {
+                let mut lines = terms.lines();
+                match (lines.next(), lines.next()) {
+                    (Some(bad), Some(good)) => Terms {
+                        bad: bad.into(),
+                        good: good.into(),
+                    },
+                    _ => Terms::default(),
+                }
+            }
+            None => Terms::default(),
+        };
+
+        let (mut bad, mut good, mut skipped) = (None, Vec::new(), Vec::new());
+        let good_prefix = format!("{}-", terms.good);
+        for reference in self.references()?.prefixed(REFS_PREFIX)? {
+            let mut reference = reference.map_err(Error::IterReference)?;
+            let id = reference.peel_to_id_in_place()?.detach();
+            let name = reference
+                .name()
+                .as_bstr()
+                .strip_prefix(REFS_PREFIX.as_bytes())
+                .expect("prefix filter");
+            if name == terms.bad {
+                bad = Some(id);
+            } else if name.starts_with(good_prefix.as_bytes()) {
+                good.push(id);
+            } else if name.starts_with(b"skip-") {
+                skipped.push(id);
+            }
+        }
+        Ok(Some(State {
+            terms,
+            bad,
+            good,
+            skipped,
+            start,
+            no_checkout: self.git_dir().join("BISECT_HEAD").is_file(),
+        }))
+    }
+
+    /// Start a bisection to find the first commit in the history of `bad` that isn't in the history of any of the `good`
+    /// commits, and configure it with `options`, similar to `git bisect start <bad> <good>…`.
+    ///
+    /// Without `bad` or `good` commits, the bisection waits for them to be marked with [`bisect_mark()`](Self::bisect_mark()).
+    /// Otherwise, the first commit to test is checked out unless [`Options::no_checkout`] is set.
+    /// A bisection that is already in progress is [reset](Self::bisect_reset()) first.
+    ///
+    /// The state is kept in the `.git` directory and the `refs/bisect/` namespace just like `git` does it, so
+    /// bisections can be continued by `git` and vice versa.
+    ///
+    /// # Deviation
+    ///
+    /// Commits are only checked out if the index and the worktree don't have changes compared to `HEAD`, whereas `git`
+    /// carries changes over if they don't conflict.
+    pub fn bisect_start(
+        &self,
+        bad: Option<ObjectId>,
+        good: &[ObjectId],
+        options: Options,
+    ) -> Result<Outcome<'_>, Error> {
+        check_terms(&options.terms)?;
+        if self.bisect_status()?.is_some() {
+            self.bisect_reset()?;
+        }
+        let head = self.head()?;
+        if head.is_unborn() {
+            return Err(Error::UnbornHead);
+        }
+        let head_id = self.head_id()?.detach();
+        let start: BString = match head.referent_name() {
+            Some(name) => name.shorten().to_owned(),
+            None => head_id.to_string().into(),
+        };
+        let no_checkout = options.no_checkout || self.is_bare();
+        if !no_checkout {
+            check_clean(self)?;
+        }
+
+        let terms = options.terms;
+        write_file(
+            self.git_dir().join("BISECT_TERMS"),
+            format!("{}\n{}\n", terms.bad, terms.good),
+        )?;
+        write_file(self.git_dir().join("BISECT_START"), format!("{start}\n"))?;
+        write_file(self.git_dir().join("BISECT_NAMES"), "\n")?;
+        if no_checkout {
+            set_reference(self, "BISECT_HEAD", head_id)?;
+        }
+
+        let mut log = String::new();
+        let mut args = Vec::new();
+        if options.no_checkout {
+            args.push("--no-checkout".to_owned());
+        }
+        if !terms.is_default() {
+            args.push(format!("--term-new={}", terms.bad));
+            args.push(format!("--term-old={}", terms.good));
+        }
+        for (id, mark) in bad
+            .iter()
+            .map(|id| (id, Mark::Bad))
+            .chain(good.iter().map(|id| (id, Mark::Good)))
+        {
+            log.push_str(&mark_commit(self, &terms, mark, *id)?);
+            args.push(id.to_string());
+        }
+        log.push_str("git bisect start");
+        for arg in args {
+            log.push_str(&format!(" '{arg}'"));
+        }
+        log.push('\n');
+        write_file(self.git_dir().join("BISECT_LOG"), log)?;
+        self.bisect_auto_next()
+    }
+
+    /// Mark `commits` with `mark`, or the commit that is currently tested if `commits` is empty, and check out the next
+    /// commit to test if enough commits are known, similar to `git bisect good|bad|skip [<commit>…]`.
+    ///
+    /// Only a single commit can be marked as bad.
+    pub fn bisect_mark(&self, mark: Mark, commits: &[ObjectId]) -> Result<Outcome<'_>, Error> {
+        let state = self.bisect_status()?.ok_or(Error::NotInProgress)?;
+        if mark == Mark::Bad && commits.len() > 1 {
+            return Err(Error::MultipleBad { term: state.terms.bad });
+        }
+        let current;
+        let commits = if commits.is_empty() {
+            current = [current_commit(self, &state)?];
+            &current[..]
+        } else {
+            commits
+        };
+        let mut log = String::new();
+        for id in commits {
+            log.push_str(&mark_commit(self, &state.terms, mark, *id)?);
+            let term = match mark {
+                Mark::Bad => state.terms.bad.as_bstr(),
+                Mark::Good => state.terms.good.as_bstr(),
+                Mark::Skip => "skip".into(),
+            };
+            log.push_str(&format!("git bisect {term} {id}\n"));
+        }
+        append_log(self, &log)?;
+        self.bisect_auto_next()
+    }
+
+    /// Check out the next commit to test, or determine the first bad commit, similar to `git bisect next`.
+    ///
+    /// This is done automatically when marking commits, and only needed to continue a bisection after checking out another
+    /// commit.
+    pub fn bisect_next(&self) -> Result<Outcome<'_>, Error> {
+        let state = self.bisect_status()?.ok_or(Error::NotInProgress)?;
+        let Some(bad) = state.bad.filter(|_| !state.good.is_empty()) else {
+            return Ok(Outcome::Waiting {
+                bad: state.bad.is_some(),
+                good: state.good.len(),
+            });
+        };
+
+        let ancestors_ok = self.git_dir().join("BISECT_ANCESTORS_OK");
+        if !ancestors_ok.is_file() {
+            let merge_bases =
+                gix_revision::merge_base(bad, &state.good, &mut self.revision_graph())?.unwrap_or_default();
+            for merge_base in merge_bases {
+                if merge_base == bad {
+                    return Err(Error::MergeBaseIsBad {
+                        merge_base,
+                        term: state.terms.bad,
+                    });
+                } else if state.good.contains(&merge_base) || state.skipped.contains(&merge_base) {
+                    continue;
+                }
+                checkout(self, &state, merge_base)?;
+                return Ok(Outcome::MergeBase {
+                    commit: Id::from_id(merge_base, self),
+                });
+            }
+            write_file(ancestors_ok, "")?;
+        }
+
+        Ok(
+            match gix_revision::bisect(bad, &state.good, &state.skipped, &mut self.revision_graph())? {
+                gix_revision::bisect::Outcome::Next {
+                    commit,
+                    remaining,
+                    steps,
+                } => {
+                    checkout(self, &state, commit)?;
+                    Outcome::Next {
+                        commit: Id::from_id(commit, self),
+                        remaining,
+                        steps,
+                    }
+                }
+                gix_revision::bisect::Outcome::FirstBad { commit } => {
+                    let summary = summary(self, commit)?;
+                    append_log(
+                        self,
+                        &format!("# first {} commit: [{commit}] {summary}\n", state.terms.bad),
+                    )?;
+                    Outcome::FirstBad {
+                        commit: Id::from_id(commit, self),
+                    }
+                }
+                gix_revision::bisect::Outcome::OnlySkipped { candidates } => {
+                    let mut log = String::from("# only skipped commits left to test\n");
+                    for id in &candidates {
+                        log.push_str(&format!(
+                            "# possible first {} commit: [{id}] {}\n",
+                            state.terms.bad,
+                            summary(self, *id)?
+                        ));
+                    }
+                    append_log(self, &log)?;
+                    Outcome::OnlySkipped { candidates }
+                }
+            },
+        )
+    }
+
+    /// Run `command` in a shell to test the current commit and mark it by its exit code, until the first bad commit is found
+    /// or only skipped commits are left, similar to `git bisect run`.
+    ///
+    /// The exit code `0` marks the commit as good and `125` skips it. All other exit codes below `128` mark it as bad,
+    /// while all others, or termination by signal, abort with an error.
+    pub fn bisect_run(&self, command: &BStr) -> Result<Outcome<'_>, Error> {
+        let state = self.bisect_status()?.ok_or(Error::NotInProgress)?;
+        if state.bad.is_none() || state.good.is_empty() {
+            return Err(Error::RunWithoutCommits {
+                bad: state.terms.bad,
+                good: state.terms.good,
+            });
+        }
+        let cwd = self.work_dir().unwrap_or(self.git_dir());
+        loop {
+            let status = std::process::Command::from(
+                gix_command::prepare(gix_path::from_bstr(command).into_owned()).with_shell(),
+            )
+            .current_dir(cwd)
+            .status()
+            .map_err(|source| Error::Run {
+                command: command.to_owned(),
+                source,
+            })?;
+            let mark = match status.code() {
+                Some(0) => Mark::Good,
+                Some(125) => Mark::Skip,
+                Some(1..=127) => Mark::Bad,
+                _ => {
+                    return Err(Error::RunFailed {
+                        command: command.to_owned(),
+                        status,
+                    })
+                }
+            };
+            match self.bisect_mark(mark, &[])? {
+                Outcome::Next { .. } | Outcome::MergeBase { .. } => continue,
+                outcome => return Ok(outcome),
+            }
+        }
+    }
+
+    /// Return the log of the bisection in progress, which can be replayed by `git bisect replay`, similar to `git bisect log`.
+    pub fn bisect_log(&self) -> Result<BString, Error> {
+        read_file(self.git_dir().join("BISECT_LOG"))?.ok_or(Error::NotInProgress)
+    }
+
+    /// Stop the bisection in progress, check out the branch or commit that was checked out when it started, and remove all
+    /// bisect state, similar to `git bisect reset`.
+    pub fn bisect_reset(&self) -> Result<(), Error> {
+        let state = self.bisect_status()?.ok_or(Error::NotInProgress)?;
+        if !state.no_checkout {
+            let branch = FullName::try_from(format!("refs/heads/{}", state.start))
+                .ok()
+                .map(|name| self.try_find_reference(&name))
+                .transpose()?
+                .flatten();
+            let (new, target) = match branch {
+                Some(mut branch) => {
+                    let target = branch.peel_to_id_in_place()?.detach();
+                    (Target::Symbolic(branch.name().to_owned()), target)
+                }
+                None => {
+                    let target = self.rev_parse_single(state.start.as_bstr())?.detach();
+                    (Target::Peeled(target), target)
+                }
+            };
+            if self.head_id()?.detach() != target {
+                check_clean(self)?;
+                update_worktree_to(self, target)?;
+            }
+            let message = format!("checkout: moving from {} to {}", head_display(self)?, state.start);
+            move_head(self, new, target, message)?;
+        }
+
+        let mut edits = Vec::new();
+        for reference in self.references()?.prefixed(REFS_PREFIX)? {
+            let reference = reference.map_err(Error::IterReference)?;
+            edits.push(RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::Any,
+                    log: RefLog::AndReference,
+                },
+                name: reference.name().to_owned(),
+                deref: false,
+            });
+        }
+        self.edit_references(edits)?;
+        for name in STATE_FILES {
+            remove_file(self.git_dir().join(name))?;
+        }
+        Ok(())
+    }
+}
+
+/// Execution
+impl Repository {
+    /// Continue with the next step if a bad and a good commit are known, or log what's missing.
+    fn bisect_auto_next(&self) -> Result<Outcome<'_>, Error> {
+        let outcome = self.bisect_next()?;
+        if let Outcome::Waiting { bad, good } = outcome {
+            let status = match (bad, good) {
+                (false, 0) => "waiting for both good and bad commits".to_owned(),
+                (false, 1) => "waiting for bad commit, 1 good commit known".to_owned(),
+                (false, good) => format!("waiting for bad commit, {good} good commits known"),
+                (true, _) => "waiting for good commit(s), bad commit known".to_owned(),
+            };
+            append_log(self, &format!("# status: {status}\n"))?;
+        }
+        Ok(outcome)
+    }
+}
+
+fn check_terms(terms: &Terms) -> Result<(), Error> {
+    let invalid = |term: &BString, reason| {
+        Err(Error::InvalidTerm {
+            term: term.clone(),
+            reason,
+        })
+    };
+    if terms.bad == terms.good {
+        return invalid(&terms.bad, "the terms must differ");
+    }
+    for (term, own, others) in [
+        (&terms.bad, "bad", ["good", "old"]),
+        (&terms.good, "good", ["bad", "new"]),
+    ] {
+        if FullName::try_from(format!("{REFS_PREFIX}{term}")).is_err() {
+            return invalid(term, "it's not a valid reference name");
+        }
+        if [
+            "help",
+            "start",
+            "skip",
+            "next",
+            "reset",
+            "visualize",
+            "view",
+            "replay",
+            "log",
+            "run",
+            "terms",
+        ]
+        .iter()
+        .any(|builtin| term == builtin)
+        {
+            return invalid(term, "it's a builtin command");
+        }
+        if term != own && others.iter().any(|other| term == other) {
+            return invalid(term, "it would change its meaning");
+        }
+    }
+    Ok(())
+}
+
+/// Write the reference that marks `id` with `mark` and return the lines to log for it.
+fn mark_commit(repo: &Repository, terms: &Terms, mark: Mark, id: ObjectId) -> Result<String, Error> {
+    let (term, name) = match mark {
+        Mark::Bad => (terms.bad.as_bstr(), terms.bad.to_string()),
+        Mark::Good => (terms.good.as_bstr(), format!("{}-{id}", terms.good)),
+        Mark::Skip => ("skip".into(), format!("skip-{id}")),
+    };
+    set_reference(repo, &format!("{REFS_PREFIX}{name}"), id)?;
+    Ok(format!("# {term}: [{id}] {}\n", summary(repo, id)?))
+}
+
+fn summary(repo: &Repository, id: ObjectId) -> Result<BString, Error> {
+    Ok(repo
+        .find_object(id)?
+        .try_into_commit()?
+        .message()?
+        .summary()
+        .into_owned())
+}
+
+/// The commit to test, which is checked out, or which `BISECT_HEAD` points to.
+fn current_commit(repo: &Repository, state: &State) -> Result<ObjectId, Error> {
+    Ok(if state.no_checkout {
+        repo.find_reference("BISECT_HEAD")?.peel_to_id_in_place()?.detach()
+    } else {
+        repo.head_id()?.detach()
+    })
+}
+
+/// Make `commit` the one to test, by checking it out or by pointing `BISECT_HEAD` to it.
+fn checkout(repo: &Repository, state: &State, commit: ObjectId) -> Result<(), Error> {
+    set_reference(repo, "BISECT_EXPECTED_REV", commit)?;
+    if state.no_checkout {
+        return set_reference(repo, "BISECT_HEAD", commit);
+    }
+    let message = format!("checkout: moving from {} to {commit}", head_display(repo)?);
+    if repo.head_id()?.detach() != commit {
+        check_clean(repo)?;
+        update_worktree_to(repo, commit)?;
+    }
+    move_head(repo, Target::Peeled(commit), commit, message)
+}
+
+/// The name of the checked out branch as `git checkout` shows it in the reflog, or the id of the detached `HEAD`.
+fn head_display(repo: &Repository) -> Result<String, Error> {
+    Ok(match repo.head_name()? {
+        Some(name) => name.shorten().to_string(),
+        None => repo.head_id()?.to_string(),
+    })
+}
+
+fn set_reference(repo: &Repository, name: &str, id: ObjectId) -> Result<(), Error> {
+    repo.edit_reference(RefEdit {
+        change: Change::Update {
+            log: LogChange::default(),
+            expected: PreviousValue::Any,
+            new: Target::Peeled(id),
+        },
+        name: name.try_into().expect("valid"),
+        deref: false,
+    })?;
+    Ok(())
+}
+
+/// Point `HEAD` to `new`, which is or points to `commit`, and log the change with `message`.
+fn move_head(repo: &Repository, new: Target, commit: ObjectId, message: String) -> Result<(), Error> {
+    let log = LogChange {
+        mode: RefLog::AndReference,
+        force_create_reflog: false,
+        message: message.into(),
+    };
+    let edit = |log, new| RefEdit {
+        change: Change::Update {
+            log,
+            expected: PreviousValue::Any,
+            new,
+        },
+        name: "HEAD".try_into().expect("valid"),
+        deref: false,
+    };
+    if let Target::Symbolic(_) = new {
+        // Symbolic updates aren't logged, so the checkout of the branch is logged with the commit it points to first.
+        repo.edit_reference(edit(
+            LogChange {
+                mode: RefLog::Only,
+                ..log.clone()
+            },
+            Target::Peeled(commit),
+        ))?;
+    }
+    repo.edit_reference(edit(log, new))?;
+    Ok(())
+}
+
+fn read_file(path: PathBuf) -> Result<Option<BString>, Error> {
+    match std::fs::read(&path) {
+        Ok(data) => Ok(Some(data.into())),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(Error::Io { path, source }),
+    }
+}
+
+fn write_file(path: PathBuf, data: impl AsRef<[u8]>) -> Result<(), Error> {
+    std::fs::write(&path, data).map_err(|source| Error::Io { path, source })
+}
+
+fn append_log(repo: &Repository, text: &str) -> Result<(), Error> {
+    use std::io::Write;
+    let path = repo.git_dir().join("BISECT_LOG");
+    std::fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .map_err(|source| Error::Io { path, source })
+}
+
+fn remove_file(path: PathBuf) -> Result<(), Error> {
+    match std::fs::remove_file(&path) {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(source) => Err(Error::Io { path, source }),
+        Ok(()) => Ok(()),
+    }
+}
+
+/// Fail if the index or tracked files in the worktree have changes compared to `HEAD`.
+fn check_clean(repo: &Repository) -> Result<(), Error> {
+    let Some(worktree) = repo.worktree() else {
+        return Ok(());
+    };
+    let index = repo.index_or_empty()?;
+    let head_tree = repo.head_tree_id()?.detach();
+    let index_tree = index.write_tree(|tree| repo.write_object(tree).map(Id::detach))?;
+    let mut current = gix_index::State::clone(&index);
+    worktree.add(
+        &mut current,
+        None::<&BStr>,
+        add::Options {
+            update_only: true,
+            ..Default::default()
+        },
+    )?;
+    let has_unstaged_changes = current.entries().iter().any(|entry| {
+        entry.flags.contains(entry::Flags::REMOVE)
+            || index
+                .entry_by_path(entry.path(&current))
+                .map_or(true, |recorded| recorded.id != entry.id || recorded.mode != entry.mode)
+    });
+    if index_tree != head_tree || has_unstaged_changes {
+        return Err(Error::UncommittedChanges);
+    }
+    Ok(())
+}
+
+/// Check out the tree of `commit` into the worktree, which matches the clean index, and write it as new index.
+fn update_worktree_to(repo: &Repository, commit: ObjectId) -> Result<(), Error> {
+    let Some(work_dir) = repo.work_dir() else {
+        return Ok(());
+    };
+    let index = repo.index_or_empty()?;
+    let tree = repo.find_object(commit)?.try_into_commit()?.tree_id()?.detach();
+    let mut to = gix_index::State::from_tree(&tree, &repo.objects)?;
+    let checkout = repo.config.checkout_options(
+        repo,
+        gix_worktree::stack::state::attributes::Source::WorktreeThenIdMapping,
+    )?;
+    let objects = repo.objects.clone().into_arc().map_err(Error::ObjectStore)?;
+    gix_worktree_state::update(
+        &index,
+        &mut to,
+        work_dir,
+        objects,
+        &gix_features::progress::Discard,
+        &gix_features::progress::Discard,
+        &AtomicBool::default(),
+        gix_worktree_state::update::Options { checkout },
+    )?;
+    let mut index = gix_index::File::clone(&index);
+    *index = to;
+    index.write(repo.index_write_options()?)?;
+    Ok(())
+}
@@ -0,0 +1,590 @@
+use std::{
+    cmp::Reverse,
+    collections::{hash_map, BinaryHeap, HashMap, HashSet},
+    ops::Range,
+};
+
+use gix_hash::ObjectId;
+
+use crate::{
+    blame::{CopySource, Entry, Error, Options, Outcome},
+    bstr::{BStr, BString, ByteSlice},
+    config::tree::{Blame, Key},
+    object::tree::diff::{change::Event, for_each, Action},
+    Repository, Tree,
+};
+
+/// Lines in the file of a suspect that are yet to be blamed, along with the lines in the blamed file they correspond to.
+#[derive(Debug, Clone)]
+struct UnblamedHunk {
+    /// The lines in the file of the suspect.
+    lines: Range<u32>,
+    /// The line in the blamed file that corresponds to the first of `lines`.
+    blamed_start: u32,
+}
+
+impl UnblamedHunk {
+    /// Return the part of this hunk at `lines`, which must be contained in our lines.
+    fn sub(&self, lines: Range<u32>) -> Self {
+        UnblamedHunk {
+            blamed_start: self.blamed_start + (lines.start - self.lines.start),
+            lines,
+        }
+    }
+}
+
+/// A commit along with the path of the file that we blame in it, and the hunks of lines that it is suspected to have introduced.
+struct Suspect {
+    blob: ObjectId,
+    hunks: Vec<UnblamedHunk>,
+    /// `true` if the suspect is in the queue to be processed.
+    queued: bool,
+}
+
+/// A parent of a suspect commit.
+struct Parent<'repo> {
+    id: ObjectId,
+    time: gix_date::SecondsSinceUnixEpoch,
+    tree: Tree<'repo>,
+    /// The path and blob of the suspect's file in the parent, if it exists there.
+    file: Option<(BString, ObjectId)>,
+}
+
+/// A run of lines in the file of a suspect, and the first of the lines in another file it corresponds to.
+type Run = (Range<u32>, u32);
+
+/// Blaming
+impl Repository {
+    /// Find the commits that introduced each line of the file at `path` as of the commit `rev`, which may also be a tag
+    /// pointing to it, like `git blame` does, and configure the operation with `options`.
+    ///
+    /// The file is followed across renames, and commits listed in the files configured in `blame.ignoreRevsFile` are
+    /// ignored, along with the ones in [`Options::ignore_revs`].
+    pub fn blame(&self, path: impl AsRef<BStr>, rev: impl Into<ObjectId>, options: &Options) -> Result<Outcome, Error> {
+        let mut entries = Vec::<Entry>::new();
+        let blob = self.blame_inner(path.as_ref(), rev.into(), options, &mut |entry| {
+            entries.push(entry);
+            Ok(())
+        })?;
+        entries.sort_by_key(|entry| entry.range_in_blamed_file.start);
+
+        let mut coalesced = Vec::<Entry>::with_capacity(entries.len());
+        for entry in entries {
+            match coalesced.last_mut() {
+                Some(previous)
+                    if previous.commit_id == entry.commit_id
+                        && previous.source_path == entry.source_path
+                        && previous.range_in_blamed_file.end == entry.range_in_blamed_file.start
+                        && previous.range_in_source.end == entry.range_in_source.start =>
+                {
+                    previous.range_in_blamed_file.end = entry.range_in_blamed_file.end;
+                    previous.range_in_source.end = entry.range_in_source.end;
+                }
+                _ => coalesced.push(entry),
+            }
+        }
+        Ok(Outcome {
+            entries: coalesced,
+            blob,
+        })
+    }
+
+    /// Like [`blame()`](Self::blame()), but pass each entry to `on_entry` as soon as it is known, similar to
+    /// `git blame --incremental`.
+    ///
+    /// Entries are produced in no particular order and adjacent entries of the same commit aren't merged.
+    /// If `on_entry` fails, the operation is aborted.
+    pub fn blame_incremental<E>(
+        &self,
+        path: impl AsRef<BStr>,
+        rev: impl Into<ObjectId>,
+        options: &Options,
+        mut on_entry: impl FnMut(Entry) -> Result<(), E>,
+    ) -> Result<(), Error>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        self.blame_inner(path.as_ref(), rev.into(), options, &mut |entry| {
+            on_entry(entry).map_err(|err| Error::Delegate(Box::new(err)))
+        })
+        .map(|_blob| ())
+    }
+
+    /// Blame the file at `path` in `rev`, pass all entries to `on_entry` and return the data of the blamed file.
+    fn blame_inner(
+        &self,
+        path: &BStr,
+        rev: ObjectId,
+        options: &Options,
+        on_entry: &mut dyn FnMut(Entry) -> Result<(), Error>,
+    ) -> Result<Vec<u8>, Error> {
+        let commit = self
+            .find_object(rev)?
+            .peel_to_kind(gix_object::Kind::Commit)?
+            .into_commit();
+        let mut buf = Vec::new();
+        let blob = commit
+            .tree()?
+            .lookup_entry(path.split(|b| *b == b'/'), &mut buf)?
+            .filter(|entry| entry.mode().is_blob_or_symlink())
+            .ok_or_else(|| Error::MissingFile {
+                path: path.to_owned(),
+                commit: commit.id,
+            })?
+            .object_id();
+        let data = self.find_object(blob)?.detach().data;
+        let num_lines = data.lines_with_terminator().count() as u32;
+
+        let mut ranges = options.ranges.clone();
+        if ranges.is_empty() {
+            if num_lines == 0 {
+                return Ok(data);
+            }
+            ranges.push(0..num_lines);
+        }
+        if let Some(range) = ranges
+            .iter()
+            .find(|range| range.start >= range.end || range.end > num_lines)
+        {
+            return Err(Error::InvalidRange {
+                range: range.clone(),
+                lines: num_lines,
+            });
+        }
+        ranges.sort_by_key(|range| range.start);
+        let mut hunks = Vec::<UnblamedHunk>::new();
+        for range in ranges.into_iter().filter(|range| !range.is_empty()) {
+            match hunks.last_mut() {
+                Some(previous) if previous.lines.end >= range.start => {
+                    previous.lines.end = previous.lines.end.max(range.end);
+                }
+                _ => hunks.push(UnblamedHunk {
+                    blamed_start: range.start,
+                    lines: range,
+                }),
+            }
+        }
+
+        let mut blame = State {
+            repo: self,
+            options,
+            ignored: self.blame_ignored_revs(options)?,
+            algorithm: self.config.diff_algorithm()?,
+            suspects: HashMap::new(),
+            queue: BinaryHeap::new(),
+            num_queued: 0,
+        };
+        blame.pass(commit.id, commit.time()?.seconds, path.to_owned(), blob, hunks);
+        while let Some((_time, _order, commit_id, path)) = blame.queue.pop() {
+            let suspect = blame
+                .suspects
+                .get_mut(&(commit_id, path.clone()))
+                .expect("queued suspects are known");
+            suspect.queued = false;
+            let hunks = std::mem::take(&mut suspect.hunks);
+            let blob = suspect.blob;
+            for hunk in blame.process(commit_id, path.as_ref(), blob, hunks)? {
+                on_entry(Entry {
+                    commit_id,
+                    range_in_blamed_file: hunk.blamed_start..hunk.blamed_start + hunk.lines.len() as u32,
+                    range_in_source: hunk.lines,
+                    source_path: path.clone(),
+                })?;
+            }
+        }
+        Ok(data)
+    }
+
+    /// Return the commits to ignore, as configured in `options` and in the files listed in `blame.ignoreRevsFile`.
+    ///
+    /// Relative paths are relative to the root of the worktree, or to the `.git` directory in bare repositories.
+    fn blame_ignored_revs(&self, options: &Options) -> Result<HashSet<ObjectId>, Error> {
+        let mut ignored: HashSet<_> = options.ignore_revs.iter().copied().collect();
+        let mut paths = Vec::new();
+        for value in self
+            .config
+            .resolved
+            .strings_filter(
+                "blame",
+                None,
+                Blame::IGNORE_REVS_FILE.name(),
+                &mut self.filter_config_section(),
+            )
+            .unwrap_or_default()
+        {
+            // Like in `git`, an empty value resets the list of files.
+            if value.is_empty() {
+                paths.clear();
+                continue;
+            }
+            let install_dir = crate::path::install_dir().ok();
+            let home = self.config.home_dir();
+            let ctx = crate::config::cache::interpolate_context(install_dir.as_deref(), home.as_deref());
+            let path = gix_config::Path::from(value).interpolate(ctx)?;
+            paths.push(self.work_dir().unwrap_or(self.git_dir()).join(path));
+        }
+        for path in paths {
+            let data = std::fs::read(&path).map_err(|source| Error::IgnoreRevsFile {
+                path: path.clone(),
+                source,
+            })?;
+            for line in data.lines().map(ByteSlice::trim) {
+                if line.is_empty() || line.starts_with(b"#") {
+                    continue;
+                }
+                let id = ObjectId::from_hex(line).map_err(|_| Error::IgnoreRevsFileLine {
+                    path: path.clone(),
+                    line: line.into(),
+                })?;
+                ignored.insert(id);
+            }
+        }
+        Ok(ignored)
+    }
+}
+
+/// The state of a blame operation.
+struct State<'repo, 'a> {
+    repo: &'repo Repository,
+    options: &'a Options,
+    ignored: HashSet<ObjectId>,
+    algorithm: gix_diff::blob::Algorithm,
+    /// All suspects by commit and path.
+    suspects: HashMap<(ObjectId, BString), Suspect>,
+    /// The suspects to process, newest commit first and in the order they were added if commits are equally old.
+    queue: BinaryHeap<(gix_date::SecondsSinceUnixEpoch, Reverse<usize>, ObjectId, BString)>,
+    num_queued: usize,
+}
+
+impl<'repo> State<'repo, '_> {
+    /// Make `hunks` in the file at `path` and `blob` the responsibility of `commit`, which was created at `time`.
+    fn pass(
+        &mut self,
+        commit: ObjectId,
+        time: gix_date::SecondsSinceUnixEpoch,
+        path: BString,
+        blob: ObjectId,
+        hunks: Vec<UnblamedHunk>,
+    ) {
+        if hunks.is_empty() {
+            return;
+        }
+        let suspect = match self.suspects.entry((commit, path.clone())) {
+            hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            hash_map::Entry::Vacant(entry) => entry.insert(Suspect {
+                blob,
+                hunks: Vec::new(),
+                queued: false,
+            }),
+        };
+        suspect.hunks.extend(hunks);
+        if !suspect.queued {
+            suspect.queued = true;
+            self.queue.push((time, Reverse(self.num_queued), commit, path));
+            self.num_queued += 1;
+        }
+    }
+
+    /// Pass as many of `hunks` of the file at `path` and `blob` in `commit` to its parents as possible, and return the
+    /// ones that `commit` introduced.
+    fn process(
+        &mut self,
+        commit: ObjectId,
+        path: &BStr,
+        blob: ObjectId,
+        mut hunks: Vec<UnblamedHunk>,
+    ) -> Result<Vec<UnblamedHunk>, Error> {
+        hunks.sort_by_key(|hunk| hunk.lines.start);
+        let commit_object = self.repo.find_object(commit)?.try_into_commit()?;
+        let tree = commit_object.tree()?;
+        let mut buf = Vec::new();
+        let mut parents = Vec::new();
+        for parent_id in commit_object.parent_ids() {
+            // Parents may be missing in shallow repositories.
+            let Some(parent) = self.repo.try_find_object(parent_id)? else {
+                continue;
+            };
+            let parent = parent.try_into_commit()?;
+            let parent_tree = parent.tree()?;
+            let file = blob_at(&parent_tree, path, &mut buf)?.map(|id| (path.to_owned(), id));
+            parents.push(Parent {
+                id: parent_id.detach(),
+                time: parent.time()?.seconds,
+                tree: parent_tree,
+                file,
+            });
+        }
+
+        // Like a path-limited walk, pass everything to a parent that didn't change the file.
+        if let Some(parent) = parents
+            .iter()
+            .find(|parent| parent.file.as_ref().map(|(_path, id)| *id) == Some(blob))
+        {
+            self.pass(parent.id, parent.time, path.to_owned(), blob, hunks);
+            return Ok(Vec::new());
+        }
+        for parent in parents.iter_mut().filter(|parent| parent.file.is_none()) {
+            parent.file = self.rename_source(&parent.tree, &tree, path)?;
+            if let Some((source_path, source_blob)) = parent.file.clone().filter(|(_path, id)| *id == blob) {
+                self.pass(parent.id, parent.time, source_path, source_blob, hunks);
+                return Ok(Vec::new());
+            }
+        }
+
+        let data = self.repo.find_object(blob)?.detach().data;
+        let lines: Vec<_> = data.lines_with_terminator().collect();
+        let is_ignored = self.ignored.contains(&commit);
+        for parent in &parents {
+            let Some((parent_path, parent_blob)) = parent.file.clone() else {
+                continue;
+            };
+            let parent_data = self.repo.find_object(parent_blob)?.detach().data;
+            let parent_lines: Vec<_> = parent_data.lines_with_terminator().collect();
+            let changes = diff(self.algorithm, &parent_lines, &lines);
+            let runs = mapped_runs(&changes, lines.len() as u32, is_ignored);
+            let passed = split_off(&mut hunks, &runs);
+            self.pass(parent.id, parent.time, parent_path, parent_blob, passed);
+        }
+
+        if self.options.moves {
+            for parent in &parents {
+                let Some((parent_path, parent_blob)) = parent.file.clone() else {
+                    continue;
+                };
+                self.pass_matching_lines(
+                    &mut hunks,
+                    &lines,
+                    parent,
+                    parent_path,
+                    parent_blob,
+                    self.options.move_score,
+                )?;
+            }
+        }
+        if let Some(copies) = self.options.copies {
+            for parent in &parents {
+                for (source_path, source_blob) in self.copy_sources(parent, &tree, copies)? {
+                    if hunks.is_empty() {
+                        break;
+                    }
+                    self.pass_matching_lines(
+                        &mut hunks,
+                        &lines,
+                        parent,
+                        source_path,
+                        source_blob,
+                        self.options.copy_score,
+                    )?;
+                }
+            }
+        }
+        Ok(hunks)
+    }
+
+    /// Pass the lines of `hunks` in `lines` that can be found in `source_blob` at `source_path` in `parent` to it,
+    /// if they have more than `min_score` alphanumeric characters.
+    fn pass_matching_lines(
+        &mut self,
+        hunks: &mut Vec<UnblamedHunk>,
+        lines: &[&[u8]],
+        parent: &Parent<'_>,
+        source_path: BString,
+        source_blob: ObjectId,
+        min_score: u32,
+    ) -> Result<(), Error> {
+        if hunks.is_empty() {
+            return Ok(());
+        }
+        let source_data = self.repo.find_object(source_blob)?.detach().data;
+        let source_lines: Vec<_> = source_data.lines_with_terminator().collect();
+        let mut runs = Vec::new();
+        for hunk in hunks.iter() {
+            let hunk_lines = &lines[hunk.lines.start as usize..hunk.lines.end as usize];
+            let changes = diff(self.algorithm, &source_lines, hunk_lines);
+            runs.extend(
+                mapped_runs(&changes, hunk_lines.len() as u32, false)
+                    .into_iter()
+                    .filter(|(run, _source_start)| score(&hunk_lines[run.start as usize..run.end as usize]) > min_score)
+                    .map(|(run, source_start)| {
+                        (run.start + hunk.lines.start..run.end + hunk.lines.start, source_start)
+                    }),
+            );
+        }
+        let passed = split_off(hunks, &runs);
+        self.pass(parent.id, parent.time, source_path, source_blob, passed);
+        Ok(())
+    }
+
+    /// Return the path and blob of the file in `parent_tree` that was renamed to `path` in `tree`, if there is one.
+    fn rename_source(
+        &self,
+        parent_tree: &Tree<'repo>,
+        tree: &Tree<'repo>,
+        path: &BStr,
+    ) -> Result<Option<(BString, ObjectId)>, Error> {
+        let rewrites = self.repo.config.diff_renames()?.unwrap_or_default();
+        let mut source = None;
+        let outcome = parent_tree
+            .changes()?
+            .track_path()
+            .track_rewrites(Some(rewrites))
+            .for_each_to_obtain_tree(tree, |change| match change.event {
+                Event::Rewrite {
+                    source_location,
+                    source_entry_mode,
+                    source_id,
+                    ..
+                } if change.location == path && source_entry_mode.is_blob_or_symlink() => {
+                    source = Some((source_location.to_owned(), source_id.detach()));
+                    Ok::<_, std::convert::Infallible>(Action::Cancel)
+                }
+                _ => Ok(Action::Continue),
+            });
+        match outcome {
+            Ok(_) | Err(for_each::Error::Diff(gix_diff::tree::changes::Error::Cancelled)) => {}
+            Err(err) => return Err(err.into()),
+        }
+        Ok(source)
+    }
+
+    /// Return the paths and blobs of the files in `parent` that may be the source of lines copied into `tree`.
+    fn copy_sources(
+        &self,
+        parent: &Parent<'repo>,
+        tree: &Tree<'repo>,
+        copies: CopySource,
+    ) -> Result<Vec<(BString, ObjectId)>, Error> {
+        let search_all = match copies {
+            CopySource::ModifiedFiles => false,
+            CopySource::AllFilesIfCreated => parent.file.is_none(),
+            CopySource::AllFiles => true,
+        };
+        if search_all {
+            return Ok(parent
+                .tree
+                .traverse()
+                .breadthfirst
+                .files()?
+                .into_iter()
+                .filter(|entry| is_copy_source(entry.mode))
+                .map(|entry| (entry.filepath, entry.oid))
+                .collect());
+        }
+
+        let mut sources = Vec::new();
+        parent
+            .tree
+            .changes()?
+            .track_path()
+            .track_rewrites(None)
+            .for_each_to_obtain_tree(tree, |change| {
+                match change.event {
+                    Event::Modification {
+                        previous_entry_mode,
+                        previous_id,
+                        ..
+                    } if is_copy_source(previous_entry_mode) => {
+                        sources.push((change.location.to_owned(), previous_id.detach()));
+                    }
+                    Event::Deletion { entry_mode, id } if is_copy_source(entry_mode) => {
+                        sources.push((change.location.to_owned(), id.detach()));
+                    }
+                    _ => {}
+                }
+                Ok::<_, std::convert::Infallible>(Action::Continue)
+            })?;
+        Ok(sources)
+    }
+}
+
+/// Return `true` if a file with `mode` may be the source of copied lines, which is any regular file, executable or not.
+fn is_copy_source(mode: gix_object::tree::EntryMode) -> bool {
+    matches!(
+        mode.kind(),
+        gix_object::tree::EntryKind::Blob | gix_object::tree::EntryKind::BlobExecutable
+    )
+}
+
+/// Return the id of the blob at `path` in `tree`, if there is one.
+fn blob_at(tree: &Tree<'_>, path: &BStr, buf: &mut Vec<u8>) -> Result<Option<ObjectId>, Error> {
+    Ok(tree
+        .lookup_entry(path.split(|b| *b == b'/'), buf)?
+        .filter(|entry| entry.mode().is_blob_or_symlink())
+        .map(|entry| entry.object_id()))
+}
+
+/// Return the hunks of changed lines needed to turn `before` into `after`.
+fn diff(algorithm: gix_diff::blob::Algorithm, before: &[&[u8]], after: &[&[u8]]) -> Vec<(Range<u32>, Range<u32>)> {
+    let mut input = gix_diff::blob::intern::InternedInput::default();
+    input.update_before(before.iter().copied());
+    input.update_after(after.iter().copied());
+    let mut changes = Vec::new();
+    gix_diff::blob::diff(algorithm, &input, |before: Range<u32>, after: Range<u32>| {
+        changes.push((before, after));
+    });
+    changes
+}
+
+/// Return the runs of `num_lines` lines that are unchanged according to `changes` along with the lines they correspond to
+/// before the change.
+///
+/// If `is_ignored`, changed lines are also mapped to the lines they replaced, as far as there are any, which attributes them
+/// to the commits that introduced these.
+fn mapped_runs(changes: &[(Range<u32>, Range<u32>)], num_lines: u32, is_ignored: bool) -> Vec<Run> {
+    let mut runs = Vec::new();
+    let (mut pos, mut before_pos) = (0, 0);
+    for (before, after) in changes {
+        if pos < after.start {
+            runs.push((pos..after.start, before_pos));
+        }
+        let num_replaced = before.len().min(after.len()) as u32;
+        if is_ignored && num_replaced > 0 {
+            runs.push((after.start..after.start + num_replaced, before.start));
+        }
+        pos = after.end;
+        before_pos = before.end;
+    }
+    if pos < num_lines {
+        runs.push((pos..num_lines, before_pos));
+    }
+    runs
+}
+
+/// Remove the parts of `hunks` that are covered by `runs` and return them with their lines translated to the lines
+/// the runs correspond to.
+fn split_off(hunks: &mut Vec<UnblamedHunk>, runs: &[Run]) -> Vec<UnblamedHunk> {
+    let mut passed = Vec::new();
+    let mut remaining = Vec::new();
+    for hunk in hunks.drain(..) {
+        let mut pos = hunk.lines.start;
+        for (run, other_start) in runs
+            .iter()
+            .filter(|(run, _)| run.start < hunk.lines.end && run.end > hunk.lines.start)
+        {
+            let start = run.start.max(hunk.lines.start);
+            let end = run.end.min(hunk.lines.end);
+            if pos < start {
+                remaining.push(hunk.sub(pos..start));
+            }
+            let other_start = other_start + (start - run.start);
+            passed.push(UnblamedHunk {
+                lines: other_start..other_start + (end - start),
+                blamed_start: hunk.blamed_start + (start - hunk.lines.start),
+            });
+            pos = end;
+        }
+        if pos < hunk.lines.end {
+            remaining.push(hunk.sub(pos..hunk.lines.end));
+        }
+    }
+    *hunks = remaining;
+    passed
+}
+
+/// Return the amount of alphanumeric characters in `lines`, which is how `git` determines if they are worth being moved.
+fn score(lines: &[&[u8]]) -> u32 {
+    lines
+        .iter()
+        .map(|line| line.iter().filter(|b| b.is_ascii_alphanumeric()).count() as u32)
+        .sum()
+}
@@ -0,0 +1,153 @@
+use gix_hash::ObjectId;
+use gix_merge::blob::Labels;
+
+use crate::{
+    cherry_pick::{message_with_origin, revert_message, Error, Options, Outcome},
+    Id, Repository,
+};
+
+/// Cherry-picking and reverting
+impl Repository {
+    /// Apply the changes that `commit` introduced to the commit `onto` and write a new commit with `onto` as parent,
+    /// similar to what `git cherry-pick` does, and configure the operation with `options`.
+    ///
+    /// The changes are applied with a three-way merge of the trees of `onto` and `commit`, using the tree of the parent
+    /// of `commit` as base, which is the [mainline](Options::mainline) parent for merge commits. The new commit keeps
+    /// the author and message of `commit`, and uses the configured committer.
+    ///
+    /// No reference is changed, and neither the index nor the worktree are touched, which makes this usable in bare repositories.
+    pub fn cherry_pick(
+        &self,
+        commit: impl Into<ObjectId>,
+        onto: impl Into<ObjectId>,
+        options: Options,
+    ) -> Result<Outcome<'_>, Error> {
+        let commit_id = commit.into();
+        let onto = onto.into();
+        let commit = self.find_object(commit_id)?.try_into_commit()?;
+        let parent = mainline_parent(&commit, options.mainline)?;
+        let parent_tree = parent.map(|parent| self.commit_tree(parent)).transpose()?;
+        let summary = commit.message()?.summary().into_owned();
+        let short_id = commit.id().shorten_or_id();
+        let ours = Id::from_id(onto, self).shorten_or_id().to_string();
+        let theirs = format!("{short_id} ({summary})");
+        let base = format!("parent of {theirs}");
+        let merged = self.merge_trees(
+            parent_tree.as_deref(),
+            &self.commit_tree(onto)?,
+            &commit.tree_id()?,
+            Labels {
+                base: Some(base.as_str().into()),
+                ours: Some(ours.as_str().into()),
+                theirs: Some(theirs.as_str().into()),
+            },
+            options.merge,
+        )?;
+        if merged.has_conflicts() {
+            return Ok(Outcome::Conflict(merged.into()));
+        }
+
+        let decoded = commit.decode()?;
+        let message = if options.record_origin {
+            message_with_origin(decoded.message, &commit_id)
+        } else {
+            decoded.message.into()
+        };
+        let committer = self.committer().ok_or(crate::commit::Error::CommitterMissing)??;
+        let new_commit = gix_object::Commit {
+            tree: merged
+                .index
+                .write_tree(|tree| self.write_object(tree).map(Id::detach))?,
+            parents: vec![onto].into(),
+            author: decoded.author.trim().into(),
+            committer: committer.into(),
+            encoding: decoded.encoding.map(ToOwned::to_owned),
+            message,
+            extra_headers: Default::default(),
+        };
+        Ok(Outcome::Commit(self.write_object(&new_commit)?))
+    }
+
+    /// Apply the inverse of the changes that `commit` introduced to the commit `onto` and write a new commit with `onto` as
+    /// parent, similar to what `git revert` does, and configure the operation with `options`.
+    ///
+    /// The changes are applied with a three-way merge of the trees of `onto` and the parent of `commit`, using the tree of
+    /// `commit` as base. For merge commits, the [mainline](Options::mainline) parent is the one whose state is restored.
+    /// The new commit has a message like `Revert "<subject>"` and uses the configured author and committer.
+    ///
+    /// No reference is changed, and neither the index nor the worktree are touched, which makes this usable in bare repositories.
+    pub fn revert(
+        &self,
+        commit: impl Into<ObjectId>,
+        onto: impl Into<ObjectId>,
+        options: Options,
+    ) -> Result<Outcome<'_>, Error> {
+        let commit_id = commit.into();
+        let onto = onto.into();
+        let commit = self.find_object(commit_id)?.try_into_commit()?;
+        let parent = mainline_parent(&commit, options.mainline)?.ok_or(Error::RevertRootCommit { id: commit_id })?;
+        let summary = commit.message()?.summary().into_owned();
+        let short_id = commit.id().shorten_or_id();
+        let ours = Id::from_id(onto, self).shorten_or_id().to_string();
+        let base = format!("{short_id} ({summary})");
+        let theirs = format!("parent of {base}");
+        let merged = self.merge_trees(
+            Some(&commit.tree_id()?),
+            &self.commit_tree(onto)?,
+            &self.commit_tree(parent)?,
+            Labels {
+                base: Some(base.as_str().into()),
+                ours: Some(ours.as_str().into()),
+                theirs: Some(theirs.as_str().into()),
+            },
+            options.merge,
+        )?;
+        if merged.has_conflicts() {
+            return Ok(Outcome::Conflict(merged.into()));
+        }
+
+        let message = revert_message(
+            summary.as_ref(),
+            &commit_id,
+            options.mainline.is_some().then_some(parent.as_ref()),
+        );
+        let author = self.author().ok_or(crate::commit::Error::AuthorMissing)??;
+        let committer = self.committer().ok_or(crate::commit::Error::CommitterMissing)??;
+        let new_commit = gix_object::Commit {
+            tree: merged
+                .index
+                .write_tree(|tree| self.write_object(tree).map(Id::detach))?,
+            parents: vec![onto].into(),
+            author: author.into(),
+            committer: committer.into(),
+            encoding: None,
+            message,
+            extra_headers: Default::default(),
+        };
+        Ok(Outcome::Commit(self.write_object(&new_commit)?))
+    }
+
+    fn commit_tree(&self, commit: ObjectId) -> Result<ObjectId, Error> {
+        Ok(self.find_object(commit)?.try_into_commit()?.tree_id()?.detach())
+    }
+}
+
+/// Return the parent of `commit` selected by the one-based `mainline`, or `None` if `commit` is a root commit.
+fn mainline_parent(commit: &crate::Commit<'_>, mainline: Option<usize>) -> Result<Option<ObjectId>, Error> {
+    let id = commit.id;
+    let parents: Vec<_> = commit.parent_ids().map(Id::detach).collect();
+    match (mainline, parents.len()) {
+        (None, 0) => Ok(None),
+        (None, 1) => Ok(Some(parents[0])),
+        (None, num_parents) => Err(Error::MissingMainline {
+            id,
+            parents: num_parents,
+        }),
+        (Some(_), 0 | 1) => Err(Error::UnexpectedMainline { id }),
+        (Some(mainline), _) => mainline
+            .checked_sub(1)
+            .and_then(|idx| parents.get(idx).copied())
+            .map(Some)
+            .ok_or(Error::InvalidMainline { id, mainline }),
+    }
+}
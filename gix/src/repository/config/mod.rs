@@ -22,6 +22,34 @@ impl crate::Repository {
         }
     }
 
+    /// Load the configuration file of the given `source` for modification without resolving its includes, preserving
+    /// comments and formatting, or start an empty one if it doesn't exist yet.
+    /// Call [`commit()`][config::FileMut::commit()] on the returned instance to write it back.
+    ///
+    /// Supported sources are [`Local`][gix_config::Source::Local] and [`Worktree`][gix_config::Source::Worktree]
+    /// of this repository, as well as the user and system configuration files whose location is determined
+    /// while respecting the permissions of our environment.
+    ///
+    /// Note that changes aren't observed by this instance, which has to be reopened to see them.
+    pub fn config_file_mut(
+        &self,
+        source: gix_config::Source,
+    ) -> Result<config::FileMut, config::file_mut::open::Error> {
+        use gix_config::Source;
+        let path = match source {
+            Source::Local => self.common_dir().join("config"),
+            Source::Worktree => self.git_dir().join("config.worktree"),
+            Source::GitInstallation | Source::System | Source::Git | Source::User => source
+                .storage_location(&mut config::Cache::make_source_env(self.config.environment))
+                .ok_or(config::file_mut::open::Error::MissingLocation { config_source: source })?
+                .into_owned(),
+            Source::Env | Source::Cli | Source::Api | Source::EnvOverride => {
+                return Err(config::file_mut::open::Error::UnsupportedSource { config_source: source })
+            }
+        };
+        config::FileMut::at(path, source)
+    }
+
     /// Return filesystem options as retrieved from the repository configuration.
     ///
     /// Note that these values have not been [probed](gix_fs::Capabilities::probe()).
@@ -37,6 +65,13 @@ impl crate::Repository {
         self.config.stat_options()
     }
 
+    /// Return the options to use when writing the index, as configured by `index.skipHash`, `index.version`,
+    /// `index.threads`, `index.recordOffsetTable`, `feature.manyFiles`, `core.splitIndex` and the `splitIndex` section.
+    #[cfg(feature = "index")]
+    pub fn index_write_options(&self) -> Result<gix_index::write::Options, config::index_write_options::Error> {
+        self.config.index_write_options()
+    }
+
     /// The options used to open the repository.
     pub fn open_options(&self) -> &crate::open::Options {
         &self.options
@@ -10,6 +10,8 @@ pub mod resource_cache {
         ResourceCache(#[from] crate::diff::resource_cache::Error),
         #[error(transparent)]
         Index(#[from] crate::repository::index_or_load_from_head::Error),
+        #[error(transparent)]
+        ExpandSparseIndex(#[from] gix_index::sparse::expand::Error),
     }
 }
 
@@ -23,16 +25,17 @@ impl Repository {
     /// one worktree is present.
     ///
     /// Note that attributes will always be obtained from the current `HEAD` index even if the resources being diffed
-    /// might live in another tree. Further, if one of the `worktree_roots` are set, attributes will also be read from
+    /// might live in another tree, with a sparse index being expanded first so attributes of all paths can be found. Further, if one of the `worktree_roots` are set, attributes will also be read from
     /// the worktree. Otherwise, it will be skipped and attributes are read from the index tree instead.
     pub fn diff_resource_cache(
         &self,
         mode: gix_diff::blob::pipeline::Mode,
         worktree_roots: gix_diff::blob::pipeline::WorktreeRoots,
     ) -> Result<gix_diff::blob::Platform, resource_cache::Error> {
+        let index = self.index_or_load_from_head()?;
         Ok(crate::diff::resource_cache(
             self,
-            &*self.index_or_load_from_head()?,
+            &*self.expand_sparse_index(&index)?,
             mode,
             if worktree_roots.new_root.is_some() || worktree_roots.old_root.is_some() {
                 gix_worktree::stack::state::attributes::Source::WorktreeThenIdMapping
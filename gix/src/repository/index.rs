@@ -103,6 +103,23 @@ impl crate::Repository {
         })
     }
 
+    /// Return `index` with all of its [sparse directory entries](gix_index::entry::Mode::DIR) expanded into the entries
+    /// of the trees they represent, or `index` itself if it isn't sparse.
+    ///
+    /// Use it for operations that need one entry per file, like comparisons with trees.
+    #[cfg(any(feature = "blob-diff", feature = "status"))]
+    pub(crate) fn expand_sparse_index<'a>(
+        &self,
+        index: &'a gix_index::State,
+    ) -> Result<std::borrow::Cow<'a, gix_index::State>, gix_index::sparse::expand::Error> {
+        if !index.is_sparse() {
+            return Ok(std::borrow::Cow::Borrowed(index));
+        }
+        let mut index = index.clone();
+        index.expand_sparse(&self.objects)?;
+        Ok(std::borrow::Cow::Owned(index))
+    }
+
     /// Create new index-file, which would live at the correct location, in memory from the given `tree`.
     ///
     /// Note that this is an expensive operation as it requires recursively traversing the entire tree to unpack it into the index.
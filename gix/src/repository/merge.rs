@@ -0,0 +1,34 @@
+use gix_merge::{blob::Labels, tree};
+
+use crate::Repository;
+
+/// Merging
+impl Repository {
+    /// Merge the changes between the trees `base` and `ours` and between `base` and `theirs`, similar to what
+    /// `git merge-tree --write-tree` does, and configure the operation with `options`.
+    ///
+    /// If `base` is `None`, the empty tree is used instead, which is useful if the histories of the trees have no common ancestor.
+    /// Merged blobs are written to the object database, and `labels` are used in the conflict markers of files that
+    /// couldn't be merged cleanly.
+    ///
+    /// If the [outcome](tree::Outcome) has no conflicts, its index can be turned into a tree using
+    /// [`write_tree()`](gix_index::State::write_tree()). Note that this works in bare repositories as well.
+    pub fn merge_trees(
+        &self,
+        base: Option<&gix_hash::oid>,
+        ours: &gix_hash::oid,
+        theirs: &gix_hash::oid,
+        labels: Labels<'_>,
+        options: tree::Options,
+    ) -> Result<tree::Outcome, tree::Error> {
+        tree::merge(
+            base,
+            ours,
+            theirs,
+            &self.objects,
+            |data| self.write_blob(data).map(crate::Id::detach),
+            labels,
+            options,
+        )
+    }
+}
@@ -35,9 +35,17 @@ impl crate::Repository {
     }
 }
 
+#[cfg(feature = "apply")]
+mod apply;
 #[cfg(any(feature = "attributes", feature = "excludes"))]
 pub mod attributes;
+#[cfg(feature = "bisect")]
+mod bisect;
+#[cfg(feature = "blame")]
+mod blame;
 mod cache;
+#[cfg(feature = "merge")]
+mod cherry_pick;
 mod config;
 ///
 #[cfg(feature = "blob-diff")]
@@ -55,15 +63,25 @@ mod kind;
 mod location;
 #[cfg(feature = "mailmap")]
 mod mailmap;
+#[cfg(feature = "merge")]
+mod merge;
 mod object;
 #[cfg(feature = "attributes")]
 mod pathspec;
+#[cfg(feature = "range-diff")]
+mod range_diff;
+#[cfg(feature = "rebase")]
+mod rebase;
 mod reference;
 mod remote;
 #[cfg(feature = "revision")]
 mod revision;
 mod shallow;
+#[cfg(feature = "stash")]
+mod stash;
 mod state;
+#[cfg(feature = "status")]
+mod status;
 #[cfg(feature = "attributes")]
 mod submodule;
 mod thread_safe;
@@ -126,6 +144,23 @@ pub mod pathspec_defaults_ignore_case {
     }
 }
 
+///
+#[cfg(feature = "revision")]
+pub mod merge_base {
+    /// The error returned by [Repository::merge_base()](crate::Repository::merge_base()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        MergeBase(#[from] gix_revision::merge_base::Error),
+        #[error("No merge base was found between {first} and {second}")]
+        NotFound {
+            first: gix_hash::ObjectId,
+            second: gix_hash::ObjectId,
+        },
+    }
+}
+
 ///
 #[cfg(feature = "index")]
 pub mod index_or_load_from_head {
@@ -144,6 +179,121 @@ pub mod index_or_load_from_head {
     }
 }
 
+///
+#[cfg(feature = "status")]
+pub mod query_fs_monitor {
+    /// The error returned by [`Repository::query_fs_monitor()`][crate::Repository::query_fs_monitor()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("core.fsmonitor must be a hook program from a trusted source")]
+        UntrustedHook,
+        #[error(transparent)]
+        Query(#[from] gix_status::fs_monitor::Error),
+    }
+}
+
+///
+#[cfg(feature = "status")]
+pub mod untracked_cache_lookup {
+    /// The error returned by [`Repository::untracked_cache_lookup()`][crate::Repository::untracked_cache_lookup()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ExcludesFile(#[from] gix_config::path::interpolate::Error),
+        #[error(transparent)]
+        StatOptions(#[from] crate::config::stat_options::Error),
+        #[error("Could not read the untracked cache")]
+        Io(#[from] std::io::Error),
+    }
+}
+
+///
+#[cfg(any(feature = "stash", feature = "rebase"))]
+pub mod update_worktree {
+    /// The error returned when bringing the worktree in line with another index, as part of stashing or rebasing.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("A worktree is needed to check out changes")]
+        MissingWorktree,
+        #[error(transparent)]
+        CheckoutOptions(#[from] crate::config::checkout_options::Error),
+        #[error("Could not obtain a thread-safe handle to the object database")]
+        ObjectStore(#[source] std::io::Error),
+        #[error(transparent)]
+        Update(#[from] gix_worktree_state::update::Error),
+    }
+}
+
+///
+#[cfg(feature = "worktree-mutation")]
+pub mod worktree_add {
+    use std::path::PathBuf;
+
+    /// What to check out in a worktree created with [`Repository::worktree_add()`][crate::Repository::worktree_add()].
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Target {
+        /// Check out the existing local branch of the given name, which must not be checked out in another worktree.
+        Branch(gix_ref::FullName),
+        /// Create the local branch `name` pointing to `id` and check it out.
+        NewBranch {
+            /// The name of the branch to create, which must not exist yet.
+            name: gix_ref::FullName,
+            /// The commit the new branch should point to.
+            id: gix_hash::ObjectId,
+        },
+        /// Check out the commit `id` with a detached `HEAD`.
+        Detached(gix_hash::ObjectId),
+    }
+
+    /// The error returned by [`Repository::worktree_add()`][crate::Repository::worktree_add()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The path '{}' already exists and is not an empty directory", .path.display())]
+        PathExists { path: PathBuf },
+        #[error("The branch '{}' doesn't exist", .name.as_bstr())]
+        MissingBranch { name: gix_ref::FullName },
+        #[error("The branch '{}' is already checked out at '{}'", .name.as_bstr(), .path.display())]
+        BranchCheckedOut { name: gix_ref::FullName, path: PathBuf },
+        #[error("The branch '{}' already exists", .name.as_bstr())]
+        BranchExists { name: gix_ref::FullName },
+        #[error(transparent)]
+        FindReference(#[from] crate::reference::find::Error),
+        #[error(transparent)]
+        PeelReference(#[from] crate::reference::peel::Error),
+        #[error(transparent)]
+        EditReference(#[from] crate::reference::edit::Error),
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        PeelToTree(#[from] crate::object::peel::to_kind::Error),
+        #[error(transparent)]
+        Link(#[from] crate::worktree::proxy::repair::Error),
+        #[error(transparent)]
+        Open(#[from] crate::open::Error),
+        #[error("Could not create index from tree at {id}")]
+        IndexFromTree {
+            id: gix_hash::ObjectId,
+            source: gix_traverse::tree::breadthfirst::Error,
+        },
+        #[error(transparent)]
+        CheckoutOptions(#[from] crate::config::checkout_options::Error),
+        #[error(transparent)]
+        IndexCheckout(#[from] gix_worktree_state::checkout::Error),
+        #[error(transparent)]
+        IndexWriteOptions(#[from] crate::config::index_write_options::Error),
+        #[error(transparent)]
+        WriteIndex(#[from] gix_index::file::write::Error),
+        #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+        OpenArcOdb(#[source] std::io::Error),
+        #[error("Could not write '{}'", .path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+    }
+}
+
 ///
 #[cfg(feature = "worktree-stream")]
 pub mod worktree_stream {
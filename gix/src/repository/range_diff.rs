@@ -0,0 +1,599 @@
+use std::collections::{HashMap, VecDeque};
+
+use gix_diff::blob::{
+    unified_diff::{ContextSize, FunctionName},
+    Algorithm, UnifiedDiff,
+};
+use gix_hash::ObjectId;
+use gix_object::tree::EntryKind;
+
+use crate::{
+    bstr::{BString, ByteSlice, ByteVec},
+    object::tree::diff::{change::Event, Action},
+    range_diff::{compute_assignment, Error, Options, Outcome, Pair, Patch, Status},
+    revision, Commit, Repository,
+};
+
+/// The cost of pairing commits that must not be paired, as used by `git`.
+const COST_MAX: i32 = 1 << 16;
+/// The amount of bytes at the beginning of a file in which a null byte marks it as binary, just like in `git`.
+const FIRST_FEW_BYTES: usize = 8000;
+/// The pattern to find the section a hunk of a diff between patches is in, as used by `git range-diff`.
+const SECTION_HEADERS: &str = "^ ## (.*) ##$\n^.?@@ (.*)$";
+
+/// One side of a changed file.
+struct Side {
+    path: BString,
+    id: ObjectId,
+    kind: EntryKind,
+}
+
+/// A change to a single file of a commit.
+struct FileChange {
+    old: Option<Side>,
+    new: Option<Side>,
+    is_rename: bool,
+}
+
+impl FileChange {
+    /// The path by which changes are ordered, which is the one of the new side unless the file was deleted.
+    fn sort_key(&self) -> (&BString, bool) {
+        let side = self.new.as_ref().or(self.old.as_ref()).expect("one side is always set");
+        (&side.path, self.new.is_some())
+    }
+}
+
+/// Comparing commit series
+impl Repository {
+    /// Compare the commits of the `old` series with the commits of the `new` series, like `git range-diff` does, and configure
+    /// the operation with `options`.
+    ///
+    /// Both series are typically obtained from [revision specs](revision::Spec::into_walk()) like `main..topic`, and are always
+    /// ordered topologically with the oldest commit first, with merge commits being left out.
+    /// Commits with the same [patch id](Self::patch_id()) are paired first, and all remaining commits are paired such that
+    /// the differences between the diffs of all pairs are minimal, using the [creation factor](Options::creation_factor) as
+    /// cost of not pairing a commit.
+    pub fn range_diff(
+        &self,
+        old: revision::walk::Platform<'_>,
+        new: revision::walk::Platform<'_>,
+        options: &Options,
+    ) -> Result<Outcome, Error> {
+        let algorithm = self.config.diff_algorithm()?;
+        let old = self.range_diff_patches(old, algorithm)?;
+        let new = self.range_diff_patches(new, algorithm)?;
+        let (old_to_new, new_to_old) = assign(&old, &new, options.creation_factor);
+
+        let section_headers = FunctionName::from_pattern(SECTION_HEADERS.into()).expect("valid pattern");
+        let mut pairs = Vec::with_capacity(old.len().max(new.len()));
+        let mut shown = vec![false; old.len()];
+        let (mut i, mut j) = (0, 0);
+        // Show pairs in the order of the new series, and commits only in the old series after all commits before them.
+        while i < old.len() || j < new.len() {
+            while i < old.len() && shown[i] {
+                i += 1;
+            }
+            if i < old.len() && old_to_new[i].is_none() {
+                pairs.push(Pair {
+                    old: Some(i),
+                    new: None,
+                    status: Status::Removed,
+                    diff: BString::default(),
+                });
+                i += 1;
+                continue;
+            }
+            while j < new.len() && new_to_old[j].is_none() {
+                pairs.push(Pair {
+                    old: None,
+                    new: Some(j),
+                    status: Status::Added,
+                    diff: BString::default(),
+                });
+                j += 1;
+            }
+            if j < new.len() {
+                let old_index = new_to_old[j].expect("unpaired commits were skipped");
+                let (old_patch, new_patch) = (&old[old_index], &new[j]);
+                let (status, diff) = if old_patch.text == new_patch.text {
+                    (Status::Unchanged, BString::default())
+                } else {
+                    (
+                        Status::Changed,
+                        patch_diff(old_patch, new_patch, algorithm, &section_headers)?,
+                    )
+                };
+                pairs.push(Pair {
+                    old: Some(old_index),
+                    new: Some(j),
+                    status,
+                    diff,
+                });
+                shown[old_index] = true;
+                j += 1;
+            }
+        }
+        Ok(Outcome { old, new, pairs })
+    }
+
+    /// Return the patch id of the changes that `commit` introduces compared to its first parent, just like
+    /// `git patch-id --stable` does for them, or `None` if it doesn't change anything.
+    ///
+    /// Patch ids are the same for commits whose changes only differ in whitespace and line numbers, which makes them suitable to
+    /// find the same change in different series of commits.
+    /// Renames aren't tracked, and binary files are identified by their ids.
+    pub fn patch_id(&self, commit: impl Into<ObjectId>) -> Result<Option<ObjectId>, Error> {
+        let commit = self
+            .find_object(commit.into())?
+            .peel_to_kind(gix_object::Kind::Commit)?
+            .into_commit();
+        let changes = self.file_changes(&commit, false)?;
+        if changes.is_empty() {
+            return Ok(None);
+        }
+
+        let mut result = [0u8; 20];
+        let mut hunks = Vec::new();
+        for change in changes {
+            let mut hasher = gix_features::hash::hasher(self.object_hash());
+            let mut update = |bytes: &[u8]| {
+                let without_space: Vec<u8> = bytes.iter().copied().filter(|b| !b.is_ascii_whitespace()).collect();
+                hasher.update(&without_space);
+            };
+            let (old_path, new_path) = match (&change.old, &change.new) {
+                (Some(old), Some(new)) => (&old.path, &new.path),
+                (Some(one), None) | (None, Some(one)) => (&one.path, &one.path),
+                (None, None) => unreachable!("one side is always set"),
+            };
+            update(b"diff--git");
+            update(b"a/");
+            update(old_path);
+            update(b"b/");
+            update(new_path);
+            match (&change.old, &change.new) {
+                (None, Some(new)) => {
+                    update(b"newfilemode");
+                    update(new.kind.as_octal_str());
+                }
+                (Some(old), None) => {
+                    update(b"deletedfilemode");
+                    update(old.kind.as_octal_str());
+                }
+                (Some(old), Some(new)) if old.kind != new.kind => {
+                    update(b"oldmode");
+                    update(old.kind.as_octal_str());
+                    update(b"newmode");
+                    update(new.kind.as_octal_str());
+                }
+                _ => {}
+            }
+
+            let old_data = change.old.as_ref().map(|old| self.side_data(old)).transpose()?;
+            let new_data = change.new.as_ref().map(|new| self.side_data(new)).transpose()?;
+            let (old_data, new_data) = (old_data.unwrap_or_default(), new_data.unwrap_or_default());
+            if is_binary(&old_data) || is_binary(&new_data) {
+                let null = self.object_hash().null();
+                update(
+                    change
+                        .old
+                        .as_ref()
+                        .map_or(null, |old| old.id)
+                        .to_hex()
+                        .to_string()
+                        .as_bytes(),
+                );
+                update(
+                    change
+                        .new
+                        .as_ref()
+                        .map_or(null, |new| new.id)
+                        .to_hex()
+                        .to_string()
+                        .as_bytes(),
+                );
+            } else {
+                hunks.clear();
+                unified_hunks(&old_data, &new_data, Algorithm::Myers, None, &mut hunks)?;
+                if !hunks.is_empty() {
+                    match &change.old {
+                        Some(old) => {
+                            update(b"---a/");
+                            update(&old.path);
+                        }
+                        None => update(b"---/dev/null"),
+                    }
+                    match &change.new {
+                        Some(new) => {
+                            update(b"+++b/");
+                            update(&new.path);
+                        }
+                        None => update(b"+++/dev/null"),
+                    }
+                    for line in hunks
+                        .lines_with_terminator()
+                        .filter(|line| !line.starts_with(b"@@") && !line.starts_with(b"\\ "))
+                    {
+                        update(line);
+                    }
+                }
+            }
+
+            // Sum up the hashes of all files so their order doesn't matter.
+            let mut carry = 0u16;
+            for (sum, byte) in result.iter_mut().zip(hasher.digest()) {
+                carry += u16::from(*sum) + u16::from(byte);
+                *sum = carry as u8;
+                carry >>= 8;
+            }
+        }
+        Ok(Some(result.into()))
+    }
+
+    /// Turn all non-merge commits of `walk` into patches, oldest first.
+    fn range_diff_patches(
+        &self,
+        walk: revision::walk::Platform<'_>,
+        algorithm: Algorithm,
+    ) -> Result<Vec<Patch>, Error> {
+        let mut patches = Vec::new();
+        for info in walk
+            .sorting(gix_traverse::commit::Sorting::Topological)
+            .reverse(true)
+            .all()?
+        {
+            let info = info?;
+            if info.parent_ids.len() > 1 {
+                continue;
+            }
+            let commit = info.object()?;
+            patches.push(self.range_diff_patch(&commit, algorithm)?);
+        }
+        Ok(patches)
+    }
+
+    /// Represent `commit` as text like `git range-diff` does, with its author, its message and its diff.
+    fn range_diff_patch(&self, commit: &Commit<'_>, algorithm: Algorithm) -> Result<Patch, Error> {
+        let author = commit.author()?;
+        let mut text = BString::from(" ## Metadata ##\n");
+        text.push_str(b"Author: ");
+        text.push_str(author.name);
+        text.push_str(b" <");
+        text.push_str(author.email);
+        text.push_str(">\n\n ## Commit message ##\n");
+        for line in commit
+            .message_raw()?
+            .lines()
+            .skip_while(|line| line.iter().all(u8::is_ascii_whitespace))
+        {
+            let line = line.trim_end();
+            if !line.is_empty() {
+                text.push_str(b"    ");
+                text.push_str(line);
+            }
+            text.push_byte(b'\n');
+        }
+
+        let mut diff_start = 0;
+        let mut diff_lines = 0;
+        let mut hunks = Vec::new();
+        for change in self.file_changes(commit, true)? {
+            text.push_byte(b'\n');
+            if diff_start == 0 {
+                diff_start = text.len();
+            }
+            let path = match (&change.old, &change.new) {
+                (None, Some(new)) => {
+                    text.push_str(b" ## ");
+                    text.push_str(&new.path);
+                    text.push_str(b" (new)");
+                    &new.path
+                }
+                (Some(old), None) => {
+                    text.push_str(b" ## ");
+                    text.push_str(&old.path);
+                    text.push_str(b" (deleted)");
+                    &old.path
+                }
+                (Some(old), Some(new)) => {
+                    text.push_str(b" ## ");
+                    if change.is_rename {
+                        text.push_str(&old.path);
+                        text.push_str(b" => ");
+                    }
+                    text.push_str(&new.path);
+                    if old.kind != new.kind {
+                        text.push_str(format!(
+                            " (mode change {} => {})",
+                            old.kind.as_octal_str(),
+                            new.kind.as_octal_str()
+                        ));
+                    }
+                    &new.path
+                }
+                (None, None) => unreachable!("one side is always set"),
+            };
+            text.push_str(b" ##\n");
+            diff_lines += 1;
+
+            let old_data = change.old.as_ref().map(|old| self.side_data(old)).transpose()?;
+            let new_data = change.new.as_ref().map(|new| self.side_data(new)).transpose()?;
+            let (old_data, new_data) = (old_data.unwrap_or_default(), new_data.unwrap_or_default());
+            if is_binary(&old_data) || is_binary(&new_data) {
+                let display = |side: &Option<Side>| side.as_ref().map_or("/dev/null".into(), |side| side.path.clone());
+                text.push_str(b" Binary files ");
+                text.push_str(display(&change.old));
+                text.push_str(b" and ");
+                text.push_str(display(&change.new));
+                text.push_str(b" differ\n");
+                diff_lines += 1;
+                continue;
+            }
+            hunks.clear();
+            unified_hunks(
+                &old_data,
+                &new_data,
+                algorithm,
+                Some(&FunctionName::Default),
+                &mut hunks,
+            )?;
+            for line in hunks.lines_with_terminator() {
+                if let Some(function_name) = hunk_header_function_name(line) {
+                    text.push_str(b"@@");
+                    if function_name != b"\n" {
+                        text.push_byte(b' ');
+                        text.push_str(path);
+                        text.push_byte(b':');
+                    }
+                    text.push_str(function_name);
+                } else {
+                    if line.starts_with(b"\\") {
+                        text.push_byte(b' ');
+                    }
+                    text.push_str(line);
+                }
+                diff_lines += 1;
+            }
+        }
+
+        Ok(Patch {
+            commit_id: commit.id,
+            short_id: commit.id().shorten_or_id(),
+            subject: commit.message()?.summary().into_owned(),
+            patch_id: self.patch_id(commit.id)?,
+            text,
+            diff_start,
+            diff_lines,
+        })
+    }
+
+    /// Return all changed files of `commit` compared to its first parent, sorted like `git` does, and with renames if
+    /// `track_rewrites` is `true` and they are enabled.
+    fn file_changes(&self, commit: &Commit<'_>, track_rewrites: bool) -> Result<Vec<FileChange>, Error> {
+        let parent_tree = match commit.parent_ids().next() {
+            Some(parent_id) => parent_id
+                .object()?
+                .peel_to_kind(gix_object::Kind::Commit)?
+                .into_commit()
+                .tree()?,
+            None => self.empty_tree(),
+        };
+        let tree = commit.tree()?;
+        let mut changes = Vec::new();
+        let mut platform = parent_tree.changes()?;
+        platform.track_path();
+        if !track_rewrites {
+            platform.track_rewrites(None);
+        }
+        platform.for_each_to_obtain_tree(&tree, |change| {
+            let side = |path: &[u8], id: crate::Id<'_>, mode: gix_object::tree::EntryMode| {
+                (!mode.is_tree()).then(|| Side {
+                    path: path.into(),
+                    id: id.detach(),
+                    kind: mode.kind(),
+                })
+            };
+            let location = change.location;
+            let (old, new, is_rename) = match change.event {
+                Event::Addition { entry_mode, id } => (None, side(location, id, entry_mode), false),
+                Event::Deletion { entry_mode, id } => (side(location, id, entry_mode), None, false),
+                Event::Modification {
+                    previous_entry_mode,
+                    previous_id,
+                    entry_mode,
+                    id,
+                } => (
+                    side(location, previous_id, previous_entry_mode),
+                    side(location, id, entry_mode),
+                    false,
+                ),
+                Event::Rewrite {
+                    source_location,
+                    source_entry_mode,
+                    source_id,
+                    entry_mode,
+                    id,
+                    copy,
+                    ..
+                } => (
+                    side(source_location, source_id, source_entry_mode),
+                    side(location, id, entry_mode),
+                    !copy,
+                ),
+            };
+            match (old, new) {
+                // Changes between files, symlinks and submodules are shown as deletion and addition.
+                (Some(old), Some(new)) if !is_same_type(old.kind, new.kind) => {
+                    changes.push(FileChange {
+                        old: Some(old),
+                        new: None,
+                        is_rename: false,
+                    });
+                    changes.push(FileChange {
+                        old: None,
+                        new: Some(new),
+                        is_rename: false,
+                    });
+                }
+                (None, None) => {}
+                (old, new) => changes.push(FileChange { old, new, is_rename }),
+            }
+            Ok::<_, std::convert::Infallible>(Action::Continue)
+        })?;
+        changes.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+        Ok(changes)
+    }
+
+    /// Return the data of `side` to diff, which is a description of the commit for submodules.
+    fn side_data(&self, side: &Side) -> Result<Vec<u8>, Error> {
+        Ok(match side.kind {
+            EntryKind::Commit => format!("Subproject commit {}\n", side.id).into_bytes(),
+            _ => self.find_object(side.id)?.detach().data,
+        })
+    }
+}
+
+/// Pair the commits of the `old` series with the ones in the `new` series, first by patch id and then by minimizing the cost
+/// of all pairs, with `creation_factor` determining the cost of not pairing a commit.
+///
+/// Return the index of the paired commit in `new` for each commit in `old`, and vice versa.
+fn assign(old: &[Patch], new: &[Patch], creation_factor: u32) -> (Vec<Option<usize>>, Vec<Option<usize>>) {
+    let mut old_to_new = vec![None; old.len()];
+    let mut new_to_old = vec![None; new.len()];
+    let mut old_by_patch_id = HashMap::<_, VecDeque<_>>::new();
+    for (index, patch) in old.iter().enumerate() {
+        if let Some(patch_id) = patch.patch_id {
+            old_by_patch_id.entry(patch_id).or_default().push_back(index);
+        }
+    }
+    for (index, patch) in new.iter().enumerate() {
+        if let Some(old_index) = patch
+            .patch_id
+            .and_then(|patch_id| old_by_patch_id.get_mut(&patch_id)?.pop_front())
+        {
+            old_to_new[old_index] = Some(index);
+            new_to_old[index] = Some(old_index);
+        }
+    }
+
+    let n = old.len() + new.len();
+    if n == 0 {
+        return (old_to_new, new_to_old);
+    }
+    let creation_cost =
+        |patch: &Patch| (patch.diff_lines as u64 * u64::from(creation_factor) / 100).min(COST_MAX as u64) as i32;
+    let mut cost = vec![0; n * n];
+    for (i, old_patch) in old.iter().enumerate() {
+        for (j, new_patch) in new.iter().enumerate() {
+            cost[i + n * j] = if old_to_new[i] == Some(j) {
+                0
+            } else if old_to_new[i].is_none() && new_to_old[j].is_none() {
+                diff_size(old_patch.diff().as_ref(), new_patch.diff().as_ref())
+            } else {
+                COST_MAX
+            };
+        }
+        let c = if old_to_new[i].is_none() {
+            creation_cost(old_patch)
+        } else {
+            COST_MAX
+        };
+        for j in new.len()..n {
+            cost[i + n * j] = c;
+        }
+    }
+    for (j, new_patch) in new.iter().enumerate() {
+        let c = if new_to_old[j].is_none() {
+            creation_cost(new_patch)
+        } else {
+            COST_MAX
+        };
+        for i in old.len()..n {
+            cost[i + n * j] = c;
+        }
+    }
+
+    let (old_to_row, _) = compute_assignment(n, n, &cost);
+    for (i, row) in old_to_row.into_iter().take(old.len()).enumerate() {
+        if row >= 0 && (row as usize) < new.len() {
+            old_to_new[i] = Some(row as usize);
+            new_to_old[row as usize] = Some(i);
+        }
+    }
+    (old_to_new, new_to_old)
+}
+
+/// Return the amount of lines in a diff between `old` and `new`, including context and hunk headers.
+fn diff_size(old: &[u8], new: &[u8]) -> i32 {
+    let mut hunks = Vec::new();
+    match unified_hunks(old, new, Algorithm::Myers, None, &mut hunks) {
+        Ok(()) => hunks.lines_with_terminator().count().min(COST_MAX as usize) as i32,
+        Err(_) => COST_MAX,
+    }
+}
+
+/// Return the diff between the text of the `old` and the `new` patch, with hunk headers that show the section of the patch
+/// they are in according to `section_headers` instead of line numbers, and each line being indented.
+fn patch_diff(
+    old: &Patch,
+    new: &Patch,
+    algorithm: Algorithm,
+    section_headers: &FunctionName,
+) -> std::io::Result<BString> {
+    let mut hunks = Vec::new();
+    unified_hunks(&old.text, &new.text, algorithm, Some(section_headers), &mut hunks)?;
+    let mut out = BString::default();
+    for line in hunks.lines_with_terminator() {
+        out.push_str(b"    ");
+        match hunk_header_function_name(line) {
+            Some(function_name) => {
+                out.push_str(b"@@");
+                out.push_str(function_name);
+            }
+            None => out.push_str(line),
+        }
+    }
+    Ok(out)
+}
+
+/// Write the hunks of a unified diff between `old` and `new` to `out`, with `function_name` used to find the function
+/// context of each hunk.
+fn unified_hunks(
+    old: &[u8],
+    new: &[u8],
+    algorithm: Algorithm,
+    function_name: Option<&FunctionName>,
+    out: &mut Vec<u8>,
+) -> std::io::Result<()> {
+    let input = gix_diff::blob::intern::InternedInput::new(
+        gix_diff::blob::sources::byte_lines_with_terminator(old),
+        gix_diff::blob::sources::byte_lines_with_terminator(new),
+    );
+    gix_diff::blob::diff(
+        algorithm,
+        &input,
+        UnifiedDiff::new(&input, out, ContextSize::default(), function_name),
+    )?;
+    Ok(())
+}
+
+/// If `line` is a hunk header like `@@ -1,2 +1,3 @@ fn main()`, return the part after it, like ` fn main()`, including the
+/// line terminator.
+fn hunk_header_function_name(line: &[u8]) -> Option<&[u8]> {
+    let ranges = line.strip_prefix(b"@@ -")?;
+    let end = ranges.find(b"@@")?;
+    Some(&ranges[end + 2..])
+}
+
+/// Return `true` if `data` looks like binary data, which is the case if its beginning contains a null byte.
+fn is_binary(data: &[u8]) -> bool {
+    data[..data.len().min(FIRST_FEW_BYTES)].contains(&0)
+}
+
+/// Return `true` if `a` and `b` are both files, symlinks or submodules.
+fn is_same_type(a: EntryKind, b: EntryKind) -> bool {
+    use EntryKind::*;
+    matches!(
+        (a, b),
+        (Blob | BlobExecutable, Blob | BlobExecutable) | (Link, Link) | (Commit, Commit)
+    )
+}
@@ -0,0 +1,854 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use gix_hash::ObjectId;
+use gix_index::entry;
+use gix_merge::blob::Labels;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+use gix_sequencer::todo::{self, FixupMessage, Instruction};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    cherry_pick,
+    rebase::{state::UpdateRef, Backend, ContinueOptions, Error, Options, Outcome, State, Stop},
+    worktree::add,
+    Id, Repository,
+};
+
+/// The prefix of the references that hold the commits remembered with `label`.
+const REWRITTEN_PREFIX: &str = "refs/rewritten/";
+
+/// Rebasing
+impl Repository {
+    /// Return the state of the rebase in progress, or `None` if there is none.
+    ///
+    /// This includes rebases started by `git`, with either of its [backends](Backend).
+    pub fn rebase_status(&self) -> Result<Option<State>, Error> {
+        Ok(State::read_from(self.git_dir())?)
+    }
+
+    /// Return the todo list that [`rebase()`](Self::rebase()) would execute to apply all commits reachable from `HEAD`, but
+    /// not from `upstream`, just like `git rebase --interactive` would present it for editing, and configure it with `options`.
+    ///
+    /// Merge commits are left out, and the oldest commit comes first.
+    pub fn rebase_todo(&self, upstream: impl Into<ObjectId>, options: &Options) -> Result<todo::List, Error> {
+        let head = self.head_id()?.detach();
+        let mut list = todo::List::default();
+        for info in self
+            .rev_walk([head])
+            .hide([upstream.into()])
+            .sorting(gix_traverse::commit::Sorting::Topological)
+            .reverse(true)
+            .all()?
+        {
+            let info = info?;
+            if info.parent_ids().count() > 1 {
+                continue;
+            }
+            let commit = info.object()?;
+            list.instructions.push(Instruction::Pick {
+                commit: info.id.into(),
+                summary: commit.message()?.summary().into_owned(),
+            });
+        }
+
+        if options.update_refs {
+            let head_name = self.head_name()?;
+            let mut branches_by_id = BTreeMap::<ObjectId, Vec<BString>>::new();
+            for reference in self.references()?.local_branches()? {
+                let reference = reference.map_err(Error::IterReference)?;
+                if Some(reference.name()) == head_name.as_ref().map(FullName::as_ref) {
+                    continue;
+                }
+                if let Some(id) = reference.try_id() {
+                    branches_by_id
+                        .entry(id.detach())
+                        .or_default()
+                        .push(reference.name().as_bstr().to_owned());
+                }
+            }
+            list.insert_update_refs(|commit| {
+                branches_by_id
+                    .get(&commit.as_oid().to_owned())
+                    .cloned()
+                    .unwrap_or_default()
+            });
+        }
+        if options.autosquash {
+            list.autosquash();
+        }
+        if list.instructions.is_empty() {
+            list.instructions.push(Instruction::Noop);
+        }
+        Ok(list)
+    }
+
+    /// Rebase the commits of `HEAD` that aren't reachable from `upstream` onto `upstream`, or onto [`Options::onto`],
+    /// similar to what `git rebase --merge` does, and configure the operation with `options`.
+    ///
+    /// If `todo` is `None`, the instructions are obtained with [`rebase_todo()`](Self::rebase_todo()). Otherwise,
+    /// the given list is executed, which makes this equivalent to `git rebase --interactive` after editing the list.
+    ///
+    /// The state of the rebase is stored in `.git/rebase-merge` just like `git` does, so a rebase that stopped can be
+    /// continued, skipped or aborted by `git` as well as with [`rebase_continue()`](Self::rebase_continue()),
+    /// [`rebase_skip()`](Self::rebase_skip()) and [`rebase_abort()`](Self::rebase_abort()).
+    /// The index and the worktree must not have changes compared to `HEAD`.
+    pub fn rebase(
+        &self,
+        upstream: impl Into<ObjectId>,
+        todo: Option<todo::List>,
+        options: Options,
+    ) -> Result<Outcome<'_>, Error> {
+        self.worktree().ok_or(Error::MissingWorktree)?;
+        if State::read_from(self.git_dir())?.is_some() {
+            return Err(Error::InProgress);
+        }
+        if self.head()?.is_unborn() {
+            return Err(Error::UnbornHead);
+        }
+        let head = self.head_commit()?;
+        let index = self.index_or_empty()?;
+        if write_index_tree(self, &index)? != head.tree_id()?.detach() || has_unstaged_changes(self, &index)? {
+            return Err(Error::UncommittedChanges);
+        }
+
+        let upstream = upstream.into();
+        let onto = options.onto.unwrap_or(upstream);
+        let todo = match todo {
+            Some(todo) => todo,
+            None => self.rebase_todo(upstream, &options)?,
+        };
+        let mut state = State::new(Backend::Merge, self.head_name()?, onto, head.id, todo);
+        state.interactive = options.interactive;
+        for instruction in &state.todo.instructions {
+            if let Instruction::UpdateRef { name } = instruction {
+                let name = full_name(name.as_ref())?;
+                let before = self.find_reference(&name)?.peel_to_id_in_place()?.detach();
+                state.update_refs.push(UpdateRef {
+                    name,
+                    before,
+                    after: None,
+                });
+            }
+        }
+        set_reference(
+            self,
+            "ORIG_HEAD".try_into().expect("valid"),
+            Target::Peeled(head.id),
+            "rebase (start)".into(),
+        )?;
+        write_state(self, &state)?;
+
+        checkout(self, &index, onto)?;
+        move_head(
+            self,
+            onto,
+            format!("rebase (start): checkout {}", Id::from_id(onto, self).shorten_or_id()),
+        )?;
+        self.rebase_run(state, options.merge)
+    }
+
+    /// Continue the rebase in progress after it stopped, and configure the operation with `options`.
+    ///
+    /// The changes in the index are committed if the rebase stopped due to conflicts, or amended to the current commit
+    /// if it stopped for editing or rewording a commit, similar to what `git rebase --continue` does.
+    /// Conflicts must be resolved and there must be no unstaged changes.
+    pub fn rebase_continue(&self, options: ContinueOptions) -> Result<Outcome<'_>, Error> {
+        let mut state = self.rebase_in_progress()?;
+        let index = self.index_or_empty()?;
+        check_unmerged(&index)?;
+        if has_unstaged_changes(self, &index)? {
+            return Err(Error::UnstagedChanges);
+        }
+        let tree = write_index_tree(self, &index)?;
+        let head = self.head_commit()?;
+        let head_tree = head.tree_id()?.detach();
+        let message = options.message.or_else(|| state.message.clone());
+
+        let merge_head_path = self.git_dir().join("MERGE_HEAD");
+        if let Some(merge_head) = read_merge_head(&merge_head_path)? {
+            let message = message.unwrap_or_else(|| "Merge\n".into());
+            let author = match &state.author {
+                Some(author) => author.clone(),
+                None => self.author().ok_or(crate::commit::Error::AuthorMissing)??.into(),
+            };
+            let id = write_commit(self, tree, vec![head.id, merge_head], author, message.clone())?;
+            move_head(
+                self,
+                id,
+                format!("rebase (merge): {}", message_summary(message.as_ref())),
+            )?;
+            remove_file(merge_head_path)?;
+        } else if state.amend == Some(head.id) {
+            let head_message = head.message_raw()?;
+            if tree != head_tree || matches!(&message, Some(message) if message != head_message) {
+                let message = message.unwrap_or_else(|| head_message.to_owned());
+                let author = match &state.author {
+                    Some(author) => author.clone(),
+                    None => head.author()?.to_owned(),
+                };
+                let parents = head.parent_ids().map(Id::detach).collect();
+                let id = write_commit(self, tree, parents, author, message.clone())?;
+                move_head(
+                    self,
+                    id,
+                    format!("rebase (continue): {}", message_summary(message.as_ref())),
+                )?;
+            }
+        } else if let Some(stopped_at) = state.stopped_at.filter(|_| tree != head_tree) {
+            let original = self.find_object(stopped_at)?.try_into_commit()?;
+            let message = match message {
+                Some(message) => message,
+                None => original.message_raw()?.to_owned(),
+            };
+            let author = match &state.author {
+                Some(author) => author.clone(),
+                None => original.author()?.to_owned(),
+            };
+            let id = write_commit(self, tree, vec![head.id], author, message.clone())?;
+            move_head(
+                self,
+                id,
+                format!("rebase (continue): {}", message_summary(message.as_ref())),
+            )?;
+        }
+        clear_stop(&mut state);
+        self.rebase_run(state, options.merge)
+    }
+
+    /// Skip the instruction the rebase in progress stopped at, discard all changes in the index and in the worktree,
+    /// and continue with the next instruction, similar to what `git rebase --skip` does, using `merge` to configure
+    /// how the remaining commits are merged.
+    pub fn rebase_skip(&self, merge: gix_merge::tree::Options) -> Result<Outcome<'_>, Error> {
+        let mut state = self.rebase_in_progress()?;
+        let head = self.head_id()?.detach();
+        reset_hard(self, head)?;
+        remove_file(self.git_dir().join("MERGE_HEAD"))?;
+        clear_stop(&mut state);
+        self.rebase_run(state, merge)
+    }
+
+    /// Stop the rebase in progress and restore the index, the worktree and `HEAD` to the state before the rebase started,
+    /// similar to what `git rebase --abort` does.
+    pub fn rebase_abort(&self) -> Result<(), Error> {
+        let state = self.rebase_in_progress()?;
+        reset_hard(self, state.orig_head)?;
+        match state.head_name {
+            Some(name) => {
+                let message = format!("rebase (abort): returning to {name}", name = name.as_bstr());
+                set_reference(
+                    self,
+                    "HEAD".try_into().expect("valid"),
+                    Target::Symbolic(name),
+                    message.into(),
+                )?;
+            }
+            None => move_head(
+                self,
+                state.orig_head,
+                format!("rebase (abort): returning to {}", state.orig_head),
+            )?,
+        }
+        delete_rewritten_refs(self)?;
+        remove_file(self.git_dir().join("MERGE_HEAD"))?;
+        State::remove(self.git_dir()).map_err(|source| self.rebase_io_error(source))
+    }
+
+    /// Replace the instructions that are yet to be executed by the rebase in progress with `todo`, similar to what
+    /// `git rebase --edit-todo` does.
+    pub fn rebase_set_todo(&self, todo: todo::List) -> Result<(), Error> {
+        let mut state = self.rebase_in_progress()?;
+        state.todo = todo;
+        state.progress = None;
+        write_state(self, &state)
+    }
+}
+
+/// Execution
+impl Repository {
+    fn rebase_in_progress(&self) -> Result<State, Error> {
+        let state = State::read_from(self.git_dir())?.ok_or(Error::NotInProgress)?;
+        if state.backend != Backend::Merge {
+            return Err(Error::ApplyBackend);
+        }
+        self.worktree().ok_or(Error::MissingWorktree)?;
+        Ok(state)
+    }
+
+    fn rebase_io_error(&self, source: std::io::Error) -> Error {
+        Error::Io {
+            path: State::dir(self.git_dir(), Backend::Merge),
+            source,
+        }
+    }
+
+    /// Execute the instructions of `state` one by one until the todo list is empty or an instruction requires to stop.
+    fn rebase_run(&self, mut state: State, merge: gix_merge::tree::Options) -> Result<Outcome<'_>, Error> {
+        while !state.todo.instructions.is_empty() {
+            let instruction = state.todo.instructions.remove(0);
+            state.done.instructions.push(instruction.clone());
+            state.progress = None;
+            let stop = self.rebase_step(&mut state, &instruction, merge)?;
+            write_state(self, &state)?;
+            if let Some(stop) = stop {
+                return Ok(Outcome::Stopped(stop));
+            }
+        }
+        self.rebase_finish(state)
+    }
+
+    fn rebase_step(
+        &self,
+        state: &mut State,
+        instruction: &Instruction,
+        merge: gix_merge::tree::Options,
+    ) -> Result<Option<Stop>, Error> {
+        let options = cherry_pick::Options {
+            merge,
+            ..Default::default()
+        };
+        Ok(match instruction {
+            Instruction::Pick { commit, .. }
+            | Instruction::Revert { commit, .. }
+            | Instruction::Reword { commit, .. }
+            | Instruction::Edit { commit, .. } => {
+                let commit_id = resolve_commit(self, commit)?;
+                let original = self.find_object(commit_id)?.try_into_commit()?;
+                let parents: Vec<_> = original.parent_ids().map(Id::detach).collect();
+                let is_revert = matches!(instruction, Instruction::Revert { .. });
+                if parents.len() > 1 {
+                    return Err(Error::MergeCommit { id: commit_id });
+                }
+                let head = self.head_id()?.detach();
+                let index = self.index_or_empty()?;
+                let new_head = if !is_revert && parents.first() == Some(&head) {
+                    commit_id
+                } else {
+                    let outcome = if is_revert {
+                        self.revert(commit_id, head, options)?
+                    } else {
+                        self.cherry_pick(commit_id, head, options)?
+                    };
+                    match outcome {
+                        cherry_pick::Outcome::Commit(id) => id.detach(),
+                        cherry_pick::Outcome::Conflict(merged) => {
+                            checkout_conflict(self, &index, &merged)?;
+                            let summary = original.message()?.summary().into_owned();
+                            state.stopped_at = Some(commit_id);
+                            state.message = Some(if is_revert {
+                                cherry_pick::revert_message(summary.as_ref(), &commit_id, None)
+                            } else {
+                                original.message_raw()?.to_owned()
+                            });
+                            state.author = (!is_revert)
+                                .then(|| original.author().map(|author| author.to_owned()))
+                                .transpose()?;
+                            return Ok(Some(Stop::Conflict {
+                                commit: commit_id,
+                                conflicts: merged.conflicts,
+                            }));
+                        }
+                    }
+                };
+                checkout(self, &index, new_head)?;
+                let new_commit = self.find_object(new_head)?.try_into_commit()?;
+                move_head(
+                    self,
+                    new_head,
+                    format!("rebase ({}): {}", instruction.name(), new_commit.message()?.summary()),
+                )?;
+                let stop_for_amend = |state: &mut State| -> Result<(), Error> {
+                    state.stopped_at = Some(commit_id);
+                    state.amend = Some(new_head);
+                    state.message = Some(new_commit.message_raw()?.to_owned());
+                    state.author = Some(new_commit.author()?.to_owned());
+                    Ok(())
+                };
+                match instruction {
+                    Instruction::Edit { .. } => {
+                        stop_for_amend(state)?;
+                        Some(Stop::Edit { commit: commit_id })
+                    }
+                    Instruction::Reword { .. } => {
+                        stop_for_amend(state)?;
+                        Some(Stop::Reword { commit: commit_id })
+                    }
+                    _ => None,
+                }
+            }
+            Instruction::Squash { commit, .. } | Instruction::Fixup { commit, .. } => {
+                let commit_id = resolve_commit(self, commit)?;
+                let has_previous_commit =
+                    state.done.instructions[..state.done.instructions.len() - 1]
+                        .iter()
+                        .any(|instruction| {
+                            instruction.commit().is_some() || matches!(instruction, Instruction::Merge { .. })
+                        });
+                if !has_previous_commit {
+                    return Err(Error::NothingToSquashInto { commit: commit_id });
+                }
+                let original = self.find_object(commit_id)?.try_into_commit()?;
+                if original.parent_ids().count() > 1 {
+                    return Err(Error::MergeCommit { id: commit_id });
+                }
+                let head = self.head_commit()?;
+                let message = meld_message(head.message_raw()?, original.message_raw()?, instruction);
+                let author = head.author()?.to_owned();
+                let index = self.index_or_empty()?;
+                match self.cherry_pick(commit_id, head.id, options)? {
+                    cherry_pick::Outcome::Commit(id) => {
+                        let tree = id.object()?.try_into_commit()?.tree_id()?.detach();
+                        let parents = head.parent_ids().map(Id::detach).collect();
+                        let new_head = write_commit(self, tree, parents, author, message.clone())?;
+                        checkout(self, &index, new_head)?;
+                        move_head(
+                            self,
+                            new_head,
+                            format!("rebase ({}): {}", instruction.name(), message_summary(message.as_ref())),
+                        )?;
+                        None
+                    }
+                    cherry_pick::Outcome::Conflict(merged) => {
+                        checkout_conflict(self, &index, &merged)?;
+                        state.stopped_at = Some(commit_id);
+                        state.amend = Some(head.id);
+                        state.message = Some(message);
+                        state.author = Some(author);
+                        Some(Stop::Conflict {
+                            commit: commit_id,
+                            conflicts: merged.conflicts,
+                        })
+                    }
+                }
+            }
+            Instruction::Exec { command } => {
+                let work_dir = self.work_dir().ok_or(Error::MissingWorktree)?;
+                let status = std::process::Command::from(
+                    gix_command::prepare(gix_path::from_bstr(command.as_bstr()).into_owned()).with_shell(),
+                )
+                .current_dir(work_dir)
+                .status()
+                .map_err(|source| Error::Exec {
+                    command: command.clone(),
+                    source,
+                })?;
+                (!status.success()).then(|| Stop::Exec {
+                    command: command.clone(),
+                    status,
+                })
+            }
+            Instruction::Break => Some(Stop::Break),
+            Instruction::Noop | Instruction::Drop { .. } => None,
+            Instruction::Label { name } => {
+                let head = self.head_id()?.detach();
+                set_reference(
+                    self,
+                    rewritten_name(name.as_ref())?,
+                    Target::Peeled(head),
+                    format!("rebase (label) '{name}'").into(),
+                )?;
+                None
+            }
+            Instruction::Reset { name } => {
+                let target = resolve_label(self, name.as_ref())?;
+                let index = self.index_or_empty()?;
+                checkout(self, &index, target)?;
+                move_head(self, target, format!("rebase (reset): '{name}'"))?;
+                None
+            }
+            Instruction::Merge {
+                commit, label, summary, ..
+            } => {
+                let head = self.head_id()?.detach();
+                let other = resolve_label(self, label.as_ref())?;
+                let original = commit
+                    .as_ref()
+                    .map(|commit| -> Result<_, Error> {
+                        let id = resolve_commit(self, commit)?;
+                        Ok(self.find_object(id)?.try_into_commit()?)
+                    })
+                    .transpose()?;
+                let message: BString = match &original {
+                    Some(original) => original.message_raw()?.to_owned(),
+                    None if !summary.is_empty() => format!("{summary}\n").into(),
+                    None => format!("Merge branch '{label}'\n").into(),
+                };
+                let index = self.index_or_empty()?;
+                if let Some(original) = original
+                    .as_ref()
+                    .filter(|original| original.parent_ids().map(Id::detach).eq([head, other]))
+                {
+                    checkout(self, &index, original.id)?;
+                    move_head(
+                        self,
+                        original.id,
+                        format!("rebase (merge): {}", message_summary(message.as_ref())),
+                    )?;
+                    return Ok(None);
+                }
+
+                let base = self.merge_base(head, other)?.detach();
+                let tree_of = |id: ObjectId| -> Result<ObjectId, Error> {
+                    Ok(self.find_object(id)?.try_into_commit()?.tree_id()?.detach())
+                };
+                let merged = self.merge_trees(
+                    Some(&tree_of(base)?),
+                    &tree_of(head)?,
+                    &tree_of(other)?,
+                    Labels {
+                        base: None,
+                        ours: Some("HEAD".into()),
+                        theirs: Some(label.as_ref()),
+                    },
+                    merge,
+                )?;
+                let author = match &original {
+                    Some(original) => original.author()?.to_owned(),
+                    None => self.author().ok_or(crate::commit::Error::AuthorMissing)??.into(),
+                };
+                if merged.has_conflicts() {
+                    checkout_conflict(self, &index, &merged)?;
+                    std::fs::write(self.git_dir().join("MERGE_HEAD"), format!("{other}\n"))
+                        .map_err(|source| self.rebase_io_error(source))?;
+                    state.stopped_at = original.as_ref().map(|original| original.id);
+                    state.message = Some(message);
+                    state.author = Some(author);
+                    return Ok(Some(Stop::Conflict {
+                        commit: state.stopped_at.unwrap_or(other),
+                        conflicts: merged.conflicts,
+                    }));
+                }
+                let tree = write_index_tree(self, &merged.index)?;
+                let new_head = write_commit(self, tree, vec![head, other], author, message.clone())?;
+                checkout(self, &index, new_head)?;
+                move_head(
+                    self,
+                    new_head,
+                    format!("rebase (merge): {}", message_summary(message.as_ref())),
+                )?;
+                None
+            }
+            Instruction::UpdateRef { name } => {
+                let head = self.head_id()?.detach();
+                let name = full_name(name.as_ref())?;
+                match state.update_refs.iter_mut().find(|update| update.name == name) {
+                    Some(update) => update.after = Some(head),
+                    None => {
+                        let before = self.find_reference(&name)?.peel_to_id_in_place()?.detach();
+                        state.update_refs.push(UpdateRef {
+                            name,
+                            before,
+                            after: Some(head),
+                        });
+                    }
+                }
+                None
+            }
+        })
+    }
+
+    /// Point the rebased branch to the current commit and check it out again, update all references that were rewritten
+    /// and remove the state of the rebase.
+    fn rebase_finish(&self, state: State) -> Result<Outcome<'_>, Error> {
+        let head = self.head_id()?.detach();
+        if let Some(name) = state.head_name {
+            set_reference(
+                self,
+                name.clone(),
+                Target::Peeled(head),
+                format!("rebase (finish): {} onto {}", name.as_bstr(), state.onto).into(),
+            )?;
+            set_reference(
+                self,
+                "HEAD".try_into().expect("valid"),
+                Target::Symbolic(name.clone()),
+                format!("rebase (finish): returning to {}", name.as_bstr()).into(),
+            )?;
+        }
+        for update in state.update_refs {
+            let Some(after) = update.after else { continue };
+            self.edit_reference(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: "rebase (update-refs)".into(),
+                    },
+                    expected: PreviousValue::MustExistAndMatch(Target::Peeled(update.before)),
+                    new: Target::Peeled(after),
+                },
+                name: update.name,
+                deref: false,
+            })?;
+        }
+        delete_rewritten_refs(self)?;
+        State::remove(self.git_dir()).map_err(|source| self.rebase_io_error(source))?;
+        Ok(Outcome::Finished {
+            head: Id::from_id(head, self),
+        })
+    }
+}
+
+/// Return the message of the commit that results from melding the commit with `message` into the commit with
+/// `previous_message` as requested by `instruction`, just like `git` does without an editor.
+fn meld_message(previous_message: &BStr, message: &BStr, instruction: &Instruction) -> BString {
+    match instruction {
+        Instruction::Squash { .. } => {
+            let mut out: BString = previous_message.trim_end_with(|c| c == '\n').into();
+            let body = without_subject_of_kind(message, "squash! ");
+            if !body.is_empty() {
+                out.extend_from_slice(b"\n\n");
+                out.extend_from_slice(body);
+            }
+            out.push(b'\n');
+            out
+        }
+        Instruction::Fixup {
+            message: FixupMessage::Replace | FixupMessage::ReplaceAndEdit,
+            ..
+        } => {
+            let mut out: BString = without_subject_of_kind(message, "amend! ").into();
+            out.push(b'\n');
+            out
+        }
+        _ => previous_message.to_owned(),
+    }
+}
+
+/// Return `message` without its first paragraph if its subject starts with `prefix`, and without trailing newlines.
+fn without_subject_of_kind<'a>(message: &'a BStr, prefix: &str) -> &'a [u8] {
+    let message = message.trim_end_with(|c| c == '\n');
+    if !message.starts_with(prefix.as_bytes()) {
+        return message;
+    }
+    message
+        .find(b"\n\n")
+        .map_or(&[][..], |pos| message[pos..].trim_start_with(|c| c == '\n'))
+}
+
+fn message_summary(message: &BStr) -> BString {
+    gix_object::commit::MessageRef::from_bytes(message)
+        .summary()
+        .into_owned()
+}
+
+fn clear_stop(state: &mut State) {
+    state.stopped_at = None;
+    state.amend = None;
+    state.message = None;
+    state.author = None;
+}
+
+fn write_state(repo: &Repository, state: &State) -> Result<(), Error> {
+    state
+        .write_to(repo.git_dir())
+        .map_err(|source| repo.rebase_io_error(source))
+}
+
+fn full_name(name: &BStr) -> Result<FullName, Error> {
+    FullName::try_from(name).map_err(|source| Error::InvalidRefName {
+        name: name.to_owned(),
+        source,
+    })
+}
+
+fn rewritten_name(label: &BStr) -> Result<FullName, Error> {
+    let mut name = BString::from(REWRITTEN_PREFIX);
+    name.extend_from_slice(label);
+    full_name(name.as_ref())
+}
+
+/// Find the only commit that `prefix` refers to.
+fn resolve_commit(repo: &Repository, prefix: &gix_hash::Prefix) -> Result<ObjectId, Error> {
+    match repo.objects.lookup_prefix(*prefix, None)? {
+        Some(Ok(id)) => Ok(id),
+        Some(Err(())) | None => Err(Error::UnknownCommit { commit: *prefix }),
+    }
+}
+
+/// Find the commit remembered as label `name`, or the commit that `name` refers to.
+fn resolve_label(repo: &Repository, name: &BStr) -> Result<ObjectId, Error> {
+    if let Ok(reference) = rewritten_name(name) {
+        if let Some(mut reference) = repo.try_find_reference(&reference)? {
+            return Ok(reference.peel_to_id_in_place()?.detach());
+        }
+    }
+    let unknown = || Error::UnknownLabel { name: name.to_owned() };
+    let spec = name.to_str().map_err(|_| unknown())?;
+    repo.rev_parse_single(spec).map(Id::detach).map_err(|_| unknown())
+}
+
+fn delete_rewritten_refs(repo: &Repository) -> Result<(), Error> {
+    let mut edits = Vec::new();
+    for reference in repo.references()?.prefixed(REWRITTEN_PREFIX)? {
+        let reference = reference.map_err(Error::IterReference)?;
+        edits.push(RefEdit {
+            change: Change::Delete {
+                expected: PreviousValue::Any,
+                log: RefLog::AndReference,
+            },
+            name: reference.name().to_owned(),
+            deref: false,
+        });
+    }
+    repo.edit_references(edits)?;
+    Ok(())
+}
+
+fn set_reference(repo: &Repository, name: FullName, target: Target, message: BString) -> Result<(), Error> {
+    repo.edit_reference(RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message,
+            },
+            expected: PreviousValue::Any,
+            new: target,
+        },
+        name,
+        deref: false,
+    })?;
+    Ok(())
+}
+
+/// Detach `HEAD` at `id` and log the change with `message`.
+fn move_head(repo: &Repository, id: ObjectId, message: String) -> Result<(), Error> {
+    set_reference(
+        repo,
+        "HEAD".try_into().expect("valid"),
+        Target::Peeled(id),
+        message.into(),
+    )
+}
+
+fn write_commit(
+    repo: &Repository,
+    tree: ObjectId,
+    parents: Vec<ObjectId>,
+    author: gix_actor::Signature,
+    message: BString,
+) -> Result<ObjectId, Error> {
+    let committer = repo.committer().ok_or(crate::commit::Error::CommitterMissing)??;
+    let commit = gix_object::Commit {
+        tree,
+        parents: parents.into(),
+        author,
+        committer: committer.into(),
+        encoding: None,
+        message,
+        extra_headers: Default::default(),
+    };
+    Ok(repo.write_object(&commit)?.detach())
+}
+
+fn write_index_tree(repo: &Repository, state: &gix_index::State) -> Result<ObjectId, Error> {
+    Ok(state.write_tree(|tree| repo.write_object(tree).map(Id::detach))?)
+}
+
+fn read_merge_head(path: &std::path::Path) -> Result<Option<ObjectId>, Error> {
+    match std::fs::read(path) {
+        Ok(data) => Ok(ObjectId::from_hex(data.trim_with(|c| c.is_ascii_whitespace())).ok()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(Error::Io {
+            path: path.to_owned(),
+            source,
+        }),
+    }
+}
+
+fn remove_file(path: PathBuf) -> Result<(), Error> {
+    match std::fs::remove_file(&path) {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(source) => Err(Error::Io { path, source }),
+        Ok(()) => Ok(()),
+    }
+}
+
+fn check_unmerged(index: &gix_index::State) -> Result<(), Error> {
+    match index.entries().iter().find(|entry| entry.stage() != 0) {
+        Some(entry) => Err(Error::Unmerged {
+            path: entry.path(index).to_owned(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Return `true` if tracked files in the worktree differ from what's recorded in `index`.
+fn has_unstaged_changes(repo: &Repository, index: &gix_index::State) -> Result<bool, Error> {
+    let worktree = repo.worktree().ok_or(Error::MissingWorktree)?;
+    let mut current = gix_index::State::clone(index);
+    worktree.add(
+        &mut current,
+        None::<&BStr>,
+        add::Options {
+            update_only: true,
+            ..Default::default()
+        },
+    )?;
+    Ok(current.entries().iter().any(|entry| {
+        entry.flags.contains(entry::Flags::REMOVE)
+            || index
+                .entry_by_path(entry.path(&current))
+                .map_or(true, |recorded| recorded.id != entry.id || recorded.mode != entry.mode)
+    }))
+}
+
+/// Check out the tree of `commit` into the worktree, which matches the clean `index`, and write it as new index.
+fn checkout(repo: &Repository, index: &gix_index::File, commit: ObjectId) -> Result<(), Error> {
+    let tree = repo.find_object(commit)?.try_into_commit()?.tree_id()?.detach();
+    let mut to = gix_index::State::from_tree(&tree, &repo.objects)?;
+    repo.update_worktree(index, &mut to)?;
+    write_index(repo, index, to)
+}
+
+/// Check out the result of a `merged` with conflicts into the worktree, which matches the clean `index`,
+/// and record the conflicts in the index.
+fn checkout_conflict(
+    repo: &Repository,
+    index: &gix_index::File,
+    merged: &gix_merge::tree::Outcome,
+) -> Result<(), Error> {
+    let mut target = merged.worktree_state();
+    repo.update_worktree(index, &mut target)?;
+    let mut state = merged.index.clone();
+    for (entry, path) in state.entries_mut_with_paths() {
+        if let Some(updated) = target
+            .entry_by_path_and_stage(path, 0)
+            .filter(|updated| updated.id == entry.id && updated.mode == entry.mode)
+        {
+            entry.stat = updated.stat;
+        }
+    }
+    write_index(repo, index, state)
+}
+
+/// Discard all changes of the index and of tracked files in the worktree, including conflicts, and check out `commit`.
+fn reset_hard(repo: &Repository, commit: ObjectId) -> Result<(), Error> {
+    let index = repo.index_or_empty()?;
+    let mut from = gix_index::State::new(repo.object_hash());
+    let null = repo.object_hash().null();
+    for entry in index.entries() {
+        let path = entry.path(&index);
+        if entry.stage() == 0 {
+            from.dangerously_push_entry(entry.stat, entry.id, entry::Flags::empty(), entry.mode, path);
+        } else if from.entry_by_path(path).is_none() {
+            // The file contains conflict markers, so it must be rewritten.
+            from.dangerously_push_entry(Default::default(), null, entry::Flags::empty(), entry.mode, path);
+        }
+    }
+    from.sort_entries();
+    let tree = repo.find_object(commit)?.try_into_commit()?.tree_id()?.detach();
+    let mut to = gix_index::State::from_tree(&tree, &repo.objects)?;
+    repo.update_worktree(&from, &mut to)?;
+    write_index(repo, &index, to)
+}
+
+fn write_index(repo: &Repository, index: &gix_index::File, state: gix_index::State) -> Result<(), Error> {
+    let mut index = gix_index::File::clone(index);
+    *index = state;
+    index.write(repo.index_write_options()?)?;
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use gix_macros::momo;
 
-use crate::{bstr::BStr, revision, Id};
+use crate::{bstr::BStr, ext::ObjectIdExt, revision, Id};
 
 /// Methods for resolving revisions by spec or working with the commit graph.
 impl crate::Repository {
@@ -45,4 +45,31 @@ impl crate::Repository {
     ) -> revision::walk::Platform<'_> {
         revision::walk::Platform::new(tips, self)
     }
+
+    /// Obtain the best merge-base between commit `one` and `two`, or fail if there is none, similar to `git merge-base`.
+    ///
+    /// If there are multiple merge-bases, as it's the case in criss-cross merges, the most recent one is returned.
+    pub fn merge_base(
+        &self,
+        one: impl Into<gix_hash::ObjectId>,
+        two: impl Into<gix_hash::ObjectId>,
+    ) -> Result<Id<'_>, super::merge_base::Error> {
+        let (first, second) = (one.into(), two.into());
+        let bases = gix_revision::merge_base(first, &[second], &mut self.revision_graph())?
+            .ok_or(super::merge_base::Error::NotFound { first, second })?;
+        Ok(bases[0].attach(self))
+    }
+
+    /// Name each of the given `commits` relative to the closest reference they can be reached from, like `git name-rev` does,
+    /// for example as `main~3^2`, with names that are `None` if a commit can't be reached from any reference.
+    ///
+    /// Names derived from tags are preferred, and among tags the oldest one is used.
+    pub fn name_rev(
+        &self,
+        commits: &[gix_hash::ObjectId],
+        options: &crate::commit::name_rev::Options,
+    ) -> Result<Vec<Option<gix_revision::name_rev::Name>>, crate::commit::name_rev::Error> {
+        let tips = crate::commit::name_rev::tips(self, options)?;
+        Ok(gix_revision::name_rev(commits, tips, &mut self.revision_graph())?)
+    }
 }
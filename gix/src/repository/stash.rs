@@ -0,0 +1,416 @@
+use std::{io::Write, path::PathBuf};
+
+use gix_hash::ObjectId;
+use gix_index::entry;
+use gix_merge::blob::Labels;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use crate::{
+    bstr::{BStr, BString},
+    commit,
+    stash::{ApplyOptions, Entry, Error, Outcome, SaveOptions, REF_NAME},
+    worktree::add,
+    Id, Repository,
+};
+
+/// Stashing
+impl Repository {
+    /// Save all changes of the index and of tracked files in the worktree as new stash, and reset the index and the worktree
+    /// to `HEAD`, similar to what `git stash push` does, and configure the operation with `options`.
+    ///
+    /// The stash is a commit holding the state of the worktree, whose parents are the commit at `HEAD`, a commit holding the state
+    /// of the index, and, if untracked files are included, a commit holding only untracked files. The reference `refs/stash`
+    /// is updated to point to it, with its reflog keeping track of all stashes just like `git` does.
+    ///
+    /// Return the id of the new stash, or `None` if there were no changes to save.
+    pub fn stash_save(&self, options: SaveOptions) -> Result<Option<Id<'_>>, Error> {
+        let worktree = self.worktree().ok_or(Error::MissingWorktree)?;
+        if self.head()?.is_unborn() {
+            return Err(Error::UnbornHead);
+        }
+        let head = self.head_commit()?;
+        let head_tree = head.tree_id()?.detach();
+        let mut index = gix_index::File::clone(&*self.index_or_empty()?);
+        check_unmerged(&index)?;
+        let index_tree = self.write_index_tree(&index)?;
+
+        let mut worktree_state = gix_index::State::clone(&index);
+        worktree.add(
+            &mut worktree_state,
+            None::<&BStr>,
+            add::Options {
+                update_only: true,
+                ..Default::default()
+            },
+        )?;
+        worktree_state.remove_entries(|_, _, entry| entry.flags.contains(entry::Flags::REMOVE));
+        let worktree_tree = self.write_index_tree(&worktree_state)?;
+
+        let mut untracked = gix_index::State::new(self.object_hash());
+        if options.include_untracked || options.include_ignored {
+            let mut state = gix_index::State::clone(&index);
+            worktree.add(
+                &mut state,
+                None::<&BStr>,
+                add::Options {
+                    force: options.include_ignored,
+                    update_only: false,
+                },
+            )?;
+            for entry in state.entries() {
+                let path = entry.path(&state);
+                if !entry.flags.contains(entry::Flags::REMOVE)
+                    && !entry.mode.is_submodule()
+                    && index.entry_by_path(path).is_none()
+                {
+                    untracked.dangerously_push_entry(entry.stat, entry.id, entry::Flags::empty(), entry.mode, path);
+                }
+            }
+            untracked.sort_entries();
+        }
+        if index_tree == head_tree && worktree_tree == index_tree && untracked.entries().is_empty() {
+            return Ok(None);
+        }
+
+        let branch = match self.head_name()? {
+            Some(name) => name.shorten().to_owned(),
+            None => "(no branch)".into(),
+        };
+        let on_branch = format!("{branch}: {} {}", head.id().shorten_or_id(), head.message()?.summary());
+        let index_commit = self.write_stash_commit(format!("index on {on_branch}"), index_tree, vec![head.id])?;
+        let mut parents = vec![head.id, index_commit];
+        if !untracked.entries().is_empty() {
+            let untracked_tree = self.write_index_tree(&untracked)?;
+            parents.push(self.write_stash_commit(
+                format!("untracked files on {on_branch}"),
+                untracked_tree,
+                Vec::new(),
+            )?);
+        }
+        let message = match &options.message {
+            Some(message) => format!("On {branch}: {message}"),
+            None => format!("WIP on {on_branch}"),
+        };
+        let stash_id = self.write_stash_commit(message.clone(), worktree_tree, parents)?;
+        self.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: true,
+                    message: message.into(),
+                },
+                expected: PreviousValue::Any,
+                new: Target::Peeled(stash_id),
+            },
+            name: stash_ref_name(),
+            deref: false,
+        })?;
+
+        let mut from = worktree_state;
+        for entry in untracked.entries() {
+            from.dangerously_push_entry(entry.stat, entry.id, entry.flags, entry.mode, entry.path(&untracked));
+        }
+        from.sort_entries();
+        let mut to = if options.keep_index {
+            gix_index::State::clone(&index)
+        } else {
+            gix_index::State::from_tree(&head_tree, &self.objects)?
+        };
+        self.update_worktree(&from, &mut to)?;
+        *index = to;
+        index.write(self.index_write_options()?)?;
+        Ok(Some(Id::from_id(stash_id, self)))
+    }
+
+    /// Return all stashes, with the most recent one first, just like `git stash list` would list them.
+    ///
+    /// The position of a stash in the list is the index that is used to refer to it, as in `stash@{<index>}`.
+    pub fn stash_list(&self) -> Result<Vec<Entry>, Error> {
+        Ok(self
+            .stash_reflog()?
+            .into_iter()
+            .rev()
+            .map(|line| Entry {
+                id: line.new_oid,
+                message: line.message,
+            })
+            .collect())
+    }
+
+    /// Apply the stash at `index` to the worktree, similar to what `git stash apply stash@{<index>}` does,
+    /// and configure the operation with `options`.
+    ///
+    /// The changes of the stash are merged with the current state of the index using a three-way merge with the commit
+    /// the stash was saved on as base. Files that couldn't be merged are recorded as conflicts in the index,
+    /// and untracked files that were saved with the stash are restored as well.
+    /// Nothing is changed if local modifications of files would be overwritten, or if untracked files already exist.
+    pub fn stash_apply(&self, index: usize, options: ApplyOptions) -> Result<Outcome, Error> {
+        let work_dir = self.work_dir().ok_or(Error::MissingWorktree)?;
+        let worktree = self.worktree().ok_or(Error::MissingWorktree)?;
+        let stash_id = self.stash_id(index)?;
+        let stash = self.find_object(stash_id)?.try_into_commit()?;
+        let parents: Vec<_> = stash.parent_ids().map(Id::detach).collect();
+        if !(2..=3).contains(&parents.len()) {
+            return Err(Error::InvalidStash { id: stash_id });
+        }
+        let tree_of = |id: ObjectId| -> Result<ObjectId, Error> {
+            Ok(self.find_object(id)?.try_into_commit()?.tree_id()?.detach())
+        };
+        let stash_tree = stash.tree_id()?.detach();
+        let base_tree = tree_of(parents[0])?;
+        let stashed_index_tree = tree_of(parents[1])?;
+        let untracked = match parents.get(2) {
+            Some(id) => gix_index::State::from_tree(&tree_of(*id)?, &self.objects)?,
+            None => gix_index::State::new(self.object_hash()),
+        };
+
+        let mut index_file = gix_index::File::clone(&*self.index_or_empty()?);
+        check_unmerged(&index_file)?;
+        let current_tree = self.write_index_tree(&index_file)?;
+        let restored_index_tree = if options.index && stashed_index_tree != base_tree {
+            let outcome = self.merge_trees(
+                Some(&base_tree),
+                &current_tree,
+                &stashed_index_tree,
+                Labels::default(),
+                options.merge,
+            )?;
+            if outcome.has_conflicts() {
+                return Err(Error::IndexConflicts);
+            }
+            Some(self.write_index_tree(&outcome.index)?)
+        } else {
+            None
+        };
+        let merged = self.merge_trees(
+            Some(&base_tree),
+            &current_tree,
+            &stash_tree,
+            Labels {
+                base: Some("Version stash was based on".into()),
+                ours: Some("Updated upstream".into()),
+                theirs: Some("Stashed changes".into()),
+            },
+            options.merge,
+        )?;
+
+        // What the worktree should look like, with the merged version of conflicting files.
+        let mut target = merged.worktree_state();
+        for entry in untracked.entries() {
+            target.dangerously_push_entry(
+                entry.stat,
+                entry.id,
+                entry::Flags::empty(),
+                entry.mode,
+                entry.path(&untracked),
+            );
+        }
+        target.sort_entries();
+
+        let mut current_worktree = gix_index::State::clone(&index_file);
+        worktree.add(
+            &mut current_worktree,
+            None::<&BStr>,
+            add::Options {
+                update_only: true,
+                ..Default::default()
+            },
+        )?;
+        let mut overwritten = Vec::new();
+        for entry in index_file.entries() {
+            let path = entry.path(&index_file);
+            let is_modified = current_worktree
+                .entry_by_path(path)
+                .filter(|current| !current.flags.contains(entry::Flags::REMOVE))
+                .map_or(true, |current| current.id != entry.id || current.mode != entry.mode);
+            let is_changed = target
+                .entry_by_path(path)
+                .map_or(true, |new| new.id != entry.id || new.mode != entry.mode);
+            if is_modified && is_changed {
+                overwritten.push(path.to_owned());
+            }
+        }
+        if !overwritten.is_empty() {
+            return Err(Error::LocalChanges { paths: overwritten });
+        }
+        for entry in target.entries() {
+            let path = entry.path(&target);
+            if index_file.entry_by_path(path).is_none()
+                && gix_index::fs::Metadata::from_path_no_follow(&work_dir.join(gix_path::from_bstr(path))).is_ok()
+            {
+                return Err(Error::UntrackedExists { path: path.to_owned() });
+            }
+        }
+
+        let mut from = gix_index::State::clone(&index_file);
+        from.remove_entries(|_, _, entry| entry.flags.contains(entry::Flags::REMOVE));
+        self.update_worktree(&from, &mut target)?;
+
+        let with_stat_of_target = |mut state: gix_index::State| {
+            for entry in state.entries_mut_with_paths() {
+                if let Some(updated) = target
+                    .entry_by_path_and_stage(entry.1, 0)
+                    .filter(|updated| updated.id == entry.0.id && updated.mode == entry.0.mode)
+                {
+                    entry.0.stat = updated.stat;
+                }
+            }
+            state
+        };
+        let new_index = if merged.has_conflicts() {
+            with_stat_of_target(merged.index)
+        } else if let Some(tree) = restored_index_tree {
+            with_stat_of_target(gix_index::State::from_tree(&tree, &self.objects)?)
+        } else {
+            let mut state = from;
+            for entry in merged.index.entries() {
+                let path = entry.path(&merged.index);
+                if state.entry_by_path(path).is_none() {
+                    let stat = target
+                        .entry_by_path(path)
+                        .map_or_else(Default::default, |updated| updated.stat);
+                    state.dangerously_push_entry(stat, entry.id, entry::Flags::empty(), entry.mode, path);
+                }
+            }
+            state.sort_entries();
+            state
+        };
+        *index_file = new_index;
+        index_file.write(self.index_write_options()?)?;
+        Ok(Outcome {
+            conflicts: merged.conflicts,
+        })
+    }
+
+    /// Like [`stash_apply()`](Self::stash_apply()), but drop the stash at `index` if it was applied without conflicts,
+    /// similar to what `git stash pop stash@{<index>}` does.
+    pub fn stash_pop(&self, index: usize, options: ApplyOptions) -> Result<Outcome, Error> {
+        let outcome = self.stash_apply(index, options)?;
+        if outcome.is_clean() {
+            self.stash_drop(index)?;
+        }
+        Ok(outcome)
+    }
+
+    /// Remove the stash at `index` from the list of stashes and return its id, similar to what `git stash drop stash@{<index>}`
+    /// does.
+    ///
+    /// Just like `git`, the reflog of `refs/stash` is rewritten without the stash, and `refs/stash` is deleted along with its
+    /// reflog once the last stash was dropped.
+    pub fn stash_drop(&self, index: usize) -> Result<ObjectId, Error> {
+        let mut lines = self.stash_reflog()?;
+        let len = lines.len();
+        if index >= len {
+            return Err(Error::NoSuchStash { index, len });
+        }
+        let pos = len - 1 - index;
+        let dropped = lines.remove(pos);
+        if lines.is_empty() {
+            self.edit_reference(RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::MustExistAndMatch(Target::Peeled(dropped.new_oid)),
+                    log: RefLog::AndReference,
+                },
+                name: stash_ref_name(),
+                deref: false,
+            })?;
+            return Ok(dropped.new_oid);
+        }
+
+        if let Some(newer) = lines.get_mut(pos) {
+            newer.previous_oid = dropped.previous_oid;
+        } else {
+            let newest = lines.last().expect("not empty");
+            self.edit_reference(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: true,
+                        message: newest.message.clone(),
+                    },
+                    expected: PreviousValue::MustExistAndMatch(Target::Peeled(dropped.new_oid)),
+                    new: Target::Peeled(newest.new_oid),
+                },
+                name: stash_ref_name(),
+                deref: false,
+            })?;
+        }
+        let path = self.stash_reflog_path();
+        let mut file = gix_lock::File::acquire_to_update_resource(&path, gix_lock::acquire::Fail::Immediately, None)?;
+        lines
+            .iter()
+            .try_for_each(|line| line.write_to(&mut file))
+            .and_then(|_| file.flush())
+            .map_err(|source| Error::Reflog {
+                path: path.clone(),
+                source,
+            })?;
+        file.commit()?;
+        Ok(dropped.new_oid)
+    }
+
+    /// Return the id of the stash at `index`.
+    fn stash_id(&self, index: usize) -> Result<ObjectId, Error> {
+        let stashes = self.stash_list()?;
+        stashes.get(index).map(|stash| stash.id).ok_or(Error::NoSuchStash {
+            index,
+            len: stashes.len(),
+        })
+    }
+
+    /// Return all lines of the reflog of `refs/stash`, from oldest to newest.
+    fn stash_reflog(&self) -> Result<Vec<gix_ref::log::Line>, Error> {
+        let Some(reference) = self.try_find_reference(REF_NAME)? else {
+            return Ok(Vec::new());
+        };
+        let mut platform = reference.log_iter();
+        let lines = platform.all().map_err(|source| Error::Reflog {
+            path: self.stash_reflog_path(),
+            source,
+        })?;
+        let Some(lines) = lines else {
+            return Ok(Vec::new());
+        };
+        lines.map(|line| Ok(line?.to_owned())).collect()
+    }
+
+    fn stash_reflog_path(&self) -> PathBuf {
+        self.common_dir().join("logs").join(REF_NAME)
+    }
+
+    fn write_stash_commit(&self, message: String, tree: ObjectId, parents: Vec<ObjectId>) -> Result<ObjectId, Error> {
+        let committer = self.committer().ok_or(commit::Error::CommitterMissing)??;
+        let mut message = BString::from(message);
+        message.push(b'\n');
+        let commit = gix_object::Commit {
+            message,
+            tree,
+            author: committer.into(),
+            committer: committer.into(),
+            encoding: None,
+            parents: parents.into(),
+            extra_headers: Default::default(),
+        };
+        Ok(self.write_object(&commit)?.detach())
+    }
+
+    fn write_index_tree(&self, state: &gix_index::State) -> Result<ObjectId, Error> {
+        Ok(state.write_tree(|tree| self.write_object(tree).map(Id::detach))?)
+    }
+}
+
+fn check_unmerged(index: &gix_index::State) -> Result<(), Error> {
+    match index.entries().iter().find(|entry| entry.stage() != 0) {
+        Some(entry) => Err(Error::Unmerged {
+            path: entry.path(index).to_owned(),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn stash_ref_name() -> FullName {
+    REF_NAME.try_into().expect("valid reference name")
+}
@@ -0,0 +1,82 @@
+use std::ffi::OsString;
+
+use crate::{
+    config::tree::Core,
+    repository::{query_fs_monitor, untracked_cache_lookup},
+    Repository,
+};
+
+/// Status
+impl Repository {
+    /// Ask the filesystem monitor hook configured in `core.fsmonitor` which paths changed since `index` was written,
+    /// and mark all other entries of `index` as [valid](gix_index::entry::Flags::FSMONITOR_VALID) so that
+    /// [`gix_status::index_as_worktree()`] won't have to check them.
+    ///
+    /// Return `None` if no hook is configured, or if this repository has no worktree.
+    /// Note that the builtin filesystem monitor daemon isn't supported, so `core.fsmonitor = true` has no effect.
+    pub fn query_fs_monitor(
+        &self,
+        index: &mut gix_index::State,
+    ) -> Result<Option<gix_status::fs_monitor::Outcome>, query_fs_monitor::Error> {
+        let (Some(work_dir), Some((hook, version))) = (self.work_dir(), self.fs_monitor_hook()?) else {
+            return Ok(None);
+        };
+        index.apply_fs_monitor_dirty_bitmap();
+        let token = index.fs_monitor().map(|fsm| fsm.token().clone());
+        let response = gix_status::fs_monitor::query(hook, version, token.as_ref(), work_dir)?;
+        Ok(Some(gix_status::fs_monitor::apply(index, response)))
+    }
+
+    /// Return a lookup into the untracked cache of `index` to accelerate [directory walks](gix_dir::walk()) in the worktree,
+    /// or `None` if `core.untrackedCache` is `false`, or if it's unset or `keep` and `index` has no untracked cache yet.
+    ///
+    /// Also return `None` if this repository has no worktree.
+    pub fn untracked_cache_lookup<'index>(
+        &self,
+        index: &'index gix_index::State,
+    ) -> Result<Option<gix_dir::untracked_cache::Lookup<'index>>, untracked_cache_lookup::Error> {
+        let Some(work_dir) = self.work_dir() else {
+            return Ok(None);
+        };
+        // `keep`, the default, uses an existing cache, but doesn't create one.
+        let use_untracked_cache = match self.config.resolved.boolean("core", None, Core::UNTRACKED_CACHE.name) {
+            Some(Ok(value)) => value,
+            Some(Err(_)) | None => index.untracked().is_some(),
+        };
+        if !use_untracked_cache {
+            return Ok(None);
+        }
+        let excludes_file = self.config.excludes_file().transpose()?;
+        let info_exclude = self.git_dir().join("info").join("exclude");
+        Ok(Some(gix_dir::untracked_cache::Lookup::new(
+            index,
+            work_dir,
+            Some(&info_exclude),
+            excludes_file.as_deref(),
+            gix_dir::untracked_cache::Options {
+                stat: self.stat_options()?,
+                object_hash: self.object_hash(),
+            },
+        )?))
+    }
+
+    /// Return the filesystem monitor hook program along with the protocol version to use with it, if one is configured.
+    pub(crate) fn fs_monitor_hook(
+        &self,
+    ) -> Result<Option<(OsString, gix_status::fs_monitor::Version)>, query_fs_monitor::Error> {
+        match self.config.resolved.boolean("core", None, Core::FSMONITOR.name) {
+            Some(Ok(_)) | None => return Ok(None),
+            Some(Err(_)) => {}
+        }
+        let hook = self
+            .config_snapshot()
+            .trusted_program("core.fsmonitor")
+            .ok_or(query_fs_monitor::Error::UntrustedHook)?
+            .into_owned();
+        let version = match self.config.resolved.integer("core", None, Core::FSMONITOR_HOOK_VERSION.name) {
+            Some(Ok(1)) => gix_status::fs_monitor::Version::V1,
+            _ => gix_status::fs_monitor::Version::V2,
+        };
+        Ok(Some((hook, version)))
+    }
+}
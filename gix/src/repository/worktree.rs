@@ -1,5 +1,283 @@
 use crate::{worktree, Worktree};
 
+#[cfg(feature = "worktree-mutation")]
+mod mutation {
+    use std::{
+        path::{Path, PathBuf},
+        sync::atomic::AtomicBool,
+    };
+
+    use gix_ref::transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog};
+
+    use crate::{
+        bstr::{BString, ByteSlice},
+        repository::worktree_add::{Error, Target},
+        worktree::proxy::{link_worktree, remove_git_dir, repair, Prunable},
+        Progress, Repository,
+    };
+
+    /// Modification
+    impl Repository {
+        /// Create a new linked worktree at `path` with `target` checked out and return it as repository, similar to `git worktree add`.
+        ///
+        /// `path` must not exist or be an empty directory. The id of the worktree is the name of the last component of `path`,
+        /// with a number appended if it is taken already. The worktree is locked while it is created so it can't be pruned concurrently.
+        /// Files are checked out using `progress`, and `should_interrupt` is checked in the process.
+        pub fn worktree_add<P>(
+            &self,
+            path: impl AsRef<Path>,
+            target: Target,
+            mut progress: P,
+            should_interrupt: &AtomicBool,
+        ) -> Result<Repository, Error>
+        where
+            P: crate::NestedProgress,
+            P::SubProgress: 'static,
+        {
+            self.worktree_add_inner(path.as_ref(), target, &mut progress, should_interrupt)
+        }
+
+        fn worktree_add_inner(
+            &self,
+            path: &Path,
+            target: Target,
+            progress: &mut dyn crate::DynNestedProgress,
+            should_interrupt: &AtomicBool,
+        ) -> Result<Repository, Error> {
+            let path_existed = path.exists();
+            if path_existed && std::fs::read_dir(path).map_or(true, |mut dir| dir.next().is_some()) {
+                return Err(Error::PathExists { path: path.to_owned() });
+            }
+            let id = match &target {
+                Target::Branch(name) => {
+                    let mut branch = self
+                        .try_find_reference(name.as_ref())?
+                        .ok_or_else(|| Error::MissingBranch { name: name.clone() })?;
+                    if let Some(path) = self.worktree_with_branch_checked_out(name.as_ref()) {
+                        return Err(Error::BranchCheckedOut {
+                            name: name.clone(),
+                            path,
+                        });
+                    }
+                    branch.peel_to_id_in_place()?.detach()
+                }
+                Target::NewBranch { name, id } => {
+                    if self.try_find_reference(name.as_ref())?.is_some() {
+                        return Err(Error::BranchExists { name: name.clone() });
+                    }
+                    *id
+                }
+                Target::Detached(id) => *id,
+            };
+            let tree = self.find_object(id)?.peel_to_tree()?.id;
+
+            let io_err = |path: &Path| {
+                let path = path.to_owned();
+                move |source| Error::Io { path, source }
+            };
+            let worktrees = self.common_dir().join("worktrees");
+            std::fs::create_dir_all(&worktrees).map_err(io_err(&worktrees))?;
+            let name = worktree_id(path);
+            let mut counter = 0;
+            let git_dir = loop {
+                let candidate = match counter {
+                    0 => worktrees.join(&name),
+                    _ => worktrees.join(format!("{name}{counter}")),
+                };
+                match std::fs::create_dir(&candidate) {
+                    Ok(()) => break candidate,
+                    Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => counter += 1,
+                    Err(source) => {
+                        return Err(Error::Io {
+                            path: candidate,
+                            source,
+                        })
+                    }
+                }
+            };
+
+            let res = self.populate_worktree(&git_dir, path, &target, tree, progress, should_interrupt);
+            if res.is_err() {
+                remove_git_dir(&git_dir).ok();
+                std::fs::remove_dir_all(path).ok();
+                if path_existed {
+                    std::fs::create_dir(path).ok();
+                }
+            }
+            res
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        fn populate_worktree(
+            &self,
+            git_dir: &Path,
+            path: &Path,
+            target: &Target,
+            tree: gix_hash::ObjectId,
+            progress: &mut dyn crate::DynNestedProgress,
+            should_interrupt: &AtomicBool,
+        ) -> Result<Repository, Error> {
+            let write = |path: PathBuf, content: &[u8]| {
+                std::fs::write(&path, content).map_err(|source| Error::Io { path, source })
+            };
+            let locked = git_dir.join("locked");
+            write(locked.clone(), b"initializing\n")?;
+            std::fs::create_dir_all(path).map_err(|source| Error::Io {
+                path: path.to_owned(),
+                source,
+            })?;
+            write(git_dir.join("commondir"), b"../..\n")?;
+            let head = match target {
+                Target::Branch(name) | Target::NewBranch { name, .. } => format!("ref: {}\n", name.as_bstr()),
+                Target::Detached(id) => format!("{id}\n"),
+            };
+            write(git_dir.join("HEAD"), head.as_bytes())?;
+            link_worktree(git_dir, path)?;
+
+            if let Target::NewBranch { name, id } = target {
+                self.edit_reference(RefEdit {
+                    change: Change::Update {
+                        log: LogChange {
+                            mode: RefLog::AndReference,
+                            force_create_reflog: false,
+                            message: format!("branch: Created from {id}").into(),
+                        },
+                        expected: PreviousValue::MustNotExist,
+                        new: gix_ref::Target::Peeled(*id),
+                    },
+                    name: name.clone(),
+                    deref: false,
+                })?;
+            }
+
+            let repo = crate::open_opts(path, self.options.clone())?;
+            let index = gix_index::State::from_tree(&tree, &repo.objects)
+                .map_err(|source| Error::IndexFromTree { id: tree, source })?;
+            let mut index = gix_index::File::from_state(index, repo.index_path());
+            let mut opts = repo
+                .config
+                .checkout_options(&repo, gix_worktree::stack::state::attributes::Source::IdMapping)?;
+            opts.destination_is_initially_empty = true;
+
+            let mut files = progress.add_child("checkout".into());
+            let mut bytes = progress.add_child("writing".into());
+            files.init(Some(index.entries().len()), crate::progress::count("files"));
+            bytes.init(None, crate::progress::bytes());
+            gix_worktree_state::checkout(
+                &mut index,
+                path,
+                repo.objects.clone().into_arc().map_err(Error::OpenArcOdb)?,
+                &files,
+                &bytes,
+                should_interrupt,
+                opts,
+            )?;
+            index.write(repo.index_write_options()?)?;
+
+            std::fs::remove_file(&locked).map_err(|source| Error::Io { path: locked, source })?;
+            Ok(repo)
+        }
+
+        /// Return the location of the worktree that has the branch `name` checked out, if there is one.
+        fn worktree_with_branch_checked_out(&self, name: &gix_ref::FullNameRef) -> Option<PathBuf> {
+            let has_branch_checked_out = |git_dir: &Path| {
+                std::fs::read(git_dir.join("HEAD")).map_or(false, |head| {
+                    head.trim()
+                        .strip_prefix(b"ref:")
+                        .map_or(false, |target| target.trim() == name.as_bstr())
+                })
+            };
+            let common_dir = self.common_dir();
+            if !self.config.is_bare && has_branch_checked_out(common_dir) {
+                return Some(common_dir.parent().unwrap_or(common_dir).to_owned());
+            }
+            self.worktrees()
+                .ok()?
+                .into_iter()
+                .find(|proxy| has_branch_checked_out(proxy.git_dir()))
+                .map(|proxy| proxy.base().unwrap_or_else(|_| proxy.git_dir().to_owned()))
+        }
+
+        /// Remove the administrative files of all linked worktrees that are [prunable](crate::worktree::Proxy::prunable()),
+        /// similar to `git worktree prune`, and return their ids along with the reason for pruning them, sorted by id.
+        ///
+        /// If `dry_run` is `true`, nothing is removed. Note that locked worktrees are never pruned.
+        pub fn prune_worktrees(&self, dry_run: bool) -> std::io::Result<Vec<(BString, Prunable)>> {
+            let mut git_dirs = match std::fs::read_dir(self.common_dir().join("worktrees")) {
+                Ok(iter) => iter
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<Result<Vec<_>, _>>()?,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(err) => return Err(err),
+            };
+            git_dirs.sort();
+            let mut out = Vec::new();
+            for git_dir in git_dirs {
+                let Some(reason) = Prunable::from_git_dir(&git_dir) else {
+                    continue;
+                };
+                if !dry_run {
+                    remove_git_dir(&git_dir)?;
+                }
+                let id =
+                    gix_path::into_bstr(Path::new(git_dir.file_name().expect("in worktrees directory"))).into_owned();
+                out.push((id, reason));
+            }
+            Ok(out)
+        }
+
+        /// Point the administrative files of the linked worktree whose checkout was moved to `path` without git's knowledge
+        /// to its new location, and return `true` if they had to be changed, similar to `git worktree repair <path>`.
+        ///
+        /// To repair worktrees whose `.git` file is broken instead, use [`Proxy::repair()`](crate::worktree::Proxy::repair()).
+        pub fn repair_worktree(&self, path: impl AsRef<Path>) -> Result<bool, repair::Error> {
+            let path = path.as_ref();
+            let not_linked = || repair::Error::NotALinkedWorktree { path: path.to_owned() };
+            let git_dir = gix_discover::path::from_gitdir_file(&path.join(gix_discover::DOT_GIT_DIR))
+                .ok()
+                .and_then(|git_dir| gix_path::realpath(git_dir).ok())
+                .ok_or_else(not_linked)?;
+            let worktrees = gix_path::realpath(self.common_dir().join("worktrees")).map_err(|_| not_linked())?;
+            if git_dir.parent() != Some(worktrees.as_path()) || !git_dir.is_dir() {
+                return Err(not_linked());
+            }
+            let expected = gix_path::realpath(path)
+                .map_err(|_| not_linked())?
+                .join(gix_discover::DOT_GIT_DIR);
+            let actual = gix_discover::path::from_plain_file(&git_dir.join("gitdir")).and_then(Result::ok);
+            if actual.as_deref() == Some(expected.as_path()) {
+                return Ok(false);
+            }
+            link_worktree(&git_dir, path)?;
+            Ok(true)
+        }
+    }
+
+    /// Derive the id of a worktree from the last component of its `path`, replacing characters that are unsuitable for directory names.
+    fn worktree_id(path: &Path) -> String {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let name: String = name
+            .trim_start_matches('.')
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        if name.is_empty() {
+            "worktree".into()
+        } else {
+            name
+        }
+    }
+}
+
 /// Interact with individual worktrees and their information.
 impl crate::Repository {
     /// Return a list of all _linked_ worktrees sorted by private git dir path as a lightweight proxy.
@@ -139,3 +417,33 @@ impl crate::Repository {
         Ok(())
     }
 }
+
+#[cfg(any(feature = "stash", feature = "rebase"))]
+mod update {
+    use std::sync::atomic::AtomicBool;
+
+    use crate::{repository::update_worktree::Error, Repository};
+
+    impl Repository {
+        /// Bring the worktree, which matches `from`, in line with `to`.
+        pub(crate) fn update_worktree(&self, from: &gix_index::State, to: &mut gix_index::State) -> Result<(), Error> {
+            let work_dir = self.work_dir().ok_or(Error::MissingWorktree)?;
+            let checkout = self.config.checkout_options(
+                self,
+                gix_worktree::stack::state::attributes::Source::WorktreeThenIdMapping,
+            )?;
+            let objects = self.objects.clone().into_arc().map_err(Error::ObjectStore)?;
+            gix_worktree_state::update(
+                from,
+                to,
+                work_dir,
+                objects,
+                &gix_features::progress::Discard,
+                &gix_features::progress::Discard,
+                &AtomicBool::default(),
+                gix_worktree_state::update::Options { checkout },
+            )?;
+            Ok(())
+        }
+    }
+}
@@ -1,9 +1,29 @@
 use crate::bstr::BStr;
-use crate::{ext::ReferenceExt, revision::Spec, Id, Reference};
+use crate::{
+    ext::{ObjectIdExt, ReferenceExt},
+    revision,
+    revision::Spec,
+    Id, Reference,
+};
 
 ///
 pub mod parse;
 
+///
+pub mod into_walk {
+    /// The error returned by [`Spec::into_walk()`](crate::revision::Spec::into_walk()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        PeelToCommit(#[from] crate::object::peel::to_kind::Error),
+        #[error(transparent)]
+        MergeBase(#[from] gix_revision::merge_base::Error),
+    }
+}
+
 mod impls {
     use std::ops::{Deref, DerefMut};
 
@@ -97,3 +117,40 @@ impl<'repo> Spec<'repo> {
         }
     }
 }
+
+/// Conversion
+impl<'repo> Spec<'repo> {
+    /// Turn this instance into a [revision walk](revision::walk::Platform) over the commits it describes,
+    /// similar to what `git rev-list <spec>` would return.
+    ///
+    /// For instance, `a..b` yields all commits reachable from `b` but not from `a`, and `a...b` yields all commits
+    /// reachable from either `a` or `b`, but not from both. All objects are peeled to commits.
+    pub fn into_walk(self) -> Result<revision::walk::Platform<'repo>, into_walk::Error> {
+        let repo = self.repo;
+        let commit = |id: gix_hash::ObjectId| -> Result<_, into_walk::Error> {
+            Ok(id
+                .attach(repo)
+                .object()?
+                .peel_to_kind(gix_object::Kind::Commit)?
+                .into_commit())
+        };
+        let (tips, hidden) = match self.inner {
+            gix_revision::Spec::Include(id) => (vec![commit(id)?.id], Vec::new()),
+            gix_revision::Spec::Exclude(id) => (Vec::new(), vec![commit(id)?.id]),
+            gix_revision::Spec::Range { from, to } => (vec![commit(to)?.id], vec![commit(from)?.id]),
+            gix_revision::Spec::Merge { theirs, ours } => {
+                let (theirs, ours) = (commit(theirs)?.id, commit(ours)?.id);
+                let bases = gix_revision::merge_base(theirs, &[ours], &mut repo.revision_graph())?.unwrap_or_default();
+                (vec![theirs, ours], bases)
+            }
+            gix_revision::Spec::IncludeOnlyParents(id) => {
+                (commit(id)?.parent_ids().map(Id::detach).collect(), Vec::new())
+            }
+            gix_revision::Spec::ExcludeParents(id) => {
+                let commit = commit(id)?;
+                (vec![commit.id], commit.parent_ids().map(Id::detach).collect())
+            }
+        };
+        Ok(repo.rev_walk(tips).hide(hidden))
+    }
+}
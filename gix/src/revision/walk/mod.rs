@@ -13,6 +13,12 @@ pub enum Error {
     ShallowCommits(#[from] crate::shallow::open::Error),
     #[error(transparent)]
     ConfigBoolean(#[from] crate::config::boolean::Error),
+    #[cfg(feature = "blob-diff")]
+    #[error("Following renames requires a pathspec with exactly one path")]
+    FollowRequiresSinglePath,
+    #[cfg(feature = "blob-diff")]
+    #[error(transparent)]
+    DiffRenames(#[from] crate::diff::new_rewrites::Error),
 }
 
 /// Information about a commit that we obtained naturally as part of the iteration.
@@ -91,8 +97,15 @@ impl<'repo> Info<'repo> {
 pub struct Platform<'repo> {
     pub(crate) repo: &'repo Repository,
     pub(crate) tips: Vec<ObjectId>,
+    pub(crate) hidden: Vec<ObjectId>,
     pub(crate) sorting: gix_traverse::commit::Sorting,
+    pub(crate) reverse: bool,
     pub(crate) parents: gix_traverse::commit::Parents,
+    pub(crate) simplification: gix_traverse::commit::Simplification,
+    #[cfg(feature = "blob-diff")]
+    pub(crate) pathspec: Option<crate::Pathspec<'repo>>,
+    #[cfg(feature = "blob-diff")]
+    pub(crate) follow_renames: bool,
     pub(crate) use_commit_graph: Option<bool>,
     pub(crate) commit_graph: Option<gix_commitgraph::Graph>,
 }
@@ -102,8 +115,15 @@ impl<'repo> Platform<'repo> {
         revision::walk::Platform {
             repo,
             tips: tips.into_iter().map(Into::into).collect(),
+            hidden: Vec::new(),
             sorting: Default::default(),
+            reverse: false,
             parents: Default::default(),
+            simplification: Default::default(),
+            #[cfg(feature = "blob-diff")]
+            pathspec: None,
+            #[cfg(feature = "blob-diff")]
+            follow_renames: false,
             use_commit_graph: None,
             commit_graph: None,
         }
@@ -118,12 +138,59 @@ impl<'repo> Platform<'repo> {
         self
     }
 
+    /// Return commits in reverse order of the configured [sorting](Platform::sorting()) if `toggle` is `true`,
+    /// like `git rev-list --reverse`.
+    ///
+    /// Note that this means that all commits are traversed before the first one can be returned.
+    pub fn reverse(mut self, toggle: bool) -> Self {
+        self.reverse = toggle;
+        self
+    }
+
+    /// Don't return the commits reachable from `tips`, nor `tips` themselves, like `^tip` does in `git rev-list`.
+    ///
+    /// This is done efficiently by painting all commits reachable from hidden tips and stopping as early as possible,
+    /// which will be exact if a commit-graph is available thanks to its generation numbers.
+    pub fn hide(mut self, tips: impl IntoIterator<Item = impl Into<ObjectId>>) -> Self {
+        self.hidden.extend(tips.into_iter().map(Into::into));
+        self
+    }
+
     /// Only traverse the first parent of the commit graph.
     pub fn first_parent_only(mut self) -> Self {
         self.parents = gix_traverse::commit::Parents::First;
         self
     }
 
+    /// Only return commits that change paths matching `pathspec`, like `git log -- <pathspec>` does, and simplify the history
+    /// as configured with [`simplification()`](Platform::simplification()).
+    ///
+    /// Changed-path Bloom filters of the commit-graph are used to avoid comparing trees if possible, unless the commit-graph
+    /// [isn't used](Platform::use_commit_graph()).
+    #[cfg(feature = "blob-diff")]
+    pub fn with_pathspec(mut self, pathspec: crate::Pathspec<'repo>) -> Self {
+        self.pathspec = Some(pathspec);
+        self
+    }
+
+    /// Set how to simplify the history if there is a [pathspec](Platform::with_pathspec()), with the default being to
+    /// only follow the parent of a merge that is the same as the merge in all paths of the pathspec, if there is one.
+    pub fn simplification(mut self, simplification: gix_traverse::commit::Simplification) -> Self {
+        self.simplification = simplification;
+        self
+    }
+
+    /// If `toggle` is `true`, follow the single path of the [pathspec](Platform::with_pathspec()) across renames and copies,
+    /// like `git log --follow -- <path>`.
+    ///
+    /// Note that renames are detected as configured in `diff.renames`, and that just like in `git`, this works best for
+    /// linear history as there is only one path to follow at a time.
+    #[cfg(feature = "blob-diff")]
+    pub fn follow_renames(mut self, toggle: bool) -> Self {
+        self.follow_renames = toggle;
+        self
+    }
+
     /// Allow using the commitgraph, if present, if `toggle` is `true`, or disallow it with `false`. Set it to `None` to leave
     /// control over this to the configuration of `core.commitGraph` (the default).
     ///
@@ -151,63 +218,55 @@ impl<'repo> Platform<'repo> {
     /// if the traversal should exclude it and its ancestry entirely.
     ///
     /// If `filter` is None, no pruning of the graph will be performed which is the default.
-    pub fn selected(
-        self,
-        mut filter: impl FnMut(&gix_hash::oid) -> bool + 'repo,
-    ) -> Result<revision::Walk<'repo>, Error> {
+    pub fn selected(self, filter: impl FnMut(&gix_hash::oid) -> bool + 'repo) -> Result<revision::Walk<'repo>, Error> {
         let Platform {
             repo,
             tips,
+            hidden,
             sorting,
+            reverse,
             parents,
+            simplification,
+            #[cfg(feature = "blob-diff")]
+            pathspec,
+            #[cfg(feature = "blob-diff")]
+            follow_renames,
             use_commit_graph,
             commit_graph,
         } = self;
+        let may_use_commit_graph = use_commit_graph.map_or_else(|| repo.config.may_use_commit_graph(), Ok)?;
         Ok(revision::Walk {
             repo,
             inner: Box::new(
-                gix_traverse::commit::Ancestors::filtered(
+                gix_traverse::commit::Ancestors::with_filter(
                     tips,
                     gix_traverse::commit::ancestors::State::default(),
                     &repo.objects,
-                    {
-                        // Note that specific shallow handling for commit-graphs isn't needed as these contain
-                        // all information there is, and exclude shallow parents to be structurally consistent.
-                        let shallow_commits = repo.shallow_commits()?;
-                        let mut grafted_parents_to_skip = Vec::new();
-                        let mut buf = Vec::new();
-                        move |id| {
-                            if !filter(id) {
-                                return false;
-                            }
-                            match shallow_commits.as_ref() {
-                                Some(commits) => {
-                                    let id = id.to_owned();
-                                    if let Ok(idx) = grafted_parents_to_skip.binary_search(&id) {
-                                        grafted_parents_to_skip.remove(idx);
-                                        return false;
-                                    };
-                                    if commits.binary_search(&id).is_ok() {
-                                        if let Ok(commit) = repo.objects.find_commit_iter(&id, &mut buf) {
-                                            grafted_parents_to_skip.extend(commit.parent_ids());
-                                            grafted_parents_to_skip.sort();
-                                        }
-                                    };
-                                    true
-                                }
-                                None => true,
-                            }
-                        }
+                    Selection {
+                        repo,
+                        filter,
+                        shallow_commits: repo.shallow_commits()?,
+                        grafted_parents_to_skip: Vec::new(),
+                        buf: Vec::new(),
+                        #[cfg(feature = "blob-diff")]
+                        paths: pathspec
+                            .map(|pathspec| {
+                                paths::Paths::new(
+                                    repo,
+                                    pathspec,
+                                    follow_renames,
+                                    may_use_commit_graph.then(|| repo.commit_graph().ok()).flatten(),
+                                )
+                            })
+                            .transpose()?,
                     },
                 )
                 .sorting(sorting)?
                 .parents(parents)
-                .commit_graph(
-                    commit_graph.or(use_commit_graph
-                        .map_or_else(|| self.repo.config.may_use_commit_graph(), Ok)?
-                        .then(|| self.repo.commit_graph().ok())
-                        .flatten()),
-                ),
+                .simplification(simplification)
+                .commit_graph(commit_graph.or(may_use_commit_graph.then(|| repo.commit_graph().ok()).flatten()))
+                .hide(hidden)?
+                .reverse(reverse),
             ),
         })
     }
@@ -222,6 +281,63 @@ impl<'repo> Platform<'repo> {
     }
 }
 
+/// The filter of the traversal, which decides which commits to traverse and compares them to their parents
+/// by the paths of the pathspec, if there is one.
+struct Selection<'repo, F> {
+    repo: &'repo Repository,
+    filter: F,
+    shallow_commits: Option<crate::shallow::Commits>,
+    grafted_parents_to_skip: Vec<ObjectId>,
+    buf: Vec<u8>,
+    #[cfg(feature = "blob-diff")]
+    paths: Option<paths::Paths<'repo>>,
+}
+
+impl<F> gix_traverse::commit::Filter for Selection<'_, F>
+where
+    F: FnMut(&gix_hash::oid) -> bool,
+{
+    fn keep(&mut self, id: &gix_hash::oid) -> bool {
+        if !(self.filter)(id) {
+            return false;
+        }
+        // Note that specific shallow handling for commit-graphs isn't needed as these contain
+        // all information there is, and exclude shallow parents to be structurally consistent.
+        match self.shallow_commits.as_ref() {
+            Some(commits) => {
+                let id = id.to_owned();
+                if let Ok(idx) = self.grafted_parents_to_skip.binary_search(&id) {
+                    self.grafted_parents_to_skip.remove(idx);
+                    return false;
+                };
+                if commits.binary_search(&id).is_ok() {
+                    if let Ok(commit) = self.repo.objects.find_commit_iter(&id, &mut self.buf) {
+                        self.grafted_parents_to_skip.extend(commit.parent_ids());
+                        self.grafted_parents_to_skip.sort();
+                    }
+                };
+                true
+            }
+            None => true,
+        }
+    }
+
+    #[cfg(feature = "blob-diff")]
+    fn is_treesame(
+        &mut self,
+        id: &gix_hash::oid,
+        parent: Option<&gix_hash::oid>,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        match self.paths.as_mut() {
+            Some(paths) => paths.is_treesame(id, parent),
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(feature = "blob-diff")]
+mod paths;
+
 pub(crate) mod iter {
     /// The iterator returned by [`crate::revision::walk::Platform::all()`].
     pub struct Walk<'repo> {
@@ -0,0 +1,215 @@
+use gix_hash::oid;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    diff::Rewrites,
+    object::tree::diff::{change::Event, for_each, Action},
+    revision::walk::Error,
+    Pathspec, Repository, Tree,
+};
+
+type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Compare commits to their parents by the paths matching a pathspec, to see if they are *TREESAME*.
+pub(super) struct Paths<'repo> {
+    repo: &'repo Repository,
+    pathspec: Pathspec<'repo>,
+    /// The path to follow across renames, along with the configuration to find the renames with, if enabled.
+    follow: Option<(BString, Rewrites)>,
+    /// The commit-graph to read changed-path Bloom filters from, along with the keys to look up for each pattern,
+    /// or `None` if Bloom filters can't be used.
+    bloom: Option<(gix_commitgraph::Graph, Vec<Vec<BString>>)>,
+    buf: Vec<u8>,
+}
+
+impl<'repo> Paths<'repo> {
+    /// Compare commits of `repo` by the paths matching `pathspec`, or follow its single path across renames if `follow` is `true`.
+    /// Use the Bloom filters of `commit_graph` to avoid diffing trees if possible.
+    pub(super) fn new(
+        repo: &'repo Repository,
+        pathspec: Pathspec<'repo>,
+        follow: bool,
+        commit_graph: Option<gix_commitgraph::Graph>,
+    ) -> Result<Self, Error> {
+        let follow = if follow {
+            let mut patterns = pathspec.search().patterns();
+            let path = match (patterns.next(), patterns.next()) {
+                (Some(pattern), None) if !pattern.is_excluded() && bloom_filter_keys(pattern).is_some() => {
+                    trim_trailing_slashes(pattern.path()).to_owned()
+                }
+                _ => return Err(Error::FollowRequiresSinglePath),
+            };
+            let mut rewrites = repo.config.diff_renames()?.unwrap_or_default();
+            if let Some(copies) = rewrites.copies.as_mut() {
+                // Like `git`, find the sources of copies in all files as only a single path is of interest.
+                copies.source = gix_diff::rewrites::CopySource::FromSetOfModifiedFilesAndAllSources;
+            }
+            Some((path, rewrites))
+        } else {
+            None
+        };
+        // Bloom filters only know the original paths, so they can't be used while following renames.
+        let bloom = commit_graph.filter(|_| follow.is_none()).and_then(|graph| {
+            let keys = pathspec
+                .search()
+                .patterns()
+                .map(|pattern| (!pattern.is_excluded()).then(|| bloom_filter_keys(pattern)).flatten())
+                .collect::<Option<Vec<_>>>()?;
+            (!keys.is_empty()).then_some((graph, keys))
+        });
+        Ok(Paths {
+            repo,
+            pathspec,
+            follow,
+            bloom,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Return `true` if commit `id` is the same as its `parent` in all paths that we are interested in, or as the empty tree
+    /// if `parent` is `None`.
+    pub(super) fn is_treesame(&mut self, id: &oid, parent: Option<&oid>) -> Result<bool, BoxedError> {
+        if self.is_unchanged_according_to_bloom_filter(id, parent) {
+            return Ok(true);
+        }
+        let Some(tree) = self.tree_of(id)? else {
+            return Ok(false);
+        };
+        let parent_tree = match parent {
+            Some(parent) => match self.tree_of(parent)? {
+                Some(tree) => tree,
+                // Parents may be missing in shallow repositories.
+                None => return Ok(false),
+            },
+            None => self.repo.empty_tree(),
+        };
+
+        if self.follow.is_some() {
+            return self.is_treesame_following_renames(&tree, &parent_tree);
+        }
+        let mut is_same = true;
+        let pathspec = &mut self.pathspec;
+        let outcome = parent_tree
+            .changes()?
+            .track_path()
+            .track_rewrites(None)
+            .for_each_to_obtain_tree(&tree, |change| {
+                if change.event.entry_mode().is_tree() || !pathspec.is_included(change.location, Some(false)) {
+                    return Ok::<_, std::convert::Infallible>(Action::Continue);
+                }
+                is_same = false;
+                Ok(Action::Cancel)
+            });
+        match outcome {
+            Ok(_) | Err(for_each::Error::Diff(gix_diff::tree::changes::Error::Cancelled)) => Ok(is_same),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Compare the path we follow in `tree` and `parent_tree`, and if it was added, continue to follow its source
+    /// if it was renamed or copied.
+    fn is_treesame_following_renames(
+        &mut self,
+        tree: &Tree<'repo>,
+        parent_tree: &Tree<'repo>,
+    ) -> Result<bool, BoxedError> {
+        let (path, rewrites) = self.follow.clone().expect("only called when following renames");
+        let current = self.entry_at(tree, path.as_ref())?;
+        let previous = self.entry_at(parent_tree, path.as_ref())?;
+        if current == previous {
+            return Ok(true);
+        }
+        if current.is_some() && previous.is_none() {
+            let mut source = None;
+            let outcome = parent_tree
+                .changes()?
+                .track_path()
+                .track_rewrites(Some(rewrites))
+                .for_each_to_obtain_tree(tree, |change| match change.event {
+                    Event::Rewrite { source_location, .. } if change.location == path => {
+                        source = Some(source_location.to_owned());
+                        Ok::<_, std::convert::Infallible>(Action::Cancel)
+                    }
+                    _ => Ok(Action::Continue),
+                });
+            match outcome {
+                Ok(_) | Err(for_each::Error::Diff(gix_diff::tree::changes::Error::Cancelled)) => {}
+                Err(err) => return Err(err.into()),
+            }
+            if let (Some(source), Some((path, _rewrites))) = (source, self.follow.as_mut()) {
+                *path = source;
+            }
+        }
+        Ok(false)
+    }
+
+    /// Return `true` if the Bloom filter of commit `id` tells us for sure that none of our paths changed compared to `parent`.
+    ///
+    /// Bloom filters only contain the changes compared to the first parent.
+    fn is_unchanged_according_to_bloom_filter(&self, id: &oid, parent: Option<&oid>) -> bool {
+        let (Some((graph, keys)), Some(parent)) = (self.bloom.as_ref(), parent) else {
+            return false;
+        };
+        let Some(commit) = graph.commit_by_id(id) else {
+            return false;
+        };
+        let is_first_parent = commit
+            .parent1()
+            .ok()
+            .flatten()
+            .map_or(false, |pos| graph.id_at(pos) == parent);
+        let Some(filter) = commit.bloom_filter().filter(|_| is_first_parent) else {
+            return false;
+        };
+        !keys
+            .iter()
+            .any(|keys| keys.iter().all(|key| filter.may_contain(key.as_ref())))
+    }
+
+    fn tree_of(&self, commit: &oid) -> Result<Option<Tree<'repo>>, BoxedError> {
+        match self.repo.try_find_object(commit)? {
+            Some(object) => Ok(Some(object.try_into_commit()?.tree()?)),
+            None => Ok(None),
+        }
+    }
+
+    fn entry_at(
+        &mut self,
+        tree: &Tree<'repo>,
+        path: &BStr,
+    ) -> Result<Option<(gix_object::tree::EntryMode, gix_hash::ObjectId)>, BoxedError> {
+        Ok(tree
+            .lookup_entry(path.split(|b| *b == b'/'), &mut self.buf)?
+            .map(|entry| (entry.mode(), entry.object_id())))
+    }
+}
+
+/// Return the keys to look up in Bloom filters for `pattern`, which are its path and all of its leading directories,
+/// or `None` if it's not a plain path.
+fn bloom_filter_keys(pattern: &gix_pathspec::Pattern) -> Option<Vec<BString>> {
+    let path = trim_trailing_slashes(pattern.path());
+    let is_plain_path = !pattern.is_nil()
+        && !path.is_empty()
+        && pattern.attributes.is_empty()
+        && !pattern.signature.contains(gix_pathspec::MagicSignature::ICASE)
+        && (pattern.search_mode == gix_pathspec::SearchMode::Literal
+            || !path.iter().any(|b| matches!(b, b'*' | b'?' | b'[' | b'\\')));
+    if !is_plain_path {
+        return None;
+    }
+    Some(
+        std::iter::once(path)
+            .chain(
+                path.iter()
+                    .enumerate()
+                    .filter(|(_idx, b)| **b == b'/')
+                    .map(|(idx, _b)| path[..idx].as_bstr()),
+            )
+            .map(ToOwned::to_owned)
+            .collect(),
+    )
+}
+
+fn trim_trailing_slashes(path: &BStr) -> &BStr {
+    path.trim_end_with(|c| c == '/').as_bstr()
+}
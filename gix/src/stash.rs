@@ -0,0 +1,127 @@
+use crate::bstr::BString;
+
+/// The name of the reference whose reflog holds all stashes, with the most recent stash being the one it points to.
+pub const REF_NAME: &str = "refs/stash";
+
+/// Options for use in [`Repository::stash_save()`](crate::Repository::stash_save()).
+#[derive(Default, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct SaveOptions {
+    /// The message to describe the stash with, like `git stash push -m <message>`.
+    ///
+    /// If unset, the branch and the subject of the commit at `HEAD` are used, just like `git` does.
+    pub message: Option<BString>,
+    /// If `true`, untracked files are stashed as well and removed from the worktree, like `git stash push --include-untracked`.
+    pub include_untracked: bool,
+    /// If `true`, ignored files are stashed along with untracked files and removed from the worktree, like `git stash push --all`.
+    pub include_ignored: bool,
+    /// If `true`, changes that are added to the index are kept in the index and in the worktree, like `git stash push --keep-index`.
+    pub keep_index: bool,
+}
+
+/// Options for use in [`Repository::stash_apply()`](crate::Repository::stash_apply()) and
+/// [`Repository::stash_pop()`](crate::Repository::stash_pop()).
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ApplyOptions {
+    /// If `true`, restore the changes that were added to the index as well, like `git stash apply --index`.
+    ///
+    /// Otherwise, only the worktree is changed, and new files are added to the index.
+    pub index: bool,
+    /// Control how files that were changed in the stash and in the worktree are merged.
+    pub merge: gix_merge::tree::Options,
+}
+
+/// A single stash as listed by [`Repository::stash_list()`](crate::Repository::stash_list()).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Entry {
+    /// The id of the commit that holds the state of the worktree, with the commit that was checked out as first parent,
+    /// the commit holding the state of the index as second parent, and the commit holding untracked files as optional
+    /// third parent.
+    pub id: gix_hash::ObjectId,
+    /// The message of the stash, like `WIP on main: 1234567 subject`.
+    pub message: BString,
+}
+
+/// The outcome of [`Repository::stash_apply()`](crate::Repository::stash_apply()) and
+/// [`Repository::stash_pop()`](crate::Repository::stash_pop()).
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Outcome {
+    /// All paths that couldn't be merged, which are recorded as conflicts in the index and have conflict markers in the
+    /// worktree if the content couldn't be merged.
+    pub conflicts: Vec<gix_merge::tree::Conflict>,
+}
+
+impl Outcome {
+    /// Return `true` if the stash was applied without conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// The error returned by the `stash_*()` methods of [`Repository`](crate::Repository).
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Stashes can only be saved and applied in repositories with a worktree")]
+    MissingWorktree,
+    #[error("Cannot stash changes without an initial commit")]
+    UnbornHead,
+    #[error("Cannot save or apply stashes while the index has unmerged paths, the first being '{path}'")]
+    Unmerged { path: BString },
+    #[error("There is no stash at index {index}, as there are only {len} stashes")]
+    NoSuchStash { index: usize, len: usize },
+    #[error("Stash {id} is not a valid stash commit")]
+    InvalidStash { id: gix_hash::ObjectId },
+    #[error("The local changes to {paths:?} would be overwritten by applying the stash")]
+    LocalChanges { paths: Vec<BString> },
+    #[error("The untracked file '{path}' already exists and would be overwritten by applying the stash")]
+    UntrackedExists { path: BString },
+    #[error("The changes of the stash to the index conflict with the current index, try without restoring the index")]
+    IndexConflicts,
+    #[error(transparent)]
+    Merge(#[from] gix_merge::tree::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    AddToIndex(#[from] crate::worktree::add::Error),
+    #[error(transparent)]
+    IndexWriteOptions(#[from] crate::config::index_write_options::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error(transparent)]
+    WriteTree(#[from] gix_index::write_tree::Error),
+    #[error(transparent)]
+    TreeTraversal(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    UpdateWorktree(#[from] crate::repository::update_worktree::Error),
+    #[error(transparent)]
+    HeadCommit(#[from] crate::reference::head_commit::Error),
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+    #[error(transparent)]
+    FindOptionalReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    Commit(#[from] crate::commit::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    CommitterTime(#[from] crate::config::time::Error),
+    #[error("Could not read or write the reflog at '{}'", path.display())]
+    Reflog {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    DecodeReflog(#[from] gix_ref::file::log::iter::decode::Error),
+    #[error(transparent)]
+    Lock(#[from] gix_lock::acquire::Error),
+    #[error(transparent)]
+    CommitLock(#[from] gix_lock::commit::Error<gix_lock::File>),
+}
@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+
+use crate::{bstr::BString, Submodule};
+
+/// The error returned by [`Submodule::absorb_git_dir()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Path(#[from] gix_submodule::config::path::Error),
+    #[error("The repository of submodule '{name}' can't be moved as '{}' already exists", git_dir.display())]
+    GitDirExists { name: BString, git_dir: PathBuf },
+    #[error("Could not move '{}' to '{}'", from.display(), to.display())]
+    Move {
+        from: PathBuf,
+        to: PathBuf,
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    Link(#[from] crate::submodule::link_git_dir::Error),
+    #[error(transparent)]
+    Open(#[from] crate::submodule::open::Error),
+    #[error(transparent)]
+    Modules(#[from] crate::submodule::modules::Error),
+    #[error("Could not absorb the repository of nested submodule '{name}'")]
+    Nested { name: BString, source: Box<Error> },
+}
+
+/// Modification
+impl Submodule<'_> {
+    /// If the repository of this submodule is located in its worktree, move it into the
+    /// [repository location of the superproject](Self::git_dir()) and link it with a `.git` file instead,
+    /// similar to `git submodule absorbgitdirs`.
+    ///
+    /// This is done recursively for submodules of this submodule as well, and it's a no-op for submodules that are
+    /// already absorbed or not checked out.
+    /// Return `true` if the repository of this submodule was moved.
+    pub fn absorb_git_dir(&self) -> Result<bool, Error> {
+        let dot_git = self.work_dir()?.join(gix_discover::DOT_GIT_DIR);
+        let moved = if dot_git.is_dir() {
+            let git_dir = self.git_dir();
+            if git_dir.exists() {
+                return Err(Error::GitDirExists {
+                    name: self.name().to_owned(),
+                    git_dir,
+                });
+            }
+            let move_err = |source| Error::Move {
+                from: dot_git.clone(),
+                to: git_dir.clone(),
+                source,
+            };
+            std::fs::create_dir_all(git_dir.parent().expect("in modules directory")).map_err(move_err)?;
+            std::fs::rename(&dot_git, &git_dir).map_err(move_err)?;
+            super::link_git_dir(&git_dir, dot_git.parent().expect("in worktree"))?;
+            true
+        } else {
+            if dot_git.is_file() && self.git_dir().is_dir() {
+                // Our superproject may have been absorbed just now, which invalidates the relative paths linking us.
+                super::link_git_dir(&self.git_dir(), dot_git.parent().expect("in worktree"))?;
+            }
+            false
+        };
+
+        if let Some(repo) = self.open()? {
+            for submodule in repo.submodules()?.into_iter().flatten() {
+                submodule.absorb_git_dir().map_err(|err| Error::Nested {
+                    name: submodule.name().to_owned(),
+                    source: Box::new(err),
+                })?;
+            }
+        }
+        Ok(moved)
+    }
+}
@@ -0,0 +1,220 @@
+use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    remote, Repository,
+};
+
+/// The error returned by [`Repository::add_submodule()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Submodules can only be added to repositories with a worktree")]
+    BareRepository,
+    #[error("The submodule path '{path}' must be relative to the worktree root and must not leave it")]
+    InvalidPath { path: BString },
+    #[error(transparent)]
+    InvalidName(#[from] gix_validate::submodule::name::Error),
+    #[error("The path '{path}' is already tracked in the index")]
+    PathInIndex { path: BString },
+    #[error("The path '{}' already exists and is not an empty directory", path.display())]
+    PathExists { path: PathBuf },
+    #[error("A submodule named '{name}' already exists")]
+    NameExists { name: BString },
+    #[error("The repository for submodule '{name}' already exists at '{}'", git_dir.display())]
+    GitDirExists { name: BString, git_dir: PathBuf },
+    #[error(transparent)]
+    Modules(#[from] crate::submodule::modules::Error),
+    #[error(transparent)]
+    ResolvedUrl(#[from] crate::submodule::resolved_url::Error),
+    #[error(transparent)]
+    UpdateConfig(#[from] crate::config::update_file::Error),
+    #[error(transparent)]
+    Clone(#[from] crate::submodule::update::Error),
+    #[error("The branch '{branch}' could not be found in the submodule repository")]
+    FindBranch {
+        branch: BString,
+        source: crate::reference::find::existing::Error,
+    },
+    #[error(transparent)]
+    InvalidBranchName(#[from] gix_validate::reference::name::Error),
+    #[error(transparent)]
+    PeelBranch(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    SetHead(#[from] crate::reference::edit::Error),
+    #[error(transparent)]
+    HeadId(#[from] crate::reference::head_id::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    AddToIndex(#[from] crate::worktree::add::Error),
+    #[error(transparent)]
+    IndexWriteOptions(#[from] crate::config::index_write_options::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+}
+
+/// Options for use in [`Repository::add_submodule()`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The name of the submodule, or `None` to use its path as name.
+    pub name: Option<BString>,
+    /// The branch to check out and to record in `.gitmodules`, or `None` to check out what `HEAD` of the remote points to.
+    pub branch: Option<BString>,
+}
+
+/// Modification
+impl Repository {
+    /// Clone the repository at `url` into a new submodule at `path` relative to the root of the worktree, configured with `options`,
+    /// and return it, similar to `git submodule add`.
+    ///
+    /// The submodule is recorded in `.gitmodules` with `url` as is, which may be relative to the url of the default remote
+    /// of this repository. Its repository is placed in the `modules` directory of this repository and the submodule is
+    /// initialized in the repository configuration. Finally, `.gitmodules` and the submodule are added to the index,
+    /// just like [`Worktree::add()`](crate::Worktree::add()) would.
+    pub fn add_submodule<P>(
+        &self,
+        url: gix_url::Url,
+        path: impl AsRef<BStr>,
+        options: Options,
+        mut progress: P,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Repository, Error>
+    where
+        P: crate::NestedProgress,
+        P::SubProgress: 'static,
+    {
+        self.add_submodule_inner(url, path.as_ref(), options, &mut progress, should_interrupt)
+    }
+
+    fn add_submodule_inner(
+        &self,
+        url: gix_url::Url,
+        path: &BStr,
+        options: Options,
+        progress: &mut dyn crate::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Repository, Error> {
+        let worktree = self.worktree().ok_or(Error::BareRepository)?;
+        let path = path.trim_end_with(|c| c == '/').as_bstr();
+        if path.is_empty()
+            || path.starts_with(b"/")
+            || path
+                .split(|b| *b == b'/')
+                .any(|c| c.is_empty() || c == b"." || c == b"..")
+        {
+            return Err(Error::InvalidPath { path: path.to_owned() });
+        }
+        let name = options.name.unwrap_or_else(|| path.to_owned());
+        gix_validate::submodule::name(name.as_ref())?;
+
+        let mut index = gix_index::File::clone(&**self.index_or_empty()?);
+        if index.entry_by_path(path).is_some() || index.prefixed_entries(format!("{path}/").as_str().into()).is_some() {
+            return Err(Error::PathInIndex { path: path.to_owned() });
+        }
+        let work_dir = worktree.base().join(gix_path::from_bstr(path));
+        if work_dir.exists() && std::fs::read_dir(&work_dir).map_or(true, |mut dir| dir.next().is_some()) {
+            return Err(Error::PathExists { path: work_dir });
+        }
+        if let Some(modules) = self.modules()? {
+            if modules.names().any(|existing| existing == name) {
+                return Err(Error::NameExists { name });
+            }
+        }
+        let git_dir = self
+            .common_dir()
+            .join("modules")
+            .join(gix_path::from_bstr(name.as_bstr()));
+        if git_dir.exists() {
+            return Err(Error::GitDirExists { name, git_dir });
+        }
+
+        let resolved_url = super::resolve_url(self, name.as_ref(), url.clone())?;
+        let repo = super::update::clone_repository(
+            self,
+            name.as_ref(),
+            resolved_url.clone(),
+            &git_dir,
+            &work_dir,
+            false,
+            progress,
+            should_interrupt,
+        )?;
+        if let Some(branch) = options.branch.as_ref() {
+            checkout_branch(&repo, branch.as_ref())?;
+        }
+        let id = repo.head_id()?.detach();
+        super::update::checkout(&repo, id, false, progress, should_interrupt)?;
+
+        crate::config::update_file(
+            worktree.base().join(super::MODULES_FILE),
+            gix_config::Source::Api,
+            |config| {
+                let name = Some(name.as_bstr());
+                config.set_raw_value("submodule", name, "path", path)?;
+                config.set_raw_value("submodule", name, "url", url.to_bstring().as_bstr())?;
+                if let Some(branch) = options.branch.as_ref() {
+                    config.set_raw_value("submodule", name, "branch", branch.as_bstr())?;
+                }
+                Ok(())
+            },
+        )?;
+        crate::config::update_file(self.common_dir().join("config"), gix_config::Source::Local, |config| {
+            let name = Some(name.as_bstr());
+            config.set_raw_value("submodule", name, "url", resolved_url.to_bstring().as_bstr())?;
+            config.set_raw_value("submodule", name, "active", "true")?;
+            Ok(())
+        })?;
+
+        worktree.add(&mut index, [BStr::new(super::MODULES_FILE), path], Default::default())?;
+        index.write(self.index_write_options()?)?;
+        Ok(repo)
+    }
+}
+
+/// Create a local `branch` in `repo` from the branch of the same name of its default remote, and make it the current branch.
+fn checkout_branch(repo: &Repository, branch: &BStr) -> Result<(), Error> {
+    let remote_name = repo
+        .remote_default_name(remote::Direction::Fetch)
+        .map_or_else(|| "origin".into(), std::borrow::Cow::into_owned);
+    let id = repo
+        .find_reference(format!("refs/remotes/{remote_name}/{branch}").as_str())
+        .map_err(|source| Error::FindBranch {
+            branch: branch.to_owned(),
+            source,
+        })?
+        .peel_to_id_in_place()?
+        .detach();
+    let local_branch: gix_ref::FullName = format!("refs/heads/{branch}").try_into()?;
+    let log = LogChange {
+        mode: RefLog::AndReference,
+        force_create_reflog: false,
+        message: format!("branch: Created from {remote_name}/{branch}").into(),
+    };
+    repo.edit_references([
+        RefEdit {
+            change: Change::Update {
+                log: log.clone(),
+                expected: PreviousValue::Any,
+                new: Target::Peeled(id),
+            },
+            name: local_branch.clone(),
+            deref: false,
+        },
+        RefEdit {
+            change: Change::Update {
+                log,
+                expected: PreviousValue::Any,
+                new: Target::Symbolic(local_branch),
+            },
+            name: "HEAD".try_into().expect("valid"),
+            deref: false,
+        },
+    ])?;
+    Ok(())
+}
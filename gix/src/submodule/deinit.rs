@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use crate::{bstr::BString, Submodule};
+
+/// The error returned by [`Submodule::deinit()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Path(#[from] gix_submodule::config::path::Error),
+    #[error(transparent)]
+    Open(#[from] crate::submodule::open::Error),
+    #[error("The worktree of submodule '{name}' contains its repository, absorb it into the superproject first")]
+    OldForm { name: BString },
+    #[error("The worktree of submodule '{name}' contains local modifications, which would be lost")]
+    Modified { name: BString },
+    #[error(transparent)]
+    IsDirty(#[from] crate::worktree::is_dirty::Error),
+    #[error("Could not access '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    UpdateConfig(#[from] crate::config::update_file::Error),
+}
+
+/// Modification
+impl Submodule<'_> {
+    /// Remove all files from the worktree of this submodule, leaving an empty directory, and remove its section from the configuration
+    /// of the superproject, similar to `git submodule deinit`. The repository of the submodule is kept so it can be initialized again later.
+    ///
+    /// Unless `force` is `true`, this fails if the worktree of the submodule [is dirty](crate::Worktree::is_dirty()).
+    /// Note that it's also an error if the repository of the submodule is still located in its worktree, as it would be lost.
+    pub fn deinit(&self, force: bool) -> Result<(), Error> {
+        let name = self.name();
+        if self.state()?.is_old_form {
+            return Err(Error::OldForm { name: name.to_owned() });
+        }
+        let work_dir = self.work_dir()?;
+        if !force {
+            if let Some(repo) = self.open()? {
+                if repo.worktree().map_or(Ok(false), |worktree| worktree.is_dirty())? {
+                    return Err(Error::Modified { name: name.to_owned() });
+                }
+            }
+        }
+
+        if work_dir.is_dir() {
+            let io_err = |source| Error::Io {
+                path: work_dir.clone(),
+                source,
+            };
+            std::fs::remove_dir_all(&work_dir).map_err(io_err)?;
+            std::fs::create_dir(&work_dir).map_err(io_err)?;
+        }
+        // The configuration of our repository instance may be outdated, so always check the file itself.
+        let repo = self.state.repo;
+        crate::config::update_file(repo.common_dir().join("config"), gix_config::Source::Local, |config| {
+            while config.remove_section("submodule", Some(name)).is_some() {}
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
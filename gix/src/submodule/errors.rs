@@ -11,6 +11,22 @@ pub mod open_modules_file {
     }
 }
 
+///
+#[cfg(feature = "worktree-mutation")]
+pub mod link_git_dir {
+    use std::path::PathBuf;
+
+    /// The error returned when linking the repository of a submodule with its worktree.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        UpdateConfig(#[from] crate::config::update_file::Error),
+        #[error("Could not access '{}'", path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+    }
+}
+
 ///
 pub mod modules {
     /// The error returned by [Repository::modules()](crate::Repository::modules()).
@@ -104,3 +120,25 @@ pub mod head_id {
         PathConfiguration(#[from] gix_submodule::config::path::Error),
     }
 }
+
+///
+pub mod resolved_url {
+    use crate::bstr::BString;
+
+    /// The error returned by [Submodule::resolved_url()](crate::Submodule::resolved_url()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Url(#[from] gix_submodule::config::url::Error),
+        #[error(transparent)]
+        FindRemote(#[from] crate::remote::find::existing::Error),
+        #[error("Could not resolve the relative url '{url}' of submodule '{name}' against '{base}'")]
+        Resolve {
+            name: BString,
+            url: BString,
+            base: BString,
+            source: gix_url::parse::Error,
+        },
+    }
+}
@@ -9,7 +9,12 @@ use std::{
 
 pub use gix_submodule::*;
 
-use crate::{bstr::BStr, repository::IndexPersistedOrInMemory, Repository, Submodule};
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    remote,
+    repository::IndexPersistedOrInMemory,
+    Repository, Submodule,
+};
 
 pub(crate) type ModulesFileStorage = gix_features::threading::OwnShared<gix_fs::SharedFileSnapshotMut<File>>;
 /// A lazily loaded and auto-updated worktree index.
@@ -21,6 +26,22 @@ pub(crate) const MODULES_FILE: &str = ".gitmodules";
 mod errors;
 pub use errors::*;
 
+///
+#[cfg(feature = "worktree-mutation")]
+pub mod absorb_git_dir;
+///
+#[cfg(all(feature = "worktree-mutation", feature = "blocking-network-client"))]
+pub mod add;
+///
+#[cfg(feature = "worktree-mutation")]
+pub mod deinit;
+///
+#[cfg(feature = "worktree-mutation")]
+pub mod sync;
+///
+#[cfg(all(feature = "worktree-mutation", feature = "blocking-network-client"))]
+pub mod update;
+
 /// A platform maintaining state needed to interact with submodules, created by [`Repository::submodules()].
 pub(crate) struct SharedState<'repo> {
     pub(crate) repo: &'repo Repository,
@@ -100,6 +121,12 @@ impl<'repo> Submodule<'repo> {
         self.state.modules.url(self.name())
     }
 
+    /// Return the url from which to clone or update the submodule, with relative urls like `../other` resolved against the url
+    /// of the default remote of the superproject, or its working tree if it doesn't have one.
+    pub fn resolved_url(&self) -> Result<gix_url::Url, resolved_url::Error> {
+        resolve_url(self.state.repo, self.name(), self.url()?)
+    }
+
     /// Return the `update` field from this submodule's configuration, if present, or `None`.
     pub fn update(&self) -> Result<Option<config::Update>, config::update::Error> {
         self.state.modules.update(self.name())
@@ -267,6 +294,109 @@ impl<'repo> Submodule<'repo> {
     }
 }
 
+/// Resolve `url` of the submodule named `name` against the url of the default remote of `repo`, or its working tree
+/// if it doesn't have one, if `url` is relative.
+pub(crate) fn resolve_url(
+    repo: &Repository,
+    name: &BStr,
+    url: gix_url::Url,
+) -> Result<gix_url::Url, resolved_url::Error> {
+    let url_bytes = url.to_bstring();
+    if !(url_bytes.starts_with(b"./") || url_bytes.starts_with(b"../")) {
+        return Ok(url);
+    }
+    let base: BString = match repo
+        .find_default_remote(remote::Direction::Fetch)
+        .transpose()?
+        .and_then(|remote| remote.url(remote::Direction::Fetch).map(gix_url::Url::to_bstring))
+    {
+        Some(url) => url,
+        None => gix_path::into_bstr(repo.work_dir().unwrap_or(repo.git_dir())).into_owned(),
+    };
+    let resolved = resolve_relative_url(base.as_ref(), url_bytes.as_ref());
+    gix_url::parse(resolved.as_ref()).map_err(|source| resolved_url::Error::Resolve {
+        name: name.to_owned(),
+        url: url_bytes,
+        base,
+        source,
+    })
+}
+
+/// Resolve `url`, which starts with `./` or `../`, against `base` like `git` does, removing one path component
+/// of `base` for each `../`.
+fn resolve_relative_url(base: &BStr, url: &BStr) -> BString {
+    let mut base = base.to_owned();
+    while base.ends_with(b"/") {
+        base.pop();
+    }
+    let mut url = url.as_bytes();
+    loop {
+        if let Some(rest) = url.strip_prefix(b"./") {
+            url = rest;
+        } else if let Some(rest) = url.strip_prefix(b"../") {
+            url = rest;
+            match base.rfind_byte(b'/') {
+                Some(pos) => base.truncate(pos),
+                None => base.clear(),
+            }
+        } else {
+            break;
+        }
+    }
+    base.push(b'/');
+    base.extend_from_slice(url);
+    base
+}
+
+/// Turn the repository at `git_dir` into the repository of the worktree at `work_dir` by setting `core.worktree`, and write
+/// a `.git` file into `work_dir` that points back to it, using relative paths like `git` does.
+#[cfg(feature = "worktree-mutation")]
+pub(crate) fn link_git_dir(
+    git_dir: &std::path::Path,
+    work_dir: &std::path::Path,
+) -> Result<(), link_git_dir::Error> {
+    use crate::{bstr::ByteVec, submodule::link_git_dir::Error};
+    let realpath = |path: &std::path::Path| {
+        gix_path::realpath(path).map_err(|err| Error::Io {
+            path: path.to_owned(),
+            source: std::io::Error::new(std::io::ErrorKind::Other, err),
+        })
+    };
+    let git_dir = realpath(git_dir)?;
+    let work_dir = realpath(work_dir)?;
+    crate::config::update_file(git_dir.join("config"), gix_config::Source::Local, |config| {
+        config.set_raw_value("core", None, "bare", "false")?;
+        config.set_raw_value(
+            "core",
+            None,
+            "worktree",
+            gix_path::to_unix_separators_on_windows(gix_path::into_bstr(relative_path(&git_dir, &work_dir))).as_ref(),
+        )?;
+        Ok(())
+    })?;
+    let dot_git = work_dir.join(gix_discover::DOT_GIT_DIR);
+    let mut content = BString::from("gitdir: ");
+    content.push_str(
+        gix_path::to_unix_separators_on_windows(gix_path::into_bstr(relative_path(&work_dir, &git_dir))).as_ref(),
+    );
+    content.push(b'\n');
+    std::fs::write(&dot_git, content).map_err(|source| Error::Io { path: dot_git, source })
+}
+
+/// Return the path to `to` relative to the directory `from`, with both paths being absolute.
+#[cfg(feature = "worktree-mutation")]
+fn relative_path(from: &std::path::Path, to: &std::path::Path) -> PathBuf {
+    use std::path::Component;
+    let from: Vec<_> = from.components().collect();
+    let to: Vec<_> = to.components().collect();
+    let common = from.iter().zip(to.iter()).take_while(|(a, b)| a == b).count();
+    from[common..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .chain(to[common..].iter().copied())
+        .collect()
+}
+
 /// A summary of the state of all parts forming a submodule, which allows to answer various questions about it.
 ///
 /// Note that expensive questions about its presence in the `HEAD` or the `index` are left to the caller.
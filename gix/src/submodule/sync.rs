@@ -0,0 +1,78 @@
+use crate::{
+    bstr::{BString, ByteSlice},
+    remote, Submodule,
+};
+
+/// The error returned by [`Submodule::sync()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Path(#[from] gix_submodule::config::path::Error),
+    #[error(transparent)]
+    Url(#[from] gix_submodule::config::url::Error),
+    #[error(transparent)]
+    ResolvedUrl(#[from] crate::submodule::resolved_url::Error),
+    #[error(transparent)]
+    UpdateConfig(#[from] crate::config::update_file::Error),
+    #[error(transparent)]
+    Open(#[from] crate::submodule::open::Error),
+    #[error("The submodule repository at '{}' has no configuration file to update", git_dir.display())]
+    MissingConfig { git_dir: std::path::PathBuf },
+}
+
+/// Modification
+impl Submodule<'_> {
+    /// Write the url of this submodule as configured in `.gitmodules` into the configuration of the superproject if the submodule
+    /// was initialized, and into the configuration of the default remote of the submodule repository if it exists, similar to
+    /// `git submodule sync`. Relative urls are resolved beforehand.
+    ///
+    /// Return the url that was written, or `None` if the submodule wasn't initialized, in which case nothing was changed.
+    /// Note that only repository instances opened afterwards will see the change.
+    pub fn sync(&self) -> Result<Option<gix_url::Url>, Error> {
+        let name = self.name();
+        let repo = self.state.repo;
+        if !self.state()?.superproject_configuration {
+            return Ok(None);
+        }
+        let url = self
+            .state
+            .modules
+            .config()
+            .string_filter("submodule", Some(name), "url", &mut |meta| {
+                meta.source == gix_config::Source::Api
+            })
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| gix_submodule::config::url::Error::Missing {
+                submodule: name.to_owned(),
+            })?;
+        let url =
+            gix_url::Url::from_bytes(url.as_ref()).map_err(|source| gix_submodule::config::url::Error::Parse {
+                submodule: name.to_owned(),
+                source,
+            })?;
+        let url = super::resolve_url(repo, name, url)?;
+        let url_bytes: BString = url.to_bstring();
+
+        crate::config::update_file(repo.common_dir().join("config"), gix_config::Source::Local, |config| {
+            config.set_raw_value("submodule", Some(name), "url", url_bytes.as_bstr())?;
+            Ok(())
+        })?;
+        if let Some(sm_repo) = self.open()? {
+            let remote_name = sm_repo
+                .remote_default_name(remote::Direction::Fetch)
+                .map_or_else(|| "origin".into(), std::borrow::Cow::into_owned);
+            let config_path = sm_repo.common_dir().join("config");
+            if !config_path.is_file() {
+                return Err(Error::MissingConfig {
+                    git_dir: sm_repo.common_dir().to_owned(),
+                });
+            }
+            crate::config::update_file(config_path, gix_config::Source::Local, |config| {
+                config.set_raw_value("remote", Some(remote_name.as_bstr()), "url", url_bytes.as_bstr())?;
+                Ok(())
+            })?;
+        }
+        Ok(Some(url))
+    }
+}
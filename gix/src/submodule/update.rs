@@ -0,0 +1,404 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    remote, Repository, Submodule,
+};
+
+/// The error returned by [`Submodule::update_worktree()`], [`Submodule::init()`] and [`Repository::update_submodules()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Modules(#[from] crate::submodule::modules::Error),
+    #[error(transparent)]
+    Url(#[from] gix_submodule::config::url::Error),
+    #[error(transparent)]
+    Path(#[from] gix_submodule::config::path::Error),
+    #[error(transparent)]
+    Update(#[from] gix_submodule::config::update::Error),
+    #[error(transparent)]
+    Shallow(#[from] gix_config::value::Error),
+    #[error(transparent)]
+    IsActive(#[from] crate::submodule::is_active::Error),
+    #[error(transparent)]
+    IndexId(#[from] crate::submodule::index_id::Error),
+    #[error(transparent)]
+    Open(#[from] crate::submodule::open::Error),
+    #[error(transparent)]
+    ResolvedUrl(#[from] crate::submodule::resolved_url::Error),
+    #[error(transparent)]
+    FindRemote(#[from] crate::remote::find::existing::Error),
+    #[error(transparent)]
+    UpdateConfig(#[from] crate::config::update_file::Error),
+    #[error(transparent)]
+    LinkGitDir(#[from] crate::submodule::link_git_dir::Error),
+    #[error(transparent)]
+    PrepareClone(#[from] crate::clone::Error),
+    #[error(transparent)]
+    Clone(#[from] crate::clone::fetch::Error),
+    #[error(transparent)]
+    Connect(#[from] crate::remote::connect::Error),
+    #[error(transparent)]
+    PrepareFetch(#[from] crate::remote::fetch::prepare::Error),
+    #[error(transparent)]
+    Fetch(#[from] crate::remote::fetch::Error),
+    #[error("Could not open the repository of submodule '{name}' after cloning it")]
+    OpenRepository { name: BString, source: crate::open::Error },
+    #[error("Could not write '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("Submodule '{name}' can only be updated by checking it out, but '{update}' was configured")]
+    UnsupportedUpdate { name: BString, update: BString },
+    #[error("Submodule '{name}' has local modifications which would be lost by checking it out, unless forced")]
+    LocalModifications { name: BString },
+    #[error("The untracked file at '{}' would be overwritten by checking out the submodule, unless forced", path.display())]
+    UntrackedWouldBeOverwritten { path: PathBuf },
+    #[error(transparent)]
+    IsDirty(#[from] crate::worktree::is_dirty::Error),
+    #[error("The commit {id} of submodule '{name}' could not be found, even after fetching from its remote")]
+    MissingCommit { name: BString, id: gix_hash::ObjectId },
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    DetachHead(#[from] crate::reference::edit::Error),
+    #[error("Could not create index from tree at {id}")]
+    IndexFromTree {
+        id: gix_hash::ObjectId,
+        source: gix_traverse::tree::breadthfirst::Error,
+    },
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    IndexWriteOptions(#[from] crate::config::index_write_options::Error),
+    #[error(transparent)]
+    Checkout(#[from] gix_worktree_state::checkout::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error("Could not obtain a thread-safe handle to the object database")]
+    ObjectStore(#[source] std::io::Error),
+}
+
+/// Options for use in [`Submodule::update_worktree()`] and [`Repository::update_submodules()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, submodules are [initialized](Submodule::init()) before they are updated, which also updates submodules
+    /// that aren't active yet, similar to `git submodule update --init`.
+    pub init: bool,
+    /// If `true`, submodules of updated submodules are updated as well, similar to `git submodule update --recursive`.
+    pub recursive: bool,
+    /// If `true`, the default, submodules that recommend to be shallow via `submodule.<name>.shallow` are cloned
+    /// with a depth of `1`.
+    pub recommend_shallow: bool,
+    /// If `true`, local modifications of tracked files and untracked files in the way are overwritten when checking out
+    /// existing submodules, similar to `git submodule update --force`.
+    /// Otherwise, the update fails for submodules with such changes.
+    pub force: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            init: false,
+            recursive: false,
+            recommend_shallow: true,
+            force: false,
+        }
+    }
+}
+
+/// Modification
+impl Submodule<'_> {
+    /// Initialize this submodule by writing its url and `submodule.<name>.active` into the configuration of the superproject,
+    /// similar to `git submodule init`, and return the url that was written.
+    ///
+    /// Relative urls like `../other` are resolved against the url of the default remote of the superproject, or its
+    /// working tree if it doesn't have one.
+    /// Note that only repository instances opened afterwards will see the change.
+    pub fn init(&self) -> Result<gix_url::Url, Error> {
+        let url = self.resolved_url()?;
+        let update = self.update()?;
+        let repo = self.state.repo;
+        crate::config::update_file(repo.common_dir().join("config"), gix_config::Source::Local, |config| {
+            let name = Some(self.name());
+            config.set_raw_value("submodule", name, "active", "true")?;
+            config.set_raw_value("submodule", name, "url", url.to_bstring().as_bstr())?;
+            if let Some(update) = update.as_ref().and_then(update_as_str) {
+                config.set_raw_value("submodule", name, "update", update)?;
+            }
+            Ok(())
+        })?;
+        Ok(url)
+    }
+
+    /// Clone this submodule into the [repository location of the superproject](Self::git_dir()) if needed, and check out the commit
+    /// recorded in the index of the superproject on a detached `HEAD`, similar to `git submodule update`.
+    ///
+    /// Submodules configured with `update = none`, inactive submodules unless [`Options::init`] is set, and submodules that aren't
+    /// present in the index of the superproject are skipped, and `None` is returned. Otherwise the repository of the submodule is returned.
+    ///
+    /// Only the `checkout` update strategy is supported, with the exception of newly cloned submodules, which like in `git` are always
+    /// checked out.
+    /// Existing submodules with modified tracked files, or with untracked files that would be overwritten, are only checked out
+    /// if [`Options::force`] is set.
+    pub fn update_worktree<P>(
+        &self,
+        options: Options,
+        mut progress: P,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Option<Repository>, Error>
+    where
+        P: crate::NestedProgress,
+        P::SubProgress: 'static,
+    {
+        self.update_inner(options, &mut progress, should_interrupt)
+    }
+
+    fn update_inner(
+        &self,
+        options: Options,
+        progress: &mut dyn crate::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Option<Repository>, Error> {
+        let _span = gix_trace::coarse!("gix::Submodule::update_worktree()", name = ?self.name());
+        let update = self.update()?.unwrap_or_default();
+        if update == gix_submodule::config::Update::None {
+            return Ok(None);
+        }
+        let Some(id) = self.index_id()? else {
+            return Ok(None);
+        };
+        let url = if options.init {
+            self.init()?
+        } else if self.is_active()? {
+            self.resolved_url()?
+        } else {
+            return Ok(None);
+        };
+
+        let name = self.name().to_owned();
+        let work_dir = self.work_dir()?;
+        let repo = match self.open()? {
+            Some(repo) => {
+                if update != gix_submodule::config::Update::Checkout {
+                    return Err(Error::UnsupportedUpdate {
+                        name,
+                        update: update_as_str(&update).unwrap_or("command").into(),
+                    });
+                }
+                if !options.force && repo.worktree().expect("submodules have a worktree").has_tracked_changes()? {
+                    return Err(Error::LocalModifications { name });
+                }
+                if !repo.has_object(id) {
+                    let remote = match repo.find_default_remote(remote::Direction::Fetch) {
+                        Some(remote) => remote?,
+                        None => return Err(Error::MissingCommit { name, id }),
+                    };
+                    remote
+                        .connect(remote::Direction::Fetch)?
+                        .prepare_fetch(&mut *progress, Default::default())?
+                        .receive_inner(progress, should_interrupt)?;
+                }
+                repo
+            }
+            None => {
+                let shallow = options.recommend_shallow && self.shallow()?.unwrap_or(false);
+                clone_repository(
+                    self.state.repo,
+                    name.as_ref(),
+                    url,
+                    &self.git_dir(),
+                    &work_dir,
+                    shallow,
+                    progress,
+                    should_interrupt,
+                )?
+            }
+        };
+
+        if !repo.has_object(id) {
+            return Err(Error::MissingCommit { name, id });
+        }
+        repo.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: format!("checkout: moving to {id}").into(),
+                },
+                expected: PreviousValue::Any,
+                new: Target::Peeled(id),
+            },
+            name: "HEAD".try_into().expect("valid"),
+            deref: false,
+        })?;
+        checkout(&repo, id, options.force, progress, should_interrupt)?;
+
+        if options.recursive {
+            repo.update_submodules_inner(options, progress, should_interrupt)?;
+        }
+        Ok(Some(repo))
+    }
+}
+
+/// Modification
+impl Repository {
+    /// Update all submodules of this repository with `options`, as described in [`Submodule::update_worktree()`], and
+    /// return the repositories of all submodules that were updated.
+    ///
+    /// Note that nothing happens if there is no submodule configuration.
+    pub fn update_submodules<P>(
+        &self,
+        options: Options,
+        mut progress: P,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Vec<Repository>, Error>
+    where
+        P: crate::NestedProgress,
+        P::SubProgress: 'static,
+    {
+        self.update_submodules_inner(options, &mut progress, should_interrupt)
+    }
+
+    pub(crate) fn update_submodules_inner(
+        &self,
+        options: Options,
+        progress: &mut dyn crate::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Vec<Repository>, Error> {
+        let mut out = Vec::new();
+        for submodule in self.submodules()?.into_iter().flatten() {
+            let mut progress = progress.add_child(format!("submodule '{}'", submodule.name()));
+            if let Some(repo) = submodule.update_inner(options, &mut progress, should_interrupt)? {
+                out.push(repo);
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Clone the submodule named `name` from `url` into `git_dir` as bare repository, turn it into a repository with a worktree at
+/// `work_dir` and open it with the options of the `superproject`. A `shallow` clone has a depth of `1`.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn clone_repository(
+    superproject: &Repository,
+    name: &BStr,
+    url: gix_url::Url,
+    git_dir: &Path,
+    work_dir: &Path,
+    shallow: bool,
+    progress: &mut dyn crate::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Result<Repository, Error> {
+    let io_err = |path: &Path| {
+        let path = path.to_owned();
+        move |source| Error::Io { path, source }
+    };
+    std::fs::create_dir_all(git_dir).map_err(io_err(git_dir))?;
+    let mut prepare = crate::clone::PrepareFetch::new(
+        url,
+        git_dir,
+        crate::create::Kind::Bare,
+        Default::default(),
+        superproject.options.clone(),
+    )?;
+    if shallow {
+        prepare = prepare.with_shallow(remote::fetch::Shallow::DepthAtRemote(1.try_into().expect("non-zero")));
+    }
+    prepare.fetch_only_inner(progress, should_interrupt)?;
+
+    std::fs::create_dir_all(work_dir).map_err(io_err(work_dir))?;
+    super::link_git_dir(git_dir, work_dir)?;
+    crate::open_opts(work_dir, superproject.options.clone()).map_err(|source| Error::OpenRepository {
+        name: name.to_owned(),
+        source,
+    })
+}
+
+/// Check out the tree of commit `id` into the worktree of `repo`, removing files that are only tracked by its current index.
+/// Untracked files that are in the way are only overwritten if `force` is `true`.
+pub(super) fn checkout(
+    repo: &Repository,
+    id: gix_hash::ObjectId,
+    force: bool,
+    progress: &mut dyn crate::DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Result<(), Error> {
+    let work_dir = repo.work_dir().expect("submodules have a worktree");
+    let tree = repo.find_object(id)?.try_into_commit()?.tree_id()?.detach();
+    let state = gix_index::State::from_tree(&tree, &repo.objects)
+        .map_err(|source| Error::IndexFromTree { id: tree, source })?;
+    let mut index = gix_index::File::from_state(state, repo.index_path());
+
+    let previous = repo.try_index()?;
+    if !force {
+        for entry in index.entries() {
+            let path = entry.path(&index);
+            if previous
+                .as_ref()
+                .map_or(true, |previous| previous.entry_by_path(path).is_none())
+                && entry.mode != gix_index::entry::Mode::COMMIT
+            {
+                let path = work_dir.join(gix_path::from_bstr(path));
+                if path.symlink_metadata().is_ok() {
+                    return Err(Error::UntrackedWouldBeOverwritten { path });
+                }
+            }
+        }
+    }
+    if let Some(previous) = previous.as_ref() {
+        for entry in previous.entries() {
+            let path = entry.path(previous);
+            if index.entry_by_path(path).is_none() && entry.mode != gix_index::entry::Mode::COMMIT {
+                let path = work_dir.join(gix_path::from_bstr(path));
+                std::fs::remove_file(&path).ok();
+                if let Some(parent) = path.parent() {
+                    std::fs::remove_dir(parent).ok();
+                }
+            }
+        }
+    }
+
+    let mut opts = repo
+        .config
+        .checkout_options(repo, gix_worktree::stack::state::attributes::Source::IdMapping)?;
+    opts.destination_is_initially_empty = false;
+    opts.overwrite_existing = true;
+    let files = progress.add_child("checkout".into());
+    let bytes = progress.add_child("writing".into());
+    gix_worktree_state::checkout(
+        &mut index,
+        work_dir,
+        repo.objects.clone().into_arc().map_err(Error::ObjectStore)?,
+        &files,
+        &bytes,
+        should_interrupt,
+        opts,
+    )?;
+    index.write(repo.index_write_options()?)?;
+    Ok(())
+}
+
+fn update_as_str(update: &gix_submodule::config::Update) -> Option<&'static str> {
+    use gix_submodule::config::Update;
+    Some(match update {
+        Update::Checkout => "checkout",
+        Update::Rebase => "rebase",
+        Update::Merge => "merge",
+        Update::None => "none",
+        Update::Command(_) => return None,
+    })
+}
@@ -0,0 +1,501 @@
+use std::{
+    io::Read,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use gix_index::entry;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    config::cache::util::ApplyLeniencyDefault,
+    Repository, Worktree,
+};
+
+/// The error returned by [`Worktree::add()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Pathspec(#[from] crate::worktree::pathspec::Error),
+    #[error(transparent)]
+    Excludes(#[from] crate::config::exclude_stack::Error),
+    #[error(transparent)]
+    Walk(#[from] gix_dir::walk::Error),
+    #[error(transparent)]
+    Attributes(#[from] crate::config::attribute_stack::Error),
+    #[error(transparent)]
+    FilterPipeline(#[from] crate::filter::pipeline::options::Error),
+    #[error("Could not convert '{rela_path}' for storage in git")]
+    ConvertToGit {
+        rela_path: BString,
+        source: crate::filter::pipeline::convert_to_git::Error,
+    },
+    #[error(transparent)]
+    WriteBlob(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    FsCapabilities(#[from] crate::config::boolean::Error),
+    #[error(transparent)]
+    StatOptions(#[from] crate::config::stat_options::Error),
+    #[error("Could not access '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("Could not obtain the modification time of '{}'", path.display())]
+    Time {
+        path: PathBuf,
+        source: std::time::SystemTimeError,
+    },
+}
+
+/// Options for use in [`Worktree::add()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, untracked files will be added even if they are excluded, like `git add --force` would do.
+    pub force: bool,
+    /// If `true`, only entries that are already tracked will be updated or removed, and untracked files will not be added,
+    /// like `git add --update` would do.
+    pub update_only: bool,
+}
+
+/// The outcome of [`Worktree::add()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Outcome {
+    /// The amount of entries that weren't present in the index before.
+    pub added: usize,
+    /// The amount of entries whose id or mode changed, or whose conflict was resolved.
+    pub modified: usize,
+    /// The amount of paths that were removed from the index as they don't exist in the worktree anymore.
+    pub removed: usize,
+}
+
+impl<'repo> Worktree<'repo> {
+    /// Update `index` to match all files in the worktree that match the pathspec `patterns`, just like `git add -A <pathspec>`,
+    /// and configure the operation with `options`.
+    ///
+    /// This writes the content of all new or changed files as blobs after passing it through the
+    /// [filter pipeline](crate::filter::Pipeline::convert_to_git()) to apply `clean` filters along with `eol`, `ident` and
+    /// `working-tree-encoding` conversions as configured in `.gitattributes`.
+    /// Their entries are inserted or updated along with fresh stat information, while entries of paths that don't exist anymore
+    /// are removed. Untracked files are only added if they aren't excluded, e.g. by `.gitignore` files.
+    /// Conflicts are resolved by replacing all stages with the worktree version, which is also recorded in the resolve-undo extension.
+    ///
+    /// Repositories nested in the worktree are added as submodule entries pointing to their `HEAD` commit, and existing submodule
+    /// entries are updated accordingly. Note that entries marked as *skip-worktree* are never touched.
+    ///
+    /// Empty `patterns` match all files. To persist the changes, [write](gix_index::File::write()) the index afterwards using
+    /// [`Repository::index_write_options()`](crate::Repository::index_write_options()).
+    pub fn add(
+        &self,
+        index: &mut gix_index::State,
+        patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+        options: Options,
+    ) -> Result<Outcome, Error> {
+        let repo = self.parent;
+        let mut ctx = Context {
+            repo,
+            root: self.path,
+            pathspec: self.pathspec(patterns)?,
+            pipeline: crate::filter::Pipeline::new(
+                repo,
+                repo.attributes_only(
+                    index,
+                    gix_worktree::stack::state::attributes::Source::WorktreeThenIdMapping,
+                )?
+                .detach(),
+            )?,
+            capabilities: repo
+                .config
+                .fs_capabilities()
+                .with_lenient_default(repo.config.lenient_config)?,
+            stat_options: repo.config.stat_options()?,
+            buf: Vec::new(),
+        };
+
+        let mut edits = Vec::new();
+        ctx.update_tracked(index, &mut edits)?;
+        if !options.update_only {
+            let mut excludes = if options.force {
+                // Nothing is excluded when forcing, so use a stack without any patterns that doesn't read ignore files either.
+                gix_worktree::Stack::new(
+                    self.path,
+                    gix_worktree::stack::State::IgnoreStack(gix_worktree::stack::state::Ignore::new(
+                        Default::default(),
+                        Default::default(),
+                        None,
+                        gix_worktree::stack::state::ignore::Source::IdMapping,
+                    )),
+                    gix_glob::pattern::Case::Sensitive,
+                    Vec::new(),
+                    Vec::new(),
+                )
+            } else {
+                repo.excludes(
+                    index,
+                    None,
+                    gix_worktree::stack::state::ignore::Source::WorktreeThenIdMappingIfNotSkipped,
+                )?
+                .detach()
+            };
+            ctx.add_untracked(index, &mut excludes, &mut edits)?;
+        }
+        Ok(apply(index, edits))
+    }
+}
+
+/// A change to make to the index once all paths were checked.
+pub(crate) enum Edit {
+    /// Replace all entries at `path` with a single unconflicted entry.
+    Upsert {
+        path: BString,
+        id: gix_hash::ObjectId,
+        mode: entry::Mode,
+        stat: entry::Stat,
+    },
+    /// Refresh the stat information of the unconflicted entry at `idx`, as its content didn't change.
+    Stat { idx: usize, stat: entry::Stat },
+    /// Remove all entries at `path`.
+    Remove { path: BString },
+    /// Replace all entries at `path` with conflicting entries at stage 1 (the common ancestor), 2 (ours) and 3 (theirs),
+    /// for each stage that is present.
+    #[cfg(feature = "apply")]
+    Conflict {
+        path: BString,
+        stages: [Option<(gix_hash::ObjectId, entry::Mode)>; 3],
+    },
+}
+
+struct Context<'repo> {
+    repo: &'repo Repository,
+    root: &'repo Path,
+    pathspec: crate::Pathspec<'repo>,
+    pipeline: crate::filter::Pipeline<'repo>,
+    capabilities: gix_fs::Capabilities,
+    stat_options: entry::stat::Options,
+    buf: Vec<u8>,
+}
+
+impl Context<'_> {
+    /// Check all tracked entries that match the pathspec for changes in the worktree.
+    fn update_tracked(&mut self, index: &gix_index::State, edits: &mut Vec<Edit>) -> Result<(), Error> {
+        let Range { start, end } = index
+            .prefixed_entries_range(self.pathspec.search().common_prefix())
+            .unwrap_or(0..0);
+        let entries = index.entries();
+        let mut idx = start;
+        while idx < end {
+            let entry = &entries[idx];
+            let rela_path = entry.path(index);
+            let num_stages = entries[idx..end]
+                .iter()
+                .take_while(|e| e.path(index) == rela_path)
+                .count();
+            let entry_idx = idx;
+            idx += num_stages;
+
+            let is_conflicted = num_stages > 1 || entry.stage() != 0;
+            if entry.flags.contains(entry::Flags::SKIP_WORKTREE)
+                || entry.mode.is_sparse()
+                || !self.pathspec.is_included(rela_path, Some(false))
+            {
+                continue;
+            }
+
+            let path = self.root.join(gix_path::from_bstr(rela_path));
+            let md = match gix_index::fs::Metadata::from_path_no_follow(&path) {
+                Ok(md) => md,
+                Err(err) if is_gone(&err, &path) => {
+                    edits.push(Edit::Remove {
+                        path: rela_path.to_owned(),
+                    });
+                    continue;
+                }
+                Err(source) => return Err(Error::Io { path, source }),
+            };
+
+            if md.is_dir() {
+                let head_id = head_id_of_nested_repository(&path);
+                match head_id {
+                    Some(id) if is_conflicted || entry.mode != entry::Mode::COMMIT || entry.id != id => {
+                        edits.push(Edit::Upsert {
+                            path: rela_path.to_owned(),
+                            id,
+                            mode: entry::Mode::COMMIT,
+                            stat: stat_from_fs(&md, &path)?,
+                        });
+                    }
+                    Some(_) => {}
+                    None if entry.mode == entry::Mode::COMMIT && !is_conflicted => {}
+                    None => {
+                        // The files in the directory, if any, are picked up when looking for untracked files.
+                        edits.push(Edit::Remove {
+                            path: rela_path.to_owned(),
+                        })
+                    }
+                }
+                continue;
+            }
+            if entry.mode == entry::Mode::COMMIT && !is_conflicted {
+                // A submodule was replaced by a file, which is picked up when looking for untracked files.
+                edits.push(Edit::Remove {
+                    path: rela_path.to_owned(),
+                });
+                continue;
+            }
+
+            let stat = stat_from_fs(&md, &path)?;
+            let mode = if is_conflicted {
+                mode_from_fs(&md, self.capabilities)
+            } else {
+                match entry
+                    .mode
+                    .change_to_match_fs(&md, self.capabilities.symlink, self.capabilities.executable_bit)
+                {
+                    Some(change) => change.apply(entry.mode),
+                    None => {
+                        if !entry.flags.contains(entry::Flags::INTENT_TO_ADD)
+                            && entry.stat.matches(&stat, self.stat_options)
+                            && !entry.stat.is_racy(index.timestamp(), self.stat_options)
+                        {
+                            continue;
+                        }
+                        entry.mode
+                    }
+                }
+            };
+
+            let id = self.write_blob(rela_path, &path, mode, index)?;
+            edits.push(if !is_conflicted && id == entry.id && mode == entry.mode {
+                Edit::Stat { idx: entry_idx, stat }
+            } else {
+                Edit::Upsert {
+                    path: rela_path.to_owned(),
+                    id,
+                    mode,
+                    stat,
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Find all untracked files and repositories nested in the worktree which match the pathspec and aren't excluded by `excludes`
+    /// with a [directory walk](gix_dir::walk()), and add them.
+    fn add_untracked(
+        &mut self,
+        index: &gix_index::State,
+        excludes: &mut gix_worktree::Stack,
+        edits: &mut Vec<Edit>,
+    ) -> Result<(), Error> {
+        let mut untracked = Vec::new();
+        let pathspec = &mut self.pathspec;
+        gix_dir::walk(
+            self.root,
+            gix_dir::walk::Context {
+                index,
+                excludes,
+                objects: &self.repo.objects,
+                untracked_cache: None,
+            },
+            gix_dir::walk::Options {
+                recurse_untracked_directories: true,
+            },
+            &mut |entry: gix_dir::walk::Entry| {
+                if pathspec.is_included(entry.rela_path.as_bstr(), Some(entry.is_dir)) {
+                    untracked.push(entry);
+                }
+                gix_dir::walk::Action::Continue
+            },
+        )?;
+
+        for gix_dir::walk::Entry { rela_path, is_dir } in untracked {
+            let path = self.root.join(gix_path::from_bstr(rela_path.as_bstr()));
+            let md = gix_index::fs::Metadata::from_path_no_follow(&path).map_err(|source| Error::Io {
+                path: path.clone(),
+                source,
+            })?;
+            if is_dir {
+                // Untracked directories are only emitted if they are repositories, which are added like submodules,
+                // unless they replace a tracked file which is handled with the tracked entries.
+                if index.entry_range(rela_path.as_bstr()).is_some() {
+                    continue;
+                }
+                if let Some(id) = head_id_of_nested_repository(&path) {
+                    edits.push(Edit::Upsert {
+                        stat: stat_from_fs(&md, &path)?,
+                        path: rela_path,
+                        id,
+                        mode: entry::Mode::COMMIT,
+                    });
+                }
+            } else if md.is_file() || md.is_symlink() {
+                let mode = mode_from_fs(&md, self.capabilities);
+                let stat = stat_from_fs(&md, &path)?;
+                let id = self.write_blob(rela_path.as_bstr(), &path, mode, index)?;
+                edits.push(Edit::Upsert {
+                    path: rela_path,
+                    id,
+                    mode,
+                    stat,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the content of the file at `path` as blob, after converting it to what's stored in git, and return its id.
+    /// Symlinks are stored with their target as content.
+    fn write_blob(
+        &mut self,
+        rela_path: &BStr,
+        path: &Path,
+        mode: entry::Mode,
+        index: &gix_index::State,
+    ) -> Result<gix_hash::ObjectId, Error> {
+        let io_err = |source| Error::Io {
+            path: path.to_owned(),
+            source,
+        };
+        self.buf.clear();
+        if mode == entry::Mode::SYMLINK {
+            let target = std::fs::read_link(path).map_err(io_err)?;
+            self.buf.extend_from_slice(gix_path::into_bstr(target).as_ref());
+        } else {
+            let file = std::fs::File::open(path).map_err(io_err)?;
+            let mut converted = self
+                .pipeline
+                .convert_to_git(file, &gix_path::from_bstr(rela_path), index)
+                .map_err(|source| Error::ConvertToGit {
+                    rela_path: rela_path.to_owned(),
+                    source,
+                })?;
+            converted.read_to_end(&mut self.buf).map_err(io_err)?;
+        }
+        Ok(self.repo.write_blob(&self.buf)?.detach())
+    }
+}
+
+/// Apply all `edits` to `index` and invalidate the trees of all changed paths.
+pub(crate) fn apply(index: &mut gix_index::State, edits: Vec<Edit>) -> Outcome {
+    let mut out = Outcome::default();
+    let mut new_entries = Vec::new();
+    for edit in edits {
+        let path = match edit {
+            Edit::Stat { idx, stat } => {
+                index.entries_mut()[idx].stat = stat;
+                continue;
+            }
+            Edit::Remove { path } => {
+                if let Some(range) = index.entry_range(path.as_bstr()) {
+                    index.record_resolve_undo(path.as_bstr());
+                    for entry in &mut index.entries_mut()[range] {
+                        entry.flags.insert(entry::Flags::REMOVE);
+                    }
+                    out.removed += 1;
+                }
+                if let Some(untracked) = index.untracked_mut() {
+                    untracked.invalidate_path(path.as_bstr());
+                }
+                path
+            }
+            #[cfg(feature = "apply")]
+            Edit::Conflict { path, stages } => {
+                if let Some(range) = index.entry_range(path.as_bstr()) {
+                    for entry in &mut index.entries_mut()[range] {
+                        entry.flags.insert(entry::Flags::REMOVE);
+                    }
+                }
+                for (stage, (id, mode)) in (1..).zip(stages).filter_map(|(stage, s)| s.map(|s| (stage, s))) {
+                    new_entries.push((
+                        path.clone(),
+                        id,
+                        mode,
+                        entry::Stat::default(),
+                        entry::Flags::from_stage(stage),
+                    ));
+                }
+                out.modified += 1;
+                path
+            }
+            Edit::Upsert { path, id, mode, stat } => {
+                match index.entry_range(path.as_bstr()) {
+                    Some(range) if range.len() == 1 && index.entries()[range.start].stage() == 0 => {
+                        let entry = &mut index.entries_mut()[range.start];
+                        entry.id = id;
+                        entry.mode = mode;
+                        entry.stat = stat;
+                        entry.flags.remove(entry::Flags::INTENT_TO_ADD);
+                        // Besides intent-to-add, skip-worktree is the only extended flag that would need the entry to stay extended.
+                        if !entry.flags.contains(entry::Flags::SKIP_WORKTREE) {
+                            entry.flags.remove(entry::Flags::EXTENDED);
+                        }
+                    }
+                    Some(range) => {
+                        index.record_resolve_undo(path.as_bstr());
+                        for entry in &mut index.entries_mut()[range] {
+                            entry.flags.insert(entry::Flags::REMOVE);
+                        }
+                        new_entries.push((path.clone(), id, mode, stat, entry::Flags::empty()));
+                    }
+                    None => {
+                        new_entries.push((path.clone(), id, mode, stat, entry::Flags::empty()));
+                        out.added += 1;
+                        if let Some(tree) = index.tree_mut() {
+                            tree.invalidate_path(path.as_bstr());
+                        }
+                        if let Some(untracked) = index.untracked_mut() {
+                            untracked.invalidate_path(path.as_bstr());
+                        }
+                        continue;
+                    }
+                }
+                out.modified += 1;
+                path
+            }
+        };
+        if let Some(tree) = index.tree_mut() {
+            tree.invalidate_path(path.as_bstr());
+        }
+    }
+
+    for (path, id, mode, stat, flags) in new_entries {
+        index.dangerously_push_entry(stat, id, flags, mode, path.as_bstr());
+    }
+    index.remove_entries(|_, _, entry| entry.flags.contains(entry::Flags::REMOVE));
+    index.sort_entries();
+    out
+}
+
+/// Return `true` if `err` indicates that nothing exists at `path`, even if the error is due to a leading path component
+/// not being a directory.
+fn is_gone(err: &std::io::Error, path: &Path) -> bool {
+    err.kind() == std::io::ErrorKind::NotFound || path.parent().map_or(false, |parent| !parent.is_dir())
+}
+
+fn stat_from_fs(md: &gix_index::fs::Metadata, path: &Path) -> Result<entry::Stat, Error> {
+    entry::Stat::from_fs(md).map_err(|source| Error::Time {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+fn mode_from_fs(md: &gix_index::fs::Metadata, capabilities: gix_fs::Capabilities) -> entry::Mode {
+    if md.is_symlink() && capabilities.symlink {
+        entry::Mode::SYMLINK
+    } else if capabilities.executable_bit && md.is_executable() {
+        entry::Mode::FILE_EXECUTABLE
+    } else {
+        entry::Mode::FILE
+    }
+}
+
+/// Return the id of the commit `HEAD` points to in the repository at `path`, or `None` if there is no repository or
+/// if its `HEAD` is unborn.
+fn head_id_of_nested_repository(path: &Path) -> Option<gix_hash::ObjectId> {
+    if !path.join(gix_discover::DOT_GIT_DIR).exists() {
+        return None;
+    }
+    crate::open_opts(path, crate::open::Options::isolated())
+        .ok()?
+        .head_id()
+        .ok()
+        .map(crate::Id::detach)
+}
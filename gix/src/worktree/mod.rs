@@ -85,6 +85,17 @@ pub(crate) fn id(git_dir: &std::path::Path, has_common_dir: bool) -> Option<&BSt
 ///
 pub mod proxy;
 
+///
+#[cfg(feature = "status")]
+pub mod add;
+///
+#[cfg(feature = "status")]
+pub mod remove;
+
+///
+#[cfg(feature = "worktree-mutation")]
+pub mod sparse_checkout;
+
 ///
 #[cfg(feature = "index")]
 pub mod open_index {
@@ -151,6 +162,208 @@ pub mod excludes {
     }
 }
 
+///
+#[cfg(feature = "status")]
+pub mod is_dirty {
+    use std::{
+        path::Path,
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    use gix_status::index_as_worktree::{traits::FastEq, EntryStatus};
+
+    use crate::{
+        bstr::BStr,
+        AttributeStack, Repository, Worktree,
+    };
+
+    /// The error returned by [`Worktree::is_dirty()`](crate::Worktree::is_dirty()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        OpenIndex(#[from] crate::worktree::open_index::Error),
+        #[error(transparent)]
+        StatOptions(#[from] crate::config::stat_options::Error),
+        #[error(transparent)]
+        FilesystemOptions(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        AttributeStack(#[from] crate::config::attribute_stack::Error),
+        #[error(transparent)]
+        FilterPipeline(#[from] crate::repository::filter::pipeline::Error),
+        #[error("Could not obtain a thread-safe handle to the object database")]
+        ObjectStore(#[source] std::io::Error),
+        #[error(transparent)]
+        IndexAsWorktree(#[from] gix_status::index_as_worktree::Error),
+        #[error(transparent)]
+        Excludes(#[from] crate::config::exclude_stack::Error),
+        #[error(transparent)]
+        Walk(#[from] gix_dir::walk::Error),
+        #[error(transparent)]
+        HeadTreeId(#[from] crate::reference::head_tree_id::Error),
+        #[error(transparent)]
+        IndexFromTree(#[from] gix_traverse::tree::breadthfirst::Error),
+        #[error(transparent)]
+        ExpandSparseIndex(#[from] gix_index::sparse::expand::Error),
+        #[error(transparent)]
+        QueryFsMonitor(#[from] crate::repository::query_fs_monitor::Error),
+        #[error(transparent)]
+        UntrackedCacheLookup(#[from] crate::repository::untracked_cache_lookup::Error),
+    }
+
+    impl<'repo> Worktree<'repo> {
+        /// Return `true` if tracked files in this worktree were modified or deleted, or if it contains untracked files
+        /// that aren't ignored, which is when `git status` would show changes to the worktree.
+        ///
+        /// Tracked files are compared just like `git status` does, which includes converting them with the
+        /// configured filters before comparing them to their version in the index.
+        /// Like `git`, the [filesystem monitor](Repository::query_fs_monitor()) and the
+        /// [untracked cache](Repository::untracked_cache_lookup()) are used if configured, but the index isn't updated.
+        /// Note that submodules are not checked for changes.
+        pub fn is_dirty(&self) -> Result<bool, Error> {
+            let repo = self.parent;
+            let index = repo.index_or_empty()?;
+            if has_worktree_changes(repo, self.path, &index)? {
+                return Ok(true);
+            }
+            let mut excludes = repo.excludes(
+                &index,
+                None,
+                gix_worktree::stack::state::ignore::Source::WorktreeThenIdMappingIfNotSkipped,
+            )?;
+            has_untracked_files(repo, self.path, &index, &mut excludes)
+        }
+
+        /// Return `true` if tracked files in this worktree were modified or deleted, or if the index differs from the tree
+        /// of `HEAD`, which is when `git describe --dirty` considers the worktree dirty.
+        ///
+        /// Unlike [`is_dirty()`](Self::is_dirty()), untracked files are ignored.
+        pub fn has_tracked_changes(&self) -> Result<bool, Error> {
+            let repo = self.parent;
+            let index = repo.index_or_empty()?;
+            let index = repo.expand_sparse_index(&index)?;
+            let head = gix_index::State::from_tree(&repo.head_tree_id()?, &repo.objects)?;
+            let is_staged = index.entries().len() != head.entries().len()
+                || index.entries().iter().zip(head.entries()).any(|(ours, theirs)| {
+                    ours.path(&index) != theirs.path(&head)
+                        || ours.id != theirs.id
+                        || ours.mode != theirs.mode
+                        || ours.stage() != theirs.stage()
+                });
+            Ok(is_staged || has_worktree_changes(repo, self.path, &index)?)
+        }
+    }
+
+    /// Return `true` if any entry of `index` differs from its file in `work_dir`, as determined by a
+    /// [status](gix_status::index_as_worktree()) which stops at the first change.
+    ///
+    /// Entries that the filesystem monitor considers unchanged are skipped, which is why `index` is copied if one is configured.
+    fn has_worktree_changes(repo: &Repository, work_dir: &Path, index: &gix_index::State) -> Result<bool, Error> {
+        let mut monitored;
+        let index = if repo.fs_monitor_hook()?.is_some() {
+            monitored = index.clone();
+            repo.query_fs_monitor(&mut monitored)?;
+            &monitored
+        } else {
+            index
+        };
+        let found_change = AtomicBool::default();
+        let (attributes, _buf) = repo.config.assemble_attribute_globals(
+            repo.git_dir(),
+            gix_worktree::stack::state::attributes::Source::WorktreeThenIdMapping,
+            repo.options.permissions.attributes,
+        )?;
+        gix_status::index_as_worktree(
+            index,
+            work_dir,
+            &mut FirstChange(&found_change),
+            FastEq,
+            IgnoreSubmodules,
+            repo.objects.clone().into_arc().map_err(Error::ObjectStore)?,
+            &mut gix_features::progress::Discard,
+            AllEntries,
+            repo.filter_pipeline(None)?.0.into_parts().0,
+            &found_change,
+            gix_status::index_as_worktree::Options {
+                fs: repo.filesystem_options()?,
+                thread_limit: None,
+                stat: repo.stat_options()?,
+                attributes,
+            },
+        )?;
+        Ok(found_change.load(Ordering::Relaxed))
+    }
+
+    /// A collector which records that a change was seen, which also interrupts the status computation.
+    struct FirstChange<'a>(&'a AtomicBool);
+
+    impl<'index> gix_status::index_as_worktree::VisitEntry<'index> for FirstChange<'_> {
+        type ContentChange = ();
+        type SubmoduleStatus = ();
+
+        fn visit_entry(
+            &mut self,
+            _entries: &'index [gix_index::Entry],
+            _entry: &'index gix_index::Entry,
+            _entry_index: usize,
+            _rela_path: &'index BStr,
+            status: EntryStatus<Self::ContentChange, Self::SubmoduleStatus>,
+        ) {
+            if !matches!(status, EntryStatus::NeedsUpdate(_)) {
+                self.0.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    struct IgnoreSubmodules;
+
+    impl gix_status::index_as_worktree::traits::SubmoduleStatus for IgnoreSubmodules {
+        type Output = ();
+        type Error = std::convert::Infallible;
+
+        fn status(&mut self, _entry: &gix_index::Entry, _rela_path: &BStr) -> Result<Option<Self::Output>, Self::Error> {
+            Ok(None)
+        }
+    }
+
+    #[derive(Clone)]
+    struct AllEntries;
+
+    impl gix_status::Pathspec for AllEntries {
+        fn common_prefix(&self) -> &BStr {
+            "".into()
+        }
+
+        fn is_included(&mut self, _relative_path: &BStr, _is_dir: Option<bool>) -> bool {
+            true
+        }
+    }
+
+    /// Return `true` if `work_dir` contains a file that is neither tracked in `index` nor excluded, or a nested repository,
+    /// as determined by a [directory walk](gix_dir::walk()) which stops at the first untracked entry.
+    fn has_untracked_files(
+        repo: &Repository,
+        work_dir: &Path,
+        index: &gix_index::State,
+        excludes: &mut AttributeStack<'_>,
+    ) -> Result<bool, Error> {
+        let mut untracked_cache = repo.untracked_cache_lookup(index)?;
+        let outcome = gix_dir::walk(
+            work_dir,
+            gix_dir::walk::Context {
+                index,
+                excludes,
+                objects: &repo.objects,
+                untracked_cache: untracked_cache.as_mut(),
+            },
+            gix_dir::walk::Options::default(),
+            &mut |_entry: gix_dir::walk::Entry| gix_dir::walk::Action::Cancel,
+        )?;
+        Ok(outcome.cancelled)
+    }
+}
+
 ///
 #[cfg(feature = "attributes")]
 pub mod attributes {
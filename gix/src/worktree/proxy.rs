@@ -24,6 +24,130 @@ pub mod into_repo {
     }
 }
 
+///
+#[cfg(feature = "worktree-mutation")]
+pub mod lock {
+    use crate::bstr::BString;
+
+    /// The error returned by [`Proxy::lock()`][super::Proxy::lock()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The worktree is already locked, reason: '{reason}'")]
+        AlreadyLocked { reason: BString },
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+    }
+}
+
+///
+#[cfg(feature = "worktree-mutation")]
+pub mod move_to {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [`Proxy::move_to()`][super::Proxy::move_to()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The worktree is locked and can't be moved, reason: '{reason}'")]
+        Locked { reason: BString },
+        #[error("Worktree at '{}' is inaccessible", .base.display())]
+        MissingWorktree { base: PathBuf },
+        #[error("The destination '{}' already exists", .path.display())]
+        DestinationExists { path: PathBuf },
+        #[error(transparent)]
+        Repair(#[from] super::repair::Error),
+        #[error("Could not move '{}' to '{}'", from.display(), to.display())]
+        Move {
+            from: PathBuf,
+            to: PathBuf,
+            source: std::io::Error,
+        },
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+    }
+}
+
+///
+#[cfg(feature = "worktree-mutation")]
+pub mod remove {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [`Proxy::remove()`][super::Proxy::remove()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The worktree is locked and can't be removed, reason: '{reason}'")]
+        Locked { reason: BString },
+        #[error("The worktree at '{}' contains modified or untracked files", .base.display())]
+        Dirty { base: PathBuf },
+        #[error(transparent)]
+        Open(#[from] crate::open::Error),
+        #[error(transparent)]
+        IsDirty(#[from] crate::worktree::is_dirty::Error),
+        #[error("Could not remove '{}'", .path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+    }
+}
+
+///
+#[cfg(feature = "worktree-mutation")]
+pub mod repair {
+    use std::path::PathBuf;
+
+    /// The error returned by [`Proxy::repair()`][super::Proxy::repair()] and
+    /// [`Repository::repair_worktree()`][crate::Repository::repair_worktree()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("'{}' is not a linked worktree of this repository", .path.display())]
+        NotALinkedWorktree { path: PathBuf },
+        #[error("Could not access '{}'", .path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+    }
+}
+
+/// The reason for a linked worktree to be prunable, as returned by [`Proxy::prunable()`] and
+/// [`Repository::prune_worktrees()`][crate::Repository::prune_worktrees()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Prunable {
+    /// The administrative directory in `worktrees/` is a file, not a directory.
+    NotADirectory,
+    /// The `gitdir` file pointing to the worktree doesn't exist.
+    MissingGitDirFile,
+    /// The `gitdir` file pointing to the worktree couldn't be read or is empty.
+    InvalidGitDirFile,
+    /// The worktree the `gitdir` file points to doesn't exist anymore.
+    MissingWorktree,
+}
+
+impl Prunable {
+    /// Determine if the administrative directory of a linked worktree at `git_dir` is prunable, which is never the case if
+    /// it is locked.
+    pub(crate) fn from_git_dir(git_dir: &Path) -> Option<Self> {
+        if !git_dir.is_dir() {
+            return Some(Prunable::NotADirectory);
+        }
+        if git_dir.join("locked").is_file() {
+            return None;
+        }
+        let gitdir_file = git_dir.join("gitdir");
+        if !gitdir_file.is_file() {
+            return Some(Prunable::MissingGitDirFile);
+        }
+        match gix_discover::path::from_plain_file(&gitdir_file) {
+            Some(Ok(base_dot_git)) if !base_dot_git.as_os_str().is_empty() => {
+                (!base_dot_git.exists()).then_some(Prunable::MissingWorktree)
+            }
+            _ => Some(Prunable::InvalidGitDirFile),
+        }
+    }
+}
+
 impl<'repo> Proxy<'repo> {
     pub(crate) fn new(parent: &'repo Repository, git_dir: impl Into<PathBuf>) -> Self {
         Proxy {
@@ -74,6 +198,14 @@ impl<'repo> Proxy<'repo> {
             .map(|contents| contents.trim().into())
     }
 
+    /// Return the reason for this worktree to be prunable, or `None` if it is valid or [locked](Self::is_locked()).
+    ///
+    /// Worktrees are prunable if the checkout they point to doesn't exist anymore, possibly because it was deleted without
+    /// removing the worktree through git.
+    pub fn prunable(&self) -> Option<Prunable> {
+        Prunable::from_git_dir(&self.git_dir)
+    }
+
     /// Transform this proxy into a [`Repository`] while ignoring issues reading `base()` and ignoring that it might not exist.
     ///
     /// Most importantly, the `Repository` might be initialized with a non-existing work tree directory as the checkout
@@ -99,3 +231,160 @@ impl<'repo> Proxy<'repo> {
         Ok(repo.into())
     }
 }
+
+/// Modification
+#[cfg(feature = "worktree-mutation")]
+impl<'repo> Proxy<'repo> {
+    /// Lock this worktree to prevent it from being pruned, moved or removed, with an optional `reason`, similar to `git worktree lock`.
+    ///
+    /// It's an error if the worktree is locked already.
+    pub fn lock(&self, reason: Option<&BStr>) -> Result<(), lock::Error> {
+        let mut file = match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(self.git_dir.join("locked"))
+        {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                return Err(lock::Error::AlreadyLocked {
+                    reason: self.lock_reason().unwrap_or_default(),
+                })
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if let Some(reason) = reason.filter(|reason| !reason.is_empty()) {
+            use std::io::Write;
+            file.write_all(reason)?;
+            file.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Unlock this worktree, and return `true` if it was locked, similar to `git worktree unlock`.
+    pub fn unlock(&self) -> std::io::Result<bool> {
+        match std::fs::remove_file(self.git_dir.join("locked")) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Move the checkout of this worktree to `destination` and return its new location, similar to `git worktree move`.
+    ///
+    /// If `destination` is an existing directory, the worktree is moved into it, keeping its directory name.
+    /// It's an error if the worktree is locked or if its checkout doesn't exist.
+    pub fn move_to(&self, destination: impl AsRef<Path>) -> Result<PathBuf, move_to::Error> {
+        if let Some(reason) = self.lock_reason() {
+            return Err(move_to::Error::Locked { reason });
+        }
+        let base = self.base()?;
+        if !base.is_dir() {
+            return Err(move_to::Error::MissingWorktree { base });
+        }
+        let mut destination = destination.as_ref().to_owned();
+        if destination.is_dir() {
+            destination.push(base.file_name().expect("worktrees have a directory name"));
+        }
+        if destination.symlink_metadata().is_ok() {
+            return Err(move_to::Error::DestinationExists { path: destination });
+        }
+        std::fs::rename(&base, &destination).map_err(|source| move_to::Error::Move {
+            from: base,
+            to: destination.clone(),
+            source,
+        })?;
+        link_worktree(&self.git_dir, &destination)?;
+        Ok(destination)
+    }
+
+    /// Remove the checkout of this worktree along with its administrative files, similar to `git worktree remove`.
+    ///
+    /// Unless `force` is `true`, this fails if the worktree [is dirty](crate::Worktree::is_dirty()).
+    /// It's always an error if the worktree is locked, so it has to be [unlocked](Self::unlock()) first.
+    pub fn remove(self, force: bool) -> Result<(), remove::Error> {
+        if let Some(reason) = self.lock_reason() {
+            return Err(remove::Error::Locked { reason });
+        }
+        let io_err = |path: &Path| {
+            let path = path.to_owned();
+            move |source| remove::Error::Io { path, source }
+        };
+        if let Ok(base) = self.base() {
+            if base.is_dir() {
+                if !force {
+                    let repo = ThreadSafeRepository::open_from_paths(
+                        self.git_dir.clone(),
+                        Some(base.clone()),
+                        self.parent.options.clone(),
+                    )?
+                    .to_thread_local();
+                    if repo.worktree().map_or(Ok(false), |worktree| worktree.is_dirty())? {
+                        return Err(remove::Error::Dirty { base });
+                    }
+                }
+                std::fs::remove_dir_all(&base).map_err(io_err(&base))?;
+            }
+        }
+        remove_git_dir(&self.git_dir).map_err(io_err(&self.git_dir))
+    }
+
+    /// Make sure the `.git` file in the checkout of this worktree points to our [`git_dir()`](Self::git_dir()),
+    /// and return `true` if it had to be rewritten, similar to `git worktree repair`.
+    ///
+    /// If the checkout was moved, use [`Repository::repair_worktree()`] with its new location instead.
+    pub fn repair(&self) -> Result<bool, repair::Error> {
+        let Ok(base) = self.base() else {
+            return Ok(false);
+        };
+        if !base.is_dir() {
+            return Ok(false);
+        }
+        let dot_git = base.join(gix_discover::DOT_GIT_DIR);
+        let is_linked = gix_discover::path::from_gitdir_file(&dot_git)
+            .ok()
+            .and_then(|git_dir| gix_path::realpath(git_dir).ok())
+            .zip(gix_path::realpath(&self.git_dir).ok())
+            .map_or(false, |(actual, expected)| actual == expected);
+        if is_linked {
+            return Ok(false);
+        }
+        link_worktree(&self.git_dir, &base)?;
+        Ok(true)
+    }
+}
+
+/// Write the `gitdir` file in the administrative `git_dir` of a linked worktree to point to `work_dir`,
+/// and the `.git` file in `work_dir` to point back to `git_dir`, both with absolute paths like `git` does.
+#[cfg(feature = "worktree-mutation")]
+pub(crate) fn link_worktree(git_dir: &Path, work_dir: &Path) -> Result<(), repair::Error> {
+    let realpath = |path: &Path| {
+        gix_path::realpath(path).map_err(|err| repair::Error::Io {
+            path: path.to_owned(),
+            source: std::io::Error::new(std::io::ErrorKind::Other, err),
+        })
+    };
+    let git_dir = realpath(git_dir)?;
+    let work_dir = realpath(work_dir)?;
+    let write = |path: PathBuf, prefix: &str, target: &Path| {
+        let mut content = BString::from(prefix);
+        content.extend_from_slice(&gix_path::to_unix_separators_on_windows(gix_path::into_bstr(target)));
+        content.push(b'\n');
+        std::fs::write(&path, content).map_err(|source| repair::Error::Io { path, source })
+    };
+    write(git_dir.join("gitdir"), "", &work_dir.join(gix_discover::DOT_GIT_DIR))?;
+    write(work_dir.join(gix_discover::DOT_GIT_DIR), "gitdir: ", &git_dir)
+}
+
+/// Remove the administrative `git_dir` of a linked worktree, along with the `worktrees` directory if it is empty afterwards.
+#[cfg(feature = "worktree-mutation")]
+pub(crate) fn remove_git_dir(git_dir: &Path) -> std::io::Result<()> {
+    if git_dir.is_dir() {
+        std::fs::remove_dir_all(git_dir)?;
+    } else {
+        std::fs::remove_file(git_dir)?;
+    }
+    if let Some(worktrees) = git_dir.parent() {
+        std::fs::remove_dir(worktrees).ok();
+    }
+    Ok(())
+}
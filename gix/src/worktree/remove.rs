@@ -0,0 +1,55 @@
+use crate::{bstr::BStr, worktree::add, Worktree};
+
+/// The error returned by [`Worktree::remove_cached()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("At least one pathspec is needed to remove paths from the index")]
+    MissingPathspec,
+    #[error(transparent)]
+    Pathspec(#[from] crate::worktree::pathspec::Error),
+}
+
+/// The outcome of [`Worktree::remove_cached()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Outcome {
+    /// The amount of paths that were removed from the index.
+    pub removed: usize,
+}
+
+impl<'repo> Worktree<'repo> {
+    /// Remove all entries matching the pathspec `patterns` from `index` while leaving the worktree untouched,
+    /// just like `git rm -r --cached <pathspec>`.
+    ///
+    /// All stages of conflicting entries are removed, which is recorded in the resolve-undo extension.
+    /// Fails if `patterns` is empty, just like `git rm` does, as otherwise all entries would be removed.
+    ///
+    /// To persist the changes, [write](gix_index::File::write()) the index afterwards using
+    /// [`Repository::index_write_options()`](crate::Repository::index_write_options()).
+    pub fn remove_cached(
+        &self,
+        index: &mut gix_index::State,
+        patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+    ) -> Result<Outcome, Error> {
+        let patterns: Vec<_> = patterns.into_iter().collect();
+        if patterns.is_empty() {
+            return Err(Error::MissingPathspec);
+        }
+        let mut pathspec = self.pathspec(patterns)?;
+        let mut edits = Vec::<add::Edit>::new();
+        if let Some(range) = index.prefixed_entries_range(pathspec.search().common_prefix()) {
+            for entry in &index.entries()[range] {
+                let path = entry.path(index);
+                let is_new_path = edits.last().map_or(
+                    true,
+                    |edit| !matches!(edit, add::Edit::Remove { path: prev } if prev == path),
+                );
+                if is_new_path && pathspec.is_included(path, Some(entry.mode.is_sparse())) {
+                    edits.push(add::Edit::Remove { path: path.to_owned() });
+                }
+            }
+        }
+        let add::Outcome { removed, .. } = add::apply(index, edits);
+        Ok(Outcome { removed })
+    }
+}
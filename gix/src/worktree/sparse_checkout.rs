@@ -0,0 +1,249 @@
+use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+pub use gix_worktree_state::sparse_checkout::{Cone, Outcome, Patterns};
+
+use crate::{bstr::BStr, Worktree};
+
+/// The error returned by [`Worktree::sparse_checkout_set()`] and related methods.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Sparse checkouts are not enabled in this worktree as 'core.sparseCheckout' is not set")]
+    Disabled,
+    #[error(transparent)]
+    ConfigBoolean(#[from] crate::config::boolean::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    IndexWriteOptions(#[from] crate::config::index_write_options::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error("Could not read or write '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    UpdateConfig(#[from] crate::config::update_file::Error),
+    #[error(transparent)]
+    Lock(#[from] gix_lock::acquire::Error),
+    #[error(transparent)]
+    Commit(#[from] gix_lock::commit::Error<gix_lock::File>),
+    #[error("Could not obtain a thread-safe handle to the object database")]
+    ObjectStore(#[source] std::io::Error),
+    #[error(transparent)]
+    ExpandSparseIndex(#[from] gix_index::sparse::expand::Error),
+    #[error(transparent)]
+    CollapseSparseIndex(#[from] gix_index::sparse::collapse::Error),
+    #[error(transparent)]
+    SparseCheckout(#[from] gix_worktree_state::sparse_checkout::Error),
+}
+
+/// Options for use in [`Worktree::sparse_checkout_set()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, the default, patterns are directories which are included along with all of their content, along with all files
+    /// in their parent directories. Otherwise, they are matched like `.gitignore` patterns to determine which files to include.
+    ///
+    /// This is stored in `core.sparseCheckoutCone`.
+    pub cone: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { cone: true }
+    }
+}
+
+impl<'repo> Worktree<'repo> {
+    /// Return the path to the file holding the sparse checkout patterns of this worktree.
+    pub fn sparse_checkout_path(&self) -> PathBuf {
+        self.parent.git_dir().join("info").join("sparse-checkout")
+    }
+
+    /// Return the patterns of the sparse checkout of this worktree, or `None` if `core.sparseCheckout` isn't enabled.
+    ///
+    /// A missing patterns file is treated like an empty one, which only includes files in the root of the worktree
+    /// in cone mode, and no file at all otherwise.
+    pub fn sparse_checkout_patterns(&self) -> Result<Option<Patterns>, Error> {
+        let Some(cone) = self.parent.config.sparse_checkout()? else {
+            return Ok(None);
+        };
+        let path = self.sparse_checkout_path();
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(source) => return Err(Error::Io { path, source }),
+        };
+        Ok(Some(Patterns::from_bytes(&data, cone)))
+    }
+
+    /// Replace the sparse checkout patterns with `patterns`, enable sparse checkouts in the configuration with `options`,
+    /// and update the index and the worktree to match, like `git sparse-checkout set` would.
+    ///
+    /// In cone mode, `patterns` are directories, otherwise they are `.gitignore` style patterns.
+    /// `should_interrupt` can be used to stop checking out files early.
+    ///
+    /// Note that `core.sparseCheckout` and `core.sparseCheckoutCone` are written to the `config.worktree` file, after enabling
+    /// `extensions.worktreeConfig` in the repository configuration. Only repository instances opened afterwards will see the change.
+    pub fn sparse_checkout_set(
+        &self,
+        patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+        options: Options,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let patterns = patterns_from(patterns, options.cone);
+        self.write_sparse_checkout_config(options.cone)?;
+        self.write_sparse_checkout_patterns(&patterns)?;
+        self.apply_sparse_checkout(&patterns, true, should_interrupt)
+    }
+
+    /// Add `patterns` to the existing sparse checkout patterns and update the index and the worktree to match,
+    /// like `git sparse-checkout add` would.
+    ///
+    /// Fails if sparse checkouts aren't enabled.
+    pub fn sparse_checkout_add(
+        &self,
+        patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let patterns = match self.sparse_checkout_patterns()?.ok_or(Error::Disabled)? {
+            Patterns::Cone(mut cone) => {
+                for dir in patterns {
+                    cone.add_directory(dir.as_ref());
+                }
+                Patterns::Cone(cone)
+            }
+            Patterns::NonCone(mut list) => {
+                let mut buf = Vec::new();
+                for pattern in patterns {
+                    buf.extend_from_slice(pattern.as_ref());
+                    buf.push(b'\n');
+                }
+                list.patterns.extend(
+                    gix_glob::search::pattern::List::<gix_ignore::search::Ignore>::from_bytes(&buf, "".into(), None)
+                        .patterns,
+                );
+                Patterns::NonCone(list)
+            }
+        };
+        self.write_sparse_checkout_patterns(&patterns)?;
+        self.apply_sparse_checkout(&patterns, true, should_interrupt)
+    }
+
+    /// Update the index and the worktree to match the current sparse checkout patterns, like `git sparse-checkout reapply` would.
+    ///
+    /// Fails if sparse checkouts aren't enabled.
+    pub fn sparse_checkout_reapply(&self, should_interrupt: &AtomicBool) -> Result<Outcome, Error> {
+        let patterns = self.sparse_checkout_patterns()?.ok_or(Error::Disabled)?;
+        self.apply_sparse_checkout(&patterns, true, should_interrupt)
+    }
+
+    /// Include all files in the worktree again and disable sparse checkouts in the configuration,
+    /// like `git sparse-checkout disable` would.
+    ///
+    /// The patterns file is kept so that it may be used once sparse checkouts are enabled again.
+    pub fn sparse_checkout_disable(&self, should_interrupt: &AtomicBool) -> Result<Outcome, Error> {
+        let include_all = Patterns::from_bytes(b"/*", false);
+        let outcome = self.apply_sparse_checkout(&include_all, false, should_interrupt)?;
+        self.write_config_values(&[("sparseCheckout", "false")])?;
+        Ok(outcome)
+    }
+
+    /// Apply `patterns` to the index and the worktree, and write the index as sparse index if `may_collapse` is `true`
+    /// and `index.sparse` is set in cone mode.
+    fn apply_sparse_checkout(
+        &self,
+        patterns: &Patterns,
+        may_collapse: bool,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let repo = self.parent;
+        let mut index = repo.open_index()?;
+        index.expand_sparse(&repo.objects)?;
+        let checkout = repo.config.checkout_options(
+            repo,
+            gix_worktree::stack::state::attributes::Source::WorktreeThenIdMapping,
+        )?;
+        let objects = repo.objects.clone().into_arc().map_err(Error::ObjectStore)?;
+        let outcome = gix_worktree_state::sparse_checkout(
+            &mut index,
+            self.path,
+            patterns,
+            objects,
+            &gix_features::progress::Discard,
+            &gix_features::progress::Discard,
+            should_interrupt,
+            gix_worktree_state::sparse_checkout::Options { checkout },
+        )?;
+        if let (true, Patterns::Cone(cone)) = (may_collapse && repo.config.sparse_index()?, patterns) {
+            index.collapse_sparse(cone, |tree| repo.write_object(tree).map(crate::Id::detach))?;
+        }
+        index.write(repo.index_write_options()?)?;
+        Ok(outcome)
+    }
+
+    fn write_sparse_checkout_patterns(&self, patterns: &Patterns) -> Result<(), Error> {
+        let path = self.sparse_checkout_path();
+        let io_err = |source| Error::Io {
+            path: path.clone(),
+            source,
+        };
+        std::fs::create_dir_all(path.parent().expect("in info directory")).map_err(io_err)?;
+        let mut file = gix_lock::File::acquire_to_update_resource(&path, gix_lock::acquire::Fail::Immediately, None)?;
+        patterns.write_to(&mut file).map_err(io_err)?;
+        file.commit()?;
+        Ok(())
+    }
+
+    fn write_sparse_checkout_config(&self, cone: bool) -> Result<(), Error> {
+        self.write_config_values(&[
+            ("sparseCheckout", "true"),
+            ("sparseCheckoutCone", if cone { "true" } else { "false" }),
+        ])
+    }
+
+    /// Set all `core` keys and values in the `config.worktree` file of this worktree, and enable `extensions.worktreeConfig`
+    /// in the repository configuration if needed, just like `git` does.
+    fn write_config_values(&self, values: &[(&'static str, &'static str)]) -> Result<(), Error> {
+        use crate::config::tree::{Extensions, Key};
+        let repo = self.parent;
+        let uses_worktree_config = repo
+            .config
+            .resolved
+            .boolean_by_key(Extensions::WORKTREE_CONFIG.logical_name().as_str())
+            .transpose()
+            .ok()
+            .flatten()
+            .unwrap_or(false);
+        if !uses_worktree_config {
+            crate::config::update_file(repo.common_dir().join("config"), gix_config::Source::Local, |config| {
+                config.set_raw_value("extensions", None, "worktreeConfig", "true")?;
+                Ok(())
+            })?;
+        }
+        crate::config::update_file(
+            repo.git_dir().join("config.worktree"),
+            gix_config::Source::Worktree,
+            |config| {
+                for (key, value) in values {
+                    config.set_raw_value("core", None, *key, *value)?;
+                }
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+fn patterns_from(patterns: impl IntoIterator<Item = impl AsRef<BStr>>, cone: bool) -> Patterns {
+    if cone {
+        Cone::from_directories(patterns).into()
+    } else {
+        let mut buf = Vec::new();
+        for pattern in patterns {
+            buf.extend_from_slice(pattern.as_ref());
+            buf.push(b'\n');
+        }
+        Patterns::from_bytes(&buf, false)
+    }
+}
@@ -508,6 +508,384 @@ mod blocking_io {
         Ok(())
     }
 
+    #[test]
+    fn checkout_writes_the_index_as_configured() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            remote::repo("base").path(),
+            tmp.path(),
+            gix::create::Kind::WithWorktree,
+            Default::default(),
+            restricted(),
+        )?
+        .with_in_memory_config_overrides(["index.version=4", "index.skipHash=true"]);
+        let (mut checkout, _out) =
+            prepare.fetch_then_checkout(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        let (repo, _) = checkout.main_worktree(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+
+        let index = repo.index()?;
+        assert_eq!(index.version(), gix::index::Version::V4);
+        assert_eq!(index.checksum(), None, "the hash is skipped");
+        Ok(())
+    }
+
+    mod submodules {
+        use std::sync::atomic::AtomicBool;
+
+        use gix::submodule::update::Options;
+
+        use crate::util::restricted;
+
+        fn clone_super(
+            open_opts: gix::open::Options,
+            submodules: Option<Option<Options>>,
+        ) -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+            let dir = gix_testtools::scripted_fixture_read_only("make_submodule_checkout_repo.sh")?;
+            let tmp = gix_testtools::tempfile::TempDir::new()?;
+            let url = format!("file://{}", gix::path::realpath(dir.join("super"))?.display());
+            let mut prepare = gix::clone::PrepareFetch::new(
+                url.as_str(),
+                tmp.path(),
+                gix::create::Kind::WithWorktree,
+                Default::default(),
+                open_opts,
+            )?;
+            let (mut checkout, _out) = prepare.fetch_then_checkout(gix::progress::Discard, &AtomicBool::default())?;
+            if let Some(submodules) = submodules {
+                checkout = checkout.with_submodules(submodules);
+            }
+            let (repo, _) = checkout.main_worktree(gix::progress::Discard, &AtomicBool::default())?;
+            Ok((repo, tmp))
+        }
+
+        fn assert_checked_out_at_head<'repo>(
+            repo: &'repo gix::Repository,
+            path: &str,
+        ) -> crate::Result<gix::Submodule<'repo>> {
+            let submodule = repo
+                .submodules()?
+                .expect("modules present")
+                .find(|sm| sm.path().map_or(false, |p| *p == path))
+                .expect("submodule exists");
+            let work_dir = submodule.work_dir()?;
+            assert!(
+                work_dir.join(".git").is_file(),
+                "the submodule is linked with a `.git` file"
+            );
+            assert!(
+                submodule.git_dir().is_dir(),
+                "its repository is located in `.git/modules`"
+            );
+            let sm_repo = submodule.open()?.expect("present");
+            assert_eq!(sm_repo.head_id()?, submodule.index_id()?.expect("recorded in index"));
+            assert!(work_dir.join("file").is_file(), "the worktree was checked out");
+            Ok(submodule)
+        }
+
+        fn assert_checked_out(repo: &gix::Repository, path: &str) -> crate::Result<gix::Repository> {
+            let submodule = repo
+                .submodules()?
+                .expect("modules present")
+                .find(|sm| sm.path().map_or(false, |p| *p == path))
+                .expect("submodule exists");
+            let work_dir = submodule.work_dir()?;
+            assert!(
+                work_dir.join(".git").is_file(),
+                "the submodule is linked with a `.git` file"
+            );
+            assert!(
+                submodule.git_dir().is_dir(),
+                "its repository is located in `.git/modules`"
+            );
+            let sm_repo = submodule.open()?.expect("present");
+            assert_eq!(
+                sm_repo.head_id()?,
+                submodule.index_id()?.expect("recorded in index"),
+                "the submodule is checked out at the commit recorded in the superproject"
+            );
+            assert!(sm_repo.head()?.is_detached(), "like in git, the head is detached");
+            assert!(work_dir.join("file").is_file(), "the worktree was checked out");
+            Ok(sm_repo)
+        }
+
+        #[test]
+        fn are_left_as_empty_directories_by_default() -> crate::Result {
+            let (repo, _tmp) = clone_super(restricted(), None)?;
+            let work_dir = repo.work_dir().expect("non-bare");
+            for path in ["m", "dir/s", "n"] {
+                let path = work_dir.join(path);
+                assert!(path.is_dir(), "{path:?} must be present");
+                assert_eq!(std::fs::read_dir(&path)?.count(), 0, "{path:?} must be empty");
+            }
+            assert!(!repo.git_dir().join("modules").exists());
+            Ok(())
+        }
+
+        #[test]
+        fn are_checked_out_recursively_if_configured() -> crate::Result {
+            let (repo, _tmp) = clone_super(
+                restricted(),
+                Some(Some(Options {
+                    init: true,
+                    recursive: true,
+                    ..Default::default()
+                })),
+            )?;
+            let module = assert_checked_out(&repo, "m")?;
+            assert!(!module.is_shallow());
+            assert_checked_out(&module, "nested")?;
+
+            let shallow = assert_checked_out(&repo, "dir/s")?;
+            assert!(shallow.is_shallow(), "the shallow recommendation is honored");
+
+            let work_dir = repo.work_dir().expect("non-bare");
+            assert_eq!(
+                std::fs::read_dir(work_dir.join("n"))?.count(),
+                0,
+                "submodules with `update = none` are skipped"
+            );
+            assert!(!repo.git_dir().join("modules").join("n").exists());
+
+            let config = std::fs::read_to_string(repo.git_dir().join("config"))?;
+            assert!(
+                config.contains("[submodule \"m\"]"),
+                "initialized submodules are recorded in the superproject configuration"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn are_checked_out_if_submodule_recurse_is_set() -> crate::Result {
+            let (repo, _tmp) = clone_super(restricted().config_overrides(["submodule.recurse=true"]), None)?;
+            let module = assert_checked_out(&repo, "m")?;
+            assert_checked_out(&module, "nested")?;
+            Ok(())
+        }
+
+        #[test]
+        fn can_be_updated_after_cloning() -> crate::Result {
+            let (repo, _tmp) = clone_super(restricted(), Some(None))?;
+            let updated = repo.update_submodules(Options::default(), gix::progress::Discard, &AtomicBool::default())?;
+            assert!(
+                updated.is_empty(),
+                "inactive submodules aren't updated without initializing them"
+            );
+
+            let options = Options {
+                init: true,
+                ..Default::default()
+            };
+            let updated = repo.update_submodules(options, gix::progress::Discard, &AtomicBool::default())?;
+            assert_eq!(updated.len(), 2, "all but the submodule with `update = none`");
+            let module = assert_checked_out(&repo, "m")?;
+            assert!(
+                !module
+                    .work_dir()
+                    .expect("non-bare")
+                    .join("nested")
+                    .join("file")
+                    .exists(),
+                "nested submodules are left alone without recursion"
+            );
+
+            let updated = repo.update_submodules(options, gix::progress::Discard, &AtomicBool::default())?;
+            assert_eq!(updated.len(), 2, "existing checkouts can be updated again");
+            assert_checked_out(&repo, "m")?;
+            Ok(())
+        }
+
+        #[test]
+        fn local_modifications_are_only_overwritten_if_forced() -> crate::Result {
+            let options = Options {
+                init: true,
+                ..Default::default()
+            };
+            let (repo, _tmp) = clone_super(restricted(), Some(Some(options)))?;
+            let file = repo.work_dir().expect("non-bare").join("m").join("file");
+            std::fs::write(&file, "local change")?;
+
+            let err = repo
+                .update_submodules(options, gix::progress::Discard, &AtomicBool::default())
+                .unwrap_err();
+            assert!(
+                matches!(err, gix::submodule::update::Error::LocalModifications { ref name } if name == "m"),
+                "{err:?}"
+            );
+            assert_eq!(std::fs::read(&file)?, b"local change", "the modification is kept");
+
+            let updated = repo.update_submodules(
+                Options { force: true, ..options },
+                gix::progress::Discard,
+                &AtomicBool::default(),
+            )?;
+            assert_eq!(updated.len(), 2);
+            assert_ne!(
+                std::fs::read(&file)?,
+                b"local change",
+                "forced updates discard local modifications"
+            );
+            Ok(())
+        }
+
+        fn find<'repo>(repo: &'repo gix::Repository, name: &str) -> crate::Result<gix::Submodule<'repo>> {
+            Ok(repo
+                .submodules()?
+                .expect("modules present")
+                .find(|sm| sm.name() == name)
+                .expect("submodule exists"))
+        }
+
+        #[test]
+        fn can_be_added() -> crate::Result {
+            let (repo, _tmp) = clone_super(restricted(), Some(None))?;
+            let url = gix::url::parse("../nested".into())?;
+            let sm_repo = repo.add_submodule(
+                url.clone(),
+                "added/",
+                Default::default(),
+                gix::progress::Discard,
+                &AtomicBool::default(),
+            )?;
+            assert_eq!(
+                sm_repo.head_name()?.expect("not detached").as_bstr(),
+                "refs/heads/main",
+                "the branch the remote HEAD points to is checked out"
+            );
+
+            let repo = gix::open_opts(repo.path(), restricted())?;
+            let added = assert_checked_out_at_head(&repo, "added")?;
+            let modules = std::fs::read_to_string(repo.work_dir().expect("non-bare").join(".gitmodules"))?;
+            assert!(
+                modules.ends_with("[submodule \"added\"]\n\tpath = added\n\turl = ../nested\n"),
+                "`.gitmodules` keeps the url as given: {modules}"
+            );
+            assert!(
+                added.state()?.superproject_configuration,
+                "the submodule is initialized"
+            );
+            assert!(
+                added.url()?.to_bstring().ends_with(b"/nested"),
+                "…with the url resolved in the superproject configuration"
+            );
+            let index = repo.index()?;
+            let entry = index.entry_by_path("added".into()).expect("staged");
+            assert!(entry.mode.is_submodule(), "the submodule is added as gitlink");
+            assert_eq!(entry.id, sm_repo.head_id()?);
+            assert!(index.entry_by_path(".gitmodules".into()).is_some());
+
+            let err = repo
+                .add_submodule(
+                    url,
+                    "added",
+                    Default::default(),
+                    gix::progress::Discard,
+                    &AtomicBool::default(),
+                )
+                .unwrap_err();
+            assert!(
+                matches!(err, gix::submodule::add::Error::PathInIndex { .. }),
+                "paths in the index can't be used: {err:?}"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn can_be_added_with_branch_and_name() -> crate::Result {
+            let (repo, _tmp) = clone_super(restricted(), Some(None))?;
+            let sm_repo = repo.add_submodule(
+                gix::url::parse("../nested".into())?,
+                "dir/other",
+                gix::submodule::add::Options {
+                    name: Some("named".into()),
+                    branch: Some("other".into()),
+                },
+                gix::progress::Discard,
+                &AtomicBool::default(),
+            )?;
+            assert_eq!(
+                sm_repo.head_name()?.expect("not detached").as_bstr(),
+                "refs/heads/other"
+            );
+            assert!(repo.git_dir().join("modules").join("named").is_dir());
+            assert_eq!(
+                std::fs::read_to_string(repo.work_dir().expect("non-bare").join("dir/other/file"))?,
+                "other\n",
+                "the branch is checked out"
+            );
+
+            let repo = gix::open_opts(repo.path(), restricted())?;
+            let added = find(&repo, "named")?;
+            assert_eq!(added.path()?.as_ref(), "dir/other");
+            assert_eq!(
+                added.branch()?,
+                Some(gix::submodule::config::Branch::Name("other".into()))
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn can_be_synced() -> crate::Result {
+            let (repo, _tmp) = clone_super(
+                restricted(),
+                Some(Some(Options {
+                    init: true,
+                    ..Default::default()
+                })),
+            )?;
+            let modules_path = repo.work_dir().expect("non-bare").join(".gitmodules");
+            let modules = std::fs::read_to_string(&modules_path)?;
+            std::fs::write(&modules_path, modules.replace("../module", "../nested"))?;
+
+            let repo = gix::open_opts(repo.path(), restricted())?;
+            let url = find(&repo, "m")?.sync()?.expect("initialized");
+            assert!(url.to_bstring().ends_with(b"/nested"), "relative urls are resolved");
+            assert_eq!(find(&repo, "n")?.sync()?, None, "uninitialized submodules are skipped");
+
+            let repo = gix::open_opts(repo.path(), restricted())?;
+            let m = find(&repo, "m")?;
+            assert_eq!(m.url()?, url, "the superproject configuration was changed");
+            let sm_repo = m.open()?.expect("checked out");
+            assert_eq!(
+                sm_repo
+                    .find_default_remote(gix::remote::Direction::Fetch)
+                    .expect("present")?
+                    .url(gix::remote::Direction::Fetch),
+                Some(&url),
+                "the remote of the submodule was changed as well"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn can_be_deinitialized() -> crate::Result {
+            let (repo, _tmp) = clone_super(
+                restricted(),
+                Some(Some(Options {
+                    init: true,
+                    ..Default::default()
+                })),
+            )?;
+            let work_dir = repo.work_dir().expect("non-bare").join("m");
+            std::fs::write(work_dir.join("file"), "changed")?;
+
+            let m = find(&repo, "m")?;
+            let err = m.deinit(false).unwrap_err();
+            assert!(
+                matches!(err, gix::submodule::deinit::Error::Modified { .. }),
+                "local modifications are protected: {err:?}"
+            );
+            assert!(work_dir.join("file").is_file());
+
+            m.deinit(true)?;
+            assert_eq!(std::fs::read_dir(&work_dir)?.count(), 0, "the worktree is empty");
+            assert!(m.git_dir().is_dir(), "the repository is kept");
+
+            let repo = gix::open_opts(repo.path(), restricted())?;
+            assert!(!find(&repo, "m")?.state()?.superproject_configuration);
+            Ok(())
+        }
+    }
+
     #[test]
     fn fetch_and_checkout_empty_remote_repo() -> crate::Result {
         for version in [
@@ -3,6 +3,8 @@ mod describe {
     use gix::commit::describe::SelectRef::{AllRefs, AllTags, AnnotatedTags};
 
     use crate::named_repo;
+    #[cfg(feature = "status")]
+    use crate::repo_rw;
 
     #[test]
     fn tags_are_sorted_by_date_and_lexicographically() -> crate::Result {
@@ -43,4 +45,194 @@ mod describe {
         }
         Ok(())
     }
+
+    #[test]
+    fn names_can_be_selected_by_patterns() -> crate::Result {
+        let repo = named_repo("make_commit_describe_multiple_tags.sh")?;
+        let head = repo.head_commit()?;
+        assert_eq!(head.describe().matching(["v2*"]).format()?.to_string(), "v2");
+        assert_eq!(head.describe().excluding(["v4"]).format()?.to_string(), "v5");
+        assert_eq!(
+            head.describe()
+                .matching(["v*"])
+                .excluding(["v[4-5]"])
+                .format()?
+                .to_string(),
+            "v2"
+        );
+
+        let resolution = head
+            .describe()
+            .names(AllTags)
+            .matching(["l*"])
+            .try_resolve()?
+            .expect("lightweight tags are in the past");
+        assert_eq!(resolution.outcome.name.as_deref(), Some("l0".into()));
+        assert_eq!(resolution.outcome.depth, 2);
+
+        assert!(
+            head.describe().names(AllRefs).matching(["*"]).try_resolve()?.is_some(),
+            "branches match as well"
+        );
+        assert!(head.describe().matching(["none*"]).try_format()?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn contains() -> crate::Result {
+        let repo = named_repo("make_commit_describe_multiple_tags.sh")?;
+        for (rev, expected, expected_excluding, expected_all) in [
+            ("HEAD~2", "v1~1", "l0", "tags/v1~1"),
+            ("HEAD~1", "v1^0", "v5~1", "tags/v1^0"),
+            ("HEAD", "v2^0", "v5^0", "tags/v2^0"),
+        ] {
+            // These are the names `git describe --contains` produces with and without `--exclude 'v[0-4]*'` or `--all`.
+            let commit = repo.rev_parse_single(rev)?.object()?.into_commit();
+            let name = |describe: gix::commit::describe::Platform<'_>| -> crate::Result<String> {
+                Ok(describe
+                    .try_name_containing()?
+                    .expect("tags contain all commits")
+                    .to_string())
+            };
+            assert_eq!(name(commit.describe())?, expected, "{rev}");
+            assert_eq!(name(commit.describe().matching(["v*"]))?, expected, "{rev}");
+            assert_eq!(
+                name(commit.describe().excluding(["v[0-4]*"]))?,
+                expected_excluding,
+                "{rev}"
+            );
+            assert_eq!(name(commit.describe().names(AllRefs))?, expected_all, "{rev}");
+        }
+        let head = repo.head_commit()?;
+        assert!(head.describe().matching(["l*"]).try_name_containing()?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "status")]
+    fn dirty_and_broken_worktrees() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_commit_describe_multiple_tags.sh")?;
+        let work_dir = repo.work_dir().expect("non-bare").to_owned();
+        let describe = || -> crate::Result<_> {
+            Ok(repo
+                .head_commit()?
+                .describe()
+                .dirty_suffix(Some("dirty".into()))
+                .format()?
+                .to_string())
+        };
+        assert_eq!(describe()?, "v4", "the worktree is clean");
+
+        std::fs::write(work_dir.join("untracked"), "content")?;
+        assert_eq!(describe()?, "v4", "untracked files don't count");
+
+        let status = std::process::Command::new("git")
+            .args(["add", "untracked"])
+            .current_dir(&work_dir)
+            .status()?;
+        assert!(status.success());
+        assert_eq!(describe()?, "v4-dirty", "staged changes count");
+
+        std::fs::write(repo.git_dir().join("index"), "garbage".repeat(20))?;
+        assert!(describe().is_err(), "the state of the worktree can't be determined");
+        let format = repo
+            .head_commit()?
+            .describe()
+            .broken_suffix(Some("broken".into()))
+            .format()?;
+        assert_eq!(format.to_string(), "v4-broken");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "status")]
+    fn tracked_files_are_compared_after_applying_filters() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_commit_describe_multiple_tags.sh")?;
+        let work_dir = repo.work_dir().expect("non-bare").to_owned();
+        std::fs::write(work_dir.join(".gitattributes"), "*.txt text eol=crlf\n")?;
+        std::fs::write(work_dir.join("file.txt"), "a\nb\n")?;
+        for args in [
+            &["add", "."][..],
+            &["-c", "user.name=a", "-c", "user.email=a@b", "commit", "-q", "-m", "add file"],
+        ] {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(&work_dir)
+                .status()?;
+            assert!(status.success());
+        }
+        std::fs::remove_file(work_dir.join("file.txt"))?;
+        let status = std::process::Command::new("git")
+            .args(["checkout", "--", "file.txt"])
+            .current_dir(&work_dir)
+            .status()?;
+        assert!(status.success());
+
+        let describe = || -> crate::Result<_> {
+            Ok(repo
+                .head_commit()?
+                .describe()
+                .names(gix::commit::describe::SelectRef::AllRefs)
+                .dirty_suffix(Some("dirty".into()))
+                .format()?
+                .to_string())
+        };
+        assert_eq!(describe()?, "main", "the worktree is clean");
+
+        std::fs::write(work_dir.join("file.txt"), "a\r\nb\r\n")?;
+        assert_eq!(
+            describe()?,
+            "main",
+            "the file is rewritten with CRLF line endings, which are converted to LF for comparison"
+        );
+
+        std::fs::write(work_dir.join("file.txt"), "a\r\nc\r\n")?;
+        assert_eq!(describe()?, "main-dirty");
+        Ok(())
+    }
+}
+
+#[cfg(feature = "revision")]
+mod name_rev {
+    use gix::commit::name_rev::Options;
+
+    use crate::named_repo;
+
+    #[test]
+    fn names_like_git() -> crate::Result {
+        let repo = named_repo("make_commit_describe_multiple_tags.sh")?;
+        let commits = ["HEAD~2", "HEAD~1", "HEAD"]
+            .iter()
+            .map(|rev| repo.rev_parse_single(*rev).map(gix::Id::detach))
+            .collect::<Result<Vec<_>, _>>()?;
+        let names = |options: Options| -> crate::Result<Vec<String>> {
+            Ok(repo
+                .name_rev(&commits, &options)?
+                .into_iter()
+                .map(|name| name.expect("all commits are named").to_string())
+                .collect())
+        };
+        // `git name-rev --name-only`
+        assert_eq!(names(Options::default())?, ["tags/v1~1", "tags/v1^0", "tags/v2^0"]);
+        // `git name-rev --name-only --tags`
+        let tags = Options {
+            tags_only: true,
+            shorten_names: true,
+            ..Default::default()
+        };
+        assert_eq!(names(tags)?, ["v1~1", "v1^0", "v2^0"]);
+        // `git name-rev --name-only --refs 'l*' --refs 'v5'`
+        let patterns = Options {
+            ref_patterns: vec!["l*".into(), "v5".into()],
+            ..Default::default()
+        };
+        assert_eq!(names(patterns)?, ["l0", "v5~1", "v5^0"]);
+        // `git name-rev --name-only --exclude 'refs/tags/*'`
+        let branches = Options {
+            exclude_patterns: vec!["refs/tags/*".into()],
+            ..Default::default()
+        };
+        assert_eq!(names(branches)?, ["main~2", "main~1", "main"]);
+        Ok(())
+    }
 }
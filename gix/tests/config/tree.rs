@@ -315,17 +315,12 @@ mod diff {
             ("Default", Algorithm::Myers),
             ("minimal", Algorithm::MyersMinimal),
             ("histogram", Algorithm::Histogram),
+            ("patience", Algorithm::Patience),
+            ("Patience", Algorithm::Patience),
         ] {
             assert_eq!(Diff::ALGORITHM.try_into_algorithm(bcow(actual))?, expected);
             assert!(Diff::ALGORITHM.validate(actual.into()).is_ok());
         }
-        assert_eq!(
-            Diff::ALGORITHM
-                .try_into_algorithm(bcow("patience"))
-                .unwrap_err()
-                .to_string(),
-            "The 'patience' algorithm is not yet implemented"
-        );
         assert_eq!(
             Diff::ALGORITHM.try_into_algorithm(bcow("foo")).unwrap_err().to_string(),
             "Unknown diff algorithm named 'foo'"
@@ -618,6 +613,56 @@ mod index {
             "The key \"index.threads=nothing\" was invalid"
         );
     }
+
+    #[test]
+    #[cfg(feature = "index")]
+    fn version() -> crate::Result {
+        for (value, expected) in [
+            (2, gix::index::Version::V2),
+            (3, gix::index::Version::V3),
+            (4, gix::index::Version::V4),
+        ] {
+            assert_eq!(Index::VERSION.try_into_index_version(Ok(value))?, expected);
+            assert!(Index::VERSION.validate(value.to_string().as_str().into()).is_ok());
+        }
+        assert_eq!(
+            Index::VERSION.try_into_index_version(Ok(5)).unwrap_err().to_string(),
+            "The value of key \"index.version\" was invalid"
+        );
+        assert!(Index::VERSION.validate("1".into()).is_err());
+        assert!(Index::VERSION.validate("5".into()).is_err());
+        assert!(Index::VERSION.validate("four".into()).is_err());
+        Ok(())
+    }
+}
+
+mod split_index {
+    use std::time::{Duration, SystemTime};
+
+    use gix::config::tree::{Key, SplitIndex};
+
+    use crate::config::tree::bcow;
+
+    #[test]
+    fn shared_index_expire() -> crate::Result {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let key = &SplitIndex::SHARED_INDEX_EXPIRE;
+        assert_eq!(key.try_into_expiry(bcow("never"), now)?, None);
+        assert_eq!(key.try_into_expiry(bcow("now"), now)?, Some(now));
+        let two_weeks_ago = Some(now - Duration::from_secs(14 * 24 * 60 * 60));
+        assert_eq!(
+            key.try_into_expiry(bcow("2.weeks.ago"), now)?,
+            two_weeks_ago,
+            "the default value uses dots as separator"
+        );
+        assert_eq!(key.try_into_expiry(bcow("2 weeks ago"), now)?, two_weeks_ago);
+        assert!(key.validate("2.weeks.ago".into()).is_ok());
+        assert_eq!(
+            key.try_into_expiry(bcow("nothing"), now).unwrap_err().to_string(),
+            "The date format at key \"splitIndex.sharedIndexExpire=nothing\" was invalid"
+        );
+        Ok(())
+    }
 }
 
 mod extensions {
@@ -20,7 +20,8 @@ fn resource_cache() -> crate::Result {
                 command: Some("command".into()),
                 algorithm: Some(Algorithm::Histogram),
                 binary_to_text_command: Some("textconv".into()),
-                is_binary: None
+                is_binary: None,
+                function_name_pattern: Some("^fn .*".into()),
             },
             Driver {
                 name: "binary-false".into(),
@@ -0,0 +1,39 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+cat <<EOT >.gitignore
+ignored*
+EOT
+cat <<EOT >.gitattributes
+*.txt eol=lf
+*.c ident
+EOT
+
+mkdir dir
+echo unchanged >unchanged
+echo modified >modified
+echo deleted >deleted
+echo executable >executable
+echo nested >dir/nested
+echo deleted >dir/deleted
+git add .
+git commit -q -m c1
+
+echo change >>modified
+rm deleted dir/deleted
+chmod +x executable
+echo untracked >untracked
+echo ignored >ignored-file
+mkdir new-dir ignored-dir
+echo new >new-dir/file
+echo new >ignored-dir/file
+printf 'one\r\ntwo\r\n' >crlf.txt
+printf '$Id: anything $\n' >ident.c
+ln -s modified link
+
+cp .git/index .git/expected-index
+GIT_INDEX_FILE=.git/expected-index git add -A
+cp .git/index .git/expected-update-index
+GIT_INDEX_FILE=.git/expected-update-index git add -u
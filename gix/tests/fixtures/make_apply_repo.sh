@@ -0,0 +1,71 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# `repo` is at the `base` commit, with `changes.patch` turning it into the `changed` commit.
+# `three-way.patch` changes `conflict` and `clean` at `base`, while the `ours` branch changed them as well.
+# `three-way` has `ours` checked out, and `baseline` has the result of `git apply --3way` on top of it.
+git init -q repo
+(cd repo
+  seq 1 10 > conflict
+  seq 1 10 > clean
+  seq 1 10 > modified
+  echo content > deleted
+  seq 1 5 > renamed
+  printf '#!/bin/sh\n' > exe
+  printf 'bin\0ary\n' > binary
+  git add . && git commit -q -m base
+  git tag base
+
+  git checkout -q -b changed
+  sed -i -e 's/^5$/five/' modified
+  git rm -q deleted
+  git mv renamed renamed-new
+  chmod +x exe
+  mkdir dir && echo new > dir/new
+  printf 'binary\0\n' > binary
+  git add -A && git commit -q -m changed
+  git diff --binary base changed > ../changes.patch
+
+  git checkout -q -b theirs base
+  sed -i -e 's/^5$/theirs/' conflict clean
+  git commit -q -am theirs
+  git diff base theirs > ../three-way.patch
+
+  git checkout -q -b ours base
+  sed -i -e 's/^5$/ours/' conflict
+  sed -i -e 's/^3$/ours/' clean
+  git commit -q -am ours
+  git checkout -q base
+)
+
+git clone -q -b ours repo three-way
+git clone -q -b ours repo baseline
+(cd baseline
+  git apply --3way ../three-way.patch 2>/dev/null || true
+  git ls-files --stage > ../index.expected
+)
+
+# `symlink` tracks `link`, which points to `outside` of the worktree, and the patches change and create files beyond it.
+mkdir outside
+echo content > outside/file
+git init -q symlink
+(cd symlink
+  ln -s ../outside link
+  git add link && git commit -q -m symlink
+)
+cat > beyond-symlink-modify.patch <<'PATCH'
+diff --git a/link/file b/link/file
+--- a/link/file
++++ b/link/file
+@@ -1 +1 @@
+-content
++changed
+PATCH
+cat > beyond-symlink-create.patch <<'PATCH'
+diff --git a/link/new b/link/new
+new file mode 100644
+--- /dev/null
++++ b/link/new
+@@ -0,0 +1 @@
++new
+PATCH
@@ -0,0 +1,31 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q repo
+(cd repo
+  for n in 1 2 3 4 5 6 7 8; do
+    echo $n > f
+    git add f && git commit -q -m "c$n"
+  done
+  git checkout -q -b side main~6
+  echo side > side
+  git add side && git commit -q -m "side"
+  git checkout -q main
+)
+
+# Each baseline is a copy of `repo` in which `git` performed a bisection whose state is kept for comparison.
+cp -R repo run
+(cd run
+  git bisect start $(git rev-parse main main~7) >/dev/null
+  git bisect run sh -c 'test $(cat f) -lt 5' >/dev/null
+)
+
+cp -R repo terms
+(cd terms
+  git bisect start --no-checkout --term-new=broken --term-old=fine >/dev/null
+  git bisect broken main >/dev/null
+  git bisect fine main~7 >/dev/null
+  git bisect skip >/dev/null
+  git bisect broken >/dev/null
+  git bisect fine >/dev/null || :
+)
@@ -0,0 +1,91 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Write `<commit> <line in source> <line in blamed file> <source path>` for each line of `git blame "$@"` to `$name.baseline`.
+function baseline() {
+  local name=${1:?the name of the baseline}
+  shift
+  git blame --line-porcelain "$@" | awk '
+    /^[0-9a-f]+ [0-9]+ [0-9]+/ { commit = $1; source_line = $2; line = $3 }
+    /^filename / { print commit, source_line, line, substr($0, 10) }
+  ' > "../$name.baseline"
+}
+
+git init -q repo
+(cd repo
+  seq 1 5 > simple
+  seq 1 5 > renamed
+  printf '%s\n' header-line \
+    'the first block of lines that will be moved to the end of the file' \
+    'and it continues here with enough characters to be considered a move' \
+    separator-line \
+    'the second block of lines stays where it is and is not moved at all' \
+    trailer-line > moved
+  printf '%s\n' 'a line in the source file that will be copied into another file' \
+    'another line in the source file that will be copied into another file' \
+    'the source file is modified in the same commit that copies it' > source-modified
+  printf '%s\n' 'a line in the unmodified file that will be copied into a new file' \
+    'another line in the unmodified file that will be copied into a new file' > source-unmodified
+  printf '%s\n' 'a line in the executable file that will be copied into another file' \
+    'another line in the executable file that will be copied into another file' > source-executable
+  chmod +x source-executable
+  touch empty
+  seq 1 5 > merged
+  printf '%s\n' 'the first line of the file' 'the second line of the file' 'the third line of the file' > reformatted
+  git add . && git commit -q -m first
+
+  sed -i -e 's/^3$/three/' simple
+  git mv renamed renamed-new
+  sed -i -e 's/^2$/two/' renamed-new
+  printf '%s\n' header-line \
+    separator-line \
+    'the second block of lines stays where it is and is not moved at all' \
+    'the first block of lines that will be moved to the end of the file' \
+    'and it continues here with enough characters to be considered a move' \
+    trailer-line > moved
+  { echo 'new first line'; head -n 2 source-modified; echo 'new last line'; } > copied-from-modified
+  echo 'an added line' >> source-modified
+  { echo 'new first line'; cat source-executable; } > copied-from-executable
+  echo 'an added line' >> source-executable
+  sed -i -e 's/^the third line of the file$/the third line, changed in the second commit/' reformatted
+  { head -n 2 source-unmodified; echo 'new last line'; } > copied-from-unmodified
+  git add . && git commit -q -m second
+
+  seq 6 7 >> simple
+  git commit -q -am third
+
+  sed -i -e 's/^2$/TWO/' -e 's/^4$/FOUR/' simple
+  sed -i -e 's/$/ # reformatted/' reformatted
+  git commit -q -am 'reformat'
+  git rev-parse HEAD > ../reformat.commit
+
+  git checkout -q -b side
+  sed -i -e 's/^1$/side/' merged
+  git commit -q -am side
+  git checkout -q main 2>/dev/null || git checkout -q master
+  sed -i -e 's/^5$/main/' merged
+  git commit -q -am main
+  git merge -q --no-edit side
+
+  baseline simple simple
+  baseline simple-range -L 2,4 simple
+  baseline reformatted reformatted
+  baseline reformatted-ignore-rev --ignore-rev "$(cat ../reformat.commit)" reformatted
+  baseline renamed renamed-new
+  baseline moved moved
+  baseline moved-M -M moved
+  baseline copied-from-modified copied-from-modified
+  baseline copied-from-modified-C -C copied-from-modified
+  baseline copied-from-executable-C -C copied-from-executable
+  baseline copied-from-unmodified-C -C copied-from-unmodified
+  baseline copied-from-unmodified-CC -C -C copied-from-unmodified
+  baseline merged merged
+  baseline empty empty
+)
+
+git clone -q repo with-ignore-revs-file
+(cd with-ignore-revs-file
+  cp ../reformat.commit .git-blame-ignore-revs
+  git config blame.ignoreRevsFile .git-blame-ignore-revs
+  baseline reformatted-ignore-revs-file reformatted
+)
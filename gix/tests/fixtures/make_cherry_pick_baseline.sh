@@ -0,0 +1,59 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Each baseline has the tree of the commit created by `git` in `<name>.tree` and its message in `<name>.message`.
+function baseline() {
+  local name=${1:?need name}
+  git rev-parse 'HEAD^{tree}' > "../$name.tree"
+  git log -1 --format=%B > "../$name.message"
+}
+
+git init -q repo
+(cd repo
+  seq 1 10 > a
+  echo b > b
+  git add . && git commit -q -m "initial"
+  git tag base
+
+  git checkout -q -b feature
+  sed -i -e 's/^2$/feature/' a
+  git commit -q -am "change a" -m "with a body"
+  echo c > c
+  git add c && git commit -q -m "add c" -m "Signed-off-by: Some One <some@example.com>"
+
+  git checkout -q main
+  echo changed > b
+  git commit -q -am "change b"
+  git branch before-merge
+
+  git checkout -q -b merged
+  git merge -q --no-ff -m "merge feature" feature
+
+  git checkout -q -b conflicting base
+  sed -i -e 's/^2$/conflicting/' a
+  git commit -q -am "conflicting change"
+
+  git checkout -q -b picked main
+  git cherry-pick -x feature~1 >/dev/null
+  baseline cherry-pick
+
+  git checkout -q -b picked-with-trailer main
+  git cherry-pick -x feature >/dev/null
+  baseline cherry-pick-trailer
+
+  git checkout -q -b picked-merge base
+  git cherry-pick -m 1 merged >/dev/null
+  baseline cherry-pick-mainline
+
+  git checkout -q -b reverted main
+  git revert --no-edit main >/dev/null
+  baseline revert
+
+  git checkout -q -b reverted-merge merged
+  git revert --no-edit -m 1 merged >/dev/null
+  baseline revert-mainline
+
+  git checkout -q main
+)
+
+git clone -q --bare repo bare.git
@@ -19,6 +19,7 @@ cat <<EOF >>.git/config
   textconv = textconv
   algorithm = histogram
   binary = auto
+  xfuncname = "^fn .*"
 EOF
 
 git checkout -b main
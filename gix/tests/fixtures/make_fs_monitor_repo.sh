@@ -0,0 +1,17 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# `core.fsmonitor` is a hook which always reports that nothing changed, and the index holds its token,
+# with all entries marked valid after the second query.
+git init -q
+cat > .git/fsmonitor-hook <<'HOOK'
+#!/bin/sh
+printf 'token\0'
+HOOK
+chmod +x .git/fsmonitor-hook
+git config core.fsmonitor .git/fsmonitor-hook
+
+echo content > file
+git add file && git commit -q -m init
+git status --porcelain >/dev/null
+git status --porcelain >/dev/null
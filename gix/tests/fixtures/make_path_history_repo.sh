@@ -0,0 +1,55 @@
+#!/bin/bash
+set -eu -o pipefail
+
+# Write the arguments and the output of `git log --format=%H <args>` into `baseline.git`,
+# with each record separated by an empty line.
+function baseline() {
+  {
+    echo "$@"
+    git log --format=%H "$@"
+    echo
+  } >> baseline.git
+}
+
+seconds=0
+function commit() {
+  seconds=$((seconds + 1))
+  export GIT_COMMITTER_DATE="2000-01-01 00:00:$(printf %02d $seconds) +0000"
+  export GIT_AUTHOR_DATE="$GIT_COMMITTER_DATE"
+  git add -A
+  git commit -q --allow-empty -m "$1"
+}
+
+function merge() {
+  seconds=$((seconds + 1))
+  export GIT_COMMITTER_DATE="2000-01-01 00:00:$(printf %02d $seconds) +0000"
+  export GIT_AUTHOR_DATE="$GIT_COMMITTER_DATE"
+  git merge -q --no-edit -m "$1" "$2"
+}
+
+git init -q
+git checkout -q -b main
+git config merge.ff false
+
+mkdir dir
+seq 10 >dir/a.txt && echo 1 >other && commit "add files"
+seq 11 >dir/a.txt && commit "change a"
+echo 2 >other && commit "change other"
+git checkout -q -b side
+echo 1 >side && commit "add side"
+git checkout -q main
+git mv dir/a.txt dir/b.txt && echo 12 >>dir/b.txt && commit "rename a to b"
+merge "merge side" side
+echo 13 >>dir/b.txt && commit "change b"
+echo 3 >other && commit "change other again"
+
+baseline -- dir/b.txt
+baseline --follow -- dir/b.txt
+baseline -- dir
+baseline -- dir/a.txt other
+baseline -- "*.txt"
+baseline --full-history -- side
+baseline -- side
+
+git commit-graph write --no-progress --reachable --changed-paths
+git repack -adq
@@ -0,0 +1,81 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function notes() {
+  cat <<EOF >> README
+
+## Notes
+
+All numbers are integers.
+Overflows are not handled.
+There is no division.
+Negative numbers are $1.
+Results are returned, not printed.
+EOF
+}
+
+git init -q
+git config merge.ff false
+
+cat <<EOF > lib.c
+int compute(int a, int b)
+{
+	int sum = a + b;
+	return sum;
+}
+
+int twice(int a)
+{
+	return compute(a, a);
+}
+EOF
+cat <<EOF > README
+# Computations
+
+This is a libary that computes.
+It has no dependencies.
+
+## Usage
+
+Call compute() with two numbers,
+or twice() with one.
+EOF
+printf 'echo run\n' > run.sh
+echo unused > old.txt
+printf 'Documentation\n\nCall compute() to compute.\nCall twice() to compute twice.\n' > docs.txt
+git add . && git commit -q -m "base"
+
+echo "build: lib.c" > Makefile
+git add Makefile && git commit -q -m "add a makefile"
+
+# The first version of the series, based on the initial commit.
+git checkout -q -b topic-v1 main~1
+echo hello > greet.txt
+git add greet.txt && git commit -q -m "add greeting"
+sed -i 's/int sum = a + b;/int sum = b + a;/' lib.c
+git commit -q -am "refactor compute" -m "Swap the operands."
+sed -i 's/libary/library/' README && notes supported
+git commit -q -am "fix typo and add notes"
+git rm -q old.txt && git commit -q -m "remove unused file"
+chmod +x run.sh && git update-index --chmod=+x run.sh && git commit -q -m "make script executable"
+
+# The second version, rebased onto `main`, with a changed message, a changed diff, a dropped commit and a new commit.
+git checkout -q -b topic-v2 main
+echo hello > greet.txt
+git add greet.txt && git commit -q -m "add greeting"
+sed -i 's/int sum = a + b;/int sum = b + a;/' lib.c
+git commit -q -am "refactor compute" -m "Swap the operands, as that reads better."
+sed -i 's/libary/library/' README && notes fine
+git commit -q -am "fix typo and add notes"
+chmod +x run.sh && git update-index --chmod=+x run.sh && git commit -q -m "make script executable"
+mkdir doc && git mv docs.txt doc/guide.txt && echo "more docs" >> doc/guide.txt
+git add doc && git commit -q -m "move documentation"
+
+git range-diff --no-color main~1..topic-v1 main..topic-v2 > baseline
+git range-diff --no-color --no-patch main~1..topic-v1 main..topic-v2 > baseline-no-patch
+git range-diff --no-color --creation-factor=10 main~1..topic-v1 main..topic-v2 > baseline-creation-factor
+git range-diff --no-color topic-v1...topic-v2 > baseline-symmetric
+
+for commit in $(git rev-list --reverse main~1..topic-v1 main..topic-v2); do
+  git show --no-renames "$commit" | git patch-id --stable
+done > patch-ids
@@ -0,0 +1,52 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q repo
+(cd repo
+  seq 1 10 > a
+  echo b > b
+  git add . && git commit -q -m "initial"
+  git tag base
+
+  git checkout -q -b topic
+  sed -i -e 's/^2$/topic/' a
+  git commit -q -am "change a" -m "with a body"
+  echo c > c
+  git add c && git commit -q -m "add c"
+  git branch mid
+  sed -i -e 's/^9$/fixup/' a
+  git commit -q -am "fixup! change a"
+
+  git checkout -q main
+  echo changed > b
+  git commit -q -am "change b"
+
+  git checkout -q -b conflicting base
+  sed -i -e 's/^2$/conflicting/' a
+  git commit -q -am "conflicting change"
+
+  git checkout -q topic
+)
+
+# Each baseline has the tree of `HEAD` after the rebase in `<name>.tree` and the subjects of the rebased commits in
+# `<name>.subjects`.
+function baseline() {
+  local name=${1:?need name}
+  git rev-parse 'HEAD^{tree}' > "../$name.tree"
+  git log --format=%s main..HEAD > "../$name.subjects"
+}
+
+git clone -q repo baseline
+(cd baseline
+  git branch -q main origin/main
+  git branch -q mid origin/mid
+  git rebase -q main
+  baseline rebase
+
+  git reset -q --hard origin/topic
+  git branch -q -f mid origin/mid
+  GIT_SEQUENCE_EDITOR=: git rebase -q -i --autosquash --update-refs main >/dev/null 2>&1
+  baseline autosquash
+  git rev-parse 'mid^{tree}' > ../autosquash-mid.tree
+  git log --format=%s main..mid > ../autosquash-mid.subjects
+)
@@ -0,0 +1,42 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q base
+(cd base
+  mkdir -p a/b/c a/other d/e
+  for path in root-file a/file a/b/file a/b/c/file a/other/file d/file d/e/file d/e/file.txt; do
+    echo "$path" >"$path"
+  done
+  git add .
+  git commit -q -m c1
+)
+
+git clone -q base cone
+git clone -q base cone-expected
+(cd cone-expected
+  git sparse-checkout set --cone a/b d/e
+)
+
+git clone -q base cone-added-expected
+(cd cone-added-expected
+  git sparse-checkout set --cone a/b
+  git sparse-checkout add a/other
+)
+
+git clone -q base non-cone
+git clone -q base non-cone-expected
+(cd non-cone-expected
+  git sparse-checkout set --no-cone '/a/b/' '*.txt' '!d/e/'
+)
+
+git clone -q base modified
+(cd modified
+  echo change >>d/file
+)
+
+git clone -q base sparse-index
+git -C sparse-index config index.sparse true
+git clone -q base sparse-index-expected
+(cd sparse-index-expected
+  git sparse-checkout set --cone --sparse-index a/b
+)
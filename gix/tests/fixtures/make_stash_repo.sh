@@ -0,0 +1,37 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q repo
+(cd repo
+  seq 1 10 > a
+  echo b > b
+  mkdir dir && echo c > dir/c
+  echo '*.ignored' > .gitignore
+  git add . && git commit -q -m "initial"
+
+  sed -i -e 's/^2$/staged/' a && git add a
+  sed -i -e 's/^9$/unstaged/' a
+  echo changed > b
+  echo untracked > untracked
+  echo untracked > dir/untracked
+  echo ignored > file.ignored
+  git status --porcelain > ../status.before
+)
+
+cp -R repo git-stashed
+(cd git-stashed
+  git stash push -q --include-untracked
+  git rev-parse 'stash^{tree}' 'stash^2^{tree}' 'stash^3^{tree}' > ../stash.trees
+  git stash list > ../stash.list
+)
+
+git init -q conflict
+(cd conflict
+  seq 1 10 > a
+  git add . && git commit -q -m "initial"
+
+  sed -i -e 's/^5$/stashed/' a
+  git stash push -q
+  sed -i -e 's/^5$/committed/' a
+  git commit -q -am "conflicting change"
+)
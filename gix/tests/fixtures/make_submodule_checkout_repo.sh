@@ -0,0 +1,49 @@
+#!/bin/bash
+set -eu -o pipefail
+
+function add_submodule () {
+  git -c protocol.file.allow=always submodule add "$@"
+}
+
+git init -q nested
+(cd nested
+  echo nested > file
+  git add file
+  git commit -q -m "nested"
+  git checkout -q -b other
+  echo other > file
+  git commit -q -am "other"
+  git checkout -q main
+)
+
+git init -q module
+(cd module
+  echo module > file
+  git add file
+  git commit -q -m "module"
+  add_submodule ../nested nested
+  git commit -q -m "add nested"
+)
+
+git init -q shallow
+(cd shallow
+  echo one > file
+  git add file
+  git commit -q -m "one"
+  echo two > file
+  git commit -q -am "two"
+)
+
+git init -q super
+(cd super
+  echo super > file
+  git add file
+  git commit -q -m "super"
+  add_submodule ../module m
+  add_submodule ../shallow dir/s
+  git config -f .gitmodules submodule.dir/s.shallow true
+  add_submodule ../nested n
+  git config -f .gitmodules submodule.n.update none
+  git add .gitmodules
+  git commit -q -m "add submodules"
+)
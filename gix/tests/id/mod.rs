@@ -134,6 +134,30 @@ mod ancestors {
         Ok(())
     }
 
+    #[test]
+    fn reverse() -> crate::Result {
+        let repo = crate::repo("make_repo_with_fork_and_dates.sh")?.to_thread_local();
+        let head = repo.head()?.into_peeled_id()?;
+
+        for use_commit_graph in [false, true] {
+            let commits = |reverse: bool| -> crate::Result<Vec<_>> {
+                Ok(head
+                    .ancestors()
+                    .sorting(commit::Sorting::Topological)
+                    .use_commit_graph(use_commit_graph)
+                    .reverse(reverse)
+                    .all()?
+                    .map(|c| c.map(|c| c.id))
+                    .collect::<Result<Vec<_>, _>>()?)
+            };
+            let mut expected = commits(false)?;
+            assert_eq!(expected.len(), 4);
+            expected.reverse();
+            assert_eq!(commits(true)?, expected, "the same commits, oldest first");
+        }
+        Ok(())
+    }
+
     #[test]
     fn filtered() -> crate::Result {
         let repo = crate::repo("make_repo_with_fork_and_dates.sh")?.to_thread_local();
@@ -144,6 +168,8 @@ mod ancestors {
                 commit::Sorting::BreadthFirst,
                 commit::Sorting::ByCommitTimeNewestFirst,
                 commit::Sorting::ByCommitTimeNewestFirstCutoffOlderThan { seconds: 0 },
+                commit::Sorting::Topological,
+                commit::Sorting::AuthorDateTopological,
             ] {
                 let commits_graph_order = head
                     .ancestors()
@@ -165,4 +191,61 @@ mod ancestors {
         }
         Ok(())
     }
+
+    #[test]
+    #[cfg(feature = "blob-diff")]
+    fn pathspec() -> crate::Result {
+        let repo = crate::repo("make_path_history_repo.sh")?.to_thread_local();
+        let baseline = std::fs::read_to_string(repo.work_dir().expect("non-bare").join("baseline.git"))?;
+        let head = repo.head()?.into_peeled_id()?;
+        let mut cases = 0;
+        for record in baseline.split("\n\n").filter(|record| !record.trim().is_empty()) {
+            let mut lines = record.lines();
+            let args = lines.next().expect("arguments");
+            let (options, paths) = args.split_once("-- ").expect("paths are always given");
+            let expected: Vec<_> = lines.map(hex_to_id).collect();
+
+            for use_commit_graph in [false, true] {
+                let mut walk = head
+                    .ancestors()
+                    .sorting(commit::Sorting::ByCommitTimeNewestFirst)
+                    .use_commit_graph(use_commit_graph)
+                    .with_pathspec(gix::Pathspec::new(&repo, paths.split(' '), false, || {
+                        unreachable!("no attributes are used")
+                    })?);
+                for option in options.split_whitespace() {
+                    walk = match option {
+                        "--follow" => walk.follow_renames(true),
+                        "--full-history" => walk.simplification(commit::Simplification::FullHistory),
+                        unknown => unreachable!("unknown option: {unknown}"),
+                    };
+                }
+                let actual = walk.all()?.map(|c| c.map(|c| c.id)).collect::<Result<Vec<_>, _>>()?;
+                assert_eq!(actual, expected, "git log {args}: commit-graph = {use_commit_graph}");
+            }
+            cases += 1;
+        }
+        assert_eq!(cases, 7, "all cases were parsed");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "blob-diff")]
+    fn follow_renames_requires_a_single_path() -> crate::Result {
+        let repo = crate::repo("make_path_history_repo.sh")?.to_thread_local();
+        let head = repo.head()?.into_peeled_id()?;
+        for paths in [&["dir/b.txt", "other"][..], &["*.txt"]] {
+            let err = head
+                .ancestors()
+                .with_pathspec(gix::Pathspec::new(&repo, paths, false, || {
+                    unreachable!("no attributes are used")
+                })?)
+                .follow_renames(true)
+                .all()
+                .map(|_| ())
+                .unwrap_err();
+            assert!(matches!(err, gix::revision::walk::Error::FollowRequiresSinglePath));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,208 @@
+use gix::{
+    apply::{Location, Options},
+    bstr::ByteSlice,
+};
+
+/// Return the writable fixture along with the repository at `name` in it.
+fn repo_rw(name: &str) -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let dir = gix_testtools::scripted_fixture_writable("make_apply_repo.sh")?;
+    let repo = gix::open_opts(dir.path().join(name), crate::restricted())?;
+    Ok((repo, dir))
+}
+
+fn patches(dir: &gix_testtools::tempfile::TempDir, name: &str) -> crate::Result<Vec<gix::apply::Patch>> {
+    Ok(gix::apply::parse(&std::fs::read(dir.path().join(name))?)?)
+}
+
+/// Return all entries as `<mode> <id> <stage>\t<path>`, like `git ls-files --stage`.
+fn entries(index: &gix::index::State) -> String {
+    index
+        .entries()
+        .iter()
+        .map(|e| {
+            format!(
+                "{:o} {} {}\t{}\n",
+                e.mode.bits(),
+                e.id,
+                e.stage(),
+                e.path(index).to_str_lossy()
+            )
+        })
+        .collect()
+}
+
+fn tree_of(repo: &gix::Repository, rev: &str) -> crate::Result<gix::ObjectId> {
+    Ok(repo.rev_parse_single(rev)?.object()?.peel_to_tree()?.id)
+}
+
+#[test]
+fn to_tree_without_worktree() -> crate::Result {
+    let (repo, tmp) = repo_rw("repo")?;
+    let patches = patches(&tmp, "changes.patch")?;
+    let (tree, outcome) = repo.apply_to_tree(tree_of(&repo, "base")?, &patches, Options::default())?;
+    assert_eq!(
+        tree,
+        tree_of(&repo, "changed")?,
+        "the patch recreates the tree it was made from"
+    );
+    assert!(outcome.is_clean());
+    assert_eq!(
+        outcome.files.iter().map(|f| f.path.to_string()).collect::<Vec<_>>(),
+        ["binary", "deleted", "dir/new", "exe", "modified", "renamed-new"]
+    );
+    Ok(())
+}
+
+#[test]
+fn to_index_and_worktree() -> crate::Result {
+    for location in [Location::Index, Location::Worktree, Location::WorktreeAndIndex] {
+        let (repo, tmp) = repo_rw("repo")?;
+        let patches = patches(&tmp, "changes.patch")?;
+        let workdir = repo.work_dir().expect("non-bare");
+        let mut index = repo.open_index()?;
+        repo.apply(&patches, &mut index, location, Options::default())?;
+
+        let index_tree = index.write_tree(|tree| repo.write_object(tree).map(gix::Id::detach))?;
+        let expected_tree = match location {
+            Location::Worktree => tree_of(&repo, "base")?,
+            Location::Index | Location::WorktreeAndIndex => tree_of(&repo, "changed")?,
+        };
+        assert_eq!(index_tree, expected_tree, "{location:?}");
+
+        let worktree_changed = location != Location::Index;
+        assert_eq!(workdir.join("dir/new").is_file(), worktree_changed, "{location:?}");
+        if worktree_changed {
+            assert_eq!(std::fs::read(workdir.join("binary"))?, b"binary\0\n");
+            assert!(!workdir.join("deleted").exists());
+            assert!(!workdir.join("renamed").exists());
+            assert_eq!(std::fs::read(workdir.join("renamed-new"))?, b"1\n2\n3\n4\n5\n");
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                assert_ne!(
+                    std::fs::metadata(workdir.join("exe"))?.permissions().mode() & 0o111,
+                    0,
+                    "the executable bit is set"
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn nothing_changes_if_a_hunk_does_not_apply_unless_rejects_are_allowed() -> crate::Result {
+    let (repo, tmp) = repo_rw("three-way")?;
+    let workdir = repo.work_dir().expect("non-bare");
+    let patches = patches(&tmp, "three-way.patch")?;
+    let mut index = repo.open_index()?;
+    let before = entries(&index);
+    let err = repo
+        .apply(&patches, &mut index, Location::WorktreeAndIndex, Options::default())
+        .unwrap_err();
+    assert_eq!(err.to_string(), "clean: patch does not apply, hunks [0] were rejected");
+    assert_eq!(entries(&index), before);
+    assert_eq!(
+        std::fs::read(workdir.join("clean"))?.as_bstr(),
+        "1\n2\nours\n4\n5\n6\n7\n8\n9\n10\n"
+    );
+
+    let outcome = repo.apply(
+        &patches,
+        &mut index,
+        Location::Worktree,
+        Options {
+            reject: true,
+            ..Default::default()
+        },
+    )?;
+    assert!(!outcome.is_clean());
+    assert_eq!(
+        outcome
+            .files
+            .iter()
+            .map(|f| (f.path.to_string(), f.rejected_hunks.clone()))
+            .collect::<Vec<_>>(),
+        [("clean".into(), vec![0]), ("conflict".into(), vec![0])]
+    );
+    Ok(())
+}
+
+#[test]
+fn three_way_merges_like_git() -> crate::Result {
+    let (repo, tmp) = repo_rw("three-way")?;
+    let workdir = repo.work_dir().expect("non-bare");
+    let patches = patches(&tmp, "three-way.patch")?;
+    let mut index = repo.open_index()?;
+    let outcome = repo.apply(
+        &patches,
+        &mut index,
+        Location::Worktree,
+        Options {
+            file: gix::apply::file::Options {
+                three_way: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        outcome.files.iter().map(|f| f.merge).collect::<Vec<_>>(),
+        [
+            Some(gix::merge::blob::Resolution::Complete),
+            Some(gix::merge::blob::Resolution::Conflict)
+        ]
+    );
+    for file in ["clean", "conflict"] {
+        assert_eq!(
+            std::fs::read(workdir.join(file))?.as_bstr(),
+            std::fs::read(tmp.path().join("baseline").join(file))?.as_bstr(),
+            "{file}: the merge result is the same as the one of git"
+        );
+    }
+    assert_eq!(
+        entries(&index),
+        std::fs::read_to_string(tmp.path().join("index.expected"))?,
+        "conflicts are recorded in the index"
+    );
+    assert!(
+        index
+            .write_tree(|tree| repo.write_object(tree).map(gix::Id::detach))
+            .is_err(),
+        "trees can't be written with conflicts"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn files_beyond_symlinks_are_rejected() -> crate::Result {
+    for (patch, path) in [
+        ("beyond-symlink-modify.patch", "link/file"),
+        ("beyond-symlink-create.patch", "link/new"),
+    ] {
+        for location in [Location::Index, Location::Worktree, Location::WorktreeAndIndex] {
+            let (repo, tmp) = repo_rw("symlink")?;
+            let link = repo.work_dir().expect("non-bare").join("link");
+            // Copying the fixture turns the symlink into a directory.
+            std::fs::remove_dir_all(&link)?;
+            std::os::unix::fs::symlink("../outside", &link)?;
+            let patches = patches(&tmp, patch)?;
+            let mut index = repo.open_index()?;
+            let err = repo
+                .apply(&patches, &mut index, location, Options::default())
+                .unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("{path}: affected file is beyond a symbolic link"),
+                "{patch}, {location:?}: like git, paths that lead through a symlink are refused"
+            );
+            assert_eq!(std::fs::read(tmp.path().join("outside/file"))?.as_bstr(), "content\n");
+            assert!(
+                !tmp.path().join("outside/new").exists(),
+                "nothing is written outside of the worktree"
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use std::path::Path;
+
+use gix::bisect::{Mark, Options, Outcome, Terms};
+
+fn repo_rw() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let dir = gix_testtools::scripted_fixture_writable("make_bisect_history_repo.sh")?;
+    let repo = gix::open_opts(dir.path().join("repo"), crate::restricted())?;
+    Ok((repo, dir))
+}
+
+fn git(work_dir: &Path, args: &[&str]) -> crate::Result<String> {
+    let out = std::process::Command::new("git")
+        .args(args)
+        .current_dir(work_dir)
+        .output()?;
+    assert!(out.status.success(), "git {args:?} failed: {out:?}");
+    Ok(String::from_utf8(out.stdout)?)
+}
+
+fn id(repo: &gix::Repository, rev: &str) -> crate::Result<gix::ObjectId> {
+    Ok(repo.rev_parse_single(rev)?.detach())
+}
+
+/// Assert that the bisect state of `repo` is the same as the one `git` left in the baseline `name`, with `files` having the
+/// same content and the pseudo-references `refs` pointing to the same commits.
+fn assert_state_like_git(
+    repo: &gix::Repository,
+    dir: &Path,
+    name: &str,
+    files: &[&str],
+    refs: &[&str],
+) -> crate::Result {
+    let baseline = dir.join(name);
+    for file in files {
+        assert_eq!(
+            std::fs::read_to_string(repo.git_dir().join(file))?,
+            std::fs::read_to_string(baseline.join(".git").join(file))?,
+            "{file} is written like git does"
+        );
+    }
+    let work_dir = repo.work_dir().expect("non-bare");
+    for name in refs {
+        assert_eq!(
+            git(work_dir, &["rev-parse", name])?,
+            git(&baseline, &["rev-parse", name])?,
+            "{name} points to the same commit"
+        );
+    }
+    assert_eq!(
+        git(work_dir, &["for-each-ref", "refs/bisect/"])?,
+        git(&baseline, &["for-each-ref", "refs/bisect/"])?,
+        "the same commits are marked"
+    );
+    assert_eq!(
+        git(work_dir, &["log", "-g", "--format=%gs", "HEAD"])?,
+        git(&baseline, &["log", "-g", "--format=%gs", "HEAD"])?,
+        "HEAD moved in the same way"
+    );
+    Ok(())
+}
+
+#[test]
+fn run_like_git() -> crate::Result {
+    let (repo, dir) = repo_rw()?;
+    let outcome = repo.bisect_start(Some(id(&repo, "main")?), &[id(&repo, "main~7")?], Options::default())?;
+    assert!(
+        matches!(outcome, Outcome::Next { commit, remaining: 3, steps: 2 } if commit == id(&repo, "main~4")?),
+        "{outcome:?}"
+    );
+    assert_eq!(
+        repo.head_id()?,
+        id(&repo, "main~4")?,
+        "the commit to test is checked out"
+    );
+    assert_eq!(
+        std::fs::read_to_string(repo.work_dir().expect("non-bare").join("f"))?,
+        "4\n"
+    );
+
+    let outcome = repo.bisect_run("test $(cat f) -lt 5".into())?;
+    let first_bad = id(&repo, "main~3")?;
+    assert!(
+        matches!(outcome, Outcome::FirstBad { commit } if commit == first_bad),
+        "{outcome:?}"
+    );
+    assert_state_like_git(
+        &repo,
+        dir.path(),
+        "run",
+        &[
+            "BISECT_LOG",
+            "BISECT_START",
+            "BISECT_TERMS",
+            "BISECT_NAMES",
+            "BISECT_ANCESTORS_OK",
+        ],
+        &["BISECT_EXPECTED_REV"],
+    )?;
+    assert_eq!(
+        repo.bisect_log()?,
+        std::fs::read(dir.path().join("run/.git/BISECT_LOG"))?
+    );
+
+    repo.bisect_reset()?;
+    assert!(repo.bisect_status()?.is_none(), "the state was removed");
+    let work_dir = repo.work_dir().expect("non-bare");
+    assert_eq!(git(work_dir, &["for-each-ref", "refs/bisect/"])?, "");
+    assert_eq!(git(work_dir, &["symbolic-ref", "HEAD"])?, "refs/heads/main\n");
+    assert_eq!(git(work_dir, &["status", "--porcelain"])?, "", "the worktree is clean");
+    assert_eq!(
+        git(work_dir, &["log", "-g", "-1", "--format=%gs", "HEAD"])?,
+        format!("checkout: moving from {first_bad} to main\n")
+    );
+    Ok(())
+}
+
+#[test]
+fn custom_terms_without_checkout_and_skip_like_git() -> crate::Result {
+    let (repo, dir) = repo_rw()?;
+    let options = Options {
+        terms: Terms {
+            bad: "broken".into(),
+            good: "fine".into(),
+        },
+        no_checkout: true,
+    };
+    let outcome = repo.bisect_start(None, &[], options)?;
+    assert!(matches!(outcome, Outcome::Waiting { bad: false, good: 0 }));
+    let state = repo.bisect_status()?.expect("in progress");
+    assert_eq!(state.terms.mark_by_name("broken".into()), Some(Mark::Bad));
+    assert_eq!(state.start, "main");
+    assert!(state.no_checkout);
+
+    let outcome = repo.bisect_mark(Mark::Bad, &[id(&repo, "main")?])?;
+    assert!(matches!(outcome, Outcome::Waiting { bad: true, good: 0 }));
+    let outcome = repo.bisect_mark(Mark::Good, &[id(&repo, "main~7")?])?;
+    assert!(matches!(outcome, Outcome::Next { commit, .. } if commit == id(&repo, "main~4")?));
+    assert_eq!(id(&repo, "BISECT_HEAD")?, id(&repo, "main~4")?);
+    assert_eq!(repo.head_id()?, id(&repo, "main")?, "nothing is checked out");
+
+    let outcome = repo.bisect_mark(Mark::Skip, &[])?;
+    assert!(
+        matches!(outcome, Outcome::Next { commit, .. } if commit == id(&repo, "main~3")?),
+        "a commit near the skipped one is chosen like git does: {outcome:?}"
+    );
+    let outcome = repo.bisect_mark(Mark::Bad, &[])?;
+    assert!(matches!(outcome, Outcome::Next { commit, .. } if commit == id(&repo, "main~5")?));
+    let outcome = repo.bisect_mark(Mark::Good, &[])?;
+    let Outcome::OnlySkipped { candidates } = outcome else {
+        unreachable!("only the skipped commit is left, along with the bad one: {outcome:?}")
+    };
+    assert_eq!(candidates, [id(&repo, "main~3")?, id(&repo, "main~4")?]);
+
+    assert_state_like_git(
+        &repo,
+        dir.path(),
+        "terms",
+        &["BISECT_LOG", "BISECT_START", "BISECT_TERMS", "BISECT_NAMES"],
+        &["BISECT_HEAD", "BISECT_EXPECTED_REV"],
+    )?;
+    let state = repo.bisect_status()?.expect("still in progress");
+    assert_eq!(state.bad, Some(id(&repo, "main~3")?));
+    assert_eq!(state.skipped, [id(&repo, "main~4")?]);
+
+    repo.bisect_reset()?;
+    assert!(repo.bisect_status()?.is_none());
+    assert_eq!(repo.head_id()?, id(&repo, "main")?, "nothing was checked out");
+    Ok(())
+}
+
+#[test]
+fn merge_base_is_tested_first() -> crate::Result {
+    let (repo, _dir) = repo_rw()?;
+    let outcome = repo.bisect_start(Some(id(&repo, "main")?), &[id(&repo, "side")?], Options::default())?;
+    let merge_base = id(&repo, "main~6")?;
+    assert!(
+        matches!(outcome, Outcome::MergeBase { commit } if commit == merge_base),
+        "{outcome:?}"
+    );
+    assert_eq!(repo.head_id()?, merge_base);
+
+    let err = repo.bisect_mark(Mark::Bad, &[]).unwrap_err();
+    assert!(
+        matches!(err, gix::bisect::Error::MergeBaseIsBad { merge_base: actual, .. } if actual == merge_base),
+        "the change happened before the good commit"
+    );
+    Ok(())
+}
+
+#[test]
+fn invalid_terms_are_rejected() -> crate::Result {
+    let (repo, _dir) = repo_rw()?;
+    for (bad, good) in [("same", "same"), ("good", "bad"), ("run", "good"), ("bad", "in valid")] {
+        let options = Options {
+            terms: Terms {
+                bad: bad.into(),
+                good: good.into(),
+            },
+            no_checkout: false,
+        };
+        assert!(matches!(
+            repo.bisect_start(None, &[], options),
+            Err(gix::bisect::Error::InvalidTerm { .. })
+        ));
+    }
+    assert!(repo.bisect_status()?.is_none(), "nothing was started");
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use gix::{
+    blame::{CopySource, Options},
+    bstr::ByteSlice,
+};
+
+fn repo(name: &str) -> crate::Result<gix::Repository> {
+    Ok(crate::named_subrepo_opts(
+        "make_blame_repo.sh",
+        name,
+        crate::restricted(),
+    )?)
+}
+
+/// Return the lines of the baseline at `name`, as `<commit> <line in source> <line in blamed file> <source path>`.
+fn baseline(name: &str) -> crate::Result<Vec<String>> {
+    let dir = gix_testtools::scripted_fixture_read_only("make_blame_repo.sh")?;
+    let data = std::fs::read(dir.join(format!("{name}.baseline")))?;
+    Ok(data.lines().map(|line| line.to_str_lossy().into_owned()).collect())
+}
+
+/// Return the lines blamed in `outcome` in the format of our baselines.
+fn lines(outcome: &gix::blame::Outcome) -> Vec<String> {
+    outcome
+        .entries
+        .iter()
+        .flat_map(|entry| {
+            entry
+                .range_in_blamed_file
+                .clone()
+                .zip(entry.range_in_source.clone())
+                .map(move |(line, source_line)| {
+                    format!(
+                        "{} {} {} {}",
+                        entry.commit_id,
+                        source_line + 1,
+                        line + 1,
+                        entry.source_path
+                    )
+                })
+        })
+        .collect()
+}
+
+fn blame(repo: &gix::Repository, path: &str, options: &Options) -> crate::Result<Vec<String>> {
+    let outcome = repo.blame(path, repo.head_id()?, options)?;
+    Ok(lines(&outcome))
+}
+
+#[test]
+fn lines_match_git() -> crate::Result {
+    let repo = repo("repo")?;
+    for (name, path) in [
+        ("simple", "simple"),
+        ("renamed", "renamed-new"),
+        ("moved", "moved"),
+        ("copied-from-modified", "copied-from-modified"),
+        ("merged", "merged"),
+        ("reformatted", "reformatted"),
+    ] {
+        assert_eq!(blame(&repo, path, &Options::default())?, baseline(name)?, "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn empty_files_have_no_entries() -> crate::Result {
+    let repo = repo("repo")?;
+    let outcome = repo.blame("empty", repo.head_id()?, &Options::default())?;
+    assert!(outcome.entries.is_empty());
+    assert!(outcome.blob.is_empty());
+    assert_eq!(lines(&outcome), baseline("empty")?, "git agrees");
+    Ok(())
+}
+
+#[test]
+fn line_ranges() -> crate::Result {
+    let repo = repo("repo")?;
+    let lines_2_to_4 = 1..4;
+    let options = Options {
+        ranges: vec![lines_2_to_4],
+        ..Default::default()
+    };
+    assert_eq!(blame(&repo, "simple", &options)?, baseline("simple-range")?);
+
+    let out_of_bounds = 5..8;
+    let err = repo
+        .blame(
+            "simple",
+            repo.head_id()?,
+            &Options {
+                ranges: vec![out_of_bounds],
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line range 6..8 is invalid as the file has only 7 lines"
+    );
+    Ok(())
+}
+
+#[test]
+fn moves_within_a_file() -> crate::Result {
+    let repo = repo("repo")?;
+    let options = Options {
+        moves: true,
+        ..Default::default()
+    };
+    assert_eq!(blame(&repo, "moved", &options)?, baseline("moved-M")?);
+    Ok(())
+}
+
+#[test]
+fn copies_from_other_files() -> crate::Result {
+    let repo = repo("repo")?;
+    for (name, path, copies) in [
+        (
+            "copied-from-modified-C",
+            "copied-from-modified",
+            CopySource::ModifiedFiles,
+        ),
+        (
+            "copied-from-executable-C",
+            "copied-from-executable",
+            CopySource::ModifiedFiles,
+        ),
+        (
+            "copied-from-unmodified-C",
+            "copied-from-unmodified",
+            CopySource::ModifiedFiles,
+        ),
+        (
+            "copied-from-unmodified-CC",
+            "copied-from-unmodified",
+            CopySource::AllFilesIfCreated,
+        ),
+    ] {
+        let options = Options {
+            copies: Some(copies),
+            ..Default::default()
+        };
+        assert_eq!(blame(&repo, path, &options)?, baseline(name)?, "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn ignored_revisions() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_read_only("make_blame_repo.sh")?;
+    let reformat = gix::ObjectId::from_hex(std::fs::read(dir.join("reformat.commit"))?.trim())?;
+    let options = Options {
+        ignore_revs: vec![reformat],
+        ..Default::default()
+    };
+    assert_eq!(
+        blame(&repo("repo")?, "reformatted", &options)?,
+        baseline("reformatted-ignore-rev")?
+    );
+    assert_eq!(
+        blame(&repo("with-ignore-revs-file")?, "reformatted", &Options::default())?,
+        baseline("reformatted-ignore-revs-file")?,
+        "`blame.ignoreRevsFile` is used as well"
+    );
+    Ok(())
+}
+
+#[test]
+fn incremental() -> crate::Result {
+    let repo = repo("repo")?;
+    let mut entries = Vec::new();
+    repo.blame_incremental("simple", repo.head_id()?, &Options::default(), |entry| {
+        entries.push(entry);
+        Ok::<_, std::convert::Infallible>(())
+    })?;
+    let dir = gix_testtools::scripted_fixture_read_only("make_blame_repo.sh")?;
+    let reformat = gix::ObjectId::from_hex(std::fs::read(dir.join("reformat.commit"))?.trim())?;
+    assert_eq!(
+        entries.first().map(|entry| entry.commit_id),
+        Some(reformat),
+        "the newest commits are blamed first"
+    );
+    entries.sort_by_key(|entry| entry.range_in_blamed_file.start);
+    let outcome = gix::blame::Outcome {
+        entries,
+        blob: Vec::new(),
+    };
+    assert_eq!(lines(&outcome), baseline("simple")?);
+    Ok(())
+}
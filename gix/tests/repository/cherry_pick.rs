@@ -0,0 +1,137 @@
+use gix::{
+    bstr::ByteSlice,
+    cherry_pick::{Options, Outcome},
+};
+
+fn bare_repo_rw() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let dir = gix_testtools::scripted_fixture_writable("make_cherry_pick_baseline.sh")?;
+    let repo = gix::open_opts(dir.path().join("bare.git"), crate::restricted())?;
+    Ok((repo, dir))
+}
+
+fn id(repo: &gix::Repository, rev: &str) -> crate::Result<gix::ObjectId> {
+    Ok(repo.rev_parse_single(rev)?.detach())
+}
+
+/// Assert that the commit created in `outcome` has the same tree and message as the one `git` created for baseline `name`,
+/// and has `parent` as its only parent.
+fn assert_like_git<'repo>(
+    dir: &gix_testtools::tempfile::TempDir,
+    outcome: Outcome<'repo>,
+    parent: gix::ObjectId,
+    name: &str,
+) -> crate::Result<gix::Commit<'repo>> {
+    let commit = outcome.commit_id().expect("no conflicts").object()?.into_commit();
+    let expected_tree = std::fs::read(dir.path().join(format!("{name}.tree")))?;
+    assert_eq!(commit.tree_id()?.to_string(), expected_tree.trim().to_str_lossy(), "{name}");
+    let expected_message = std::fs::read(dir.path().join(format!("{name}.message")))?;
+    assert_eq!(
+        commit.message_raw()?.trim_end(),
+        expected_message.trim_end().as_bstr(),
+        "{name}"
+    );
+    assert_eq!(commit.parent_ids().map(gix::Id::detach).collect::<Vec<_>>(), [parent]);
+    Ok(commit)
+}
+
+#[test]
+fn cherry_pick_in_bare_repository() -> crate::Result {
+    let (repo, dir) = bare_repo_rw()?;
+    let main = id(&repo, "main")?;
+    let options = Options {
+        record_origin: true,
+        ..Default::default()
+    };
+    let outcome = repo.cherry_pick(id(&repo, "feature~1")?, main, options)?;
+    let commit = assert_like_git(&dir, outcome, main, "cherry-pick")?;
+    let original = repo.rev_parse_single("feature~1")?.object()?.into_commit();
+    assert_eq!(commit.author()?, original.author()?, "the author is kept");
+
+    let outcome = repo.cherry_pick(id(&repo, "feature")?, main, options)?;
+    assert_like_git(&dir, outcome, main, "cherry-pick-trailer")?;
+
+    let head_before = repo.head_id()?;
+    assert_eq!(head_before, main, "references are not changed");
+    Ok(())
+}
+
+#[test]
+fn revert_in_bare_repository() -> crate::Result {
+    let (repo, dir) = bare_repo_rw()?;
+    let main = id(&repo, "main")?;
+    let outcome = repo.revert(main, main, Options::default())?;
+    assert_like_git(&dir, outcome, main, "revert")?;
+    Ok(())
+}
+
+#[test]
+fn mainline() -> crate::Result {
+    let (repo, dir) = bare_repo_rw()?;
+    let merged = id(&repo, "merged")?;
+    let base = id(&repo, "base")?;
+
+    let err = repo
+        .cherry_pick(merged, base, Options::default())
+        .err()
+        .expect("mainline is needed");
+    assert_eq!(
+        err.to_string(),
+        format!("Commit {merged} is a merge with 2 parents, but no mainline was specified")
+    );
+    let err = repo
+        .cherry_pick(
+            id(&repo, "feature")?,
+            base,
+            Options {
+                mainline: Some(1),
+                ..Default::default()
+            },
+        )
+        .err()
+        .expect("not a merge");
+    assert!(matches!(err, gix::cherry_pick::Error::UnexpectedMainline { .. }));
+    let err = repo
+        .cherry_pick(
+            merged,
+            base,
+            Options {
+                mainline: Some(3),
+                ..Default::default()
+            },
+        )
+        .err()
+        .expect("out of bounds");
+    assert_eq!(err.to_string(), format!("Commit {merged} does not have parent 3"));
+
+    let options = Options {
+        mainline: Some(1),
+        ..Default::default()
+    };
+    let outcome = repo.cherry_pick(merged, base, options)?;
+    assert_like_git(&dir, outcome, base, "cherry-pick-mainline")?;
+    let outcome = repo.revert(merged, merged, options)?;
+    assert_like_git(&dir, outcome, merged, "revert-mainline")?;
+    Ok(())
+}
+
+#[test]
+fn conflicts_return_the_index() -> crate::Result {
+    let (repo, _dir) = bare_repo_rw()?;
+    let outcome = repo.cherry_pick(id(&repo, "conflicting")?, id(&repo, "feature")?, Options::default())?;
+    let Outcome::Conflict(merge) = outcome else {
+        panic!("expected a conflict");
+    };
+    assert_eq!(merge.conflicts.len(), 1);
+    assert_eq!(merge.conflicts[0].path, "a");
+    assert_eq!(
+        merge
+            .index
+            .entries()
+            .iter()
+            .filter(|entry| entry.stage() != 0)
+            .map(gix::index::Entry::stage)
+            .collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use gix::config::tree::{Branch, Core, Key};
+
+use crate::util::repo_rw;
+
+#[test]
+fn changes_are_written_back_while_preserving_the_original_content() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_basic_repo.sh")?;
+    let config_path = repo.common_dir().join("config");
+    let mut original = std::fs::read(&config_path)?;
+    original.extend_from_slice(b"# a comment\n[core] # trailing comment\n\tabbrev = 8\n");
+    std::fs::write(&config_path, &original)?;
+
+    let mut config = repo.config_file_mut(gix::config::Source::Local)?;
+    assert_eq!(config.path(), config_path);
+    assert_eq!(
+        config.set_value(&Core::ABBREV, "4")?.as_ref().map(|v| v.as_slice()),
+        Some(&b"8"[..]),
+        "the previous value is returned"
+    );
+    config.set_subsection_value(&Branch::MERGE, "main", "refs/heads/foo")?;
+    assert!(
+        config.set_value(&Core::ABBREV, "invalid").is_err(),
+        "values are validated"
+    );
+    config.commit()?;
+
+    let actual = std::fs::read(&config_path)?;
+    assert_eq!(
+        gix::bstr::BStr::new(&actual),
+        gix::bstr::BString::from(
+            [
+                &original[..original.len() - "8\n".len()],
+                b"4\n[branch \"main\"]\n\tmerge = refs/heads/foo\n"
+            ]
+            .concat()
+        ),
+        "only the value changed, while comments and formatting remain"
+    );
+    assert_eq!(
+        repo.config_snapshot().integer(Core::ABBREV.logical_name().as_str()),
+        None,
+        "the repository instance doesn't see changes made to the file after it was opened"
+    );
+
+    let repo = gix::open_opts(repo.git_dir(), repo.open_options().clone())?;
+    assert_eq!(
+        repo.config_snapshot().integer(Core::ABBREV.logical_name().as_str()),
+        Some(4)
+    );
+    assert_eq!(
+        repo.config_snapshot()
+            .string("branch.main.merge")
+            .expect("set")
+            .as_ref(),
+        "refs/heads/foo"
+    );
+    Ok(())
+}
+
+#[test]
+fn dropping_or_forgetting_discards_all_changes() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_basic_repo.sh")?;
+    let config_path = repo.common_dir().join("config");
+    let original = std::fs::read(&config_path)?;
+    {
+        let mut config = repo.config_file_mut(gix::config::Source::Local)?;
+        config.set_raw_value("core", None, "abbrev", "4")?;
+    }
+    let mut config = repo.config_file_mut(gix::config::Source::Local)?;
+    config.set_raw_value("core", None, "abbrev", "4")?;
+    let config = config.forget();
+    assert_eq!(config.raw_value("core", None, "abbrev")?.as_ref(), "4");
+    assert_eq!(std::fs::read(&config_path)?, original, "nothing was written");
+    Ok(())
+}
+
+#[test]
+fn missing_files_are_created_on_commit() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_basic_repo.sh")?;
+    let mut config = repo.config_file_mut(gix::config::Source::Worktree)?;
+    assert_eq!(config.path(), repo.git_dir().join("config.worktree"));
+    assert!(!config.path().exists());
+    assert_eq!(config.meta().source, gix::config::Source::Worktree);
+
+    config.set_value(&Core::ABBREV, "5")?;
+    config.commit()?;
+    assert_eq!(
+        std::fs::read_to_string(repo.git_dir().join("config.worktree"))?,
+        "[core]\n\tabbrev = 5\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn sources_without_file_are_rejected() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_basic_repo.sh")?;
+    for source in [
+        gix::config::Source::Env,
+        gix::config::Source::Cli,
+        gix::config::Source::Api,
+        gix::config::Source::EnvOverride,
+    ] {
+        assert!(matches!(
+            repo.config_file_mut(source),
+            Err(gix::config::file_mut::open::Error::UnsupportedSource { config_source }) if config_source == source
+        ));
+    }
+    Ok(())
+}
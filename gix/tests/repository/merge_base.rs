@@ -0,0 +1,16 @@
+use crate::util::hex_to_id;
+
+#[test]
+fn between_commits_on_forked_branches() -> crate::Result {
+    let repo = crate::named_repo("make_repo_with_fork_and_dates.sh")?;
+    let c1 = hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03");
+    let b1c1 = repo.rev_parse_single("branch1")?;
+    assert_eq!(repo.merge_base(repo.rev_parse_single("main~1")?, b1c1)?, c1);
+    assert_eq!(
+        repo.merge_base(repo.rev_parse_single("main")?, b1c1)?,
+        b1c1,
+        "a commit merged into another one is their merge-base"
+    );
+    assert_eq!(repo.merge_base(c1, c1)?, c1, "commits are their own merge-base");
+    Ok(())
+}
@@ -1,15 +1,31 @@
 use gix::Repository;
 
+#[cfg(feature = "apply")]
+mod apply;
+#[cfg(feature = "bisect")]
+mod bisect;
+#[cfg(feature = "blame")]
+mod blame;
+#[cfg(feature = "merge")]
+mod cherry_pick;
 mod config;
 #[cfg(feature = "attributes")]
 mod filter;
+#[cfg(feature = "revision")]
+mod merge_base;
 mod object;
 mod open;
 #[cfg(feature = "attributes")]
 mod pathspec;
+#[cfg(feature = "range-diff")]
+mod range_diff;
+#[cfg(feature = "rebase")]
+mod rebase;
 mod reference;
 mod remote;
 mod shallow;
+#[cfg(feature = "stash")]
+mod stash;
 mod state;
 #[cfg(feature = "attributes")]
 mod submodule;
@@ -0,0 +1,127 @@
+use std::path::PathBuf;
+
+use gix::{
+    bstr::{BString, ByteSlice},
+    range_diff::{Options, Status},
+};
+
+fn repo() -> crate::Result<(gix::Repository, PathBuf)> {
+    let dir = gix_testtools::scripted_fixture_read_only("make_range_diff_repo.sh")?;
+    let repo = gix::open_opts(&dir, crate::restricted())?;
+    Ok((repo, dir))
+}
+
+fn range_diff(
+    repo: &gix::Repository,
+    old: &str,
+    new: &str,
+    options: Options,
+) -> crate::Result<gix::range_diff::Outcome> {
+    Ok(repo.range_diff(
+        repo.rev_parse(old)?.into_walk()?,
+        repo.rev_parse(new)?.into_walk()?,
+        &options,
+    )?)
+}
+
+fn write(outcome: &gix::range_diff::Outcome, with_diffs: bool) -> crate::Result<BString> {
+    let mut out = Vec::new();
+    outcome.write_to(&mut out, with_diffs)?;
+    Ok(out.into())
+}
+
+fn baseline(dir: &std::path::Path, name: &str) -> crate::Result<BString> {
+    Ok(std::fs::read(dir.join(name))?.into())
+}
+
+#[test]
+fn output_is_like_git() -> crate::Result {
+    let (repo, dir) = repo()?;
+    let outcome = range_diff(&repo, "main~1..topic-v1", "main..topic-v2", Options::default())?;
+    assert_eq!(write(&outcome, true)?, baseline(&dir, "baseline")?);
+    assert_eq!(write(&outcome, false)?, baseline(&dir, "baseline-no-patch")?);
+    assert_eq!(
+        outcome.pairs.iter().map(|pair| pair.status).collect::<Vec<_>>(),
+        [
+            Status::Unchanged,
+            Status::Changed,
+            Status::Changed,
+            Status::Removed,
+            Status::Unchanged,
+            Status::Added
+        ]
+    );
+    assert_eq!(outcome.old[3].subject, "remove unused file");
+    assert_eq!(outcome.new[4].subject, "move documentation");
+    assert!(
+        outcome.new[4].diff().contains_str(" ## docs.txt => doc/guide.txt ##\n"),
+        "renames are tracked"
+    );
+    assert!(
+        outcome.new[3]
+            .diff()
+            .contains_str(" ## run.sh (mode change 100644 => 100755) ##\n"),
+        "mode changes are shown"
+    );
+    Ok(())
+}
+
+#[test]
+fn creation_factor_controls_pairing() -> crate::Result {
+    let (repo, dir) = repo()?;
+    let outcome = range_diff(
+        &repo,
+        "main~1..topic-v1",
+        "main..topic-v2",
+        Options { creation_factor: 10 },
+    )?;
+    assert_eq!(write(&outcome, true)?, baseline(&dir, "baseline-creation-factor")?);
+    Ok(())
+}
+
+#[test]
+fn symmetric_ranges_like_git() -> crate::Result {
+    let (repo, dir) = repo()?;
+    let (v1, v2) = (repo.rev_parse_single("topic-v1")?, repo.rev_parse_single("topic-v2")?);
+    let outcome = repo.range_diff(
+        repo.rev_walk([v1]).hide([v2]),
+        repo.rev_walk([v2]).hide([v1]),
+        &Options::default(),
+    )?;
+    assert_eq!(write(&outcome, true)?, baseline(&dir, "baseline-symmetric")?);
+    Ok(())
+}
+
+#[test]
+fn empty_ranges() -> crate::Result {
+    let (repo, _dir) = repo()?;
+    let outcome = range_diff(&repo, "main..main", "main..main", Options::default())?;
+    assert!(outcome.pairs.is_empty());
+    assert_eq!(write(&outcome, true)?, "");
+
+    let outcome = range_diff(&repo, "main..main", "main~1..topic-v1", Options::default())?;
+    assert!(outcome.pairs.iter().all(|pair| pair.status == Status::Added));
+    assert_eq!(outcome.pairs.len(), 5);
+    Ok(())
+}
+
+#[test]
+fn patch_ids_like_git() -> crate::Result {
+    let (repo, dir) = repo()?;
+    let expected = std::fs::read(dir.join("patch-ids"))?;
+    let mut count = 0;
+    for line in expected.lines() {
+        let mut tokens = line.split_str(" ");
+        let patch_id = gix::ObjectId::from_hex(tokens.next().expect("patch id"))?;
+        let commit = gix::ObjectId::from_hex(tokens.next().expect("commit"))?;
+        assert_eq!(repo.patch_id(commit)?, Some(patch_id), "{commit}");
+        count += 1;
+    }
+    assert_eq!(count, 10, "all commits of both series were checked");
+    assert_eq!(
+        repo.patch_id(repo.rev_parse_single("topic-v1")?)?,
+        repo.patch_id(repo.rev_parse_single("topic-v2~1")?)?,
+        "the same change in a different place has the same patch id"
+    );
+    Ok(())
+}
@@ -0,0 +1,334 @@
+use std::path::Path;
+
+use gix::{
+    rebase::{ContinueOptions, Options, Outcome, Stop},
+    sequencer::todo::Instruction,
+};
+
+fn repo_rw() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let dir = gix_testtools::scripted_fixture_writable("make_rebase_repo.sh")?;
+    let repo = gix::open_opts(dir.path().join("repo"), crate::restricted())?;
+    Ok((repo, dir))
+}
+
+fn git_command(work_dir: &Path, args: &[&str]) -> std::process::Command {
+    let mut cmd = std::process::Command::new("git");
+    cmd.args(args)
+        .current_dir(work_dir)
+        .env("GIT_COMMITTER_NAME", "committer")
+        .env("GIT_COMMITTER_EMAIL", "committer@example.com");
+    cmd
+}
+
+fn git(work_dir: &Path, args: &[&str]) -> crate::Result<String> {
+    let out = git_command(work_dir, args).output()?;
+    assert!(out.status.success(), "git {args:?} failed: {out:?}");
+    Ok(String::from_utf8(out.stdout)?)
+}
+
+fn id(repo: &gix::Repository, rev: &str) -> crate::Result<gix::ObjectId> {
+    Ok(repo.rev_parse_single(rev)?.detach())
+}
+
+/// Assert that `rev` has the same tree as the baseline `name` created by `git`, and that the commits between `main`
+/// and `rev` have the same subjects.
+fn assert_like_git(dir: &Path, rev: &str, name: &str) -> crate::Result {
+    let work_dir = dir.join("repo");
+    assert_eq!(
+        git(&work_dir, &["rev-parse", &format!("{rev}^{{tree}}")])?,
+        std::fs::read_to_string(dir.join(format!("{name}.tree")))?,
+        "{name}"
+    );
+    assert_eq!(
+        git(&work_dir, &["log", "--format=%s", &format!("main..{rev}")])?,
+        std::fs::read_to_string(dir.join(format!("{name}.subjects")))?,
+        "{name}"
+    );
+    Ok(())
+}
+
+fn assert_clean_and_on_topic(repo: &gix::Repository) -> crate::Result {
+    let work_dir = repo.work_dir().expect("non-bare");
+    assert_eq!(
+        git(work_dir, &["status", "--porcelain"])?,
+        "",
+        "index and worktree match HEAD"
+    );
+    assert_eq!(
+        git(work_dir, &["symbolic-ref", "HEAD"])?,
+        "refs/heads/topic\n",
+        "the branch is checked out again"
+    );
+    assert!(repo.rebase_status()?.is_none(), "the state was removed");
+    Ok(())
+}
+
+fn conflicting_rebase(repo: &gix::Repository) -> crate::Result<Stop> {
+    match repo.rebase(id(repo, "conflicting")?, None, Options::default())? {
+        Outcome::Stopped(stop) => Ok(stop),
+        Outcome::Finished { .. } => unreachable!("the first commit conflicts"),
+    }
+}
+
+#[test]
+fn rebase_like_git() -> crate::Result {
+    let (repo, dir) = repo_rw()?;
+    let todo = repo.rebase_todo(id(&repo, "main")?, &Options::default())?;
+    assert_eq!(
+        todo.to_bstring(),
+        format!(
+            "pick {} change a\npick {} add c\npick {} fixup! change a\n",
+            id(&repo, "topic~2")?,
+            id(&repo, "topic~1")?,
+            id(&repo, "topic")?
+        ),
+        "the oldest commit comes first"
+    );
+
+    let outcome = repo.rebase(id(&repo, "main")?, None, Options::default())?;
+    let Outcome::Finished { head } = outcome else {
+        unreachable!("there are no conflicts")
+    };
+    assert_eq!(head, id(&repo, "topic")?);
+    assert_like_git(dir.path(), "topic", "rebase")?;
+    assert_clean_and_on_topic(&repo)?;
+    assert_eq!(
+        id(&repo, "ORIG_HEAD")?,
+        id(&repo, "topic@{1}")?,
+        "ORIG_HEAD points to the branch before the rebase"
+    );
+    Ok(())
+}
+
+#[test]
+fn autosquash_and_update_refs_like_git() -> crate::Result {
+    let (repo, dir) = repo_rw()?;
+    let options = Options {
+        autosquash: true,
+        update_refs: true,
+        ..Default::default()
+    };
+    let todo = repo.rebase_todo(id(&repo, "main")?, &options)?;
+    assert_eq!(
+        todo.instructions.iter().map(Instruction::name).collect::<Vec<_>>(),
+        ["pick", "fixup", "pick", "update-ref"],
+        "the fixup moves next to its commit, and `mid` is updated"
+    );
+
+    let outcome = repo.rebase(id(&repo, "main")?, None, options)?;
+    assert!(matches!(outcome, Outcome::Finished { .. }));
+    assert_like_git(dir.path(), "topic", "autosquash")?;
+    assert_like_git(dir.path(), "mid", "autosquash-mid")?;
+    assert_eq!(
+        repo.rev_parse_single("topic~1")?
+            .object()?
+            .into_commit()
+            .message_raw()?,
+        "change a\n\nwith a body\n",
+        "the message of a fixup is discarded"
+    );
+    assert_clean_and_on_topic(&repo)?;
+    Ok(())
+}
+
+#[test]
+fn conflicts_can_be_resolved_and_continued() -> crate::Result {
+    let (repo, _dir) = repo_rw()?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    let Stop::Conflict { commit, conflicts } = conflicting_rebase(&repo)? else {
+        unreachable!("conflicts stop the rebase")
+    };
+    assert_eq!(commit, id(&repo, "topic~2")?);
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].path, "a");
+    assert!(std::fs::read_to_string(work_dir.join("a"))?.contains("<<<<<<<"));
+
+    let state = repo.rebase_status()?.expect("in progress");
+    assert_eq!(state.stopped_at, Some(commit));
+    assert_eq!(state.head_name.expect("on branch").as_bstr(), "refs/heads/topic");
+    assert_eq!(
+        git(&work_dir, &["status", "--porcelain"])?,
+        "UU a\n",
+        "git sees the conflict"
+    );
+    assert!(
+        matches!(
+            repo.rebase_continue(ContinueOptions::default()),
+            Err(gix::rebase::Error::Unmerged { .. })
+        ),
+        "conflicts must be resolved first"
+    );
+
+    std::fs::write(work_dir.join("a"), "1\nresolved\n3\n4\n5\n6\n7\n8\n9\n10\n")?;
+    git(&work_dir, &["add", "a"])?;
+    let outcome = repo.rebase_continue(ContinueOptions::default())?;
+    assert!(matches!(outcome, Outcome::Finished { .. }));
+    assert_eq!(
+        git(&work_dir, &["log", "--format=%s", "conflicting..topic"])?,
+        "fixup! change a\nadd c\nchange a\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(work_dir.join("a"))?,
+        "1\nresolved\n3\n4\n5\n6\n7\n8\nfixup\n10\n"
+    );
+    let resolved = repo.rev_parse_single("topic~2")?.object()?.into_commit();
+    assert_eq!(resolved.message_raw()?, "change a\n\nwith a body\n");
+    assert_eq!(resolved.author()?.name, "author", "the original author is kept");
+    assert_clean_and_on_topic(&repo)?;
+    Ok(())
+}
+
+#[test]
+fn stopped_rebases_can_be_continued_by_git() -> crate::Result {
+    let (repo, _dir) = repo_rw()?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    conflicting_rebase(&repo)?;
+
+    std::fs::write(work_dir.join("a"), "1\nresolved\n3\n4\n5\n6\n7\n8\n9\n10\n")?;
+    git(&work_dir, &["add", "a"])?;
+    git(&work_dir, &["-c", "core.editor=true", "rebase", "--continue"])?;
+    assert_eq!(
+        git(&work_dir, &["log", "--format=%s", "conflicting..topic"])?,
+        "fixup! change a\nadd c\nchange a\n"
+    );
+    assert_clean_and_on_topic(&repo)?;
+    Ok(())
+}
+
+#[test]
+fn skip_drops_the_conflicting_commit() -> crate::Result {
+    let (repo, _dir) = repo_rw()?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    conflicting_rebase(&repo)?;
+
+    let outcome = repo.rebase_skip(Default::default())?;
+    assert!(matches!(outcome, Outcome::Finished { .. }));
+    assert_eq!(
+        git(&work_dir, &["log", "--format=%s", "conflicting..topic"])?,
+        "fixup! change a\nadd c\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(work_dir.join("a"))?,
+        "1\nconflicting\n3\n4\n5\n6\n7\n8\nfixup\n10\n",
+        "the conflict markers are gone"
+    );
+    assert_clean_and_on_topic(&repo)?;
+    Ok(())
+}
+
+#[test]
+fn abort_restores_the_branch() -> crate::Result {
+    let (repo, _dir) = repo_rw()?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    let topic = id(&repo, "topic")?;
+    conflicting_rebase(&repo)?;
+    assert!(matches!(
+        repo.rebase(id(&repo, "main")?, None, Options::default()),
+        Err(gix::rebase::Error::InProgress)
+    ));
+
+    repo.rebase_abort()?;
+    assert_eq!(id(&repo, "HEAD")?, topic);
+    assert_eq!(
+        std::fs::read_to_string(work_dir.join("a"))?,
+        "1\ntopic\n3\n4\n5\n6\n7\n8\nfixup\n10\n"
+    );
+    assert_clean_and_on_topic(&repo)?;
+    assert!(matches!(repo.rebase_abort(), Err(gix::rebase::Error::NotInProgress)));
+    Ok(())
+}
+
+#[test]
+fn edit_reword_exec_and_break() -> crate::Result {
+    let (repo, _dir) = repo_rw()?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    let main = id(&repo, "main")?;
+    let mut todo = repo.rebase_todo(main, &Options::default())?;
+    let to_edit = todo.instructions[0].commit().expect("pick").to_owned();
+    let to_reword = todo.instructions[1].commit().expect("pick").to_owned();
+    todo.instructions[0] = Instruction::Edit {
+        commit: to_edit,
+        summary: "change a".into(),
+    };
+    todo.instructions[1] = Instruction::Reword {
+        commit: to_reword,
+        summary: "add c".into(),
+    };
+    todo.instructions.insert(
+        2,
+        Instruction::Exec {
+            command: "echo exec >untracked".into(),
+        },
+    );
+    todo.instructions.insert(3, Instruction::Break);
+    todo.instructions.insert(
+        4,
+        Instruction::Exec {
+            command: "exit 42".into(),
+        },
+    );
+
+    let outcome = repo.rebase(main, Some(todo), Options::default())?;
+    assert!(matches!(outcome, Outcome::Stopped(Stop::Edit { commit }) if commit == id(&repo, "topic~2")?));
+    std::fs::write(work_dir.join("b"), "edited\n")?;
+    git(&work_dir, &["add", "b"])?;
+    let outcome = repo.rebase_continue(ContinueOptions::default())?;
+    assert!(matches!(outcome, Outcome::Stopped(Stop::Reword { .. })));
+    assert_eq!(
+        git(&work_dir, &["show", "HEAD~1:b"])?,
+        "edited\n",
+        "staged changes were amended to the edited commit"
+    );
+
+    let outcome = repo.rebase_continue(ContinueOptions {
+        message: Some("reworded\n".into()),
+        ..Default::default()
+    })?;
+    assert!(matches!(outcome, Outcome::Stopped(Stop::Break)));
+    assert_eq!(std::fs::read_to_string(work_dir.join("untracked"))?, "exec\n");
+    let state = repo.rebase_status()?.expect("in progress");
+    assert_eq!(
+        state
+            .todo
+            .instructions
+            .iter()
+            .map(Instruction::name)
+            .collect::<Vec<_>>(),
+        ["exec", "pick"]
+    );
+
+    let outcome = repo.rebase_continue(ContinueOptions::default())?;
+    let Outcome::Stopped(Stop::Exec { command, status }) = outcome else {
+        unreachable!("the command fails")
+    };
+    assert_eq!(command, "exit 42");
+    assert_eq!(status.code(), Some(42));
+
+    let outcome = repo.rebase_continue(ContinueOptions::default())?;
+    assert!(matches!(outcome, Outcome::Finished { .. }));
+    assert_eq!(
+        git(&work_dir, &["log", "--format=%s", "main..topic"])?,
+        "fixup! change a\nreworded\nchange a\n"
+    );
+    std::fs::remove_file(work_dir.join("untracked"))?;
+    assert_clean_and_on_topic(&repo)?;
+    Ok(())
+}
+
+#[test]
+fn status_of_rebase_started_by_git() -> crate::Result {
+    let (repo, _dir) = repo_rw()?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    assert!(repo.rebase_status()?.is_none());
+    let out = git_command(&work_dir, &["rebase", "--merge", "conflicting"]).output()?;
+    assert!(!out.status.success(), "the rebase stops due to conflicts");
+
+    let state = repo.rebase_status()?.expect("in progress");
+    assert_eq!(state.backend, gix::rebase::Backend::Merge);
+    assert_eq!(state.onto, id(&repo, "conflicting")?);
+    assert_eq!(state.stopped_at, Some(id(&repo, "topic~2")?));
+
+    repo.rebase_abort()?;
+    assert_clean_and_on_topic(&repo)?;
+    Ok(())
+}
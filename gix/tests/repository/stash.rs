@@ -0,0 +1,212 @@
+use std::path::Path;
+
+use gix::stash::{ApplyOptions, SaveOptions};
+
+fn repo_rw(name: &str) -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let dir = gix_testtools::scripted_fixture_writable("make_stash_repo.sh")?;
+    let repo = gix::open_opts(dir.path().join(name), crate::restricted())?;
+    Ok((repo, dir))
+}
+
+fn git(work_dir: &Path, args: &[&str]) -> crate::Result<String> {
+    let out = std::process::Command::new("git")
+        .args(args)
+        .current_dir(work_dir)
+        .output()?;
+    assert!(out.status.success(), "git {args:?} failed: {:?}", out);
+    Ok(String::from_utf8(out.stdout)?)
+}
+
+fn read(dir: &Path, name: &str) -> crate::Result<String> {
+    Ok(std::fs::read_to_string(dir.join(name))?)
+}
+
+fn tree_of(repo: &gix::Repository, rev: &str) -> crate::Result<String> {
+    Ok(repo.rev_parse_single(rev)?.object()?.peel_to_tree()?.id.to_string())
+}
+
+fn untracked_options() -> SaveOptions {
+    SaveOptions {
+        include_untracked: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn save_and_apply_like_git() -> crate::Result {
+    let (repo, tmp) = repo_rw("repo")?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    let stash_id = repo.stash_save(untracked_options())?.expect("there are changes");
+
+    let stash = stash_id.to_string();
+    assert_eq!(
+        [
+            tree_of(&repo, &stash)?,
+            tree_of(&repo, &format!("{stash}^2"))?,
+            tree_of(&repo, &format!("{stash}^3"))?,
+        ]
+        .join("\n")
+            + "\n",
+        read(tmp.path(), "stash.trees")?,
+        "the worktree, the index and untracked files are stored just like git does"
+    );
+    assert_eq!(
+        git(&work_dir, &["stash", "list"])?,
+        read(tmp.path(), "stash.list")?,
+        "git sees the stash in its reflog"
+    );
+    assert_eq!(git(&work_dir, &["status", "--porcelain"])?, "", "everything was reset");
+    assert!(
+        work_dir.join("file.ignored").is_file(),
+        "ignored files are left alone unless they are included"
+    );
+    assert!(
+        repo.stash_save(untracked_options())?.is_none(),
+        "there is nothing left to stash"
+    );
+
+    let outcome = repo.stash_apply(
+        0,
+        ApplyOptions {
+            index: true,
+            ..Default::default()
+        },
+    )?;
+    assert!(outcome.is_clean());
+    assert_eq!(
+        git(&work_dir, &["status", "--porcelain"])?,
+        read(tmp.path(), "status.before")?,
+        "the worktree and the index are restored"
+    );
+    assert_eq!(repo.stash_list()?.len(), 1, "applying keeps the stash");
+    Ok(())
+}
+
+#[test]
+fn apply_without_index_unstages_changes() -> crate::Result {
+    let (repo, _tmp) = repo_rw("repo")?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    repo.stash_save(SaveOptions::default())?.expect("there are changes");
+    assert_eq!(
+        git(&work_dir, &["status", "--porcelain"])?,
+        "?? dir/untracked\n?? untracked\n",
+        "untracked files are kept by default"
+    );
+
+    let outcome = repo.stash_pop(0, ApplyOptions::default())?;
+    assert!(outcome.is_clean());
+    assert_eq!(
+        git(&work_dir, &["status", "--porcelain"])?,
+        " M a\n M b\n?? dir/untracked\n?? untracked\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(work_dir.join("a"))?,
+        "1\nstaged\n3\n4\n5\n6\n7\n8\nunstaged\n10\n"
+    );
+    assert!(repo.stash_list()?.is_empty(), "popping drops the stash");
+    assert!(
+        repo.try_find_reference("refs/stash")?.is_none(),
+        "the reference is removed with the last stash"
+    );
+    Ok(())
+}
+
+#[test]
+fn keep_index() -> crate::Result {
+    let (repo, _tmp) = repo_rw("repo")?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    repo.stash_save(SaveOptions {
+        keep_index: true,
+        message: Some("custom".into()),
+        ..Default::default()
+    })?
+    .expect("there are changes");
+    assert_eq!(
+        git(&work_dir, &["status", "--porcelain"])?,
+        "M  a\n?? dir/untracked\n?? untracked\n"
+    );
+    assert_eq!(git(&work_dir, &["stash", "list"])?, "stash@{0}: On main: custom\n");
+    Ok(())
+}
+
+#[test]
+fn list_and_drop() -> crate::Result {
+    let (repo, _tmp) = repo_rw("repo")?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    let first = repo.stash_save(SaveOptions::default())?.expect("changes").detach();
+    std::fs::write(work_dir.join("b"), "second\n")?;
+    let second = repo
+        .stash_save(SaveOptions {
+            message: Some("second".into()),
+            ..Default::default()
+        })?
+        .expect("changes")
+        .detach();
+    std::fs::write(work_dir.join("b"), "third\n")?;
+    let third = repo.stash_save(SaveOptions::default())?.expect("changes").detach();
+    assert_eq!(
+        repo.stash_list()?.iter().map(|stash| stash.id).collect::<Vec<_>>(),
+        [third, second, first],
+        "the most recent stash comes first"
+    );
+
+    assert_eq!(repo.stash_drop(1)?, second);
+    assert_eq!(
+        git(&work_dir, &["stash", "list", "--format=%H %gs"])?,
+        format!(
+            "{third} WIP on main: {short} initial\n{first} WIP on main: {short} initial\n",
+            short = &repo.head_id()?.shorten()?.to_string()
+        ),
+        "git sees the same reflog"
+    );
+    assert_eq!(repo.stash_drop(0)?, third);
+    assert_eq!(
+        repo.find_reference("refs/stash")?.id(),
+        first,
+        "the reference points to the most recent stash"
+    );
+    assert_eq!(git(&work_dir, &["rev-parse", "stash@{0}"])?.trim(), first.to_string());
+
+    let err = repo.stash_drop(1).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "There is no stash at index 1, as there are only 1 stashes"
+    );
+    Ok(())
+}
+
+#[test]
+fn apply_with_conflicts() -> crate::Result {
+    let (repo, _tmp) = repo_rw("conflict")?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    let outcome = repo.stash_pop(0, ApplyOptions::default())?;
+    assert_eq!(outcome.conflicts.len(), 1);
+    assert_eq!(outcome.conflicts[0].path, "a");
+    assert_eq!(git(&work_dir, &["status", "--porcelain"])?, "UU a\n");
+    assert_eq!(
+        std::fs::read_to_string(work_dir.join("a"))?,
+        "1\n2\n3\n4\n<<<<<<< Updated upstream\ncommitted\n=======\nstashed\n>>>>>>> Stashed changes\n6\n7\n8\n9\n10\n"
+    );
+    assert_eq!(repo.stash_list()?.len(), 1, "the stash is kept if there were conflicts");
+
+    let err = repo.stash_save(SaveOptions::default()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Cannot save or apply stashes while the index has unmerged paths, the first being 'a'"
+    );
+    Ok(())
+}
+
+#[test]
+fn apply_refuses_to_overwrite_local_changes() -> crate::Result {
+    let (repo, _tmp) = repo_rw("conflict")?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    std::fs::write(work_dir.join("a"), "local\n")?;
+    let err = repo.stash_apply(0, ApplyOptions::default()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The local changes to [\"a\"] would be overwritten by applying the stash"
+    );
+    assert_eq!(std::fs::read_to_string(work_dir.join("a"))?, "local\n");
+    Ok(())
+}
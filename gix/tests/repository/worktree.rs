@@ -305,6 +305,14 @@ fn run_assertions(main_repo: gix::Repository, should_be_bare: bool) {
             expected.prunable.is_none(),
             "in our case prunable repos have no worktree base"
         );
+        assert_eq!(
+            actual.prunable(),
+            expected
+                .prunable
+                .is_some()
+                .then_some(gix::worktree::proxy::Prunable::MissingWorktree),
+            "we detect the same prunable worktrees as git"
+        );
 
         let repo = if base.is_dir() {
             let repo = actual.into_repo().unwrap();
@@ -336,3 +344,766 @@ fn run_assertions(main_repo: gix::Repository, should_be_bare: bool) {
         );
     }
 }
+
+#[cfg(feature = "status")]
+mod add {
+    use gix::bstr::{BStr, ByteSlice};
+
+    fn entries(index: &gix::index::State) -> Vec<(String, gix::index::entry::Mode, gix_hash::ObjectId)> {
+        index
+            .entries()
+            .iter()
+            .map(|e| (e.path(index).to_str_lossy().into_owned(), e.mode, e.id))
+            .collect()
+    }
+
+    /// The script is executed in place as symlinks can't be copied.
+    fn repo_rw() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+        let dir = gix_testtools::scripted_fixture_writable_with_args(
+            "make_add_repo.sh",
+            None::<String>,
+            gix_testtools::Creation::ExecuteScript,
+        )?;
+        let repo = gix::open_opts(dir.path(), crate::restricted())?;
+        Ok((repo, dir))
+    }
+
+    fn expected_index(repo: &gix::Repository, name: &str) -> crate::Result<gix::index::File> {
+        Ok(gix::index::File::at(
+            repo.git_dir().join(name),
+            repo.object_hash(),
+            false,
+            Default::default(),
+        )?)
+    }
+
+    #[test]
+    fn all_changes_match_git() -> crate::Result {
+        let (repo, _tmp) = repo_rw()?;
+        let mut index = repo.open_index()?;
+        let outcome = repo
+            .worktree()
+            .expect("non-bare")
+            .add(&mut index, None::<&BStr>, Default::default())?;
+        assert_eq!(
+            outcome,
+            gix::worktree::add::Outcome {
+                added: 5,
+                modified: 2,
+                removed: 2,
+            },
+            "crlf.txt, ident.c, link, new-dir/file and untracked were added, while ignored files were skipped"
+        );
+        let expected = expected_index(&repo, "expected-index")?;
+        assert_eq!(entries(&index), entries(&expected), "the filter pipeline is applied");
+        assert!(
+            index.tree().map_or(true, |tree| tree.num_entries.is_none()),
+            "the root tree can't be valid anymore"
+        );
+
+        index.write(repo.index_write_options()?)?;
+        let index = repo.open_index()?;
+        assert_eq!(
+            entries(&index),
+            entries(&expected),
+            "the index can be written and read back"
+        );
+
+        let outcome = repo
+            .worktree()
+            .expect("non-bare")
+            .add(&mut index.clone(), None::<&BStr>, Default::default())?;
+        assert_eq!(outcome, Default::default(), "nothing changes when adding again");
+        Ok(())
+    }
+
+    #[test]
+    fn update_only_does_not_add_untracked_files() -> crate::Result {
+        let (repo, _tmp) = repo_rw()?;
+        let mut index = repo.open_index()?;
+        let outcome = repo.worktree().expect("non-bare").add(
+            &mut index,
+            None::<&BStr>,
+            gix::worktree::add::Options {
+                update_only: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            outcome,
+            gix::worktree::add::Outcome {
+                added: 0,
+                modified: 2,
+                removed: 2,
+            }
+        );
+        let expected = expected_index(&repo, "expected-update-index")?;
+        assert_eq!(entries(&index), entries(&expected));
+        Ok(())
+    }
+
+    #[test]
+    fn pathspecs_limit_changes_and_force_adds_ignored_files() -> crate::Result {
+        let (repo, _tmp) = repo_rw()?;
+        let mut index = repo.open_index()?;
+        let outcome = repo.worktree().expect("non-bare").add(
+            &mut index,
+            ["ignored-dir", "dir"],
+            gix::worktree::add::Options {
+                force: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            outcome,
+            gix::worktree::add::Outcome {
+                added: 1,
+                modified: 0,
+                removed: 1,
+            }
+        );
+        let paths: Vec<_> = entries(&index).into_iter().map(|t| t.0).collect();
+        assert_eq!(
+            paths,
+            [
+                ".gitattributes",
+                ".gitignore",
+                "deleted",
+                "dir/nested",
+                "executable",
+                "ignored-dir/file",
+                "modified",
+                "unchanged"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn intent_to_add_entries_lose_their_extended_flags() -> crate::Result {
+        use gix::index::entry::Flags;
+        let (repo, _tmp) = repo_rw()?;
+        let mut index = repo.open_index()?;
+        index
+            .entry_mut_by_path_and_stage("modified".into(), 0)
+            .expect("tracked")
+            .flags
+            .insert(Flags::INTENT_TO_ADD | Flags::EXTENDED);
+        repo.worktree()
+            .expect("non-bare")
+            .add(&mut index, ["modified"], Default::default())?;
+        let flags = index.entry_by_path("modified".into()).expect("still tracked").flags;
+        assert!(
+            !flags.intersects(Flags::INTENT_TO_ADD | Flags::EXTENDED),
+            "no extended flag remains that would need to be stored"
+        );
+        let (version, _checksum) = index.write_to(std::io::sink(), Default::default())?;
+        assert_eq!(
+            version,
+            gix::index::Version::V2,
+            "hence the index doesn't need V3 anymore"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn remove_cached() -> crate::Result {
+        let (repo, _tmp) = repo_rw()?;
+        let mut index = repo.open_index()?;
+        let worktree = repo.worktree().expect("non-bare");
+        let err = worktree.remove_cached(&mut index, None::<&str>).unwrap_err();
+        assert!(
+            matches!(err, gix::worktree::remove::Error::MissingPathspec),
+            "an empty pathspec would remove everything"
+        );
+        let outcome = worktree.remove_cached(&mut index, ["dir", "modified"])?;
+        assert_eq!(outcome.removed, 3);
+        let paths: Vec<_> = entries(&index).into_iter().map(|t| t.0).collect();
+        assert_eq!(
+            paths,
+            [".gitattributes", ".gitignore", "deleted", "executable", "unchanged"]
+        );
+        assert!(
+            repo.work_dir().expect("non-bare").join("modified").is_file(),
+            "the worktree isn't touched"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(feature = "status")]
+mod is_dirty {
+    use crate::repo_rw;
+
+    #[test]
+    fn untracked_files_count_unless_they_are_excluded() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_commit_describe_multiple_tags.sh")?;
+        let work_dir = repo.work_dir().expect("non-bare").to_owned();
+        let is_dirty = || repo.worktree().expect("non-bare").is_dirty();
+        assert!(!is_dirty()?, "the worktree is empty");
+
+        std::fs::write(repo.git_dir().join("info").join("exclude"), "ignored*\n")?;
+        std::fs::create_dir_all(work_dir.join("ignored-dir"))?;
+        std::fs::write(work_dir.join("ignored-dir").join("file"), "content")?;
+        std::fs::write(work_dir.join("ignored-file"), "content")?;
+        std::fs::create_dir_all(work_dir.join("dir").join("empty"))?;
+        assert!(
+            !is_dirty()?,
+            "excluded files and directories without files don't make the worktree dirty"
+        );
+
+        std::fs::write(work_dir.join("dir").join("empty").join("untracked"), "content")?;
+        assert!(is_dirty()?, "untracked files in untracked directories are found");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn files_are_only_checked_if_the_filesystem_monitor_reports_them() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_fs_monitor_repo.sh")?;
+        let work_dir = repo.work_dir().expect("non-bare").to_owned();
+        let is_dirty = || repo.worktree().expect("non-bare").is_dirty();
+
+        std::fs::write(work_dir.join("file"), "changed\n")?;
+        assert!(
+            !is_dirty()?,
+            "like git, changes that the filesystem monitor doesn't report go unnoticed"
+        );
+
+        std::fs::write(
+            repo.git_dir().join("fsmonitor-hook"),
+            "#!/bin/sh\nprintf 'token\\0file\\0'\n",
+        )?;
+        assert!(is_dirty()?, "reported files are checked");
+        Ok(())
+    }
+}
+
+#[cfg(feature = "worktree-mutation")]
+mod sparse_checkout {
+    use std::{path::Path, sync::atomic::AtomicBool};
+
+    use gix::bstr::ByteSlice;
+
+    fn repo_rw() -> crate::Result<gix_testtools::tempfile::TempDir> {
+        gix_testtools::scripted_fixture_writable("make_sparse_checkout_repo.sh")
+    }
+
+    fn open(path: &Path) -> crate::Result<gix::Repository> {
+        Ok(gix::open_opts(path, crate::restricted())?)
+    }
+
+    /// Paths of all index entries along with their *skip-worktree* flag.
+    fn skip_worktree(repo: &gix::Repository) -> crate::Result<Vec<(String, bool)>> {
+        let index = repo.open_index()?;
+        Ok(index
+            .entries()
+            .iter()
+            .map(|e| {
+                (
+                    e.path(&index).to_str_lossy().into_owned(),
+                    e.flags.contains(gix::index::entry::Flags::SKIP_WORKTREE),
+                )
+            })
+            .collect())
+    }
+
+    fn worktree_files(repo: &gix::Repository) -> crate::Result<Vec<String>> {
+        fn collect(root: &Path, dir: &Path, out: &mut Vec<String>) -> std::io::Result<()> {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    if path.file_name() != Some(".git".as_ref()) {
+                        collect(root, &path, out)?;
+                    }
+                } else {
+                    let rela_path = path.strip_prefix(root).expect("within worktree");
+                    out.push(gix::path::into_bstr(rela_path).to_str_lossy().replace('\\', "/"));
+                }
+            }
+            Ok(())
+        }
+        let root = repo.work_dir().expect("non-bare");
+        let mut files = Vec::new();
+        collect(root, root, &mut files)?;
+        files.sort();
+        Ok(files)
+    }
+
+    fn assert_matches_git(actual: &gix::Repository, expected: &gix::Repository) -> crate::Result {
+        assert_eq!(skip_worktree(actual)?, skip_worktree(expected)?);
+        assert_eq!(worktree_files(actual)?, worktree_files(expected)?);
+        assert_eq!(
+            std::fs::read(actual.git_dir().join("info/sparse-checkout"))?.as_bstr(),
+            std::fs::read(expected.git_dir().join("info/sparse-checkout"))?.as_bstr(),
+            "patterns are written just like git does"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn set_in_cone_mode() -> crate::Result {
+        let tmp = repo_rw()?;
+        let repo = open(&tmp.path().join("cone"))?;
+        let worktree = repo.worktree().expect("non-bare");
+        assert!(worktree.sparse_checkout_patterns()?.is_none(), "not yet enabled");
+
+        let outcome = worktree.sparse_checkout_set(["a/b", "d/e"], Default::default(), &AtomicBool::default())?;
+        assert_eq!(outcome.entries_skipped, 1);
+        assert_eq!(outcome.files_removed, 1);
+        assert_eq!(outcome.entries_included, 0);
+
+        let repo = open(&tmp.path().join("cone"))?;
+        assert_matches_git(&repo, &open(&tmp.path().join("cone-expected"))?)?;
+        let patterns = repo
+            .worktree()
+            .expect("non-bare")
+            .sparse_checkout_patterns()?
+            .expect("enabled");
+        let gix::worktree::sparse_checkout::Patterns::Cone(cone) = patterns else {
+            unreachable!("cone mode is configured")
+        };
+        assert_eq!(cone.directories().collect::<Vec<_>>(), ["a/b", "d/e"]);
+        Ok(())
+    }
+
+    #[test]
+    fn add_in_cone_mode_and_disable() -> crate::Result {
+        let tmp = repo_rw()?;
+        let path = tmp.path().join("cone");
+        let should_interrupt = AtomicBool::default();
+        let repo = open(&path)?;
+        assert!(matches!(
+            repo.worktree()
+                .expect("non-bare")
+                .sparse_checkout_add(Some("a/other"), &should_interrupt),
+            Err(gix::worktree::sparse_checkout::Error::Disabled)
+        ));
+        repo.worktree()
+            .expect("non-bare")
+            .sparse_checkout_set(Some("a/b"), Default::default(), &should_interrupt)?;
+        assert!(
+            !path.join("a/other/file").exists() && !path.join("a/other").exists(),
+            "empty directories are removed as well"
+        );
+
+        let repo = open(&path)?;
+        let outcome = repo
+            .worktree()
+            .expect("non-bare")
+            .sparse_checkout_add(Some("a/other"), &should_interrupt)?;
+        assert_eq!(outcome.entries_included, 1);
+        assert!(path.join("a/other/file").is_file());
+        assert_matches_git(&repo, &open(&tmp.path().join("cone-added-expected"))?)?;
+
+        let outcome = repo
+            .worktree()
+            .expect("non-bare")
+            .sparse_checkout_reapply(&should_interrupt)?;
+        assert_eq!(
+            (outcome.entries_included, outcome.entries_skipped),
+            (0, 0),
+            "nothing changes"
+        );
+
+        let outcome = repo
+            .worktree()
+            .expect("non-bare")
+            .sparse_checkout_disable(&should_interrupt)?;
+        assert_eq!(outcome.entries_included, 3);
+        let repo = open(&path)?;
+        assert!(repo.worktree().expect("non-bare").sparse_checkout_patterns()?.is_none());
+        assert!(skip_worktree(&repo)?.iter().all(|(_, skipped)| !skipped));
+        assert_eq!(worktree_files(&repo)?.len(), 8, "all files are checked out again");
+        Ok(())
+    }
+
+    #[test]
+    fn set_in_non_cone_mode() -> crate::Result {
+        let tmp = repo_rw()?;
+        let repo = open(&tmp.path().join("non-cone"))?;
+        let outcome = repo.worktree().expect("non-bare").sparse_checkout_set(
+            ["/a/b/", "*.txt", "!d/e/"],
+            gix::worktree::sparse_checkout::Options { cone: false },
+            &AtomicBool::default(),
+        )?;
+        assert_eq!(outcome.entries_skipped, 5);
+
+        let repo = open(&tmp.path().join("non-cone"))?;
+        assert!(matches!(
+            repo.worktree().expect("non-bare").sparse_checkout_patterns()?,
+            Some(gix::worktree::sparse_checkout::Patterns::NonCone(_))
+        ));
+        assert_matches_git(&repo, &open(&tmp.path().join("non-cone-expected"))?)?;
+        Ok(())
+    }
+
+    #[test]
+    fn sparse_index_is_written_in_cone_mode_if_configured() -> crate::Result {
+        let tmp = repo_rw()?;
+        let path = tmp.path().join("sparse-index");
+        let should_interrupt = AtomicBool::default();
+        let repo = open(&path)?;
+        repo.worktree()
+            .expect("non-bare")
+            .sparse_checkout_set(Some("a/b"), Default::default(), &should_interrupt)?;
+
+        let index = open(&path)?.open_index()?;
+        let expected = open(&tmp.path().join("sparse-index-expected"))?.open_index()?;
+        assert!(index.is_sparse());
+        let entries = |index: &gix::index::File| {
+            index
+                .entries()
+                .iter()
+                .map(|e| (e.path(index).to_owned(), e.mode, e.id, e.flags))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            entries(&index),
+            entries(&expected),
+            "sparse directories are the same as the ones of git"
+        );
+
+        let repo = open(&path)?;
+        let outcome = repo
+            .worktree()
+            .expect("non-bare")
+            .sparse_checkout_disable(&should_interrupt)?;
+        assert_eq!(outcome.entries_included, 4, "sparse directories are expanded first");
+        let repo = open(&path)?;
+        assert!(
+            !repo.open_index()?.is_sparse(),
+            "no sparse index without sparse checkout"
+        );
+        assert_eq!(worktree_files(&repo)?.len(), 8);
+        Ok(())
+    }
+
+    #[test]
+    fn sparse_directories_are_not_reported_as_changes() -> crate::Result {
+        let tmp = repo_rw()?;
+        let repo = open(&tmp.path().join("sparse-index-expected"))?;
+        assert!(repo.open_index()?.is_sparse());
+        let worktree = repo.worktree().expect("non-bare");
+        assert!(
+            !worktree.has_tracked_changes()?,
+            "the index is expanded to compare it with the tree of HEAD"
+        );
+        assert!(!worktree.is_dirty()?, "files in sparse directories aren't expected on disk");
+
+        std::fs::write(tmp.path().join("sparse-index-expected/a/b/file"), "change")?;
+        assert!(worktree.has_tracked_changes()?);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "stash")]
+    fn sparse_directories_are_not_checked_out_when_updating_the_worktree() -> crate::Result {
+        let tmp = repo_rw()?;
+        let repo = open(&tmp.path().join("sparse-index-expected"))?;
+        let files = worktree_files(&repo)?;
+        assert_eq!(files, ["a/b/c/file", "a/b/file", "a/file", "root-file"]);
+
+        std::fs::write(tmp.path().join("sparse-index-expected/a/b/file"), "change")?;
+        repo.stash_save(Default::default())?.expect("there are changes");
+        assert_eq!(
+            worktree_files(&repo)?,
+            files,
+            "only the stashed file is checked out, files in sparse directories stay absent"
+        );
+        assert_eq!(
+            std::fs::read(tmp.path().join("sparse-index-expected/a/b/file"))?.as_bstr(),
+            "a/b/file\n"
+        );
+        let skipped: Vec<_> = skip_worktree(&repo)?
+            .into_iter()
+            .filter_map(|(path, skipped)| skipped.then_some(path))
+            .collect();
+        assert_eq!(
+            skipped,
+            ["a/other/file", "d/e/file", "d/e/file.txt", "d/file"],
+            "entries of sparse directories are kept out of the worktree"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn modified_files_are_kept() -> crate::Result {
+        let tmp = repo_rw()?;
+        let path = tmp.path().join("modified");
+        let repo = open(&path)?;
+        let outcome = repo.worktree().expect("non-bare").sparse_checkout_set(
+            Some("a"),
+            Default::default(),
+            &AtomicBool::default(),
+        )?;
+        assert_eq!(outcome.files_kept, ["d/file"]);
+        assert_eq!(outcome.entries_skipped, 2);
+        assert!(path.join("d/file").is_file());
+        assert!(!path.join("d/e").exists());
+
+        let repo = open(&path)?;
+        let skipped: Vec<_> = skip_worktree(&repo)?
+            .into_iter()
+            .filter_map(|(path, skipped)| skipped.then_some(path))
+            .collect();
+        assert_eq!(skipped, ["d/e/file", "d/e/file.txt"], "modified files aren't skipped");
+        Ok(())
+    }
+}
+
+#[cfg(feature = "worktree-mutation")]
+mod linked {
+    use std::{path::Path, sync::atomic::AtomicBool};
+
+    use gix::{
+        repository::worktree_add::{Error, Target},
+        worktree::proxy::Prunable,
+    };
+
+    fn repo_rw() -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+        let tmp = gix_testtools::scripted_fixture_writable_with_args(
+            "make_worktree_repo.sh",
+            None::<String>,
+            gix_testtools::Creation::ExecuteScript,
+        )?;
+        let repo = gix::open_opts(tmp.path().join("repo"), crate::restricted())?;
+        Ok((repo, tmp))
+    }
+
+    fn proxy<'repo>(repo: &'repo gix::Repository, id: &str) -> gix::worktree::Proxy<'repo> {
+        repo.worktrees()
+            .unwrap()
+            .into_iter()
+            .find(|proxy| proxy.id() == id)
+            .expect("worktree exists")
+    }
+
+    fn add(repo: &gix::Repository, path: &Path, target: Target) -> Result<gix::Repository, Error> {
+        repo.worktree_add(path, target, gix::progress::Discard, &AtomicBool::default())
+    }
+
+    fn git_status(work_dir: &Path) -> crate::Result<String> {
+        let out = std::process::Command::new("git")
+            .args(["status", "--porcelain"])
+            .current_dir(work_dir)
+            .output()?;
+        assert!(out.status.success(), "git can use the worktree");
+        Ok(String::from_utf8(out.stdout)?)
+    }
+
+    #[test]
+    fn add_with_new_branch_or_detached_head() -> crate::Result {
+        let (repo, tmp) = repo_rw()?;
+        let head_id = repo.head_id()?.detach();
+        let path = tmp.path().join("new");
+        let wt = add(
+            &repo,
+            &path,
+            Target::NewBranch {
+                name: "refs/heads/new".try_into()?,
+                id: head_id,
+            },
+        )?;
+        assert_eq!(wt.head_name()?.expect("not detached").as_bstr(), "refs/heads/new");
+        assert_eq!(wt.head_id()?, head_id);
+        assert_eq!(repo.find_reference("new")?.id(), head_id);
+        for file in ["a", "b", "dir/c"] {
+            assert!(path.join(file).is_file(), "{file} was checked out");
+        }
+        let worktree = wt.worktree().expect("non-bare");
+        assert!(!worktree.is_dirty()?);
+        assert!(!worktree.is_locked(), "the worktree is unlocked once it's created");
+        assert_eq!(worktree.id().map(ToOwned::to_owned), Some("new".into()));
+        assert_eq!(git_status(&path)?, "", "git agrees that the worktree is clean");
+
+        std::fs::create_dir(tmp.path().join("other"))?;
+        let path = tmp.path().join("other").join("wt-a");
+        let parent_id = repo.head_commit()?.parent_ids().next().expect("one parent").detach();
+        let wt = add(&repo, &path, Target::Detached(parent_id))?;
+        assert!(wt.head()?.is_detached());
+        assert_eq!(wt.head_id()?, parent_id);
+        assert_eq!(
+            wt.worktree().expect("non-bare").id().map(ToOwned::to_owned),
+            Some("wt-a2".into()),
+            "ids are made unique"
+        );
+        assert_eq!(git_status(&path)?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn add_fails_for_branches_that_are_checked_out_or_exist() -> crate::Result {
+        let (repo, tmp) = repo_rw()?;
+        let path = tmp.path().join("new");
+        for branch in ["refs/heads/main", "refs/heads/wt-a"] {
+            let err = add(&repo, &path, Target::Branch(branch.try_into()?)).unwrap_err();
+            assert!(matches!(err, Error::BranchCheckedOut { .. }), "{err:?}");
+        }
+        let err = add(
+            &repo,
+            &path,
+            Target::NewBranch {
+                name: "refs/heads/main".try_into()?,
+                id: repo.head_id()?.detach(),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::BranchExists { .. }), "{err:?}");
+        let err = add(
+            &repo,
+            &tmp.path().join("wt-a"),
+            Target::Detached(repo.head_id()?.detach()),
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::PathExists { .. }), "{err:?}");
+        assert!(!path.exists(), "nothing was created");
+
+        let err = add(&repo, &path, Target::Branch("refs/heads/wt-deleted".try_into()?)).unwrap_err();
+        assert!(
+            matches!(err, Error::BranchCheckedOut { .. }),
+            "branches of prunable worktrees are still considered checked out: {err:?}"
+        );
+        repo.prune_worktrees(false)?;
+        let wt = add(&repo, &path, Target::Branch("refs/heads/wt-deleted".try_into()?))?;
+        assert_eq!(
+            wt.head_name()?.expect("not detached").as_bstr(),
+            "refs/heads/wt-deleted"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn lock_and_unlock() -> crate::Result {
+        let (repo, tmp) = repo_rw()?;
+        let wt = proxy(&repo, "wt-b");
+        wt.lock(Some("on a removable device".into()))?;
+        assert!(wt.is_locked());
+        assert_eq!(wt.lock_reason().expect("locked"), "on a removable device");
+        assert!(matches!(
+            wt.lock(None),
+            Err(gix::worktree::proxy::lock::Error::AlreadyLocked { .. })
+        ));
+        assert!(matches!(
+            wt.move_to(tmp.path().join("moved")),
+            Err(gix::worktree::proxy::move_to::Error::Locked { .. })
+        ));
+        assert!(matches!(
+            wt.clone().remove(true),
+            Err(gix::worktree::proxy::remove::Error::Locked { .. })
+        ));
+        assert_eq!(wt.prunable(), None);
+
+        assert!(wt.unlock()?);
+        assert!(!wt.is_locked());
+        assert!(!wt.unlock()?, "unlocking is idempotent");
+
+        wt.lock(None)?;
+        assert_eq!(wt.lock_reason().expect("locked"), "", "locks don't need a reason");
+        Ok(())
+    }
+
+    #[test]
+    fn move_and_repair() -> crate::Result {
+        let (repo, tmp) = repo_rw()?;
+        let wt = proxy(&repo, "wt-b");
+        let destination = wt.move_to(tmp.path().join("prev"))?;
+        assert_eq!(
+            destination,
+            tmp.path().join("prev").join("wt-b"),
+            "moved into directories"
+        );
+        assert_eq!(wt.base()?, gix::path::realpath(&destination)?.join(""));
+        assert!(wt.clone().into_repo()?.head()?.is_detached());
+        assert_eq!(git_status(&destination)?, "");
+
+        let err = wt.move_to(tmp.path().join("wt-a").join("a")).unwrap_err();
+        assert!(
+            matches!(err, gix::worktree::proxy::move_to::Error::DestinationExists { .. }),
+            "{err:?}"
+        );
+
+        let moved = tmp.path().join("moved-by-hand");
+        std::fs::rename(&destination, &moved)?;
+        assert_eq!(wt.prunable(), Some(Prunable::MissingWorktree));
+        assert!(repo.repair_worktree(&moved)?);
+        assert!(!repo.repair_worktree(&moved)?, "nothing to do anymore");
+        assert_eq!(wt.prunable(), None);
+        assert_eq!(git_status(&moved)?, "");
+
+        std::fs::write(moved.join(".git"), "gitdir: /invalid")?;
+        assert!(wt.repair()?);
+        assert!(!wt.repair()?);
+        assert_eq!(git_status(&moved)?, "");
+
+        assert!(matches!(
+            repo.repair_worktree(repo.work_dir().expect("non-bare")),
+            Err(gix::worktree::proxy::repair::Error::NotALinkedWorktree { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn remove() -> crate::Result {
+        let (repo, tmp) = repo_rw()?;
+        let base = tmp.path().join("wt-b");
+        std::fs::write(base.join("untracked"), "")?;
+        assert!(matches!(
+            proxy(&repo, "wt-b").remove(false),
+            Err(gix::worktree::proxy::remove::Error::Dirty { .. })
+        ));
+        std::fs::remove_file(base.join("untracked"))?;
+        std::fs::write(base.join("a"), "changed")?;
+        assert!(matches!(
+            proxy(&repo, "wt-b").remove(false),
+            Err(gix::worktree::proxy::remove::Error::Dirty { .. })
+        ));
+
+        assert!(
+            matches!(
+                proxy(&repo, "wt-a").remove(false),
+                Err(gix::worktree::proxy::remove::Error::Dirty { .. })
+            ),
+            "nested worktrees count as untracked files, like in git"
+        );
+
+        let wt = proxy(&repo, "wt-a1");
+        let git_dir = wt.git_dir().to_owned();
+        wt.remove(false)?;
+        assert!(!git_dir.exists());
+        assert!(!tmp.path().join("prev").join("wt-a").exists());
+
+        proxy(&repo, "wt-b").remove(true)?;
+        assert!(!base.exists());
+        assert!(repo.worktrees()?.iter().all(|wt| wt.id() != "wt-b"));
+        Ok(())
+    }
+
+    #[test]
+    fn prune() -> crate::Result {
+        let (repo, tmp) = repo_rw()?;
+        std::fs::remove_dir_all(tmp.path().join("wt-c-locked"))?;
+        let worktrees = repo.common_dir().join("worktrees");
+        std::fs::create_dir(worktrees.join("no-gitdir"))?;
+        std::fs::write(worktrees.join("not-a-dir"), "")?;
+
+        let expected = vec![
+            ("no-gitdir".into(), Prunable::MissingGitDirFile),
+            ("not-a-dir".into(), Prunable::NotADirectory),
+            ("wt-deleted".into(), Prunable::MissingWorktree),
+        ];
+        assert_eq!(repo.prune_worktrees(true)?, expected);
+        assert!(
+            worktrees.join("wt-deleted").is_dir(),
+            "nothing is changed in dry-run mode"
+        );
+        assert_eq!(repo.prune_worktrees(false)?, expected);
+        assert_eq!(repo.prune_worktrees(false)?, vec![], "everything was pruned");
+        assert!(!worktrees.join("wt-deleted").exists());
+        assert!(
+            worktrees.join("wt-c-locked").is_dir(),
+            "locked worktrees aren't pruned even though they are missing"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use crate::util::hex_to_id;
+
+fn walk(repo: &gix::Repository, spec: &str) -> crate::Result<Vec<gix::ObjectId>> {
+    Ok(repo
+        .rev_parse(spec)?
+        .into_walk()?
+        .sorting(gix::traverse::commit::Sorting::ByCommitTimeNewestFirst)
+        .all()?
+        .map(|info| info.map(|info| info.id))
+        .collect::<Result<_, _>>()?)
+}
+
+#[test]
+fn ranges_hide_commits_like_rev_list() -> crate::Result {
+    let repo = crate::named_repo("make_repo_with_fork_and_dates.sh")?;
+    let m1b1 = hex_to_id("288e509293165cb5630d08f4185bdf2445bf6170");
+    let b1c1 = hex_to_id("bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac");
+    let c2 = hex_to_id("9902e3c3e8f0c569b4ab295ddf473e6de763e1e7");
+    let c1 = hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03");
+
+    assert_eq!(walk(&repo, "main")?, [m1b1, b1c1, c2, c1]);
+    assert!(walk(&repo, "^main")?.is_empty(), "nothing to include");
+    assert_eq!(walk(&repo, "branch1..main")?, [m1b1, c2]);
+    assert!(walk(&repo, "main..branch1")?.is_empty(), "branch1 was merged into main");
+    assert_eq!(
+        walk(&repo, "main...branch1")?,
+        [m1b1, c2],
+        "the merge-base is branch1 itself"
+    );
+    assert_eq!(
+        walk(&repo, "main~1...branch1")?,
+        [b1c1, c2],
+        "all but the merge-base c1 and its ancestors"
+    );
+    assert_eq!(walk(&repo, "main^!")?, [m1b1], "only the commit itself");
+    assert_eq!(walk(&repo, "main^@")?, [b1c1, c2, c1], "all but the commit itself");
+
+    assert_eq!(
+        repo.rev_walk([m1b1])
+            .hide([c2, b1c1])
+            .all()?
+            .map(|info| info.map(|info| info.id))
+            .collect::<Result<Vec<_>, _>>()?,
+        [m1b1],
+        "multiple commits can be hidden"
+    );
+    Ok(())
+}
@@ -141,3 +141,34 @@ mod open {
         Ok(())
     }
 }
+
+#[cfg(feature = "worktree-mutation")]
+mod absorb_git_dir {
+    #[test]
+    fn moves_the_repository_into_the_superproject() -> crate::Result {
+        let tmp = gix_testtools::scripted_fixture_writable("make_submodules.sh")?;
+        let repo = gix::open_opts(tmp.path().join("old-form"), gix::open::Options::isolated())?;
+        let sm = repo
+            .submodules()?
+            .expect("modules present")
+            .next()
+            .expect("one submodule");
+        let head_id = sm.open()?.expect("present").head_id()?.detach();
+        assert!(sm.state()?.is_old_form);
+
+        assert!(sm.absorb_git_dir()?, "the repository was moved");
+        assert!(sm.work_dir()?.join(".git").is_file());
+        assert!(sm.git_dir().is_dir());
+        assert!(!sm.state()?.is_old_form);
+        let sm_repo = sm.open()?.expect("present");
+        assert_eq!(sm_repo.head_id()?, head_id);
+        assert_eq!(
+            sm_repo.work_dir().map(gix::path::realpath).transpose()?,
+            Some(gix::path::realpath(sm.work_dir()?)?),
+            "the worktree is still associated with the repository"
+        );
+
+        assert!(!sm.absorb_git_dir()?, "absorbed submodules are left alone");
+        Ok(())
+    }
+}
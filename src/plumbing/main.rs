@@ -16,8 +16,8 @@ use gix::bstr::{io::BufReadExt, BString};
 
 use crate::plumbing::{
     options::{
-        attributes, commit, commitgraph, config, credential, exclude, free, fsck, index, mailmap, odb, revision, tree,
-        Args, Subcommands,
+        apply, attributes, bisect, commit, commitgraph, config, credential, exclude, free, fsck, index, mailmap, odb,
+        range_diff, revision, tree, Args, Subcommands,
     },
     show_progress,
 };
@@ -193,6 +193,64 @@ pub fn main() -> Result<()> {
                 None,
                 move |_progress, out, _err| core::repository::submodule::list(repository(Mode::Lenient)?, out, format),
             ),
+            #[cfg(feature = "gitoxide-core-blocking-client")]
+            crate::plumbing::options::submodule::Subcommands::Add {
+                name,
+                branch,
+                url,
+                path,
+            } => prepare_and_run(
+                "submodule-add",
+                trace,
+                auto_verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |progress, out, _err| {
+                    core::repository::submodule::add(
+                        repository(Mode::Lenient)?,
+                        url,
+                        path,
+                        gix::submodule::add::Options { name, branch },
+                        progress,
+                        out,
+                        format,
+                    )
+                },
+            ),
+            crate::plumbing::options::submodule::Subcommands::Sync { paths } => prepare_and_run(
+                "submodule-sync",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::submodule::sync(repository(Mode::Lenient)?, paths, out, format)
+                },
+            ),
+            crate::plumbing::options::submodule::Subcommands::Deinit { force, paths } => prepare_and_run(
+                "submodule-deinit",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::submodule::deinit(repository(Mode::Lenient)?, paths, force, out, format)
+                },
+            ),
+            crate::plumbing::options::submodule::Subcommands::AbsorbGitDirs { paths } => prepare_and_run(
+                "submodule-absorb-git-dirs",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::submodule::absorb_git_dirs(repository(Mode::Lenient)?, paths, out, format)
+                },
+            ),
         },
         #[cfg(feature = "gitoxide-core-tools-archive")]
         Subcommands::Archive(crate::plumbing::options::archive::Platform {
@@ -444,23 +502,83 @@ pub fn main() -> Result<()> {
                 }
             }
         }
-        Subcommands::Config(config::Platform { filter }) => prepare_and_run(
-            "config-list",
-            trace,
-            verbose,
-            progress,
-            progress_keep_open,
-            None,
-            move |_progress, out, _err| {
-                core::repository::config::list(
-                    repository(Mode::LenientWithGitInstallConfig)?,
-                    filter,
-                    config,
-                    format,
-                    out,
-                )
-            },
-        )
+        Subcommands::Config(config::Platform { filter, cmd }) => match cmd {
+            None => prepare_and_run(
+                "config-list",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::config::list(
+                        repository(Mode::LenientWithGitInstallConfig)?,
+                        filter,
+                        config,
+                        format,
+                        out,
+                    )
+                },
+            ),
+            Some(cmd) => {
+                let file = |file: config::File| {
+                    use core::repository::config::File;
+                    if file.worktree {
+                        File::Worktree
+                    } else if file.global {
+                        File::Global
+                    } else if file.system {
+                        File::System
+                    } else {
+                        File::Local
+                    }
+                };
+                match cmd {
+                    config::Subcommands::Set { file: f, key, value } => prepare_and_run(
+                        "config-set",
+                        trace,
+                        verbose,
+                        progress,
+                        progress_keep_open,
+                        None,
+                        move |_progress, _out, _err| {
+                            core::repository::config::set(repository(Mode::Lenient)?, file(f), key, value)
+                        },
+                    ),
+                    config::Subcommands::Unset { file: f, key } => prepare_and_run(
+                        "config-unset",
+                        trace,
+                        verbose,
+                        progress,
+                        progress_keep_open,
+                        None,
+                        move |_progress, _out, _err| {
+                            core::repository::config::unset(repository(Mode::Lenient)?, file(f), key)
+                        },
+                    ),
+                    config::Subcommands::RenameSection {
+                        file: f,
+                        old_name,
+                        new_name,
+                    } => prepare_and_run(
+                        "config-rename-section",
+                        trace,
+                        verbose,
+                        progress,
+                        progress_keep_open,
+                        None,
+                        move |_progress, _out, _err| {
+                            core::repository::config::rename_section(
+                                repository(Mode::Lenient)?,
+                                file(f),
+                                old_name,
+                                new_name,
+                            )
+                        },
+                    ),
+                }
+            }
+        }
         .map(|_| ()),
         Subcommands::Free(subcommands) => match subcommands {
             free::Subcommands::Discover => prepare_and_run(
@@ -994,6 +1112,11 @@ pub fn main() -> Result<()> {
                 long,
                 statistics,
                 max_candidates,
+                match_patterns,
+                exclude,
+                dirty,
+                broken,
+                contains,
                 rev_spec,
             } => prepare_and_run(
                 "commit-describe",
@@ -1016,6 +1139,38 @@ pub fn main() -> Result<()> {
                             statistics,
                             max_candidates,
                             always,
+                            match_patterns,
+                            exclude_patterns: exclude,
+                            dirty_suffix: dirty,
+                            broken_suffix: broken,
+                            contains,
+                        },
+                    )
+                },
+            ),
+            commit::Subcommands::NameRev {
+                tags,
+                ref_patterns,
+                exclude,
+                name_only,
+                revs,
+            } => prepare_and_run(
+                "commit-name-rev",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::commit::name_rev(
+                        repository(Mode::Lenient)?,
+                        &revs,
+                        out,
+                        core::repository::commit::name_rev::Options {
+                            tags_only: tags,
+                            ref_patterns,
+                            exclude_patterns: exclude,
+                            name_only,
                         },
                     )
                 },
@@ -1100,6 +1255,139 @@ pub fn main() -> Result<()> {
                 move |_progress, out, err| core::repository::odb::info(repository(Mode::Strict)?, format, out, err),
             ),
         },
+        Subcommands::Apply(apply::Platform {
+            cached,
+            index,
+            three_way,
+            reject,
+            context,
+            patch,
+        }) => prepare_and_run(
+            "apply",
+            trace,
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, _out, err| {
+                use gix::apply::Location;
+                core::repository::apply(
+                    repository(Mode::Lenient)?,
+                    &patch,
+                    err,
+                    core::repository::apply::Options {
+                        location: if cached {
+                            Location::Index
+                        } else if index {
+                            Location::WorktreeAndIndex
+                        } else {
+                            Location::Worktree
+                        },
+                        three_way,
+                        reject,
+                        min_context: context,
+                    },
+                )
+            },
+        ),
+        Subcommands::RangeDiff(range_diff::Platform {
+            creation_factor,
+            no_patch,
+            specs,
+        }) => prepare_and_run(
+            "range-diff",
+            trace,
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, out, _err| {
+                core::repository::range_diff(
+                    repository(Mode::Lenient)?,
+                    &specs,
+                    out,
+                    core::repository::range_diff::Options {
+                        creation_factor,
+                        no_patch,
+                    },
+                )
+            },
+        ),
+        Subcommands::Bisect(cmd) => match cmd {
+            bisect::Subcommands::Start {
+                no_checkout,
+                term_new,
+                term_old,
+                bad,
+                good,
+            } => prepare_and_run(
+                "bisect-start",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    let defaults = gix::bisect::Terms::default();
+                    core::repository::bisect::start(
+                        repository(Mode::Lenient)?,
+                        bad,
+                        good,
+                        gix::bisect::Terms {
+                            bad: term_new.map_or(defaults.bad, Into::into),
+                            good: term_old.map_or(defaults.good, Into::into),
+                        },
+                        no_checkout,
+                        out,
+                    )
+                },
+            ),
+            bisect::Subcommands::Mark { term, revs } => prepare_and_run(
+                "bisect-mark",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| core::repository::bisect::mark(repository(Mode::Lenient)?, term, revs, out),
+            ),
+            bisect::Subcommands::Next => prepare_and_run(
+                "bisect-next",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| core::repository::bisect::next(repository(Mode::Lenient)?, out),
+            ),
+            bisect::Subcommands::Run { command } => prepare_and_run(
+                "bisect-run",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| core::repository::bisect::run(repository(Mode::Lenient)?, command, out),
+            ),
+            bisect::Subcommands::Log => prepare_and_run(
+                "bisect-log",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| core::repository::bisect::log(repository(Mode::Lenient)?, out),
+            ),
+            bisect::Subcommands::Reset => prepare_and_run(
+                "bisect-reset",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, _out, _err| core::repository::bisect::reset(repository(Mode::Lenient)?),
+            ),
+        },
         Subcommands::Fsck(fsck::Platform { spec }) => prepare_and_run(
             "fsck",
             trace,
@@ -78,9 +78,16 @@ pub struct Args {
 
 #[derive(Debug, clap::Subcommand)]
 pub enum Subcommands {
+    /// Apply a patch to files in the worktree, the index, or both.
+    Apply(apply::Platform),
     /// Subcommands for creating worktree archives
     #[cfg(feature = "gitoxide-core-tools-archive")]
     Archive(archive::Platform),
+    /// Find the commit that introduced a change by binary search, like `git bisect`.
+    #[clap(subcommand)]
+    Bisect(bisect::Subcommands),
+    /// Compare two series of commits, like two versions of a branch, similar to `git range-diff`.
+    RangeDiff(range_diff::Platform),
     /// Subcommands for interacting with commit-graphs
     #[clap(subcommand)]
     CommitGraph(commitgraph::Subcommands),
@@ -285,6 +292,56 @@ pub mod config {
         /// and comparisons are case-insensitive.
         #[clap(value_parser = gitoxide::shared::AsBString)]
         pub filter: Vec<BString>,
+        #[clap(subcommand)]
+        pub cmd: Option<Subcommands>,
+    }
+
+    #[derive(Debug, clap::Subcommand)]
+    pub enum Subcommands {
+        /// Set the value at `key` like `core.bare` or `remote.origin.url`, replacing the last existing value.
+        Set {
+            #[clap(flatten)]
+            file: File,
+            /// The key of the value to set, with the section and value name separated by `.`.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            key: BString,
+            /// The value to set.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            value: BString,
+        },
+        /// Remove the value at `key`, which must exist exactly once.
+        Unset {
+            #[clap(flatten)]
+            file: File,
+            /// The key of the value to remove, with the section and value name separated by `.`.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            key: BString,
+        },
+        /// Rename all sections like `remote.origin` to the given new name, like `remote.upstream`.
+        RenameSection {
+            #[clap(flatten)]
+            file: File,
+            /// The name of the section to rename, with an optional subsection separated by `.`.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            old_name: BString,
+            /// The new name of the section, with an optional subsection separated by `.`.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            new_name: BString,
+        },
+    }
+
+    /// Select the configuration file to change, which is the one of the repository by default.
+    #[derive(Debug, clap::Args)]
+    pub struct File {
+        /// Change the configuration file of the current worktree if `extensions.worktreeConfig` is enabled.
+        #[clap(long, conflicts_with_all = ["global", "system"])]
+        pub worktree: bool,
+        /// Change the configuration file of the current user.
+        #[clap(long, conflicts_with = "system")]
+        pub global: bool,
+        /// Change the system-wide configuration file.
+        #[clap(long)]
+        pub system: bool,
     }
 }
 
@@ -491,6 +548,88 @@ pub mod odb {
     }
 }
 
+pub mod apply {
+    use std::path::PathBuf;
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// Apply the patch to the index only, leaving the worktree untouched.
+        #[clap(long, conflicts_with = "index")]
+        pub cached: bool,
+        /// Apply the patch to both, the worktree and the index.
+        #[clap(long)]
+        pub index: bool,
+        /// Fall back to a three-way merge if the patch doesn't apply cleanly, recording conflicts in the index.
+        #[clap(long = "3way", short = '3')]
+        pub three_way: bool,
+        /// Apply the hunks that apply and report the ones that don't, instead of failing without changing anything.
+        #[clap(long, conflicts_with = "three_way")]
+        pub reject: bool,
+        /// Require at least this many lines of context to match around each hunk.
+        #[clap(short = 'C', value_name = "LINES")]
+        pub context: Option<u32>,
+        /// The patch to apply, or `-` to read it from stdin.
+        pub patch: PathBuf,
+    }
+}
+
+pub mod bisect {
+    #[derive(Debug, clap::Subcommand)]
+    pub enum Subcommands {
+        /// Start bisecting, optionally with the bad commit and any amount of good commits.
+        Start {
+            /// Don't check out the commits to test, but point `BISECT_HEAD` to them instead.
+            #[clap(long)]
+            no_checkout: bool,
+            /// The term to use for commits that have the change, instead of `bad`.
+            #[clap(long, alias = "term-bad", value_name = "TERM")]
+            term_new: Option<String>,
+            /// The term to use for commits that don't have the change yet, instead of `good`.
+            #[clap(long, alias = "term-good", value_name = "TERM")]
+            term_old: Option<String>,
+            /// The commit that has the change.
+            bad: Option<String>,
+            /// The commits that don't have the change yet.
+            good: Vec<String>,
+        },
+        /// Mark the given revisions, or the commit currently being tested, with `term`, which is one of the terms or `skip`.
+        Mark {
+            /// The term to mark the commits with, like `bad`, `good` or `skip`.
+            term: String,
+            /// The revisions to mark, or the commit being tested if none are given.
+            revs: Vec<String>,
+        },
+        /// Check out the next commit to test.
+        Next,
+        /// Run a shell command on each commit to test, and mark it based on its exit code.
+        ///
+        /// Exit code 0 means good, 125 means skip, 1 to 127 mean bad, and all others abort the bisection.
+        Run {
+            /// The command to run, which will be passed to the shell.
+            command: String,
+        },
+        /// Print the bisect log.
+        Log,
+        /// Stop bisecting and check out the commit or branch that was checked out when bisecting was started.
+        Reset,
+    }
+}
+
+pub mod range_diff {
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// The percentage of the size of a commit's diff that may differ from another one for both to still be paired.
+        #[clap(long, default_value_t = gix::range_diff::DEFAULT_CREATION_FACTOR)]
+        pub creation_factor: u32,
+        /// Only list how the commits were paired, without showing how paired commits differ.
+        #[clap(long, short = 's')]
+        pub no_patch: bool,
+        /// Either `<old-tip>...<new-tip>`, `<old-range> <new-range>`, or `<base> <old-tip> <new-tip>`.
+        #[clap(required = true, num_args = 1..=3)]
+        pub specs: Vec<String>,
+    }
+}
+
 pub mod fsck {
     #[derive(Debug, clap::Parser)]
     pub struct Platform {
@@ -564,9 +703,65 @@ pub mod commit {
             /// If there was no way to describe the commit, fallback to using the abbreviated input revision.
             always: bool,
 
+            /// Only use names that match the given glob pattern, without their `refs/tags/` prefix. May be given multiple times.
+            #[clap(long = "match", value_name = "PATTERN")]
+            match_patterns: Vec<String>,
+
+            /// Don't use names that match the given glob pattern, without their `refs/tags/` prefix. May be given multiple times.
+            #[clap(long, value_name = "PATTERN")]
+            exclude: Vec<String>,
+
+            /// Append `-<SUFFIX>` if tracked files in the worktree or the index differ from `HEAD`.
+            #[clap(
+                long,
+                value_name = "SUFFIX",
+                num_args = 0..=1,
+                require_equals = true,
+                default_missing_value = "dirty",
+                conflicts_with = "rev_spec"
+            )]
+            dirty: Option<String>,
+
+            /// Like `--dirty`, but append `-<SUFFIX>` instead of failing if the state of the worktree can't be determined.
+            #[clap(
+                long,
+                value_name = "SUFFIX",
+                num_args = 0..=1,
+                require_equals = true,
+                default_missing_value = "broken",
+                conflicts_with = "rev_spec"
+            )]
+            broken: Option<String>,
+
+            /// Name the commit relative to the oldest tag that contains it instead, like `v1.0~3^2`.
+            #[clap(long, conflicts_with_all = ["dirty", "broken", "long", "first_parent"])]
+            contains: bool,
+
             /// A specification of the revision to use, or the current `HEAD` if unset.
             rev_spec: Option<String>,
         },
+        /// Name commits relative to the closest reference they can be reached from, like `main~3^2`.
+        NameRev {
+            /// Only use tags to name commits.
+            #[clap(long)]
+            tags: bool,
+
+            /// Only use references that match the given glob pattern. May be given multiple times.
+            #[clap(long = "refs", value_name = "PATTERN")]
+            ref_patterns: Vec<String>,
+
+            /// Don't use references that match the given glob pattern. May be given multiple times.
+            #[clap(long, value_name = "PATTERN")]
+            exclude: Vec<String>,
+
+            /// Print only the names, without the revisions they belong to.
+            #[clap(long)]
+            name_only: bool,
+
+            /// The revisions to name.
+            #[clap(required = true)]
+            revs: Vec<String>,
+        },
     }
 }
 
@@ -597,7 +792,7 @@ pub mod commitgraph {
         },
         /// List all entries in the commit-graph as reachable by starting from `HEAD`.
         List {
-            /// The rev-spec to list reachable commits from.
+            /// The rev-spec to list reachable commits from, which may be a range like `a..b` or `a...b`.
             #[clap(default_value = "@")]
             spec: std::ffi::OsString,
         },
@@ -631,7 +826,7 @@ pub mod revision {
     #[derive(Debug, clap::Subcommand)]
     #[clap(visible_alias = "rev", visible_alias = "r")]
     pub enum Subcommands {
-        /// List all commits reachable from the given rev-spec.
+        /// List all commits reachable from the given rev-spec, or in the given range.
         #[clap(visible_alias = "l")]
         List {
             /// How many commits to list at most.
@@ -640,7 +835,7 @@ pub mod revision {
             /// Write the graph as SVG file to the given path.
             #[clap(long, short = 's')]
             svg: Option<std::path::PathBuf>,
-            /// The rev-spec to list reachable commits from.
+            /// The rev-spec to list reachable commits from, which may be a range like `a..b` or `a...b`.
             #[clap(default_value = "@")]
             spec: std::ffi::OsString,
         },
@@ -798,6 +993,7 @@ pub mod index {
 }
 
 pub mod submodule {
+    use gix::bstr::BString;
 
     #[derive(Debug, clap::Parser)]
     pub struct Platform {
@@ -809,6 +1005,44 @@ pub mod submodule {
     pub enum Subcommands {
         /// Print all direct submodules to standard output
         List,
+        /// Clone a repository into a new submodule and add it to the index.
+        #[cfg(feature = "gitoxide-core-blocking-client")]
+        Add {
+            /// The name of the submodule, which defaults to its path.
+            #[clap(long, value_parser = gitoxide::shared::AsBString)]
+            name: Option<BString>,
+            /// The branch to check out and to track, instead of the one the remote `HEAD` points to.
+            #[clap(long, short = 'b', value_parser = gitoxide::shared::AsBString)]
+            branch: Option<BString>,
+            /// The url of the repository to clone, which may be relative to the url of the default remote.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            url: BString,
+            /// The path to the submodule relative to the root of the worktree.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            path: BString,
+        },
+        /// Write the urls of submodules in `.gitmodules` into the configuration of the superproject and of the submodules.
+        Sync {
+            /// The paths of the submodules to synchronize, or all submodules if empty.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            paths: Vec<BString>,
+        },
+        /// Remove the worktree of submodules along with their configuration in the superproject, but keep their repositories.
+        Deinit {
+            /// Remove the worktree even if it contains local modifications.
+            #[clap(long, short = 'f')]
+            force: bool,
+            /// The paths of the submodules to deinitialize, or all submodules if empty.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            paths: Vec<BString>,
+        },
+        /// Move repositories located in the worktree of submodules into the superproject, recursively.
+        #[clap(visible_alias = "absorbgitdirs")]
+        AbsorbGitDirs {
+            /// The paths of the submodules whose repositories to move, or all submodules if empty.
+            #[clap(value_parser = gitoxide::shared::AsBString)]
+            paths: Vec<BString>,
+        },
     }
 }
 